@@ -0,0 +1,180 @@
+use std::cmp::Ordering;
+
+use poem_openapi::{Enum, Object};
+
+use crate::types::Timestamp;
+
+const HOUR: i64 = 60 * 60;
+const DAY: i64 = 24 * HOUR;
+const WEEK: i64 = 7 * DAY;
+
+/// The unix epoch was a thursday, weeks are offset by this to start on monday.
+const WEEK_OFFSET: i64 = 3 * DAY;
+
+#[derive(
+    Enum,
+    Copy,
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    strum::AsRefStr,
+    strum::EnumString,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[oai(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum BucketSize {
+    Hour,
+    Day,
+    /// ISO weeks, starting on monday 00:00 UTC.
+    Week,
+}
+
+cql_text_enum!(BucketSize);
+
+impl BucketSize {
+    #[inline]
+    pub fn seconds(&self) -> i64 {
+        match self {
+            Self::Hour => HOUR,
+            Self::Day => DAY,
+            Self::Week => WEEK,
+        }
+    }
+
+    /// The start of the bucket containing the unix timestamp `secs`.
+    fn floor(&self, secs: i64) -> i64 {
+        match self {
+            Self::Hour | Self::Day => secs - secs.rem_euclid(self.seconds()),
+            Self::Week => secs - (secs + WEEK_OFFSET).rem_euclid(WEEK),
+        }
+    }
+}
+
+/// A fixed period of time which analytics are counted in.
+///
+/// Every service must derive buckets from here so the cron writing rollups
+/// and the API reading them agree on where buckets start.
+#[derive(Object, Copy, Clone, Debug, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub struct TimeBucket {
+    pub size: BucketSize,
+    pub start: Timestamp,
+}
+
+impl TimeBucket {
+    /// The bucket of the given size containing `ts`.
+    pub fn of(size: BucketSize, ts: &Timestamp) -> Self {
+        Self {
+            size,
+            start: Timestamp::from(size.floor(ts.timestamp())),
+        }
+    }
+
+    /// The first instant after the bucket.
+    pub fn end(&self) -> Timestamp {
+        Timestamp::from(self.start.timestamp() + self.size.seconds())
+    }
+
+    pub fn contains(&self, ts: &Timestamp) -> bool {
+        let secs = ts.timestamp();
+        secs >= self.start.timestamp() && secs < self.end().timestamp()
+    }
+
+    pub fn next(&self) -> Self {
+        Self {
+            size: self.size,
+            start: self.end(),
+        }
+    }
+
+    /// A stable key for storing the bucket, e.g. `2024-01-31T13`,
+    /// `2024-01-31` or `2024-W05`.
+    ///
+    /// Week keys use the ISO week year which differs from the calendar year
+    /// around new year.
+    pub fn key(&self) -> String {
+        let format = match self.size {
+            BucketSize::Hour => "%Y-%m-%dT%H",
+            BucketSize::Day => "%Y-%m-%d",
+            BucketSize::Week => "%G-W%V",
+        };
+
+        self.start.format(format).to_string()
+    }
+
+    /// Every bucket of the given size overlapping `from..to`.
+    pub fn range(
+        size: BucketSize,
+        from: &Timestamp,
+        to: &Timestamp,
+    ) -> impl Iterator<Item = TimeBucket> {
+        let end = to.timestamp();
+        std::iter::successors(Some(Self::of(size, from)), |v| Some(v.next()))
+            .take_while(move |v| v.start.timestamp() < end)
+    }
+}
+
+impl PartialOrd for TimeBucket {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for TimeBucket {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.start
+            .timestamp()
+            .cmp(&other.start.timestamp())
+            .then(self.size.cmp(&other.size))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn ts(v: &str) -> Timestamp {
+        Timestamp::from_str(v).unwrap()
+    }
+
+    #[test]
+    fn test_week_starts_on_monday() {
+        let sunday = TimeBucket::of(BucketSize::Week, &ts("2024-02-04T23:59:59Z"));
+        let monday = TimeBucket::of(BucketSize::Week, &ts("2024-02-05T00:00:00Z"));
+
+        assert_eq!(sunday.start, ts("2024-01-29T00:00:00Z"));
+        assert_eq!(monday.start, ts("2024-02-05T00:00:00Z"));
+        assert_eq!(sunday.next(), monday);
+        assert!(sunday.contains(&ts("2024-01-29T00:00:00Z")));
+        assert!(!sunday.contains(&ts("2024-02-05T00:00:00Z")));
+    }
+
+    #[test]
+    fn test_keys() {
+        let at = ts("2024-12-31T13:45:00Z");
+
+        assert_eq!(TimeBucket::of(BucketSize::Hour, &at).key(), "2024-12-31T13");
+        assert_eq!(TimeBucket::of(BucketSize::Day, &at).key(), "2024-12-31");
+        assert_eq!(TimeBucket::of(BucketSize::Week, &at).key(), "2025-W01");
+    }
+
+    #[test]
+    fn test_range() {
+        let buckets = TimeBucket::range(
+            BucketSize::Day,
+            &ts("2024-01-01T12:00:00Z"),
+            &ts("2024-01-03T00:00:00Z"),
+        )
+        .map(|v| v.key())
+        .collect::<Vec<_>>();
+
+        assert_eq!(buckets, ["2024-01-01", "2024-01-02"]);
+    }
+}
@@ -0,0 +1,41 @@
+use std::collections::BTreeMap;
+
+use crate::types::{JsSafeBigInt, Timestamp};
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct AnalyticsEvent {
+    /// The kind of event, e.g. `view`, `vote` or `invite`.
+    pub kind: String,
+    /// The bot or pack the event relates to.
+    pub entity_id: JsSafeBigInt,
+    pub timestamp: Timestamp,
+    /// The user which triggered the event if they were logged in.
+    pub user_id: Option<JsSafeBigInt>,
+    pub session_id: Option<String>,
+    pub properties: BTreeMap<String, String>,
+}
+
+impl AnalyticsEvent {
+    /// Strips everything from the event which could identify the user,
+    /// leaving only what is needed for aggregate counts.
+    pub fn into_aggregate(self) -> Self {
+        Self {
+            kind: self.kind,
+            entity_id: self.entity_id,
+            timestamp: self.timestamp,
+            user_id: None,
+            session_id: None,
+            properties: BTreeMap::new(),
+        }
+    }
+
+    #[inline]
+    pub fn is_anonymous(&self) -> bool {
+        self.user_id.is_none() && self.session_id.is_none() && self.properties.is_empty()
+    }
+}
+
+/// Somewhere analytics events can be buffered before being written.
+pub trait EventSink {
+    fn push(&self, event: AnalyticsEvent);
+}
@@ -0,0 +1,180 @@
+use std::collections::HashMap;
+
+use crate::analytics::{AnalyticsEvent, EventSink};
+use crate::types::JsSafeBigInt;
+
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    strum::AsRefStr,
+    strum::EnumString,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ConsentLevel {
+    /// Events are recorded as is.
+    Full,
+    /// Events are only counted, any identifying fields are removed.
+    AggregateOnly,
+    /// Events are dropped entirely.
+    None,
+}
+
+cql_text_enum!(ConsentLevel);
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ConsentPreferences {
+    pub user_id: JsSafeBigInt,
+    pub analytics: ConsentLevel,
+}
+
+/// Looks up the analytics consent a user has given.
+pub trait ConsentSource {
+    fn consent_for(&self, user_id: JsSafeBigInt) -> Option<ConsentLevel>;
+}
+
+impl ConsentSource for HashMap<JsSafeBigInt, ConsentLevel> {
+    fn consent_for(&self, user_id: JsSafeBigInt) -> Option<ConsentLevel> {
+        self.get(&user_id).copied()
+    }
+}
+
+impl ConsentSource for Vec<ConsentPreferences> {
+    fn consent_for(&self, user_id: JsSafeBigInt) -> Option<ConsentLevel> {
+        self.iter()
+            .find(|v| v.user_id == user_id)
+            .map(|v| v.analytics)
+    }
+}
+
+/// Applies the user's consent to events before they reach the wrapped sink.
+///
+/// Events without a user are anonymous sessions and are always reduced to their
+/// aggregate form unless `default_level` is `None`.
+pub struct ConsentFilter<S, C> {
+    sink: S,
+    consent: C,
+    default_level: ConsentLevel,
+}
+
+impl<S: EventSink, C: ConsentSource> ConsentFilter<S, C> {
+    pub fn new(sink: S, consent: C, default_level: ConsentLevel) -> Self {
+        Self {
+            sink,
+            consent,
+            default_level,
+        }
+    }
+
+    #[inline]
+    pub fn inner(&self) -> &S {
+        &self.sink
+    }
+
+    pub fn level_for(&self, event: &AnalyticsEvent) -> ConsentLevel {
+        match event.user_id {
+            Some(user_id) => self
+                .consent
+                .consent_for(user_id)
+                .unwrap_or(self.default_level),
+            None if self.default_level == ConsentLevel::None => ConsentLevel::None,
+            None => ConsentLevel::AggregateOnly,
+        }
+    }
+
+    /// Applies the consent rules to the event, returning `None` if it should be dropped.
+    pub fn apply(&self, event: AnalyticsEvent) -> Option<AnalyticsEvent> {
+        match self.level_for(&event) {
+            ConsentLevel::Full => Some(event),
+            ConsentLevel::AggregateOnly => Some(event.into_aggregate()),
+            ConsentLevel::None => None,
+        }
+    }
+}
+
+impl<S: EventSink, C: ConsentSource> EventSink for ConsentFilter<S, C> {
+    fn push(&self, event: AnalyticsEvent) {
+        if let Some(event) = self.apply(event) {
+            self.sink.push(event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Timestamp;
+    use std::collections::BTreeMap;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct RecordingSink(Mutex<Vec<AnalyticsEvent>>);
+
+    impl EventSink for RecordingSink {
+        fn push(&self, event: AnalyticsEvent) {
+            self.0.lock().unwrap().push(event);
+        }
+    }
+
+    fn event(user_id: Option<i64>) -> AnalyticsEvent {
+        AnalyticsEvent {
+            kind: "view".to_string(),
+            entity_id: JsSafeBigInt(1),
+            timestamp: Timestamp::default(),
+            user_id: user_id.map(JsSafeBigInt),
+            session_id: Some("abc".to_string()),
+            properties: BTreeMap::from_iter([("referrer".to_string(), "search".to_string())]),
+        }
+    }
+
+    fn consent() -> HashMap<JsSafeBigInt, ConsentLevel> {
+        HashMap::from_iter([
+            (JsSafeBigInt(10), ConsentLevel::Full),
+            (JsSafeBigInt(20), ConsentLevel::AggregateOnly),
+            (JsSafeBigInt(30), ConsentLevel::None),
+        ])
+    }
+
+    #[test]
+    fn test_opted_out_identifiers_never_reach_sink() {
+        let filter = ConsentFilter::new(RecordingSink::default(), consent(), ConsentLevel::Full);
+
+        for user_id in [10, 20, 30, 40] {
+            filter.push(event(Some(user_id)));
+        }
+        filter.push(event(None));
+
+        let written = filter.inner().0.lock().unwrap();
+        assert_eq!(written.len(), 4);
+
+        let identified: Vec<i64> = written
+            .iter()
+            .filter_map(|v| v.user_id.map(|id| id.0))
+            .collect();
+        assert_eq!(identified, vec![10, 40]);
+
+        assert!(written
+            .iter()
+            .filter(|v| v.user_id.is_none())
+            .all(|v| v.is_anonymous()));
+    }
+
+    #[test]
+    fn test_default_level_applies_to_unknown_users() {
+        let filter = ConsentFilter::new(RecordingSink::default(), consent(), ConsentLevel::None);
+
+        filter.push(event(Some(40)));
+        filter.push(event(None));
+        filter.push(event(Some(10)));
+
+        let written = filter.inner().0.lock().unwrap();
+        assert_eq!(written.len(), 1);
+        assert_eq!(written[0].user_id, Some(JsSafeBigInt(10)));
+    }
+}
@@ -0,0 +1,13 @@
+mod bucket;
+mod event;
+pub mod filter;
+mod rollup;
+#[cfg(feature = "bincode")]
+mod sketch;
+
+pub use bucket::{BucketSize, TimeBucket};
+pub use event::{AnalyticsEvent, EventSink};
+pub use filter::{ConsentFilter, ConsentLevel, ConsentPreferences, ConsentSource};
+pub use rollup::{merge_buckets, rebucket, rollup_events, RollupCounter, RollupKind};
+#[cfg(feature = "bincode")]
+pub use sketch::{NonFiniteValue, QuantileSketch, QuantileSummary, MAX_BINS, RELATIVE_ACCURACY};
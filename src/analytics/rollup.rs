@@ -0,0 +1,180 @@
+use std::collections::BTreeMap;
+use std::ops::AddAssign;
+
+use poem_openapi::Object;
+
+use crate::analytics::{AnalyticsEvent, BucketSize, TimeBucket};
+
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    strum::AsRefStr,
+    strum::EnumString,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum RollupKind {
+    View,
+    Vote,
+    Invite,
+}
+
+/// The counts of an entity within a single bucket.
+#[derive(
+    Object,
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    scylla::FromRow,
+    scylla::ValueList,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+pub struct RollupCounter {
+    pub views: i64,
+    pub votes: i64,
+    pub invites: i64,
+}
+
+impl RollupCounter {
+    pub fn record(&mut self, kind: RollupKind, n: i64) {
+        match kind {
+            RollupKind::View => self.views += n,
+            RollupKind::Vote => self.votes += n,
+            RollupKind::Invite => self.invites += n,
+        }
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl AddAssign for RollupCounter {
+    fn add_assign(&mut self, rhs: Self) {
+        self.views += rhs.views;
+        self.votes += rhs.votes;
+        self.invites += rhs.invites;
+    }
+}
+
+/// Combines partial counts of the same buckets, e.g. from several workers.
+pub fn merge_buckets(
+    partials: impl IntoIterator<Item = (TimeBucket, RollupCounter)>,
+) -> BTreeMap<TimeBucket, RollupCounter> {
+    let mut merged = BTreeMap::<TimeBucket, RollupCounter>::new();
+    for (bucket, counter) in partials {
+        *merged.entry(bucket).or_default() += counter;
+    }
+
+    merged
+}
+
+/// Rolls buckets up into larger buckets, e.g. hours into days.
+///
+/// `size` must not be smaller than the given buckets as counts cannot be
+/// split between buckets.
+pub fn rebucket(
+    partials: impl IntoIterator<Item = (TimeBucket, RollupCounter)>,
+    size: BucketSize,
+) -> BTreeMap<TimeBucket, RollupCounter> {
+    merge_buckets(
+        partials
+            .into_iter()
+            .map(|(bucket, counter)| (TimeBucket::of(size, &bucket.start), counter)),
+    )
+}
+
+/// Counts events into buckets of the given size, events of unknown kinds
+/// are ignored.
+pub fn rollup_events<'a>(
+    events: impl IntoIterator<Item = &'a AnalyticsEvent>,
+    size: BucketSize,
+) -> BTreeMap<TimeBucket, RollupCounter> {
+    let mut buckets = BTreeMap::<TimeBucket, RollupCounter>::new();
+    for event in events {
+        let Ok(kind) = event.kind.parse::<RollupKind>() else {
+            continue;
+        };
+
+        buckets
+            .entry(TimeBucket::of(size, &event.timestamp))
+            .or_default()
+            .record(kind, 1);
+    }
+
+    buckets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{JsSafeBigInt, Timestamp};
+    use std::str::FromStr;
+
+    fn event(kind: &str, at: &str) -> AnalyticsEvent {
+        AnalyticsEvent {
+            kind: kind.to_string(),
+            entity_id: JsSafeBigInt(1),
+            timestamp: Timestamp::from_str(at).unwrap(),
+            user_id: None,
+            session_id: None,
+            properties: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_rollup_and_rebucket() {
+        let events = [
+            event("view", "2024-02-04T10:15:00Z"),
+            event("view", "2024-02-04T10:45:00Z"),
+            event("vote", "2024-02-04T11:00:00Z"),
+            event("invite", "2024-02-05T00:00:00Z"),
+            event("unknown", "2024-02-05T00:00:00Z"),
+        ];
+
+        let hours = rollup_events(&events, BucketSize::Hour);
+        assert_eq!(hours.len(), 3);
+        assert_eq!(hours.values().next().unwrap().views, 2);
+
+        let weeks = rebucket(hours, BucketSize::Week);
+        let counts = weeks.values().copied().collect::<Vec<_>>();
+        assert_eq!(
+            counts,
+            [
+                RollupCounter {
+                    views: 2,
+                    votes: 1,
+                    invites: 0
+                },
+                RollupCounter {
+                    views: 0,
+                    votes: 0,
+                    invites: 1
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_merge_buckets() {
+        let at = Timestamp::from_str("2024-02-04T10:15:00Z").unwrap();
+        let bucket = TimeBucket::of(BucketSize::Day, &at);
+        let mut partial = RollupCounter::default();
+        partial.record(RollupKind::Vote, 2);
+
+        let merged = merge_buckets([(bucket, partial), (bucket, partial)]);
+        assert_eq!(merged[&bucket].votes, 4);
+        assert!(!merged[&bucket].is_empty());
+    }
+}
@@ -0,0 +1,323 @@
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+
+use bincode::{Decode, Encode};
+use poem_openapi::Object;
+use scylla::cql_to_rust::{FromCqlVal, FromCqlValError};
+use scylla::frame::response::result::CqlValue;
+use scylla::frame::value::ValueTooBig;
+
+use crate::db::BincodeBlob;
+
+/// Quantiles are within this fraction of the true value.
+pub const RELATIVE_ACCURACY: f64 = 0.01;
+
+/// The number of bins kept before the lowest bins are collapsed together,
+/// at 1% accuracy this covers roughly nine orders of magnitude.
+pub const MAX_BINS: usize = 1024;
+
+/// Values at or below this are counted as zero.
+const MIN_VALUE: f64 = 1e-9;
+
+fn gamma() -> f64 {
+    (1.0 + RELATIVE_ACCURACY) / (1.0 - RELATIVE_ACCURACY)
+}
+
+fn bin_of(value: f64) -> i32 {
+    (value.ln() / gamma().ln()).ceil() as i32
+}
+
+/// The value every sample in a bin is reported as, within
+/// [RELATIVE_ACCURACY] of all values falling into the bin.
+fn bin_value(bin: i32) -> f64 {
+    let gamma = gamma();
+    2.0 * gamma.powi(bin) / (gamma + 1.0)
+}
+
+/// A value which can't be recorded in a sketch, NaN or infinite.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NonFiniteValue(pub f64);
+
+impl Display for NonFiniteValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "cannot record non-finite value {}", self.0)
+    }
+}
+
+impl std::error::Error for NonFiniteValue {}
+
+/// A mergeable quantile sketch of non-negative values such as response
+/// times or ratings.
+///
+/// Values are counted in logarithmic bins so quantiles have a bounded
+/// relative error regardless of the value range, and sketches recorded by
+/// separate workers can be merged without losing accuracy. Negative values
+/// are counted as zero. Stored as a [BincodeBlob].
+#[derive(Clone, Debug, Default, PartialEq, Encode, Decode)]
+pub struct QuantileSketch {
+    count: u64,
+    zero_count: u64,
+    sum: f64,
+    min: f64,
+    max: f64,
+    bins: BTreeMap<i32, u64>,
+}
+
+impl QuantileSketch {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&mut self, value: f64) -> Result<(), NonFiniteValue> {
+        self.record_n(value, 1)
+    }
+
+    /// Records `value` `n` times, NaN and infinite values are rejected as
+    /// they would poison the sum and bins.
+    pub fn record_n(&mut self, value: f64, n: u64) -> Result<(), NonFiniteValue> {
+        if !value.is_finite() {
+            return Err(NonFiniteValue(value));
+        }
+        if n == 0 {
+            return Ok(());
+        }
+
+        let value = value.max(0.0);
+        if self.count == 0 {
+            self.min = value;
+            self.max = value;
+        } else {
+            self.min = self.min.min(value);
+            self.max = self.max.max(value);
+        }
+
+        self.count += n;
+        self.sum += value * n as f64;
+
+        if value <= MIN_VALUE {
+            self.zero_count += n;
+        } else {
+            *self.bins.entry(bin_of(value)).or_default() += n;
+            self.collapse();
+        }
+
+        Ok(())
+    }
+
+    /// Adds every value recorded by `other` to this sketch.
+    pub fn merge(&mut self, other: &Self) {
+        if other.count == 0 {
+            return;
+        }
+
+        if self.count == 0 {
+            *self = other.clone();
+            return;
+        }
+
+        self.count += other.count;
+        self.zero_count += other.zero_count;
+        self.sum += other.sum;
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+
+        for (bin, n) in other.bins.iter() {
+            *self.bins.entry(*bin).or_default() += n;
+        }
+        self.collapse();
+    }
+
+    /// Folds the lowest bins together once there are too many, trading
+    /// accuracy of the smallest values for a bounded size.
+    fn collapse(&mut self) {
+        while self.bins.len() > MAX_BINS {
+            let (_, lowest) = self.bins.pop_first().unwrap();
+            *self.bins.first_entry().unwrap().get_mut() += lowest;
+        }
+    }
+
+    #[inline]
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    pub fn min(&self) -> Option<f64> {
+        (!self.is_empty()).then_some(self.min)
+    }
+
+    pub fn max(&self) -> Option<f64> {
+        (!self.is_empty()).then_some(self.max)
+    }
+
+    pub fn mean(&self) -> Option<f64> {
+        (!self.is_empty()).then(|| self.sum / self.count as f64)
+    }
+
+    /// The value below which the fraction `q` of values fall, `q` is
+    /// clamped between `0.0` and `1.0`.
+    pub fn quantile(&self, q: f64) -> Option<f64> {
+        if self.is_empty() {
+            return None;
+        }
+
+        let rank = (q.clamp(0.0, 1.0) * (self.count - 1) as f64).floor() as u64;
+        if rank < self.zero_count {
+            return Some(self.min);
+        }
+
+        let mut seen = self.zero_count;
+        for (bin, n) in self.bins.iter() {
+            seen += n;
+            if seen > rank {
+                return Some(bin_value(*bin).clamp(self.min, self.max));
+            }
+        }
+
+        Some(self.max)
+    }
+
+    #[inline]
+    pub fn p50(&self) -> Option<f64> {
+        self.quantile(0.5)
+    }
+
+    #[inline]
+    pub fn p95(&self) -> Option<f64> {
+        self.quantile(0.95)
+    }
+
+    #[inline]
+    pub fn p99(&self) -> Option<f64> {
+        self.quantile(0.99)
+    }
+
+    pub fn summary(&self) -> QuantileSummary {
+        QuantileSummary {
+            count: self.count,
+            p50: self.p50(),
+            p95: self.p95(),
+            p99: self.p99(),
+        }
+    }
+}
+
+impl scylla::frame::value::Value for QuantileSketch {
+    fn serialize(&self, buf: &mut Vec<u8>) -> Result<(), ValueTooBig> {
+        scylla::frame::value::Value::serialize(&BincodeBlob(self), buf)
+    }
+}
+
+impl FromCqlVal<CqlValue> for QuantileSketch {
+    fn from_cql(cql_val: CqlValue) -> Result<Self, FromCqlValError> {
+        let blob = cql_val.into_blob().ok_or(FromCqlValError::BadCqlType)?;
+
+        BincodeBlob::decode(&blob).map_err(|_| FromCqlValError::BadCqlType)
+    }
+}
+
+/// The quantiles of a sketch as shown on listings.
+#[derive(Object, Copy, Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct QuantileSummary {
+    pub count: u64,
+    pub p50: Option<f64>,
+    pub p95: Option<f64>,
+    pub p99: Option<f64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: Option<f64>, expected: f64) {
+        let actual = actual.unwrap();
+        assert!(
+            (actual - expected).abs() <= expected * RELATIVE_ACCURACY,
+            "{} is not within 1% of {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_quantiles() {
+        let mut sketch = QuantileSketch::new();
+        assert_eq!(sketch.p50(), None);
+
+        for v in 1..=1000 {
+            sketch.record(v as f64).unwrap();
+        }
+
+        assert_eq!(sketch.count(), 1000);
+        assert_close(sketch.p50(), 500.0);
+        assert_close(sketch.p95(), 950.0);
+        assert_close(sketch.p99(), 990.0);
+        assert_eq!(sketch.quantile(0.0), Some(1.0));
+        assert_eq!(sketch.quantile(1.0), Some(1000.0));
+        assert_eq!(sketch.mean(), Some(500.5));
+    }
+
+    #[test]
+    fn test_merge_matches_single_sketch() {
+        let mut whole = QuantileSketch::new();
+        let mut even = QuantileSketch::new();
+        let mut odd = QuantileSketch::new();
+
+        for v in 0..500 {
+            whole.record(v as f64).unwrap();
+            if v % 2 == 0 {
+                even.record(v as f64).unwrap();
+            } else {
+                odd.record(v as f64).unwrap();
+            }
+        }
+
+        even.merge(&odd);
+        assert_eq!(even.summary(), whole.summary());
+        assert_eq!(even.min(), Some(0.0));
+    }
+
+    #[test]
+    fn test_bins_are_bounded() {
+        let mut sketch = QuantileSketch::new();
+        for exp in -200..200 {
+            sketch.record_n(1.1_f64.powi(exp * 10), 2).unwrap();
+        }
+
+        assert!(sketch.bins.len() <= MAX_BINS);
+        assert_eq!(sketch.count(), 800);
+    }
+
+    #[test]
+    fn test_rejects_non_finite_values() {
+        let mut sketch = QuantileSketch::new();
+        sketch.record(1.0).unwrap();
+
+        assert!(sketch.record(f64::NAN).is_err());
+        assert_eq!(
+            sketch.record_n(f64::INFINITY, 0),
+            Err(NonFiniteValue(f64::INFINITY))
+        );
+        assert!(sketch.record(f64::NEG_INFINITY).is_err());
+        assert_eq!(sketch.count(), 1);
+        assert_eq!(sketch.mean(), Some(1.0));
+    }
+
+    #[test]
+    fn test_blob_roundtrip() {
+        let mut sketch = QuantileSketch::new();
+        sketch.record(12.5).unwrap();
+        sketch.record(0.0).unwrap();
+
+        let mut buf = vec![];
+        scylla::frame::value::Value::serialize(&sketch, &mut buf).unwrap();
+        let decoded = QuantileSketch::from_cql(CqlValue::Blob(buf[4..].to_vec())).unwrap();
+
+        assert_eq!(decoded, sketch);
+    }
+}
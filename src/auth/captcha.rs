@@ -0,0 +1,296 @@
+use std::borrow::Cow;
+use std::fmt::{Debug, Display, Formatter};
+use std::str::FromStr;
+
+use poem_openapi::registry::{MetaSchema, MetaSchemaRef};
+use poem_openapi::types::{
+    ParseError, ParseFromJSON, ParseFromParameter, ParseResult, ToJSON, Type,
+};
+use serde_json::Value;
+
+use crate::errors::ApiError;
+#[cfg(feature = "captcha")]
+use crate::middleware::{with_deadline, DeadlineExceeded};
+
+pub const TURNSTILE_VERIFY_URL: &str = "https://challenges.cloudflare.com/turnstile/v0/siteverify";
+
+/// Turnstile tokens are at most this long.
+pub const MAX_CAPTCHA_TOKEN_LENGTH: usize = 2048;
+
+/// A Cloudflare Turnstile response token submitted by the client.
+///
+/// Tokens are single use and expire after 5 minutes. `Debug` does not
+/// output the token.
+#[derive(Clone, PartialEq, Eq)]
+pub struct CaptchaToken(String);
+
+impl CaptchaToken {
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Verifies the token with Turnstile.
+    ///
+    /// Invalid tokens are not an error, check the returned verification. The
+    /// request id is not forwarded as Turnstile is a third party.
+    #[cfg(feature = "captcha")]
+    pub async fn verify(&self, secret: &str) -> Result<CaptchaVerification, CaptchaError> {
+        static CLIENT: once_cell::sync::Lazy<reqwest::Client> =
+            once_cell::sync::Lazy::new(reqwest::Client::new);
+
+        let req = CLIENT.post(TURNSTILE_VERIFY_URL).json(&serde_json::json!({
+            "secret": secret,
+            "response": self.0,
+        }));
+
+        let resp = with_deadline(req.send()).await??;
+        let status = resp.status().as_u16();
+        if !(200..300).contains(&status) {
+            return Err(CaptchaError::Status(status));
+        }
+
+        let body = with_deadline(resp.text()).await??;
+        serde_json::from_str(&body).map_err(CaptchaError::Decode)
+    }
+}
+
+impl Debug for CaptchaToken {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "CaptchaToken({} chars)", self.0.len())
+    }
+}
+
+impl FromStr for CaptchaToken {
+    type Err = ParseError<Self>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+
+        if s.is_empty() {
+            return Err(ParseError::custom("A captcha token is required."));
+        }
+
+        if s.len() > MAX_CAPTCHA_TOKEN_LENGTH {
+            return Err(ParseError::custom("Invalid captcha token."));
+        }
+
+        Ok(Self(s.to_string()))
+    }
+}
+
+impl Type for CaptchaToken {
+    const IS_REQUIRED: bool = true;
+    type RawValueType = Self;
+    type RawElementValueType = Self;
+
+    fn name() -> Cow<'static, str> {
+        Cow::from("CaptchaToken")
+    }
+
+    fn schema_ref() -> MetaSchemaRef {
+        MetaSchemaRef::Inline(Box::new(MetaSchema {
+            max_length: Some(MAX_CAPTCHA_TOKEN_LENGTH),
+            ..MetaSchema::new("string")
+        }))
+    }
+
+    fn as_raw_value(&self) -> Option<&Self::RawValueType> {
+        Some(self)
+    }
+
+    fn raw_element_iter<'a>(
+        &'a self,
+    ) -> Box<dyn Iterator<Item = &'a Self::RawElementValueType> + 'a> {
+        Box::new(std::iter::once(self))
+    }
+}
+
+impl ToJSON for CaptchaToken {
+    fn to_json(&self) -> Option<Value> {
+        Some(Value::String(self.0.clone()))
+    }
+}
+
+impl ParseFromJSON for CaptchaToken {
+    fn parse_from_json(value: Option<Value>) -> ParseResult<Self> {
+        match value {
+            Some(Value::String(v)) => Self::from_str(&v),
+            None | Some(Value::Null) => Err(ParseError::custom("A captcha token is required.")),
+            other => Err(ParseError::custom(format!(
+                "Expected captcha token got {:?}",
+                other
+            ))),
+        }
+    }
+}
+
+/// Allows the token to be sent in a header, e.g. `cf-turnstile-response`.
+impl ParseFromParameter for CaptchaToken {
+    fn parse_from_parameter(value: &str) -> ParseResult<Self> {
+        Self::from_str(value)
+    }
+}
+
+#[cfg(feature = "captcha")]
+#[derive(Debug)]
+pub enum CaptchaError {
+    Request(reqwest::Error),
+    Status(u16),
+    Decode(serde_json::Error),
+    DeadlineExceeded,
+}
+
+#[cfg(feature = "captcha")]
+impl Display for CaptchaError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Request(e) => write!(f, "captcha verification request failed: {}", e),
+            Self::Status(status) => write!(f, "captcha verification returned {}", status),
+            Self::Decode(e) => write!(f, "cannot decode captcha verification: {}", e),
+            Self::DeadlineExceeded => write!(f, "request deadline exceeded"),
+        }
+    }
+}
+
+#[cfg(feature = "captcha")]
+impl std::error::Error for CaptchaError {}
+
+#[cfg(feature = "captcha")]
+impl From<reqwest::Error> for CaptchaError {
+    fn from(e: reqwest::Error) -> Self {
+        Self::Request(e)
+    }
+}
+
+#[cfg(feature = "captcha")]
+impl From<DeadlineExceeded> for CaptchaError {
+    fn from(_: DeadlineExceeded) -> Self {
+        Self::DeadlineExceeded
+    }
+}
+
+#[cfg(feature = "captcha")]
+impl From<CaptchaError> for ApiError {
+    fn from(e: CaptchaError) -> Self {
+        tracing::warn!(error = %e, "captcha verification failed");
+        Self::internal()
+    }
+}
+
+/// The outcome of a Turnstile verification.
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct CaptchaVerification {
+    pub success: bool,
+    /// The hostname of the site the challenge was solved on.
+    #[serde(default)]
+    pub hostname: Option<String>,
+    /// The action given to the widget, e.g. `vote` or `report`.
+    #[serde(default)]
+    pub action: Option<String>,
+    #[serde(default, rename = "error-codes")]
+    pub error_codes: Vec<String>,
+}
+
+impl CaptchaVerification {
+    /// Checks the token was valid and solved on the expected site for the
+    /// expected action, so a token from one form cannot be replayed on another.
+    pub fn check(&self, hostname: &str, action: Option<&str>) -> Result<(), CaptchaRejection> {
+        if !self.success {
+            return Err(CaptchaRejection::Failed(self.error_codes.clone()));
+        }
+
+        if !self
+            .hostname
+            .as_deref()
+            .map(|v| v.eq_ignore_ascii_case(hostname))
+            .unwrap_or_default()
+        {
+            return Err(CaptchaRejection::HostnameMismatch(self.hostname.clone()));
+        }
+
+        if action.is_some() && self.action.as_deref() != action {
+            return Err(CaptchaRejection::ActionMismatch(self.action.clone()));
+        }
+
+        Ok(())
+    }
+}
+
+/// Why a verified token was not accepted.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CaptchaRejection {
+    /// Turnstile rejected the token, with its error codes.
+    Failed(Vec<String>),
+    HostnameMismatch(Option<String>),
+    ActionMismatch(Option<String>),
+}
+
+impl Display for CaptchaRejection {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Failed(codes) => write!(f, "captcha failed: {}", codes.join(", ")),
+            Self::HostnameMismatch(v) => write!(f, "captcha solved on unexpected host {:?}", v),
+            Self::ActionMismatch(v) => write!(f, "captcha solved for unexpected action {:?}", v),
+        }
+    }
+}
+
+impl std::error::Error for CaptchaRejection {}
+
+impl From<CaptchaRejection> for ApiError {
+    fn from(_: CaptchaRejection) -> Self {
+        Self::validation("Captcha verification failed, please try again.")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::ErrorCode;
+
+    #[test]
+    fn test_parse_token() {
+        let token = CaptchaToken::parse_from_json(Some(Value::String(" 0.abc ".into()))).unwrap();
+        assert_eq!(token.as_str(), "0.abc");
+        assert!(!format!("{:?}", token).contains("abc"));
+
+        assert!(CaptchaToken::parse_from_json(None).is_err());
+        assert!(CaptchaToken::parse_from_parameter("").is_err());
+        assert!(CaptchaToken::from_str(&"a".repeat(MAX_CAPTCHA_TOKEN_LENGTH + 1)).is_err());
+    }
+
+    #[test]
+    fn test_check_verification() {
+        let verification: CaptchaVerification = serde_json::from_str(
+            r#"{"success":true,"hostname":"discordlist.gg","action":"vote","error-codes":[]}"#,
+        )
+        .unwrap();
+
+        assert!(verification.check("discordlist.gg", Some("vote")).is_ok());
+        assert!(verification.check("DiscordList.gg", None).is_ok());
+        assert_eq!(
+            verification.check("evil.example", Some("vote")),
+            Err(CaptchaRejection::HostnameMismatch(Some(
+                "discordlist.gg".to_string()
+            )))
+        );
+        assert!(matches!(
+            verification.check("discordlist.gg", Some("report")),
+            Err(CaptchaRejection::ActionMismatch(_))
+        ));
+
+        let failed: CaptchaVerification =
+            serde_json::from_str(r#"{"success":false,"error-codes":["timeout-or-duplicate"]}"#)
+                .unwrap();
+        let rejection = failed.check("discordlist.gg", None).unwrap_err();
+        assert_eq!(
+            rejection,
+            CaptchaRejection::Failed(vec!["timeout-or-duplicate".to_string()])
+        );
+        assert_eq!(
+            ApiError::from(rejection).code(),
+            ErrorCode::ValidationFailed
+        );
+    }
+}
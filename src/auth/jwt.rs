@@ -0,0 +1,171 @@
+use std::fmt::{Debug, Formatter};
+
+use chrono::Duration;
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use poem::http::StatusCode;
+use poem::{FromRequest, Request, RequestBody};
+
+use crate::auth::Role;
+use crate::crypto::{signing_key, EmptySecret};
+use crate::telemetry::record_user_id;
+use crate::types::{JsSafeBigInt, Timestamp};
+
+pub use jsonwebtoken::errors::Error as JwtError;
+
+/// The claims of every token issued by our APIs.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Claims {
+    /// The user's snowflake.
+    pub sub: JsSafeBigInt,
+    /// The user's account flags.
+    #[serde(default)]
+    pub flags: u64,
+    /// The user's staff role, tokens issued before roles existed are plain users.
+    #[serde(default)]
+    pub role: Role,
+    #[serde(with = "unix_seconds")]
+    pub iat: Timestamp,
+    #[serde(with = "unix_seconds")]
+    pub exp: Timestamp,
+}
+
+impl Claims {
+    pub fn new(user_id: JsSafeBigInt, flags: u64, ttl: Duration) -> Self {
+        let now = Timestamp::default();
+
+        Self {
+            sub: user_id,
+            flags,
+            role: Role::User,
+            iat: now,
+            exp: Timestamp(now.0 + ttl),
+        }
+    }
+
+    pub fn with_role(mut self, role: Role) -> Self {
+        self.role = role;
+        self
+    }
+
+    #[inline]
+    pub fn has_flag(&self, flag: u64) -> bool {
+        self.flags & flag == flag
+    }
+}
+
+/// The keys used to sign and verify tokens.
+///
+/// This must be added to the app with `.data(keys)` for the [Claims] extractor to work,
+/// or as an `Extension` with the `axum` feature.
+#[derive(Clone)]
+pub struct JwtKeys {
+    encoding: EncodingKey,
+    decoding: DecodingKey,
+    validation: Validation,
+}
+
+impl JwtKeys {
+    /// Rejects empty secrets, which would let anyone forge tokens.
+    pub fn from_secret(secret: impl AsRef<[u8]>) -> Result<Self, EmptySecret> {
+        let secret = signing_key(secret)?;
+        let mut validation = Validation::new(Algorithm::HS256);
+        validation.set_required_spec_claims(&["exp", "sub"]);
+
+        Ok(Self {
+            encoding: EncodingKey::from_secret(secret.expose()),
+            decoding: DecodingKey::from_secret(secret.expose()),
+            validation,
+        })
+    }
+
+    pub fn sign(&self, claims: &Claims) -> Result<String, JwtError> {
+        jsonwebtoken::encode(&Header::new(Algorithm::HS256), claims, &self.encoding)
+    }
+
+    /// Verifies the token's signature and expiry.
+    pub fn verify(&self, token: &str) -> Result<Claims, JwtError> {
+        jsonwebtoken::decode(token, &self.decoding, &self.validation).map(|v| v.claims)
+    }
+}
+
+impl Debug for JwtKeys {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JwtKeys").finish_non_exhaustive()
+    }
+}
+
+#[poem::async_trait]
+impl<'a> FromRequest<'a> for Claims {
+    async fn from_request(req: &'a Request, _body: &mut RequestBody) -> poem::Result<Self> {
+        let keys = req.data::<JwtKeys>().ok_or_else(|| {
+            poem::Error::from_string(
+                "JWT keys are not configured",
+                StatusCode::INTERNAL_SERVER_ERROR,
+            )
+        })?;
+
+        let token = req
+            .header("Authorization")
+            .and_then(|v| v.strip_prefix("Bearer "))
+            .ok_or_else(|| {
+                poem::Error::from_string("Missing bearer token", StatusCode::UNAUTHORIZED)
+            })?;
+
+        let claims = keys
+            .verify(token)
+            .map_err(|_| poem::Error::from_string("Invalid token", StatusCode::UNAUTHORIZED))?;
+
+        record_user_id(claims.sub);
+        Ok(claims)
+    }
+}
+
+/// JWTs require the time based claims to be numeric seconds.
+mod unix_seconds {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    use crate::types::Timestamp;
+
+    pub fn serialize<S: Serializer>(v: &Timestamp, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_i64(v.0.timestamp())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Timestamp, D::Error> {
+        i64::deserialize(deserializer).map(Timestamp::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign_and_verify() {
+        let keys = JwtKeys::from_secret("secret").unwrap();
+        let claims = Claims::new(JsSafeBigInt(1234), 0b101, Duration::hours(1));
+
+        let token = keys.sign(&claims).unwrap();
+        let verified = keys.verify(&token).unwrap();
+
+        assert_eq!(verified.sub, claims.sub);
+        assert_eq!(verified.exp.0.timestamp(), claims.exp.0.timestamp());
+        assert!(verified.has_flag(0b100));
+        assert!(!verified.has_flag(0b010));
+
+        assert!(JwtKeys::from_secret("other").unwrap().verify(&token).is_err());
+    }
+
+    #[test]
+    fn test_expired_rejected() {
+        let keys = JwtKeys::from_secret("secret").unwrap();
+        let claims = Claims::new(JsSafeBigInt(1234), 0, Duration::hours(-1));
+
+        let token = keys.sign(&claims).unwrap();
+        assert!(keys.verify(&token).is_err());
+    }
+
+    #[test]
+    fn test_empty_secret_rejected() {
+        assert!(matches!(JwtKeys::from_secret(""), Err(EmptySecret)));
+    }
+}
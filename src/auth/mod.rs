@@ -0,0 +1,25 @@
+mod captcha;
+#[cfg(feature = "jwt")]
+pub mod jwt;
+mod owner_invite;
+pub mod rbac;
+mod session;
+mod token;
+
+#[cfg(feature = "captcha")]
+pub use captcha::CaptchaError;
+pub use captcha::{
+    CaptchaRejection, CaptchaToken, CaptchaVerification, MAX_CAPTCHA_TOKEN_LENGTH,
+    TURNSTILE_VERIFY_URL,
+};
+#[cfg(feature = "jwt")]
+pub use jwt::{Claims, JwtKeys};
+pub use owner_invite::{
+    InviteToken, InviteTokenError, InviteTokenSigner, OwnerInviteCreated, OwnerInviteDecision,
+    OwnerInviteReply, OwnerInviteStore, OWNER_INVITE_TTL_DAYS,
+};
+#[cfg(feature = "jwt")]
+pub use rbac::{require, RequiredRole, RoleRequirement};
+pub use rbac::{Actor, Policy, Role};
+pub use session::{Session, SessionError, SessionId, SessionKeys, SESSION_COOKIE};
+pub use token::{ApiToken, HashedApiToken, API_TOKEN_PREFIX};
@@ -0,0 +1,400 @@
+use std::fmt::{Debug, Display, Formatter};
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration};
+use hmac::Mac;
+use poem_openapi::{Enum, Object};
+use uuid::Uuid;
+
+use crate::crypto::{hmac_sha256, signing_key, EmptySecret, HmacSha256};
+use crate::db::{execute_lwt, insert_using_ttl, DbError, Ignored, Session, WithTtl};
+use crate::errors::{ApiError, ApiResult};
+use crate::types::{JsSafeBigInt, Owners, Secret, Timestamp};
+
+/// How long a co-owner invite can be accepted for.
+pub const OWNER_INVITE_TTL_DAYS: i64 = 7;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum InviteTokenError {
+    /// The token is not in the `<bot id>.<invitee id>.<expires>.<nonce>.<signature>` format.
+    Malformed,
+    Expired,
+    Mismatch,
+    /// The token was issued to a different user.
+    WrongInvitee,
+    /// The invite was already accepted, declined or revoked.
+    Consumed,
+}
+
+impl Display for InviteTokenError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Malformed => write!(f, "malformed invite token"),
+            Self::Expired => write!(f, "invite token has expired"),
+            Self::Mismatch => write!(f, "invite token signature does not match"),
+            Self::WrongInvitee => write!(f, "invite token was issued to another user"),
+            Self::Consumed => write!(f, "invite token was already used or revoked"),
+        }
+    }
+}
+
+impl std::error::Error for InviteTokenError {}
+
+impl From<InviteTokenError> for ApiError {
+    fn from(e: InviteTokenError) -> Self {
+        match e {
+            InviteTokenError::Expired => Self::validation("This invite has expired."),
+            InviteTokenError::Consumed => Self::validation("This invite is no longer valid."),
+            _ => Self::validation("This invite is not valid."),
+        }
+    }
+}
+
+/// An invitation for a user to become a co-owner of a bot.
+///
+/// The nonce is stored with [OwnerInviteStore] so each invite can only be
+/// used once and can be revoked before it expires.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct InviteToken {
+    pub bot_id: JsSafeBigInt,
+    pub invitee_id: JsSafeBigInt,
+    pub expires_at: Timestamp,
+    pub nonce: Uuid,
+}
+
+impl InviteToken {
+    /// An invite expiring after [OWNER_INVITE_TTL_DAYS].
+    pub fn new(bot_id: JsSafeBigInt, invitee_id: JsSafeBigInt) -> Self {
+        Self {
+            bot_id,
+            invitee_id,
+            expires_at: Timestamp(Timestamp::default().0 + Duration::days(OWNER_INVITE_TTL_DAYS)),
+            nonce: Uuid::new_v4(),
+        }
+    }
+
+    /// Adds the invitee to the owners once they accept.
+    pub fn accept(&self, owners: &mut Owners) -> ApiResult<()> {
+        owners.add(self.invitee_id)
+    }
+}
+
+/// Signs and verifies co-owner invite tokens.
+///
+/// Tokens are formatted as
+/// `<bot id>.<invitee id>.<expires unix seconds>.<hex nonce>.<hex hmac>` so
+/// they are safe to put in urls as is. A valid signature only proves the
+/// token was issued, use [OwnerInviteStore::redeem] to check it is unused.
+#[derive(Clone)]
+pub struct InviteTokenSigner {
+    secret: Secret<Vec<u8>>,
+}
+
+impl InviteTokenSigner {
+    pub fn new(secret: impl AsRef<[u8]>) -> Result<Self, EmptySecret> {
+        Ok(Self {
+            secret: signing_key(secret)?,
+        })
+    }
+
+    fn mac(&self, payload: &str) -> HmacSha256 {
+        hmac_sha256(self.secret.expose(), &[payload.as_bytes()])
+    }
+
+    pub fn sign(&self, invite: &InviteToken) -> String {
+        let payload = format!(
+            "{}.{}.{}.{}",
+            invite.bot_id,
+            invite.invitee_id,
+            invite.expires_at.0.timestamp(),
+            invite.nonce.simple()
+        );
+        let signature = self.mac(&payload).finalize().into_bytes();
+
+        format!("{}.{}", payload, hex::encode(signature))
+    }
+
+    /// Verifies the signature in constant time, the expiry and that the
+    /// token belongs to the user accepting or declining it.
+    pub fn verify(
+        &self,
+        token: &str,
+        user_id: JsSafeBigInt,
+        now: Timestamp,
+    ) -> Result<InviteToken, InviteTokenError> {
+        let (payload, signature) = token.rsplit_once('.').ok_or(InviteTokenError::Malformed)?;
+
+        let mut parts = payload.split('.');
+        let (Some(bot_id), Some(invitee_id), Some(expires_at), Some(nonce), None) = (
+            parts.next(),
+            parts.next(),
+            parts.next(),
+            parts.next(),
+            parts.next(),
+        ) else {
+            return Err(InviteTokenError::Malformed);
+        };
+
+        let bot_id = bot_id
+            .parse()
+            .map(JsSafeBigInt)
+            .map_err(|_| InviteTokenError::Malformed)?;
+        let invitee_id = invitee_id
+            .parse()
+            .map(JsSafeBigInt)
+            .map_err(|_| InviteTokenError::Malformed)?;
+        let expires_at = expires_at
+            .parse::<i64>()
+            .ok()
+            .and_then(|v| DateTime::from_timestamp(v, 0))
+            .map(Timestamp)
+            .ok_or(InviteTokenError::Malformed)?;
+        let nonce = Uuid::try_parse(nonce).map_err(|_| InviteTokenError::Malformed)?;
+        let signature = hex::decode(signature).map_err(|_| InviteTokenError::Malformed)?;
+
+        self.mac(payload)
+            .verify_slice(&signature)
+            .map_err(|_| InviteTokenError::Mismatch)?;
+
+        if now.0 >= expires_at.0 {
+            return Err(InviteTokenError::Expired);
+        }
+
+        if invitee_id != user_id {
+            return Err(InviteTokenError::WrongInvitee);
+        }
+
+        Ok(InviteToken {
+            bot_id,
+            invitee_id,
+            expires_at,
+            nonce,
+        })
+    }
+}
+
+const INSERT_INVITE_QUERY: &str =
+    "INSERT INTO owner_invites (bot_id, invitee_id, nonce) VALUES (?, ?, ?);";
+const CONSUME_INVITE_QUERY: &str =
+    "DELETE FROM owner_invites WHERE bot_id = ? AND invitee_id = ? AND nonce = ? IF EXISTS;";
+const REVOKE_INVITES_QUERY: &str = "DELETE FROM owner_invites WHERE bot_id = ? AND invitee_id = ?;";
+
+/// The outstanding invites of each bot.
+///
+/// ```cql
+/// CREATE TABLE owner_invites (
+///     bot_id bigint,
+///     invitee_id bigint,
+///     nonce uuid,
+///     PRIMARY KEY (bot_id, invitee_id, nonce)
+/// );
+/// ```
+///
+/// Rows expire with the invite, accepting or declining deletes the row with
+/// a lightweight transaction so two concurrent replies can't both succeed.
+pub struct OwnerInviteStore {
+    session: Arc<Session>,
+}
+
+impl OwnerInviteStore {
+    pub fn new(session: Arc<Session>) -> Self {
+        Self { session }
+    }
+
+    /// Records a newly signed invite so it can be redeemed.
+    pub async fn create(&self, invite: &InviteToken) -> Result<(), DbError> {
+        let ttl = (invite.expires_at.0 - Timestamp::default().0)
+            .to_std()
+            .unwrap_or_default();
+        let values = (invite.bot_id, invite.invitee_id, invite.nonce);
+
+        self.session
+            .execute(
+                &insert_using_ttl(INSERT_INVITE_QUERY)?,
+                WithTtl::new(values, ttl),
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Verifies the token and marks it as used, this must be called for
+    /// both accepting and declining an invite.
+    pub async fn redeem(
+        &self,
+        signer: &InviteTokenSigner,
+        token: &str,
+        user_id: JsSafeBigInt,
+        now: Timestamp,
+    ) -> ApiResult<InviteToken> {
+        let invite = signer.verify(token, user_id, now)?;
+
+        let res = execute_lwt::<Ignored>(
+            &self.session,
+            CONSUME_INVITE_QUERY,
+            (invite.bot_id, invite.invitee_id, invite.nonce),
+        )
+        .await?;
+
+        if !res.is_applied() {
+            return Err(InviteTokenError::Consumed.into());
+        }
+
+        Ok(invite)
+    }
+
+    /// Revokes every outstanding invite of the bot to the user.
+    pub async fn revoke(
+        &self,
+        bot_id: JsSafeBigInt,
+        invitee_id: JsSafeBigInt,
+    ) -> Result<(), DbError> {
+        self.session
+            .execute(REVOKE_INVITES_QUERY, (bot_id, invitee_id))
+            .await?;
+        Ok(())
+    }
+}
+
+impl Debug for InviteTokenSigner {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("InviteTokenSigner").finish_non_exhaustive()
+    }
+}
+
+#[derive(
+    Enum,
+    Copy,
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    strum::AsRefStr,
+    strum::EnumString,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[oai(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum OwnerInviteDecision {
+    Accept,
+    Decline,
+}
+
+/// Sent by the invitee to accept or decline an invite.
+#[derive(Object, Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct OwnerInviteReply {
+    pub token: String,
+    pub decision: OwnerInviteDecision,
+}
+
+/// Returned to the owner when an invite is created.
+#[derive(Object, Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct OwnerInviteCreated {
+    pub token: String,
+    pub invitee_id: JsSafeBigInt,
+    pub expires_at: Timestamp,
+}
+
+impl OwnerInviteCreated {
+    pub fn new(signer: &InviteTokenSigner, invite: &InviteToken) -> Self {
+        Self {
+            token: signer.sign(invite),
+            invitee_id: invite.invitee_id,
+            expires_at: invite.expires_at,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BOT: JsSafeBigInt = JsSafeBigInt(100);
+    const INVITEE: JsSafeBigInt = JsSafeBigInt(2);
+
+    fn invite() -> InviteToken {
+        InviteToken {
+            bot_id: BOT,
+            invitee_id: INVITEE,
+            expires_at: Timestamp::from(1656633600),
+            nonce: Uuid::from_u128(0x1234),
+        }
+    }
+
+    #[test]
+    fn test_sign_and_verify() {
+        let signer = InviteTokenSigner::new("secret").unwrap();
+        assert!(InviteTokenSigner::new("").is_err());
+        let token = signer.sign(&invite());
+        let now = Timestamp::from(1656633600 - 60);
+
+        assert_eq!(token.split('.').count(), 5);
+        assert_eq!(signer.verify(&token, INVITEE, now), Ok(invite()));
+        assert_eq!(
+            signer.verify(&token, JsSafeBigInt(3), now),
+            Err(InviteTokenError::WrongInvitee)
+        );
+        assert_eq!(
+            signer.verify(&token, INVITEE, Timestamp::from(1656633600)),
+            Err(InviteTokenError::Expired)
+        );
+        assert_eq!(
+            InviteTokenSigner::new("other")
+                .unwrap()
+                .verify(&token, INVITEE, now),
+            Err(InviteTokenError::Mismatch)
+        );
+    }
+
+    #[test]
+    fn test_rejects_tampered_tokens() {
+        let signer = InviteTokenSigner::new("secret").unwrap();
+        let token = signer.sign(&invite());
+        let now = Timestamp::from(0);
+
+        assert_eq!(
+            signer.verify(&token.replacen("2.", "3.", 1), JsSafeBigInt(3), now),
+            Err(InviteTokenError::Mismatch)
+        );
+        assert_eq!(
+            signer.verify("100.2.abc", INVITEE, now),
+            Err(InviteTokenError::Malformed)
+        );
+        assert_eq!(
+            signer.verify(&format!("1.{}", token), INVITEE, now),
+            Err(InviteTokenError::Malformed)
+        );
+
+        let other = signer.sign(&InviteToken {
+            nonce: Uuid::from_u128(1),
+            ..invite()
+        });
+        let (payload, _) = token.rsplit_once('.').unwrap();
+        let (_, signature) = other.rsplit_once('.').unwrap();
+        assert_eq!(
+            signer.verify(&format!("{}.{}", payload, signature), INVITEE, now),
+            Err(InviteTokenError::Mismatch)
+        );
+    }
+
+    #[test]
+    fn test_invites_are_unique() {
+        let a = InviteToken::new(BOT, INVITEE);
+        let b = InviteToken::new(BOT, INVITEE);
+        assert_ne!(a.nonce, b.nonce);
+
+        let signer = InviteTokenSigner::new("secret").unwrap();
+        assert_ne!(signer.sign(&a), signer.sign(&b));
+    }
+
+    #[test]
+    fn test_accept() {
+        let mut owners = Owners::new(JsSafeBigInt(1));
+
+        invite().accept(&mut owners).unwrap();
+        assert!(owners.contains(INVITEE));
+        assert!(invite().accept(&mut owners).is_err());
+    }
+}
@@ -0,0 +1,290 @@
+#[cfg(feature = "jwt")]
+use std::marker::PhantomData;
+#[cfg(feature = "jwt")]
+use std::ops::Deref;
+
+use poem_openapi::Enum;
+
+#[cfg(feature = "jwt")]
+use crate::auth::Claims;
+use crate::errors::{ApiError, ApiResult};
+use crate::types::JsSafeBigInt;
+
+/// A user's staff role, declared from least to most privileged so roles
+/// can be compared with `>=`.
+#[derive(
+    Enum,
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    strum::AsRefStr,
+    strum::EnumString,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[oai(rename_all = "kebab-case")]
+#[strum(serialize_all = "kebab-case")]
+#[serde(rename_all = "kebab-case")]
+pub enum Role {
+    #[default]
+    User,
+    BotReviewer,
+    Moderator,
+    Admin,
+}
+
+impl Role {
+    /// Whether this role grants at least the permissions of `required`.
+    #[inline]
+    pub fn satisfies(&self, required: Role) -> bool {
+        *self >= required
+    }
+}
+
+cql_text_enum!(Role);
+
+/// Who is attempting an action.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Actor {
+    pub user_id: JsSafeBigInt,
+    pub role: Role,
+}
+
+#[cfg(feature = "jwt")]
+impl From<&Claims> for Actor {
+    fn from(claims: &Claims) -> Self {
+        Self {
+            user_id: claims.sub,
+            role: claims.role,
+        }
+    }
+}
+
+/// A composable authorisation rule, e.g.
+/// `Policy::owner().or(Policy::role(Role::Moderator))` allows the entity's
+/// owners and any moderator.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Policy {
+    /// The actor has at least the role.
+    Role(Role),
+    /// The actor is one of the entity's owners.
+    Owner,
+    Any(Vec<Policy>),
+    All(Vec<Policy>),
+}
+
+impl Policy {
+    #[inline]
+    pub fn role(role: Role) -> Self {
+        Self::Role(role)
+    }
+
+    #[inline]
+    pub fn owner() -> Self {
+        Self::Owner
+    }
+
+    pub fn or(self, other: Policy) -> Self {
+        match self {
+            Self::Any(mut policies) => {
+                policies.push(other);
+                Self::Any(policies)
+            }
+            policy => Self::Any(vec![policy, other]),
+        }
+    }
+
+    pub fn and(self, other: Policy) -> Self {
+        match self {
+            Self::All(mut policies) => {
+                policies.push(other);
+                Self::All(policies)
+            }
+            policy => Self::All(vec![policy, other]),
+        }
+    }
+
+    pub fn allows(&self, actor: &Actor, owners: &[JsSafeBigInt]) -> bool {
+        match self {
+            Self::Role(role) => actor.role.satisfies(*role),
+            Self::Owner => owners.contains(&actor.user_id),
+            Self::Any(policies) => policies.iter().any(|v| v.allows(actor, owners)),
+            Self::All(policies) => policies.iter().all(|v| v.allows(actor, owners)),
+        }
+    }
+
+    /// Checks the policy, returning the standard forbidden error if it fails.
+    pub fn authorise(&self, actor: &Actor, owners: &[JsSafeBigInt]) -> ApiResult<()> {
+        if !self.allows(actor, owners) {
+            return Err(ApiError::forbidden(
+                "You do not have permission to perform this action.",
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// The minimum role of a [RequiredRole] extractor.
+#[cfg(feature = "jwt")]
+pub trait RoleRequirement: Send + Sync + 'static {
+    const ROLE: Role;
+}
+
+/// Markers for the roles which can be required by [RequiredRole].
+#[cfg(feature = "jwt")]
+pub mod require {
+    use super::{Role, RoleRequirement};
+
+    pub struct BotReviewer;
+    pub struct Moderator;
+    pub struct Admin;
+
+    impl RoleRequirement for BotReviewer {
+        const ROLE: Role = Role::BotReviewer;
+    }
+
+    impl RoleRequirement for Moderator {
+        const ROLE: Role = Role::Moderator;
+    }
+
+    impl RoleRequirement for Admin {
+        const ROLE: Role = Role::Admin;
+    }
+}
+
+/// Extracts the [Claims] of a user with at least the required role,
+/// rejecting everyone else with `403 Forbidden`.
+///
+/// e.g. `user: RequiredRole<require::Moderator>`
+#[cfg(feature = "jwt")]
+pub struct RequiredRole<R: RoleRequirement> {
+    pub claims: Claims,
+    _role: PhantomData<fn() -> R>,
+}
+
+#[cfg(feature = "jwt")]
+impl<R: RoleRequirement> RequiredRole<R> {
+    pub fn actor(&self) -> Actor {
+        Actor::from(&self.claims)
+    }
+}
+
+#[cfg(feature = "jwt")]
+impl<R: RoleRequirement> Deref for RequiredRole<R> {
+    type Target = Claims;
+
+    fn deref(&self) -> &Self::Target {
+        &self.claims
+    }
+}
+
+#[cfg(feature = "jwt")]
+#[poem::async_trait]
+impl<'a, R: RoleRequirement> poem::FromRequest<'a> for RequiredRole<R> {
+    async fn from_request(
+        req: &'a poem::Request,
+        body: &mut poem::RequestBody,
+    ) -> poem::Result<Self> {
+        let claims = Claims::from_request(req, body).await?;
+
+        if !claims.role.satisfies(R::ROLE) {
+            return Err(poem::Error::from_string(
+                "Insufficient role",
+                poem::http::StatusCode::FORBIDDEN,
+            ));
+        }
+
+        Ok(Self {
+            claims,
+            _role: PhantomData,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::ErrorCode;
+
+    const OWNER: JsSafeBigInt = JsSafeBigInt(1);
+
+    fn actor(user_id: i64, role: Role) -> Actor {
+        Actor {
+            user_id: JsSafeBigInt(user_id),
+            role,
+        }
+    }
+
+    #[test]
+    fn test_role_ordering() {
+        assert!(Role::Admin.satisfies(Role::Moderator));
+        assert!(Role::BotReviewer.satisfies(Role::User));
+        assert!(!Role::BotReviewer.satisfies(Role::Moderator));
+        assert_eq!(Role::BotReviewer.as_ref(), "bot-reviewer");
+    }
+
+    #[test]
+    fn test_policy() {
+        let policy = Policy::owner().or(Policy::role(Role::Moderator));
+
+        assert!(policy.allows(&actor(1, Role::User), &[OWNER]));
+        assert!(policy.allows(&actor(2, Role::Admin), &[OWNER]));
+        assert!(!policy.allows(&actor(2, Role::BotReviewer), &[OWNER]));
+
+        let strict = Policy::owner().and(Policy::role(Role::BotReviewer));
+        assert!(!strict.allows(&actor(1, Role::User), &[OWNER]));
+        assert!(strict.allows(&actor(1, Role::BotReviewer), &[OWNER]));
+
+        let err = policy
+            .authorise(&actor(2, Role::User), &[OWNER])
+            .unwrap_err();
+        assert_eq!(err.code(), ErrorCode::Forbidden);
+    }
+
+    #[cfg(feature = "jwt")]
+    #[test]
+    fn test_required_role_extractor() {
+        use crate::auth::JwtKeys;
+        use poem::FromRequest;
+
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+
+        rt.block_on(async {
+            let keys = JwtKeys::from_secret("secret").unwrap();
+            let request = |role: Role| {
+                let claims = Claims::new(OWNER, 0, chrono::Duration::hours(1)).with_role(role);
+                let token = keys.sign(&claims).unwrap();
+
+                poem::Request::builder()
+                    .header("Authorization", format!("Bearer {}", token))
+                    .finish()
+            };
+
+            let mut req = request(Role::Moderator);
+            req.extensions_mut().insert(keys.clone());
+            let (req, mut body) = req.split();
+            let user = RequiredRole::<require::Moderator>::from_request(&req, &mut body)
+                .await
+                .unwrap();
+            assert_eq!(user.actor(), actor(1, Role::Moderator));
+
+            let mut req = request(Role::BotReviewer);
+            req.extensions_mut().insert(keys.clone());
+            let (req, mut body) = req.split();
+            let err = RequiredRole::<require::Moderator>::from_request(&req, &mut body)
+                .await
+                .err()
+                .unwrap();
+            assert_eq!(err.status(), poem::http::StatusCode::FORBIDDEN);
+        });
+    }
+}
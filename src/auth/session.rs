@@ -0,0 +1,299 @@
+use std::fmt::{Debug, Display, Formatter};
+use std::str::FromStr;
+
+use chrono::{DateTime, Duration};
+use hmac::Mac;
+use poem::http::StatusCode;
+use poem::{FromRequest, Request, RequestBody};
+use uuid::Uuid;
+
+use crate::crypto::{hmac_sha256, signing_key, EmptySecret, HmacSha256};
+use crate::types::{Secret, Timestamp};
+
+pub const SESSION_COOKIE: &str = "dlist_session";
+
+/// A random session identifier.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub struct SessionId(pub Uuid);
+
+impl SessionId {
+    pub fn generate() -> Self {
+        Self(Uuid::new_v4())
+    }
+}
+
+impl Display for SessionId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0.as_hyphenated())
+    }
+}
+
+impl FromStr for SessionId {
+    type Err = uuid::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Uuid::parse_str(s).map(Self)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SessionError {
+    /// The cookie is not in the `<id>.<expires>.<signature>` format.
+    Malformed,
+    Expired,
+    /// The signature does not match any of the keys.
+    Mismatch,
+}
+
+impl Display for SessionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Malformed => write!(f, "malformed session cookie"),
+            Self::Expired => write!(f, "session has expired"),
+            Self::Mismatch => write!(f, "session signature does not match"),
+        }
+    }
+}
+
+impl std::error::Error for SessionError {}
+
+/// A session as carried by the signed cookie.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Session {
+    pub id: SessionId,
+    pub expires_at: Timestamp,
+}
+
+impl Session {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            id: SessionId::generate(),
+            expires_at: Timestamp(Timestamp::default().0 + ttl),
+        }
+    }
+
+    #[inline]
+    pub fn is_expired(&self, now: Timestamp) -> bool {
+        now.0 >= self.expires_at.0
+    }
+
+    /// Whether less than half of the `ttl` remains, active sessions should be
+    /// reissued with [Session::refreshed] so they do not expire mid use.
+    pub fn should_refresh(&self, now: Timestamp, ttl: Duration) -> bool {
+        self.expires_at.0 - now.0 < ttl / 2
+    }
+
+    /// The same session with its expiry extended to `ttl` from now.
+    pub fn refreshed(&self, ttl: Duration) -> Self {
+        Self {
+            id: self.id,
+            expires_at: Timestamp(Timestamp::default().0 + ttl),
+        }
+    }
+}
+
+/// The keys used to sign and verify session cookies.
+///
+/// Cookies are always signed with the current key, the previous keys are
+/// still accepted so keys can be rotated without logging everyone out.
+/// This must be added to the app with `.data(keys)` for the [Session]
+/// extractor to work.
+#[derive(Clone)]
+pub struct SessionKeys {
+    current: Secret<Vec<u8>>,
+    previous: Vec<Secret<Vec<u8>>>,
+}
+
+impl SessionKeys {
+    pub fn new(secret: impl AsRef<[u8]>) -> Result<Self, EmptySecret> {
+        Ok(Self {
+            current: signing_key(secret)?,
+            previous: vec![],
+        })
+    }
+
+    /// Accepts cookies signed with an older key.
+    pub fn with_previous(mut self, secret: impl AsRef<[u8]>) -> Result<Self, EmptySecret> {
+        self.previous.push(signing_key(secret)?);
+        Ok(self)
+    }
+
+    fn mac(key: &[u8], payload: &str) -> HmacSha256 {
+        hmac_sha256(key, &[payload.as_bytes()])
+    }
+
+    /// The cookie value in the form `<id>.<expires unix seconds>.<hex hmac>`.
+    pub fn encode(&self, session: &Session) -> String {
+        let payload = format!("{}.{}", session.id, session.expires_at.0.timestamp());
+        let signature = Self::mac(self.current.expose(), &payload)
+            .finalize()
+            .into_bytes();
+
+        format!("{}.{}", payload, hex::encode(signature))
+    }
+
+    /// Verifies the signature in constant time and checks the expiry.
+    pub fn decode(&self, value: &str, now: Timestamp) -> Result<Session, SessionError> {
+        let (payload, signature) = value.rsplit_once('.').ok_or(SessionError::Malformed)?;
+        let (id, expires_at) = payload.split_once('.').ok_or(SessionError::Malformed)?;
+
+        let id = SessionId::from_str(id).map_err(|_| SessionError::Malformed)?;
+        let expires_at = expires_at
+            .parse::<i64>()
+            .ok()
+            .and_then(|v| DateTime::from_timestamp(v, 0))
+            .map(Timestamp)
+            .ok_or(SessionError::Malformed)?;
+        let signature = hex::decode(signature).map_err(|_| SessionError::Malformed)?;
+
+        let is_valid = std::iter::once(&self.current)
+            .chain(&self.previous)
+            .any(|key| {
+                Self::mac(key.expose(), payload)
+                    .verify_slice(&signature)
+                    .is_ok()
+            });
+        if !is_valid {
+            return Err(SessionError::Mismatch);
+        }
+
+        let session = Session { id, expires_at };
+        if session.is_expired(now) {
+            return Err(SessionError::Expired);
+        }
+
+        Ok(session)
+    }
+
+    /// The value of a `Set-Cookie` header carrying the session.
+    pub fn set_cookie(&self, session: &Session) -> String {
+        format!(
+            "{}={}; Path=/; Expires={}; HttpOnly; Secure; SameSite=Lax",
+            SESSION_COOKIE,
+            self.encode(session),
+            session.expires_at.0.format("%a, %d %b %Y %H:%M:%S GMT"),
+        )
+    }
+
+    /// The value of a `Set-Cookie` header removing the session.
+    pub fn clear_cookie() -> String {
+        format!(
+            "{}=; Path=/; Max-Age=0; HttpOnly; Secure; SameSite=Lax",
+            SESSION_COOKIE
+        )
+    }
+}
+
+impl Debug for SessionKeys {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SessionKeys").finish_non_exhaustive()
+    }
+}
+
+/// Finds the session cookie in the value of a `Cookie` header.
+fn find_cookie(header: &str) -> Option<&str> {
+    header
+        .split(';')
+        .filter_map(|v| v.trim().split_once('='))
+        .find(|(name, _)| *name == SESSION_COOKIE)
+        .map(|(_, value)| value.trim_matches('"'))
+}
+
+#[poem::async_trait]
+impl<'a> FromRequest<'a> for Session {
+    async fn from_request(req: &'a Request, _body: &mut RequestBody) -> poem::Result<Self> {
+        let keys = req.data::<SessionKeys>().ok_or_else(|| {
+            poem::Error::from_string(
+                "Session keys are not configured",
+                StatusCode::INTERNAL_SERVER_ERROR,
+            )
+        })?;
+
+        let cookie = req
+            .headers()
+            .get_all(poem::http::header::COOKIE)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .find_map(find_cookie)
+            .ok_or_else(|| {
+                poem::Error::from_string("Missing session cookie", StatusCode::UNAUTHORIZED)
+            })?;
+
+        keys.decode(cookie, Timestamp::default())
+            .map_err(|_| poem::Error::from_string("Invalid session", StatusCode::UNAUTHORIZED))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session() -> Session {
+        Session {
+            id: SessionId::generate(),
+            expires_at: Timestamp::from(1656633600),
+        }
+    }
+
+    #[test]
+    fn test_encode_and_decode() {
+        let keys = SessionKeys::new("secret").unwrap();
+        let session = session();
+        let value = keys.encode(&session);
+        let now = Timestamp::from(1656633600 - 60);
+
+        assert_eq!(keys.decode(&value, now), Ok(session));
+        assert_eq!(
+            keys.decode(&value, Timestamp::from(1656633600)),
+            Err(SessionError::Expired)
+        );
+        assert_eq!(
+            SessionKeys::new("other").unwrap().decode(&value, now),
+            Err(SessionError::Mismatch)
+        );
+
+        let tampered = value.replace(".1656633600.", ".1756633600.");
+        assert_eq!(keys.decode(&tampered, now), Err(SessionError::Mismatch));
+        assert_eq!(keys.decode("abc.def", now), Err(SessionError::Malformed));
+    }
+
+    #[test]
+    fn test_key_rotation() {
+        let session = session();
+        let now = Timestamp::from(0);
+        let old = SessionKeys::new("old").unwrap().encode(&session);
+
+        let rotated = SessionKeys::new("new")
+            .unwrap()
+            .with_previous("old")
+            .unwrap();
+        assert_eq!(rotated.decode(&old, now), Ok(session));
+        assert_ne!(rotated.encode(&session), old);
+
+        assert!(SessionKeys::new("").is_err());
+        assert!(SessionKeys::new("new").unwrap().with_previous("").is_err());
+    }
+
+    #[test]
+    fn test_refresh() {
+        let ttl = Duration::days(14);
+        let session = Session::new(ttl);
+        let now = Timestamp::default();
+
+        assert!(!session.should_refresh(now, ttl));
+        assert!(session.should_refresh(Timestamp(now.0 + Duration::days(8)), ttl));
+        assert_eq!(session.refreshed(ttl).id, session.id);
+    }
+
+    #[test]
+    fn test_find_cookie() {
+        let header = format!("theme=dark; {}=\"abc.1.ff\"; other=1", SESSION_COOKIE);
+        assert_eq!(find_cookie(&header), Some("abc.1.ff"));
+        assert_eq!(find_cookie("theme=dark"), None);
+
+        let keys = SessionKeys::new("secret").unwrap();
+        assert!(keys
+            .set_cookie(&session())
+            .contains("Expires=Fri, 01 Jul 2022 00:00:00 GMT"));
+    }
+}
@@ -0,0 +1,330 @@
+use std::borrow::Cow;
+use std::fmt::{Debug, Display, Formatter};
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+
+use poem_openapi::registry::{MetaSchema, MetaSchemaRef};
+use poem_openapi::types::{ParseError, ParseFromJSON, ParseResult, ToJSON, Type};
+use rand::Rng;
+use scylla::cql_to_rust::{FromCqlVal, FromCqlValError};
+use scylla::frame::response::result::CqlValue;
+use scylla::frame::value::ValueTooBig;
+use serde::de::Error;
+use serde::{Deserializer, Serializer};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+
+pub const API_TOKEN_PREFIX: &str = "dlist_";
+
+const BODY_LEN: usize = 32;
+const CHECKSUM_LEN: usize = 6;
+const ALPHABET: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+/// A bot API token in the form `dlist_<body><checksum>`.
+///
+/// The checksum allows malformed tokens to be rejected without a database lookup
+/// and lets secret scanners detect leaked tokens. `Debug` and `Display` never
+/// output the token itself, use `expose` when the raw value is required.
+///
+/// Only the hash is ever written to the database, so tokens can't leak through
+/// stored rows. The token can be deserialized but not serialized, anything
+/// stored or cached should hold the [HashedApiToken] instead.
+#[derive(Clone)]
+pub struct ApiToken(String);
+
+impl ApiToken {
+    pub fn generate() -> Self {
+        let mut rng = rand::thread_rng();
+        let body: String = (0..BODY_LEN)
+            .map(|_| ALPHABET[rng.gen_range(0..ALPHABET.len())] as char)
+            .collect();
+
+        Self(format!("{}{}{}", API_TOKEN_PREFIX, body, checksum(&body)))
+    }
+
+    /// The raw token, this should only ever be shown to the owner once.
+    #[inline]
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+
+    /// The SHA-256 hash of the token, tokens are stored and looked up by this.
+    pub fn hashed(&self) -> HashedApiToken {
+        HashedApiToken(hex::encode(Sha256::digest(self.0.as_bytes())))
+    }
+
+    fn body(&self) -> &str {
+        &self.0[API_TOKEN_PREFIX.len()..self.0.len() - CHECKSUM_LEN]
+    }
+}
+
+fn checksum(body: &str) -> String {
+    let digest = Sha256::digest(body.as_bytes());
+    let mut v = u32::from_be_bytes([digest[0], digest[1], digest[2], digest[3]]);
+
+    let mut out = [b'0'; CHECKSUM_LEN];
+    for slot in out.iter_mut().rev() {
+        *slot = ALPHABET[(v % ALPHABET.len() as u32) as usize];
+        v /= ALPHABET.len() as u32;
+    }
+
+    String::from_utf8(out.to_vec()).unwrap()
+}
+
+impl FromStr for ApiToken {
+    type Err = ParseError<Self>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rest = s
+            .strip_prefix(API_TOKEN_PREFIX)
+            .ok_or_else(|| ParseError::custom("Invalid API token prefix."))?;
+
+        if rest.len() != BODY_LEN + CHECKSUM_LEN || !rest.bytes().all(|v| ALPHABET.contains(&v)) {
+            return Err(ParseError::custom("Invalid API token format."));
+        }
+
+        let (body, check) = rest.split_at(BODY_LEN);
+        if !bool::from(checksum(body).as_bytes().ct_eq(check.as_bytes())) {
+            return Err(ParseError::custom("Invalid API token checksum."));
+        }
+
+        Ok(Self(s.to_string()))
+    }
+}
+
+impl PartialEq for ApiToken {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.as_bytes().ct_eq(other.0.as_bytes()).into()
+    }
+}
+
+impl Eq for ApiToken {}
+
+impl Hash for ApiToken {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state)
+    }
+}
+
+impl Debug for ApiToken {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ApiToken({})", self)
+    }
+}
+
+impl Display for ApiToken {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}…", API_TOKEN_PREFIX, &self.body()[..4])
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for ApiToken {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let raw = String::deserialize(deserializer)?;
+        Self::from_str(&raw).map_err(|e| D::Error::custom(e.into_message()))
+    }
+}
+
+impl Type for ApiToken {
+    const IS_REQUIRED: bool = true;
+    type RawValueType = Self;
+    type RawElementValueType = Self;
+
+    fn name() -> Cow<'static, str> {
+        Cow::from("ApiToken")
+    }
+
+    fn schema_ref() -> MetaSchemaRef {
+        MetaSchemaRef::Inline(Box::new(MetaSchema {
+            format: Some("password"),
+            ..MetaSchema::new("string")
+        }))
+    }
+
+    fn as_raw_value(&self) -> Option<&Self::RawValueType> {
+        Some(self)
+    }
+
+    fn raw_element_iter<'a>(
+        &'a self,
+    ) -> Box<dyn Iterator<Item = &'a Self::RawElementValueType> + 'a> {
+        Box::new(std::iter::once(self))
+    }
+}
+
+impl ToJSON for ApiToken {
+    fn to_json(&self) -> Option<Value> {
+        Some(Value::String(self.0.clone()))
+    }
+}
+
+impl ParseFromJSON for ApiToken {
+    fn parse_from_json(value: Option<Value>) -> ParseResult<Self> {
+        match value {
+            Some(Value::String(v)) => Self::from_str(&v),
+            other => Err(ParseError::custom(format!(
+                "Expected API token got {:?}",
+                other
+            ))),
+        }
+    }
+}
+
+/// Binds the token's hash, the raw token can't be read back from a row so
+/// there is no `FromCqlVal` impl.
+impl scylla::frame::value::Value for ApiToken {
+    fn serialize(&self, buf: &mut Vec<u8>) -> Result<(), ValueTooBig> {
+        self.hashed().serialize(buf)
+    }
+}
+
+/// The hex encoded SHA-256 hash of an [ApiToken].
+///
+/// Unlike the token itself this is safe to store, cache and log, and it
+/// serializes to and from the same hash.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct HashedApiToken(String);
+
+impl HashedApiToken {
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl FromStr for HashedApiToken {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let is_hash = s.len() == 64 && s.bytes().all(|v| matches!(v, b'0'..=b'9' | b'a'..=b'f'));
+        if !is_hash {
+            return Err("Invalid API token hash.".to_string());
+        }
+
+        Ok(Self(s.to_string()))
+    }
+}
+
+impl Display for HashedApiToken {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<&ApiToken> for HashedApiToken {
+    fn from(token: &ApiToken) -> Self {
+        token.hashed()
+    }
+}
+
+impl serde::Serialize for HashedApiToken {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for HashedApiToken {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let raw = String::deserialize(deserializer)?;
+        Self::from_str(&raw).map_err(D::Error::custom)
+    }
+}
+
+impl FromCqlVal<CqlValue> for HashedApiToken {
+    fn from_cql(cql_val: CqlValue) -> Result<Self, FromCqlValError> {
+        if let Some(v) = cql_val.as_text() {
+            Self::from_str(v).map_err(|_| FromCqlValError::BadCqlType)
+        } else {
+            Err(FromCqlValError::BadCqlType)
+        }
+    }
+}
+
+impl scylla::frame::value::Value for HashedApiToken {
+    fn serialize(&self, buf: &mut Vec<u8>) -> Result<(), ValueTooBig> {
+        self.0.serialize(buf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_and_parse() {
+        let token = ApiToken::generate();
+        assert!(token.expose().starts_with(API_TOKEN_PREFIX));
+        assert_eq!(
+            token.expose().len(),
+            API_TOKEN_PREFIX.len() + BODY_LEN + CHECKSUM_LEN
+        );
+
+        let parsed = ApiToken::from_str(token.expose()).unwrap();
+        assert_eq!(parsed, token);
+        assert_ne!(ApiToken::generate(), token);
+        assert_eq!(parsed.hashed(), token.hashed());
+    }
+
+    #[test]
+    fn test_rejects_bad_tokens() {
+        let token = ApiToken::generate();
+        let raw = token.expose();
+
+        let mut tampered = raw.to_string();
+        let flipped = if raw.as_bytes()[10] == b'a' { "b" } else { "a" };
+        tampered.replace_range(10..11, flipped);
+
+        assert!(ApiToken::from_str(&tampered).is_err());
+        assert!(ApiToken::from_str(&raw.replace("dlist_", "other_")).is_err());
+        assert!(ApiToken::from_str(&raw[..raw.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn test_redacted_output() {
+        let token = ApiToken::generate();
+        let body = &token.expose()[API_TOKEN_PREFIX.len()..];
+
+        assert!(!format!("{}", token).contains(body));
+        assert!(!format!("{:?}", token).contains(body));
+
+        let mut stored = vec![];
+        scylla::frame::value::Value::serialize(&token, &mut stored).unwrap();
+        let mut expected = vec![];
+        scylla::frame::value::Value::serialize(&token.hashed(), &mut expected).unwrap();
+        assert_eq!(stored, expected);
+    }
+
+    #[test]
+    fn test_hashed_round_trip() {
+        let token = ApiToken::generate();
+        let hashed = token.hashed();
+        assert!(!hashed.as_str().contains(&token.expose()[API_TOKEN_PREFIX.len()..]));
+
+        let json = serde_json::to_string(&hashed).unwrap();
+        assert_eq!(json, format!("\"{}\"", hashed));
+        assert_eq!(
+            serde_json::from_str::<HashedApiToken>(&json).unwrap(),
+            hashed
+        );
+        assert_eq!(
+            HashedApiToken::from_cql(CqlValue::Text(hashed.to_string())).unwrap(),
+            hashed
+        );
+
+        // A raw token is not a hash, and a hash is not a token.
+        let raw = serde_json::to_string(token.expose()).unwrap();
+        assert!(serde_json::from_str::<HashedApiToken>(&raw).is_err());
+        assert!(serde_json::from_str::<ApiToken>(&json).is_err());
+        assert_eq!(serde_json::from_str::<ApiToken>(&raw).unwrap(), token);
+    }
+}
@@ -0,0 +1,64 @@
+use axum::http::header::RETRY_AFTER;
+use axum::http::{HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+
+use crate::errors::ApiError;
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let (status, retry_after) = match &self {
+            Self::Validation(_) => (StatusCode::BAD_REQUEST, None),
+            Self::Forbidden(_) => (StatusCode::FORBIDDEN, None),
+            Self::NotFound(_) => (StatusCode::NOT_FOUND, None),
+            Self::RateLimited(_, secs) => (StatusCode::TOO_MANY_REQUESTS, Some(*secs)),
+            Self::Internal(_) => (StatusCode::INTERNAL_SERVER_ERROR, None),
+            Self::Status(status, _) => (
+                StatusCode::from_u16(*status).unwrap_or(StatusCode::BAD_REQUEST),
+                None,
+            ),
+        };
+
+        let mut resp = (status, Json(self.body())).into_response();
+        if let Some(secs) = retry_after {
+            resp.headers_mut()
+                .insert(RETRY_AFTER, HeaderValue::from(secs));
+        }
+
+        resp
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use axum::body::to_bytes;
+
+    use super::*;
+    use crate::errors::{ErrorBody, ErrorCode};
+
+    #[test]
+    fn test_into_response() {
+        let resp = ApiError::rate_limited(Duration::from_millis(1500)).into_response();
+        assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(resp.headers()[RETRY_AFTER], "2");
+
+        let resp = ApiError::not_found("No such bot.").into_response();
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+        assert!(resp.headers().get(RETRY_AFTER).is_none());
+
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let body = rt.block_on(to_bytes(resp.into_body(), usize::MAX)).unwrap();
+        assert_eq!(
+            serde_json::from_slice::<ErrorBody>(&body).unwrap(),
+            ErrorBody {
+                code: ErrorCode::NotFound,
+                message: "No such bot.".to_string(),
+                errors: None,
+            }
+        );
+    }
+}
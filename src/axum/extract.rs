@@ -0,0 +1,229 @@
+use std::convert::Infallible;
+use std::str::FromStr;
+
+use axum::extract::FromRequestParts;
+use axum::http::request::Parts;
+use axum::http::HeaderValue;
+use axum::response::{IntoResponseParts, ResponseParts};
+
+use crate::types::{RequestId, REQUEST_ID_HEADER};
+
+/// Takes the id from the `X-Request-Id` header, generating a new one when the
+/// header is missing or invalid.
+///
+/// The id is stored in the request extensions so every extractor of the same
+/// request sees the same id.
+impl<S: Send + Sync> FromRequestParts<S> for RequestId {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        if let Some(id) = parts.extensions.get::<RequestId>() {
+            return Ok(*id);
+        }
+
+        let id = parts
+            .headers
+            .get(REQUEST_ID_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| RequestId::from_str(v).ok())
+            .unwrap_or_default();
+        parts.extensions.insert(id);
+
+        Ok(id)
+    }
+}
+
+/// Echoes the id back in the `X-Request-Id` header.
+impl IntoResponseParts for RequestId {
+    type Error = Infallible;
+
+    fn into_response_parts(self, mut res: ResponseParts) -> Result<ResponseParts, Self::Error> {
+        if let Ok(value) = HeaderValue::from_str(&self.to_string()) {
+            res.headers_mut().insert(REQUEST_ID_HEADER, value);
+        }
+
+        Ok(res)
+    }
+}
+
+#[cfg(feature = "server")]
+mod paging {
+    use axum::extract::Query;
+    use scylla::FromRow;
+
+    use super::*;
+    use crate::db::{Cursor, PagedQuery, DEFAULT_PAGE_SIZE};
+    use crate::errors::ApiError;
+
+    /// The `cursor` and `limit` query parameters of a paginated listing.
+    #[derive(Clone, Debug, Default, PartialEq, Eq, serde::Deserialize)]
+    pub struct PageParams {
+        pub cursor: Option<Cursor>,
+        pub limit: Option<u32>,
+    }
+
+    impl PageParams {
+        /// The requested page size, [PagedQuery] clamps it to the allowed range.
+        #[inline]
+        pub fn page_size(&self) -> u32 {
+            self.limit.unwrap_or(DEFAULT_PAGE_SIZE as u32)
+        }
+
+        /// Resumes the query from the cursor with the requested page size.
+        pub fn apply<'a, T: FromRow>(self, query: PagedQuery<'a, T>) -> PagedQuery<'a, T> {
+            let page_size = self.page_size();
+            query.with_page_size(page_size).starting_at(self.cursor)
+        }
+    }
+
+    impl<S: Send + Sync> FromRequestParts<S> for PageParams {
+        type Rejection = ApiError;
+
+        async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+            Query::<Self>::from_request_parts(parts, state)
+                .await
+                .map(|Query(v)| v)
+                .map_err(|e| ApiError::validation(e.body_text()))
+        }
+    }
+}
+
+#[cfg(feature = "server")]
+pub use paging::PageParams;
+
+#[cfg(feature = "jwt")]
+mod jwt {
+    use axum::http::header::AUTHORIZATION;
+    use axum::http::StatusCode;
+
+    use super::*;
+    use crate::auth::{Claims, JwtKeys};
+    use crate::telemetry::record_user_id;
+
+    /// Verifies the bearer token, [JwtKeys] must be added to the router as an
+    /// `Extension`.
+    impl<S: Send + Sync> FromRequestParts<S> for Claims {
+        type Rejection = (StatusCode, &'static str);
+
+        async fn from_request_parts(
+            parts: &mut Parts,
+            _state: &S,
+        ) -> Result<Self, Self::Rejection> {
+            let keys = parts.extensions.get::<JwtKeys>().ok_or((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "JWT keys are not configured",
+            ))?;
+
+            let token = parts
+                .headers
+                .get(AUTHORIZATION)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.strip_prefix("Bearer "))
+                .ok_or((StatusCode::UNAUTHORIZED, "Missing bearer token"))?;
+
+            let claims = keys
+                .verify(token)
+                .map_err(|_| (StatusCode::UNAUTHORIZED, "Invalid token"))?;
+
+            record_user_id(claims.sub);
+            Ok(claims)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::future::Future;
+
+    use axum::http::Request;
+
+    use super::*;
+
+    fn run<F: Future>(fut: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(fut)
+    }
+
+    fn parts(uri: &str, headers: &[(&'static str, &str)]) -> Parts {
+        let mut req = Request::builder().uri(uri);
+        for (name, value) in headers {
+            req = req.header(*name, *value);
+        }
+
+        req.body(()).unwrap().into_parts().0
+    }
+
+    #[test]
+    fn test_request_id() {
+        let id = RequestId::new();
+        let mut from_header = parts("/", &[(REQUEST_ID_HEADER, &id.to_string())]);
+        assert_eq!(
+            run(RequestId::from_request_parts(&mut from_header, &())).unwrap(),
+            id
+        );
+
+        let mut generated = parts("/", &[(REQUEST_ID_HEADER, "not-an-id")]);
+        let first = run(RequestId::from_request_parts(&mut generated, &())).unwrap();
+        assert_ne!(first, id);
+        assert_eq!(
+            run(RequestId::from_request_parts(&mut generated, &())).unwrap(),
+            first
+        );
+    }
+
+    #[cfg(feature = "server")]
+    #[test]
+    fn test_page_params() {
+        use crate::db::{Cursor, DEFAULT_PAGE_SIZE};
+
+        let mut req = parts("/bots?cursor=0001fe&limit=20", &[]);
+        let params = run(PageParams::from_request_parts(&mut req, &())).unwrap();
+        assert_eq!(params.cursor, Some(Cursor::from_str("0001fe").unwrap()));
+        assert_eq!(params.page_size(), 20);
+
+        let mut req = parts("/bots", &[]);
+        let params = run(PageParams::from_request_parts(&mut req, &())).unwrap();
+        assert_eq!(params, PageParams::default());
+        assert_eq!(params.page_size(), DEFAULT_PAGE_SIZE as u32);
+
+        let mut req = parts("/bots?cursor=nothex", &[]);
+        let err = run(PageParams::from_request_parts(&mut req, &())).unwrap_err();
+        assert_eq!(err.code(), crate::errors::ErrorCode::ValidationFailed);
+    }
+
+    #[cfg(feature = "jwt")]
+    #[test]
+    fn test_claims() {
+        use axum::http::StatusCode;
+        use chrono::Duration;
+
+        use crate::auth::{Claims, JwtKeys};
+        use crate::types::JsSafeBigInt;
+
+        let keys = JwtKeys::from_secret("secret").unwrap();
+        let token = keys
+            .sign(&Claims::new(JsSafeBigInt(1234), 0, Duration::hours(1)))
+            .unwrap();
+
+        let bearer = format!("Bearer {}", token);
+        let mut req = parts("/", &[("Authorization", &bearer)]);
+        assert_eq!(
+            run(Claims::from_request_parts(&mut req, &()))
+                .unwrap_err()
+                .0,
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+
+        req.extensions.insert(keys);
+        let claims = run(Claims::from_request_parts(&mut req, &())).unwrap();
+        assert_eq!(claims.sub, JsSafeBigInt(1234));
+
+        req.headers.remove("Authorization");
+        assert_eq!(
+            run(Claims::from_request_parts(&mut req, &())).unwrap_err(),
+            (StatusCode::UNAUTHORIZED, "Missing bearer token")
+        );
+    }
+}
@@ -0,0 +1,13 @@
+//! `axum` support for the shared types, for the internal tools which are not
+//! built on poem.
+//!
+//! The extractors mirror their poem counterparts: `RequestId` and `Claims`
+//! are taken from the request the same way and `ApiError` renders the same
+//! status codes and body. Only the paging and JWT extractors need the
+//! `server` and `jwt` features.
+
+mod error;
+mod extract;
+
+#[cfg(feature = "server")]
+pub use extract::PageParams;
@@ -0,0 +1,131 @@
+use chrono::Duration;
+
+use crate::premium::Tier;
+use crate::types::Timestamp;
+
+/// The time an entity must wait between bumps.
+pub const BUMP_COOLDOWN: Duration = Duration::hours(2);
+
+/// The number of times an entity can be bumped per UTC day.
+pub const BUMP_DAILY_CAP: i32 = 6;
+
+/// How much more often entities of a premium tier can be bumped.
+pub fn premium_multiplier(tier: Tier) -> f64 {
+    match tier {
+        Tier::Free => 1.0,
+        Tier::Pro => 1.5,
+        Tier::Business => 2.0,
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct BumpLimits {
+    pub cooldown: Duration,
+    pub daily_cap: i32,
+}
+
+impl Default for BumpLimits {
+    fn default() -> Self {
+        Self {
+            cooldown: BUMP_COOLDOWN,
+            daily_cap: BUMP_DAILY_CAP,
+        }
+    }
+}
+
+impl BumpLimits {
+    /// The limits of an entity of the given tier.
+    pub fn for_tier(tier: Tier) -> Self {
+        Self::default().with_multiplier(premium_multiplier(tier))
+    }
+
+    /// Divides the cooldown and multiplies the daily cap, multipliers below
+    /// `1.0` are ignored so limits can only be relaxed.
+    pub fn with_multiplier(self, multiplier: f64) -> Self {
+        let multiplier = multiplier.max(1.0);
+
+        Self {
+            cooldown: Duration::seconds(
+                (self.cooldown.num_seconds() as f64 / multiplier).round() as i64
+            ),
+            daily_cap: (self.daily_cap as f64 * multiplier).floor() as i32,
+        }
+    }
+}
+
+/// The cooldown window following an entity's last bump.
+///
+/// The boundary is inclusive, an entity can be bumped again at exactly
+/// `last_bumped_at + window`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct BumpCooldown {
+    pub last_bumped_at: Timestamp,
+    pub window: Duration,
+}
+
+impl BumpCooldown {
+    pub fn new(last_bumped_at: Timestamp) -> Self {
+        Self {
+            last_bumped_at,
+            window: BUMP_COOLDOWN,
+        }
+    }
+
+    pub fn with_window(mut self, window: Duration) -> Self {
+        self.window = window;
+        self
+    }
+
+    #[inline]
+    pub fn next_bump_at(&self) -> Timestamp {
+        Timestamp(self.last_bumped_at.0 + self.window)
+    }
+
+    #[inline]
+    pub fn can_bump_at(&self, now: Timestamp) -> bool {
+        now.0 >= self.next_bump_at().0
+    }
+
+    /// The time left until the entity can be bumped again, zero if it already can.
+    pub fn remaining(&self, now: Timestamp) -> std::time::Duration {
+        (self.next_bump_at().0 - now.0).to_std().unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cooldown_boundary() {
+        let cooldown = BumpCooldown::new(Timestamp::from(0));
+        let boundary = BUMP_COOLDOWN.num_seconds();
+
+        assert!(!cooldown.can_bump_at(Timestamp::from(boundary - 1)));
+        assert!(cooldown.can_bump_at(Timestamp::from(boundary)));
+        assert_eq!(
+            cooldown.remaining(Timestamp::from(boundary - 60)),
+            std::time::Duration::from_secs(60)
+        );
+    }
+
+    #[test]
+    fn test_premium_limits() {
+        assert_eq!(BumpLimits::for_tier(Tier::Free), BumpLimits::default());
+        assert_eq!(
+            BumpLimits::for_tier(Tier::Business),
+            BumpLimits {
+                cooldown: Duration::hours(1),
+                daily_cap: 12,
+            }
+        );
+        assert_eq!(
+            BumpLimits::for_tier(Tier::Pro).cooldown,
+            Duration::minutes(80)
+        );
+        assert_eq!(
+            BumpLimits::default().with_multiplier(0.5),
+            BumpLimits::default()
+        );
+    }
+}
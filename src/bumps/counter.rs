@@ -0,0 +1,372 @@
+use std::fmt::{Display, Formatter};
+use std::time::Duration;
+
+use poem_openapi::{Enum, Object};
+
+use crate::analytics::{BucketSize, TimeBucket};
+use crate::bumps::{BumpCooldown, BumpLimits};
+use crate::db::{
+    execute_lwt, insert_using_ttl, update_using_ttl, DbError, Ignored, Session, WithTtl,
+};
+use crate::errors::ApiError;
+use crate::types::{JsSafeBigInt, Timestamp};
+
+/// Partitioned by `(target, entity_id)` and clustered by `day DESC`.
+pub const BUMP_COUNTER_TABLE: &str = "bump_counters";
+
+/// Counters are only needed for the current day and the cooldown carried
+/// over midnight, they are kept for two days.
+pub const BUMP_COUNTER_TTL: Duration = Duration::from_secs(2 * 24 * 60 * 60);
+
+/// How many times [bump] re-reads the counter after a concurrent bump won
+/// the conditional write.
+pub const BUMP_MAX_ATTEMPTS: usize = 5;
+
+#[derive(
+    Enum,
+    Copy,
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    strum::AsRefStr,
+    strum::EnumString,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[oai(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum BumpTarget {
+    Bot,
+    Guild,
+}
+
+cql_text_enum!(BumpTarget);
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum BumpRejection {
+    /// The entity was bumped too recently, with the time until it can be bumped again.
+    Cooldown(Duration),
+    /// The entity has used all of today's bumps, with the time until midnight UTC.
+    DailyCapReached(Duration),
+}
+
+impl BumpRejection {
+    #[inline]
+    pub fn retry_after(&self) -> Duration {
+        match self {
+            Self::Cooldown(v) | Self::DailyCapReached(v) => *v,
+        }
+    }
+}
+
+impl Display for BumpRejection {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Cooldown(v) => write!(f, "bumped too recently, retry in {}s", v.as_secs()),
+            Self::DailyCapReached(v) => {
+                write!(f, "daily bump cap reached, resets in {}s", v.as_secs())
+            }
+        }
+    }
+}
+
+impl std::error::Error for BumpRejection {}
+
+impl From<BumpRejection> for ApiError {
+    fn from(e: BumpRejection) -> Self {
+        Self::rate_limited(e.retry_after())
+    }
+}
+
+#[derive(Debug)]
+pub enum BumpError {
+    Rejected(BumpRejection),
+    /// Every attempt lost the conditional write to a concurrent bump.
+    Contended,
+    Db(DbError),
+}
+
+impl Display for BumpError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Rejected(e) => write!(f, "{}", e),
+            Self::Contended => write!(f, "bump counter is contended"),
+            Self::Db(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for BumpError {}
+
+impl From<BumpRejection> for BumpError {
+    fn from(e: BumpRejection) -> Self {
+        Self::Rejected(e)
+    }
+}
+
+impl From<DbError> for BumpError {
+    fn from(e: DbError) -> Self {
+        Self::Db(e)
+    }
+}
+
+impl From<BumpError> for ApiError {
+    fn from(e: BumpError) -> Self {
+        match e {
+            BumpError::Rejected(e) => e.into(),
+            BumpError::Contended => Self::rate_limited(Duration::from_secs(1)),
+            BumpError::Db(e) => e.into(),
+        }
+    }
+}
+
+/// An entity's bumps for a single UTC day.
+///
+/// The field order matches the column order of [BUMP_COUNTER_TABLE] so this
+/// can be read and written directly as a row.
+#[derive(
+    Object,
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    scylla::FromRow,
+    scylla::ValueList,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+pub struct BumpCounter {
+    pub target: BumpTarget,
+    pub entity_id: JsSafeBigInt,
+    /// The start of the UTC day being counted.
+    pub day: Timestamp,
+    pub count: i32,
+    pub last_bumped_at: Option<Timestamp>,
+}
+
+impl BumpCounter {
+    pub fn new(target: BumpTarget, entity_id: JsSafeBigInt, now: Timestamp) -> Self {
+        Self {
+            target,
+            entity_id,
+            day: TimeBucket::of(BucketSize::Day, &now).start,
+            count: 0,
+            last_bumped_at: None,
+        }
+    }
+
+    /// The counter for the day of `now`, a counter from an earlier day is
+    /// reset but keeps its last bump so the cooldown carries over midnight.
+    pub fn rolled_over(&self, now: Timestamp) -> Self {
+        let day = TimeBucket::of(BucketSize::Day, &now).start;
+        if day == self.day {
+            return self.clone();
+        }
+
+        Self {
+            day,
+            count: 0,
+            ..self.clone()
+        }
+    }
+
+    /// The number of bumps left today.
+    pub fn remaining(&self, limits: &BumpLimits, now: Timestamp) -> i32 {
+        (limits.daily_cap - self.rolled_over(now).count).max(0)
+    }
+
+    pub fn check(&self, limits: &BumpLimits, now: Timestamp) -> Result<(), BumpRejection> {
+        let counter = self.rolled_over(now);
+
+        if let Some(last_bumped_at) = counter.last_bumped_at {
+            let cooldown = BumpCooldown::new(last_bumped_at).with_window(limits.cooldown);
+            if !cooldown.can_bump_at(now) {
+                return Err(BumpRejection::Cooldown(cooldown.remaining(now)));
+            }
+        }
+
+        if counter.count >= limits.daily_cap {
+            let resets_at = TimeBucket::of(BucketSize::Day, &now).end();
+            let remaining = (resets_at.0 - now.0).to_std().unwrap_or_default();
+            return Err(BumpRejection::DailyCapReached(remaining));
+        }
+
+        Ok(())
+    }
+
+    /// Counts a bump at `now` if the limits allow it.
+    pub fn record(&mut self, limits: &BumpLimits, now: Timestamp) -> Result<(), BumpRejection> {
+        self.check(limits, now)?;
+
+        *self = self.rolled_over(now);
+        self.count += 1;
+        self.last_bumped_at = Some(now);
+        Ok(())
+    }
+}
+
+const SELECT_COUNTER_QUERY: &str = "SELECT target, entity_id, day, count, last_bumped_at \
+    FROM bump_counters WHERE target = ? AND entity_id = ? LIMIT 1;";
+const INSERT_COUNTER_QUERY: &str = "INSERT INTO bump_counters \
+    (target, entity_id, day, count, last_bumped_at) VALUES (?, ?, ?, ?, ?) IF NOT EXISTS;";
+const UPDATE_COUNTER_QUERY: &str = "UPDATE bump_counters \
+    SET count = ?, last_bumped_at = ? WHERE target = ? AND entity_id = ? AND day = ? \
+    IF count = ?;";
+
+/// Counts a bump of the entity at `now` if the limits allow it.
+///
+/// The counter is written with a lightweight transaction conditioned on the
+/// count that was read, so concurrent bumps can't both take the last slot.
+/// A lost race re-reads the counter and checks the limits again.
+pub async fn bump(
+    session: &Session,
+    limits: &BumpLimits,
+    target: BumpTarget,
+    entity_id: JsSafeBigInt,
+    now: Timestamp,
+) -> Result<BumpCounter, BumpError> {
+    for _ in 0..BUMP_MAX_ATTEMPTS {
+        let stored = fetch_stored(session, target, entity_id).await?;
+        let mut counter = match &stored {
+            Some(counter) => counter.rolled_over(now),
+            None => BumpCounter::new(target, entity_id, now),
+        };
+        let expected = stored.filter(|v| v.day == counter.day).map(|v| v.count);
+
+        counter.record(limits, now)?;
+        if store(session, &counter, expected).await? {
+            return Ok(counter);
+        }
+    }
+
+    Err(BumpError::Contended)
+}
+
+/// Writes the counter if the stored count for its day is still `expected`,
+/// `None` meaning there is no row for the day yet. Returns whether the
+/// write was applied.
+pub async fn store(
+    session: &Session,
+    counter: &BumpCounter,
+    expected: Option<i32>,
+) -> Result<bool, DbError> {
+    let res = match expected {
+        None => {
+            execute_lwt::<Ignored>(
+                session,
+                &insert_using_ttl(INSERT_COUNTER_QUERY)?,
+                WithTtl::new(counter, BUMP_COUNTER_TTL),
+            )
+            .await?
+        }
+        Some(expected) => {
+            let values = (
+                counter.count,
+                counter.last_bumped_at,
+                counter.target,
+                counter.entity_id,
+                counter.day,
+                expected,
+            );
+            execute_lwt::<Ignored>(
+                session,
+                &update_using_ttl(UPDATE_COUNTER_QUERY)?,
+                WithTtl::leading(values, BUMP_COUNTER_TTL),
+            )
+            .await?
+        }
+    };
+
+    Ok(res.is_applied())
+}
+
+async fn fetch_stored(
+    session: &Session,
+    target: BumpTarget,
+    entity_id: JsSafeBigInt,
+) -> Result<Option<BumpCounter>, DbError> {
+    session
+        .query_one::<BumpCounter>(SELECT_COUNTER_QUERY, (target, entity_id))
+        .await
+}
+
+/// Fetches the entity's counter rolled over to the day of `now`, a new
+/// counter is returned if the entity has not been bumped recently.
+pub async fn fetch(
+    session: &Session,
+    target: BumpTarget,
+    entity_id: JsSafeBigInt,
+    now: Timestamp,
+) -> Result<BumpCounter, DbError> {
+    Ok(match fetch_stored(session, target, entity_id).await? {
+        Some(counter) => counter.rolled_over(now),
+        None => BumpCounter::new(target, entity_id, now),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bumps::BUMP_COOLDOWN;
+    use crate::errors::ErrorCode;
+
+    // Friday 2022-07-01 00:00:00 UTC
+    const MIDNIGHT: i64 = 1656633600;
+    const HOUR: i64 = 60 * 60;
+
+    fn counter(at: i64) -> BumpCounter {
+        BumpCounter::new(BumpTarget::Guild, JsSafeBigInt(1), Timestamp::from(at))
+    }
+
+    #[test]
+    fn test_cooldown_and_daily_cap() {
+        let limits = BumpLimits::default();
+        let mut counter = counter(MIDNIGHT);
+
+        for i in 0..limits.daily_cap as i64 {
+            let at = Timestamp::from(MIDNIGHT + i * BUMP_COOLDOWN.num_seconds());
+            counter.record(&limits, at).unwrap();
+        }
+        assert_eq!(counter.count, limits.daily_cap);
+
+        let soon = Timestamp::from(MIDNIGHT + 10 * HOUR + 60);
+        assert_eq!(
+            counter.check(&limits, soon),
+            Err(BumpRejection::Cooldown(Duration::from_secs(
+                (2 * HOUR - 60) as u64
+            )))
+        );
+
+        let later = Timestamp::from(MIDNIGHT + 20 * HOUR);
+        assert_eq!(
+            counter.record(&limits, later),
+            Err(BumpRejection::DailyCapReached(Duration::from_secs(
+                (4 * HOUR) as u64
+            )))
+        );
+        assert_eq!(counter.remaining(&limits, later), 0);
+    }
+
+    #[test]
+    fn test_rolls_over_at_midnight() {
+        let limits = BumpLimits::default();
+        let mut counter = counter(MIDNIGHT);
+        counter
+            .record(&limits, Timestamp::from(MIDNIGHT + 23 * HOUR))
+            .unwrap();
+
+        let next_day = Timestamp::from(MIDNIGHT + 24 * HOUR + 60);
+        let rejection = counter.check(&limits, next_day).unwrap_err();
+        assert!(matches!(rejection, BumpRejection::Cooldown(_)));
+        assert_eq!(ApiError::from(rejection).code(), ErrorCode::RateLimited);
+
+        let later = Timestamp::from(MIDNIGHT + 25 * HOUR);
+        assert_eq!(counter.remaining(&limits, later), limits.daily_cap);
+        counter.record(&limits, later).unwrap();
+        assert_eq!(counter.count, 1);
+        assert_eq!(counter.day, Timestamp::from(MIDNIGHT + 24 * HOUR));
+    }
+}
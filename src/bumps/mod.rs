@@ -0,0 +1,8 @@
+mod cooldown;
+pub mod counter;
+
+pub use cooldown::{premium_multiplier, BumpCooldown, BumpLimits, BUMP_COOLDOWN, BUMP_DAILY_CAP};
+pub use counter::{
+    bump, BumpCounter, BumpError, BumpRejection, BumpTarget, BUMP_COUNTER_TABLE, BUMP_COUNTER_TTL,
+    BUMP_MAX_ATTEMPTS,
+};
@@ -0,0 +1,276 @@
+use arc_swap::ArcSwap;
+use std::cmp::Ordering;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex};
+
+use chrono::Duration;
+#[cfg(feature = "events")]
+use tokio::task::JoinHandle;
+
+#[cfg(feature = "events")]
+use crate::events::{Subscription, VoteCast};
+#[cfg(feature = "events")]
+use crate::types::JsSafeBigInt;
+use crate::types::Timestamp;
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct RankedEntry<T> {
+    pub id: T,
+    pub score: f64,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct LeaderboardSnapshot<T> {
+    pub entries: Vec<RankedEntry<T>>,
+    pub last_refresh: Timestamp,
+}
+
+impl<T> LeaderboardSnapshot<T> {
+    #[inline]
+    pub fn is_stale(&self, max_age: Duration) -> bool {
+        chrono::Utc::now() - *self.last_refresh > max_age
+    }
+}
+
+/// An in-memory top-N leaderboard updated incrementally.
+///
+/// Incremental updates only see the entries currently tracked, so an entry which
+/// falls out of the tracked window and later climbs back up is only picked up again
+/// once it beats the lowest tracked entry. A headroom of extra entries is tracked
+/// beyond `N` to soften this, and `refresh` should be called periodically with the
+/// full data set to reconcile any drift.
+///
+/// Readers get a lock-free snapshot which is swapped after every update.
+pub struct TopN<T> {
+    capacity: usize,
+    tracked: Mutex<Vec<RankedEntry<T>>>,
+    snapshot: ArcSwap<LeaderboardSnapshot<T>>,
+}
+
+impl<T: Clone + Eq + Hash> TopN<T> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            tracked: Mutex::new(vec![]),
+            snapshot: ArcSwap::from_pointee(LeaderboardSnapshot {
+                entries: vec![],
+                last_refresh: Timestamp::default(),
+            }),
+        }
+    }
+
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    #[inline]
+    fn tracked_capacity(&self) -> usize {
+        self.capacity * 2
+    }
+
+    /// The current top `N` entries ordered by highest score first.
+    pub fn snapshot(&self) -> Arc<LeaderboardSnapshot<T>> {
+        self.snapshot.load_full()
+    }
+
+    /// Sets the absolute score of an entry, e.g. a recalculated trending score.
+    pub fn set(&self, id: T, score: f64) {
+        self.modify(id, |_| score)
+    }
+
+    /// Adds the given delta to the entry's score, e.g. a newly cast vote.
+    ///
+    /// Entries which are not currently tracked are treated as starting from `0`.
+    pub fn increment(&self, id: T, delta: f64) {
+        self.modify(id, |current| current.unwrap_or_default() + delta)
+    }
+
+    /// Removes an entry entirely, e.g. when a listing is deleted or delisted.
+    pub fn remove(&self, id: &T) {
+        let mut tracked = self.tracked.lock().unwrap();
+        tracked.retain(|entry| &entry.id != id);
+        self.publish(&tracked, None);
+    }
+
+    /// Replaces all tracked entries with a full recalculation.
+    pub fn refresh(&self, entries: impl IntoIterator<Item = (T, f64)>) {
+        let mut all: Vec<RankedEntry<T>> = entries
+            .into_iter()
+            .map(|(id, score)| RankedEntry { id, score })
+            .collect();
+
+        sort_entries(&mut all);
+        all.truncate(self.tracked_capacity());
+
+        let mut tracked = self.tracked.lock().unwrap();
+        *tracked = all;
+        self.publish(&tracked, Some(Timestamp::default()));
+    }
+
+    fn modify(&self, id: T, f: impl FnOnce(Option<f64>) -> f64) {
+        let mut tracked = self.tracked.lock().unwrap();
+
+        match tracked.iter().position(|entry| entry.id == id) {
+            Some(idx) => {
+                let mut entry = tracked.remove(idx);
+                entry.score = f(Some(entry.score));
+                insert_sorted(&mut tracked, entry);
+            }
+            None => {
+                let entry = RankedEntry { id, score: f(None) };
+                insert_sorted(&mut tracked, entry);
+            }
+        }
+
+        tracked.truncate(self.tracked_capacity());
+        self.publish(&tracked, None);
+    }
+
+    fn publish(&self, tracked: &[RankedEntry<T>], refreshed_at: Option<Timestamp>) {
+        let last_refresh = refreshed_at.unwrap_or_else(|| self.snapshot.load().last_refresh);
+        let entries = tracked.iter().take(self.capacity).cloned().collect();
+
+        self.snapshot.store(Arc::new(LeaderboardSnapshot {
+            entries,
+            last_refresh,
+        }));
+    }
+}
+
+#[cfg(feature = "events")]
+impl TopN<JsSafeBigInt> {
+    /// Spawns a task counting every [VoteCast] on the subscription as one point
+    /// for the voted bot, until the bus is closed.
+    pub fn spawn_vote_consumer(
+        self: Arc<Self>,
+        mut votes: Subscription<VoteCast>,
+    ) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            while let Some(vote) = votes.recv().await {
+                match vote {
+                    Ok(vote) => self.increment(vote.bot_id, 1.0),
+                    Err(e) => tracing::warn!(error = %e, "skipping undecodable vote event"),
+                }
+            }
+        })
+    }
+}
+
+/// Orders by highest score first, with `NaN` scores always ranked last.
+#[inline]
+fn rank_order(a: f64, b: f64) -> Ordering {
+    match (a.is_nan(), b.is_nan()) {
+        (false, false) => b.total_cmp(&a),
+        (a_nan, b_nan) => a_nan.cmp(&b_nan),
+    }
+}
+
+#[inline]
+fn sort_entries<T>(entries: &mut [RankedEntry<T>]) {
+    entries.sort_by(|a, b| rank_order(a.score, b.score));
+}
+
+#[inline]
+fn insert_sorted<T>(entries: &mut Vec<RankedEntry<T>>, entry: RankedEntry<T>) {
+    let idx = entries.partition_point(|v| rank_order(v.score, entry.score) != Ordering::Greater);
+    entries.insert(idx, entry);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(board: &TopN<u64>) -> Vec<u64> {
+        board.snapshot().entries.iter().map(|v| v.id).collect()
+    }
+
+    #[test]
+    fn test_incremental_ordering() {
+        let board = TopN::new(3);
+
+        board.increment(1, 5.0);
+        board.increment(2, 10.0);
+        board.increment(3, 1.0);
+        board.increment(4, 7.0);
+        assert_eq!(ids(&board), vec![2, 4, 1]);
+
+        board.increment(3, 20.0);
+        assert_eq!(ids(&board), vec![3, 2, 4]);
+
+        board.set(2, 0.0);
+        assert_eq!(ids(&board), vec![3, 4, 1]);
+
+        board.remove(&3);
+        assert_eq!(ids(&board), vec![4, 1, 2]);
+    }
+
+    #[test]
+    fn test_refresh_reconciles() {
+        let board = TopN::new(2);
+        board.increment(1, 100.0);
+
+        board.refresh(vec![(5, 3.0), (6, 9.0), (7, 4.0)]);
+        assert_eq!(ids(&board), vec![6, 7]);
+        assert!(!board.snapshot().is_stale(Duration::minutes(1)));
+    }
+
+    #[test]
+    fn test_nan_ranks_last() {
+        let board = TopN::new(4);
+        board.set(1, f64::NAN);
+        board.set(2, 3.0);
+        board.set(3, -f64::NAN);
+        board.set(4, -1.0);
+        assert_eq!(ids(&board), vec![2, 4, 1, 3]);
+
+        board.refresh(vec![(1, f64::NAN), (2, 3.0), (3, -f64::NAN), (4, -1.0)]);
+        assert_eq!(&ids(&board)[..2], &[2, 4]);
+    }
+
+    #[cfg(feature = "events")]
+    #[test]
+    fn test_vote_consumer() {
+        use crate::events::{LocalBus, Publisher, Subscriber};
+
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+
+        rt.block_on(async {
+            let bus = LocalBus::default();
+            let board = Arc::new(TopN::new(2));
+            let votes = bus.subscribe::<VoteCast>().await.unwrap();
+            let handle = board.clone().spawn_vote_consumer(votes);
+
+            for bot_id in [1, 2, 2] {
+                let vote = VoteCast {
+                    bot_id: JsSafeBigInt(bot_id),
+                    user_id: JsSafeBigInt(10),
+                    cast_at: Timestamp::from(1_700_000_000),
+                };
+                bus.publish(&vote).await.unwrap();
+            }
+
+            drop(bus);
+            handle.await.unwrap();
+
+            let entries = &board.snapshot().entries;
+            assert_eq!(
+                entries[0],
+                RankedEntry {
+                    id: JsSafeBigInt(2),
+                    score: 2.0
+                }
+            );
+            assert_eq!(
+                entries[1],
+                RankedEntry {
+                    id: JsSafeBigInt(1),
+                    score: 1.0
+                }
+            );
+        });
+    }
+}
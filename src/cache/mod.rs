@@ -0,0 +1,11 @@
+pub mod leaderboard;
+#[cfg(feature = "redis-cache")]
+pub mod redis;
+pub mod single_flight;
+pub mod store;
+
+#[cfg(feature = "redis-cache")]
+pub use self::redis::RedisCache;
+pub use leaderboard::{LeaderboardSnapshot, RankedEntry, TopN};
+pub use single_flight::SingleFlight;
+pub use store::{Cache, CacheError, MemoryCache, DEFAULT_MEMORY_CACHE_CAPACITY};
@@ -0,0 +1,86 @@
+use std::fmt::Display;
+use std::marker::PhantomData;
+use std::time::Duration;
+
+use redis::aio::ConnectionManager;
+use redis::AsyncCommands;
+
+use crate::cache::{Cache, CacheError};
+use crate::codec::{CodecError, Versioned, VersionedDecode};
+
+impl From<redis::RedisError> for CacheError {
+    fn from(e: redis::RedisError) -> Self {
+        Self::Backend(e.to_string())
+    }
+}
+
+impl From<CodecError> for CacheError {
+    fn from(e: CodecError) -> Self {
+        Self::Serialization(e.to_string())
+    }
+}
+
+/// A cache shared between services, values are stored under `<prefix>:<key>`
+/// as versioned bincode so services on different releases can share entries.
+pub struct RedisCache<K, V> {
+    conn: ConnectionManager,
+    prefix: String,
+    _marker: PhantomData<fn(K) -> V>,
+}
+
+impl<K, V> RedisCache<K, V> {
+    pub fn new(conn: ConnectionManager, prefix: impl Into<String>) -> Self {
+        Self {
+            conn,
+            prefix: prefix.into(),
+            _marker: PhantomData,
+        }
+    }
+
+    fn key(&self, key: &K) -> String
+    where
+        K: Display,
+    {
+        format!("{}:{}", self.prefix, key)
+    }
+}
+
+#[async_trait::async_trait]
+impl<K, V> Cache<K, V> for RedisCache<K, V>
+where
+    K: Display + Send + Sync + 'static,
+    V: VersionedDecode + Send + Sync,
+{
+    async fn get(&self, key: &K) -> Result<Option<V>, CacheError> {
+        let mut conn = self.conn.clone();
+        let raw: Option<Vec<u8>> = conn.get(self.key(key)).await?;
+
+        match raw {
+            Some(v) => Ok(Some(Versioned::decode(&v)?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn set(&self, key: K, value: V, ttl: Duration) -> Result<(), CacheError> {
+        let mut conn = self.conn.clone();
+        let raw = Versioned::encode(&value)?;
+
+        // Redis rejects an expiry of zero.
+        let ttl = ttl.as_millis().max(1) as u64;
+        redis::cmd("SET")
+            .arg(self.key(&key))
+            .arg(raw)
+            .arg("PX")
+            .arg(ttl)
+            .query_async::<_, ()>(&mut conn)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn remove(&self, key: &K) -> Result<(), CacheError> {
+        let mut conn = self.conn.clone();
+        conn.del::<_, ()>(self.key(key)).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,259 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::cache::Cache;
+
+/// Ensures only one caller computes a missing cache entry at a time.
+///
+/// When an entry expires under load every request would otherwise miss the
+/// cache and recompute it at once. Callers for the same key instead wait for
+/// the first one to finish and then read its result from the cache.
+pub struct SingleFlight<K> {
+    in_flight: Mutex<HashMap<K, Arc<tokio::sync::Mutex<()>>>>,
+}
+
+impl<K> Default for SingleFlight<K> {
+    fn default() -> Self {
+        Self {
+            in_flight: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl<K> SingleFlight<K>
+where
+    K: Clone + Eq + Hash + Send + Sync + 'static,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the cached value or computes and caches it.
+    ///
+    /// Cache errors are logged and treated as a miss so an unavailable cache
+    /// does not fail requests.
+    pub async fn get_or_compute<V, E, F, Fut>(
+        &self,
+        cache: &dyn Cache<K, V>,
+        key: K,
+        ttl: Duration,
+        compute: F,
+    ) -> Result<V, E>
+    where
+        V: Clone + Send + Sync + 'static,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<V, E>>,
+    {
+        if let Some(value) = cached(cache, &key).await {
+            return Ok(value);
+        }
+
+        let lock = self
+            .in_flight
+            .lock()
+            .unwrap()
+            .entry(key.clone())
+            .or_default()
+            .clone();
+        let _guard = lock.lock().await;
+        // Declared after the lock guard so the entry is removed before the
+        // next waiter runs, even if this future is dropped or `compute` panics.
+        let _entry = InFlightEntry {
+            in_flight: &self.in_flight,
+            key: &key,
+            lock: &lock,
+        };
+
+        // Another caller may have filled the entry while we were waiting.
+        if let Some(value) = cached(cache, &key).await {
+            return Ok(value);
+        }
+
+        let result = compute().await;
+
+        if let Ok(value) = result.as_ref() {
+            if let Err(e) = cache.set(key.clone(), value.clone(), ttl).await {
+                tracing::warn!(error = %e, "failed to store computed cache entry");
+            }
+        }
+
+        result
+    }
+}
+
+/// Removes a key from `in_flight` when the caller holding its lock finishes.
+struct InFlightEntry<'a, K: Eq + Hash> {
+    in_flight: &'a Mutex<HashMap<K, Arc<tokio::sync::Mutex<()>>>>,
+    key: &'a K,
+    lock: &'a Arc<tokio::sync::Mutex<()>>,
+}
+
+impl<K: Eq + Hash> Drop for InFlightEntry<'_, K> {
+    fn drop(&mut self) {
+        let mut in_flight = match self.in_flight.lock() {
+            Ok(in_flight) => in_flight,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+
+        // A later caller may already have started a new flight for the key.
+        if in_flight
+            .get(self.key)
+            .is_some_and(|lock| Arc::ptr_eq(lock, self.lock))
+        {
+            in_flight.remove(self.key);
+        }
+    }
+}
+
+async fn cached<K, V>(cache: &dyn Cache<K, V>, key: &K) -> Option<V>
+where
+    K: Send + Sync + 'static,
+    V: Send + Sync + 'static,
+{
+    match cache.get(key).await {
+        Ok(value) => value,
+        Err(e) => {
+            tracing::warn!(error = %e, "cache lookup failed, treating as a miss");
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::MemoryCache;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    #[test]
+    fn test_concurrent_misses_compute_once() {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .unwrap();
+
+        rt.block_on(async {
+            let cache = MemoryCache::<String, u32>::new();
+            let flight = SingleFlight::new();
+            let calls = AtomicU32::new(0);
+
+            let load = || async {
+                calls.fetch_add(1, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                Ok::<_, ()>(42)
+            };
+
+            let ttl = Duration::from_secs(60);
+            let (a, b, c) = tokio::join!(
+                flight.get_or_compute(&cache, "top".to_string(), ttl, load),
+                flight.get_or_compute(&cache, "top".to_string(), ttl, load),
+                flight.get_or_compute(&cache, "top".to_string(), ttl, load),
+            );
+
+            assert_eq!((a, b, c), (Ok(42), Ok(42), Ok(42)));
+            assert_eq!(calls.load(Ordering::SeqCst), 1);
+        });
+    }
+
+    #[test]
+    fn test_errors_are_not_cached() {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+
+        rt.block_on(async {
+            let cache = MemoryCache::<u64, u32>::new();
+            let flight = SingleFlight::new();
+            let ttl = Duration::from_secs(60);
+
+            let res = flight
+                .get_or_compute(&cache, 1, ttl, || async { Err::<u32, _>("down") })
+                .await;
+            assert_eq!(res, Err("down"));
+            assert!(cache.is_empty());
+
+            let res = flight
+                .get_or_compute(&cache, 1, ttl, || async { Ok::<_, &str>(7) })
+                .await;
+            assert_eq!(res, Ok(7));
+            assert_eq!(cache.get(&1).await.unwrap(), Some(7));
+        });
+    }
+
+    #[test]
+    fn test_cancelled_flights_are_removed() {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .unwrap();
+
+        rt.block_on(async {
+            let cache = MemoryCache::<u64, u32>::new();
+            let flight = SingleFlight::new();
+            let ttl = Duration::from_secs(60);
+
+            let res = tokio::time::timeout(
+                Duration::from_millis(5),
+                flight.get_or_compute(&cache, 1, ttl, || async {
+                    std::future::pending::<Result<u32, ()>>().await
+                }),
+            )
+            .await;
+            assert!(res.is_err());
+            assert!(flight.in_flight.lock().unwrap().is_empty());
+
+            let res = flight
+                .get_or_compute(&cache, 1, ttl, || async { Ok::<_, ()>(7) })
+                .await;
+            assert_eq!(res, Ok(7));
+        });
+    }
+
+    #[test]
+    fn test_memory_cache_capacity() {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+
+        rt.block_on(async {
+            let cache = MemoryCache::new().with_capacity(2);
+            cache.set("a", 1, Duration::from_secs(60)).await.unwrap();
+            cache.set("b", 2, Duration::from_secs(30)).await.unwrap();
+            cache.set("a", 3, Duration::from_secs(60)).await.unwrap();
+            assert_eq!(cache.len(), 2);
+
+            cache.set("c", 4, Duration::from_secs(90)).await.unwrap();
+            assert_eq!(cache.len(), 2);
+            assert_eq!(cache.get(&"b").await.unwrap(), None);
+            assert_eq!(cache.get(&"a").await.unwrap(), Some(3));
+
+            cache.set("d", 5, Duration::ZERO).await.unwrap();
+            cache.set("e", 6, Duration::from_secs(60)).await.unwrap();
+            assert_eq!(cache.len(), 2);
+            assert_eq!(cache.get(&"c").await.unwrap(), Some(4));
+        });
+    }
+
+    #[test]
+    fn test_memory_cache_expiry() {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+
+        rt.block_on(async {
+            let cache = MemoryCache::new();
+            cache.set("a", 1, Duration::ZERO).await.unwrap();
+            cache.set("b", 2, Duration::from_secs(60)).await.unwrap();
+
+            assert_eq!(cache.get(&"a").await.unwrap(), None);
+            assert_eq!(cache.get(&"b").await.unwrap(), Some(2));
+
+            cache.set("c", 3, Duration::ZERO).await.unwrap();
+            cache.prune();
+            assert_eq!(cache.len(), 1);
+        });
+    }
+}
@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::hash::Hash;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+/// The number of entries a [MemoryCache] holds unless configured otherwise.
+pub const DEFAULT_MEMORY_CACHE_CAPACITY: usize = 10_000;
+
+#[derive(Debug)]
+pub enum CacheError {
+    /// The cache backend could not be reached.
+    Backend(String),
+    /// The cached value could not be encoded or decoded.
+    Serialization(String),
+}
+
+impl Display for CacheError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Backend(e) => write!(f, "cache backend error: {}", e),
+            Self::Serialization(e) => write!(f, "cannot (de)serialize cached value: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for CacheError {}
+
+/// A key value cache where every entry expires after its TTL.
+#[async_trait::async_trait]
+pub trait Cache<K, V>: Send + Sync
+where
+    K: Send + Sync + 'static,
+    V: Send + Sync + 'static,
+{
+    async fn get(&self, key: &K) -> Result<Option<V>, CacheError>;
+
+    async fn set(&self, key: K, value: V, ttl: Duration) -> Result<(), CacheError>;
+
+    async fn remove(&self, key: &K) -> Result<(), CacheError>;
+}
+
+/// An in-process cache, expired entries are dropped when read or by `prune`.
+///
+/// Entries whose TTL is too large to represent, such as `Duration::MAX`,
+/// never expire.
+///
+/// Once the cache is full, inserting a new key first drops the expired
+/// entries and then the entry closest to expiring.
+pub struct MemoryCache<K, V> {
+    entries: Mutex<HashMap<K, (V, Option<Instant>)>>,
+    capacity: usize,
+}
+
+impl<K, V> Default for MemoryCache<K, V> {
+    fn default() -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+            capacity: DEFAULT_MEMORY_CACHE_CAPACITY,
+        }
+    }
+}
+
+fn is_live(expires_at: Option<Instant>, now: Instant) -> bool {
+    expires_at.is_none_or(|v| v > now)
+}
+
+impl<K: Eq + Hash, V> MemoryCache<K, V> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity.max(1);
+        self
+    }
+
+    /// Removes all expired entries.
+    pub fn prune(&self) {
+        let now = Instant::now();
+        self.entries
+            .lock()
+            .unwrap()
+            .retain(|_, (_, expires_at)| is_live(*expires_at, now));
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[async_trait::async_trait]
+impl<K, V> Cache<K, V> for MemoryCache<K, V>
+where
+    K: Eq + Hash + Send + Sync + 'static,
+    V: Clone + Send + Sync + 'static,
+{
+    async fn get(&self, key: &K) -> Result<Option<V>, CacheError> {
+        let mut entries = self.entries.lock().unwrap();
+
+        match entries.get(key) {
+            Some((value, expires_at)) if is_live(*expires_at, Instant::now()) => {
+                Ok(Some(value.clone()))
+            }
+            Some(_) => {
+                entries.remove(key);
+                Ok(None)
+            }
+            None => Ok(None),
+        }
+    }
+
+    async fn set(&self, key: K, value: V, ttl: Duration) -> Result<(), CacheError> {
+        let now = Instant::now();
+        let mut entries = self.entries.lock().unwrap();
+
+        if entries.len() >= self.capacity && !entries.contains_key(&key) {
+            entries.retain(|_, (_, expires_at)| is_live(*expires_at, now));
+        }
+        if entries.len() >= self.capacity && !entries.contains_key(&key) {
+            let soonest = entries.values().filter_map(|(_, expires_at)| *expires_at).min();
+            match soonest {
+                Some(soonest) => entries.retain(|_, (_, expires_at)| is_live(*expires_at, soonest)),
+                // Every entry lives forever, so any of them can make room.
+                None => entries.clear(),
+            }
+        }
+
+        entries.insert(key, (value, now.checked_add(ttl)));
+        Ok(())
+    }
+
+    async fn remove(&self, key: &K) -> Result<(), CacheError> {
+        self.entries.lock().unwrap().remove(key);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unrepresentable_ttl_never_expires() {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+
+        rt.block_on(async {
+            let cache = MemoryCache::<&str, u32>::new().with_capacity(2);
+            cache.set("forever", 1, Duration::MAX).await.unwrap();
+            cache.set("short", 2, Duration::ZERO).await.unwrap();
+
+            assert_eq!(cache.get(&"forever").await.unwrap(), Some(1));
+            assert_eq!(cache.get(&"short").await.unwrap(), None);
+
+            // Entries with an expiry are evicted before those without one.
+            cache.set("minute", 3, Duration::from_secs(60)).await.unwrap();
+            cache.set("hour", 4, Duration::from_secs(3600)).await.unwrap();
+            assert_eq!(cache.get(&"forever").await.unwrap(), Some(1));
+            assert_eq!(cache.get(&"minute").await.unwrap(), None);
+            assert_eq!(cache.get(&"hour").await.unwrap(), Some(4));
+        })
+    }
+}
@@ -0,0 +1,3 @@
+mod versioned;
+
+pub use versioned::{CodecError, Migrations, Versioned, VersionedDecode, MAX_PAYLOAD_SIZE};
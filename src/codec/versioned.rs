@@ -0,0 +1,258 @@
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+
+use bincode::config::standard;
+use bincode::error::{DecodeError, EncodeError};
+use bincode::{Decode, Encode};
+
+/// The most bytes a payload may claim while decoding, so a forged length
+/// prefix fails instead of allocating whatever it asks for.
+pub const MAX_PAYLOAD_SIZE: usize = 16 * 1024 * 1024;
+
+type Migration<T> = Box<dyn Fn(&[u8]) -> Result<T, CodecError> + Send + Sync>;
+
+#[derive(Debug)]
+pub enum CodecError {
+    Encode(EncodeError),
+    Decode(DecodeError),
+    /// The payload is shorter than the version header.
+    Truncated,
+    /// The payload was written by a newer version of the type.
+    FutureVersion(u16),
+    /// The payload was written by an older version with no registered migration.
+    NoMigration(u16),
+    /// The payload continues after the value, it was likely written as another type.
+    TrailingBytes(usize),
+}
+
+impl Display for CodecError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Encode(e) => write!(f, "cannot encode value: {}", e),
+            Self::Decode(e) => write!(f, "cannot decode value: {}", e),
+            Self::Truncated => write!(f, "payload is missing its version"),
+            Self::FutureVersion(v) => write!(f, "payload version {} is newer than supported", v),
+            Self::NoMigration(v) => write!(f, "no migration from payload version {}", v),
+            Self::TrailingBytes(n) => write!(f, "{} unexpected bytes after the value", n),
+        }
+    }
+}
+
+impl std::error::Error for CodecError {}
+
+/// The migrations from older layouts of a type to its current layout.
+///
+/// Each migration decodes the old layout and converts it straight to the
+/// current one, so when a type changes again the existing migrations are
+/// updated to produce the new layout rather than chained.
+pub struct Migrations<T> {
+    steps: BTreeMap<u16, Migration<T>>,
+}
+
+impl<T> Default for Migrations<T> {
+    fn default() -> Self {
+        Self {
+            steps: BTreeMap::new(),
+        }
+    }
+}
+
+impl<T: 'static> Migrations<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers how to read payloads written with the given version.
+    pub fn register<Old: Decode>(
+        mut self,
+        version: u16,
+        migrate: impl Fn(Old) -> T + Send + Sync + 'static,
+    ) -> Self {
+        let step = move |payload: &[u8]| decode_exact::<Old>(payload).map(&migrate);
+
+        self.steps.insert(version, Box::new(step));
+        self
+    }
+
+    fn run(&self, version: u16, payload: &[u8]) -> Result<T, CodecError> {
+        let step = self
+            .steps
+            .get(&version)
+            .ok_or(CodecError::NoMigration(version))?;
+        step(payload)
+    }
+}
+
+/// Decodes the whole payload, rejecting any bytes left over after the value.
+fn decode_exact<T: Decode>(payload: &[u8]) -> Result<T, CodecError> {
+    let config = standard().with_limit::<MAX_PAYLOAD_SIZE>();
+    let (value, read) = bincode::decode_from_slice(payload, config).map_err(CodecError::Decode)?;
+    match payload.len() - read {
+        0 => Ok(value),
+        trailing => Err(CodecError::TrailingBytes(trailing)),
+    }
+}
+
+/// A type which is encoded with its schema version so older payloads can
+/// still be read after the type changes.
+///
+/// Bump `VERSION` whenever the bincode layout changes and register a
+/// migration from the previous layout, the previous layout is usually kept
+/// as a private copy of the old struct.
+pub trait VersionedDecode: Encode + Decode + Sized + 'static {
+    const VERSION: u16;
+
+    fn migrations() -> Migrations<Self> {
+        Migrations::new()
+    }
+}
+
+/// Encodes and decodes values prefixed with their schema version.
+///
+/// The version is written as two little endian bytes before the bincode payload.
+pub struct Versioned<T>(pub T);
+
+impl<T: VersionedDecode> Versioned<T> {
+    pub fn encode(value: &T) -> Result<Vec<u8>, CodecError> {
+        let mut buf = T::VERSION.to_le_bytes().to_vec();
+        let payload = bincode::encode_to_vec(value, standard()).map_err(CodecError::Encode)?;
+        buf.extend_from_slice(&payload);
+        Ok(buf)
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<T, CodecError> {
+        let (version, payload) = match bytes {
+            [a, b, payload @ ..] => (u16::from_le_bytes([*a, *b]), payload),
+            _ => return Err(CodecError::Truncated),
+        };
+
+        match version.cmp(&T::VERSION) {
+            std::cmp::Ordering::Equal => decode_exact(payload),
+            std::cmp::Ordering::Less => T::migrations().run(version, payload),
+            std::cmp::Ordering::Greater => Err(CodecError::FutureVersion(version)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Encode, Decode)]
+    struct CachedTagV1 {
+        name: String,
+    }
+
+    #[derive(Debug, PartialEq, Encode, Decode)]
+    struct CachedTag {
+        name: String,
+        emoji: Option<String>,
+    }
+
+    impl VersionedDecode for CachedTag {
+        const VERSION: u16 = 2;
+
+        fn migrations() -> Migrations<Self> {
+            Migrations::new().register(1, |old: CachedTagV1| CachedTag {
+                name: old.name,
+                emoji: None,
+            })
+        }
+    }
+
+    fn encode_v1(name: &str) -> Vec<u8> {
+        let mut buf = 1u16.to_le_bytes().to_vec();
+        let old = CachedTagV1 {
+            name: name.to_string(),
+        };
+        buf.extend(bincode::encode_to_vec(old, standard()).unwrap());
+        buf
+    }
+
+    #[test]
+    fn test_current_version_round_trip() {
+        let tag = CachedTag {
+            name: "music".to_string(),
+            emoji: Some("🎵".to_string()),
+        };
+
+        let bytes = Versioned::encode(&tag).unwrap();
+        assert_eq!(&bytes[..2], &[2, 0]);
+        assert_eq!(Versioned::<CachedTag>::decode(&bytes).unwrap(), tag);
+    }
+
+    #[test]
+    fn test_old_version_migrated() {
+        let tag = Versioned::<CachedTag>::decode(&encode_v1("music")).unwrap();
+        assert_eq!(
+            tag,
+            CachedTag {
+                name: "music".to_string(),
+                emoji: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_unknown_versions() {
+        let mut bytes = encode_v1("music");
+        bytes[0] = 0;
+        assert!(matches!(
+            Versioned::<CachedTag>::decode(&bytes),
+            Err(CodecError::NoMigration(0))
+        ));
+
+        bytes[0] = 3;
+        assert!(matches!(
+            Versioned::<CachedTag>::decode(&bytes),
+            Err(CodecError::FutureVersion(3))
+        ));
+        assert!(matches!(
+            Versioned::<CachedTag>::decode(&[1]),
+            Err(CodecError::Truncated)
+        ));
+    }
+
+    #[test]
+    fn test_rejects_trailing_bytes() {
+        let tag = CachedTag {
+            name: "music".to_string(),
+            emoji: None,
+        };
+        let mut bytes = Versioned::encode(&tag).unwrap();
+        bytes.push(0);
+        assert!(matches!(
+            Versioned::<CachedTag>::decode(&bytes),
+            Err(CodecError::TrailingBytes(1))
+        ));
+
+        let mut bytes = encode_v1("music");
+        bytes.extend([0, 0]);
+        assert!(matches!(
+            Versioned::<CachedTag>::decode(&bytes),
+            Err(CodecError::TrailingBytes(2))
+        ));
+    }
+
+    #[test]
+    fn test_rejects_oversized_length_prefix() {
+        use crate::types::{JsSafeBigInt, Set};
+
+        // Version 1 followed by a varint claiming `u64::MAX` elements.
+        let mut bytes = vec![1, 0, 253];
+        bytes.extend(u64::MAX.to_le_bytes());
+        assert!(matches!(
+            Versioned::<Set<JsSafeBigInt, 5>>::decode(&bytes),
+            Err(CodecError::Decode(DecodeError::LimitExceeded))
+        ));
+
+        let mut bytes = encode_v1("music");
+        bytes.truncate(2);
+        bytes.push(253);
+        bytes.extend(u64::MAX.to_le_bytes());
+        assert!(matches!(
+            Versioned::<CachedTag>::decode(&bytes),
+            Err(CodecError::Decode(DecodeError::LimitExceeded))
+        ));
+    }
+}
@@ -0,0 +1,7 @@
+pub mod onboarding;
+pub mod screening;
+
+pub use onboarding::{OnboardingError, OnboardingState, PartnerOnboarding};
+pub use screening::{
+    AllowAll, DenyReason, ScreeningDecision, ScreeningError, ScreeningProvider, ScreeningSubject,
+};
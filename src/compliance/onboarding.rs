@@ -0,0 +1,195 @@
+use std::fmt::{Display, Formatter};
+
+use poem_openapi::{Enum, Object};
+
+use crate::compliance::{ScreeningDecision, ScreeningError, ScreeningProvider, ScreeningSubject};
+use crate::types::Timestamp;
+
+#[derive(
+    Enum,
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    Hash,
+    strum::AsRefStr,
+    strum::EnumString,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[oai(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum OnboardingState {
+    #[default]
+    Pending,
+    InReview,
+    Approved,
+    Rejected,
+}
+
+cql_text_enum!(OnboardingState);
+
+#[derive(Debug)]
+pub enum OnboardingError {
+    /// The application has already been screened, it is only screened once
+    /// while pending.
+    NotPending(OnboardingState),
+    Screening(ScreeningError),
+}
+
+impl Display for OnboardingError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotPending(state) => {
+                write!(
+                    f,
+                    "cannot screen an application which is {}",
+                    state.as_ref()
+                )
+            }
+            Self::Screening(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for OnboardingError {}
+
+impl From<ScreeningError> for OnboardingError {
+    fn from(e: ScreeningError) -> Self {
+        Self::Screening(e)
+    }
+}
+
+/// A user's application to the partner program, payouts are only enabled
+/// once the application is approved.
+#[derive(Object, Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct PartnerOnboarding {
+    pub subject: ScreeningSubject,
+    pub state: OnboardingState,
+    pub decision: Option<ScreeningDecision>,
+    pub updated_at: Timestamp,
+}
+
+impl PartnerOnboarding {
+    pub fn new(subject: ScreeningSubject) -> Self {
+        Self {
+            subject,
+            state: OnboardingState::Pending,
+            decision: None,
+            updated_at: Timestamp::default(),
+        }
+    }
+
+    #[inline]
+    pub fn payouts_enabled(&self) -> bool {
+        self.state == OnboardingState::Approved
+    }
+
+    /// Screens the subject and moves the application to the resulting state.
+    ///
+    /// Only pending applications can be screened, so a rejected or approved
+    /// application can't be moved to another state by screening it again.
+    pub async fn screen(
+        &mut self,
+        provider: &dyn ScreeningProvider,
+    ) -> Result<OnboardingState, OnboardingError> {
+        if self.state != OnboardingState::Pending {
+            return Err(OnboardingError::NotPending(self.state));
+        }
+
+        let decision = provider.screen(&self.subject).await?;
+
+        self.state = match &decision {
+            ScreeningDecision::Allow(_) => OnboardingState::Approved,
+            ScreeningDecision::Review(_) => OnboardingState::InReview,
+            ScreeningDecision::Deny(_) => OnboardingState::Rejected,
+        };
+        self.decision = Some(decision);
+        self.updated_at = Timestamp::default();
+
+        Ok(self.state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compliance::screening::Denied;
+    use crate::compliance::{AllowAll, DenyReason};
+    use crate::types::JsSafeBigInt;
+
+    struct DenyCountry(&'static str);
+
+    #[async_trait::async_trait]
+    impl ScreeningProvider for DenyCountry {
+        async fn screen(
+            &self,
+            subject: &ScreeningSubject,
+        ) -> Result<ScreeningDecision, ScreeningError> {
+            if subject.country == self.0 {
+                return Ok(ScreeningDecision::Deny(Denied {
+                    reference: Some("ref-1".to_string()),
+                    reason: DenyReason::RestrictedCountry,
+                }));
+            }
+
+            AllowAll.screen(subject).await
+        }
+    }
+
+    fn subject(country: &str) -> ScreeningSubject {
+        ScreeningSubject {
+            user_id: JsSafeBigInt(1),
+            legal_name: "Sam Example".to_string(),
+            country: country.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_screening_transitions() {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+
+        rt.block_on(async {
+            let provider = DenyCountry("XX");
+
+            let mut allowed = PartnerOnboarding::new(subject("GB"));
+            assert_eq!(
+                allowed.screen(&provider).await.unwrap(),
+                OnboardingState::Approved
+            );
+            assert!(allowed.payouts_enabled());
+
+            let mut denied = PartnerOnboarding::new(subject("XX"));
+            assert_eq!(
+                denied.screen(&provider).await.unwrap(),
+                OnboardingState::Rejected
+            );
+            assert!(denied.decision.as_ref().unwrap().is_denied());
+
+            assert!(matches!(
+                denied.screen(&AllowAll).await,
+                Err(OnboardingError::NotPending(OnboardingState::Rejected))
+            ));
+            assert_eq!(denied.state, OnboardingState::Rejected);
+            assert!(!denied.payouts_enabled());
+        });
+    }
+
+    #[test]
+    fn test_decision_wire_format() {
+        let decision = ScreeningDecision::Deny(Denied {
+            reference: None,
+            reason: DenyReason::SanctionsMatch,
+        });
+
+        assert_eq!(
+            serde_json::to_value(&decision).unwrap(),
+            serde_json::json!({"decision": "deny", "reference": null, "reason": "sanctions_match"})
+        );
+    }
+}
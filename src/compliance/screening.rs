@@ -0,0 +1,110 @@
+use std::fmt::{Display, Formatter};
+
+use poem_openapi::{Enum, Object, Union};
+
+use crate::types::JsSafeBigInt;
+
+/// The details of a person being screened before they can receive payouts.
+#[derive(Object, Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ScreeningSubject {
+    pub user_id: JsSafeBigInt,
+    pub legal_name: String,
+    /// The ISO 3166-1 alpha-2 country code of the subject's residence.
+    pub country: String,
+}
+
+#[derive(
+    Enum,
+    Copy,
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    strum::AsRefStr,
+    strum::EnumString,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[oai(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum DenyReason {
+    SanctionsMatch,
+    RestrictedCountry,
+    Other,
+}
+
+cql_text_enum!(DenyReason);
+
+#[derive(Object, Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Allowed {
+    /// The provider's reference for the check, kept for audits.
+    pub reference: Option<String>,
+}
+
+#[derive(Object, Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct NeedsReview {
+    pub reference: Option<String>,
+    pub note: String,
+}
+
+#[derive(Object, Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Denied {
+    pub reference: Option<String>,
+    pub reason: DenyReason,
+}
+
+#[derive(Union, Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[oai(discriminator_name = "decision", one_of)]
+#[serde(tag = "decision", rename_all = "snake_case")]
+pub enum ScreeningDecision {
+    #[oai(mapping = "allow")]
+    Allow(Allowed),
+    /// The provider could not decide and a member of staff must review the subject.
+    #[oai(mapping = "review")]
+    Review(NeedsReview),
+    #[oai(mapping = "deny")]
+    Deny(Denied),
+}
+
+impl ScreeningDecision {
+    #[inline]
+    pub fn is_allowed(&self) -> bool {
+        matches!(self, Self::Allow(_))
+    }
+
+    #[inline]
+    pub fn is_denied(&self) -> bool {
+        matches!(self, Self::Deny(_))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScreeningError(pub String);
+
+impl Display for ScreeningError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "screening failed: {}", self.0)
+    }
+}
+
+impl std::error::Error for ScreeningError {}
+
+/// Something which can screen a subject against sanctions lists.
+#[async_trait::async_trait]
+pub trait ScreeningProvider: Send + Sync {
+    async fn screen(&self, subject: &ScreeningSubject)
+        -> Result<ScreeningDecision, ScreeningError>;
+}
+
+/// A provider which allows everyone, used where no provider is configured.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct AllowAll;
+
+#[async_trait::async_trait]
+impl ScreeningProvider for AllowAll {
+    async fn screen(&self, _: &ScreeningSubject) -> Result<ScreeningDecision, ScreeningError> {
+        Ok(ScreeningDecision::Allow(Allowed { reference: None }))
+    }
+}
@@ -0,0 +1,84 @@
+use std::net::SocketAddr;
+
+use crate::config::{DatabaseNodes, RedisUrl};
+use crate::types::Secret;
+
+env_config! {
+    /// The settings shared by every service, services embed it in their own
+    /// configuration with `nested()`.
+    #[derive(Clone, Debug)]
+    pub struct CommonConfig {
+        pub scylla_nodes: DatabaseNodes = required("SCYLLA_NODES"),
+        pub scylla_keyspace: String = default("SCYLLA_KEYSPACE", "discordlist".to_string()),
+        pub scylla_username: Option<String> = optional("SCYLLA_USERNAME"),
+        pub scylla_password: Option<Secret<String>> = optional("SCYLLA_PASSWORD"),
+        pub redis_url: Option<RedisUrl> = optional("REDIS_URL"),
+        pub bind_addr: SocketAddr = default("BIND_ADDR", SocketAddr::from(([0, 0, 0, 0], 8080))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::config::{ConfigProblem, EnvConfig};
+
+    env_config! {
+        #[derive(Debug)]
+        struct WorkerConfig {
+            common: CommonConfig = nested(),
+            token: Secret<String> = required("WORKER_TOKEN"),
+            concurrency: usize = default("WORKER_CONCURRENCY", 4),
+        }
+    }
+
+    fn env(vars: &[(&str, &str)]) -> HashMap<String, String> {
+        vars.iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_load_extension_config() {
+        let env = env(&[
+            ("SCYLLA_NODES", "10.0.0.1:9042,10.0.0.2:9042"),
+            ("REDIS_URL", "redis://cache:6379"),
+            ("SCYLLA_PASSWORD", "hunter2"),
+            ("WORKER_TOKEN", "secret"),
+        ]);
+        let config = WorkerConfig::from_source(&env).unwrap();
+
+        assert_eq!(config.common.scylla_nodes.len(), 2);
+        assert_eq!(config.common.scylla_keyspace, "discordlist");
+        assert!(config.common.redis_url.is_some());
+        assert_eq!(config.common.bind_addr.port(), 8080);
+        assert_eq!(config.token.expose(), "secret");
+        assert!(!format!("{:?}", config).contains("hunter2"));
+        assert_eq!(config.concurrency, 4);
+    }
+
+    #[test]
+    fn test_reports_every_problem() {
+        let env = env(&[("REDIS_URL", "http://cache"), ("WORKER_CONCURRENCY", "-1")]);
+        let err = WorkerConfig::from_source(&env).unwrap_err();
+
+        let names = err
+            .problems
+            .iter()
+            .map(|v| match v {
+                ConfigProblem::Missing(name) => *name,
+                ConfigProblem::Invalid { name, .. } => *name,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            [
+                "SCYLLA_NODES",
+                "REDIS_URL",
+                "WORKER_TOKEN",
+                "WORKER_CONCURRENCY"
+            ]
+        );
+    }
+}
@@ -0,0 +1,270 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+use url::Url;
+
+/// Where configuration values are read from, the process environment
+/// outside of tests.
+pub trait EnvSource {
+    fn var(&self, name: &str) -> Option<String>;
+}
+
+/// Reads the process environment.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct ProcessEnv;
+
+impl EnvSource for ProcessEnv {
+    fn var(&self, name: &str) -> Option<String> {
+        std::env::var(name).ok()
+    }
+}
+
+impl EnvSource for HashMap<String, String> {
+    fn var(&self, name: &str) -> Option<String> {
+        self.get(name).cloned()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigProblem {
+    Missing(&'static str),
+    /// The variable is set but cannot be parsed, the reason never contains
+    /// the value itself as it may be a secret.
+    Invalid {
+        name: &'static str,
+        reason: String,
+    },
+}
+
+impl Display for ConfigProblem {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Missing(name) => write!(f, "{} is required but not set", name),
+            Self::Invalid { name, reason } => write!(f, "{} is invalid: {}", name, reason),
+        }
+    }
+}
+
+/// Every problem found while loading a configuration, so they can all be
+/// fixed at once rather than one restart at a time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigError {
+    pub problems: Vec<ConfigProblem>,
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid configuration:")?;
+        for problem in self.problems.iter() {
+            write!(f, "\n  - {}", problem)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// A type which can be parsed from the value of an environment variable.
+pub trait FromEnv: Sized {
+    fn from_env(raw: &str) -> Result<Self, String>;
+}
+
+macro_rules! from_env_via_from_str {
+    ($($ty:ty),+) => {
+        $(
+            impl FromEnv for $ty {
+                fn from_env(raw: &str) -> Result<Self, String> {
+                    <$ty>::from_str(raw.trim()).map_err(|e| e.to_string())
+                }
+            }
+        )+
+    };
+}
+
+from_env_via_from_str!(u16, u32, u64, usize, i32, i64, f64, SocketAddr, Url, PathBuf);
+
+impl FromEnv for String {
+    fn from_env(raw: &str) -> Result<Self, String> {
+        Ok(raw.to_string())
+    }
+}
+
+impl FromEnv for bool {
+    fn from_env(raw: &str) -> Result<Self, String> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "1" | "true" | "yes" | "on" => Ok(true),
+            "0" | "false" | "no" | "off" => Ok(false),
+            _ => Err("expected true or false".to_string()),
+        }
+    }
+}
+
+/// Whole seconds, or a number followed by `ms`, `s`, `m` or `h`.
+impl FromEnv for Duration {
+    fn from_env(raw: &str) -> Result<Self, String> {
+        let raw = raw.trim();
+        let split = raw.find(|c: char| !c.is_ascii_digit()).unwrap_or(raw.len());
+        let (value, unit) = raw.split_at(split);
+        let value = value
+            .parse::<u64>()
+            .map_err(|_| "expected a duration such as 30s".to_string())?;
+
+        match unit {
+            "ms" => Ok(Duration::from_millis(value)),
+            "" | "s" => Ok(Duration::from_secs(value)),
+            "m" => Ok(Duration::from_secs(value.saturating_mul(60))),
+            "h" => Ok(Duration::from_secs(value.saturating_mul(60 * 60))),
+            _ => Err(format!("unknown duration unit {:?}", unit)),
+        }
+    }
+}
+
+/// A comma separated list, empty entries are skipped.
+impl<T: FromEnv> FromEnv for Vec<T> {
+    fn from_env(raw: &str) -> Result<Self, String> {
+        raw.split(',')
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .enumerate()
+            .map(|(i, v)| T::from_env(v).map_err(|e| format!("entry {}: {}", i + 1, e)))
+            .collect()
+    }
+}
+
+/// Reads variables while collecting every problem instead of stopping at
+/// the first one.
+///
+/// Empty variables are treated as unset.
+pub struct EnvReader<'a> {
+    source: &'a dyn EnvSource,
+    problems: Vec<ConfigProblem>,
+}
+
+impl<'a> EnvReader<'a> {
+    pub fn new(source: &'a dyn EnvSource) -> Self {
+        Self {
+            source,
+            problems: Vec::new(),
+        }
+    }
+
+    fn read<T: FromEnv>(&mut self, name: &'static str) -> Option<Option<T>> {
+        let raw = match self.source.var(name) {
+            Some(v) if !v.trim().is_empty() => v,
+            _ => return Some(None),
+        };
+
+        match T::from_env(&raw) {
+            Ok(v) => Some(Some(v)),
+            Err(reason) => {
+                self.problems.push(ConfigProblem::Invalid { name, reason });
+                None
+            }
+        }
+    }
+
+    /// `None` if the variable is missing or invalid, the problem is recorded.
+    pub fn required<T: FromEnv>(&mut self, name: &'static str) -> Option<T> {
+        let value = self.read(name)?;
+        if value.is_none() {
+            self.problems.push(ConfigProblem::Missing(name));
+        }
+
+        value
+    }
+
+    /// `None` if the variable is invalid, a missing variable is `Some(None)`.
+    pub fn optional<T: FromEnv>(&mut self, name: &'static str) -> Option<Option<T>> {
+        self.read(name)
+    }
+
+    pub fn default<T: FromEnv>(&mut self, name: &'static str, default: T) -> Option<T> {
+        self.read(name).map(|v| v.unwrap_or(default))
+    }
+
+    /// Reads a nested configuration, such as the common settings embedded in
+    /// a service's own configuration.
+    pub fn nested<T: EnvConfig>(&mut self) -> Option<T> {
+        T::read(self)
+    }
+
+    pub fn finish(self) -> Result<(), ConfigError> {
+        if self.problems.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigError {
+                problems: self.problems,
+            })
+        }
+    }
+}
+
+/// A configuration loaded from environment variables, usually declared
+/// with [env_config!](crate::env_config).
+pub trait EnvConfig: Sized {
+    /// Reads every field, returning `None` if any of them had a problem.
+    fn read(reader: &mut EnvReader<'_>) -> Option<Self>;
+
+    fn from_source(source: &dyn EnvSource) -> Result<Self, ConfigError> {
+        let mut reader = EnvReader::new(source);
+        let config = Self::read(&mut reader);
+        reader.finish()?;
+
+        Ok(config.expect("configuration read without problems"))
+    }
+
+    /// Loads the configuration from the process environment, meant to be
+    /// called once at startup.
+    fn from_env() -> Result<Self, ConfigError> {
+        Self::from_source(&ProcessEnv)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env(vars: &[(&str, &str)]) -> HashMap<String, String> {
+        vars.iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_values() {
+        assert_eq!(bool::from_env(" Yes "), Ok(true));
+        assert!(bool::from_env("maybe").is_err());
+        assert_eq!(Duration::from_env("250ms"), Ok(Duration::from_millis(250)));
+        assert_eq!(Duration::from_env("90"), Ok(Duration::from_secs(90)));
+        assert_eq!(Duration::from_env("2h"), Ok(Duration::from_secs(7200)));
+        assert!(Duration::from_env("2d").is_err());
+        assert_eq!(Vec::<u16>::from_env("1, 2,,3"), Ok(vec![1, 2, 3]));
+        assert!(Vec::<u16>::from_env("1,x")
+            .unwrap_err()
+            .starts_with("entry 2"));
+    }
+
+    #[test]
+    fn test_reader_collects_problems() {
+        let env = env(&[("PORT", "http"), ("NAME", ""), ("WORKERS", "4")]);
+        let mut reader = EnvReader::new(&env);
+
+        assert_eq!(reader.required::<String>("NAME"), None);
+        assert_eq!(reader.default::<u16>("PORT", 8080), None);
+        assert_eq!(reader.default::<u16>("WORKERS", 1), Some(4));
+        assert_eq!(reader.optional::<u16>("THREADS"), Some(None));
+
+        let err = reader.finish().unwrap_err();
+        assert_eq!(err.problems.len(), 2);
+        assert_eq!(err.problems[0], ConfigProblem::Missing("NAME"));
+        assert_eq!(
+            err.to_string(),
+            "invalid configuration:\n  - NAME is required but not set\n  \
+            - PORT is invalid: invalid digit found in string"
+        );
+    }
+}
@@ -0,0 +1,7 @@
+mod common;
+mod env;
+mod values;
+
+pub use common::CommonConfig;
+pub use env::{ConfigError, ConfigProblem, EnvConfig, EnvReader, EnvSource, FromEnv, ProcessEnv};
+pub use values::{DatabaseNodes, RedisUrl};
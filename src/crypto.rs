@@ -0,0 +1,71 @@
+use std::fmt::{Display, Formatter};
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::types::Secret;
+
+pub type HmacSha256 = Hmac<Sha256>;
+
+/// A signer was given an empty secret, which anyone could forge signatures with.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct EmptySecret;
+
+impl Display for EmptySecret {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "signing secret must not be empty")
+    }
+}
+
+impl std::error::Error for EmptySecret {}
+
+/// Copies a signing secret, rejecting empty ones.
+pub fn signing_key(secret: impl AsRef<[u8]>) -> Result<Secret<Vec<u8>>, EmptySecret> {
+    match secret.as_ref() {
+        [] => Err(EmptySecret),
+        secret => Ok(Secret::new(secret.to_vec())),
+    }
+}
+
+/// The HMAC-SHA256 of the concatenated `parts`.
+///
+/// Call `finalize` for the signature or `verify_slice` to compare one in
+/// constant time.
+pub fn hmac_sha256(key: &[u8], parts: &[&[u8]]) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts keys of any length");
+    for part in parts {
+        mac.update(part);
+    }
+    mac
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hmac_sha256() {
+        // Test case 2 of RFC 4231.
+        let signature = hmac_sha256(b"Jefe", &[b"what do ya want ", b"for nothing?"]);
+        assert_eq!(
+            hex::encode(signature.finalize().into_bytes()),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+
+        let expected = hmac_sha256(b"Jefe", &[b"what do ya want for nothing?"])
+            .finalize()
+            .into_bytes();
+        assert!(hmac_sha256(b"Jefe", &[b"what do ya want for nothing?"])
+            .verify_slice(&expected)
+            .is_ok());
+        assert!(hmac_sha256(b"Jeff", &[b"what do ya want for nothing?"])
+            .verify_slice(&expected)
+            .is_err());
+    }
+
+    #[test]
+    fn test_signing_key() {
+        assert_eq!(signing_key("secret").unwrap().expose(), b"secret");
+        assert!(matches!(signing_key(""), Err(EmptySecret)));
+    }
+}
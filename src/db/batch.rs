@@ -0,0 +1,214 @@
+use scylla::batch::{Batch, BatchStatement, BatchType};
+use scylla::frame::value::{SerializedValues, ValueList};
+
+use crate::db::{DbError, Session};
+use crate::middleware::with_deadline;
+
+/// Scylla fails batches above 1MiB by default, we stay well below that so
+/// the coordinator never has to warn about batch sizes.
+pub const DEFAULT_MAX_BATCH_BYTES: usize = 50 * 1024;
+pub const DEFAULT_MAX_BATCH_STATEMENTS: usize = 100;
+
+#[derive(Debug, Default)]
+struct PendingBatch {
+    queries: Vec<String>,
+    values: Vec<SerializedValues>,
+    bytes: usize,
+}
+
+/// Accumulates statements and splits them into as many batches as needed to
+/// stay within the size and statement limits.
+///
+/// Logged batches are never split, as they would then only be atomic per
+/// split. Appending past their limits fails with [DbError::BatchFull].
+pub struct BatchBuilder {
+    batch_type: BatchType,
+    max_bytes: usize,
+    max_statements: usize,
+    batches: Vec<PendingBatch>,
+}
+
+impl BatchBuilder {
+    pub fn new(batch_type: BatchType) -> Self {
+        Self {
+            batch_type,
+            max_bytes: DEFAULT_MAX_BATCH_BYTES,
+            max_statements: DEFAULT_MAX_BATCH_STATEMENTS,
+            batches: vec![],
+        }
+    }
+
+    #[inline]
+    pub fn logged() -> Self {
+        Self::new(BatchType::Logged)
+    }
+
+    #[inline]
+    pub fn unlogged() -> Self {
+        Self::new(BatchType::Unlogged)
+    }
+
+    pub fn with_max_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_bytes = max_bytes;
+        self
+    }
+
+    pub fn with_max_statements(mut self, max_statements: usize) -> Self {
+        self.max_statements = max_statements.max(1);
+        self
+    }
+
+    /// Adds a statement, starting a new batch if it would exceed the limits.
+    ///
+    /// A statement which is larger than the byte limit by itself is placed
+    /// into its own batch. Logged batches fail with [DbError::BatchFull]
+    /// instead of starting another batch.
+    pub fn append(
+        &mut self,
+        query: impl Into<String>,
+        values: impl ValueList,
+    ) -> Result<(), DbError> {
+        let query = query.into();
+        let values = serialize(&values)?;
+        let bytes = query.len() + values_size(&values);
+
+        if self.needs_split(bytes) {
+            if matches!(self.batch_type, BatchType::Logged) && !self.batches.is_empty() {
+                return Err(DbError::BatchFull);
+            }
+            self.batches.push(PendingBatch::default());
+        }
+
+        let batch = self.batches.last_mut().unwrap();
+        batch.queries.push(query);
+        batch.values.push(values);
+        batch.bytes += bytes;
+
+        Ok(())
+    }
+
+    /// Whether appending the statement would start another batch, the first
+    /// statement never does.
+    pub fn would_split(&self, query: &str, values: &impl ValueList) -> Result<bool, DbError> {
+        let bytes = query.len() + values_size(&serialize(values)?);
+        Ok(!self.batches.is_empty() && self.needs_split(bytes))
+    }
+
+    fn needs_split(&self, bytes: usize) -> bool {
+        match self.batches.last() {
+            None => true,
+            Some(batch) => {
+                batch.queries.len() >= self.max_statements
+                    || (!batch.queries.is_empty() && batch.bytes + bytes > self.max_bytes)
+            }
+        }
+    }
+
+    /// The total number of statements across all batches.
+    pub fn len(&self) -> usize {
+        self.batches.iter().map(|v| v.queries.len()).sum()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.batches.is_empty()
+    }
+
+    /// The number of batches the statements have been split into.
+    #[inline]
+    pub fn batch_count(&self) -> usize {
+        self.batches.len()
+    }
+
+    /// Prepares and executes every batch in order.
+    pub async fn execute(self, session: &Session) -> Result<(), DbError> {
+        for pending in self.batches {
+            let mut batch = Batch::new(self.batch_type);
+            for query in pending.queries.iter() {
+                let prepared = session.prepare(query).await?;
+                batch.append_statement(BatchStatement::PreparedStatement(prepared));
+            }
+
+            with_deadline(session.inner().batch(&batch, &pending.values)).await??;
+        }
+
+        Ok(())
+    }
+}
+
+fn serialize(values: &impl ValueList) -> Result<SerializedValues, DbError> {
+    Ok(values
+        .serialized()
+        .map_err(|e| DbError::Query(e.into()))?
+        .into_owned())
+}
+
+fn values_size(values: &SerializedValues) -> usize {
+    values.iter().map(|v| v.map_or(0, <[u8]>::len)).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const QUERY: &str = "INSERT INTO votes (id, data) VALUES (?, ?)";
+
+    #[test]
+    fn test_splits_on_statement_count() {
+        let mut builder = BatchBuilder::unlogged().with_max_statements(2);
+        for i in 0..5 {
+            builder.append(QUERY, (i, "")).unwrap();
+        }
+
+        assert_eq!(builder.len(), 5);
+        assert_eq!(builder.batch_count(), 3);
+    }
+
+    #[test]
+    fn test_splits_on_size() {
+        // Each statement is the query text plus 4 bytes of id and 40 of data.
+        let max_bytes = 2 * (QUERY.len() + 44);
+        let mut builder = BatchBuilder::unlogged().with_max_bytes(max_bytes);
+        builder.append(QUERY, (1, "a".repeat(40))).unwrap();
+        builder.append(QUERY, (2, "a".repeat(40))).unwrap();
+        assert_eq!(builder.batch_count(), 1);
+
+        builder.append(QUERY, (3, "a".repeat(40))).unwrap();
+        assert_eq!(builder.batch_count(), 2);
+
+        // Oversized statements still go out, just on their own.
+        builder.append(QUERY, (4, "a".repeat(500))).unwrap();
+        builder.append(QUERY, (5, "")).unwrap();
+        assert_eq!(builder.batch_count(), 4);
+        assert!(!builder.is_empty());
+    }
+
+    #[test]
+    fn test_counts_statement_text() {
+        let mut builder = BatchBuilder::unlogged().with_max_bytes(QUERY.len() + 8);
+        builder.append(QUERY, (1, "")).unwrap();
+        assert!(builder.would_split(QUERY, &(2, "")).unwrap());
+        assert!(!builder.would_split("", &(2, "")).unwrap());
+    }
+
+    #[test]
+    fn test_logged_never_splits() {
+        let mut builder = BatchBuilder::logged().with_max_statements(2);
+        builder.append(QUERY, (1, "")).unwrap();
+        builder.append(QUERY, (2, "")).unwrap();
+        assert!(matches!(
+            builder.append(QUERY, (3, "")),
+            Err(DbError::BatchFull)
+        ));
+        assert_eq!(builder.len(), 2);
+        assert_eq!(builder.batch_count(), 1);
+
+        // A single oversized statement is still sent.
+        let mut builder = BatchBuilder::logged().with_max_bytes(10);
+        builder.append(QUERY, (1, "a".repeat(500))).unwrap();
+        assert!(matches!(
+            builder.append(QUERY, (2, "")),
+            Err(DbError::BatchFull)
+        ));
+    }
+}
@@ -0,0 +1,303 @@
+use std::fmt::{Display, Formatter};
+use std::ops::{Deref, DerefMut};
+
+use bincode::config::standard;
+use bincode::error::{DecodeError, EncodeError};
+use bincode::{Decode, Encode};
+use scylla::cql_to_rust::{FromCqlVal, FromCqlValError};
+use scylla::frame::response::result::CqlValue;
+use scylla::frame::value::ValueTooBig;
+
+/// The version of the blob header, bumped if the header layout changes.
+pub const BLOB_FORMAT_VERSION: u8 = 1;
+
+/// Payloads smaller than this are not worth compressing.
+#[cfg(feature = "blob-compression")]
+const COMPRESSION_THRESHOLD: usize = 512;
+
+#[cfg(feature = "blob-compression")]
+const COMPRESSION_LEVEL: i32 = 3;
+
+/// The largest payload a compressed blob may expand to, and the most bytes
+/// a payload may claim while decoding, so a corrupt or hostile blob can't
+/// exhaust memory.
+const MAX_PAYLOAD_SIZE: usize = 16 * 1024 * 1024;
+
+const CODEC_RAW: u8 = 0;
+#[cfg(feature = "blob-compression")]
+const CODEC_ZSTD: u8 = 1;
+
+#[derive(Debug)]
+pub enum BlobError {
+    Encode(EncodeError),
+    Decode(DecodeError),
+    /// The blob is shorter than its header.
+    Truncated,
+    UnsupportedVersion(u8),
+    /// The blob is compressed with a codec which is not enabled.
+    UnsupportedCodec(u8),
+    Compression(std::io::Error),
+    /// The payload expands to more than the allowed size.
+    TooLarge,
+    /// The payload continues after the value, it was likely written as another type.
+    TrailingBytes(usize),
+}
+
+impl Display for BlobError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Encode(e) => write!(f, "cannot encode blob: {}", e),
+            Self::Decode(e) => write!(f, "cannot decode blob: {}", e),
+            Self::Truncated => write!(f, "blob is missing its header"),
+            Self::UnsupportedVersion(v) => write!(f, "unsupported blob format version {}", v),
+            Self::UnsupportedCodec(v) => write!(f, "unsupported blob codec {}", v),
+            Self::Compression(e) => write!(f, "cannot (de)compress blob: {}", e),
+            Self::TooLarge => write!(f, "blob payload is too large"),
+            Self::TrailingBytes(n) => write!(f, "{} unexpected bytes after the value", n),
+        }
+    }
+}
+
+impl std::error::Error for BlobError {}
+
+/// A value stored in a `blob` column as bincode.
+///
+/// Blobs start with a two byte header of the format version and the codec
+/// the payload is compressed with. With the `blob-compression` feature larger
+/// payloads are compressed with zstd, blobs written either way can be read
+/// by services with the feature enabled.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct BincodeBlob<T>(pub T);
+
+impl<T> BincodeBlob<T> {
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T: Encode> BincodeBlob<T> {
+    pub fn encode(value: &T) -> Result<Vec<u8>, BlobError> {
+        let payload = bincode::encode_to_vec(value, standard()).map_err(BlobError::Encode)?;
+        let (codec, payload) = compress(payload)?;
+
+        let mut blob = Vec::with_capacity(payload.len() + 2);
+        blob.push(BLOB_FORMAT_VERSION);
+        blob.push(codec);
+        blob.extend_from_slice(&payload);
+
+        Ok(blob)
+    }
+}
+
+impl<T: Decode> BincodeBlob<T> {
+    pub fn decode(blob: &[u8]) -> Result<T, BlobError> {
+        let (version, codec, payload) = match blob {
+            [version, codec, payload @ ..] => (*version, *codec, payload),
+            _ => return Err(BlobError::Truncated),
+        };
+
+        if version != BLOB_FORMAT_VERSION {
+            return Err(BlobError::UnsupportedVersion(version));
+        }
+
+        let payload = decompress(codec, payload)?;
+        let config = standard().with_limit::<MAX_PAYLOAD_SIZE>();
+        let (value, read) =
+            bincode::decode_from_slice(&payload, config).map_err(BlobError::Decode)?;
+        match payload.len() - read {
+            0 => Ok(value),
+            trailing => Err(BlobError::TrailingBytes(trailing)),
+        }
+    }
+}
+
+#[cfg(feature = "blob-compression")]
+fn compress(payload: Vec<u8>) -> Result<(u8, Vec<u8>), BlobError> {
+    if payload.len() < COMPRESSION_THRESHOLD {
+        return Ok((CODEC_RAW, payload));
+    }
+
+    zstd::encode_all(payload.as_slice(), COMPRESSION_LEVEL)
+        .map(|v| (CODEC_ZSTD, v))
+        .map_err(BlobError::Compression)
+}
+
+#[cfg(not(feature = "blob-compression"))]
+fn compress(payload: Vec<u8>) -> Result<(u8, Vec<u8>), BlobError> {
+    Ok((CODEC_RAW, payload))
+}
+
+fn decompress(codec: u8, payload: &[u8]) -> Result<Vec<u8>, BlobError> {
+    match codec {
+        CODEC_RAW => Ok(payload.to_vec()),
+        #[cfg(feature = "blob-compression")]
+        CODEC_ZSTD => decompress_zstd(payload),
+        other => Err(BlobError::UnsupportedCodec(other)),
+    }
+}
+
+#[cfg(feature = "blob-compression")]
+fn decompress_zstd(payload: &[u8]) -> Result<Vec<u8>, BlobError> {
+    use std::io::Read;
+
+    let decoder = zstd::Decoder::new(payload).map_err(BlobError::Compression)?;
+    let mut decompressed = Vec::new();
+    decoder
+        .take(MAX_PAYLOAD_SIZE as u64 + 1)
+        .read_to_end(&mut decompressed)
+        .map_err(BlobError::Compression)?;
+
+    if decompressed.len() > MAX_PAYLOAD_SIZE {
+        return Err(BlobError::TooLarge);
+    }
+
+    Ok(decompressed)
+}
+
+impl<T> From<T> for BincodeBlob<T> {
+    fn from(v: T) -> Self {
+        Self(v)
+    }
+}
+
+impl<T> Deref for BincodeBlob<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for BincodeBlob<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T: Encode> scylla::frame::value::Value for BincodeBlob<T> {
+    fn serialize(&self, buf: &mut Vec<u8>) -> Result<(), ValueTooBig> {
+        // The driver only has an error for oversized values, the cause is
+        // logged instead so the write fails rather than storing an empty blob.
+        let blob = Self::encode(&self.0).map_err(|e| {
+            tracing::error!(error = %e, "failed to encode blob column");
+            ValueTooBig
+        })?;
+        scylla::frame::value::Value::serialize(&blob, buf)
+    }
+}
+
+impl<T: Decode> FromCqlVal<CqlValue> for BincodeBlob<T> {
+    fn from_cql(cql_val: CqlValue) -> Result<Self, FromCqlValError> {
+        let blob = cql_val.into_blob().ok_or(FromCqlValError::BadCqlType)?;
+        Self::decode(&blob)
+            .map(Self)
+            .map_err(|_| FromCqlValError::BadCqlType)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let value = (42u64, "music".to_string(), vec![1u32, 2, 3]);
+        let blob = BincodeBlob::encode(&value).unwrap();
+
+        assert_eq!(blob[0], BLOB_FORMAT_VERSION);
+        assert_eq!(blob[1], CODEC_RAW);
+        assert_eq!(
+            BincodeBlob::<(u64, String, Vec<u32>)>::decode(&blob).unwrap(),
+            value
+        );
+    }
+
+    #[test]
+    fn test_cql_round_trip() {
+        let value = BincodeBlob(vec!["a".to_string(); 1000]);
+
+        let mut buf = vec![];
+        scylla::frame::value::Value::serialize(&value, &mut buf).unwrap();
+
+        // Skip the 4 byte length prefix of the serialized value.
+        let decoded = BincodeBlob::<Vec<String>>::from_cql(CqlValue::Blob(buf[4..].to_vec()));
+        assert_eq!(decoded.unwrap(), value);
+
+        #[cfg(feature = "blob-compression")]
+        assert_eq!(buf[5], CODEC_ZSTD);
+    }
+
+    #[test]
+    fn test_encode_errors_fail_the_write() {
+        struct Unencodable;
+
+        impl Encode for Unencodable {
+            fn encode<E: bincode::enc::Encoder>(&self, _: &mut E) -> Result<(), EncodeError> {
+                Err(EncodeError::Other("unencodable"))
+            }
+        }
+
+        let mut buf = vec![];
+        let res = scylla::frame::value::Value::serialize(&BincodeBlob(Unencodable), &mut buf);
+        assert!(res.is_err());
+        assert!(buf.is_empty());
+    }
+
+    #[cfg(feature = "blob-compression")]
+    #[test]
+    fn test_rejects_oversized_payloads() {
+        let payload = vec![0u8; MAX_PAYLOAD_SIZE + 1];
+        let compressed = zstd::encode_all(payload.as_slice(), COMPRESSION_LEVEL).unwrap();
+
+        assert!(matches!(
+            decompress(CODEC_ZSTD, &compressed),
+            Err(BlobError::TooLarge)
+        ));
+
+        let compressed = zstd::encode_all(&payload[..1024], COMPRESSION_LEVEL).unwrap();
+        assert_eq!(
+            decompress(CODEC_ZSTD, &compressed).unwrap(),
+            &payload[..1024]
+        );
+    }
+
+    #[test]
+    fn test_rejects_oversized_length_prefix() {
+        // A raw blob with a varint claiming `u64::MAX` elements.
+        let mut blob = vec![BLOB_FORMAT_VERSION, CODEC_RAW, 253];
+        blob.extend(u64::MAX.to_le_bytes());
+
+        assert!(matches!(
+            BincodeBlob::<Vec<u64>>::decode(&blob),
+            Err(BlobError::Decode(DecodeError::LimitExceeded))
+        ));
+    }
+
+    #[test]
+    fn test_rejects_trailing_bytes() {
+        let mut blob = BincodeBlob::encode(&42u32).unwrap();
+        blob.extend([0, 0]);
+
+        assert!(matches!(
+            BincodeBlob::<u32>::decode(&blob),
+            Err(BlobError::TrailingBytes(2))
+        ));
+    }
+
+    #[test]
+    fn test_rejects_unknown_headers() {
+        assert!(matches!(
+            BincodeBlob::<u8>::decode(&[1]),
+            Err(BlobError::Truncated)
+        ));
+        assert!(matches!(
+            BincodeBlob::<u8>::decode(&[9, 0, 1]),
+            Err(BlobError::UnsupportedVersion(9))
+        ));
+        assert!(matches!(
+            BincodeBlob::<u8>::decode(&[1, 7, 1]),
+            Err(BlobError::UnsupportedCodec(7))
+        ));
+    }
+}
@@ -0,0 +1,164 @@
+use scylla::cql_to_rust::FromRowError;
+use scylla::frame::response::result::Row;
+use scylla::frame::value::ValueList;
+use scylla::{FromRow, QueryResult};
+
+use crate::db::{DbError, Session};
+
+/// The outcome of a lightweight transaction.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LwtResult<T> {
+    Applied,
+    /// The condition failed, contains the conflicting row if scylla returned one.
+    ///
+    /// For `IF NOT EXISTS` this is the full existing row in table column order,
+    /// for `IF` conditions it is only the columns used in the condition.
+    Rejected(Option<T>),
+}
+
+impl<T> LwtResult<T> {
+    #[inline]
+    pub fn is_applied(&self) -> bool {
+        matches!(self, Self::Applied)
+    }
+
+    pub fn existing(&self) -> Option<&T> {
+        match self {
+            Self::Applied => None,
+            Self::Rejected(existing) => existing.as_ref(),
+        }
+    }
+
+    pub fn into_existing(self) -> Option<T> {
+        match self {
+            Self::Applied => None,
+            Self::Rejected(existing) => existing,
+        }
+    }
+}
+
+/// A conflicting row which is never needed, use this when only whether the
+/// statement was applied matters.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Ignored;
+
+impl FromRow for Ignored {
+    fn from_row(_: Row) -> Result<Self, FromRowError> {
+        Ok(Self)
+    }
+}
+
+/// Parses the `[applied]` column and the conflicting row of a LWT result.
+pub fn parse_lwt<T: FromRow>(result: QueryResult) -> Result<LwtResult<T>, DbError> {
+    let mut row = result
+        .rows
+        .and_then(|rows| rows.into_iter().next())
+        .ok_or_else(|| DbError::Rows("conditional statement returned no rows".to_string()))?;
+
+    if row.columns.is_empty() {
+        return Err(DbError::Rows("missing [applied] column".to_string()));
+    }
+
+    let applied = row
+        .columns
+        .remove(0)
+        .and_then(|v| v.as_boolean())
+        .ok_or_else(|| DbError::Rows("[applied] column is not a boolean".to_string()))?;
+
+    if applied {
+        return Ok(LwtResult::Applied);
+    }
+
+    if row.columns.iter().all(Option::is_none) {
+        return Ok(LwtResult::Rejected(None));
+    }
+
+    let existing = T::from_row(Row {
+        columns: row.columns,
+    })?;
+    Ok(LwtResult::Rejected(Some(existing)))
+}
+
+/// Executes a conditional `INSERT ... IF NOT EXISTS` or `UPDATE ... IF` statement.
+pub async fn execute_lwt<T: FromRow>(
+    session: &Session,
+    query: &str,
+    values: impl ValueList,
+) -> Result<LwtResult<T>, DbError> {
+    let result = session.execute(query, values).await?;
+    parse_lwt(result)
+}
+
+/// Executes the insert with `IF NOT EXISTS`, adding the condition if it is missing.
+pub async fn insert_if_not_exists<T: FromRow>(
+    session: &Session,
+    query: &str,
+    values: impl ValueList,
+) -> Result<LwtResult<T>, DbError> {
+    let query = if_not_exists(query);
+    execute_lwt(session, &query, values).await
+}
+
+fn if_not_exists(query: &str) -> String {
+    let query = query.trim().trim_end_matches(';').trim_end();
+
+    if query.to_ascii_uppercase().ends_with(" IF NOT EXISTS") {
+        format!("{};", query)
+    } else {
+        format!("{} IF NOT EXISTS;", query)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scylla::frame::response::result::CqlValue;
+
+    fn result(columns: Vec<Option<CqlValue>>) -> QueryResult {
+        QueryResult {
+            rows: Some(vec![Row { columns }]),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_parse_applied() {
+        let res =
+            parse_lwt::<(String, i64)>(result(vec![Some(CqlValue::Boolean(true)), None, None]));
+        assert_eq!(res.unwrap(), LwtResult::Applied);
+    }
+
+    #[test]
+    fn test_parse_rejected() {
+        let res = parse_lwt::<(String, i64)>(result(vec![
+            Some(CqlValue::Boolean(false)),
+            Some(CqlValue::Text("my-bot".into())),
+            Some(CqlValue::BigInt(42)),
+        ]))
+        .unwrap();
+        assert!(!res.is_applied());
+        assert_eq!(res.existing(), Some(&("my-bot".to_string(), 42)));
+
+        let res = parse_lwt::<(String,)>(result(vec![Some(CqlValue::Boolean(false)), None]));
+        assert_eq!(res.unwrap(), LwtResult::Rejected(None));
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert!(parse_lwt::<(i32,)>(QueryResult::default()).is_err());
+        assert!(parse_lwt::<(i32,)>(result(vec![])).is_err());
+        assert!(parse_lwt::<(i32,)>(result(vec![Some(CqlValue::Int(1))])).is_err());
+    }
+
+    #[test]
+    fn test_if_not_exists() {
+        assert_eq!(
+            if_not_exists("INSERT INTO slugs (slug) VALUES (?);"),
+            "INSERT INTO slugs (slug) VALUES (?) IF NOT EXISTS;"
+        );
+        assert_eq!(
+            if_not_exists("INSERT INTO slugs (slug) VALUES (?) if not exists"),
+            "INSERT INTO slugs (slug) VALUES (?) if not exists;"
+        );
+    }
+}
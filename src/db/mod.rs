@@ -0,0 +1,20 @@
+mod batch;
+#[cfg(feature = "bincode")]
+mod blob;
+mod lwt;
+mod paging;
+mod row;
+mod session;
+mod ttl;
+
+pub use batch::{BatchBuilder, DEFAULT_MAX_BATCH_BYTES, DEFAULT_MAX_BATCH_STATEMENTS};
+#[cfg(feature = "bincode")]
+pub use blob::{BincodeBlob, BlobError, BLOB_FORMAT_VERSION};
+pub use lwt::{execute_lwt, insert_if_not_exists, parse_lwt, Ignored, LwtResult};
+pub use paging::{Cursor, Page, PageSource, PagedQuery, DEFAULT_PAGE_SIZE, MAX_PAGE_SIZE};
+pub use row::{rows_named, FromRowExt, RowError, RowReader};
+pub use session::{DbError, Session};
+pub use ttl::{
+    insert_using_ttl, ttl_from_duration, update_using_ttl, UnsupportedStatement, WithTtl,
+    MAX_TTL_SECS,
+};
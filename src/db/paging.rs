@@ -0,0 +1,379 @@
+use std::borrow::Cow;
+use std::fmt::{Display, Formatter};
+use std::marker::PhantomData;
+use std::str::FromStr;
+
+use bytes::Bytes;
+use futures_util::{stream, Stream, TryStreamExt};
+use poem_openapi::registry::{MetaSchema, MetaSchemaRef};
+use poem_openapi::types::{ParseError, ParseFromJSON, ParseResult, ToJSON, Type};
+use scylla::frame::value::{SerializedValues, ValueList};
+use scylla::{FromRow, QueryResult};
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+
+use crate::db::session::rows_typed;
+use crate::db::{DbError, Session};
+
+pub const DEFAULT_PAGE_SIZE: i32 = 50;
+pub const MAX_PAGE_SIZE: i32 = 1000;
+
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+/// An opaque pagination cursor handed to API clients, wrapping the scylla
+/// paging state of the next page.
+pub struct Cursor(pub Bytes);
+
+impl Cursor {
+    #[inline]
+    pub fn paging_state(&self) -> Bytes {
+        self.0.clone()
+    }
+}
+
+impl Display for Cursor {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", hex::encode(&self.0))
+    }
+}
+
+impl FromStr for Cursor {
+    type Err = ParseError<Self>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        hex::decode(s)
+            .ok()
+            .filter(|v| !v.is_empty())
+            .map(|v| Self(Bytes::from(v)))
+            .ok_or_else(|| ParseError::custom("invalid cursor"))
+    }
+}
+
+impl Serialize for Cursor {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.to_string().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Cursor {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        Self::from_str(&s).map_err(|e| D::Error::custom(e.into_message()))
+    }
+}
+
+impl Type for Cursor {
+    const IS_REQUIRED: bool = true;
+    type RawValueType = Self;
+    type RawElementValueType = Self;
+
+    fn name() -> Cow<'static, str> {
+        Cow::from("Cursor")
+    }
+
+    fn schema_ref() -> MetaSchemaRef {
+        MetaSchemaRef::Inline(Box::new(MetaSchema {
+            pattern: Some("^([0-9a-fA-F]{2})+$".to_string()),
+            ..MetaSchema::new("string")
+        }))
+    }
+
+    fn as_raw_value(&self) -> Option<&Self::RawValueType> {
+        Some(self)
+    }
+
+    fn raw_element_iter<'a>(
+        &'a self,
+    ) -> Box<dyn Iterator<Item = &'a Self::RawElementValueType> + 'a> {
+        Box::new(self.as_raw_value().into_iter())
+    }
+}
+
+impl ToJSON for Cursor {
+    fn to_json(&self) -> Option<Value> {
+        Some(Value::String(self.to_string()))
+    }
+}
+
+impl ParseFromJSON for Cursor {
+    fn parse_from_json(value: Option<Value>) -> ParseResult<Self> {
+        match value {
+            Some(Value::String(v)) => Self::from_str(&v),
+            _ => Err(ParseError::custom("expected a cursor string")),
+        }
+    }
+}
+
+/// A single page of rows.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Page<T> {
+    pub rows: Vec<T>,
+    /// The cursor of the next page, `None` once the last page is reached.
+    pub cursor: Option<Cursor>,
+}
+
+/// Executes a single page of a query, implemented by [Session].
+#[async_trait::async_trait]
+pub trait PageSource: Sync {
+    async fn fetch_page(
+        &self,
+        query: &str,
+        values: &SerializedValues,
+        page_size: i32,
+        paging_state: Option<Bytes>,
+    ) -> Result<QueryResult, DbError>;
+}
+
+#[async_trait::async_trait]
+impl PageSource for Session {
+    async fn fetch_page(
+        &self,
+        query: &str,
+        values: &SerializedValues,
+        page_size: i32,
+        paging_state: Option<Bytes>,
+    ) -> Result<QueryResult, DbError> {
+        self.execute_paged(query, values, Some(page_size), paging_state)
+            .await
+    }
+}
+
+/// A query which is read one page at a time.
+///
+/// The paging state is tracked internally so pages are never repeated or
+/// skipped, and empty intermediate pages are followed rather than being
+/// mistaken for the end of the results.
+pub struct PagedQuery<'a, T, S = Session> {
+    session: &'a S,
+    query: String,
+    values: SerializedValues,
+    page_size: i32,
+    paging_state: Option<Bytes>,
+    is_finished: bool,
+    _row: PhantomData<T>,
+}
+
+impl<'a, T: FromRow, S: PageSource> PagedQuery<'a, T, S> {
+    pub fn new(
+        session: &'a S,
+        query: impl Into<String>,
+        values: impl ValueList,
+    ) -> Result<Self, DbError> {
+        let values = values.serialized().map_err(|e| DbError::Query(e.into()))?;
+
+        Ok(Self {
+            session,
+            query: query.into(),
+            values: values.into_owned(),
+            page_size: DEFAULT_PAGE_SIZE,
+            paging_state: None,
+            is_finished: false,
+            _row: PhantomData,
+        })
+    }
+
+    /// Sets the page size, clamped to `1..=MAX_PAGE_SIZE`.
+    pub fn with_page_size(mut self, page_size: u32) -> Self {
+        self.page_size = clamp_page_size(page_size);
+        self
+    }
+
+    /// Resumes from the cursor of a previously returned page.
+    pub fn starting_at(mut self, cursor: Option<Cursor>) -> Self {
+        self.paging_state = cursor.map(|v| v.paging_state());
+        self
+    }
+
+    #[inline]
+    pub fn page_size(&self) -> i32 {
+        self.page_size
+    }
+
+    /// Fetches the next non-empty page, returns `None` once the results are exhausted.
+    pub async fn next_page(&mut self) -> Result<Option<Page<T>>, DbError> {
+        while !self.is_finished {
+            let result = self
+                .session
+                .fetch_page(
+                    &self.query,
+                    &self.values,
+                    self.page_size,
+                    self.paging_state.take(),
+                )
+                .await?;
+
+            self.paging_state = result.paging_state.clone();
+            self.is_finished = self.paging_state.is_none();
+
+            let rows = rows_typed(result)?;
+            if !rows.is_empty() {
+                return Ok(Some(Page {
+                    rows,
+                    cursor: self.paging_state.clone().map(Cursor),
+                }));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Streams every remaining row, fetching pages as they are needed.
+    pub fn into_stream(self) -> impl Stream<Item = Result<T, DbError>> + 'a
+    where
+        T: 'a,
+    {
+        stream::try_unfold(self, |mut query| async move {
+            let page = query.next_page().await?;
+            Ok::<_, DbError>(page.map(|page| (page, query)))
+        })
+        .map_ok(|page| stream::iter(page.rows.into_iter().map(Ok)))
+        .try_flatten()
+    }
+}
+
+#[inline]
+fn clamp_page_size(page_size: u32) -> i32 {
+    page_size.clamp(1, MAX_PAGE_SIZE as u32) as i32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scylla::frame::response::result::{CqlValue, Row};
+    use serde_json::json;
+
+    #[test]
+    fn test_cursor_round_trip() {
+        let cursor = Cursor(Bytes::from_static(&[0, 1, 254]));
+        assert_eq!(cursor.to_json(), Some(json!("0001fe")));
+        assert_eq!(Cursor::parse_from_json(cursor.to_json()).unwrap(), cursor);
+        assert_eq!(
+            serde_json::from_value::<Cursor>(json!("0001fe")).unwrap(),
+            cursor
+        );
+
+        assert!(Cursor::from_str("").is_err());
+        assert!(Cursor::from_str("not hex").is_err());
+    }
+
+    /// The `int` rows of a page and the paging state of the next one.
+    type FakePage = (Vec<i32>, Option<&'static [u8]>);
+
+    /// Serves canned pages, recording the paging states requested.
+    struct FakePages {
+        pages: std::sync::Mutex<Vec<FakePage>>,
+        requested: std::sync::Mutex<Vec<Option<Bytes>>>,
+    }
+
+    impl FakePages {
+        fn new(pages: Vec<FakePage>) -> Self {
+            Self {
+                pages: std::sync::Mutex::new(pages),
+                requested: std::sync::Mutex::default(),
+            }
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl PageSource for FakePages {
+        async fn fetch_page(
+            &self,
+            _query: &str,
+            _values: &SerializedValues,
+            _page_size: i32,
+            paging_state: Option<Bytes>,
+        ) -> Result<QueryResult, DbError> {
+            self.requested.lock().unwrap().push(paging_state);
+
+            let (rows, paging_state) = self.pages.lock().unwrap().remove(0);
+            let rows = rows
+                .into_iter()
+                .map(|v| Row {
+                    columns: vec![Some(CqlValue::Int(v))],
+                })
+                .collect();
+
+            Ok(QueryResult {
+                rows: Some(rows),
+                paging_state: paging_state.map(Bytes::from_static),
+                ..Default::default()
+            })
+        }
+    }
+
+    fn block_on<F: std::future::Future>(fut: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(fut)
+    }
+
+    #[test]
+    fn test_next_page_follows_empty_pages() {
+        let source = FakePages::new(vec![
+            (vec![1, 2], Some(b"a")),
+            (vec![], Some(b"b")),
+            (vec![3], None),
+        ]);
+
+        block_on(async {
+            let mut query = PagedQuery::<(i32,), _>::new(&source, "SELECT", ()).unwrap();
+
+            let page = query.next_page().await.unwrap().unwrap();
+            assert_eq!(page.rows, vec![(1,), (2,)]);
+            assert_eq!(page.cursor, Some(Cursor(Bytes::from_static(b"a"))));
+
+            let page = query.next_page().await.unwrap().unwrap();
+            assert_eq!(page.rows, vec![(3,)]);
+            assert_eq!(page.cursor, None);
+
+            assert_eq!(query.next_page().await.unwrap(), None);
+        });
+
+        assert_eq!(
+            *source.requested.lock().unwrap(),
+            vec![
+                None,
+                Some(Bytes::from_static(b"a")),
+                Some(Bytes::from_static(b"b"))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_into_stream_resumes_from_cursor() {
+        let source = FakePages::new(vec![(vec![4, 5], Some(b"d")), (vec![6], None)]);
+
+        let rows = block_on(async {
+            PagedQuery::<(i32,), _>::new(&source, "SELECT", ())
+                .unwrap()
+                .starting_at(Some(Cursor(Bytes::from_static(b"c"))))
+                .into_stream()
+                .try_collect::<Vec<_>>()
+                .await
+                .unwrap()
+        });
+
+        assert_eq!(rows, vec![(4,), (5,), (6,)]);
+        assert_eq!(
+            *source.requested.lock().unwrap(),
+            vec![
+                Some(Bytes::from_static(b"c")),
+                Some(Bytes::from_static(b"d"))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_page_size_clamped() {
+        assert_eq!(clamp_page_size(0), 1);
+        assert_eq!(clamp_page_size(25), 25);
+        assert_eq!(clamp_page_size(u32::MAX), MAX_PAGE_SIZE);
+    }
+}
@@ -0,0 +1,220 @@
+use std::fmt::{Display, Formatter};
+
+use scylla::cql_to_rust::{FromCqlVal, FromCqlValError};
+use scylla::frame::response::result::{ColumnSpec, CqlValue, Row};
+use scylla::QueryResult;
+
+use crate::db::DbError;
+
+/// Why a row could not be converted, naming the column at fault.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RowError {
+    /// The result has no column with this name.
+    Missing(&'static str),
+    Invalid {
+        column: &'static str,
+        error: FromCqlValError,
+    },
+}
+
+impl Display for RowError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Missing(column) => write!(f, "column {} is not in the result", column),
+            Self::Invalid { column, error } => write!(f, "column {} is invalid: {}", column, error),
+        }
+    }
+}
+
+impl std::error::Error for RowError {}
+
+impl From<RowError> for DbError {
+    fn from(e: RowError) -> Self {
+        Self::Rows(e.to_string())
+    }
+}
+
+/// Reads the columns of a row by name rather than by position.
+pub struct RowReader<'a> {
+    specs: &'a [ColumnSpec],
+    columns: Vec<Option<CqlValue>>,
+}
+
+impl<'a> RowReader<'a> {
+    pub fn new(specs: &'a [ColumnSpec], row: Row) -> Self {
+        Self {
+            specs,
+            columns: row.columns,
+        }
+    }
+
+    /// `None` if the result has no such column, a null column is `Some(None)`.
+    fn take(&mut self, name: &str) -> Option<Option<CqlValue>> {
+        let idx = self.specs.iter().position(|v| v.name == name)?;
+        self.columns.get_mut(idx).map(Option::take)
+    }
+
+    /// The column must be in the result, it may only be null if `T` is an `Option`.
+    pub fn column<T>(&mut self, name: &'static str) -> Result<T, RowError>
+    where
+        T: FromCqlVal<Option<CqlValue>>,
+    {
+        let value = self.take(name).ok_or(RowError::Missing(name))?;
+        T::from_cql(value).map_err(|error| RowError::Invalid {
+            column: name,
+            error,
+        })
+    }
+
+    /// Falls back to the default when the column is null or not in the result.
+    pub fn default<T>(&mut self, name: &'static str, default: T) -> Result<T, RowError>
+    where
+        T: FromCqlVal<CqlValue>,
+    {
+        match self.take(name).flatten() {
+            Some(value) => T::from_cql(value).map_err(|error| RowError::Invalid {
+                column: name,
+                error,
+            }),
+            None => Ok(default),
+        }
+    }
+}
+
+/// A struct read from a row by column name, usually declared with
+/// [named_row!](crate::named_row).
+pub trait FromRowExt: Sized {
+    fn from_reader(reader: &mut RowReader<'_>) -> Result<Self, RowError>;
+}
+
+/// Converts the rows of a result by column name, results without rows are
+/// treated as empty.
+pub fn rows_named<T: FromRowExt>(result: QueryResult) -> Result<Vec<T>, DbError> {
+    let QueryResult {
+        rows, col_specs, ..
+    } = result;
+
+    rows.unwrap_or_default()
+        .into_iter()
+        .map(|row| Ok(T::from_reader(&mut RowReader::new(&col_specs, row))?))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use scylla::frame::response::result::{ColumnType, TableSpec};
+
+    use super::*;
+    use crate::types::JsSafeBigInt;
+
+    crate::named_row! {
+        #[derive(Debug, PartialEq)]
+        struct BotRow {
+            id: JsSafeBigInt = column("id"),
+            name: String = column("bot_name"),
+            description: Option<String> = column("description"),
+            votes: i64 = default("votes", 0),
+        }
+    }
+
+    fn spec(name: &str) -> ColumnSpec {
+        ColumnSpec {
+            table_spec: TableSpec {
+                ks_name: "discordlist".to_string(),
+                table_name: "bots".to_string(),
+            },
+            name: name.to_string(),
+            typ: ColumnType::Text,
+        }
+    }
+
+    fn result(columns: &[&str], rows: Vec<Vec<Option<CqlValue>>>) -> QueryResult {
+        QueryResult {
+            rows: Some(rows.into_iter().map(|columns| Row { columns }).collect()),
+            col_specs: columns.iter().map(|v| spec(v)).collect(),
+            ..Default::default()
+        }
+    }
+
+    fn text(v: &str) -> Option<CqlValue> {
+        Some(CqlValue::Text(v.to_string()))
+    }
+
+    #[test]
+    fn test_named_columns() {
+        let rows = rows_named::<BotRow>(result(
+            &["bot_name", "votes", "id", "description"],
+            vec![
+                vec![
+                    text("Rusty"),
+                    Some(CqlValue::BigInt(3)),
+                    Some(CqlValue::BigInt(1)),
+                    None,
+                ],
+                vec![
+                    text("Other"),
+                    None,
+                    Some(CqlValue::BigInt(2)),
+                    text("A bot."),
+                ],
+            ],
+        ))
+        .unwrap();
+
+        assert_eq!(
+            rows,
+            vec![
+                BotRow {
+                    id: JsSafeBigInt(1),
+                    name: "Rusty".to_string(),
+                    description: None,
+                    votes: 3,
+                },
+                BotRow {
+                    id: JsSafeBigInt(2),
+                    name: "Other".to_string(),
+                    description: Some("A bot.".to_string()),
+                    votes: 0,
+                },
+            ]
+        );
+
+        let without_votes = rows_named::<BotRow>(result(
+            &["id", "bot_name", "description"],
+            vec![vec![Some(CqlValue::BigInt(1)), text("Rusty"), None]],
+        ))
+        .unwrap();
+        assert_eq!(without_votes[0].votes, 0);
+    }
+
+    #[test]
+    fn test_errors_name_column() {
+        let missing = rows_named::<BotRow>(result(&["id"], vec![vec![Some(CqlValue::BigInt(1))]]));
+        assert_eq!(
+            missing.unwrap_err().to_string(),
+            DbError::from(RowError::Missing("bot_name")).to_string()
+        );
+
+        let specs = [spec("id"), spec("bot_name")];
+        let mut reader = RowReader::new(
+            &specs,
+            Row {
+                columns: vec![text("one"), None],
+            },
+        );
+        assert_eq!(
+            reader.column::<JsSafeBigInt>("id"),
+            Err(RowError::Invalid {
+                column: "id",
+                error: FromCqlValError::BadCqlType,
+            })
+        );
+        assert_eq!(
+            reader.column::<String>("bot_name"),
+            Err(RowError::Invalid {
+                column: "bot_name",
+                error: FromCqlValError::ValIsNull,
+            })
+        );
+    }
+}
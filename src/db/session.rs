@@ -0,0 +1,224 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::sync::{Arc, RwLock};
+
+use bytes::Bytes;
+use scylla::cql_to_rust::FromRowError;
+use scylla::frame::value::ValueList;
+use scylla::prepared_statement::PreparedStatement;
+use scylla::transport::errors::QueryError;
+use scylla::transport::query_result::RowsExpectedError;
+use scylla::{FromRow, QueryResult};
+
+use crate::db::{rows_named, FromRowExt};
+use crate::errors::ApiError;
+use crate::middleware::{with_deadline, DeadlineExceeded};
+use crate::telemetry::instrument_query;
+
+#[derive(Debug)]
+pub enum DbError {
+    Query(QueryError),
+    /// The rows returned could not be converted into the requested type.
+    Rows(String),
+    /// The statement would not fit into the logged batch.
+    BatchFull,
+}
+
+impl Display for DbError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Query(e) => write!(f, "query failed: {}", e),
+            Self::Rows(e) => write!(f, "unexpected rows: {}", e),
+            Self::BatchFull => write!(f, "statement exceeds the logged batch limits"),
+        }
+    }
+}
+
+impl std::error::Error for DbError {}
+
+impl From<QueryError> for DbError {
+    fn from(e: QueryError) -> Self {
+        Self::Query(e)
+    }
+}
+
+impl From<DeadlineExceeded> for DbError {
+    fn from(e: DeadlineExceeded) -> Self {
+        Self::Query(e.into())
+    }
+}
+
+impl From<FromRowError> for DbError {
+    fn from(e: FromRowError) -> Self {
+        Self::Rows(e.to_string())
+    }
+}
+
+impl From<RowsExpectedError> for DbError {
+    fn from(e: RowsExpectedError) -> Self {
+        Self::Rows(e.to_string())
+    }
+}
+
+impl From<DbError> for ApiError {
+    fn from(e: DbError) -> Self {
+        tracing::error!(error = %e, "database error");
+        Self::internal()
+    }
+}
+
+/// A scylla session which prepares statements on first use and caches
+/// them by their query string.
+///
+/// All queries respect the deadline of the current request.
+pub struct Session {
+    inner: Arc<scylla::Session>,
+    prepared: RwLock<HashMap<String, PreparedStatement>>,
+}
+
+impl Session {
+    pub fn new(inner: Arc<scylla::Session>) -> Self {
+        Self {
+            inner,
+            prepared: RwLock::default(),
+        }
+    }
+
+    #[inline]
+    pub fn inner(&self) -> &Arc<scylla::Session> {
+        &self.inner
+    }
+
+    /// The number of statements which have been prepared.
+    pub fn prepared_len(&self) -> usize {
+        self.prepared.read().unwrap().len()
+    }
+
+    /// Gets the prepared statement for the query, preparing it if needed.
+    pub async fn prepare(&self, query: &str) -> Result<PreparedStatement, DbError> {
+        if let Some(prepared) = self.prepared.read().unwrap().get(query) {
+            return Ok(prepared.clone());
+        }
+
+        // Concurrent callers may prepare the same query twice, that is
+        // harmless and cheaper than holding a lock across the round trip.
+        let prepared = with_deadline(self.inner.prepare(query)).await??;
+        self.prepared
+            .write()
+            .unwrap()
+            .insert(query.to_string(), prepared.clone());
+
+        Ok(prepared)
+    }
+
+    pub async fn execute(
+        &self,
+        query: &str,
+        values: impl ValueList,
+    ) -> Result<QueryResult, DbError> {
+        self.execute_paged(query, values, None, None).await
+    }
+
+    /// Executes a single page of the query, continuing from the paging state
+    /// of the previous page if given.
+    pub async fn execute_paged(
+        &self,
+        query: &str,
+        values: impl ValueList,
+        page_size: Option<i32>,
+        paging_state: Option<Bytes>,
+    ) -> Result<QueryResult, DbError> {
+        let mut prepared = self.prepare(query).await?;
+        if let Some(page_size) = page_size {
+            prepared.set_page_size(page_size);
+        }
+
+        let fut = self.inner.execute_paged(&prepared, values, paging_state);
+        let result = with_deadline(instrument_query(query, fut)).await??;
+        Ok(result)
+    }
+
+    /// Executes the query and converts every returned row.
+    pub async fn query_typed<T: FromRow>(
+        &self,
+        query: &str,
+        values: impl ValueList,
+    ) -> Result<Vec<T>, DbError> {
+        let result = self.execute(query, values).await?;
+        rows_typed(result)
+    }
+
+    /// Executes the query and converts the first row, if any.
+    pub async fn query_one<T: FromRow>(
+        &self,
+        query: &str,
+        values: impl ValueList,
+    ) -> Result<Option<T>, DbError> {
+        let rows = self.query_typed(query, values).await?;
+        Ok(rows.into_iter().next())
+    }
+
+    /// Executes the query and converts every returned row by column name.
+    pub async fn query_named<T: FromRowExt>(
+        &self,
+        query: &str,
+        values: impl ValueList,
+    ) -> Result<Vec<T>, DbError> {
+        let result = self.execute(query, values).await?;
+        rows_named(result)
+    }
+}
+
+/// Converts the rows of a result, results without rows are treated as empty.
+pub(crate) fn rows_typed<T: FromRow>(result: QueryResult) -> Result<Vec<T>, DbError> {
+    result
+        .rows_typed_or_empty::<T>()
+        .map(|row| row.map_err(DbError::from))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::ErrorCode;
+    use scylla::frame::response::result::{CqlValue, Row};
+
+    fn result(rows: Vec<Row>) -> QueryResult {
+        QueryResult {
+            rows: Some(rows),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_rows_typed() {
+        let rows = vec![
+            Row {
+                columns: vec![Some(CqlValue::Int(1))],
+            },
+            Row {
+                columns: vec![Some(CqlValue::Int(2))],
+            },
+        ];
+        let values = rows_typed::<(i32,)>(result(rows)).unwrap();
+        assert_eq!(values, vec![(1,), (2,)]);
+
+        let bad = vec![Row {
+            columns: vec![Some(CqlValue::Text("one".to_string()))],
+        }];
+        assert!(matches!(
+            rows_typed::<(i32,)>(result(bad)),
+            Err(DbError::Rows(_))
+        ));
+
+        assert!(rows_typed::<(i32,)>(QueryResult::default())
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_api_error_mapping() {
+        let err = ApiError::from(DbError::from(QueryError::TimeoutError));
+        assert_eq!(err.code(), ErrorCode::InternalError);
+    }
+}
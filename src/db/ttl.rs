@@ -0,0 +1,243 @@
+use std::borrow::Cow;
+use std::fmt::{Display, Formatter};
+
+use scylla::frame::value::{
+    SerializeValuesError, SerializedResult, SerializedValues, Value, ValueList, ValueTooBig,
+};
+use scylla::transport::errors::{BadQuery, QueryError};
+
+use crate::db::DbError;
+use crate::types::HumanDuration;
+
+/// The largest TTL scylla accepts, 20 years.
+pub const MAX_TTL_SECS: i32 = 630_720_000;
+
+/// Converts a duration into TTL seconds.
+///
+/// Durations are clamped to `1..=MAX_TTL_SECS` as a TTL of `0` would
+/// make the row live forever.
+pub fn ttl_from_duration(duration: HumanDuration) -> i32 {
+    duration.as_secs().clamp(1, MAX_TTL_SECS as u64) as i32
+}
+
+/// A statement which a `USING TTL` clause cannot be added to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnsupportedStatement(pub String);
+
+impl Display for UnsupportedStatement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "cannot add a TTL to statement {:?}", self.0)
+    }
+}
+
+impl std::error::Error for UnsupportedStatement {}
+
+impl From<UnsupportedStatement> for QueryError {
+    fn from(e: UnsupportedStatement) -> Self {
+        Self::BadQuery(BadQuery::Other(e.to_string()))
+    }
+}
+
+impl From<UnsupportedStatement> for DbError {
+    fn from(e: UnsupportedStatement) -> Self {
+        Self::Query(e.into())
+    }
+}
+
+/// Adds a `USING TTL ?` clause to an `INSERT` statement, before any
+/// `IF NOT EXISTS` condition.
+///
+/// The TTL is bound as the last value so it pairs with [WithTtl::new], use
+/// [update_using_ttl] for updates.
+pub fn insert_using_ttl(query: &str) -> Result<String, UnsupportedStatement> {
+    let query = query.trim().trim_end_matches(';').trim_end();
+    if !query.to_ascii_uppercase().starts_with("INSERT ") {
+        return Err(UnsupportedStatement(query.to_string()));
+    }
+
+    let (insert, condition) = match query.to_ascii_uppercase().rfind(" IF NOT EXISTS") {
+        Some(idx) => query.split_at(idx),
+        None => (query, ""),
+    };
+
+    Ok(format!("{} USING TTL ?{};", insert, condition))
+}
+
+/// Adds a `USING TTL ?` clause to an `UPDATE` statement, before its `SET`.
+///
+/// The TTL is bound as the first value so it pairs with [WithTtl::leading].
+pub fn update_using_ttl(query: &str) -> Result<String, UnsupportedStatement> {
+    let query = query.trim().trim_end_matches(';').trim_end();
+
+    let upper = query.to_ascii_uppercase();
+    let set = upper
+        .match_indices("SET")
+        .map(|(idx, _)| idx)
+        .find(|&idx| {
+            let before = upper[..idx].chars().next_back();
+            let after = upper[idx + 3..].chars().next();
+            before.is_some_and(char::is_whitespace) && after.is_some_and(char::is_whitespace)
+        })
+        .filter(|_| upper.starts_with("UPDATE "))
+        .ok_or_else(|| UnsupportedStatement(query.to_string()))?;
+
+    let (table, assignments) = query.split_at(set);
+    Ok(format!("{} USING TTL ? {};", table.trim_end(), assignments))
+}
+
+/// Values for a statement built with [insert_using_ttl] or [update_using_ttl].
+///
+/// Inserts bind the TTL after the wrapped values, updates bind it before them.
+#[derive(Clone, Debug)]
+pub struct WithTtl<T> {
+    pub values: T,
+    pub ttl: HumanDuration,
+    leading: bool,
+}
+
+impl<T: ValueList> WithTtl<T> {
+    /// Binds the TTL last, for [insert_using_ttl].
+    pub fn new(values: T, ttl: impl Into<HumanDuration>) -> Self {
+        Self {
+            values,
+            ttl: ttl.into(),
+            leading: false,
+        }
+    }
+
+    /// Binds the TTL first, for [update_using_ttl].
+    pub fn leading(values: T, ttl: impl Into<HumanDuration>) -> Self {
+        Self {
+            leading: true,
+            ..Self::new(values, ttl)
+        }
+    }
+}
+
+/// An already serialized value, including its length prefix.
+struct RawValue<'a>(&'a [u8]);
+
+impl Value for RawValue<'_> {
+    fn serialize(&self, buf: &mut Vec<u8>) -> Result<(), ValueTooBig> {
+        buf.extend_from_slice(self.0);
+        Ok(())
+    }
+}
+
+/// Copies the serialized values after `first`, keeping null and unset
+/// values as they are.
+fn prepend(first: &impl Value, values: &SerializedValues) -> SerializedResult<'static> {
+    let mut raw = Vec::new();
+    values.write_to_request(&mut raw);
+    let mut raw = &raw[2..];
+
+    let mut out = SerializedValues::with_capacity(raw.len() + 8);
+    out.add_value(first)?;
+    while !raw.is_empty() {
+        let len = i32::from_be_bytes([raw[0], raw[1], raw[2], raw[3]]);
+        let (value, rest) = raw.split_at(4 + len.max(0) as usize);
+        out.add_value(&RawValue(value))?;
+        raw = rest;
+    }
+
+    Ok(Cow::Owned(out))
+}
+
+impl<T: ValueList> ValueList for WithTtl<T> {
+    fn serialized(&self) -> SerializedResult<'_> {
+        let values = self.values.serialized()?;
+        if values.has_names() {
+            return Err(SerializeValuesError::MixingNamedAndNotNamedValues);
+        }
+
+        let ttl = ttl_from_duration(self.ttl);
+        if self.leading {
+            return prepend(&ttl, &values);
+        }
+
+        let mut values = values.into_owned();
+        values.add_value(&ttl)?;
+        Ok(Cow::Owned(values))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_insert_using_ttl() {
+        assert_eq!(
+            insert_using_ttl("INSERT INTO votes (id) VALUES (?);").unwrap(),
+            "INSERT INTO votes (id) VALUES (?) USING TTL ?;"
+        );
+        assert_eq!(
+            insert_using_ttl("INSERT INTO slugs (slug) VALUES (?) IF NOT EXISTS").unwrap(),
+            "INSERT INTO slugs (slug) VALUES (?) USING TTL ? IF NOT EXISTS;"
+        );
+    }
+
+    #[test]
+    fn test_update_using_ttl() {
+        assert_eq!(
+            update_using_ttl("UPDATE sessions SET user_id = ? WHERE id = ?;").unwrap(),
+            "UPDATE sessions USING TTL ? SET user_id = ? WHERE id = ?;"
+        );
+        assert_eq!(
+            update_using_ttl("update bump_counters\n    set count = ? WHERE day = ? IF count = ?").unwrap(),
+            "update bump_counters USING TTL ? set count = ? WHERE day = ? IF count = ?;"
+        );
+        // Identifiers containing `set` are not mistaken for the clause.
+        assert_eq!(
+            update_using_ttl("UPDATE asset_sets SET offset = ? WHERE id = ?").unwrap(),
+            "UPDATE asset_sets USING TTL ? SET offset = ? WHERE id = ?;"
+        );
+    }
+
+    #[test]
+    fn test_rejects_other_statements() {
+        assert_eq!(
+            update_using_ttl("INSERT INTO votes (id) VALUES (?);"),
+            Err(UnsupportedStatement(
+                "INSERT INTO votes (id) VALUES (?)".to_string()
+            ))
+        );
+        assert!(update_using_ttl("SELECT id FROM votes WHERE set = ?").is_err());
+        assert!(insert_using_ttl("UPDATE votes SET count = ? WHERE id = ?;").is_err());
+    }
+
+    #[test]
+    fn test_ttl_prepended() {
+        let values = WithTtl::leading((1i32, None::<i32>, "a"), Duration::from_secs(60));
+        let serialized = values.serialized().unwrap();
+        let values: Vec<_> = serialized.iter().collect();
+
+        assert_eq!(
+            values,
+            vec![
+                Some(&60i32.to_be_bytes()[..]),
+                Some(&1i32.to_be_bytes()[..]),
+                None,
+                Some(&b"a"[..]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ttl_appended() {
+        let values = WithTtl::new((1i32, "a"), Duration::from_secs(60));
+        let serialized = values.serialized().unwrap();
+        assert_eq!(serialized.len(), 3);
+        assert_eq!(
+            serialized.iter().last().unwrap(),
+            Some(&60i32.to_be_bytes()[..])
+        );
+
+        assert_eq!(ttl_from_duration(HumanDuration::from_secs(0)), 1);
+        assert_eq!(
+            ttl_from_duration(HumanDuration::from_secs(u64::MAX)),
+            MAX_TTL_SECS
+        );
+    }
+}
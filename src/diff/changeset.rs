@@ -0,0 +1,208 @@
+use poem_openapi::Object;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+pub use crate::types::REDACTED;
+
+/// A single field which differs between two versions of a value.
+#[derive(Object, Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct FieldChange {
+    /// The dot separated path of the field, e.g. `links.github`.
+    pub path: String,
+    /// The previous value, `null` if the field was added.
+    pub old: Value,
+    /// The new value, `null` if the field was removed.
+    pub new: Value,
+}
+
+type RedactHook = Box<dyn Fn(&str) -> bool + Send + Sync>;
+
+/// Computes field level changes between two serializable values.
+///
+/// Objects are compared field by field recursively, anything else including
+/// arrays is compared as a whole. Redacted fields are still reported as
+/// changed but their values are replaced with [REDACTED].
+#[derive(Default)]
+pub struct Differ {
+    redacted_keys: Vec<String>,
+    hooks: Vec<RedactHook>,
+}
+
+impl Differ {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Redacts any field with the given name regardless of where it is nested.
+    pub fn redact_key(mut self, key: impl Into<String>) -> Self {
+        self.redacted_keys.push(key.into());
+        self
+    }
+
+    /// Redacts any field whose full path the hook returns `true` for.
+    pub fn redact_with(mut self, hook: impl Fn(&str) -> bool + Send + Sync + 'static) -> Self {
+        self.hooks.push(Box::new(hook));
+        self
+    }
+
+    pub fn diff<T: Serialize + ?Sized>(
+        &self,
+        old: &T,
+        new: &T,
+    ) -> serde_json::Result<Vec<FieldChange>> {
+        let old = serde_json::to_value(old)?;
+        let new = serde_json::to_value(new)?;
+
+        let mut changes = vec![];
+        self.walk("", &old, &new, &mut changes);
+        Ok(changes)
+    }
+
+    fn walk(&self, path: &str, old: &Value, new: &Value, changes: &mut Vec<FieldChange>) {
+        if old == new {
+            return;
+        }
+
+        // Checked before recursing so a redacted object is reported as a
+        // single change rather than leaking its fields.
+        let (old, new) = if !path.is_empty() && self.is_redacted(path) {
+            (redact(old), redact(new))
+        } else if let (Value::Object(old), Value::Object(new)) = (old, new) {
+            return self.walk_object(path, old, new, changes);
+        } else {
+            (old.clone(), new.clone())
+        };
+
+        changes.push(FieldChange {
+            path: path.to_string(),
+            old,
+            new,
+        });
+    }
+
+    fn walk_object(
+        &self,
+        path: &str,
+        old: &Map<String, Value>,
+        new: &Map<String, Value>,
+        changes: &mut Vec<FieldChange>,
+    ) {
+        let mut keys: Vec<&String> = old.keys().chain(new.keys()).collect();
+        keys.sort();
+        keys.dedup();
+
+        for key in keys {
+            let child = if path.is_empty() {
+                key.clone()
+            } else {
+                format!("{}.{}", path, key)
+            };
+
+            self.walk(
+                &child,
+                old.get(key).unwrap_or(&Value::Null),
+                new.get(key).unwrap_or(&Value::Null),
+                changes,
+            );
+        }
+    }
+
+    fn is_redacted(&self, path: &str) -> bool {
+        let key = path.rsplit('.').next().unwrap_or(path);
+
+        self.redacted_keys.iter().any(|v| v == key) || self.hooks.iter().any(|hook| hook(path))
+    }
+}
+
+fn redact(value: &Value) -> Value {
+    match value {
+        Value::Null => Value::Null,
+        _ => Value::String(REDACTED.to_string()),
+    }
+}
+
+/// Computes the changes between two values without any redaction.
+pub fn changes<T: Serialize + ?Sized>(old: &T, new: &T) -> serde_json::Result<Vec<FieldChange>> {
+    Differ::new().diff(old, new)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_nested_changes() {
+        let old = json!({
+            "name": "Cool Bot",
+            "links": {"github": "https://github.com/a", "website": null},
+            "tags": ["music"],
+        });
+        let new = json!({
+            "name": "Cool Bot",
+            "links": {"github": "https://github.com/b", "website": null},
+            "tags": ["music", "fun"],
+            "prefix": "!",
+        });
+
+        assert_eq!(
+            changes(&old, &new).unwrap(),
+            vec![
+                FieldChange {
+                    path: "links.github".to_string(),
+                    old: json!("https://github.com/a"),
+                    new: json!("https://github.com/b"),
+                },
+                FieldChange {
+                    path: "prefix".to_string(),
+                    old: Value::Null,
+                    new: json!("!"),
+                },
+                FieldChange {
+                    path: "tags".to_string(),
+                    old: json!(["music"]),
+                    new: json!(["music", "fun"]),
+                },
+            ]
+        );
+        assert!(changes(&old, &old).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_redaction() {
+        let old = json!({"webhook": {"secret": "abc", "url": "https://a"}, "api_token": null});
+        let new = json!({"webhook": {"secret": "xyz", "url": "https://b"}, "api_token": "dlist_1"});
+
+        let differ = Differ::new()
+            .redact_key("secret")
+            .redact_with(|path| path.ends_with("_token"));
+        let changes = differ.diff(&old, &new).unwrap();
+
+        assert_eq!(changes[0].path, "api_token");
+        assert_eq!(changes[0].old, Value::Null);
+        assert_eq!(changes[0].new, json!(REDACTED));
+        assert_eq!(changes[1].path, "webhook.secret");
+        assert_eq!(changes[1].new, json!(REDACTED));
+        assert_eq!(changes[2].new, json!("https://b"));
+    }
+
+    #[test]
+    fn test_redacted_objects() {
+        let old = json!({"credentials": {"key": "abc", "nested": {"secret": "1"}}});
+        let new = json!({"credentials": {"key": "xyz", "nested": {"secret": "2"}}});
+
+        let changes = Differ::new()
+            .redact_key("credentials")
+            .diff(&old, &new)
+            .unwrap();
+
+        assert_eq!(
+            changes,
+            vec![FieldChange {
+                path: "credentials".to_string(),
+                old: json!(REDACTED),
+                new: json!(REDACTED),
+            }]
+        );
+    }
+}
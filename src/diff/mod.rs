@@ -0,0 +1,3 @@
+mod changeset;
+
+pub use changeset::{changes, Differ, FieldChange, REDACTED};
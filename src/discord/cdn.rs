@@ -0,0 +1,103 @@
+use url::Url;
+
+use crate::discord::ImageHash;
+use crate::types::{DiscordUrl, JsSafeBigInt};
+
+pub const CDN_BASE: &str = "https://cdn.discordapp.com";
+
+const MIN_SIZE: u32 = 16;
+const MAX_SIZE: u32 = 4096;
+
+/// Rounds the size up to the nearest power of two Discord accepts.
+pub fn clamp_size(size: u32) -> u32 {
+    size.clamp(MIN_SIZE, MAX_SIZE).next_power_of_two()
+}
+
+fn build(path: &str, hash: &ImageHash, size: u32, allow_animated: bool) -> DiscordUrl {
+    let ext = if allow_animated && hash.is_animated() {
+        "gif"
+    } else {
+        "png"
+    };
+
+    let url = format!(
+        "{}/{}/{}.{}?size={}",
+        CDN_BASE,
+        path,
+        hash,
+        ext,
+        clamp_size(size)
+    );
+    DiscordUrl(Url::parse(&url).expect("CDN urls are always valid"))
+}
+
+pub fn user_avatar(user_id: JsSafeBigInt, hash: &ImageHash, size: u32) -> DiscordUrl {
+    build(&format!("avatars/{}", user_id), hash, size, true)
+}
+
+/// The avatar shown for users without one set.
+pub fn default_avatar(user_id: JsSafeBigInt) -> DiscordUrl {
+    let index = (*user_id >> 22) % 6;
+    let url = format!("{}/embed/avatars/{}.png", CDN_BASE, index);
+    DiscordUrl(Url::parse(&url).expect("CDN urls are always valid"))
+}
+
+pub fn guild_icon(guild_id: JsSafeBigInt, hash: &ImageHash, size: u32) -> DiscordUrl {
+    build(&format!("icons/{}", guild_id), hash, size, true)
+}
+
+/// The banner of a user or guild.
+pub fn banner(id: JsSafeBigInt, hash: &ImageHash, size: u32) -> DiscordUrl {
+    build(&format!("banners/{}", id), hash, size, true)
+}
+
+/// Splashes are never animated.
+pub fn guild_splash(guild_id: JsSafeBigInt, hash: &ImageHash, size: u32) -> DiscordUrl {
+    build(&format!("splashes/{}", guild_id), hash, size, false)
+}
+
+pub fn application_icon(application_id: JsSafeBigInt, hash: &ImageHash, size: u32) -> DiscordUrl {
+    build(&format!("app-icons/{}", application_id), hash, size, false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    const HASH: &str = "1269e74af4df7417b13759eae50c83dc";
+
+    #[test]
+    fn test_size_clamping() {
+        assert_eq!(clamp_size(0), 16);
+        assert_eq!(clamp_size(100), 128);
+        assert_eq!(clamp_size(128), 128);
+        assert_eq!(clamp_size(10_000), 4096);
+    }
+
+    #[test]
+    fn test_animated_extension() {
+        let still = ImageHash::from_str(HASH).unwrap();
+        let animated = ImageHash::from_str(&format!("a_{}", HASH)).unwrap();
+
+        assert_eq!(
+            user_avatar(JsSafeBigInt(1), &still, 100).as_str(),
+            format!("https://cdn.discordapp.com/avatars/1/{}.png?size=128", HASH)
+        );
+        assert_eq!(
+            guild_icon(JsSafeBigInt(1), &animated, 512).as_str(),
+            format!("https://cdn.discordapp.com/icons/1/a_{}.gif?size=512", HASH)
+        );
+        assert!(guild_splash(JsSafeBigInt(1), &animated, 512)
+            .as_str()
+            .ends_with(".png?size=512"));
+    }
+
+    #[test]
+    fn test_default_avatar() {
+        assert_eq!(
+            default_avatar(JsSafeBigInt(80351110224678912)).as_str(),
+            "https://cdn.discordapp.com/embed/avatars/5.png"
+        );
+    }
+}
@@ -0,0 +1,410 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use reqwest::{Method, StatusCode};
+use serde::de::DeserializeOwned;
+use tokio::time::Instant;
+
+use crate::discord::{Invite, PartialGuild};
+use crate::middleware::{with_deadline, DeadlineExceeded};
+use crate::types::JsSafeBigInt;
+
+#[cfg(any(test, feature = "testing"))]
+use crate::testing::{RecordedRequest, RecordedResponse, Vcr, VcrMode};
+
+pub const API_BASE: &str = "https://discord.com/api/v10";
+
+const DEFAULT_MAX_RETRIES: u32 = 3;
+
+#[derive(Debug)]
+pub enum HttpError {
+    Request(reqwest::Error),
+    /// Discord responded with an unexpected status.
+    Status(u16, String),
+    /// The request was still rate limited after all retries.
+    RateLimited(Duration),
+    Decode(serde_json::Error),
+    DeadlineExceeded,
+}
+
+impl Display for HttpError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Request(e) => write!(f, "discord request failed: {}", e),
+            Self::Status(status, body) => write!(f, "discord returned {}: {}", status, body),
+            Self::RateLimited(v) => write!(f, "discord rate limited for {:?}", v),
+            Self::Decode(e) => write!(f, "cannot decode discord response: {}", e),
+            Self::DeadlineExceeded => write!(f, "request deadline exceeded"),
+        }
+    }
+}
+
+impl std::error::Error for HttpError {}
+
+impl From<reqwest::Error> for HttpError {
+    fn from(e: reqwest::Error) -> Self {
+        Self::Request(e)
+    }
+}
+
+impl From<DeadlineExceeded> for HttpError {
+    fn from(_: DeadlineExceeded) -> Self {
+        Self::DeadlineExceeded
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+struct BucketState {
+    remaining: u64,
+    reset_at: Instant,
+}
+
+#[derive(Default)]
+struct RateLimits {
+    /// Maps a route to the bucket Discord told us it belongs to.
+    routes: HashMap<String, String>,
+    buckets: HashMap<String, BucketState>,
+    global_until: Option<Instant>,
+}
+
+impl RateLimits {
+    /// How long to wait before the route can be requested.
+    fn wait_for(&self, route: &str, now: Instant) -> Option<Duration> {
+        let global = self.global_until.filter(|v| *v > now);
+        let bucket = self
+            .routes
+            .get(route)
+            .and_then(|v| self.buckets.get(v))
+            .filter(|v| v.remaining == 0 && v.reset_at > now)
+            .map(|v| v.reset_at);
+
+        global.max(bucket).map(|v| v - now)
+    }
+}
+
+struct RawResponse {
+    status: u16,
+    headers: HashMap<String, String>,
+    body: String,
+}
+
+/// A minimal Discord REST client which respects Discord's rate limit buckets.
+///
+/// Requests wait for exhausted buckets to reset, are retried when rate limited
+/// and respect the deadline of the current request.
+#[derive(Clone)]
+pub struct DiscordHttp {
+    client: reqwest::Client,
+    token: String,
+    base: String,
+    max_retries: u32,
+    limits: Arc<Mutex<RateLimits>>,
+    #[cfg(any(test, feature = "testing"))]
+    vcr: Option<Arc<Vcr>>,
+}
+
+impl DiscordHttp {
+    pub fn new(token: impl Into<String>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            token: token.into(),
+            base: API_BASE.to_string(),
+            max_retries: DEFAULT_MAX_RETRIES,
+            limits: Arc::default(),
+            #[cfg(any(test, feature = "testing"))]
+            vcr: None,
+        }
+    }
+
+    pub fn with_base(mut self, base: impl Into<String>) -> Self {
+        self.base = base.into();
+        self
+    }
+
+    pub fn with_max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Records or replays every request through the given recorder.
+    #[cfg(any(test, feature = "testing"))]
+    pub fn with_vcr(mut self, vcr: Arc<Vcr>) -> Self {
+        self.vcr = Some(vcr);
+        self
+    }
+
+    pub async fn get_guild(&self, guild_id: JsSafeBigInt) -> Result<PartialGuild, HttpError> {
+        self.get(&format!("/guilds/{}?with_counts=true", guild_id))
+            .await
+    }
+
+    pub async fn get_member_count(&self, guild_id: JsSafeBigInt) -> Result<Option<u64>, HttpError> {
+        Ok(self.get_guild(guild_id).await?.approximate_member_count)
+    }
+
+    /// Fetches an invite, returns `None` if the invite does not exist or has expired.
+    pub async fn get_invite(&self, code: &str) -> Result<Option<Invite>, HttpError> {
+        let path = format!("/invites/{}?with_counts=true&with_expiration=true", code);
+        match self.get(&path).await {
+            Ok(invite) => Ok(Some(invite)),
+            Err(HttpError::Status(404, _)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T, HttpError> {
+        let resp = self.request(Method::GET, path).await?;
+        serde_json::from_str(&resp.body).map_err(HttpError::Decode)
+    }
+
+    async fn request(&self, method: Method, path: &str) -> Result<RawResponse, HttpError> {
+        let route = format!("{} {}", method, path.split('?').next().unwrap_or(path));
+        let url = format!("{}{}", self.base, path);
+
+        let mut attempt = 0;
+        loop {
+            let wait = self.limits.lock().unwrap().wait_for(&route, Instant::now());
+            if let Some(wait) = wait {
+                with_deadline(tokio::time::sleep(wait)).await?;
+            }
+
+            let resp = self.send(method.clone(), &url).await?;
+            self.update_limits(&route, &resp);
+
+            if resp.status == StatusCode::TOO_MANY_REQUESTS.as_u16() {
+                let retry_after = retry_after(&resp);
+                if attempt >= self.max_retries {
+                    return Err(HttpError::RateLimited(retry_after));
+                }
+
+                attempt += 1;
+                with_deadline(tokio::time::sleep(retry_after)).await?;
+                continue;
+            }
+
+            if !(200..300).contains(&resp.status) {
+                return Err(HttpError::Status(resp.status, resp.body));
+            }
+
+            return Ok(resp);
+        }
+    }
+
+    async fn send(&self, method: Method, url: &str) -> Result<RawResponse, HttpError> {
+        #[cfg(any(test, feature = "testing"))]
+        if let Some(vcr) = self.vcr.as_ref().filter(|v| v.mode() == VcrMode::Replay) {
+            let recorded = vcr.next_response(method.as_str(), url).ok_or_else(|| {
+                HttpError::Status(404, format!("no recorded response for {} {}", method, url))
+            })?;
+
+            return Ok(RawResponse {
+                status: recorded.status,
+                headers: recorded.headers.into_iter().collect(),
+                body: recorded.body,
+            });
+        }
+
+        let req = self
+            .client
+            .request(method.clone(), url)
+            .header("Authorization", format!("Bot {}", self.token))
+            .send();
+
+        let resp = with_deadline(req).await??;
+        let status = resp.status().as_u16();
+        let headers = resp
+            .headers()
+            .iter()
+            .filter_map(|(k, v)| Some((k.as_str().to_lowercase(), v.to_str().ok()?.to_string())))
+            .collect();
+        let body = with_deadline(resp.text()).await??;
+
+        let resp = RawResponse {
+            status,
+            headers,
+            body,
+        };
+
+        #[cfg(any(test, feature = "testing"))]
+        if let Some(vcr) = self.vcr.as_ref() {
+            vcr.push(
+                RecordedRequest {
+                    method: method.to_string(),
+                    url: url.to_string(),
+                    headers: [("Authorization".to_string(), format!("Bot {}", self.token))]
+                        .into_iter()
+                        .collect(),
+                    body: None,
+                },
+                RecordedResponse {
+                    status: resp.status,
+                    headers: resp
+                        .headers
+                        .iter()
+                        .map(|(k, v)| (k.clone(), v.clone()))
+                        .collect(),
+                    body: resp.body.clone(),
+                },
+            );
+        }
+
+        Ok(resp)
+    }
+
+    fn update_limits(&self, route: &str, resp: &RawResponse) {
+        let now = Instant::now();
+        let mut limits = self.limits.lock().unwrap();
+
+        if resp.status == StatusCode::TOO_MANY_REQUESTS.as_u16()
+            && resp.headers.get("x-ratelimit-global").map(String::as_str) == Some("true")
+        {
+            limits.global_until = Some(now + retry_after(resp));
+        }
+
+        let bucket = match resp.headers.get("x-ratelimit-bucket") {
+            Some(v) => v.clone(),
+            None => return,
+        };
+
+        let remaining = header_f64(resp, "x-ratelimit-remaining").unwrap_or(1.0) as u64;
+        let reset_after = header_f64(resp, "x-ratelimit-reset-after").unwrap_or_default();
+
+        limits.routes.insert(route.to_string(), bucket.clone());
+        limits.buckets.insert(
+            bucket,
+            BucketState {
+                remaining,
+                reset_at: now + Duration::from_secs_f64(reset_after.max(0.0)),
+            },
+        );
+    }
+}
+
+fn header_f64(resp: &RawResponse, name: &str) -> Option<f64> {
+    resp.headers.get(name).and_then(|v| v.parse().ok())
+}
+
+/// The time to wait after a 429, preferring the body's more precise value.
+fn retry_after(resp: &RawResponse) -> Duration {
+    #[derive(serde::Deserialize)]
+    struct Body {
+        retry_after: f64,
+    }
+
+    let secs = serde_json::from_str::<Body>(&resp.body)
+        .map(|v| v.retry_after)
+        .ok()
+        .or_else(|| header_f64(resp, "retry-after"))
+        .unwrap_or(1.0);
+
+    Duration::from_secs_f64(secs.max(0.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{Cassette, Interaction};
+    use std::collections::BTreeMap;
+
+    fn interaction(url: &str, status: u16, headers: &[(&str, &str)], body: &str) -> Interaction {
+        Interaction {
+            request: RecordedRequest {
+                method: "GET".to_string(),
+                url: url.to_string(),
+                headers: BTreeMap::new(),
+                body: None,
+            },
+            response: RecordedResponse {
+                status,
+                headers: headers
+                    .iter()
+                    .map(|(k, v)| (k.to_string(), v.to_string()))
+                    .collect(),
+                body: body.to_string(),
+            },
+        }
+    }
+
+    fn replay(interactions: Vec<Interaction>) -> DiscordHttp {
+        let path = std::env::temp_dir().join(format!("dlist-http-{}.json", uuid::Uuid::new_v4()));
+        Cassette { interactions }.save(&path).unwrap();
+
+        let vcr = Vcr::replay(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        DiscordHttp::new("token").with_vcr(Arc::new(vcr))
+    }
+
+    fn run<F: std::future::Future>(fut: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .unwrap()
+            .block_on(fut)
+    }
+
+    #[test]
+    fn test_retries_after_rate_limit() {
+        let url = format!("{}/guilds/1?with_counts=true", API_BASE);
+        let client = replay(vec![
+            interaction(
+                &url,
+                429,
+                &[("x-ratelimit-bucket", "abc")],
+                r#"{"retry_after": 0.01, "global": false}"#,
+            ),
+            interaction(
+                &url,
+                200,
+                &[
+                    ("x-ratelimit-bucket", "abc"),
+                    ("x-ratelimit-remaining", "4"),
+                    ("x-ratelimit-reset-after", "1.5"),
+                ],
+                r#"{"id": "1", "name": "Guild", "approximate_member_count": 42}"#,
+            ),
+        ]);
+
+        let count = run(client.get_member_count(JsSafeBigInt(1))).unwrap();
+        assert_eq!(count, Some(42));
+    }
+
+    #[test]
+    fn test_missing_invite() {
+        let url = format!(
+            "{}/invites/gone?with_counts=true&with_expiration=true",
+            API_BASE
+        );
+        let client = replay(vec![interaction(
+            &url,
+            404,
+            &[],
+            r#"{"message": "Unknown Invite", "code": 10006}"#,
+        )]);
+
+        assert_eq!(run(client.get_invite("gone")).unwrap(), None);
+    }
+
+    #[test]
+    fn test_exhausted_bucket_waits() {
+        let mut limits = RateLimits::default();
+        let now = Instant::now();
+
+        limits.routes.insert("GET /guilds/1".into(), "abc".into());
+        limits.buckets.insert(
+            "abc".into(),
+            BucketState {
+                remaining: 0,
+                reset_at: now + Duration::from_secs(2),
+            },
+        );
+
+        assert_eq!(
+            limits.wait_for("GET /guilds/1", now),
+            Some(Duration::from_secs(2))
+        );
+        assert_eq!(limits.wait_for("GET /guilds/2", now), None);
+    }
+}
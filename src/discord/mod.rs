@@ -0,0 +1,13 @@
+pub mod cdn;
+#[cfg(feature = "discord-http")]
+pub mod http;
+pub mod models;
+pub mod stats;
+
+#[cfg(feature = "discord-http")]
+pub use http::{DiscordHttp, HttpError};
+pub use models::{Application, ImageHash, Invite, PartialGuild, PartialUser, Team, TeamMember};
+pub use stats::{
+    AnomalyKind, AnomalySeverity, BotStats, PlausibilityLimits, PreviousStats, ShardStats,
+    StatsAnomaly, StatsPayload,
+};
@@ -0,0 +1,276 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+#[cfg(feature = "discord-openapi")]
+use poem_openapi::Object;
+
+use crate::types::{JsSafeBigInt, Timestamp};
+
+/// The hash of a Discord avatar, icon or banner.
+///
+/// Animated images are prefixed with `a_`.
+#[derive(Clone, PartialEq, Eq, Hash, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct ImageHash(String);
+
+impl ImageHash {
+    #[inline]
+    pub fn is_animated(&self) -> bool {
+        self.0.starts_with("a_")
+    }
+
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl FromStr for ImageHash {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let hex = s.strip_prefix("a_").unwrap_or(s);
+        if hex.len() != 32 || !hex.bytes().all(|v| v.is_ascii_hexdigit()) {
+            return Err(format!("Invalid image hash {:?}", s));
+        }
+
+        Ok(Self(s.to_string()))
+    }
+}
+
+impl TryFrom<String> for ImageHash {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::from_str(&value)
+    }
+}
+
+impl From<ImageHash> for String {
+    fn from(v: ImageHash) -> Self {
+        v.0
+    }
+}
+
+impl Display for ImageHash {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[cfg(feature = "discord-openapi")]
+mod openapi {
+    use std::borrow::Cow;
+    use std::str::FromStr;
+
+    use poem_openapi::registry::{MetaSchema, MetaSchemaRef};
+    use poem_openapi::types::{ParseError, ParseFromJSON, ParseResult, ToJSON, Type};
+    use serde_json::Value;
+
+    use super::ImageHash;
+
+    impl Type for ImageHash {
+        const IS_REQUIRED: bool = true;
+        type RawValueType = Self;
+        type RawElementValueType = Self;
+
+        fn name() -> Cow<'static, str> {
+            Cow::from("ImageHash")
+        }
+
+        fn schema_ref() -> MetaSchemaRef {
+            MetaSchemaRef::Inline(Box::new(MetaSchema {
+                pattern: Some("^(a_)?[0-9a-fA-F]{32}$".to_string()),
+                ..MetaSchema::new("string")
+            }))
+        }
+
+        fn as_raw_value(&self) -> Option<&Self::RawValueType> {
+            Some(self)
+        }
+
+        fn raw_element_iter<'a>(
+            &'a self,
+        ) -> Box<dyn Iterator<Item = &'a Self::RawElementValueType> + 'a> {
+            Box::new(std::iter::once(self))
+        }
+    }
+
+    impl ToJSON for ImageHash {
+        fn to_json(&self) -> Option<Value> {
+            Some(Value::String(self.0.clone()))
+        }
+    }
+
+    impl ParseFromJSON for ImageHash {
+        fn parse_from_json(value: Option<Value>) -> ParseResult<Self> {
+            match value {
+                Some(Value::String(v)) => Self::from_str(&v).map_err(ParseError::custom),
+                other => Err(ParseError::custom(format!(
+                    "Expected image hash got {:?}",
+                    other
+                ))),
+            }
+        }
+    }
+}
+
+#[cfg_attr(feature = "discord-openapi", derive(Object))]
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct PartialUser {
+    pub id: JsSafeBigInt,
+    pub username: String,
+    /// Users which have migrated to unique usernames have a discriminator of `0`.
+    #[serde(default)]
+    pub discriminator: Option<String>,
+    #[serde(default)]
+    pub global_name: Option<String>,
+    #[serde(default)]
+    pub avatar: Option<ImageHash>,
+    #[serde(default)]
+    pub bot: bool,
+    #[serde(default)]
+    pub public_flags: Option<u64>,
+}
+
+impl PartialUser {
+    /// The name shown for the user, preferring their global display name.
+    pub fn display_name(&self) -> &str {
+        self.global_name.as_deref().unwrap_or(&self.username)
+    }
+}
+
+#[cfg_attr(feature = "discord-openapi", derive(Object))]
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct PartialGuild {
+    pub id: JsSafeBigInt,
+    pub name: String,
+    #[serde(default)]
+    pub icon: Option<ImageHash>,
+    /// Only present on the current user's guild list.
+    #[serde(default)]
+    pub owner: Option<bool>,
+    /// The current user's permissions in the guild, as a stringified bitfield.
+    #[serde(default)]
+    pub permissions: Option<String>,
+    #[serde(default)]
+    pub features: Vec<String>,
+    #[serde(default)]
+    pub approximate_member_count: Option<u64>,
+    #[serde(default)]
+    pub approximate_presence_count: Option<u64>,
+}
+
+#[cfg_attr(feature = "discord-openapi", derive(Object))]
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct TeamMember {
+    /// `1` if the user has been invited, `2` once they have accepted.
+    pub membership_state: u8,
+    pub team_id: JsSafeBigInt,
+    pub user: PartialUser,
+    #[serde(default)]
+    pub role: Option<String>,
+}
+
+#[cfg_attr(feature = "discord-openapi", derive(Object))]
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Team {
+    pub id: JsSafeBigInt,
+    pub name: String,
+    #[serde(default)]
+    pub icon: Option<ImageHash>,
+    pub owner_user_id: JsSafeBigInt,
+    #[serde(default)]
+    pub members: Vec<TeamMember>,
+}
+
+#[cfg_attr(feature = "discord-openapi", derive(Object))]
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Application {
+    pub id: JsSafeBigInt,
+    pub name: String,
+    #[serde(default)]
+    pub icon: Option<ImageHash>,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub bot_public: bool,
+    #[serde(default)]
+    pub bot_require_code_grant: bool,
+    #[serde(default)]
+    pub owner: Option<PartialUser>,
+    #[serde(default)]
+    pub team: Option<Team>,
+}
+
+impl Application {
+    /// The ids of everyone allowed to manage the application's listing.
+    pub fn owner_ids(&self) -> Vec<JsSafeBigInt> {
+        if let Some(team) = self.team.as_ref() {
+            return team
+                .members
+                .iter()
+                .filter(|v| v.membership_state == 2)
+                .map(|v| v.user.id)
+                .collect();
+        }
+
+        self.owner.iter().map(|v| v.id).collect()
+    }
+}
+
+#[cfg_attr(feature = "discord-openapi", derive(Object))]
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Invite {
+    pub code: String,
+    /// Missing for group DM invites.
+    #[serde(default)]
+    pub guild: Option<PartialGuild>,
+    #[serde(default)]
+    pub approximate_member_count: Option<u64>,
+    #[serde(default)]
+    pub approximate_presence_count: Option<u64>,
+    /// `None` for invites which never expire.
+    #[serde(default)]
+    pub expires_at: Option<Timestamp>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::Tolerant;
+    use serde_json::json;
+
+    #[test]
+    fn test_image_hash() {
+        let hash = ImageHash::from_str("a_1269e74af4df7417b13759eae50c83dc").unwrap();
+        assert!(hash.is_animated());
+        assert!(!ImageHash::from_str("1269e74af4df7417b13759eae50c83dc")
+            .unwrap()
+            .is_animated());
+        assert!(ImageHash::from_str("not-a-hash").is_err());
+    }
+
+    #[test]
+    fn test_application_owners() {
+        let app: Tolerant<Application> = serde_json::from_value(json!({
+            "id": "1234",
+            "name": "Cool Bot",
+            "icon": null,
+            "flags": 0,
+            "team": {
+                "id": "99",
+                "name": "Team",
+                "owner_user_id": "1",
+                "members": [
+                    {"membership_state": 2, "team_id": "99", "user": {"id": "1", "username": "a"}},
+                    {"membership_state": 1, "team_id": "99", "user": {"id": "2", "username": "b"}},
+                ],
+            },
+        }))
+        .unwrap();
+
+        assert_eq!(app.owner_ids(), vec![JsSafeBigInt(1)]);
+        assert!(app.unknown.contains_key("flags"));
+    }
+}
@@ -0,0 +1,542 @@
+use std::collections::BTreeSet;
+use std::fmt::{Display, Formatter};
+
+#[cfg(feature = "discord-openapi")]
+use poem_openapi::{Enum, Object};
+
+use crate::errors::{ApiError, ApiResult};
+use crate::types::Timestamp;
+use crate::validation::{Validate, ValidationErrors};
+
+/// Discord requires sharding far below this, anything above is a bogus post.
+pub const MAX_SHARD_COUNT: i32 = 65_536;
+
+#[cfg_attr(feature = "discord-openapi", derive(Object))]
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ShardStats {
+    pub shard_id: i32,
+    pub guild_count: i64,
+    /// The gateway heartbeat latency in milliseconds.
+    #[serde(default)]
+    pub latency: Option<f64>,
+}
+
+/// The validated stats of a bot, either for every shard or the subset of
+/// shards a single cluster posted.
+#[cfg_attr(feature = "discord-openapi", derive(Object))]
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct BotStats {
+    pub guild_count: i64,
+    pub shard_count: Option<i32>,
+    #[serde(default)]
+    pub shards: Vec<ShardStats>,
+}
+
+impl BotStats {
+    /// Creates the stats of a bot from its shards.
+    pub fn from_shards(shard_count: Option<i32>, shards: Vec<ShardStats>) -> ApiResult<Self> {
+        let stats = Self {
+            guild_count: shard_guild_total(&shards)?,
+            shard_count,
+            shards,
+        };
+
+        stats.validate()?;
+        Ok(stats)
+    }
+
+    /// The mean latency of the shards which reported one.
+    pub fn average_latency(&self) -> Option<f64> {
+        let latencies = self
+            .shards
+            .iter()
+            .filter_map(|v| v.latency)
+            .collect::<Vec<_>>();
+
+        (!latencies.is_empty()).then(|| latencies.iter().sum::<f64>() / latencies.len() as f64)
+    }
+
+    pub fn validate(&self) -> ApiResult<()> {
+        Ok(self.check()?)
+    }
+}
+
+impl Validate for ShardStats {
+    fn validate_fields(&self, errors: &mut ValidationErrors) {
+        if self.guild_count < 0 {
+            errors.add(
+                "guild_count",
+                format!(
+                    "The guild count of shard {} cannot be negative.",
+                    self.shard_id
+                ),
+            );
+        }
+
+        if self.latency.is_some_and(|v| !v.is_finite() || v < 0.0) {
+            errors.add(
+                "latency",
+                format!(
+                    "The latency of shard {} must be a positive number.",
+                    self.shard_id
+                ),
+            );
+        }
+    }
+}
+
+impl Validate for BotStats {
+    fn validate_fields(&self, errors: &mut ValidationErrors) {
+        if self.guild_count < 0 {
+            errors.add("guild_count", "The guild count cannot be negative.");
+        }
+
+        if let Some(shard_count) = self.shard_count {
+            if !(1..=MAX_SHARD_COUNT).contains(&shard_count) {
+                errors.add(
+                    "shard_count",
+                    format!("The shard count must be between 1 and {}.", MAX_SHARD_COUNT),
+                );
+            }
+
+            if self.shards.len() > shard_count.max(0) as usize {
+                errors.add("shards", "More shards were given than the shard count.");
+            }
+        }
+
+        let max_shard_id = self.shard_count.unwrap_or(MAX_SHARD_COUNT);
+        let mut seen = BTreeSet::new();
+        for (i, shard) in self.shards.iter().enumerate() {
+            if !(0..max_shard_id).contains(&shard.shard_id) {
+                errors.add(
+                    format!("shards.{}.shard_id", i),
+                    format!("Shard {} is outside of the shard count.", shard.shard_id),
+                );
+            }
+
+            if !seen.insert(shard.shard_id) {
+                errors.add(
+                    format!("shards.{}.shard_id", i),
+                    format!("Shard {} was given more than once.", shard.shard_id),
+                );
+            }
+
+            errors.nested(format_args!("shards.{}", i), shard);
+        }
+
+        match checked_guild_total(&self.shards) {
+            Some(total) if total > self.guild_count => errors.add(
+                "guild_count",
+                "The guild count is lower than the guild counts of its shards.",
+            ),
+            Some(_) => {}
+            None => errors.add("shards", SHARD_TOTAL_OVERFLOW),
+        }
+    }
+}
+
+const SHARD_TOTAL_OVERFLOW: &str = "The guild counts of the shards are too large.";
+
+/// Sums the guild counts of the shards, `None` if the total overflows.
+fn checked_guild_total(shards: &[ShardStats]) -> Option<i64> {
+    shards
+        .iter()
+        .try_fold(0i64, |acc, v| acc.checked_add(v.guild_count))
+}
+
+fn shard_guild_total(shards: &[ShardStats]) -> ApiResult<i64> {
+    checked_guild_total(shards).ok_or_else(|| ApiError::validation(SHARD_TOTAL_OVERFLOW))
+}
+
+/// The body of the stats endpoint.
+///
+/// Library integrations post slightly different shapes, `guild_count` and
+/// `server_count` are interchangeable and a single shard may post its own
+/// count along with its `shard_id`. Use [StatsPayload::into_stats] to get
+/// the normalised and validated [BotStats].
+#[cfg_attr(feature = "discord-openapi", derive(Object))]
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct StatsPayload {
+    #[serde(default)]
+    pub guild_count: Option<i64>,
+    /// An alias of `guild_count`.
+    #[serde(default)]
+    pub server_count: Option<i64>,
+    #[serde(default)]
+    pub shard_count: Option<i32>,
+    /// The shard posting the counts, when a single shard posts its own stats.
+    #[serde(default)]
+    pub shard_id: Option<i32>,
+    #[serde(default)]
+    pub shards: Option<Vec<ShardStats>>,
+}
+
+impl StatsPayload {
+    pub fn into_stats(self) -> ApiResult<BotStats> {
+        let guild_count = self.guild_count.or(self.server_count);
+        let mut shards = self.shards.unwrap_or_default();
+
+        if let Some(shard_id) = self.shard_id {
+            if !shards.is_empty() {
+                return Err(ApiError::validation(
+                    "Either a shard id or a list of shards can be given, not both.",
+                ));
+            }
+
+            let guild_count = guild_count.ok_or_else(|| {
+                ApiError::validation("A guild count is required when posting a shard.")
+            })?;
+            shards.push(ShardStats {
+                shard_id,
+                guild_count,
+                latency: None,
+            });
+        }
+
+        let guild_count = match guild_count {
+            Some(v) => v,
+            None if !shards.is_empty() => shard_guild_total(&shards)?,
+            None => return Err(ApiError::validation("A guild count is required.")),
+        };
+
+        let stats = BotStats {
+            guild_count,
+            shard_count: self.shard_count,
+            shards,
+        };
+
+        stats.validate()?;
+        Ok(stats)
+    }
+}
+
+/// How far a bot's guild count may move between two posts.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PlausibilityLimits {
+    /// The fraction a guild count may grow by per day of elapsed time.
+    pub max_daily_growth: f64,
+    /// Growth allowed per day regardless of the rate, so small bots joining
+    /// a popular server are not flagged.
+    pub min_allowed_growth: i64,
+    /// Growth above this fraction of the allowed growth is flagged.
+    pub suspicious_fraction: f64,
+    /// Drops above this fraction of the previous count are flagged.
+    pub max_drop: f64,
+}
+
+impl Default for PlausibilityLimits {
+    fn default() -> Self {
+        Self {
+            max_daily_growth: 0.5,
+            min_allowed_growth: 250,
+            suspicious_fraction: 0.5,
+            max_drop: 0.5,
+        }
+    }
+}
+
+/// The last accepted guild count of a bot.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PreviousStats {
+    pub guild_count: i64,
+    pub posted_at: Timestamp,
+}
+
+#[cfg_attr(feature = "discord-openapi", derive(Enum))]
+#[cfg_attr(feature = "discord-openapi", oai(rename_all = "snake_case"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnomalyKind {
+    /// The count grew faster than any real bot could.
+    GrowthTooFast,
+    /// The count grew unusually fast but not impossibly so.
+    SuspiciousJump,
+    /// The count dropped sharply, usually shards failing to connect.
+    SuddenDrop,
+}
+
+#[cfg_attr(feature = "discord-openapi", derive(Enum))]
+#[cfg_attr(feature = "discord-openapi", oai(rename_all = "snake_case"))]
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, serde::Serialize, serde::Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum AnomalySeverity {
+    /// The stats are accepted but should be reviewed.
+    Flag,
+    /// The stats must not be shown until reviewed.
+    Quarantine,
+}
+
+/// A guild count which is implausible compared to the previous post.
+#[cfg_attr(feature = "discord-openapi", derive(Object))]
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct StatsAnomaly {
+    pub kind: AnomalyKind,
+    pub severity: AnomalySeverity,
+    pub previous: i64,
+    pub current: i64,
+    /// The highest count which would have been accepted.
+    pub allowed: i64,
+}
+
+impl StatsAnomaly {
+    #[inline]
+    pub fn should_quarantine(&self) -> bool {
+        self.severity == AnomalySeverity::Quarantine
+    }
+}
+
+impl Display for StatsAnomaly {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:?} from {} to {} guilds (allowed up to {})",
+            self.kind, self.previous, self.current, self.allowed
+        )
+    }
+}
+
+impl std::error::Error for StatsAnomaly {}
+
+impl BotStats {
+    /// Compares the guild count against the previously accepted post.
+    ///
+    /// The allowed growth scales with the time since the previous accepted
+    /// post, so posting more often does not allow a bot to grow any faster.
+    pub fn check_plausibility(
+        &self,
+        previous: Option<&PreviousStats>,
+        now: &Timestamp,
+        limits: &PlausibilityLimits,
+    ) -> Result<(), StatsAnomaly> {
+        let Some(previous) = previous else {
+            return Ok(());
+        };
+
+        let elapsed_days =
+            (now.timestamp() - previous.posted_at.timestamp()).max(0) as f64 / 86_400.0;
+        let daily_growth = (previous.guild_count as f64 * limits.max_daily_growth)
+            .max(limits.min_allowed_growth as f64);
+        let allowed_growth = (daily_growth * elapsed_days).ceil() as i64;
+        let growth = self.guild_count - previous.guild_count;
+
+        let anomaly = |kind, severity| StatsAnomaly {
+            kind,
+            severity,
+            previous: previous.guild_count,
+            current: self.guild_count,
+            allowed: previous.guild_count.saturating_add(allowed_growth),
+        };
+
+        if growth > allowed_growth {
+            return Err(anomaly(
+                AnomalyKind::GrowthTooFast,
+                AnomalySeverity::Quarantine,
+            ));
+        }
+
+        if growth > limits.min_allowed_growth
+            && growth as f64 > allowed_growth as f64 * limits.suspicious_fraction
+        {
+            return Err(anomaly(AnomalyKind::SuspiciousJump, AnomalySeverity::Flag));
+        }
+
+        let drop = -growth;
+        if drop > limits.min_allowed_growth
+            && drop as f64 > previous.guild_count as f64 * limits.max_drop
+        {
+            return Err(anomaly(AnomalyKind::SuddenDrop, AnomalySeverity::Flag));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn parse(value: serde_json::Value) -> ApiResult<BotStats> {
+        serde_json::from_value::<StatsPayload>(value)
+            .unwrap()
+            .into_stats()
+    }
+
+    #[test]
+    fn test_library_shapes() {
+        let simple = parse(json!({ "server_count": 120, "shard_count": 2 })).unwrap();
+        assert_eq!(simple.guild_count, 120);
+        assert!(simple.shards.is_empty());
+
+        let single = parse(json!({ "guild_count": 60, "shard_id": 1, "shard_count": 2 })).unwrap();
+        assert_eq!(single.shards[0].shard_id, 1);
+        assert_eq!(single.shards[0].guild_count, 60);
+
+        let sharded = parse(json!({
+            "shard_count": 2,
+            "shards": [
+                { "shard_id": 0, "guild_count": 50, "latency": 40.0 },
+                { "shard_id": 1, "guild_count": 70, "latency": 60.0 },
+            ],
+        }))
+        .unwrap();
+        assert_eq!(sharded.guild_count, 120);
+        assert_eq!(sharded.average_latency(), Some(50.0));
+    }
+
+    #[test]
+    fn test_validation() {
+        assert!(parse(json!({})).is_err());
+        assert!(parse(json!({ "guild_count": -1 })).is_err());
+        assert!(parse(json!({ "guild_count": 1, "shard_count": 0 })).is_err());
+        assert!(parse(json!({ "guild_count": 1, "shard_id": 2, "shard_count": 2 })).is_err());
+        assert!(parse(json!({
+            "guild_count": 10,
+            "shards": [
+                { "shard_id": 0, "guild_count": 5 },
+                { "shard_id": 0, "guild_count": 5 },
+            ],
+        }))
+        .is_err());
+        assert!(parse(json!({
+            "guild_count": 10,
+            "shards": [{ "shard_id": 0, "guild_count": 50 }],
+        }))
+        .is_err());
+    }
+
+    #[test]
+    fn test_guild_total_overflow() {
+        let shards = json!([
+            { "shard_id": 0, "guild_count": i64::MAX },
+            { "shard_id": 1, "guild_count": 1 },
+        ]);
+
+        assert!(parse(json!({ "shards": shards })).is_err());
+        assert!(parse(json!({ "guild_count": i64::MAX, "shards": shards })).is_err());
+
+        let shards = serde_json::from_value(shards).unwrap();
+        assert!(BotStats::from_shards(None, shards).is_err());
+    }
+
+    #[test]
+    fn test_validation_paths() {
+        let stats = BotStats {
+            guild_count: 10,
+            shard_count: Some(2),
+            shards: vec![
+                ShardStats {
+                    shard_id: 0,
+                    guild_count: 5,
+                    latency: Some(-1.0),
+                },
+                ShardStats {
+                    shard_id: 0,
+                    guild_count: 5,
+                    latency: None,
+                },
+            ],
+        };
+
+        let errors = stats.check().unwrap_err();
+        assert_eq!(errors.len(), 2);
+        assert_eq!(
+            errors.get("shards.0.latency"),
+            Some("The latency of shard 0 must be a positive number.")
+        );
+        assert_eq!(
+            errors.get("shards.1.shard_id"),
+            Some("Shard 0 was given more than once.")
+        );
+    }
+
+    fn stats(guild_count: i64) -> BotStats {
+        BotStats {
+            guild_count,
+            shard_count: None,
+            shards: vec![],
+        }
+    }
+
+    #[test]
+    fn test_plausibility() {
+        let limits = PlausibilityLimits::default();
+        let previous = PreviousStats {
+            guild_count: 10_000,
+            posted_at: Timestamp::from(1_700_000_000),
+        };
+        let day_later = Timestamp::from(1_700_000_000 + 24 * 60 * 60);
+        let check =
+            |count: i64| stats(count).check_plausibility(Some(&previous), &day_later, &limits);
+
+        assert!(stats(1_000_000)
+            .check_plausibility(None, &day_later, &limits)
+            .is_ok());
+        assert!(check(10_500).is_ok());
+        assert!(check(9_000).is_ok());
+
+        let jump = check(13_000).unwrap_err();
+        assert_eq!(jump.kind, AnomalyKind::SuspiciousJump);
+        assert!(!jump.should_quarantine());
+
+        let fraud = check(50_000).unwrap_err();
+        assert_eq!(fraud.kind, AnomalyKind::GrowthTooFast);
+        assert_eq!(fraud.allowed, 15_000);
+        assert!(fraud.should_quarantine());
+
+        assert_eq!(check(2_000).unwrap_err().kind, AnomalyKind::SuddenDrop);
+    }
+
+    #[test]
+    fn test_small_bots_may_grow() {
+        let previous = PreviousStats {
+            guild_count: 3,
+            posted_at: Timestamp::from(1_700_000_000),
+        };
+        let limits = PlausibilityLimits::default();
+
+        let day_later = Timestamp::from(1_700_000_000 + 24 * 60 * 60);
+        assert!(stats(200)
+            .check_plausibility(Some(&previous), &day_later, &limits)
+            .is_ok());
+
+        let minute_later = Timestamp::from(1_700_000_060);
+        assert!(stats(4)
+            .check_plausibility(Some(&previous), &minute_later, &limits)
+            .is_ok());
+        assert!(stats(200)
+            .check_plausibility(Some(&previous), &minute_later, &limits)
+            .is_err());
+    }
+
+    #[test]
+    fn test_frequent_posts_do_not_add_up() {
+        let limits = PlausibilityLimits::default();
+        let mut previous = PreviousStats {
+            guild_count: 10_000,
+            posted_at: Timestamp::from(1_700_000_000),
+        };
+
+        // Posting every minute at the allowed rate reaches the same count
+        // as posting once a day.
+        for _ in 0..24 * 60 {
+            let now = Timestamp::from(previous.posted_at.timestamp() + 60);
+            let count = previous.guild_count + 4;
+            stats(count)
+                .check_plausibility(Some(&previous), &now, &limits)
+                .unwrap();
+            previous = PreviousStats {
+                guild_count: count,
+                posted_at: now,
+            };
+        }
+        assert!(previous.guild_count < 16_000);
+
+        let now = Timestamp::from(previous.posted_at.timestamp() + 60);
+        assert!(stats(previous.guild_count + 500)
+            .check_plausibility(Some(&previous), &now, &limits)
+            .is_err());
+    }
+}
@@ -0,0 +1,161 @@
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::time::Duration;
+
+#[cfg(feature = "scylla")]
+use scylla::transport::errors::QueryError;
+
+/// A stable, machine-readable error code.
+///
+/// Clients match on these so variants must never be renamed.
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    strum::AsRefStr,
+    strum::EnumString,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[cfg_attr(feature = "poem", derive(poem_openapi::Enum))]
+#[cfg_attr(feature = "poem", oai(rename_all = "snake_case"))]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    ValidationFailed,
+    Unauthorized,
+    Forbidden,
+    NotFound,
+    RateLimited,
+    InternalError,
+}
+
+#[cfg_attr(feature = "poem", derive(poem_openapi::Object))]
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ErrorBody {
+    pub code: ErrorCode,
+    /// A human readable description of the error.
+    pub message: String,
+    /// The problem with each invalid field keyed by its path, e.g. `links.github`.
+    #[cfg_attr(feature = "poem", oai(skip_serializing_if_is_none))]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub errors: Option<BTreeMap<String, String>>,
+}
+
+pub type ApiResult<T> = Result<T, ApiError>;
+
+/// The error returned by all of our APIs.
+///
+/// The responses are rendered by the `poem` and `axum` integrations, both
+/// use the same status codes and body.
+#[derive(Debug)]
+pub enum ApiError {
+    /// 400
+    Validation(ErrorBody),
+    /// 403
+    Forbidden(ErrorBody),
+    /// 404
+    NotFound(ErrorBody),
+    /// 429, with the number of seconds to wait before retrying.
+    RateLimited(ErrorBody, u64),
+    /// 500
+    Internal(ErrorBody),
+    /// Any other status, e.g. `401` or `415` when a request is rejected
+    /// before reaching the handler.
+    Status(u16, ErrorBody),
+}
+
+impl ApiError {
+    pub fn validation(message: impl Into<String>) -> Self {
+        Self::Validation(body(ErrorCode::ValidationFailed, message))
+    }
+
+    pub fn forbidden(message: impl Into<String>) -> Self {
+        Self::Forbidden(body(ErrorCode::Forbidden, message))
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::NotFound(body(ErrorCode::NotFound, message))
+    }
+
+    pub fn rate_limited(retry_after: Duration) -> Self {
+        // Round up so clients never retry before the limit resets.
+        let secs = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+        Self::RateLimited(
+            body(ErrorCode::RateLimited, "You are being rate limited."),
+            secs,
+        )
+    }
+
+    /// An internal error, the cause is deliberately not exposed to the client.
+    pub fn internal() -> Self {
+        Self::Internal(body(
+            ErrorCode::InternalError,
+            "An internal error occurred, please try again later.",
+        ))
+    }
+
+    pub fn body(&self) -> &ErrorBody {
+        match self {
+            Self::Validation(v) | Self::Forbidden(v) | Self::NotFound(v) | Self::Internal(v) => v,
+            Self::RateLimited(v, _) | Self::Status(_, v) => v,
+        }
+    }
+
+    #[inline]
+    pub fn code(&self) -> ErrorCode {
+        self.body().code
+    }
+}
+
+impl Display for ApiError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let body = self.body();
+        write!(f, "{}: {}", body.code.as_ref(), body.message)
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+#[cfg(feature = "scylla")]
+impl From<QueryError> for ApiError {
+    fn from(e: QueryError) -> Self {
+        tracing::error!(error = %e, "database query failed");
+        Self::internal()
+    }
+}
+
+#[inline]
+fn body(code: ErrorCode, message: impl Into<String>) -> ErrorBody {
+    ErrorBody {
+        code,
+        message: message.into(),
+        errors: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate_limited() {
+        let err = ApiError::rate_limited(Duration::from_millis(1500));
+        assert_eq!(err.code(), ErrorCode::RateLimited);
+        assert!(matches!(err, ApiError::RateLimited(_, 2)));
+    }
+
+    #[cfg(feature = "scylla")]
+    #[test]
+    fn test_query_errors_are_internal() {
+        let err = ApiError::from(QueryError::TimeoutError);
+        assert_eq!(err.code(), ErrorCode::InternalError);
+        assert_eq!(
+            err.to_string(),
+            "internal_error: An internal error occurred, please try again later."
+        );
+    }
+}
@@ -0,0 +1,7 @@
+mod api;
+#[cfg(feature = "poem")]
+mod openapi;
+
+pub use api::{ApiError, ApiResult, ErrorBody, ErrorCode};
+#[cfg(feature = "poem")]
+pub use openapi::bad_request_handler;
@@ -0,0 +1,186 @@
+//! `poem_openapi` responses for [ApiError], enabled by the `poem` feature.
+
+use poem::http::StatusCode;
+use poem::{IntoResponse, Response};
+use poem_openapi::payload::Json;
+use poem_openapi::registry::{MetaResponses, Registry};
+use poem_openapi::types::{ParseError, Type};
+use poem_openapi::ApiResponse;
+
+use crate::errors::{ApiError, ErrorBody, ErrorCode};
+
+/// The documented responses of [ApiError], kept separate so the error
+/// itself doesn't depend on poem.
+#[derive(ApiResponse)]
+enum ErrorResponse {
+    #[oai(status = 400)]
+    Validation(Json<ErrorBody>),
+    #[oai(status = 403)]
+    Forbidden(Json<ErrorBody>),
+    #[oai(status = 404)]
+    NotFound(Json<ErrorBody>),
+    #[oai(status = 429)]
+    RateLimited(
+        Json<ErrorBody>,
+        /// The number of seconds to wait before retrying.
+        #[oai(header = "Retry-After")]
+        u64,
+    ),
+    #[oai(status = 500)]
+    Internal(Json<ErrorBody>),
+    Status(StatusCode, Json<ErrorBody>),
+}
+
+impl From<ApiError> for ErrorResponse {
+    fn from(e: ApiError) -> Self {
+        match e {
+            ApiError::Validation(v) => Self::Validation(Json(v)),
+            ApiError::Forbidden(v) => Self::Forbidden(Json(v)),
+            ApiError::NotFound(v) => Self::NotFound(Json(v)),
+            ApiError::RateLimited(v, secs) => Self::RateLimited(Json(v), secs),
+            ApiError::Internal(v) => Self::Internal(Json(v)),
+            ApiError::Status(status, v) => Self::Status(
+                StatusCode::from_u16(status).unwrap_or(StatusCode::BAD_REQUEST),
+                Json(v),
+            ),
+        }
+    }
+}
+
+impl ApiResponse for ApiError {
+    const BAD_REQUEST_HANDLER: bool = true;
+
+    fn meta() -> MetaResponses {
+        ErrorResponse::meta()
+    }
+
+    fn register(registry: &mut Registry) {
+        ErrorResponse::register(registry);
+    }
+
+    fn from_parse_request_error(err: poem::Error) -> Self {
+        bad_request_handler(err)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        ErrorResponse::from(self).into_response()
+    }
+}
+
+impl From<ApiError> for poem::Error {
+    fn from(e: ApiError) -> Self {
+        poem::Error::from(ErrorResponse::from(e))
+    }
+}
+
+impl<T: Type> From<ParseError<T>> for ApiError {
+    fn from(e: ParseError<T>) -> Self {
+        Self::validation(e.into_message())
+    }
+}
+
+/// Converts request parsing failures into the standard errors, keeping the
+/// status of the failure, e.g. `401` for a missing API key.
+pub fn bad_request_handler(e: poem::Error) -> ApiError {
+    let status = e.status();
+
+    match status {
+        StatusCode::BAD_REQUEST => ApiError::validation(e.to_string()),
+        StatusCode::FORBIDDEN => ApiError::forbidden(e.to_string()),
+        StatusCode::NOT_FOUND => ApiError::not_found(e.to_string()),
+        StatusCode::UNAUTHORIZED => ApiError::Status(
+            status.as_u16(),
+            ErrorBody {
+                code: ErrorCode::Unauthorized,
+                message: e.to_string(),
+                errors: None,
+            },
+        ),
+        _ if status.is_server_error() => {
+            tracing::error!(error = %e, "failed to read request");
+            ApiError::internal()
+        }
+        _ => ApiError::Status(
+            status.as_u16(),
+            ErrorBody {
+                code: ErrorCode::ValidationFailed,
+                message: e.to_string(),
+                errors: None,
+            },
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use poem::http::StatusCode;
+
+    use super::*;
+    use crate::errors::ErrorCode;
+
+    #[test]
+    fn test_rate_limited_response() {
+        let resp = ApiError::rate_limited(Duration::from_millis(1500)).into_response();
+
+        assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(resp.headers().get("Retry-After").unwrap(), "2");
+    }
+
+    #[test]
+    fn test_conversions() {
+        let err = ApiError::from(ParseError::<i64>::custom("bad number"));
+        assert_eq!(err.code(), ErrorCode::ValidationFailed);
+        assert!(err.body().message.contains("bad number"));
+
+        assert_eq!(
+            ApiError::internal().into_response().status(),
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+        assert_eq!(
+            poem::Error::from(ApiError::forbidden("No.")).status(),
+            StatusCode::FORBIDDEN
+        );
+    }
+
+    #[test]
+    fn test_bad_request_handler() {
+        let err = bad_request_handler(poem::Error::from_string("bad id", StatusCode::BAD_REQUEST));
+        assert_eq!(err.code(), ErrorCode::ValidationFailed);
+        assert_eq!(err.into_response().status(), StatusCode::BAD_REQUEST);
+
+        let err = bad_request_handler(poem::Error::from_status(StatusCode::UNAUTHORIZED));
+        assert_eq!(err.code(), ErrorCode::Unauthorized);
+        assert_eq!(err.into_response().status(), StatusCode::UNAUTHORIZED);
+
+        let err = bad_request_handler(poem::Error::from_status(StatusCode::UNSUPPORTED_MEDIA_TYPE));
+        assert_eq!(err.code(), ErrorCode::ValidationFailed);
+        assert_eq!(
+            err.into_response().status(),
+            StatusCode::UNSUPPORTED_MEDIA_TYPE
+        );
+
+        let err = bad_request_handler(poem::Error::from_string(
+            "pool exhausted",
+            StatusCode::SERVICE_UNAVAILABLE,
+        ));
+        assert_eq!(err.code(), ErrorCode::InternalError);
+        assert!(!err.body().message.contains("pool"));
+    }
+
+    #[test]
+    fn test_documented_statuses() {
+        let statuses = ApiError::meta()
+            .responses
+            .iter()
+            .map(|v| v.status)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            statuses,
+            [Some(400), Some(403), Some(404), Some(429), Some(500), None]
+        );
+    }
+}
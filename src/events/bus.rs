@@ -0,0 +1,97 @@
+use std::fmt::{Debug, Display, Formatter};
+use std::marker::PhantomData;
+use std::pin::Pin;
+
+use futures_util::{Stream, StreamExt};
+
+use crate::codec::{CodecError, Versioned, VersionedDecode};
+
+/// An event which is published on its own topic.
+///
+/// Payloads are encoded with [Versioned] so subscribers can keep decoding
+/// events published by services running an older version of the payload.
+pub trait Event: VersionedDecode + Clone + Send + Sync {
+    const TOPIC: &'static str;
+
+    fn to_payload(&self) -> Result<Vec<u8>, EventError> {
+        Ok(Versioned::encode(self)?)
+    }
+
+    fn from_payload(bytes: &[u8]) -> Result<Self, EventError> {
+        Ok(Versioned::decode(bytes)?)
+    }
+}
+
+#[derive(Debug)]
+pub enum EventError {
+    Codec(CodecError),
+    /// The bus could not deliver or receive events.
+    Backend(String),
+    /// The event could not be staged without splitting the batch, so it
+    /// would not be written atomically with the change.
+    BatchFull,
+}
+
+impl Display for EventError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Codec(e) => write!(f, "invalid event payload: {}", e),
+            Self::Backend(e) => write!(f, "event bus error: {}", e),
+            Self::BatchFull => write!(f, "staging the event would split the batch"),
+        }
+    }
+}
+
+impl std::error::Error for EventError {}
+
+impl From<CodecError> for EventError {
+    fn from(e: CodecError) -> Self {
+        Self::Codec(e)
+    }
+}
+
+#[async_trait::async_trait]
+pub trait Publisher: Send + Sync {
+    /// Publishes an already encoded payload on `topic`, used to forward
+    /// events without knowing their type.
+    async fn publish_raw(&self, topic: &str, payload: Vec<u8>) -> Result<(), EventError>;
+
+    async fn publish<E: Event>(&self, event: &E) -> Result<(), EventError> {
+        self.publish_raw(E::TOPIC, event.to_payload()?).await
+    }
+}
+
+#[async_trait::async_trait]
+pub trait Subscriber: Send + Sync {
+    async fn subscribe<E: Event>(&self) -> Result<Subscription<E>, EventError>;
+}
+
+/// A stream of events from a single topic.
+pub struct Subscription<E> {
+    payloads: Pin<Box<dyn Stream<Item = Vec<u8>> + Send>>,
+    _event: PhantomData<fn() -> E>,
+}
+
+impl<E: Event> Subscription<E> {
+    pub fn new(payloads: impl Stream<Item = Vec<u8>> + Send + 'static) -> Self {
+        Self {
+            payloads: Box::pin(payloads),
+            _event: PhantomData,
+        }
+    }
+
+    /// Waits for the next event, returns `None` once the bus is closed.
+    ///
+    /// Payloads which cannot be decoded are returned as errors rather than
+    /// ending the subscription.
+    pub async fn recv(&mut self) -> Option<Result<E, EventError>> {
+        let payload = self.payloads.next().await?;
+        Some(E::from_payload(&payload))
+    }
+}
+
+impl<E> Debug for Subscription<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Subscription").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,127 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use futures_util::StreamExt;
+use tokio::sync::broadcast;
+use tokio_stream::wrappers::errors::BroadcastStreamRecvError;
+use tokio_stream::wrappers::BroadcastStream;
+
+use crate::events::{Event, EventError, Publisher, Subscriber, Subscription};
+
+pub const DEFAULT_CAPACITY: usize = 1024;
+
+/// An in-process bus backed by a broadcast channel per topic.
+///
+/// Slow subscribers which fall more than `capacity` events behind skip the
+/// events they missed.
+pub struct LocalBus {
+    capacity: usize,
+    topics: Mutex<HashMap<String, broadcast::Sender<Vec<u8>>>>,
+}
+
+impl Default for LocalBus {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
+
+impl LocalBus {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            topics: Mutex::default(),
+        }
+    }
+
+    fn sender(&self, topic: &str) -> broadcast::Sender<Vec<u8>> {
+        self.topics
+            .lock()
+            .unwrap()
+            .entry(topic.to_string())
+            .or_insert_with(|| broadcast::channel(self.capacity).0)
+            .clone()
+    }
+}
+
+#[async_trait::async_trait]
+impl Publisher for LocalBus {
+    async fn publish_raw(&self, topic: &str, payload: Vec<u8>) -> Result<(), EventError> {
+        // Publishing without any subscribers is not an error.
+        let _ = self.sender(topic).send(payload);
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl Subscriber for LocalBus {
+    async fn subscribe<E: Event>(&self) -> Result<Subscription<E>, EventError> {
+        let receiver = self.sender(E::TOPIC).subscribe();
+        let payloads = BroadcastStream::new(receiver).filter_map(|v| async move {
+            match v {
+                Ok(payload) => Some(payload),
+                Err(BroadcastStreamRecvError::Lagged(missed)) => {
+                    tracing::warn!(topic = E::TOPIC, missed, "event subscriber lagged");
+                    None
+                }
+            }
+        });
+
+        Ok(Subscription::new(payloads))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::{BotUpdated, TagReloaded};
+    use crate::types::JsSafeBigInt;
+
+    #[test]
+    fn test_publish_subscribe() {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+
+        rt.block_on(async {
+            let bus = LocalBus::default();
+            let mut updates = bus.subscribe::<BotUpdated>().await.unwrap();
+            let mut reloads = bus.subscribe::<TagReloaded>().await.unwrap();
+
+            let event = BotUpdated {
+                bot_id: JsSafeBigInt(1),
+                changed: vec!["name".to_string()],
+            };
+            bus.publish(&event).await.unwrap();
+            bus.publish(&TagReloaded {
+                registry: "bot".to_string(),
+            })
+            .await
+            .unwrap();
+
+            assert_eq!(updates.recv().await.unwrap().unwrap(), event);
+            assert_eq!(reloads.recv().await.unwrap().unwrap().registry, "bot");
+        });
+    }
+
+    #[test]
+    fn test_lagged_subscriber_skips() {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+
+        rt.block_on(async {
+            let bus = LocalBus::new(2);
+            let mut reloads = bus.subscribe::<TagReloaded>().await.unwrap();
+
+            for registry in ["bot", "guild", "pack"] {
+                let event = TagReloaded {
+                    registry: registry.to_string(),
+                };
+                bus.publish(&event).await.unwrap();
+            }
+
+            assert_eq!(reloads.recv().await.unwrap().unwrap().registry, "guild");
+            assert_eq!(reloads.recv().await.unwrap().unwrap().registry, "pack");
+        });
+    }
+}
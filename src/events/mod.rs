@@ -0,0 +1,23 @@
+mod bus;
+mod local;
+#[cfg(feature = "nats")]
+mod nats;
+mod outbox;
+mod payloads;
+#[cfg(feature = "redis-events")]
+mod redis;
+
+#[cfg(feature = "redis-events")]
+pub use self::redis::RedisBus;
+pub use bus::{Event, EventError, Publisher, Subscriber, Subscription};
+pub use local::LocalBus;
+#[cfg(feature = "nats")]
+pub use nats::{
+    Delivery, GroupConsumer, NatsBus, DEFAULT_ACK_WAIT, DEFAULT_MAX_DELIVER, VERSION_MISMATCH_DELAY,
+};
+pub use outbox::{
+    Outbox, OutboxEntry, DEFAULT_RELAY_BATCH, DEFAULT_RELAY_INTERVAL, OUTBOX_BUCKET_SECS,
+    OUTBOX_CURSOR_TABLE, OUTBOX_RELAY_LOCK, OUTBOX_SETTLE_DELAY, OUTBOX_SHARDS, OUTBOX_TABLE,
+    OUTBOX_TTL,
+};
+pub use payloads::{BotUpdated, TagReloaded, VoteCast};
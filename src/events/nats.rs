@@ -0,0 +1,255 @@
+use std::fmt::{Debug, Display};
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::time::Duration;
+
+use async_nats::jetstream::consumer::{pull, AckPolicy};
+use async_nats::jetstream::{self, AckKind};
+use futures_util::{Stream, StreamExt};
+
+use crate::codec::CodecError;
+use crate::events::{Event, EventError, Publisher, Subscriber, Subscription};
+
+/// How many times a message is redelivered to a consumer group before the
+/// server gives up on it.
+pub const DEFAULT_MAX_DELIVER: i64 = 10;
+
+/// How long a consumer has to ack a message before it is redelivered.
+pub const DEFAULT_ACK_WAIT: Duration = Duration::from_secs(30);
+
+/// How long to wait before redelivering an event this build cannot decode
+/// yet, long enough for the rest of a rollout to pick it up.
+pub const VERSION_MISMATCH_DELAY: Duration = Duration::from_secs(60);
+
+impl<K> From<async_nats::error::Error<K>> for EventError
+where
+    K: Clone + Debug + Display + PartialEq,
+{
+    fn from(e: async_nats::error::Error<K>) -> Self {
+        Self::Backend(e.to_string())
+    }
+}
+
+impl From<async_nats::SubscribeError> for EventError {
+    fn from(e: async_nats::SubscribeError) -> Self {
+        Self::Backend(e.to_string())
+    }
+}
+
+impl From<async_nats::Error> for EventError {
+    fn from(e: async_nats::Error) -> Self {
+        Self::Backend(e.to_string())
+    }
+}
+
+/// A bus backed by a NATS JetStream stream, topics are published on the
+/// `<prefix>.<topic>` subject.
+///
+/// Unlike [RedisBus](crate::events::RedisBus) events are persisted by the
+/// stream, [NatsBus::consume] reads them through a durable consumer so
+/// events published while a service is down are delivered once it is back.
+/// [Subscriber::subscribe] only sees events published while subscribed.
+pub struct NatsBus {
+    client: async_nats::Client,
+    context: jetstream::Context,
+    stream: String,
+    prefix: String,
+}
+
+impl NatsBus {
+    pub async fn connect(
+        url: &str,
+        stream: impl Into<String>,
+        prefix: impl Into<String>,
+    ) -> Result<Self, EventError> {
+        let client = async_nats::connect(url).await?;
+        Self::new(client, stream, prefix).await
+    }
+
+    /// Creates the bus, creating the stream capturing every subject under
+    /// the prefix if it does not exist yet.
+    pub async fn new(
+        client: async_nats::Client,
+        stream: impl Into<String>,
+        prefix: impl Into<String>,
+    ) -> Result<Self, EventError> {
+        let bus = Self {
+            context: jetstream::new(client.clone()),
+            client,
+            stream: stream.into(),
+            prefix: prefix.into(),
+        };
+
+        bus.context
+            .get_or_create_stream(jetstream::stream::Config {
+                name: bus.stream.clone(),
+                subjects: vec![format!("{}.>", bus.prefix)],
+                ..Default::default()
+            })
+            .await?;
+
+        Ok(bus)
+    }
+
+    fn subject(&self, topic: &str) -> String {
+        format!("{}.{}", self.prefix, topic)
+    }
+
+    /// Joins the consumer `group` for the events of type `E`.
+    ///
+    /// Every service using the same group shares a single durable consumer,
+    /// each event is delivered to one member of the group and redelivered
+    /// until it is acked. Handlers must therefore be idempotent.
+    pub async fn consume<E: Event>(&self, group: &str) -> Result<GroupConsumer<E>, EventError> {
+        let name = consumer_name(group, E::TOPIC);
+        let stream = self.context.get_stream(&self.stream).await?;
+        let consumer = stream
+            .get_or_create_consumer(
+                &name,
+                pull::Config {
+                    durable_name: Some(name.clone()),
+                    filter_subject: self.subject(E::TOPIC),
+                    ack_policy: AckPolicy::Explicit,
+                    ack_wait: DEFAULT_ACK_WAIT,
+                    max_deliver: DEFAULT_MAX_DELIVER,
+                    ..Default::default()
+                },
+            )
+            .await?;
+
+        Ok(GroupConsumer {
+            messages: Box::pin(consumer.messages().await?),
+            _event: PhantomData,
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl Publisher for NatsBus {
+    /// Resolves once the stream has acknowledged the event.
+    async fn publish_raw(&self, topic: &str, payload: Vec<u8>) -> Result<(), EventError> {
+        self.context
+            .publish(self.subject(topic), payload.into())
+            .await?
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl Subscriber for NatsBus {
+    async fn subscribe<E: Event>(&self) -> Result<Subscription<E>, EventError> {
+        let subscriber = self.client.subscribe(self.subject(E::TOPIC)).await?;
+        let payloads = subscriber.map(|msg| msg.payload.to_vec());
+
+        Ok(Subscription::new(payloads))
+    }
+}
+
+/// Durable consumer names may not contain `.`, `*` or `>`.
+fn consumer_name(group: &str, topic: &str) -> String {
+    format!("{}-{}", group, topic)
+        .chars()
+        .map(|c| match c {
+            '.' | '*' | '>' | ' ' => '_',
+            c => c,
+        })
+        .collect()
+}
+
+type MessageStream =
+    Pin<Box<dyn Stream<Item = Result<jetstream::Message, pull::MessagesError>> + Send>>;
+
+/// The events of a single topic delivered to a consumer group.
+pub struct GroupConsumer<E> {
+    messages: MessageStream,
+    _event: PhantomData<fn() -> E>,
+}
+
+impl<E: Event> GroupConsumer<E> {
+    /// Waits for the next event, returns `None` once the connection is closed.
+    ///
+    /// Payloads which cannot be decoded are returned as errors. Payloads from
+    /// a newer version than this build knows are nacked with a delay so
+    /// another consumer, or this one after a deploy, can handle them. Corrupt
+    /// payloads and versions older than every migration are terminated, no
+    /// deploy will make them readable.
+    pub async fn next(&mut self) -> Option<Result<Delivery<E>, EventError>> {
+        let message = match self.messages.next().await? {
+            Ok(message) => message,
+            Err(e) => return Some(Err(e.into())),
+        };
+
+        match E::from_payload(&message.payload) {
+            Ok(event) => Some(Ok(Delivery { event, message })),
+            Err(e) => {
+                if let Err(ack) = message.ack_with(undecodable_ack(&e)).await {
+                    tracing::warn!(topic = E::TOPIC, error = %ack, "failed to ack undecodable event");
+                }
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+fn undecodable_ack(error: &EventError) -> AckKind {
+    match error {
+        EventError::Codec(CodecError::FutureVersion(_)) => {
+            AckKind::Nak(Some(VERSION_MISMATCH_DELAY))
+        }
+        _ => AckKind::Term,
+    }
+}
+
+/// An event delivered to a consumer group, it is redelivered unless it is
+/// acked before the ack deadline.
+pub struct Delivery<E> {
+    pub event: E,
+    message: jetstream::Message,
+}
+
+impl<E> Delivery<E> {
+    /// How many times this event has been delivered, starting at 1.
+    pub fn attempt(&self) -> i64 {
+        self.message.info().map(|info| info.delivered).unwrap_or(1)
+    }
+
+    /// Marks the event as handled.
+    pub async fn ack(&self) -> Result<(), EventError> {
+        Ok(self.message.ack().await?)
+    }
+
+    /// Requests the event to be redelivered, after `delay` if given.
+    pub async fn nak(&self, delay: Option<Duration>) -> Result<(), EventError> {
+        Ok(self.message.ack_with(AckKind::Nak(delay)).await?)
+    }
+
+    /// Stops the event from being redelivered without handling it.
+    pub async fn term(&self) -> Result<(), EventError> {
+        Ok(self.message.ack_with(AckKind::Term).await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_consumer_names_are_valid() {
+        assert_eq!(consumer_name("api", "bot_updated"), "api-bot_updated");
+        assert_eq!(consumer_name("api.v2", "votes.*"), "api_v2-votes__");
+    }
+
+    #[test]
+    fn test_future_versions_are_retried() {
+        let ack = undecodable_ack(&EventError::Codec(CodecError::FutureVersion(3)));
+        assert!(matches!(ack, AckKind::Nak(Some(VERSION_MISMATCH_DELAY))));
+
+        let ack = undecodable_ack(&EventError::Codec(CodecError::NoMigration(0)));
+        assert!(matches!(ack, AckKind::Term));
+
+        let ack = undecodable_ack(&EventError::Codec(CodecError::Truncated));
+        assert!(matches!(ack, AckKind::Term));
+    }
+}
@@ -0,0 +1,385 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+use crate::db::{insert_using_ttl, BatchBuilder, DbError, Session, UnsupportedStatement, WithTtl};
+use crate::events::{Event, EventError, Publisher};
+use crate::sync::DistributedLock;
+use crate::types::Timestamp;
+
+pub const OUTBOX_TABLE: &str = "event_outbox";
+pub const OUTBOX_CURSOR_TABLE: &str = "event_outbox_cursors";
+
+/// The name of the lock held by the instance relaying the outbox.
+pub const OUTBOX_RELAY_LOCK: &str = "event_outbox_relay";
+
+/// Entries are spread over this many partitions per bucket so a backlog does
+/// not end up in a single wide partition.
+pub const OUTBOX_SHARDS: i32 = 16;
+
+/// Entries are partitioned by the hour they were written in.
+pub const OUTBOX_BUCKET_SECS: i64 = 60 * 60;
+
+/// Entries expire after this whether or not they were relayed, the relay
+/// must catch up within it.
+pub const OUTBOX_TTL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// Entries younger than this are left for the next pass, so an entry which
+/// commits after a newer one in the same shard is not skipped.
+pub const OUTBOX_SETTLE_DELAY: Duration = Duration::from_secs(5);
+
+/// How many entries are read from each shard per relay pass.
+pub const DEFAULT_RELAY_BATCH: i32 = 100;
+
+/// How long the relay waits after finding the outbox empty.
+pub const DEFAULT_RELAY_INTERVAL: Duration = Duration::from_secs(1);
+
+const INSERT_QUERY: &str =
+    "INSERT INTO event_outbox (bucket, shard, id, topic, payload) VALUES (?, ?, ?, ?, ?);";
+const SELECT_QUERY: &str = "SELECT bucket, shard, id, topic, payload FROM event_outbox \
+    WHERE bucket = ? AND shard = ? LIMIT ?;";
+const SELECT_AFTER_QUERY: &str = "SELECT bucket, shard, id, topic, payload FROM event_outbox \
+    WHERE bucket = ? AND shard = ? AND id > ? LIMIT ?;";
+const SELECT_CURSOR_QUERY: &str =
+    "SELECT bucket, last_id FROM event_outbox_cursors WHERE shard = ?;";
+const UPDATE_CURSOR_QUERY: &str =
+    "UPDATE event_outbox_cursors SET bucket = ?, last_id = ? WHERE shard = ?;";
+
+impl From<DbError> for EventError {
+    fn from(e: DbError) -> Self {
+        Self::Backend(e.to_string())
+    }
+}
+
+impl From<UnsupportedStatement> for EventError {
+    fn from(e: UnsupportedStatement) -> Self {
+        Self::Backend(e.to_string())
+    }
+}
+
+/// An encoded event waiting to be published.
+///
+/// Ids are UUIDv7 so entries within a shard are relayed in the order they
+/// were written.
+#[derive(Clone, Debug, PartialEq, Eq, scylla::FromRow, scylla::ValueList)]
+pub struct OutboxEntry {
+    /// The start of the hour the entry was written in.
+    pub bucket: Timestamp,
+    pub shard: i32,
+    pub id: Uuid,
+    pub topic: String,
+    pub payload: Vec<u8>,
+}
+
+impl OutboxEntry {
+    pub fn new<E: Event>(event: &E) -> Result<Self, EventError> {
+        let id = Uuid::now_v7();
+
+        Ok(Self {
+            bucket: bucket_of(unix_secs(&id)),
+            shard: shard_of(&id),
+            id,
+            topic: E::TOPIC.to_string(),
+            payload: event.to_payload()?,
+        })
+    }
+}
+
+fn shard_of(id: &Uuid) -> i32 {
+    // The trailing bytes of a v7 id are random.
+    (id.as_bytes()[15] as i32) % OUTBOX_SHARDS
+}
+
+fn unix_secs(id: &Uuid) -> i64 {
+    id.get_timestamp().map_or(0, |v| v.to_unix().0 as i64)
+}
+
+fn bucket_of(secs: i64) -> Timestamp {
+    Timestamp::from(secs - secs.rem_euclid(OUTBOX_BUCKET_SECS))
+}
+
+/// How far a shard has been relayed.
+#[derive(Clone, Debug, PartialEq, Eq, scylla::FromRow)]
+struct RelayCursor {
+    bucket: Timestamp,
+    /// The last relayed entry of the bucket, `None` if none were.
+    last_id: Option<Uuid>,
+}
+
+/// Guarantees events are published at least once by writing them to the
+/// `event_outbox` table together with the change which caused them, a relay
+/// then publishes them in order.
+///
+/// ```cql
+/// CREATE TABLE event_outbox (
+///     bucket timestamp,
+///     shard int,
+///     id uuid,
+///     topic text,
+///     payload blob,
+///     PRIMARY KEY ((bucket, shard), id)
+/// );
+///
+/// CREATE TABLE event_outbox_cursors (
+///     shard int PRIMARY KEY,
+///     bucket timestamp,
+///     last_id uuid
+/// );
+/// ```
+///
+/// Entries are never deleted, they expire after [OUTBOX_TTL] so relaying
+/// does not leave tombstones behind. The relay instead records how far it
+/// got in each shard, if it stops before saving its progress the events are
+/// published again so consumers must be idempotent.
+pub struct Outbox {
+    session: Arc<Session>,
+    batch_size: i32,
+}
+
+impl Outbox {
+    pub fn new(session: Arc<Session>) -> Self {
+        Self {
+            session,
+            batch_size: DEFAULT_RELAY_BATCH,
+        }
+    }
+
+    pub fn with_batch_size(mut self, batch_size: i32) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Adds the event to a batch holding the entity change, the batch must
+    /// be logged for the event to be written atomically with the change.
+    ///
+    /// Fails with [EventError::BatchFull] rather than starting another batch,
+    /// as the event could then be written without the change or vice versa.
+    pub fn stage<E: Event>(batch: &mut BatchBuilder, event: &E) -> Result<(), EventError> {
+        let values = WithTtl::new(OutboxEntry::new(event)?, OUTBOX_TTL);
+        let query = insert_using_ttl(INSERT_QUERY)?;
+        if batch.would_split(&query, &values)? {
+            return Err(EventError::BatchFull);
+        }
+
+        batch.append(query, values)?;
+        Ok(())
+    }
+
+    /// Writes the event on its own, for changes which cannot be batched.
+    pub async fn write<E: Event>(&self, event: &E) -> Result<(), EventError> {
+        let values = WithTtl::new(OutboxEntry::new(event)?, OUTBOX_TTL);
+        self.session
+            .execute(&insert_using_ttl(INSERT_QUERY)?, values)
+            .await?;
+        Ok(())
+    }
+
+    /// Publishes up to the batch size of entries from every shard, returning
+    /// how many were relayed.
+    ///
+    /// A shard stops at the first entry which fails to publish so its
+    /// entries are never published out of order. Only one instance should
+    /// relay at a time, [Outbox::spawn_relay] takes care of that.
+    pub async fn relay_once<P: Publisher + ?Sized>(
+        &self,
+        publisher: &P,
+    ) -> Result<usize, EventError> {
+        let settled = Timestamp::default().0.timestamp() - OUTBOX_SETTLE_DELAY.as_secs() as i64;
+        let mut relayed = 0;
+
+        for shard in 0..OUTBOX_SHARDS {
+            relayed += self.relay_shard(publisher, shard, settled).await?;
+        }
+
+        Ok(relayed)
+    }
+
+    async fn relay_shard<P: Publisher + ?Sized>(
+        &self,
+        publisher: &P,
+        shard: i32,
+        settled: i64,
+    ) -> Result<usize, EventError> {
+        let mut cursor = match self
+            .session
+            .query_one::<RelayCursor>(SELECT_CURSOR_QUERY, (shard,))
+            .await?
+        {
+            Some(cursor) => cursor,
+            None => RelayCursor {
+                bucket: bucket_of(settled - OUTBOX_TTL.as_secs() as i64),
+                last_id: None,
+            },
+        };
+        let start = cursor.clone();
+        let last_bucket = bucket_of(settled);
+        let mut relayed = 0;
+
+        let res = loop {
+            let entries = match self.read_after(&cursor, shard).await {
+                Ok(entries) => entries,
+                Err(e) => break Err(e),
+            };
+            let full = entries.len() >= self.batch_size as usize;
+
+            let mut unsettled = false;
+            let mut failed = None;
+            for entry in entries {
+                if unix_secs(&entry.id) > settled {
+                    unsettled = true;
+                    break;
+                }
+
+                if let Err(e) = publisher.publish_raw(&entry.topic, entry.payload).await {
+                    failed = Some(e);
+                    break;
+                }
+                cursor.last_id = Some(entry.id);
+                relayed += 1;
+            }
+
+            if let Some(e) = failed {
+                break Err(e);
+            }
+
+            if full || unsettled || cursor.bucket.0 >= last_bucket.0 {
+                break Ok(relayed);
+            }
+
+            cursor = RelayCursor {
+                bucket: Timestamp(cursor.bucket.0 + chrono::Duration::seconds(OUTBOX_BUCKET_SECS)),
+                last_id: None,
+            };
+        };
+
+        // Progress is saved even when publishing failed part way through, so
+        // the published entries aren't sent again on the next pass.
+        if cursor != start {
+            self.session
+                .execute(UPDATE_CURSOR_QUERY, (cursor.bucket, cursor.last_id, shard))
+                .await?;
+        }
+
+        res
+    }
+
+    async fn read_after(
+        &self,
+        cursor: &RelayCursor,
+        shard: i32,
+    ) -> Result<Vec<OutboxEntry>, EventError> {
+        let entries = match cursor.last_id {
+            Some(last_id) => {
+                self.session
+                    .query_typed(
+                        SELECT_AFTER_QUERY,
+                        (cursor.bucket, shard, last_id, self.batch_size),
+                    )
+                    .await?
+            }
+            None => {
+                self.session
+                    .query_typed(SELECT_QUERY, (cursor.bucket, shard, self.batch_size))
+                    .await?
+            }
+        };
+
+        Ok(entries)
+    }
+
+    /// Spawns a task relaying entries until it is aborted, waiting for
+    /// `interval` whenever the outbox is empty or the relay fails.
+    ///
+    /// Every pass runs under `lock`, so with many instances only one relays
+    /// and shards are not published twice concurrently.
+    pub fn spawn_relay<P: Publisher + 'static>(
+        self: Arc<Self>,
+        publisher: Arc<P>,
+        lock: DistributedLock,
+        interval: Duration,
+    ) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            loop {
+                let res = lock
+                    .run_exclusive(|_| self.relay_once(publisher.as_ref()))
+                    .await;
+
+                match res {
+                    Ok(Some(Ok(0))) | Ok(None) => tokio::time::sleep(interval).await,
+                    Ok(Some(Ok(_))) => {}
+                    Ok(Some(Err(e))) => {
+                        tracing::warn!(error = %e, "failed to relay outbox events");
+                        tokio::time::sleep(interval).await;
+                    }
+                    Err(e) => {
+                        tracing::warn!(error = %e, "failed to take the outbox relay lock");
+                        tokio::time::sleep(interval).await;
+                    }
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::VoteCast;
+    use crate::types::{JsSafeBigInt, Timestamp};
+
+    fn vote() -> VoteCast {
+        VoteCast {
+            bot_id: JsSafeBigInt(1),
+            user_id: JsSafeBigInt(2),
+            cast_at: Timestamp::from(1_700_000_000),
+        }
+    }
+
+    #[test]
+    fn test_entry_roundtrip() {
+        let event = vote();
+        let entry = OutboxEntry::new(&event).unwrap();
+
+        assert_eq!(entry.topic, VoteCast::TOPIC);
+        assert!((0..OUTBOX_SHARDS).contains(&entry.shard));
+        assert_eq!(entry.bucket, bucket_of(unix_secs(&entry.id)));
+        assert_eq!(VoteCast::from_payload(&entry.payload).unwrap(), event);
+    }
+
+    #[test]
+    fn test_buckets() {
+        assert_eq!(bucket_of(1_700_000_000), Timestamp::from(1_699_999_200));
+        assert_eq!(bucket_of(1_699_999_200), Timestamp::from(1_699_999_200));
+    }
+
+    #[test]
+    fn test_stage_appends_to_batch() {
+        let mut batch = BatchBuilder::logged();
+        batch
+            .append(
+                "UPDATE bots SET updated_at = ? WHERE id = ?",
+                (Timestamp::from(1_700_000_000), 1_i64),
+            )
+            .unwrap();
+        Outbox::stage(&mut batch, &vote()).unwrap();
+
+        assert_eq!(batch.len(), 2);
+        assert_eq!(batch.batch_count(), 1);
+    }
+
+    #[test]
+    fn test_stage_never_splits_batch() {
+        let mut batch = BatchBuilder::logged().with_max_statements(1);
+        batch
+            .append("UPDATE bots SET name = ? WHERE id = ?", ("bot", 1_i64))
+            .unwrap();
+
+        assert!(matches!(
+            Outbox::stage(&mut batch, &vote()),
+            Err(EventError::BatchFull)
+        ));
+        assert_eq!(batch.len(), 1);
+    }
+}
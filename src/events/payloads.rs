@@ -0,0 +1,49 @@
+use bincode::{Decode, Encode};
+
+use crate::codec::VersionedDecode;
+use crate::events::Event;
+use crate::types::{JsSafeBigInt, Timestamp};
+
+/// A bot's listing was edited, `changed` contains the dotted paths of the edited fields.
+#[derive(Encode, Decode, Clone, Debug, PartialEq, Eq)]
+pub struct BotUpdated {
+    pub bot_id: JsSafeBigInt,
+    pub changed: Vec<String>,
+}
+
+impl VersionedDecode for BotUpdated {
+    const VERSION: u16 = 1;
+}
+
+impl Event for BotUpdated {
+    const TOPIC: &'static str = "bot_updated";
+}
+
+#[derive(Encode, Decode, Clone, Debug, PartialEq, Eq)]
+pub struct VoteCast {
+    pub bot_id: JsSafeBigInt,
+    pub user_id: JsSafeBigInt,
+    pub cast_at: Timestamp,
+}
+
+impl VersionedDecode for VoteCast {
+    const VERSION: u16 = 1;
+}
+
+impl Event for VoteCast {
+    const TOPIC: &'static str = "vote_cast";
+}
+
+/// A tag registry was reloaded, `registry` is one of `bot`, `guild` or `pack`.
+#[derive(Encode, Decode, Clone, Debug, PartialEq, Eq)]
+pub struct TagReloaded {
+    pub registry: String,
+}
+
+impl VersionedDecode for TagReloaded {
+    const VERSION: u16 = 1;
+}
+
+impl Event for TagReloaded {
+    const TOPIC: &'static str = "tag_reloaded";
+}
@@ -0,0 +1,67 @@
+use futures_util::StreamExt;
+use redis::aio::ConnectionManager;
+
+use crate::events::{Event, EventError, Publisher, Subscriber, Subscription};
+
+impl From<redis::RedisError> for EventError {
+    fn from(e: redis::RedisError) -> Self {
+        Self::Backend(e.to_string())
+    }
+}
+
+/// A bus shared between services using redis pub/sub, topics are published
+/// on the `<prefix>:<topic>` channel.
+///
+/// Redis pub/sub is fire and forget, events published while a subscriber is
+/// disconnected are lost.
+pub struct RedisBus {
+    client: redis::Client,
+    conn: ConnectionManager,
+    prefix: String,
+}
+
+impl RedisBus {
+    pub async fn new(client: redis::Client, prefix: impl Into<String>) -> Result<Self, EventError> {
+        let conn = ConnectionManager::new(client.clone()).await?;
+
+        Ok(Self {
+            client,
+            conn,
+            prefix: prefix.into(),
+        })
+    }
+
+    fn channel(&self, topic: &str) -> String {
+        format!("{}:{}", self.prefix, topic)
+    }
+}
+
+#[async_trait::async_trait]
+impl Publisher for RedisBus {
+    async fn publish_raw(&self, topic: &str, payload: Vec<u8>) -> Result<(), EventError> {
+        let mut conn = self.conn.clone();
+        redis::cmd("PUBLISH")
+            .arg(self.channel(topic))
+            .arg(payload)
+            .query_async::<_, ()>(&mut conn)
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl Subscriber for RedisBus {
+    /// Each subscription holds its own connection as redis does not allow
+    /// other commands on a connection in pub/sub mode.
+    async fn subscribe<E: Event>(&self) -> Result<Subscription<E>, EventError> {
+        let mut pubsub = self.client.get_async_connection().await?.into_pubsub();
+        pubsub.subscribe(self.channel(E::TOPIC)).await?;
+
+        let payloads = pubsub
+            .into_on_message()
+            .map(|msg| msg.get_payload_bytes().to_vec());
+
+        Ok(Subscription::new(payloads))
+    }
+}
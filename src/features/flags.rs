@@ -0,0 +1,196 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use once_cell::sync::Lazy;
+use poem_openapi::Object;
+use sha2::{Digest, Sha256};
+
+use crate::live::LiveValue;
+use crate::types::JsSafeBigInt;
+
+static LOADED_FEATURE_FLAGS: Lazy<LiveValue<FeatureFlags>> = Lazy::new(LiveValue::default);
+
+pub fn get_feature_flags() -> &'static LiveValue<FeatureFlags> {
+    &LOADED_FEATURE_FLAGS
+}
+
+pub fn set_feature_flags(flags: FeatureFlags) {
+    LOADED_FEATURE_FLAGS.set(flags);
+}
+
+/// Checks if the flag is enabled for the subject using the loaded flags.
+///
+/// Unknown flags are always disabled.
+pub fn is_enabled(flag: &str, subject: &FlagSubject) -> bool {
+    get_feature_flags().load().is_enabled(flag, subject)
+}
+
+/// Who a flag is being evaluated for.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct FlagSubject {
+    pub user_id: Option<JsSafeBigInt>,
+    pub guild_id: Option<JsSafeBigInt>,
+}
+
+impl FlagSubject {
+    pub fn user(user_id: JsSafeBigInt) -> Self {
+        Self {
+            user_id: Some(user_id),
+            guild_id: None,
+        }
+    }
+
+    pub fn guild(guild_id: JsSafeBigInt) -> Self {
+        Self {
+            user_id: None,
+            guild_id: Some(guild_id),
+        }
+    }
+
+    pub fn with_guild(mut self, guild_id: JsSafeBigInt) -> Self {
+        self.guild_id = Some(guild_id);
+        self
+    }
+}
+
+/// The rollout rules of a single flag.
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct FeatureFlag {
+    /// Turns the flag off for everyone, including allow-listed subjects.
+    pub killed: bool,
+    /// The percentage of subjects the flag is rolled out to.
+    ///
+    /// Subjects are bucketed by their user id, falling back to their guild id.
+    pub percentage: u8,
+    /// Users which always have the flag enabled.
+    pub users: BTreeSet<i64>,
+    /// Guilds which always have the flag enabled.
+    pub guilds: BTreeSet<i64>,
+}
+
+impl FeatureFlag {
+    pub fn is_enabled(&self, name: &str, subject: &FlagSubject) -> bool {
+        if self.killed {
+            return false;
+        }
+
+        let user_id = subject.user_id.map(|v| v.0);
+        let guild_id = subject.guild_id.map(|v| v.0);
+
+        if user_id.is_some_and(|id| self.users.contains(&id))
+            || guild_id.is_some_and(|id| self.guilds.contains(&id))
+        {
+            return true;
+        }
+
+        match user_id.or(guild_id) {
+            Some(id) => bucket(name, id) < self.percentage.min(100),
+            None => self.percentage >= 100,
+        }
+    }
+}
+
+/// Places the snowflake into one of 100 buckets.
+///
+/// The flag name is part of the hash so the same subjects are not always
+/// the first to receive every rollout.
+fn bucket(name: &str, id: i64) -> u8 {
+    let mut hasher = Sha256::new();
+    hasher.update(name.as_bytes());
+    hasher.update(id.to_be_bytes());
+    let digest = hasher.finalize();
+
+    let mut prefix = [0; 8];
+    prefix.copy_from_slice(&digest[..8]);
+    (u64::from_be_bytes(prefix) % 100) as u8
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(transparent)]
+pub struct FeatureFlags(pub BTreeMap<String, FeatureFlag>);
+
+impl FeatureFlags {
+    pub fn is_enabled(&self, flag: &str, subject: &FlagSubject) -> bool {
+        self.0
+            .get(flag)
+            .is_some_and(|rule| rule.is_enabled(flag, subject))
+    }
+
+    /// Evaluates every flag for the subject.
+    pub fn evaluate(&self, subject: &FlagSubject) -> EvaluatedFlags {
+        let flags = self
+            .0
+            .iter()
+            .map(|(name, rule)| (name.clone(), rule.is_enabled(name, subject)))
+            .collect();
+
+        EvaluatedFlags { flags }
+    }
+}
+
+/// The flags of a single subject, as sent to the frontend on bootstrap.
+#[derive(Object, Clone, Debug, Default, PartialEq, Eq)]
+pub struct EvaluatedFlags {
+    pub flags: BTreeMap<String, bool>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flags() -> FeatureFlags {
+        serde_json::from_value(serde_json::json!({
+            "new-search": {"percentage": 50, "users": [1]},
+            "reviews": {"guilds": [7]},
+            "everyone": {"percentage": 100},
+            "killed": {"killed": true, "percentage": 100, "users": [1]},
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_allow_lists_and_kill_switch() {
+        let flags = flags();
+        let user = FlagSubject::user(JsSafeBigInt(1));
+
+        assert!(flags.is_enabled("new-search", &user));
+        assert!(flags.is_enabled("everyone", &user));
+        assert!(!flags.is_enabled("killed", &user));
+        assert!(!flags.is_enabled("missing", &user));
+
+        assert!(!flags.is_enabled("reviews", &user));
+        assert!(flags.is_enabled("reviews", &user.with_guild(JsSafeBigInt(7))));
+        assert!(flags.is_enabled("everyone", &FlagSubject::default()));
+    }
+
+    #[test]
+    fn test_percentage_rollout() {
+        let rule = FeatureFlag {
+            percentage: 25,
+            ..Default::default()
+        };
+
+        let enabled = (1_000..11_000)
+            .filter(|id| rule.is_enabled("rollout", &FlagSubject::user(JsSafeBigInt(*id))))
+            .count();
+        assert!((2_000..3_000).contains(&enabled), "{enabled}");
+
+        let subject = FlagSubject::guild(JsSafeBigInt(123));
+        assert_eq!(
+            rule.is_enabled("rollout", &subject),
+            rule.is_enabled("rollout", &subject)
+        );
+    }
+
+    #[test]
+    fn test_evaluate() {
+        set_feature_flags(flags());
+
+        let subject = FlagSubject::user(JsSafeBigInt(1));
+        let evaluated = get_feature_flags().load().evaluate(&subject);
+        assert_eq!(evaluated.flags.len(), 4);
+        assert_eq!(evaluated.flags.get("killed"), Some(&false));
+        assert_eq!(evaluated.flags.get("new-search"), Some(&true));
+        assert!(is_enabled("everyone", &subject));
+    }
+}
@@ -0,0 +1,6 @@
+pub mod flags;
+
+pub use flags::{
+    get_feature_flags, is_enabled, set_feature_flags, EvaluatedFlags, FeatureFlag, FeatureFlags,
+    FlagSubject,
+};
@@ -0,0 +1,193 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures_util::future::join_all;
+use poem::handler;
+use poem::web::Data;
+use poem_openapi::payload::Json;
+use poem_openapi::{ApiResponse, Object};
+use tokio::time::Instant;
+
+use crate::health::{HealthCheck, HealthStatus};
+
+pub const DEFAULT_CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Object, Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct CheckResult {
+    pub status: HealthStatus,
+    pub latency_ms: u64,
+    #[oai(skip_serializing_if_is_none)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+/// The body of every service's `/healthz` endpoint.
+#[derive(Object, Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct HealthReport {
+    pub status: HealthStatus,
+    pub checks: BTreeMap<String, CheckResult>,
+}
+
+/// `200` while the service can serve traffic, `503` once a critical check fails
+/// so the load balancer takes the instance out of rotation.
+#[derive(ApiResponse, Debug)]
+pub enum HealthResponse {
+    #[oai(status = 200)]
+    Healthy(Json<HealthReport>),
+    #[oai(status = 503)]
+    Unhealthy(Json<HealthReport>),
+}
+
+impl From<HealthReport> for HealthResponse {
+    fn from(report: HealthReport) -> Self {
+        match report.status {
+            HealthStatus::Fail => Self::Unhealthy(Json(report)),
+            _ => Self::Healthy(Json(report)),
+        }
+    }
+}
+
+/// Runs every check concurrently, each bounded by the timeout.
+pub struct HealthAggregator {
+    checks: Vec<Box<dyn HealthCheck>>,
+    timeout: Duration,
+}
+
+impl Default for HealthAggregator {
+    fn default() -> Self {
+        Self::new(DEFAULT_CHECK_TIMEOUT)
+    }
+}
+
+impl HealthAggregator {
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            checks: vec![],
+            timeout,
+        }
+    }
+
+    pub fn with_check(mut self, check: impl HealthCheck + 'static) -> Self {
+        self.checks.push(Box::new(check));
+        self
+    }
+
+    pub async fn run(&self) -> HealthReport {
+        let results = join_all(
+            self.checks
+                .iter()
+                .map(|check| self.run_check(check.as_ref())),
+        )
+        .await;
+
+        let status = results
+            .iter()
+            .map(|(_, result)| result.status)
+            .max()
+            .unwrap_or(HealthStatus::Pass);
+
+        HealthReport {
+            status,
+            checks: results.into_iter().collect(),
+        }
+    }
+
+    async fn run_check(&self, check: &dyn HealthCheck) -> (String, CheckResult) {
+        let start = Instant::now();
+        let outcome = match tokio::time::timeout(self.timeout, check.check()).await {
+            Ok(outcome) => outcome,
+            Err(_) => Err(format!("timed out after {:?}", self.timeout)),
+        };
+        let latency_ms = start.elapsed().as_millis() as u64;
+
+        let result = match outcome {
+            Ok(()) => CheckResult {
+                status: HealthStatus::Pass,
+                latency_ms,
+                message: None,
+            },
+            Err(message) => CheckResult {
+                status: if check.is_critical() {
+                    HealthStatus::Fail
+                } else {
+                    HealthStatus::Warn
+                },
+                latency_ms,
+                message: Some(message),
+            },
+        };
+
+        (check.name().to_string(), result)
+    }
+}
+
+/// A `/healthz` handler, expects the aggregator to be added as data.
+#[handler]
+pub async fn healthz(aggregator: Data<&Arc<HealthAggregator>>) -> HealthResponse {
+    aggregator.run().await.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::health::FnCheck;
+    use poem::http::{StatusCode, Uri};
+    use poem::{Endpoint, EndpointExt, Request, Route};
+
+    fn run<F: std::future::Future>(fut: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .unwrap()
+            .block_on(fut)
+    }
+
+    #[test]
+    fn test_aggregate_status() {
+        let aggregator = HealthAggregator::new(Duration::from_millis(20))
+            .with_check(FnCheck::new("scylla", || async { Ok(()) }))
+            .with_check(
+                FnCheck::new("redis", || async { Err("refused".to_string()) }).non_critical(),
+            );
+
+        let report = run(aggregator.run());
+        assert_eq!(report.status, HealthStatus::Warn);
+        assert_eq!(report.checks["scylla"].status, HealthStatus::Pass);
+        assert_eq!(report.checks["redis"].message.as_deref(), Some("refused"));
+
+        let aggregator = aggregator.with_check(FnCheck::new("search", || async {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            Ok(())
+        }));
+        let report = run(aggregator.run());
+        assert_eq!(report.status, HealthStatus::Fail);
+        assert!(report.checks["search"]
+            .message
+            .as_ref()
+            .unwrap()
+            .contains("timed out"));
+    }
+
+    #[test]
+    fn test_healthz_endpoint() {
+        let aggregator = HealthAggregator::default()
+            .with_check(FnCheck::new("scylla", || async { Err("down".to_string()) }));
+        let app = Route::new()
+            .at("/healthz", healthz)
+            .data(Arc::new(aggregator));
+
+        run(async {
+            let req = Request::builder()
+                .uri(Uri::from_static("/healthz"))
+                .finish();
+            let resp = app.get_response(req).await;
+            assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+            let body: serde_json::Value =
+                serde_json::from_str(&resp.into_body().into_string().await.unwrap()).unwrap();
+            assert_eq!(body["status"], "fail");
+            assert_eq!(body["checks"]["scylla"]["message"], "down");
+        });
+    }
+}
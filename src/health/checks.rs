@@ -0,0 +1,211 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use poem_openapi::Enum;
+use scylla::Session;
+
+#[derive(
+    Enum,
+    Copy,
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    strum::AsRefStr,
+    strum::EnumString,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[oai(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+/// Ordered by severity, the overall status is the worst of all checks.
+pub enum HealthStatus {
+    Pass,
+    /// A non-critical dependency is failing, the service can still serve traffic.
+    Warn,
+    Fail,
+}
+
+/// A check of a single dependency.
+#[async_trait::async_trait]
+pub trait HealthCheck: Send + Sync {
+    fn name(&self) -> &str;
+
+    /// Whether a failure makes the whole service unhealthy, otherwise it is
+    /// reported as a warning.
+    fn is_critical(&self) -> bool {
+        true
+    }
+
+    async fn check(&self) -> Result<(), String>;
+}
+
+/// Checks scylla is reachable by querying the local node.
+pub struct ScyllaCheck {
+    session: Arc<Session>,
+}
+
+impl ScyllaCheck {
+    pub fn new(session: Arc<Session>) -> Self {
+        Self { session }
+    }
+}
+
+#[async_trait::async_trait]
+impl HealthCheck for ScyllaCheck {
+    fn name(&self) -> &str {
+        "scylla"
+    }
+
+    async fn check(&self) -> Result<(), String> {
+        self.session
+            .query("SELECT now() FROM system.local;", &[])
+            .await
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// Checks redis responds to a `PING`.
+#[cfg(feature = "redis-cache")]
+pub struct RedisCheck {
+    conn: redis::aio::ConnectionManager,
+    is_critical: bool,
+}
+
+#[cfg(feature = "redis-cache")]
+impl RedisCheck {
+    /// Redis is only used for caching so failures are not critical by default.
+    pub fn new(conn: redis::aio::ConnectionManager) -> Self {
+        Self {
+            conn,
+            is_critical: false,
+        }
+    }
+
+    pub fn critical(mut self) -> Self {
+        self.is_critical = true;
+        self
+    }
+}
+
+#[cfg(feature = "redis-cache")]
+#[async_trait::async_trait]
+impl HealthCheck for RedisCheck {
+    fn name(&self) -> &str {
+        "redis"
+    }
+
+    fn is_critical(&self) -> bool {
+        self.is_critical
+    }
+
+    async fn check(&self) -> Result<(), String> {
+        let mut conn = self.conn.clone();
+        redis::cmd("PING")
+            .query_async::<_, ()>(&mut conn)
+            .await
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// Checks a HTTP dependency such as the search engine returns a success status.
+#[cfg(feature = "reqwest")]
+pub struct HttpCheck {
+    name: String,
+    url: String,
+    client: reqwest::Client,
+    is_critical: bool,
+}
+
+#[cfg(feature = "reqwest")]
+impl HttpCheck {
+    pub fn new(name: impl Into<String>, url: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            url: url.into(),
+            client: reqwest::Client::new(),
+            is_critical: true,
+        }
+    }
+
+    pub fn non_critical(mut self) -> Self {
+        self.is_critical = false;
+        self
+    }
+}
+
+#[cfg(feature = "reqwest")]
+#[async_trait::async_trait]
+impl HealthCheck for HttpCheck {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn is_critical(&self) -> bool {
+        self.is_critical
+    }
+
+    async fn check(&self) -> Result<(), String> {
+        let resp = self
+            .client
+            .get(&self.url)
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+
+        if resp.status().is_success() {
+            Ok(())
+        } else {
+            Err(format!("responded with {}", resp.status()))
+        }
+    }
+}
+
+type CheckFn = dyn Fn() -> Pin<Box<dyn Future<Output = Result<(), String>> + Send>> + Send + Sync;
+
+/// A check backed by a closure, for dependencies without a built in check.
+pub struct FnCheck {
+    name: String,
+    is_critical: bool,
+    check: Box<CheckFn>,
+}
+
+impl FnCheck {
+    pub fn new<F, Fut>(name: impl Into<String>, check: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), String>> + Send + 'static,
+    {
+        Self {
+            name: name.into(),
+            is_critical: true,
+            check: Box::new(move || Box::pin(check())),
+        }
+    }
+
+    pub fn non_critical(mut self) -> Self {
+        self.is_critical = false;
+        self
+    }
+}
+
+#[async_trait::async_trait]
+impl HealthCheck for FnCheck {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn is_critical(&self) -> bool {
+        self.is_critical
+    }
+
+    async fn check(&self) -> Result<(), String> {
+        (self.check)().await
+    }
+}
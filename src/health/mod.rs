@@ -0,0 +1,9 @@
+mod aggregate;
+mod checks;
+
+pub use aggregate::{healthz, CheckResult, HealthAggregator, HealthReport, HealthResponse};
+#[cfg(feature = "reqwest")]
+pub use checks::HttpCheck;
+#[cfg(feature = "redis-cache")]
+pub use checks::RedisCheck;
+pub use checks::{FnCheck, HealthCheck, HealthStatus, ScyllaCheck};
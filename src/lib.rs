@@ -1,3 +1,59 @@
+#[macro_use]
+mod macros;
+
+cfg_server! {
+    pub mod analytics;
+    pub mod auth;
+    pub mod bumps;
+    pub mod cache;
+    pub mod compliance;
+    pub mod crypto;
+    pub mod db;
+    pub mod diff;
+    pub mod discord;
+    #[cfg(feature = "events")]
+    pub mod events;
+    pub mod features;
+    pub mod health;
+    pub mod live;
+    pub mod media;
+    #[cfg(feature = "metrics")]
+    pub mod metrics;
+    pub mod middleware;
+    pub mod models;
+    pub mod moderation;
+    pub mod monitoring;
+    pub mod notifications;
+    pub mod premium;
+    pub mod ranking;
+    pub mod ratelimit;
+    pub mod realtime;
+    pub mod responses;
+    pub mod retry;
+    pub mod reviews;
+    pub mod scylla_ext;
+    pub mod search;
+    pub mod storage;
+    pub mod sync;
+    pub mod tasks;
+    pub mod telemetry;
+    #[cfg(any(test, feature = "testing"))]
+    pub mod testing;
+    pub mod urls;
+    pub mod util;
+    pub mod validation;
+    pub mod votes;
+    pub mod webhooks;
+}
+
+#[cfg(feature = "axum")]
+pub mod axum;
+#[cfg(feature = "bincode")]
+pub mod codec;
+pub mod config;
+pub mod errors;
+#[cfg(feature = "proto")]
+pub mod proto;
 pub mod tags;
 pub mod types;
 
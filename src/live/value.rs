@@ -0,0 +1,114 @@
+use arc_swap::ArcSwap;
+use std::ops::Deref;
+use std::sync::Arc;
+
+use tokio::sync::watch;
+
+/// A value which can be swapped at runtime without restarting the service,
+/// e.g. the tag registries, banned words or feature flags.
+///
+/// Reads are lock-free. Every change bumps a version which subscribers are
+/// notified of through a watch channel so they can react to reloads.
+pub struct LiveValue<T> {
+    value: ArcSwap<T>,
+    version: watch::Sender<u64>,
+}
+
+impl<T> LiveValue<T> {
+    pub fn new(value: T) -> Self {
+        Self {
+            value: ArcSwap::from_pointee(value),
+            version: watch::channel(0).0,
+        }
+    }
+
+    /// The current value.
+    #[inline]
+    pub fn get(&self) -> Arc<T> {
+        self.value.load_full()
+    }
+
+    pub fn set(&self, value: T) {
+        self.value.store(Arc::new(value));
+        self.notify();
+    }
+
+    /// Replaces the value with the result of `f`.
+    ///
+    /// `f` may be called more than once if the value is changed concurrently.
+    pub fn update(&self, f: impl Fn(&T) -> T) {
+        self.value.rcu(|current| f(current));
+        self.notify();
+    }
+
+    /// The number of times the value has changed.
+    #[inline]
+    pub fn version(&self) -> u64 {
+        *self.version.borrow()
+    }
+
+    /// Subscribes to changes, the receiver yields the new version after every change.
+    pub fn subscribe(&self) -> watch::Receiver<u64> {
+        self.version.subscribe()
+    }
+
+    fn notify(&self) {
+        self.version.send_modify(|v| *v += 1);
+    }
+}
+
+impl<T: Default> Default for LiveValue<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+/// Exposes the underlying [ArcSwap] so existing `load()` callers keep working.
+impl<T> Deref for LiveValue<T> {
+    type Target = ArcSwap<T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_set_update() {
+        let value = LiveValue::new(vec!["spam".to_string()]);
+        assert_eq!(value.version(), 0);
+
+        value.update(|words| {
+            let mut words = words.clone();
+            words.push("scam".to_string());
+            words
+        });
+        assert_eq!(value.get().len(), 2);
+
+        value.set(vec![]);
+        assert!(value.load().is_empty());
+        assert_eq!(value.version(), 2);
+    }
+
+    #[test]
+    fn test_subscribers_notified() {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+
+        rt.block_on(async {
+            let value = Arc::new(LiveValue::new(1));
+            let mut changes = value.subscribe();
+
+            let writer = value.clone();
+            tokio::spawn(async move { writer.set(2) });
+
+            changes.changed().await.unwrap();
+            assert_eq!(*changes.borrow(), 1);
+            assert_eq!(*value.get(), 2);
+        });
+    }
+}
@@ -0,0 +1,184 @@
+/// Declares items which are only compiled with the `server` feature, these
+/// are everything besides the pure types shared with the frontend.
+macro_rules! cfg_server {
+    ($($item:item)*) => {
+        $(
+            #[cfg(feature = "server")]
+            $item
+        )*
+    };
+}
+
+/// Implements the scylla `Value` and `FromCqlVal` traits for an enum which
+/// is stored as a text column using its strum `AsRefStr` and `EnumString` impls.
+#[cfg(feature = "server")]
+macro_rules! cql_text_enum {
+    ($name:ty) => {
+        impl scylla::frame::value::Value for $name {
+            fn serialize(
+                &self,
+                buf: &mut Vec<u8>,
+            ) -> Result<(), scylla::frame::value::ValueTooBig> {
+                scylla::frame::value::Value::serialize(&AsRef::<str>::as_ref(self), buf)
+            }
+        }
+
+        impl scylla::cql_to_rust::FromCqlVal<scylla::frame::response::result::CqlValue> for $name {
+            fn from_cql(
+                cql_val: scylla::frame::response::result::CqlValue,
+            ) -> Result<Self, scylla::cql_to_rust::FromCqlValError> {
+                cql_val
+                    .as_text()
+                    .and_then(|v| <$name as std::str::FromStr>::from_str(v).ok())
+                    .ok_or(scylla::cql_to_rust::FromCqlValError::BadCqlType)
+            }
+        }
+    };
+}
+
+/// Implements the scylla `Value` and `FromCqlVal` traits for a struct which is
+/// stored as a user defined type, fields are matched by name when reading.
+///
+/// The optional fallback converts values of any other CQL type into the struct,
+/// this keeps columns readable which were written before the UDT existed.
+#[cfg(feature = "scylla")]
+macro_rules! cql_udt {
+    ($name:ty { $($field:ident),+ $(,)? } $(, fallback = $fallback:expr)?) => {
+        impl scylla::frame::value::Value for $name {
+            fn serialize(
+                &self,
+                buf: &mut Vec<u8>,
+            ) -> Result<(), scylla::frame::value::ValueTooBig> {
+                let start = buf.len();
+                buf.extend_from_slice(&[0; 4]);
+                $(scylla::frame::value::Value::serialize(&self.$field, buf)?;)+
+
+                let len = i32::try_from(buf.len() - start - 4)
+                    .map_err(|_| scylla::frame::value::ValueTooBig)?;
+                buf[start..start + 4].copy_from_slice(&len.to_be_bytes());
+                Ok(())
+            }
+        }
+
+        impl scylla::cql_to_rust::FromCqlVal<scylla::frame::response::result::CqlValue> for $name {
+            fn from_cql(
+                cql_val: scylla::frame::response::result::CqlValue,
+            ) -> Result<Self, scylla::cql_to_rust::FromCqlValError> {
+                use scylla::cql_to_rust::{FromCqlVal, FromCqlValError};
+                use scylla::frame::response::result::CqlValue;
+
+                #[allow(unreachable_patterns)]
+                let mut fields = match cql_val {
+                    CqlValue::UserDefinedType { fields, .. } => fields,
+                    $(other => return ($fallback)(other).ok_or(FromCqlValError::BadCqlType),)?
+                    _ => return Err(FromCqlValError::BadCqlType),
+                };
+
+                Ok(Self {
+                    $($field: {
+                        let value = fields
+                            .iter_mut()
+                            .find(|(name, _)| name == stringify!($field))
+                            .and_then(|(_, value)| value.take());
+                        FromCqlVal::<Option<CqlValue>>::from_cql(value)?
+                    },)+
+                })
+            }
+        }
+    };
+}
+
+/// Implements the `async_graphql` `ScalarType` for a type using its
+/// `ParseFromJSON` and `ToJSON` impls, so GraphQL accepts the same values as
+/// the REST APIs.
+#[cfg(feature = "graphql")]
+macro_rules! graphql_json_scalar {
+    ($ty:ty, $name:literal) => {
+        #[async_graphql::Scalar(name = $name)]
+        impl async_graphql::ScalarType for $ty {
+            fn parse(value: async_graphql::Value) -> async_graphql::InputValueResult<Self> {
+                let json = value
+                    .into_json()
+                    .map_err(async_graphql::InputValueError::custom)?;
+                <Self as poem_openapi::types::ParseFromJSON>::parse_from_json(Some(json))
+                    .map_err(|e| async_graphql::InputValueError::custom(e.into_message()))
+            }
+
+            fn to_value(&self) -> async_graphql::Value {
+                poem_openapi::types::ToJSON::to_json(self)
+                    .and_then(|v| async_graphql::Value::from_json(v).ok())
+                    .unwrap_or(async_graphql::Value::Null)
+            }
+        }
+    };
+}
+
+/// Declares a configuration struct loaded from environment variables and
+/// implements [EnvConfig](crate::config::EnvConfig) for it.
+///
+/// Every field states how it is read, `required("NAME")`, `optional("NAME")`
+/// for `Option` fields, `default("NAME", value)` or `nested()` to embed
+/// another configuration such as [CommonConfig](crate::config::CommonConfig).
+/// Services use it to declare their own configuration on top of the common one.
+#[macro_export]
+macro_rules! env_config {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident {
+            $(
+                $(#[$field_meta:meta])*
+                $field_vis:vis $field:ident: $ty:ty = $kind:ident($($arg:expr),* $(,)?)
+            ),+ $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        $vis struct $name {
+            $($(#[$field_meta])* $field_vis $field: $ty,)+
+        }
+
+        impl $crate::config::EnvConfig for $name {
+            fn read(reader: &mut $crate::config::EnvReader<'_>) -> Option<Self> {
+                // Every field is read before returning so all problems are reported.
+                $(let $field: Option<$ty> = reader.$kind($($arg),*);)+
+
+                Some(Self {
+                    $($field: $field?,)+
+                })
+            }
+        }
+    };
+}
+
+/// Declares a struct read from a scylla row by column name and implements
+/// [FromRowExt](crate::db::FromRowExt) for it.
+///
+/// Every field states how it is read, `column("name")` for columns which
+/// must be in the result, or `default("name", value)` to fall back when the
+/// column is null or was not selected. Conversion errors name the column.
+#[macro_export]
+macro_rules! named_row {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident {
+            $(
+                $(#[$field_meta:meta])*
+                $field_vis:vis $field:ident: $ty:ty = $kind:ident($($arg:expr),* $(,)?)
+            ),+ $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        $vis struct $name {
+            $($(#[$field_meta])* $field_vis $field: $ty,)+
+        }
+
+        impl $crate::db::FromRowExt for $name {
+            fn from_reader(
+                reader: &mut $crate::db::RowReader<'_>,
+            ) -> Result<Self, $crate::db::RowError> {
+                Ok(Self {
+                    $($field: reader.$kind($($arg),*)?,)+
+                })
+            }
+        }
+    };
+}
@@ -0,0 +1,287 @@
+use std::borrow::Cow;
+use std::fmt::{Display, Formatter};
+use std::ops::Deref;
+use std::str::FromStr;
+
+use poem_openapi::registry::MetaSchemaRef;
+use poem_openapi::types::{ParseError, ParseFromJSON, ParseResult, ToJSON, Type};
+use poem_openapi::Enum;
+use scylla::cql_to_rust::{FromCqlVal, FromCqlValError};
+use scylla::frame::response::result::CqlValue;
+use scylla::frame::value::ValueTooBig;
+use serde_json::Value;
+
+use crate::types::{DiscordUrl, SafeOutboundUrl};
+
+#[derive(
+    Enum,
+    Copy,
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    strum::AsRefStr,
+    strum::EnumString,
+    strum::EnumIter,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[oai(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ImageFormat {
+    Png,
+    Jpeg,
+    Gif,
+    Webp,
+}
+
+impl ImageFormat {
+    pub fn from_mime(mime: &str) -> Option<Self> {
+        let essence = mime.split(';').next().unwrap_or_default().trim();
+
+        match essence.to_ascii_lowercase().as_str() {
+            "image/png" => Some(Self::Png),
+            "image/jpeg" | "image/jpg" => Some(Self::Jpeg),
+            "image/gif" => Some(Self::Gif),
+            "image/webp" => Some(Self::Webp),
+            _ => None,
+        }
+    }
+
+    pub fn mime(&self) -> &'static str {
+        match self {
+            Self::Png => "image/png",
+            Self::Jpeg => "image/jpeg",
+            Self::Gif => "image/gif",
+            Self::Webp => "image/webp",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Png => "png",
+            Self::Jpeg => "jpg",
+            Self::Gif => "gif",
+            Self::Webp => "webp",
+        }
+    }
+
+    /// Detects the format from the first bytes of the file.
+    pub fn sniff(bytes: &[u8]) -> Option<Self> {
+        match bytes {
+            [0x89, b'P', b'N', b'G', ..] => Some(Self::Png),
+            [0xFF, 0xD8, 0xFF, ..] => Some(Self::Jpeg),
+            [b'G', b'I', b'F', b'8', ..] => Some(Self::Gif),
+            [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', ..] => Some(Self::Webp),
+            _ => None,
+        }
+    }
+}
+
+cql_text_enum!(ImageFormat);
+
+/// Reads the width and height of the image from its header.
+///
+/// Only the start of the file is needed, `None` is returned if the header is
+/// not contained in the given bytes.
+pub fn sniff_dimensions(bytes: &[u8]) -> Option<(u32, u32)> {
+    let be16 = |i: usize| Some(u16::from_be_bytes(bytes.get(i..i + 2)?.try_into().ok()?) as u32);
+    let le16 = |i: usize| Some(u16::from_le_bytes(bytes.get(i..i + 2)?.try_into().ok()?) as u32);
+    let be32 = |i: usize| Some(u32::from_be_bytes(bytes.get(i..i + 4)?.try_into().ok()?));
+    let le24 = |i: usize| {
+        let v = bytes.get(i..i + 3)?;
+        Some(v[0] as u32 | (v[1] as u32) << 8 | (v[2] as u32) << 16)
+    };
+
+    match ImageFormat::sniff(bytes)? {
+        ImageFormat::Png => Some((be32(16)?, be32(20)?)),
+        ImageFormat::Gif => Some((le16(6)?, le16(8)?)),
+        ImageFormat::Webp => match bytes.get(12..16)? {
+            b"VP8 " => Some((le16(26)? & 0x3FFF, le16(28)? & 0x3FFF)),
+            b"VP8L" => {
+                let bits = u32::from_le_bytes(bytes.get(21..25)?.try_into().ok()?);
+                Some(((bits & 0x3FFF) + 1, ((bits >> 14) & 0x3FFF) + 1))
+            }
+            b"VP8X" => Some((le24(24)? + 1, le24(27)? + 1)),
+            _ => None,
+        },
+        ImageFormat::Jpeg => {
+            let mut i = 2;
+
+            loop {
+                if *bytes.get(i)? != 0xFF {
+                    return None;
+                }
+
+                let marker = *bytes.get(i + 1)?;
+                let is_frame =
+                    matches!(marker, 0xC0..=0xCF) && !matches!(marker, 0xC4 | 0xC8 | 0xCC);
+
+                if is_frame {
+                    return Some((be16(i + 7)?, be16(i + 5)?));
+                }
+
+                i += 2 + be16(i + 2)? as usize;
+            }
+        }
+    }
+}
+
+/// A user provided `https` image URL, e.g. a bot's banner or screenshots.
+///
+/// This has the same restrictions as [SafeOutboundUrl] as the backend fetches
+/// the image to validate it.
+#[derive(Clone, PartialEq, Eq, Hash, Debug, serde::Serialize)]
+pub struct ImageUrl(pub SafeOutboundUrl);
+
+impl<'de> serde::Deserialize<'de> for ImageUrl {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        Self::from_str(&s).map_err(|e| serde::de::Error::custom(e.into_message()))
+    }
+}
+
+impl Display for ImageUrl {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Deref for ImageUrl {
+    type Target = SafeOutboundUrl;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Type for ImageUrl {
+    const IS_REQUIRED: bool = <DiscordUrl as Type>::IS_REQUIRED;
+    type RawValueType = <DiscordUrl as Type>::RawValueType;
+    type RawElementValueType = <DiscordUrl as Type>::RawElementValueType;
+
+    fn name() -> Cow<'static, str> {
+        DiscordUrl::name()
+    }
+
+    fn schema_ref() -> MetaSchemaRef {
+        DiscordUrl::schema_ref()
+    }
+
+    fn as_raw_value(&self) -> Option<&Self::RawValueType> {
+        self.0.as_raw_value()
+    }
+
+    fn raw_element_iter<'a>(
+        &'a self,
+    ) -> Box<dyn Iterator<Item = &'a Self::RawElementValueType> + 'a> {
+        self.0.raw_element_iter()
+    }
+}
+
+impl ToJSON for ImageUrl {
+    fn to_json(&self) -> Option<Value> {
+        self.0.to_json()
+    }
+}
+
+impl ParseFromJSON for ImageUrl {
+    fn parse_from_json(value: Option<Value>) -> ParseResult<Self> {
+        let slf = SafeOutboundUrl::parse_from_json(value).map_err(|e| e.propagate())?;
+
+        if slf.scheme() != "https" {
+            return Err(ParseError::custom("Image urls must use https."));
+        }
+
+        Ok(Self(slf))
+    }
+}
+
+impl FromStr for ImageUrl {
+    type Err = ParseError<Self>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let slf = SafeOutboundUrl::from_str(s)?;
+
+        if slf.scheme() != "https" {
+            return Err(ParseError::custom("Image urls must use https."));
+        }
+
+        Ok(Self(slf))
+    }
+}
+
+impl FromCqlVal<CqlValue> for ImageUrl {
+    fn from_cql(cql_val: CqlValue) -> Result<Self, FromCqlValError> {
+        if let Some(v) = cql_val.as_text() {
+            Self::from_str(v).map_err(|_| FromCqlValError::BadCqlType)
+        } else {
+            Err(FromCqlValError::BadCqlType)
+        }
+    }
+}
+
+impl scylla::frame::value::Value for ImageUrl {
+    fn serialize(&self, buf: &mut Vec<u8>) -> Result<(), ValueTooBig> {
+        self.0.serialize(buf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_image_url() {
+        assert!(ImageUrl::from_str("https://cdn.example.com/banner.png").is_ok());
+        assert!(ImageUrl::from_str("http://cdn.example.com/banner.png").is_err());
+        assert!(ImageUrl::from_str("https://10.0.0.1/banner.png").is_err());
+    }
+
+    #[test]
+    fn test_image_url_deserialize() {
+        let url: ImageUrl = serde_json::from_str(r#""https://cdn.example.com/a.png""#).unwrap();
+        assert_eq!(url.as_str(), "https://cdn.example.com/a.png");
+
+        assert!(serde_json::from_str::<ImageUrl>(r#""http://cdn.example.com/a.png""#).is_err());
+        assert!(serde_json::from_str::<ImageUrl>(r#""https://127.0.0.1/a.png""#).is_err());
+    }
+
+    #[test]
+    fn test_format_from_mime() {
+        assert_eq!(
+            ImageFormat::from_mime("image/PNG; charset=binary"),
+            Some(ImageFormat::Png)
+        );
+        assert_eq!(ImageFormat::from_mime("image/svg+xml"), None);
+    }
+
+    #[test]
+    fn test_sniff_dimensions() {
+        let mut png = vec![0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+        png.extend_from_slice(&[0, 0, 0, 13, b'I', b'H', b'D', b'R']);
+        png.extend_from_slice(&960u32.to_be_bytes());
+        png.extend_from_slice(&540u32.to_be_bytes());
+        assert_eq!(sniff_dimensions(&png), Some((960, 540)));
+
+        let mut gif = b"GIF89a".to_vec();
+        gif.extend_from_slice(&320u16.to_le_bytes());
+        gif.extend_from_slice(&240u16.to_le_bytes());
+        assert_eq!(sniff_dimensions(&gif), Some((320, 240)));
+
+        // SOI, an APP0 segment with no payload then a baseline frame header.
+        let jpeg = [
+            0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x02, 0xFF, 0xC0, 0x00, 0x11, 0x08, 0x01, 0xE0, 0x02,
+            0x80,
+        ];
+        assert_eq!(sniff_dimensions(&jpeg), Some((640, 480)));
+
+        assert_eq!(sniff_dimensions(&png[..18]), None);
+        assert_eq!(sniff_dimensions(b"<svg></svg>"), None);
+    }
+}
@@ -0,0 +1,11 @@
+pub mod image;
+#[cfg(feature = "media-probe")]
+pub mod probe;
+pub mod signed_url;
+pub mod upload;
+
+pub use image::{sniff_dimensions, ImageFormat, ImageUrl};
+#[cfg(feature = "media-probe")]
+pub use probe::{probe, ImageInfo, ProbeConfig, ProbeError};
+pub use signed_url::{SignedUrlError, UrlSigner, DEFAULT_SIGNED_URL_TTL};
+pub use upload::{strip_metadata, UploadError, UploadedImage, ValidatedImage};
@@ -0,0 +1,226 @@
+use std::fmt::{Display, Formatter};
+use std::time::Duration;
+
+use reqwest::header::{CONTENT_LENGTH, CONTENT_TYPE, RANGE};
+use reqwest::redirect::Policy;
+use reqwest::Client;
+
+use crate::media::{sniff_dimensions, ImageFormat, ImageUrl};
+use crate::middleware::{with_deadline, DeadlineExceeded};
+use crate::types::url::OutboundUrlError;
+
+/// The number of bytes fetched when sniffing dimensions, this is enough to
+/// reach the frame header of most JPEGs with embedded metadata.
+const SNIFF_BYTES: u64 = 64 * 1024;
+
+#[derive(Debug, Clone)]
+pub struct ProbeConfig {
+    pub allowed: Vec<ImageFormat>,
+    pub max_bytes: u64,
+    pub sniff_dimensions: bool,
+    pub timeout: Duration,
+}
+
+impl Default for ProbeConfig {
+    fn default() -> Self {
+        Self {
+            allowed: vec![
+                ImageFormat::Png,
+                ImageFormat::Jpeg,
+                ImageFormat::Gif,
+                ImageFormat::Webp,
+            ],
+            max_bytes: 8 * 1024 * 1024,
+            sniff_dimensions: true,
+            timeout: Duration::from_secs(5),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImageInfo {
+    pub format: ImageFormat,
+    /// The size of the image if the server reported it.
+    pub bytes: Option<u64>,
+    pub dimensions: Option<(u32, u32)>,
+}
+
+#[derive(Debug)]
+pub enum ProbeError {
+    Request(reqwest::Error),
+    /// The host could not be resolved or resolved to a non public address.
+    Blocked(OutboundUrlError),
+    Status(u16),
+    UnsupportedType(String),
+    TooLarge(u64),
+    /// The file contents do not match the content type the server sent.
+    ContentMismatch,
+    DeadlineExceeded,
+}
+
+impl Display for ProbeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Request(e) => write!(f, "image request failed: {}", e),
+            Self::Blocked(e) => write!(f, "image url is not allowed: {}", e),
+            Self::Status(status) => write!(f, "image url returned {}", status),
+            Self::UnsupportedType(v) => write!(f, "unsupported image type {:?}", v),
+            Self::TooLarge(v) => write!(f, "image is too large ({} bytes)", v),
+            Self::ContentMismatch => write!(f, "image contents do not match its content type"),
+            Self::DeadlineExceeded => write!(f, "request deadline exceeded"),
+        }
+    }
+}
+
+impl std::error::Error for ProbeError {}
+
+impl From<reqwest::Error> for ProbeError {
+    fn from(e: reqwest::Error) -> Self {
+        Self::Request(e)
+    }
+}
+
+impl From<OutboundUrlError> for ProbeError {
+    fn from(e: OutboundUrlError) -> Self {
+        Self::Blocked(e)
+    }
+}
+
+impl From<DeadlineExceeded> for ProbeError {
+    fn from(_: DeadlineExceeded) -> Self {
+        Self::DeadlineExceeded
+    }
+}
+
+/// Checks a user provided image is reachable, of an allowed type and within
+/// the size limit.
+///
+/// The type and size come from a `HEAD` request, when sniffing is enabled the
+/// start of the file is fetched with a range request to verify the type and
+/// read the dimensions. If the server doesn't report a size the body is
+/// streamed instead and rejected once it passes `max_bytes`.
+///
+/// The host is resolved once and the client is pinned to those addresses, so
+/// neither DNS rebinding nor redirects can be used to reach internal
+/// addresses.
+pub async fn probe(url: &ImageUrl, config: &ProbeConfig) -> Result<ImageInfo, ProbeError> {
+    let addrs = with_deadline(url.resolve()).await??;
+
+    let mut builder = Client::builder()
+        .redirect(Policy::none())
+        .timeout(config.timeout);
+    if let Some(host) = url.host_str() {
+        builder = builder.resolve_to_addrs(host, &addrs);
+    }
+    let client = builder.build()?;
+
+    let resp = with_deadline(client.head(url.as_str()).send()).await??;
+    if !resp.status().is_success() {
+        return Err(ProbeError::Status(resp.status().as_u16()));
+    }
+
+    let content_type = resp
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+    let format = ImageFormat::from_mime(&content_type)
+        .filter(|v| config.allowed.contains(v))
+        .ok_or(ProbeError::UnsupportedType(content_type))?;
+
+    let bytes = resp
+        .headers()
+        .get(CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<u64>().ok());
+    if let Some(size) = bytes.filter(|v| *v > config.max_bytes) {
+        return Err(ProbeError::TooLarge(size));
+    }
+
+    if bytes.is_none() {
+        return probe_unsized(&client, url, format, config).await;
+    }
+
+    if !config.sniff_dimensions {
+        return Ok(ImageInfo {
+            format,
+            bytes,
+            dimensions: None,
+        });
+    }
+
+    let resp = with_deadline(
+        client
+            .get(url.as_str())
+            .header(RANGE, format!("bytes=0-{}", SNIFF_BYTES - 1))
+            .send(),
+    )
+    .await??;
+    if !resp.status().is_success() {
+        return Err(ProbeError::Status(resp.status().as_u16()));
+    }
+
+    let head = read_prefix(resp, SNIFF_BYTES as usize).await?;
+    if ImageFormat::sniff(&head) != Some(format) {
+        return Err(ProbeError::ContentMismatch);
+    }
+
+    Ok(ImageInfo {
+        format,
+        bytes,
+        dimensions: sniff_dimensions(&head),
+    })
+}
+
+/// Streams the whole body of an image without a `Content-Length`, failing as
+/// soon as it goes over the size limit.
+async fn probe_unsized(
+    client: &Client,
+    url: &ImageUrl,
+    format: ImageFormat,
+    config: &ProbeConfig,
+) -> Result<ImageInfo, ProbeError> {
+    let resp = with_deadline(client.get(url.as_str()).send()).await??;
+    if !resp.status().is_success() {
+        return Err(ProbeError::Status(resp.status().as_u16()));
+    }
+
+    let limit = usize::try_from(config.max_bytes).unwrap_or(usize::MAX - 1);
+    let body = read_prefix(resp, limit + 1).await?;
+    if body.len() > limit {
+        return Err(ProbeError::TooLarge(body.len() as u64));
+    }
+
+    if ImageFormat::sniff(&body) != Some(format) {
+        return Err(ProbeError::ContentMismatch);
+    }
+
+    let dimensions = if config.sniff_dimensions {
+        sniff_dimensions(&body)
+    } else {
+        None
+    };
+
+    Ok(ImageInfo {
+        format,
+        bytes: Some(body.len() as u64),
+        dimensions,
+    })
+}
+
+/// Reads at most `limit` bytes of the body, servers which ignore the range
+/// header are not read to the end.
+async fn read_prefix(mut resp: reqwest::Response, limit: usize) -> Result<Vec<u8>, ProbeError> {
+    let mut buf = Vec::new();
+
+    while buf.len() < limit {
+        match with_deadline(resp.chunk()).await?? {
+            Some(chunk) => buf.extend_from_slice(&chunk),
+            None => break,
+        }
+    }
+
+    buf.truncate(limit);
+    Ok(buf)
+}
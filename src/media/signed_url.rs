@@ -0,0 +1,212 @@
+use std::fmt::{Debug, Display, Formatter};
+
+use chrono::{DateTime, Duration};
+use hmac::Mac;
+use url::Url;
+
+use crate::crypto::{hmac_sha256, signing_key, EmptySecret, HmacSha256};
+use crate::types::{DiscordUrl, Secret, Timestamp};
+
+/// How long urls signed by [UrlSigner::signed_url] are valid for.
+pub const DEFAULT_SIGNED_URL_TTL: Duration = Duration::minutes(15);
+
+/// How far past its expiry a url is still accepted, covering clock drift
+/// between the API and the servers verifying the url.
+pub const CLOCK_SKEW_TOLERANCE: Duration = Duration::seconds(30);
+
+const EXPIRES_PARAM: &str = "expires";
+const SIGNATURE_PARAM: &str = "signature";
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SignedUrlError {
+    /// The url has no `expires` or `signature` query parameter.
+    Unsigned,
+    Malformed,
+    Expired,
+    Mismatch,
+}
+
+impl Display for SignedUrlError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Unsigned => write!(f, "url is not signed"),
+            Self::Malformed => write!(f, "malformed url signature"),
+            Self::Expired => write!(f, "signed url has expired"),
+            Self::Mismatch => write!(f, "url signature does not match"),
+        }
+    }
+}
+
+impl std::error::Error for SignedUrlError {}
+
+/// Signs urls to private assets, such as banners waiting for review, so
+/// they can be fetched directly for a limited time.
+///
+/// The expiry and a hex HMAC of the rest of the url are added as the
+/// `expires` and `signature` query parameters.
+#[derive(Clone)]
+pub struct UrlSigner {
+    secret: Secret<Vec<u8>>,
+}
+
+impl UrlSigner {
+    pub fn new(secret: impl AsRef<[u8]>) -> Result<Self, EmptySecret> {
+        Ok(Self {
+            secret: signing_key(secret)?,
+        })
+    }
+
+    fn mac(&self, url: &Url) -> HmacSha256 {
+        hmac_sha256(self.secret.expose(), &[url.as_str().as_bytes()])
+    }
+
+    /// Signs the url to expire after [DEFAULT_SIGNED_URL_TTL].
+    pub fn signed_url(&self, url: &DiscordUrl) -> DiscordUrl {
+        let expires_at = Timestamp(Timestamp::default().0 + DEFAULT_SIGNED_URL_TTL);
+        self.sign(url, expires_at)
+    }
+
+    pub fn sign(&self, url: &DiscordUrl, expires_at: Timestamp) -> DiscordUrl {
+        let mut url = without_params(&url.0, &[EXPIRES_PARAM, SIGNATURE_PARAM]);
+        url.query_pairs_mut()
+            .append_pair(EXPIRES_PARAM, &expires_at.0.timestamp().to_string());
+
+        let signature = self.mac(&url).finalize().into_bytes();
+        url.query_pairs_mut()
+            .append_pair(SIGNATURE_PARAM, &hex::encode(signature));
+
+        DiscordUrl(url)
+    }
+
+    /// Verifies the signature in constant time and that the url has not
+    /// expired, allowing for [CLOCK_SKEW_TOLERANCE].
+    pub fn verify(&self, url: &Url, now: Timestamp) -> Result<(), SignedUrlError> {
+        let signature = url
+            .query_pairs()
+            .find(|(k, _)| k == SIGNATURE_PARAM)
+            .ok_or(SignedUrlError::Unsigned)?
+            .1;
+        let expires_at = url
+            .query_pairs()
+            .find(|(k, _)| k == EXPIRES_PARAM)
+            .ok_or(SignedUrlError::Unsigned)?
+            .1
+            .parse::<i64>()
+            .ok()
+            .and_then(|v| DateTime::from_timestamp(v, 0))
+            .ok_or(SignedUrlError::Malformed)?;
+        let signature = hex::decode(signature.as_ref()).map_err(|_| SignedUrlError::Malformed)?;
+
+        self.mac(&without_params(url, &[SIGNATURE_PARAM]))
+            .verify_slice(&signature)
+            .map_err(|_| SignedUrlError::Mismatch)?;
+
+        if now.0 > expires_at + CLOCK_SKEW_TOLERANCE {
+            return Err(SignedUrlError::Expired);
+        }
+
+        Ok(())
+    }
+}
+
+impl Debug for UrlSigner {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("UrlSigner").finish_non_exhaustive()
+    }
+}
+
+/// The url without the given query parameters, so signing an already
+/// signed url replaces its expiry and signature.
+fn without_params(url: &Url, params: &[&str]) -> Url {
+    let pairs = url
+        .query_pairs()
+        .filter(|(k, _)| !params.contains(&k.as_ref()))
+        .map(|(k, v)| (k.into_owned(), v.into_owned()))
+        .collect::<Vec<_>>();
+
+    let mut url = url.clone();
+    url.set_query(None);
+    if !pairs.is_empty() {
+        url.query_pairs_mut().extend_pairs(pairs);
+    }
+
+    url
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    const EXPIRES: i64 = 1656633600;
+
+    fn signed() -> DiscordUrl {
+        let url =
+            DiscordUrl::from_str("https://cdn.discordlist.gg/banners/1.webp?size=512").unwrap();
+        UrlSigner::new("secret")
+            .unwrap()
+            .sign(&url, Timestamp::from(EXPIRES))
+    }
+
+    #[test]
+    fn test_sign_and_verify() {
+        assert!(UrlSigner::new("").is_err());
+
+        let signer = UrlSigner::new("secret").unwrap();
+        let url = signed();
+
+        assert!(url.as_str().starts_with(
+            "https://cdn.discordlist.gg/banners/1.webp?size=512&expires=1656633600&signature="
+        ));
+        assert_eq!(signer.verify(&url, Timestamp::from(EXPIRES)), Ok(()));
+        assert_eq!(
+            UrlSigner::new("other")
+                .unwrap()
+                .verify(&url, Timestamp::from(0)),
+            Err(SignedUrlError::Mismatch)
+        );
+    }
+
+    #[test]
+    fn test_expiry_tolerates_skew() {
+        let signer = UrlSigner::new("secret").unwrap();
+        let url = signed();
+        let tolerance = CLOCK_SKEW_TOLERANCE.num_seconds();
+
+        assert_eq!(
+            signer.verify(&url, Timestamp::from(EXPIRES + tolerance)),
+            Ok(())
+        );
+        assert_eq!(
+            signer.verify(&url, Timestamp::from(EXPIRES + tolerance + 1)),
+            Err(SignedUrlError::Expired)
+        );
+    }
+
+    #[test]
+    fn test_rejects_tampered_urls() {
+        let signer = UrlSigner::new("secret").unwrap();
+        let url = signed();
+        let now = Timestamp::from(0);
+
+        let extended = Url::parse(
+            &url.as_str()
+                .replace("expires=1656633600", "expires=1756633600"),
+        )
+        .unwrap();
+        assert_eq!(signer.verify(&extended, now), Err(SignedUrlError::Mismatch));
+
+        let other = Url::parse(&url.as_str().replace("1.webp", "2.webp")).unwrap();
+        assert_eq!(signer.verify(&other, now), Err(SignedUrlError::Mismatch));
+
+        let unsigned = Url::parse("https://cdn.discordlist.gg/banners/1.webp").unwrap();
+        assert_eq!(signer.verify(&unsigned, now), Err(SignedUrlError::Unsigned));
+
+        let resigned = signer.sign(&url, Timestamp::from(EXPIRES + 60));
+        assert_eq!(resigned.query_pairs().count(), 3);
+        assert_eq!(
+            signer.verify(&resigned, Timestamp::from(EXPIRES + 60)),
+            Ok(())
+        );
+    }
+}
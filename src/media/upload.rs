@@ -0,0 +1,431 @@
+use std::fmt::{Display, Formatter};
+
+use poem_openapi::types::multipart::Upload;
+use poem_openapi::Multipart;
+use sha2::{Digest, Sha256};
+use tokio::io::AsyncReadExt;
+
+use crate::errors::ApiError;
+use crate::media::{sniff_dimensions, ImageFormat};
+use crate::types::DiscordUrl;
+
+/// The default upload size limit of 8MiB.
+pub const DEFAULT_MAX_UPLOAD_BYTES: u64 = 8 * 1024 * 1024;
+
+#[derive(Debug)]
+pub enum UploadError {
+    Io(std::io::Error),
+    TooLarge(u64),
+    /// The file is not one of the supported image formats.
+    UnsupportedType,
+    /// The file claims to be a supported format but could not be parsed.
+    Corrupt,
+}
+
+impl Display for UploadError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "failed to read upload: {}", e),
+            Self::TooLarge(max) => write!(f, "image must be at most {} bytes", max),
+            Self::UnsupportedType => write!(f, "image must be a png, jpeg, gif or webp"),
+            Self::Corrupt => write!(f, "image could not be read"),
+        }
+    }
+}
+
+impl std::error::Error for UploadError {}
+
+impl From<UploadError> for ApiError {
+    fn from(e: UploadError) -> Self {
+        match e {
+            UploadError::Io(_) => Self::internal(),
+            e => Self::validation(e.to_string()),
+        }
+    }
+}
+
+/// A single image uploaded as `multipart/form-data`, shared by every edit
+/// endpoint which accepts images.
+#[derive(Multipart, Debug)]
+pub struct UploadedImage {
+    pub image: Upload,
+}
+
+impl UploadedImage {
+    /// Reads the upload, rejecting it as soon as it exceeds `max_bytes`.
+    pub async fn validate(self, max_bytes: u64) -> Result<ValidatedImage, UploadError> {
+        let mut data = Vec::new();
+        self.image
+            .into_async_read()
+            .take(max_bytes + 1)
+            .read_to_end(&mut data)
+            .await
+            .map_err(UploadError::Io)?;
+
+        ValidatedImage::from_bytes(data, max_bytes)
+    }
+}
+
+/// An uploaded image which has been sniffed and had its metadata stripped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidatedImage {
+    pub format: ImageFormat,
+    pub dimensions: Option<(u32, u32)>,
+    pub data: Vec<u8>,
+}
+
+impl ValidatedImage {
+    pub fn from_bytes(data: Vec<u8>, max_bytes: u64) -> Result<Self, UploadError> {
+        if data.len() as u64 > max_bytes {
+            return Err(UploadError::TooLarge(max_bytes));
+        }
+
+        let format = ImageFormat::sniff(&data).ok_or(UploadError::UnsupportedType)?;
+        let data = strip_metadata(format, &data).ok_or(UploadError::Corrupt)?;
+
+        Ok(Self {
+            format,
+            dimensions: sniff_dimensions(&data),
+            data,
+        })
+    }
+
+    /// The object store key for the image under the given prefix.
+    ///
+    /// Keys are derived from the contents so re-uploading the same image does
+    /// not create a new object.
+    pub fn key(&self, prefix: &str) -> String {
+        let hash = hex::encode(Sha256::digest(&self.data));
+        format!(
+            "{}/{}.{}",
+            prefix.trim_end_matches('/'),
+            hash,
+            self.format.extension()
+        )
+    }
+
+    /// The public URL of an object store key served from the given CDN base.
+    ///
+    /// The key is always added below the base's path, even if the base
+    /// doesn't end with a `/`.
+    pub fn url_for(cdn_base: &DiscordUrl, key: &str) -> Option<DiscordUrl> {
+        let mut base = cdn_base.0.clone();
+        if !base.path().ends_with('/') {
+            base.set_path(&format!("{}/", base.path()));
+        }
+
+        base.join(key.trim_start_matches('/')).ok().map(DiscordUrl)
+    }
+}
+
+const XMP_JPEG_HEADERS: [&[u8]; 2] = [
+    b"http://ns.adobe.com/xap/1.0/\0",
+    b"http://ns.adobe.com/xmp/extension/\0",
+];
+const XMP_PNG_KEYWORD: &[u8] = b"XML:com.adobe.xmp\0";
+const XMP_GIF_APPLICATION: &[u8] = b"XMP DataXMP";
+
+/// Removes EXIF and XMP metadata from the image, either may contain the
+/// location it was taken at or details of its author.
+///
+/// Returns `None` if the image structure cannot be parsed.
+pub fn strip_metadata(format: ImageFormat, data: &[u8]) -> Option<Vec<u8>> {
+    match format {
+        ImageFormat::Jpeg => strip_jpeg(data),
+        ImageFormat::Png => strip_png(data),
+        ImageFormat::Webp => strip_webp(data),
+        ImageFormat::Gif => strip_gif(data),
+    }
+}
+
+fn strip_jpeg(data: &[u8]) -> Option<Vec<u8>> {
+    let mut out = data.get(..2)?.to_vec();
+    let mut i = 2;
+
+    loop {
+        if *data.get(i)? != 0xFF {
+            return None;
+        }
+
+        let marker = *data.get(i + 1)?;
+
+        // Markers without a length.
+        if matches!(marker, 0x01 | 0xD0..=0xD7) {
+            out.extend_from_slice(&data[i..i + 2]);
+            i += 2;
+            continue;
+        }
+
+        // The start of scan is followed by the image data which runs to the end.
+        if matches!(marker, 0xD9 | 0xDA) {
+            out.extend_from_slice(&data[i..]);
+            return Some(out);
+        }
+
+        let len = u16::from_be_bytes(data.get(i + 2..i + 4)?.try_into().ok()?) as usize;
+        let segment = data.get(i..i + 2 + len)?;
+
+        let payload = segment.get(4..).unwrap_or_default();
+        let is_metadata = marker == 0xE1
+            && (payload.starts_with(b"Exif\0\0")
+                || XMP_JPEG_HEADERS.iter().any(|v| payload.starts_with(v)));
+        if !is_metadata {
+            out.extend_from_slice(segment);
+        }
+
+        i += 2 + len;
+    }
+}
+
+fn strip_png(data: &[u8]) -> Option<Vec<u8>> {
+    let mut out = data.get(..8)?.to_vec();
+    let mut i = 8;
+
+    while i < data.len() {
+        let len = u32::from_be_bytes(data.get(i..i + 4)?.try_into().ok()?) as usize;
+        let kind = data.get(i + 4..i + 8)?;
+        let chunk = data.get(i..i + 12 + len)?;
+
+        let is_xmp = kind == b"iTXt" && chunk[8..].starts_with(XMP_PNG_KEYWORD);
+        if kind != b"eXIf" && !is_xmp {
+            out.extend_from_slice(chunk);
+        }
+
+        i += 12 + len;
+
+        if kind == b"IEND" {
+            break;
+        }
+    }
+
+    Some(out)
+}
+
+fn strip_webp(data: &[u8]) -> Option<Vec<u8>> {
+    const EXIF_FLAG: u8 = 0x08;
+    const XMP_FLAG: u8 = 0x04;
+
+    let mut out = data.get(..12)?.to_vec();
+    let mut i = 12;
+
+    while i < data.len() {
+        let kind = data.get(i..i + 4)?;
+        let len = u32::from_le_bytes(data.get(i + 4..i + 8)?.try_into().ok()?) as usize;
+        let padded = len + (len & 1);
+        let chunk = data.get(i..(i + 8 + padded).min(data.len()))?;
+
+        if kind == b"VP8X" {
+            let start = out.len();
+            out.extend_from_slice(chunk);
+            *out.get_mut(start + 8)? &= !(EXIF_FLAG | XMP_FLAG);
+        } else if kind != b"EXIF" && kind != b"XMP " {
+            out.extend_from_slice(chunk);
+        }
+
+        i += 8 + padded;
+    }
+
+    let riff_size = (out.len() - 8) as u32;
+    out[4..8].copy_from_slice(&riff_size.to_le_bytes());
+
+    Some(out)
+}
+
+fn strip_gif(data: &[u8]) -> Option<Vec<u8>> {
+    const HAS_COLOR_TABLE: u8 = 0x80;
+
+    let color_table = |flags: u8| {
+        if flags & HAS_COLOR_TABLE != 0 {
+            3 << ((flags & 0x07) + 1)
+        } else {
+            0
+        }
+    };
+
+    // The offset just past the data sub-blocks starting at `i`.
+    let skip_blocks = |mut i: usize| loop {
+        let len = *data.get(i)? as usize;
+        i += 1 + len;
+        if len == 0 {
+            return Some(i);
+        }
+    };
+
+    let mut i = 13 + color_table(*data.get(10)?);
+    let mut out = data.get(..i)?.to_vec();
+
+    loop {
+        let start = i;
+
+        match *data.get(i)? {
+            // Extensions, XMP is stored in an application extension.
+            0x21 => {
+                let label = *data.get(i + 1)?;
+                i = skip_blocks(i + 2)?;
+
+                let is_xmp = label == 0xFF
+                    && data.get(start + 2) == Some(&11)
+                    && data.get(start + 3..start + 14) == Some(XMP_GIF_APPLICATION);
+                if !is_xmp {
+                    out.extend_from_slice(&data[start..i]);
+                }
+            }
+            // An image descriptor followed by its pixel data.
+            0x2C => {
+                i += 10 + color_table(*data.get(i + 9)?);
+                i = skip_blocks(i + 1)?;
+                out.extend_from_slice(&data[start..i]);
+            }
+            // The trailer.
+            0x3B => {
+                out.push(0x3B);
+                return Some(out);
+            }
+            _ => return None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn jpeg_with_exif() -> Vec<u8> {
+        let mut data = vec![0xFF, 0xD8];
+        // APP1 containing EXIF.
+        data.extend_from_slice(&[0xFF, 0xE1, 0x00, 0x0A]);
+        data.extend_from_slice(b"Exif\0\0GP");
+        // APP1 containing XMP.
+        let xmp = b"http://ns.adobe.com/xap/1.0/\0<x:xmpmeta/>";
+        data.extend_from_slice(&[0xFF, 0xE1, 0x00, xmp.len() as u8 + 2]);
+        data.extend_from_slice(xmp);
+        // Baseline frame header for a 640x480 image.
+        data.extend_from_slice(&[
+            0xFF, 0xC0, 0x00, 0x11, 0x08, 0x01, 0xE0, 0x02, 0x80, 0x03, 0x01, 0x22, 0x00, 0x02,
+            0x11, 0x01, 0x03, 0x11, 0x01,
+        ]);
+        data.extend_from_slice(&[0xFF, 0xDA, 0x00, 0x02, 0x12, 0x34, 0xFF, 0xD9]);
+        data
+    }
+
+    #[test]
+    fn test_jpeg_exif_stripped() {
+        let image = ValidatedImage::from_bytes(jpeg_with_exif(), 1024).unwrap();
+
+        assert_eq!(image.format, ImageFormat::Jpeg);
+        assert_eq!(image.dimensions, Some((640, 480)));
+        assert!(!image.data.windows(4).any(|v| v == b"Exif"));
+        assert!(!image.data.windows(7).any(|v| v == b"xmpmeta"));
+        assert!(image.data.ends_with(&[0xFF, 0xD9]));
+    }
+
+    #[test]
+    fn test_webp_exif_stripped() {
+        let mut data = b"RIFF\0\0\0\0WEBP".to_vec();
+        data.extend_from_slice(b"VP8X");
+        data.extend_from_slice(&10u32.to_le_bytes());
+        data.extend_from_slice(&[0x0C, 0, 0, 0, 0x7F, 0x02, 0x00, 0xDF, 0x01, 0x00]);
+        data.extend_from_slice(b"EXIF");
+        data.extend_from_slice(&3u32.to_le_bytes());
+        data.extend_from_slice(b"GPS\0");
+        data.extend_from_slice(b"XMP ");
+        data.extend_from_slice(&4u32.to_le_bytes());
+        data.extend_from_slice(b"<x/>");
+
+        let stripped = strip_metadata(ImageFormat::Webp, &data).unwrap();
+        assert_eq!(stripped.len(), 30);
+        assert_eq!(stripped[20] & 0x0C, 0);
+        assert_eq!(&stripped[4..8], &22u32.to_le_bytes());
+        assert_eq!(sniff_dimensions(&stripped), Some((640, 480)));
+    }
+
+    #[test]
+    fn test_png_xmp_stripped() {
+        fn chunk(kind: &[u8], data: &[u8]) -> Vec<u8> {
+            let mut out = (data.len() as u32).to_be_bytes().to_vec();
+            out.extend_from_slice(kind);
+            out.extend_from_slice(data);
+            out.extend_from_slice(&[0; 4]);
+            out
+        }
+
+        let mut data = b"\x89PNG\r\n\x1a\n".to_vec();
+        data.extend(chunk(
+            b"IHDR",
+            &[0, 0, 2, 0x80, 0, 0, 1, 0xE0, 8, 6, 0, 0, 0],
+        ));
+        data.extend(chunk(b"iTXt", b"XML:com.adobe.xmp\0\0\0\0\0<x/>"));
+        data.extend(chunk(b"iTXt", b"Comment\0\0\0\0\0hello"));
+        data.extend(chunk(b"eXIf", b"GPS"));
+        data.extend(chunk(b"IEND", b""));
+
+        let stripped = strip_metadata(ImageFormat::Png, &data).unwrap();
+        assert!(!stripped.windows(3).any(|v| v == b"XML" || v == b"GPS"));
+        assert!(stripped.windows(5).any(|v| v == b"hello"));
+        assert_eq!(sniff_dimensions(&stripped), Some((640, 480)));
+    }
+
+    #[test]
+    fn test_gif_xmp_stripped() {
+        // A 1x1 image with a two colour global table.
+        let mut data = b"GIF89a\x01\x00\x01\x00\x80\x00\x00".to_vec();
+        data.extend_from_slice(&[0, 0, 0, 0xFF, 0xFF, 0xFF]);
+        // Graphic control extension.
+        data.extend_from_slice(&[0x21, 0xF9, 0x04, 0x00, 0x00, 0x00, 0x00, 0x00]);
+        // XMP application extension.
+        data.extend_from_slice(&[0x21, 0xFF, 0x0B]);
+        data.extend_from_slice(b"XMP DataXMP");
+        data.extend_from_slice(&[0x04, b'<', b'x', b'/', b'>', 0x00]);
+        // Image descriptor and pixel data.
+        data.extend_from_slice(&[0x2C, 0, 0, 0, 0, 1, 0, 1, 0, 0x00]);
+        data.extend_from_slice(&[0x02, 0x02, 0x44, 0x01, 0x00, 0x3B]);
+
+        let stripped = strip_metadata(ImageFormat::Gif, &data).unwrap();
+        assert_eq!(stripped.len(), data.len() - 20);
+        assert!(!stripped.windows(3).any(|v| v == b"XMP"));
+        assert!(stripped.ends_with(&[0x44, 0x01, 0x00, 0x3B]));
+
+        assert_eq!(
+            strip_metadata(ImageFormat::Gif, &data[..data.len() - 3]),
+            None
+        );
+    }
+
+    #[test]
+    fn test_rejected_uploads() {
+        assert!(matches!(
+            ValidatedImage::from_bytes(jpeg_with_exif(), 8),
+            Err(UploadError::TooLarge(8))
+        ));
+        assert!(matches!(
+            ValidatedImage::from_bytes(b"<svg></svg>".to_vec(), 1024),
+            Err(UploadError::UnsupportedType)
+        ));
+        assert!(matches!(
+            ValidatedImage::from_bytes(vec![0xFF, 0xD8, 0xFF, 0xE0, 0x00], 1024),
+            Err(UploadError::Corrupt)
+        ));
+    }
+
+    #[test]
+    fn test_storage_key() {
+        let image = ValidatedImage::from_bytes(jpeg_with_exif(), 1024).unwrap();
+        let key = image.key("bots/banners/");
+
+        assert!(key.starts_with("bots/banners/"));
+        assert!(key.ends_with(".jpg"));
+        assert_eq!(key, image.key("bots/banners"));
+
+        let base = DiscordUrl::from_str("https://cdn.discordlist.gg/").unwrap();
+        let url = ValidatedImage::url_for(&base, &key).unwrap();
+        assert_eq!(url.as_str(), format!("https://cdn.discordlist.gg/{}", key));
+
+        let base = DiscordUrl::from_str("https://cdn.discordlist.gg/media").unwrap();
+        let url = ValidatedImage::url_for(&base, &key).unwrap();
+        assert_eq!(
+            url.as_str(),
+            format!("https://cdn.discordlist.gg/media/{}", key)
+        );
+    }
+}
@@ -0,0 +1,76 @@
+use poem::{Endpoint, IntoResponse, Middleware, PathPattern, Request, Response, Result};
+use tokio::time::Instant;
+
+use crate::metrics::record_request;
+
+/// The route label used for requests which did not match a route, so
+/// unknown paths cannot blow up the label cardinality.
+const UNMATCHED_ROUTE: &str = "unmatched";
+
+/// Records the latency of every request, labelled by the matched route pattern.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct RouteMetrics;
+
+impl<E: Endpoint> Middleware<E> for RouteMetrics {
+    type Output = RouteMetricsEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        RouteMetricsEndpoint { inner: ep }
+    }
+}
+
+pub struct RouteMetricsEndpoint<E> {
+    inner: E,
+}
+
+#[poem::async_trait]
+impl<E: Endpoint> Endpoint for RouteMetricsEndpoint<E> {
+    type Output = Response;
+
+    async fn call(&self, req: Request) -> Result<Self::Output> {
+        let method = req.method().to_string();
+        let start = Instant::now();
+
+        let result = self.inner.call(req).await.map(IntoResponse::into_response);
+        let elapsed = start.elapsed();
+
+        let (route, status) = match &result {
+            Ok(resp) => (resp.data::<PathPattern>(), resp.status()),
+            Err(err) => (err.data::<PathPattern>(), err.status()),
+        };
+        let route = route.map_or(UNMATCHED_ROUTE, |v| &v.0);
+        record_request(&method, route, status.as_u16(), elapsed);
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use poem::http::{StatusCode, Uri};
+    use poem::{handler, EndpointExt, Route};
+
+    #[handler]
+    fn hello() -> &'static str {
+        "hello"
+    }
+
+    #[test]
+    fn test_passes_responses_through() {
+        let app = Route::new().at("/bots/:id", hello).with(RouteMetrics);
+
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        rt.block_on(async {
+            let req = Request::builder().uri(Uri::from_static("/bots/1")).finish();
+            assert_eq!(app.get_response(req).await.status(), StatusCode::OK);
+
+            let req = Request::builder()
+                .uri(Uri::from_static("/missing"))
+                .finish();
+            assert_eq!(app.get_response(req).await.status(), StatusCode::NOT_FOUND);
+        });
+    }
+}
@@ -0,0 +1,8 @@
+mod middleware;
+mod standard;
+
+pub use middleware::{RouteMetrics, RouteMetricsEndpoint};
+pub use standard::{
+    describe, record_cache, record_query, record_request, record_tag_reload, CACHE_REQUESTS,
+    REQUEST_DURATION, SCYLLA_QUERY_DURATION, TAG_REGISTRY_RELOADS,
+};
@@ -0,0 +1,53 @@
+use std::time::Duration;
+
+use ::metrics::{counter, describe_counter, describe_histogram, histogram, Unit};
+
+/// Labelled by `method`, `route` and `status`.
+pub const REQUEST_DURATION: &str = "http_request_duration_seconds";
+/// Labelled by `query` and `outcome`.
+pub const SCYLLA_QUERY_DURATION: &str = "scylla_query_duration_seconds";
+/// Labelled by `cache` and `result`, either `hit` or `miss`.
+pub const CACHE_REQUESTS: &str = "cache_requests_total";
+/// Labelled by `registry`.
+pub const TAG_REGISTRY_RELOADS: &str = "tag_registry_reloads_total";
+
+/// Registers the descriptions of the standard metrics with the installed recorder.
+pub fn describe() {
+    describe_histogram!(
+        REQUEST_DURATION,
+        Unit::Seconds,
+        "HTTP request latency by route."
+    );
+    describe_histogram!(
+        SCYLLA_QUERY_DURATION,
+        Unit::Seconds,
+        "Scylla query latency by query name."
+    );
+    describe_counter!(CACHE_REQUESTS, "Cache lookups by cache and result.");
+    describe_counter!(TAG_REGISTRY_RELOADS, "Tag registry reloads by registry.");
+}
+
+pub fn record_request(method: &str, route: &str, status: u16, elapsed: Duration) {
+    histogram!(
+        REQUEST_DURATION,
+        "method" => method.to_string(),
+        "route" => route.to_string(),
+        "status" => status.to_string(),
+    )
+    .record(elapsed);
+}
+
+/// `query` should be a short, stable name rather than the CQL itself.
+pub fn record_query(query: &'static str, is_ok: bool, elapsed: Duration) {
+    let outcome = if is_ok { "ok" } else { "error" };
+    histogram!(SCYLLA_QUERY_DURATION, "query" => query, "outcome" => outcome).record(elapsed);
+}
+
+pub fn record_cache(cache: &'static str, is_hit: bool) {
+    let result = if is_hit { "hit" } else { "miss" };
+    counter!(CACHE_REQUESTS, "cache" => cache, "result" => result).increment(1);
+}
+
+pub fn record_tag_reload(registry: &'static str) {
+    counter!(TAG_REGISTRY_RELOADS, "registry" => registry).increment(1);
+}
@@ -0,0 +1,201 @@
+use std::future::Future;
+use std::time::Duration;
+
+use poem::{Endpoint, Middleware, Request, Result};
+use scylla::transport::errors::QueryError;
+use tokio::time::Instant;
+
+/// The time left to handle the request in milliseconds.
+///
+/// A duration is sent rather than a point in time so the deadline does not
+/// depend on the clocks of both services agreeing.
+pub const DEADLINE_HEADER: &str = "X-Request-Timeout-Ms";
+
+tokio::task_local! {
+    static DEADLINE: Instant;
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct DeadlineExceeded;
+
+impl std::fmt::Display for DeadlineExceeded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "request deadline exceeded")
+    }
+}
+
+impl std::error::Error for DeadlineExceeded {}
+
+impl From<DeadlineExceeded> for QueryError {
+    fn from(_: DeadlineExceeded) -> Self {
+        QueryError::RequestTimeout("request deadline exceeded".to_string())
+    }
+}
+
+/// The deadline of the current request, if one is set.
+pub fn current_deadline() -> Option<Instant> {
+    DEADLINE.try_with(|v| *v).ok()
+}
+
+/// The time left before the current request's deadline.
+///
+/// Returns `None` when called outside of a request with a deadline.
+pub fn remaining_budget() -> Option<Duration> {
+    current_deadline().map(|v| v.saturating_duration_since(Instant::now()))
+}
+
+/// Runs the future, failing with [DeadlineExceeded] if the current request's
+/// deadline passes before it completes.
+pub async fn with_deadline<F: Future>(fut: F) -> Result<F::Output, DeadlineExceeded> {
+    match current_deadline() {
+        Some(deadline) => tokio::time::timeout_at(deadline, fut)
+            .await
+            .map_err(|_| DeadlineExceeded),
+        None => Ok(fut.await),
+    }
+}
+
+/// Adds the time left before the current request's deadline to an outbound
+/// request to one of our services, so it gives up when we would.
+///
+/// This must not be used for third party APIs.
+#[cfg(feature = "reqwest")]
+pub fn propagate_deadline(builder: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+    match remaining_budget() {
+        Some(budget) => builder.header(DEADLINE_HEADER, budget.as_millis().to_string()),
+        None => builder,
+    }
+}
+
+/// Runs the future with the given deadline, this is used to carry the
+/// deadline over to spawned tasks.
+pub async fn scope_deadline<F: Future>(deadline: Instant, fut: F) -> F::Output {
+    DEADLINE.scope(deadline, fut).await
+}
+
+/// Assigns every request a deadline from the `X-Request-Timeout-Ms` header.
+///
+/// The header is the time left in milliseconds, requests without the header
+/// get the default budget. Deadlines are capped to the max
+/// budget so callers cannot hold resources indefinitely.
+#[derive(Debug, Copy, Clone)]
+pub struct Deadline {
+    default_budget: Duration,
+    max_budget: Duration,
+}
+
+impl Deadline {
+    pub fn new(default_budget: Duration) -> Self {
+        Self {
+            default_budget,
+            max_budget: default_budget,
+        }
+    }
+
+    pub fn with_max_budget(mut self, max_budget: Duration) -> Self {
+        self.max_budget = max_budget;
+        self
+    }
+
+    fn budget_for(&self, header: Option<&str>) -> Duration {
+        header
+            .and_then(|v| v.trim().parse::<u64>().ok())
+            .map(Duration::from_millis)
+            .unwrap_or(self.default_budget)
+            .min(self.max_budget)
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for Deadline {
+    type Output = DeadlineEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        DeadlineEndpoint {
+            inner: ep,
+            config: *self,
+        }
+    }
+}
+
+pub struct DeadlineEndpoint<E> {
+    inner: E,
+    config: Deadline,
+}
+
+#[poem::async_trait]
+impl<E: Endpoint> Endpoint for DeadlineEndpoint<E> {
+    type Output = E::Output;
+
+    async fn call(&self, req: Request) -> Result<Self::Output> {
+        let budget = self.config.budget_for(req.header(DEADLINE_HEADER));
+        let deadline = Instant::now() + budget;
+
+        scope_deadline(deadline, self.inner.call(req)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_budget_from_header() {
+        let config = Deadline::new(Duration::from_secs(5)).with_max_budget(Duration::from_secs(30));
+
+        assert_eq!(config.budget_for(Some("10000")), Duration::from_secs(10));
+        assert_eq!(config.budget_for(Some(" 250 ")), Duration::from_millis(250));
+        assert_eq!(config.budget_for(Some("120000")), Duration::from_secs(30));
+
+        assert_eq!(config.budget_for(Some("0")), Duration::ZERO);
+        assert_eq!(config.budget_for(Some("soon")), Duration::from_secs(5));
+        assert_eq!(config.budget_for(None), Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_with_deadline() {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .unwrap();
+
+        rt.block_on(async {
+            assert_eq!(remaining_budget(), None);
+            assert_eq!(with_deadline(async { 1 }).await, Ok(1));
+
+            let deadline = Instant::now() + Duration::from_millis(10);
+            scope_deadline(deadline, async {
+                assert!(remaining_budget().unwrap() <= Duration::from_millis(10));
+
+                let res = with_deadline(tokio::time::sleep(Duration::from_secs(5))).await;
+                assert_eq!(res, Err(DeadlineExceeded));
+            })
+            .await;
+        });
+    }
+
+    #[cfg(feature = "reqwest")]
+    #[test]
+    fn test_propagate_deadline() {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .unwrap();
+        let client = reqwest::Client::new();
+
+        let req = propagate_deadline(client.get("http://search.internal"))
+            .build()
+            .unwrap();
+        assert!(req.headers().get(DEADLINE_HEADER).is_none());
+
+        let deadline = Instant::now() + Duration::from_secs(2);
+        let req = rt.block_on(scope_deadline(deadline, async {
+            propagate_deadline(client.get("http://search.internal"))
+                .build()
+                .unwrap()
+        }));
+
+        let budget = req.headers()[DEADLINE_HEADER].to_str().unwrap();
+        let budget = Duration::from_millis(budget.parse().unwrap());
+        assert!(budget > Duration::from_secs(1) && budget <= Duration::from_secs(2));
+    }
+}
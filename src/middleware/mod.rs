@@ -0,0 +1,9 @@
+pub mod deadline;
+pub mod request_id;
+
+#[cfg(feature = "reqwest")]
+pub use deadline::propagate_deadline;
+pub use deadline::{remaining_budget, with_deadline, Deadline, DeadlineExceeded};
+#[cfg(feature = "reqwest")]
+pub use request_id::propagate_request_id;
+pub use request_id::{current_request_id, scope_request_id, PropagateRequestId, REQUEST_ID_HEADER};
@@ -0,0 +1,148 @@
+use std::future::Future;
+use std::str::FromStr;
+
+use poem::http::HeaderValue;
+use poem::{Endpoint, IntoResponse, Middleware, Request, Response, Result};
+
+use crate::types::RequestId;
+pub use crate::types::REQUEST_ID_HEADER;
+
+tokio::task_local! {
+    static REQUEST_ID: RequestId;
+}
+
+/// The id of the current request, if one is set.
+pub fn current_request_id() -> Option<RequestId> {
+    REQUEST_ID.try_with(|v| *v).ok()
+}
+
+/// Runs the future with the given request id, this is used to carry the
+/// id over to spawned tasks.
+pub async fn scope_request_id<F: Future>(id: RequestId, fut: F) -> F::Output {
+    REQUEST_ID.scope(id, fut).await
+}
+
+/// Adds the current request id to an outbound request to one of our services.
+///
+/// This must not be used for third party APIs.
+#[cfg(feature = "reqwest")]
+pub fn propagate_request_id(builder: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+    match current_request_id() {
+        Some(id) => builder.header(REQUEST_ID_HEADER, id.to_string()),
+        None => builder,
+    }
+}
+
+/// Assigns every request a [RequestId] from the `X-Request-Id` header,
+/// generating a new one when the header is missing or invalid.
+///
+/// The id is added to the request data, is available through
+/// [current_request_id] for the rest of the request and is echoed back in
+/// every response, including errors.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct PropagateRequestId;
+
+impl<E: Endpoint> Middleware<E> for PropagateRequestId {
+    type Output = PropagateRequestIdEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        PropagateRequestIdEndpoint { inner: ep }
+    }
+}
+
+pub struct PropagateRequestIdEndpoint<E> {
+    inner: E,
+}
+
+#[poem::async_trait]
+impl<E: Endpoint> Endpoint for PropagateRequestIdEndpoint<E> {
+    type Output = Response;
+
+    async fn call(&self, mut req: Request) -> Result<Self::Output> {
+        let id = req
+            .header(REQUEST_ID_HEADER)
+            .and_then(|v| RequestId::from_str(v).ok())
+            .unwrap_or_default();
+        req.set_data(id);
+
+        // Errors are converted here so failed requests carry the id as well.
+        let mut resp = match scope_request_id(id, self.inner.call(req)).await {
+            Ok(resp) => resp.into_response(),
+            Err(e) => e.into_response(),
+        };
+
+        if let Ok(value) = HeaderValue::from_str(&id.to_string()) {
+            resp.headers_mut().insert(REQUEST_ID_HEADER, value);
+        }
+
+        Ok(resp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use poem::http::{StatusCode, Uri};
+    use poem::{handler, EndpointExt, Route};
+
+    #[handler]
+    fn echo(req: &Request) -> String {
+        let from_data = req.data::<RequestId>().copied();
+        assert_eq!(from_data, current_request_id());
+        from_data.unwrap().to_string()
+    }
+
+    fn call(header: Option<&str>) -> (String, String) {
+        let app = Route::new().at("/", echo).with(PropagateRequestId);
+
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        rt.block_on(async {
+            let mut req = Request::builder().uri(Uri::from_static("/"));
+            if let Some(header) = header {
+                req = req.header(REQUEST_ID_HEADER, header);
+            }
+
+            let resp = app.get_response(req.finish()).await;
+            let header = resp.headers()[REQUEST_ID_HEADER]
+                .to_str()
+                .unwrap()
+                .to_string();
+            (header, resp.into_body().into_string().await.unwrap())
+        })
+    }
+
+    #[test]
+    fn test_extracts_or_generates() {
+        let id = RequestId::new().to_string();
+        assert_eq!(call(Some(&id)), (id.clone(), id));
+
+        let (header, body) = call(Some("not-an-id"));
+        assert_eq!(header, body);
+        assert!(RequestId::from_str(&header).is_ok());
+
+        let (header, _) = call(None);
+        assert!(RequestId::from_str(&header).is_ok());
+    }
+
+    #[test]
+    fn test_sets_header_on_errors() {
+        let app = Route::new().at("/", echo).with(PropagateRequestId);
+        let id = RequestId::new().to_string();
+
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let resp = rt.block_on(async {
+            let req = Request::builder()
+                .uri(Uri::from_static("/missing"))
+                .header(REQUEST_ID_HEADER, &id)
+                .finish();
+            app.call(req).await.unwrap()
+        });
+
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+        assert_eq!(resp.headers()[REQUEST_ID_HEADER], id.as_str());
+    }
+}
@@ -0,0 +1,8 @@
+pub mod support;
+pub mod theme;
+
+pub use support::{
+    Attachment, InvalidTransition, Ticket, TicketCategory, TicketMessage, TicketStatus,
+    TicketSubject,
+};
+pub use theme::{BannerMode, HexColor, ListingTheme, SanitisedCss, ThemeError, CUSTOM_CSS_TIER};
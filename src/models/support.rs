@@ -0,0 +1,255 @@
+use std::fmt::{Display, Formatter};
+
+use poem_openapi::{Enum, Object};
+use uuid::Uuid;
+
+use crate::types::{JsSafeBigInt, NormalisingString, Timestamp};
+
+/// The object store prefix all support attachments are uploaded under.
+pub const ATTACHMENT_PREFIX: &str = "support/attachments";
+
+pub type TicketSubject = NormalisingString<5, 100, true>;
+
+#[derive(
+    Enum,
+    Copy,
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    strum::AsRefStr,
+    strum::EnumString,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[oai(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum TicketCategory {
+    Account,
+    Billing,
+    Listing,
+    Report,
+    Other,
+}
+
+cql_text_enum!(TicketCategory);
+
+#[derive(
+    Enum,
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    Hash,
+    strum::AsRefStr,
+    strum::EnumString,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[oai(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum TicketStatus {
+    #[default]
+    Open,
+    AwaitingStaff,
+    AwaitingUser,
+    Resolved,
+    Closed,
+}
+
+impl TicketStatus {
+    /// Closed tickets are final, resolved tickets can still be reopened.
+    pub fn can_transition_to(&self, next: TicketStatus) -> bool {
+        use TicketStatus::*;
+
+        match (self, next) {
+            (Closed, _) => false,
+            (current, next) if *current == next => false,
+            (Resolved, Open | Closed) => true,
+            (Resolved, _) => false,
+            (_, Open) => false,
+            _ => true,
+        }
+    }
+
+    #[inline]
+    pub fn is_active(&self) -> bool {
+        !matches!(self, Self::Resolved | Self::Closed)
+    }
+}
+
+cql_text_enum!(TicketStatus);
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct InvalidTransition {
+    pub from: TicketStatus,
+    pub to: TicketStatus,
+}
+
+impl Display for InvalidTransition {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "ticket cannot move from {} to {}",
+            self.from.as_ref(),
+            self.to.as_ref()
+        )
+    }
+}
+
+impl std::error::Error for InvalidTransition {}
+
+/// A file attached to a ticket message, the contents live in the object store.
+#[derive(Object, Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Attachment {
+    /// The object store key of the file.
+    pub key: String,
+    pub filename: String,
+    pub content_type: String,
+    pub size: u64,
+}
+
+impl Attachment {
+    /// The object store key an attachment of a ticket is uploaded to.
+    pub fn key_for(ticket_id: Uuid, attachment_id: Uuid) -> String {
+        format!("{}/{}/{}", ATTACHMENT_PREFIX, ticket_id, attachment_id)
+    }
+}
+
+#[derive(Object, Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct TicketMessage {
+    pub id: Uuid,
+    pub author: JsSafeBigInt,
+    /// If the message was sent by a member of staff.
+    pub is_staff: bool,
+    pub content: String,
+    pub attachments: Vec<Attachment>,
+    pub created_at: Timestamp,
+}
+
+#[derive(Object, Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Ticket {
+    pub id: Uuid,
+    pub author: JsSafeBigInt,
+    pub category: TicketCategory,
+    pub subject: TicketSubject,
+    /// The messages of the ticket, oldest first.
+    pub messages: Vec<TicketMessage>,
+    pub status: TicketStatus,
+    pub created_at: Timestamp,
+    pub updated_at: Timestamp,
+}
+
+impl Ticket {
+    pub fn new(author: JsSafeBigInt, category: TicketCategory, subject: TicketSubject) -> Self {
+        let now = Timestamp::default();
+
+        Self {
+            id: Uuid::new_v4(),
+            author,
+            category,
+            subject,
+            messages: vec![],
+            status: TicketStatus::Open,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    pub fn transition(&mut self, next: TicketStatus) -> Result<(), InvalidTransition> {
+        if !self.status.can_transition_to(next) {
+            return Err(InvalidTransition {
+                from: self.status,
+                to: next,
+            });
+        }
+
+        self.status = next;
+        self.updated_at = Timestamp::default();
+        Ok(())
+    }
+
+    /// Adds a message to the ticket, moving it to wait on the other party.
+    ///
+    /// Replying to a resolved ticket reopens it.
+    pub fn reply(&mut self, message: TicketMessage) -> Result<(), InvalidTransition> {
+        let next = if message.is_staff {
+            TicketStatus::AwaitingUser
+        } else {
+            TicketStatus::AwaitingStaff
+        };
+
+        if self.status == TicketStatus::Resolved {
+            self.transition(TicketStatus::Open)?;
+        }
+
+        if self.status != next {
+            self.transition(next)?;
+        }
+
+        self.updated_at = message.created_at;
+        self.messages.push(message);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(is_staff: bool) -> TicketMessage {
+        TicketMessage {
+            id: Uuid::new_v4(),
+            author: JsSafeBigInt(1),
+            is_staff,
+            content: "hello".to_string(),
+            attachments: vec![],
+            created_at: Timestamp::default(),
+        }
+    }
+
+    fn ticket() -> Ticket {
+        Ticket::new(
+            JsSafeBigInt(1),
+            TicketCategory::Listing,
+            TicketSubject::from("My bot is missing"),
+        )
+    }
+
+    #[test]
+    fn test_replies_move_status() {
+        let mut ticket = ticket();
+
+        ticket.reply(message(false)).unwrap();
+        assert_eq!(ticket.status, TicketStatus::AwaitingStaff);
+
+        ticket.reply(message(true)).unwrap();
+        assert_eq!(ticket.status, TicketStatus::AwaitingUser);
+
+        ticket.transition(TicketStatus::Resolved).unwrap();
+        ticket.reply(message(false)).unwrap();
+        assert_eq!(ticket.status, TicketStatus::AwaitingStaff);
+        assert_eq!(ticket.messages.len(), 3);
+    }
+
+    #[test]
+    fn test_closed_is_final() {
+        let mut ticket = ticket();
+        ticket.transition(TicketStatus::Closed).unwrap();
+
+        assert_eq!(
+            ticket.reply(message(false)),
+            Err(InvalidTransition {
+                from: TicketStatus::Closed,
+                to: TicketStatus::AwaitingStaff,
+            })
+        );
+        assert!(ticket.messages.is_empty());
+        assert!(ticket.transition(TicketStatus::Open).is_err());
+    }
+}
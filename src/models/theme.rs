@@ -0,0 +1,467 @@
+use std::borrow::Cow;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use poem_openapi::registry::{MetaSchema, MetaSchemaRef};
+use poem_openapi::types::{ParseError, ParseFromJSON, ParseResult, ToJSON, Type};
+use poem_openapi::{Enum, Object};
+use serde::de::Error;
+use serde::{Deserializer, Serializer};
+use serde_json::Value;
+
+use crate::premium::{ResolvedTier, Tier};
+
+/// The lowest tier which may use custom CSS.
+pub const CUSTOM_CSS_TIER: Tier = Tier::Pro;
+
+/// The longest custom CSS accepted, after sanitising.
+pub const MAX_CSS_LENGTH: usize = 4096;
+
+/// The properties listings may set in their custom CSS.
+pub const ALLOWED_CSS_PROPERTIES: &[&str] = &[
+    "background-color",
+    "border-color",
+    "border-radius",
+    "border-style",
+    "border-width",
+    "box-shadow",
+    "color",
+    "font-family",
+    "font-style",
+    "font-weight",
+    "letter-spacing",
+    "line-height",
+    "opacity",
+    "text-align",
+    "text-decoration",
+    "text-shadow",
+    "text-transform",
+];
+
+/// Value fragments which could load external resources or run scripts.
+const BLOCKED_CSS_VALUES: &[&str] = &[
+    "url(",
+    "image(",
+    "image-set(",
+    "expression(",
+    "javascript:",
+    "@import",
+    "\\",
+    "<",
+    "{",
+    "}",
+];
+
+/// A `#rrggbb` colour.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Default)]
+pub struct HexColor(pub u32);
+
+impl FromStr for HexColor {
+    type Err = ParseError<Self>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let hex = s
+            .strip_prefix('#')
+            .filter(|v| v.len() == 6 && v.bytes().all(|b| b.is_ascii_hexdigit()))
+            .ok_or_else(|| ParseError::custom(format!("Invalid hex colour {:?}", s)))?;
+
+        u32::from_str_radix(hex, 16)
+            .map(Self)
+            .map_err(|_| ParseError::custom(format!("Invalid hex colour {:?}", s)))
+    }
+}
+
+impl Display for HexColor {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "#{:06x}", self.0)
+    }
+}
+
+impl serde::Serialize for HexColor {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.to_string().serialize(serializer)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for HexColor {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let raw = String::deserialize(deserializer)?;
+        Self::from_str(&raw).map_err(|e| D::Error::custom(e.into_message()))
+    }
+}
+
+impl Type for HexColor {
+    const IS_REQUIRED: bool = true;
+    type RawValueType = Self;
+    type RawElementValueType = Self;
+
+    fn name() -> Cow<'static, str> {
+        Cow::from("HexColor")
+    }
+
+    fn schema_ref() -> MetaSchemaRef {
+        MetaSchemaRef::Inline(Box::new(MetaSchema {
+            pattern: Some("^#[0-9a-fA-F]{6}$".to_string()),
+            ..MetaSchema::new("string")
+        }))
+    }
+
+    fn as_raw_value(&self) -> Option<&Self::RawValueType> {
+        Some(self)
+    }
+
+    fn raw_element_iter<'a>(
+        &'a self,
+    ) -> Box<dyn Iterator<Item = &'a Self::RawElementValueType> + 'a> {
+        Box::new(std::iter::once(self))
+    }
+}
+
+impl ToJSON for HexColor {
+    fn to_json(&self) -> Option<Value> {
+        Some(Value::String(self.to_string()))
+    }
+}
+
+impl ParseFromJSON for HexColor {
+    fn parse_from_json(value: Option<Value>) -> ParseResult<Self> {
+        match value {
+            Some(Value::String(v)) => Self::from_str(&v),
+            other => Err(ParseError::custom(format!(
+                "Expected hex colour got {:?}",
+                other
+            ))),
+        }
+    }
+}
+
+/// Custom CSS reduced to a whitelisted set of declarations.
+///
+/// The CSS is a plain declaration list, e.g. `color: #fff; border-radius: 4px`,
+/// selectors and at-rules are not supported. Declarations using a property
+/// outside of [ALLOWED_CSS_PROPERTIES] or a value which could load external
+/// resources are dropped rather than rejected, while CSS which can't be
+/// split into declarations safely, e.g. with comments or unterminated
+/// strings, is rejected.
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Default)]
+pub struct SanitisedCss(String);
+
+impl SanitisedCss {
+    pub fn sanitise(raw: &str) -> Result<Self, ThemeError> {
+        let mut declarations = vec![];
+        for declaration in split_declarations(raw)? {
+            let (property, value) = match declaration.split_once(':') {
+                Some((property, value)) => (property.trim().to_lowercase(), value.trim()),
+                None => continue,
+            };
+
+            let lowered = value.to_lowercase();
+            if value.is_empty()
+                || !ALLOWED_CSS_PROPERTIES.contains(&property.as_str())
+                || BLOCKED_CSS_VALUES.iter().any(|v| lowered.contains(v))
+            {
+                continue;
+            }
+
+            declarations.push(format!("{}: {};", property, value));
+        }
+
+        let css = declarations.join(" ");
+        if css.len() > MAX_CSS_LENGTH {
+            return Err(ThemeError::CssTooLong);
+        }
+
+        Ok(Self(css))
+    }
+
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// Splits a declaration list on the `;` which are outside of strings and
+/// parentheses, so a `;` in a string can't smuggle in another declaration.
+fn split_declarations(raw: &str) -> Result<Vec<&str>, ThemeError> {
+    let mut declarations = vec![];
+    let mut start = 0;
+    let mut quote = None;
+    let mut depth = 0usize;
+    let mut chars = raw.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        match (quote, c) {
+            // Escapes are dropped by the value check, they are only skipped
+            // here so an escaped quote does not end the string.
+            (_, '\\') => {
+                chars.next();
+            }
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), '\n' | '\r') => return Err(ThemeError::MalformedCss),
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '/') if matches!(chars.peek(), Some((_, '*'))) => {
+                return Err(ThemeError::MalformedCss)
+            }
+            (None, '(') => depth += 1,
+            (None, ')') => depth = depth.checked_sub(1).ok_or(ThemeError::MalformedCss)?,
+            (None, ';') if depth == 0 => {
+                declarations.push(&raw[start..i]);
+                start = i + 1;
+            }
+            (None, _) => {}
+        }
+    }
+
+    if quote.is_some() || depth != 0 {
+        return Err(ThemeError::MalformedCss);
+    }
+
+    declarations.push(&raw[start..]);
+    Ok(declarations)
+}
+
+impl Display for SanitisedCss {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl serde::Serialize for SanitisedCss {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for SanitisedCss {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let raw = String::deserialize(deserializer)?;
+        Self::sanitise(&raw).map_err(D::Error::custom)
+    }
+}
+
+impl Type for SanitisedCss {
+    const IS_REQUIRED: bool = true;
+    type RawValueType = Self;
+    type RawElementValueType = Self;
+
+    fn name() -> Cow<'static, str> {
+        Cow::from("SanitisedCss")
+    }
+
+    fn schema_ref() -> MetaSchemaRef {
+        MetaSchemaRef::Inline(Box::new(MetaSchema {
+            max_length: Some(MAX_CSS_LENGTH),
+            ..MetaSchema::new("string")
+        }))
+    }
+
+    fn as_raw_value(&self) -> Option<&Self::RawValueType> {
+        Some(self)
+    }
+
+    fn raw_element_iter<'a>(
+        &'a self,
+    ) -> Box<dyn Iterator<Item = &'a Self::RawElementValueType> + 'a> {
+        Box::new(std::iter::once(self))
+    }
+}
+
+impl ToJSON for SanitisedCss {
+    fn to_json(&self) -> Option<Value> {
+        Some(Value::String(self.0.clone()))
+    }
+}
+
+impl ParseFromJSON for SanitisedCss {
+    fn parse_from_json(value: Option<Value>) -> ParseResult<Self> {
+        let raw = match value {
+            Some(Value::String(v)) => v,
+            other => {
+                return Err(ParseError::custom(format!(
+                    "Expected CSS string got {:?}",
+                    other
+                )))
+            }
+        };
+
+        match Self::sanitise(&raw) {
+            Ok(css) => Ok(css),
+            Err(ThemeError::CssTooLong) => Err(ParseError::custom(format!(
+                "Custom CSS is above the maximum length of {} characters.",
+                MAX_CSS_LENGTH
+            ))),
+            Err(_) => Err(ParseError::custom(
+                "Custom CSS must be a list of declarations without comments.",
+            )),
+        }
+    }
+}
+
+#[derive(
+    Enum,
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    Hash,
+    strum::AsRefStr,
+    strum::EnumString,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[oai(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum BannerMode {
+    #[default]
+    Image,
+    Gradient,
+    Solid,
+}
+
+cql_text_enum!(BannerMode);
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ThemeError {
+    /// Custom CSS was set on a listing which is not entitled to it.
+    CustomCssNotEntitled,
+    /// The custom CSS contains a comment, an unterminated string or
+    /// unbalanced parentheses.
+    MalformedCss,
+    /// The custom CSS is above [MAX_CSS_LENGTH] after sanitising.
+    CssTooLong,
+}
+
+impl Display for ThemeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::CustomCssNotEntitled => write!(f, "custom CSS requires a premium plan"),
+            Self::MalformedCss => write!(f, "custom CSS is malformed"),
+            Self::CssTooLong => write!(
+                f,
+                "custom CSS is above the maximum length of {} characters",
+                MAX_CSS_LENGTH
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ThemeError {}
+
+#[derive(Object, Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ListingTheme {
+    pub accent: HexColor,
+    #[serde(default)]
+    pub banner_mode: BannerMode,
+    #[oai(skip_serializing_if_is_none)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub custom_css: Option<SanitisedCss>,
+}
+
+impl ListingTheme {
+    /// Checks the theme only uses features the listing's tier includes.
+    pub fn check_entitlements(&self, tier: &ResolvedTier) -> Result<(), ThemeError> {
+        let has_css = self.custom_css.as_ref().is_some_and(|v| !v.is_empty());
+        if has_css && tier.tier < CUSTOM_CSS_TIER {
+            return Err(ThemeError::CustomCssNotEntitled);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_hex_color() {
+        assert_eq!(HexColor::from_str("#FF8800").unwrap(), HexColor(0xff8800));
+        assert_eq!(HexColor(0x0a0b0c).to_string(), "#0a0b0c");
+        assert!(HexColor::from_str("FF8800").is_err());
+        assert!(HexColor::from_str("#FFF").is_err());
+        assert!(HexColor::from_str("#GGGGGG").is_err());
+        assert!(HexColor::from_str("#+12345").is_err());
+        assert!(HexColor::from_str("#-12345").is_err());
+    }
+
+    #[test]
+    fn test_css_sanitising() {
+        let css = SanitisedCss::sanitise(
+            "color: #fff; position: fixed; background-color: URL(https://evil.test/x.png);\
+             @import 'https://evil.test'; border-radius:4px; font-family: a\\62 c",
+        )
+        .unwrap();
+
+        assert_eq!(css.as_str(), "color: #fff; border-radius: 4px;");
+
+        let css = SanitisedCss::sanitise("font-family: 'a;b', serif; color: red").unwrap();
+        assert_eq!(css.as_str(), "font-family: 'a;b', serif; color: red;");
+    }
+
+    #[test]
+    fn test_malformed_css() {
+        for raw in [
+            "color: red /* ; */",
+            "font-family: 'unterminated; color: red",
+            "font-family: \"a\nb\"",
+            "color: rgb(1, 2, 3; opacity: 1",
+            "color: red)",
+        ] {
+            assert_eq!(
+                SanitisedCss::sanitise(raw),
+                Err(ThemeError::MalformedCss),
+                "{}",
+                raw
+            );
+        }
+
+        let long = "color: red; ".repeat(MAX_CSS_LENGTH / 10);
+        assert_eq!(SanitisedCss::sanitise(&long), Err(ThemeError::CssTooLong));
+        assert!(serde_json::from_value::<SanitisedCss>(json!(long)).is_err());
+        assert!(serde_json::from_value::<SanitisedCss>(json!("a: 'b")).is_err());
+    }
+
+    #[test]
+    fn test_custom_css_requires_entitlement() {
+        let theme = ListingTheme::parse_from_json(Some(json!({
+            "accent": "#5865f2",
+            "banner_mode": "gradient",
+            "custom_css": "color: red",
+        })))
+        .unwrap();
+
+        assert_eq!(theme.banner_mode, BannerMode::Gradient);
+        assert_eq!(
+            theme.check_entitlements(&ResolvedTier::default()),
+            Err(ThemeError::CustomCssNotEntitled)
+        );
+
+        let pro = ResolvedTier {
+            tier: Tier::Pro,
+            until: None,
+        };
+        assert_eq!(theme.check_entitlements(&pro), Ok(()));
+    }
+}
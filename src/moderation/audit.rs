@@ -0,0 +1,367 @@
+use std::borrow::Cow;
+use std::fmt::{Display, Formatter};
+use std::ops::Deref;
+
+#[cfg(feature = "bincode")]
+use bincode::{
+    de::Decoder,
+    enc::Encoder,
+    error::{DecodeError, EncodeError},
+    Decode, Encode,
+};
+
+use poem_openapi::registry::MetaSchemaRef;
+use poem_openapi::types::{ParseFromJSON, ParseResult, ToJSON, Type};
+use poem_openapi::{Enum, Object};
+use scylla::cql_to_rust::{FromCqlVal, FromCqlValError};
+use scylla::frame::response::result::CqlValue;
+use scylla::frame::value::ValueTooBig;
+use serde_json::{json, Map, Value};
+use uuid::Uuid;
+
+use crate::diff::{Differ, FieldChange};
+use crate::types::{JsSafeBigInt, Timestamp};
+use crate::util::canonical_json;
+
+#[cfg_attr(feature = "bincode", derive(Decode, Encode))]
+#[derive(
+    Enum,
+    Copy,
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    strum::AsRefStr,
+    strum::EnumString,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[oai(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    Create,
+    Update,
+    Delete,
+    Approve,
+    Deny,
+    Ban,
+    Unban,
+    Verify,
+    Unverify,
+}
+
+cql_text_enum!(AuditAction);
+
+#[cfg_attr(feature = "bincode", derive(Decode, Encode))]
+#[derive(
+    Enum,
+    Copy,
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    strum::AsRefStr,
+    strum::EnumString,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[oai(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum AuditSubject {
+    Bot,
+    Pack,
+    User,
+    Review,
+    Report,
+}
+
+cql_text_enum!(AuditSubject);
+
+/// The fields changed by an action as `{"path": {"old": .., "new": ..}}`.
+///
+/// This is stored as canonical JSON text so identical diffs are byte identical.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct AuditDiff(pub Value);
+
+impl AuditDiff {
+    /// Computes the fields which differ between the serialized values.
+    pub fn between<T: serde::Serialize + ?Sized>(old: &T, new: &T) -> serde_json::Result<Self> {
+        Differ::new().diff(old, new).map(Self::from)
+    }
+
+    /// The diff of a newly created value, every field is new.
+    pub fn created<T: serde::Serialize + ?Sized>(new: &T) -> serde_json::Result<Self> {
+        Self::between(&Value::Object(Map::new()), &serde_json::to_value(new)?)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        match &self.0 {
+            Value::Null => true,
+            Value::Object(v) => v.is_empty(),
+            _ => false,
+        }
+    }
+}
+
+impl From<Vec<FieldChange>> for AuditDiff {
+    fn from(changes: Vec<FieldChange>) -> Self {
+        let changes = changes
+            .into_iter()
+            .map(|v| (v.path, json!({"old": v.old, "new": v.new})))
+            .collect::<Map<String, Value>>();
+
+        Self(Value::Object(changes))
+    }
+}
+
+impl serde::Serialize for AuditDiff {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for AuditDiff {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        Value::deserialize(deserializer).map(Self)
+    }
+}
+
+#[cfg(feature = "bincode")]
+impl Encode for AuditDiff {
+    fn encode<E: Encoder>(&self, encoder: &mut E) -> Result<(), EncodeError> {
+        canonical_json(&self.0).encode(encoder)
+    }
+}
+
+#[cfg(feature = "bincode")]
+impl Decode for AuditDiff {
+    fn decode<D: Decoder>(decoder: &mut D) -> Result<Self, DecodeError> {
+        let inner = String::decode(decoder)?;
+        serde_json::from_str(&inner)
+            .map(Self)
+            .map_err(|e| DecodeError::OtherString(e.to_string()))
+    }
+}
+
+#[cfg(feature = "bincode")]
+bincode::impl_borrow_decode!(AuditDiff);
+
+impl Display for AuditDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", canonical_json(&self.0))
+    }
+}
+
+impl Deref for AuditDiff {
+    type Target = Value;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Type for AuditDiff {
+    const IS_REQUIRED: bool = <Value as Type>::IS_REQUIRED;
+    type RawValueType = <Value as Type>::RawValueType;
+    type RawElementValueType = <Value as Type>::RawElementValueType;
+
+    fn name() -> Cow<'static, str> {
+        Cow::from("AuditDiff")
+    }
+
+    fn schema_ref() -> MetaSchemaRef {
+        Value::schema_ref()
+    }
+
+    fn as_raw_value(&self) -> Option<&Self::RawValueType> {
+        Some(&self.0)
+    }
+
+    fn raw_element_iter<'a>(
+        &'a self,
+    ) -> Box<dyn Iterator<Item = &'a Self::RawElementValueType> + 'a> {
+        self.0.raw_element_iter()
+    }
+}
+
+impl ToJSON for AuditDiff {
+    fn to_json(&self) -> Option<Value> {
+        Some(self.0.clone())
+    }
+}
+
+impl ParseFromJSON for AuditDiff {
+    fn parse_from_json(value: Option<Value>) -> ParseResult<Self> {
+        Ok(Self(value.unwrap_or_default()))
+    }
+}
+
+impl FromCqlVal<CqlValue> for AuditDiff {
+    fn from_cql(cql_val: CqlValue) -> Result<Self, FromCqlValError> {
+        cql_val
+            .as_text()
+            .and_then(|v| serde_json::from_str(v).ok())
+            .map(Self)
+            .ok_or(FromCqlValError::BadCqlType)
+    }
+}
+
+impl scylla::frame::value::Value for AuditDiff {
+    fn serialize(&self, buf: &mut Vec<u8>) -> Result<(), ValueTooBig> {
+        canonical_json(&self.0).serialize(buf)
+    }
+}
+
+/// A single action taken by a member of staff or a user on something they manage.
+///
+/// The field order matches the column order of the audit log table.
+#[cfg_attr(feature = "bincode", derive(Decode, Encode))]
+#[derive(
+    Object,
+    Clone,
+    Debug,
+    PartialEq,
+    scylla::FromRow,
+    scylla::ValueList,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+pub struct AuditEntry {
+    #[cfg_attr(feature = "bincode", bincode(with_serde))]
+    pub id: Uuid,
+    pub actor_id: JsSafeBigInt,
+    pub subject: AuditSubject,
+    pub subject_id: JsSafeBigInt,
+    pub action: AuditAction,
+    pub diff: AuditDiff,
+    pub reason: Option<String>,
+    pub created_at: Timestamp,
+}
+
+impl AuditEntry {
+    pub fn builder(
+        actor_id: JsSafeBigInt,
+        action: AuditAction,
+        subject: AuditSubject,
+        subject_id: JsSafeBigInt,
+    ) -> AuditEntryBuilder {
+        AuditEntryBuilder {
+            entry: Self {
+                id: Uuid::new_v4(),
+                actor_id,
+                subject,
+                subject_id,
+                action,
+                diff: AuditDiff::default(),
+                reason: None,
+                created_at: Timestamp::default(),
+            },
+        }
+    }
+}
+
+pub struct AuditEntryBuilder {
+    entry: AuditEntry,
+}
+
+impl AuditEntryBuilder {
+    /// Records the fields which changed between the old and new versions.
+    pub fn diff<T: serde::Serialize + ?Sized>(
+        mut self,
+        old: &T,
+        new: &T,
+    ) -> serde_json::Result<Self> {
+        self.entry.diff = AuditDiff::between(old, new)?;
+        Ok(self)
+    }
+
+    /// Records changes computed with a [Differ], e.g. one which redacts secrets.
+    pub fn changes(mut self, changes: Vec<FieldChange>) -> Self {
+        self.entry.diff = AuditDiff::from(changes);
+        self
+    }
+
+    pub fn reason(mut self, reason: impl Into<String>) -> Self {
+        self.entry.reason = Some(reason.into());
+        self
+    }
+
+    pub fn created_at(mut self, created_at: Timestamp) -> Self {
+        self.entry.created_at = created_at;
+        self
+    }
+
+    pub fn build(self) -> AuditEntry {
+        self.entry
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(serde::Serialize)]
+    struct Listing {
+        name: &'static str,
+        prefix: Option<&'static str>,
+        tags: Vec<&'static str>,
+    }
+
+    #[test]
+    fn test_diff_between_versions() {
+        let old = Listing {
+            name: "Cool Bot",
+            prefix: Some("!"),
+            tags: vec!["music"],
+        };
+        let new = Listing {
+            name: "Cool Bot",
+            prefix: None,
+            tags: vec!["music", "fun"],
+        };
+
+        let entry = AuditEntry::builder(
+            JsSafeBigInt(1),
+            AuditAction::Update,
+            AuditSubject::Bot,
+            JsSafeBigInt(2),
+        )
+        .diff(&old, &new)
+        .unwrap()
+        .reason("Owner request")
+        .build();
+
+        assert_eq!(
+            entry.diff.0,
+            json!({
+                "prefix": {"old": "!", "new": null},
+                "tags": {"old": ["music"], "new": ["music", "fun"]},
+            })
+        );
+        assert_eq!(
+            entry.diff.to_string(),
+            r#"{"prefix":{"new":null,"old":"!"},"tags":{"new":["music","fun"],"old":["music"]}}"#
+        );
+        assert!(AuditDiff::between(&old, &old).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_created_diff() {
+        let diff = AuditDiff::created(&json!({"name": "Pack"})).unwrap();
+        assert_eq!(diff.0, json!({"name": {"old": null, "new": "Pack"}}));
+
+        let diff =
+            AuditDiff::between(&json!({"links": {"github": "a"}}), &json!({"links": {}})).unwrap();
+        assert_eq!(diff.0, json!({"links.github": {"old": "a", "new": null}}));
+    }
+}
@@ -0,0 +1,448 @@
+use std::borrow::Cow;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use poem_openapi::registry::{MetaSchemaRef, Registry};
+use poem_openapi::types::{ParseError, ParseFromJSON, ParseResult, ToJSON, Type};
+use poem_openapi::{Enum, Object};
+use scylla::cql_to_rust::{FromCqlVal, FromCqlValError};
+use scylla::frame::response::result::CqlValue;
+use scylla::frame::value::ValueTooBig;
+use serde_json::Value;
+
+use crate::auth::Role;
+use crate::db::{execute_lwt, DbError, LwtResult, Session};
+use crate::errors::ApiError;
+use crate::types::JsSafeBigInt;
+
+/// The review status of a listing without the details of the state.
+#[derive(
+    Enum,
+    Copy,
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    strum::AsRefStr,
+    strum::EnumString,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[oai(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ListingStatus {
+    Draft,
+    Pending,
+    Approved,
+    Denied,
+    Delisted,
+}
+
+cql_text_enum!(ListingStatus);
+
+/// The kind of listing, deciding which table its state is stored in.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ListingKind {
+    Bot,
+    Pack,
+}
+
+impl ListingKind {
+    fn update_state_query(self) -> &'static str {
+        match self {
+            Self::Bot => UPDATE_BOT_STATE_QUERY,
+            Self::Pack => UPDATE_PACK_STATE_QUERY,
+        }
+    }
+}
+
+const UPDATE_BOT_STATE_QUERY: &str = "UPDATE bots SET state = ? WHERE id = ? IF state = ?;";
+const UPDATE_PACK_STATE_QUERY: &str = "UPDATE packs SET state = ? WHERE id = ? IF state = ?;";
+
+/// Where a bot or pack is in the review workflow.
+///
+/// States must only be changed through [ListingState::try_transition] so a
+/// listing can never skip review. Stored as text, the status name optionally
+/// followed by `:<reason>` for denied listings.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum ListingState {
+    Draft,
+    Pending,
+    Approved,
+    Denied { reason: String },
+    Delisted,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransitionError {
+    /// The workflow does not allow moving between the states at all.
+    Illegal {
+        from: ListingStatus,
+        to: ListingStatus,
+    },
+    /// The transition is allowed but needs a higher role.
+    Forbidden { required: Role },
+    /// Listings can only be denied with a reason.
+    MissingReason,
+}
+
+impl Display for TransitionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Illegal { from, to } => write!(
+                f,
+                "cannot move a listing from {} to {}",
+                from.as_ref(),
+                to.as_ref()
+            ),
+            Self::Forbidden { required } => {
+                write!(f, "transition requires the {} role", required.as_ref())
+            }
+            Self::MissingReason => write!(f, "denied listings require a reason"),
+        }
+    }
+}
+
+impl std::error::Error for TransitionError {}
+
+impl From<TransitionError> for ApiError {
+    fn from(e: TransitionError) -> Self {
+        match e {
+            TransitionError::Forbidden { .. } => {
+                Self::forbidden("You do not have permission to change this listing's state.")
+            }
+            TransitionError::Illegal { from, to } => Self::validation(format!(
+                "A listing cannot be moved from {} to {}.",
+                from.as_ref(),
+                to.as_ref()
+            )),
+            TransitionError::MissingReason => {
+                Self::validation("A reason must be given when denying a listing.")
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum StoreTransitionError {
+    Transition(TransitionError),
+    /// The stored state was changed since it was read, contains the state
+    /// it was changed to if scylla returned it.
+    Conflict(Option<ListingState>),
+    Db(DbError),
+}
+
+impl Display for StoreTransitionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Transition(e) => write!(f, "{}", e),
+            Self::Conflict(_) => write!(f, "listing state was changed concurrently"),
+            Self::Db(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for StoreTransitionError {}
+
+impl From<TransitionError> for StoreTransitionError {
+    fn from(e: TransitionError) -> Self {
+        Self::Transition(e)
+    }
+}
+
+impl From<DbError> for StoreTransitionError {
+    fn from(e: DbError) -> Self {
+        Self::Db(e)
+    }
+}
+
+impl From<StoreTransitionError> for ApiError {
+    fn from(e: StoreTransitionError) -> Self {
+        match e {
+            StoreTransitionError::Transition(e) => e.into(),
+            StoreTransitionError::Conflict(_) => Self::validation(
+                "The listing's state was changed by someone else, reload it and try again.",
+            ),
+            StoreTransitionError::Db(e) => e.into(),
+        }
+    }
+}
+
+impl ListingState {
+    pub fn status(&self) -> ListingStatus {
+        match self {
+            Self::Draft => ListingStatus::Draft,
+            Self::Pending => ListingStatus::Pending,
+            Self::Approved => ListingStatus::Approved,
+            Self::Denied { .. } => ListingStatus::Denied,
+            Self::Delisted => ListingStatus::Delisted,
+        }
+    }
+
+    /// Whether the listing is shown publicly.
+    #[inline]
+    pub fn is_listed(&self) -> bool {
+        matches!(self, Self::Approved)
+    }
+
+    /// The least role allowed to move a listing between the states, `None`
+    /// if the transition is never allowed.
+    ///
+    /// Transitions requiring [Role::User] are made by the listing's owners,
+    /// checking ownership is left to the caller.
+    pub fn required_role(from: ListingStatus, to: ListingStatus) -> Option<Role> {
+        use ListingStatus::*;
+
+        match (from, to) {
+            (Draft, Pending) | (Pending, Draft) => Some(Role::User),
+            (Denied, Pending) | (Denied, Draft) => Some(Role::User),
+            (Approved, Pending) => Some(Role::User),
+            (Pending, Approved) | (Pending, Denied) => Some(Role::BotReviewer),
+            (Approved, Delisted) | (Delisted, Pending) => Some(Role::Moderator),
+            _ => None,
+        }
+    }
+
+    /// Checks the transition is legal for the actor, returning the new state.
+    pub fn try_transition(
+        from: &ListingState,
+        to: ListingState,
+        actor_role: Role,
+    ) -> Result<ListingState, TransitionError> {
+        let required =
+            Self::required_role(from.status(), to.status()).ok_or(TransitionError::Illegal {
+                from: from.status(),
+                to: to.status(),
+            })?;
+
+        if !actor_role.satisfies(required) {
+            return Err(TransitionError::Forbidden { required });
+        }
+
+        if matches!(&to, Self::Denied { reason } if reason.trim().is_empty()) {
+            return Err(TransitionError::MissingReason);
+        }
+
+        Ok(to)
+    }
+
+    /// Checks the transition and writes it, conditioned on the stored state
+    /// still being `from` so concurrent writes can't skip the check.
+    pub async fn store_transition(
+        session: &Session,
+        kind: ListingKind,
+        id: JsSafeBigInt,
+        from: &ListingState,
+        to: ListingState,
+        actor_role: Role,
+    ) -> Result<ListingState, StoreTransitionError> {
+        let to = Self::try_transition(from, to, actor_role)?;
+
+        let res =
+            execute_lwt::<(ListingState,)>(session, kind.update_state_query(), (&to, id, from))
+                .await?;
+
+        match res {
+            LwtResult::Applied => Ok(to),
+            LwtResult::Rejected(existing) => Err(StoreTransitionError::Conflict(
+                existing.map(|(state,)| state),
+            )),
+        }
+    }
+}
+
+impl Display for ListingState {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Denied { reason } => write!(f, "{}:{}", self.status().as_ref(), reason),
+            other => write!(f, "{}", other.status().as_ref()),
+        }
+    }
+}
+
+impl FromStr for ListingState {
+    type Err = strum::ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (status, reason) = match s.split_once(':') {
+            Some((status, reason)) => (status, Some(reason)),
+            None => (s, None),
+        };
+
+        Ok(match (ListingStatus::from_str(status)?, reason) {
+            (ListingStatus::Denied, reason) => Self::Denied {
+                reason: reason.unwrap_or_default().to_string(),
+            },
+            // Only denied listings are stored with a reason.
+            (_, Some(_)) => return Err(strum::ParseError::VariantNotFound),
+            (ListingStatus::Draft, None) => Self::Draft,
+            (ListingStatus::Pending, None) => Self::Pending,
+            (ListingStatus::Approved, None) => Self::Approved,
+            (ListingStatus::Delisted, None) => Self::Delisted,
+        })
+    }
+}
+
+/// The JSON form of [ListingState], used for its schema.
+#[derive(Object)]
+#[oai(rename = "ListingState")]
+struct ListingStateBody {
+    status: ListingStatus,
+    /// Why the listing was denied, only present for denied listings.
+    #[oai(skip_serializing_if_is_none)]
+    reason: Option<String>,
+}
+
+impl Type for ListingState {
+    const IS_REQUIRED: bool = true;
+    type RawValueType = Self;
+    type RawElementValueType = Self;
+
+    fn name() -> Cow<'static, str> {
+        ListingStateBody::name()
+    }
+
+    fn schema_ref() -> MetaSchemaRef {
+        ListingStateBody::schema_ref()
+    }
+
+    fn register(registry: &mut Registry) {
+        ListingStateBody::register(registry);
+    }
+
+    fn as_raw_value(&self) -> Option<&Self::RawValueType> {
+        Some(self)
+    }
+
+    fn raw_element_iter<'a>(
+        &'a self,
+    ) -> Box<dyn Iterator<Item = &'a Self::RawElementValueType> + 'a> {
+        Box::new(std::iter::once(self))
+    }
+}
+
+impl ToJSON for ListingState {
+    fn to_json(&self) -> Option<Value> {
+        serde_json::to_value(self).ok()
+    }
+}
+
+impl ParseFromJSON for ListingState {
+    fn parse_from_json(value: Option<Value>) -> ParseResult<Self> {
+        let value = value.unwrap_or_default();
+        serde_json::from_value(value).map_err(|e| ParseError::custom(e.to_string()))
+    }
+}
+
+impl FromCqlVal<CqlValue> for ListingState {
+    fn from_cql(cql_val: CqlValue) -> Result<Self, FromCqlValError> {
+        let raw = String::from_cql(cql_val)?;
+        Self::from_str(&raw).map_err(|_| FromCqlValError::BadCqlType)
+    }
+}
+
+impl scylla::frame::value::Value for ListingState {
+    fn serialize(&self, buf: &mut Vec<u8>) -> Result<(), ValueTooBig> {
+        self.to_string().serialize(buf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn denied() -> ListingState {
+        ListingState::Denied {
+            reason: "Bot is offline".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_transitions() {
+        let pending = ListingState::Pending;
+
+        assert_eq!(
+            ListingState::try_transition(&ListingState::Draft, pending.clone(), Role::User),
+            Ok(ListingState::Pending)
+        );
+        assert_eq!(
+            ListingState::try_transition(&pending, ListingState::Approved, Role::User),
+            Err(TransitionError::Forbidden {
+                required: Role::BotReviewer
+            })
+        );
+        assert_eq!(
+            ListingState::try_transition(&pending, denied(), Role::Admin),
+            Ok(denied())
+        );
+        assert_eq!(
+            ListingState::try_transition(
+                &pending,
+                ListingState::Denied {
+                    reason: " ".to_string()
+                },
+                Role::Admin
+            ),
+            Err(TransitionError::MissingReason)
+        );
+    }
+
+    #[test]
+    fn test_denied_requires_re_review() {
+        assert_eq!(
+            ListingState::try_transition(&denied(), ListingState::Approved, Role::Admin),
+            Err(TransitionError::Illegal {
+                from: ListingStatus::Denied,
+                to: ListingStatus::Approved
+            })
+        );
+        assert!(ListingState::try_transition(&denied(), ListingState::Pending, Role::User).is_ok());
+        assert!(ListingState::try_transition(
+            &ListingState::Approved,
+            ListingState::Approved,
+            Role::Admin
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_round_trips() {
+        assert_eq!(denied().to_string(), "denied:Bot is offline");
+        assert_eq!(
+            ListingState::from_str("denied:Bot is offline"),
+            Ok(denied())
+        );
+        assert_eq!(
+            ListingState::from_str("delisted"),
+            Ok(ListingState::Delisted)
+        );
+        assert!(ListingState::from_str("unknown").is_err());
+        assert!(ListingState::from_str("approved:xyz").is_err());
+        assert!(ListingState::from_str("pending:").is_err());
+
+        let value = denied().to_json().unwrap();
+        assert_eq!(
+            value,
+            json!({"status": "denied", "reason": "Bot is offline"})
+        );
+        assert_eq!(
+            ListingState::parse_from_json(Some(value)).unwrap(),
+            denied()
+        );
+        assert_eq!(
+            ListingState::parse_from_json(Some(json!({"status": "approved"}))).unwrap(),
+            ListingState::Approved
+        );
+
+        let mut buf = vec![];
+        scylla::frame::value::Value::serialize(&denied(), &mut buf).unwrap();
+        let decoded = ListingState::from_cql(CqlValue::Text(
+            String::from_utf8(buf[4..].to_vec()).unwrap(),
+        ));
+        assert_eq!(decoded.unwrap(), denied());
+    }
+}
@@ -0,0 +1,16 @@
+pub mod audit;
+pub mod listing;
+pub mod nsfw;
+pub mod report;
+pub mod spam;
+
+pub use audit::{AuditAction, AuditDiff, AuditEntry, AuditEntryBuilder, AuditSubject};
+pub use listing::{
+    ListingKind, ListingState, ListingStatus, StoreTransitionError, TransitionError,
+};
+pub use nsfw::{
+    get_nsfw_rules, screen_nsfw, set_nsfw_rules, NsfwMatch, NsfwRuleKind, NsfwRules, NsfwScreening,
+    NSFW_FLAG_THRESHOLD,
+};
+pub use report::{ReportPayload, ReportReason, MAX_DETAIL_LENGTH, MIN_DETAIL_LENGTH};
+pub use spam::{check_spam, Fingerprint, MinHash, SimHash, SpamLimits, SpamSignal, SpamVerdict};
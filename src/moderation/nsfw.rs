@@ -0,0 +1,211 @@
+use std::collections::BTreeSet;
+
+use once_cell::sync::Lazy;
+use poem_openapi::{Enum, Object};
+use url::Url;
+
+use crate::live::LiveValue;
+use crate::moderation::spam::{parse_link, words};
+
+/// The score at which a listing is flagged for review.
+pub const NSFW_FLAG_THRESHOLD: f64 = 0.5;
+
+const KEYWORD_WEIGHT: f64 = 0.25;
+const TAG_WEIGHT: f64 = 0.5;
+const DOMAIN_WEIGHT: f64 = 1.0;
+
+static LOADED_NSFW_RULES: Lazy<LiveValue<NsfwRules>> = Lazy::new(LiveValue::default);
+
+pub fn get_nsfw_rules() -> &'static LiveValue<NsfwRules> {
+    &LOADED_NSFW_RULES
+}
+
+pub fn set_nsfw_rules(rules: NsfwRules) {
+    LOADED_NSFW_RULES.set(rules);
+}
+
+/// Screens a listing using the loaded rules.
+pub fn screen_nsfw<'a>(
+    description: &str,
+    urls: impl IntoIterator<Item = &'a Url>,
+    tags: &[String],
+) -> NsfwScreening {
+    get_nsfw_rules().load().screen(description, urls, tags)
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct NsfwRules {
+    /// Words or phrases suggesting adult content, matched as whole words
+    /// ignoring case and accents.
+    pub keywords: BTreeSet<String>,
+    /// Blocked domains, their subdomains are blocked too.
+    pub domains: BTreeSet<String>,
+    /// Tags which mark a listing as adult.
+    pub tags: BTreeSet<String>,
+}
+
+impl NsfwRules {
+    /// Checks the description, any links within it, the given urls and the
+    /// tags. Every rule is only counted once.
+    pub fn screen<'a>(
+        &self,
+        description: &str,
+        urls: impl IntoIterator<Item = &'a Url>,
+        tags: &[String],
+    ) -> NsfwScreening {
+        let mut screening = NsfwScreening::default();
+
+        let text = format!(" {} ", words(description).join(" "));
+        for keyword in &self.keywords {
+            let normalised = words(keyword).join(" ");
+            if !normalised.is_empty() && text.contains(&format!(" {} ", normalised)) {
+                screening.push(NsfwRuleKind::Keyword, keyword);
+            }
+        }
+
+        let linked = description.split_whitespace().filter_map(parse_link);
+        for url in urls.into_iter().cloned().chain(linked) {
+            if let Some(domain) = url.host_str().and_then(|v| self.blocked_domain(v)) {
+                screening.push(NsfwRuleKind::Domain, domain);
+            }
+        }
+
+        for tag in tags {
+            if let Some(rule) = self.tags.iter().find(|v| v.eq_ignore_ascii_case(tag)) {
+                screening.push(NsfwRuleKind::Tag, rule);
+            }
+        }
+
+        screening
+    }
+
+    fn blocked_domain(&self, host: &str) -> Option<&str> {
+        let host = host.trim_end_matches('.').to_ascii_lowercase();
+
+        self.domains
+            .iter()
+            .find(|domain| {
+                let domain = domain.to_ascii_lowercase();
+                host == domain || host.ends_with(&format!(".{}", domain))
+            })
+            .map(String::as_str)
+    }
+}
+
+#[derive(
+    Enum,
+    Copy,
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    strum::AsRefStr,
+    strum::EnumString,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[oai(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum NsfwRuleKind {
+    Keyword,
+    Domain,
+    Tag,
+}
+
+impl NsfwRuleKind {
+    fn weight(&self) -> f64 {
+        match self {
+            Self::Keyword => KEYWORD_WEIGHT,
+            Self::Domain => DOMAIN_WEIGHT,
+            Self::Tag => TAG_WEIGHT,
+        }
+    }
+}
+
+#[derive(Object, Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct NsfwMatch {
+    pub kind: NsfwRuleKind,
+    /// The rule as it is written in the rule set.
+    pub rule: String,
+}
+
+#[derive(Object, Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct NsfwScreening {
+    /// Between `0.0` and `1.0`, a blocked domain alone is enough to reach `1.0`.
+    pub score: f64,
+    pub matches: Vec<NsfwMatch>,
+}
+
+impl NsfwScreening {
+    #[inline]
+    pub fn should_flag(&self) -> bool {
+        self.score >= NSFW_FLAG_THRESHOLD
+    }
+
+    fn push(&mut self, kind: NsfwRuleKind, rule: &str) {
+        if self
+            .matches
+            .iter()
+            .any(|v| v.kind == kind && v.rule == rule)
+        {
+            return;
+        }
+
+        self.score = (self.score + kind.weight()).min(1.0);
+        self.matches.push(NsfwMatch {
+            kind,
+            rule: rule.to_string(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules() -> NsfwRules {
+        NsfwRules {
+            keywords: ["hentai", "adult content"].map(String::from).into(),
+            domains: ["example-nsfw.com"].map(String::from).into(),
+            tags: ["nsfw"].map(String::from).into(),
+        }
+    }
+
+    #[test]
+    fn test_screen_keywords_and_tags() {
+        let rules = rules();
+
+        let screening = rules.screen("Posts ADULT   content and Hentai!", [], &[]);
+        assert_eq!(screening.matches.len(), 2);
+        assert_eq!(screening.score, 0.5);
+        assert!(screening.should_flag());
+
+        let screening = rules.screen("Unadulterated hentaigana lessons", [], &[]);
+        assert!(screening.matches.is_empty());
+
+        let screening = rules.screen("", [], &["NSFW".to_string()]);
+        assert_eq!(
+            screening.matches,
+            [NsfwMatch {
+                kind: NsfwRuleKind::Tag,
+                rule: "nsfw".to_string()
+            }]
+        );
+    }
+
+    #[test]
+    fn test_screen_domains() {
+        let rules = rules();
+        let url = Url::parse("https://cdn.example-nsfw.com/a.png").unwrap();
+
+        let screening = rules.screen("see www.example-nsfw.com", [&url], &[]);
+        assert_eq!(screening.matches.len(), 1);
+        assert_eq!(screening.score, 1.0);
+
+        let url = Url::parse("https://notexample-nsfw.com").unwrap();
+        assert!(!rules.screen("", [&url], &[]).should_flag());
+    }
+}
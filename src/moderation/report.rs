@@ -0,0 +1,129 @@
+use poem_openapi::{Enum, Object};
+
+use crate::errors::ApiResult;
+use crate::types::JsSafeBigInt;
+use crate::validation::{Validate, ValidationErrors};
+
+pub const MIN_DETAIL_LENGTH: usize = 10;
+pub const MAX_DETAIL_LENGTH: usize = 1000;
+
+#[derive(
+    Enum,
+    Copy,
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    strum::AsRefStr,
+    strum::EnumString,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[oai(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ReportReason {
+    Spam,
+    Nsfw,
+    Malware,
+    Impersonation,
+    /// Requires the reporter to describe the problem.
+    Other,
+}
+
+impl ReportReason {
+    #[inline]
+    pub fn requires_detail(&self) -> bool {
+        matches!(self, Self::Other)
+    }
+}
+
+cql_text_enum!(ReportReason);
+
+#[derive(Object, Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ReportPayload {
+    /// The bot or pack being reported.
+    pub entity_id: JsSafeBigInt,
+    pub reason: ReportReason,
+    /// A description of the problem, required when the reason is `other`.
+    #[oai(skip_serializing_if_is_none)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+impl ReportPayload {
+    /// Checks the detail is given when the reason requires it.
+    ///
+    /// The detail is optional context for the other reasons so is only
+    /// length checked when the reason is `other`.
+    pub fn validate(&self) -> ApiResult<()> {
+        Ok(self.check()?)
+    }
+}
+
+impl Validate for ReportPayload {
+    fn validate_fields(&self, errors: &mut ValidationErrors) {
+        if !self.reason.requires_detail() {
+            return;
+        }
+
+        let length = self
+            .detail
+            .as_deref()
+            .map(|v| v.trim().chars().count())
+            .unwrap_or_default();
+
+        if !(MIN_DETAIL_LENGTH..=MAX_DETAIL_LENGTH).contains(&length) {
+            errors.add(
+                "detail",
+                format!(
+                    "Reports with the reason 'other' must include between {} and {} characters of detail.",
+                    MIN_DETAIL_LENGTH, MAX_DETAIL_LENGTH
+                ),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn payload(reason: ReportReason, detail: Option<&str>) -> ReportPayload {
+        ReportPayload {
+            entity_id: JsSafeBigInt(1),
+            reason,
+            detail: detail.map(|v| v.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_detail_only_required_for_other() {
+        assert!(payload(ReportReason::Spam, None).validate().is_ok());
+        assert!(payload(ReportReason::Malware, Some("x")).validate().is_ok());
+
+        assert!(payload(ReportReason::Other, None).validate().is_err());
+        assert!(payload(ReportReason::Other, None)
+            .check()
+            .unwrap_err()
+            .get("detail")
+            .is_some());
+        assert!(payload(ReportReason::Other, Some("   short   "))
+            .validate()
+            .is_err());
+        assert!(payload(ReportReason::Other, Some(&"a".repeat(1001)))
+            .validate()
+            .is_err());
+        assert!(payload(ReportReason::Other, Some("Steals user tokens"))
+            .validate()
+            .is_ok());
+    }
+
+    #[test]
+    fn test_reason_text_round_trip() {
+        assert_eq!(ReportReason::Impersonation.as_ref(), "impersonation");
+        assert_eq!(ReportReason::from_str("nsfw").unwrap(), ReportReason::Nsfw);
+    }
+}
@@ -0,0 +1,371 @@
+use poem_openapi::{Enum, Object};
+use sha2::{Digest, Sha256};
+use url::Url;
+
+use crate::types::{DiscordInvite, JsSafeBigInt};
+
+/// The number of words in each shingle, descriptions are compared by the
+/// overlap of these rather than of single words.
+pub const SHINGLE_SIZE: usize = 3;
+/// The number of hash functions in a [MinHash] signature.
+pub const MINHASH_SIZE: usize = 64;
+
+fn hash(bytes: &[u8]) -> u64 {
+    let digest = Sha256::digest(bytes);
+    let mut prefix = [0; 8];
+    prefix.copy_from_slice(&digest[..8]);
+    u64::from_be_bytes(prefix)
+}
+
+/// The splitmix64 finaliser, used to derive the minhash functions from a
+/// single shingle hash.
+fn mix(mut v: u64) -> u64 {
+    v = (v ^ (v >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    v = (v ^ (v >> 27)).wrapping_mul(0x94d049bb133111eb);
+    v ^ (v >> 31)
+}
+
+/// The lowercase ASCII words of the text, punctuation is dropped.
+pub(crate) fn words(text: &str) -> Vec<String> {
+    deunicode::deunicode(text)
+        .to_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|v| !v.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// The hashes of each run of [SHINGLE_SIZE] words, texts shorter than that
+/// are a single shingle.
+fn shingles(text: &str) -> Vec<u64> {
+    let words = words(text);
+    if words.is_empty() {
+        return vec![];
+    }
+
+    let mut shingles = words
+        .windows(SHINGLE_SIZE.min(words.len()))
+        .map(|v| hash(v.join(" ").as_bytes()))
+        .collect::<Vec<_>>();
+    shingles.sort_unstable();
+    shingles.dedup();
+    shingles
+}
+
+/// A 64 bit locality sensitive hash, near duplicate texts differ in few bits.
+#[derive(
+    Copy, Clone, Debug, Default, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize,
+)]
+pub struct SimHash(pub u64);
+
+impl SimHash {
+    pub fn of(text: &str) -> Self {
+        Self::from_shingles(&shingles(text))
+    }
+
+    fn from_shingles(shingles: &[u64]) -> Self {
+        let mut weights = [0i32; 64];
+        for shingle in shingles {
+            for (bit, weight) in weights.iter_mut().enumerate() {
+                if shingle >> bit & 1 == 1 {
+                    *weight += 1;
+                } else {
+                    *weight -= 1;
+                }
+            }
+        }
+
+        let hash = weights
+            .iter()
+            .enumerate()
+            .filter(|(_, w)| **w > 0)
+            .fold(0u64, |acc, (bit, _)| acc | 1 << bit);
+
+        Self(hash)
+    }
+
+    /// The number of differing bits.
+    #[inline]
+    pub fn distance(&self, other: &Self) -> u32 {
+        (self.0 ^ other.0).count_ones()
+    }
+}
+
+/// A signature estimating the Jaccard similarity of two texts' shingles.
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct MinHash(pub Vec<u64>);
+
+impl MinHash {
+    pub fn of(text: &str) -> Self {
+        Self::from_shingles(&shingles(text))
+    }
+
+    fn from_shingles(shingles: &[u64]) -> Self {
+        if shingles.is_empty() {
+            return Self::default();
+        }
+
+        let signature = (0..MINHASH_SIZE as u64)
+            .map(|seed| {
+                let seed = mix(seed.wrapping_add(1));
+                shingles.iter().map(|v| mix(v ^ seed)).min().unwrap()
+            })
+            .collect();
+
+        Self(signature)
+    }
+
+    /// The estimated similarity between `0.0` and `1.0`, empty texts are
+    /// never similar to anything.
+    pub fn similarity(&self, other: &Self) -> f64 {
+        if self.0.is_empty() || self.0.len() != other.0.len() {
+            return 0.0;
+        }
+
+        let matching = self.0.iter().zip(&other.0).filter(|(a, b)| a == b).count();
+        matching as f64 / self.0.len() as f64
+    }
+}
+
+/// The fingerprints of a submitted description, stored alongside recent
+/// submissions so new ones can be compared against them.
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Fingerprint {
+    pub simhash: SimHash,
+    pub minhash: MinHash,
+}
+
+impl Fingerprint {
+    pub fn of(text: &str) -> Self {
+        let shingles = shingles(text);
+
+        Self {
+            simhash: SimHash::from_shingles(&shingles),
+            minhash: MinHash::from_shingles(&shingles),
+        }
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.minhash.0.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpamLimits {
+    /// The estimated Jaccard similarity at which a submission is a near duplicate.
+    pub similarity_threshold: f64,
+    /// The simhash distance at or below which a submission is a near duplicate.
+    pub max_simhash_distance: u32,
+    /// The fraction of words which may be links.
+    pub max_link_density: f64,
+    /// Link density is not checked for texts shorter than this, a short
+    /// description with a single support link is fine.
+    pub min_words_for_density: usize,
+    /// The number of distinct guild invites allowed in a description.
+    pub max_invites: usize,
+}
+
+impl Default for SpamLimits {
+    fn default() -> Self {
+        Self {
+            similarity_threshold: 0.8,
+            max_simhash_distance: 3,
+            max_link_density: 0.2,
+            min_words_for_density: 10,
+            max_invites: 1,
+        }
+    }
+}
+
+#[derive(
+    Enum,
+    Copy,
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    strum::AsRefStr,
+    strum::EnumString,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[oai(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum SpamSignal {
+    /// The text is a near copy of a recent submission.
+    NearDuplicate,
+    /// Too much of the text is links.
+    LinkDensity,
+    /// The text advertises too many guild invites.
+    InviteSpam,
+}
+
+#[derive(Object, Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct SpamVerdict {
+    pub signals: Vec<SpamSignal>,
+    /// The most similar recent submission when the text is a near duplicate.
+    #[oai(skip_serializing_if_is_none)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duplicate_of: Option<JsSafeBigInt>,
+    /// The highest estimated similarity to a recent submission.
+    pub similarity: f64,
+    pub link_density: f64,
+    pub invite_count: u32,
+}
+
+impl SpamVerdict {
+    #[inline]
+    pub fn is_spam(&self) -> bool {
+        !self.signals.is_empty()
+    }
+}
+
+/// Parses a whitespace separated word as a link, bare `www.` and
+/// `discord.gg/` links are accepted without a scheme.
+pub(crate) fn parse_link(word: &str) -> Option<Url> {
+    let word = word.trim_matches(|c: char| "()<>[]{}\"',.!?".contains(c));
+    let lower = word.to_ascii_lowercase();
+
+    if lower.starts_with("http://") || lower.starts_with("https://") {
+        Url::parse(word).ok()
+    } else if lower.starts_with("www.") || lower.starts_with("discord.gg/") {
+        Url::parse(&format!("https://{}", word)).ok()
+    } else {
+        None
+    }
+}
+
+/// The number of words, how many of them are links and the distinct guild
+/// invite codes.
+fn count_links(text: &str) -> (usize, usize, Vec<String>) {
+    let mut words = 0;
+    let mut links = 0;
+    let mut invites = Vec::new();
+
+    for word in text.split_whitespace() {
+        words += 1;
+
+        let Some(url) = parse_link(word) else {
+            continue;
+        };
+        links += 1;
+
+        if let Some(code) = DiscordInvite(url).code() {
+            if !invites.iter().any(|v: &String| v == code) {
+                invites.push(code.to_string());
+            }
+        }
+    }
+
+    (words, links, invites)
+}
+
+/// Checks a description against the recent submissions and the link
+/// heuristics.
+pub fn check_spam<'a>(
+    text: &str,
+    recent: impl IntoIterator<Item = (JsSafeBigInt, &'a Fingerprint)>,
+    limits: &SpamLimits,
+) -> SpamVerdict {
+    let mut verdict = SpamVerdict::default();
+    let fingerprint = Fingerprint::of(text);
+
+    if !fingerprint.is_empty() {
+        for (id, other) in recent {
+            let similarity = fingerprint.minhash.similarity(&other.minhash);
+            let is_duplicate = similarity >= limits.similarity_threshold
+                || (!other.is_empty()
+                    && fingerprint.simhash.distance(&other.simhash) <= limits.max_simhash_distance);
+
+            if is_duplicate && (verdict.duplicate_of.is_none() || similarity > verdict.similarity) {
+                verdict.duplicate_of = Some(id);
+            }
+            verdict.similarity = verdict.similarity.max(similarity);
+        }
+    }
+
+    if verdict.duplicate_of.is_some() {
+        verdict.signals.push(SpamSignal::NearDuplicate);
+    }
+
+    let (words, links, invites) = count_links(text);
+    if words > 0 {
+        verdict.link_density = links as f64 / words as f64;
+    }
+    if words >= limits.min_words_for_density && verdict.link_density > limits.max_link_density {
+        verdict.signals.push(SpamSignal::LinkDensity);
+    }
+
+    verdict.invite_count = invites.len() as u32;
+    if invites.len() > limits.max_invites {
+        verdict.signals.push(SpamSignal::InviteSpam);
+    }
+
+    verdict
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DESCRIPTION: &str = "A feature packed music bot with playlists, lyrics, filters \
+        and high quality playback from every major streaming service you love.";
+
+    #[test]
+    fn test_near_duplicates_are_similar() {
+        let original = Fingerprint::of(DESCRIPTION);
+        let copy = Fingerprint::of(&DESCRIPTION.replace("you love", "you enjoy"));
+        let other = Fingerprint::of(
+            "Moderation tools for large communities including automod, logging and raid protection.",
+        );
+
+        assert!(original.minhash.similarity(&copy.minhash) > 0.6);
+        assert!(original.minhash.similarity(&other.minhash) < 0.2);
+        assert!(
+            original.simhash.distance(&copy.simhash) < original.simhash.distance(&other.simhash)
+        );
+        assert_eq!(
+            original.minhash.similarity(&Fingerprint::of("").minhash),
+            0.0
+        );
+    }
+
+    #[test]
+    fn test_check_spam_duplicate() {
+        let recent = Fingerprint::of(DESCRIPTION);
+        let text = DESCRIPTION.to_uppercase();
+        let verdict = check_spam(&text, [(JsSafeBigInt(7), &recent)], &SpamLimits::default());
+
+        assert_eq!(verdict.signals, [SpamSignal::NearDuplicate]);
+        assert_eq!(verdict.duplicate_of, Some(JsSafeBigInt(7)));
+        assert_eq!(verdict.similarity, 1.0);
+
+        let verdict = check_spam(DESCRIPTION, [], &SpamLimits::default());
+        assert!(!verdict.is_spam());
+    }
+
+    #[test]
+    fn test_check_spam_links() {
+        let text =
+            "join discord.gg/abc and https://discord.gg/def or https://discord.com/invite/ghi \
+            for giveaways, see www.example.com too";
+        let verdict = check_spam(text, [], &SpamLimits::default());
+
+        assert_eq!(verdict.invite_count, 3);
+        assert_eq!(
+            verdict.signals,
+            [SpamSignal::LinkDensity, SpamSignal::InviteSpam]
+        );
+
+        let verdict = check_spam(
+            "Support server: https://discord.gg/abc.",
+            [],
+            &SpamLimits::default(),
+        );
+        assert!(!verdict.is_spam());
+        assert_eq!(verdict.invite_count, 1);
+    }
+}
@@ -0,0 +1,5 @@
+pub mod status;
+
+pub use status::{
+    overall, ComponentState, ComponentStatus, Incident, IncidentState, IncidentUpdate,
+};
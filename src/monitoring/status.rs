@@ -0,0 +1,416 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+use futures_util::TryStreamExt;
+use poem_openapi::{Enum, Object};
+use scylla::query::Query;
+use scylla::transport::errors::QueryError;
+use scylla::transport::iterator::NextRowError;
+use scylla::Session;
+use uuid::Uuid;
+
+use crate::db::DEFAULT_PAGE_SIZE;
+use crate::types::{Set, Timestamp};
+
+pub const COMPONENTS_TABLE: &str = "status_components";
+pub const INCIDENTS_TABLE: &str = "status_incidents";
+pub const INCIDENT_UPDATES_TABLE: &str = "status_incident_updates";
+
+/// The number of incidents whose updates are fetched with a single query,
+/// Scylla rejects `IN` restrictions on more than 100 partition keys by default.
+const UPDATES_BATCH_SIZE: usize = 100;
+
+type IncidentRow = (
+    Uuid,
+    String,
+    IncidentState,
+    ComponentState,
+    Set<String>,
+    Timestamp,
+    Option<Timestamp>,
+);
+type IncidentUpdateRow = (Uuid, Timestamp, IncidentState, String);
+
+#[derive(
+    Enum,
+    Copy,
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    strum::AsRefStr,
+    strum::EnumString,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[oai(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ComponentState {
+    Operational,
+    UnderMaintenance,
+    DegradedPerformance,
+    PartialOutage,
+    MajorOutage,
+}
+
+impl ComponentState {
+    /// How badly the state affects users, used to derive the overall status.
+    pub fn severity(&self) -> u8 {
+        match self {
+            Self::Operational => 0,
+            Self::UnderMaintenance => 1,
+            Self::DegradedPerformance => 2,
+            Self::PartialOutage => 3,
+            Self::MajorOutage => 4,
+        }
+    }
+}
+
+cql_text_enum!(ComponentState);
+
+#[derive(
+    Enum,
+    Copy,
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    strum::AsRefStr,
+    strum::EnumString,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[oai(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum IncidentState {
+    Investigating,
+    Identified,
+    Monitoring,
+    Resolved,
+}
+
+cql_text_enum!(IncidentState);
+
+#[derive(Object, Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ComponentStatus {
+    pub id: String,
+    pub name: String,
+    pub state: ComponentState,
+    pub updated_at: Timestamp,
+}
+
+#[derive(Object, Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct IncidentUpdate {
+    pub incident_id: Uuid,
+    pub state: IncidentState,
+    pub message: String,
+    pub created_at: Timestamp,
+}
+
+#[derive(Object, Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Incident {
+    pub id: Uuid,
+    pub title: String,
+    pub state: IncidentState,
+    pub impact: ComponentState,
+    /// The ids of the components affected by the incident.
+    pub components: Set<String>,
+    pub created_at: Timestamp,
+    pub resolved_at: Option<Timestamp>,
+    /// The updates posted on the incident, newest first.
+    pub updates: Vec<IncidentUpdate>,
+}
+
+impl Incident {
+    #[inline]
+    pub fn is_resolved(&self) -> bool {
+        self.state == IncidentState::Resolved
+    }
+}
+
+/// Computes the status shown at the top of the status page.
+///
+/// This is the most severe state out of all components and the impact of any
+/// unresolved incidents.
+pub fn overall(components: &[ComponentStatus], incidents: &[Incident]) -> ComponentState {
+    let component_states = components.iter().map(|v| v.state);
+    let incident_states = incidents
+        .iter()
+        .filter(|v| !v.is_resolved())
+        .map(|v| v.impact);
+
+    component_states
+        .chain(incident_states)
+        .max_by_key(|v| v.severity())
+        .unwrap_or(ComponentState::Operational)
+}
+
+pub async fn store_component(
+    session: &Session,
+    status: &ComponentStatus,
+) -> Result<(), QueryError> {
+    let query = format!(
+        "INSERT INTO {} (id, name, state, updated_at) VALUES (?, ?, ?, ?);",
+        COMPONENTS_TABLE
+    );
+
+    session
+        .query(
+            query,
+            (&status.id, &status.name, status.state, status.updated_at),
+        )
+        .await?;
+
+    Ok(())
+}
+
+pub async fn fetch_components(session: &Session) -> Result<Vec<ComponentStatus>, QueryError> {
+    let query = format!(
+        "SELECT id, name, state, updated_at FROM {};",
+        COMPONENTS_TABLE
+    );
+
+    let rows = session
+        .query(query, &[])
+        .await?
+        .rows_typed_or_empty::<(String, String, ComponentState, Timestamp)>();
+
+    let mut components = vec![];
+    for row in rows {
+        let (id, name, state, updated_at) = row.map_err(|e| parse_error(COMPONENTS_TABLE, e))?;
+        components.push(ComponentStatus {
+            id,
+            name,
+            state,
+            updated_at,
+        });
+    }
+
+    Ok(components)
+}
+
+/// Stores the incident itself, updates are stored separately with [store_incident_update].
+pub async fn store_incident(session: &Session, incident: &Incident) -> Result<(), QueryError> {
+    let query = format!(
+        "INSERT INTO {} (id, title, state, impact, components, created_at, resolved_at) \
+        VALUES (?, ?, ?, ?, ?, ?, ?);",
+        INCIDENTS_TABLE
+    );
+
+    session
+        .query(
+            query,
+            (
+                incident.id,
+                &incident.title,
+                incident.state,
+                incident.impact,
+                &incident.components,
+                incident.created_at,
+                incident.resolved_at,
+            ),
+        )
+        .await?;
+
+    Ok(())
+}
+
+pub async fn store_incident_update(
+    session: &Session,
+    update: &IncidentUpdate,
+) -> Result<(), QueryError> {
+    let query = format!(
+        "INSERT INTO {} (incident_id, created_at, state, message) VALUES (?, ?, ?, ?);",
+        INCIDENT_UPDATES_TABLE
+    );
+
+    session
+        .query(
+            query,
+            (
+                update.incident_id,
+                update.created_at,
+                update.state,
+                &update.message,
+            ),
+        )
+        .await?;
+
+    Ok(())
+}
+
+pub async fn fetch_incident_updates(
+    session: &Session,
+    incident_id: Uuid,
+) -> Result<Vec<IncidentUpdate>, QueryError> {
+    let query = format!(
+        "SELECT incident_id, created_at, state, message FROM {} WHERE incident_id = ?;",
+        INCIDENT_UPDATES_TABLE
+    );
+
+    let rows = session
+        .query(query, (incident_id,))
+        .await?
+        .rows_typed_or_empty::<IncidentUpdateRow>();
+
+    let mut updates = vec![];
+    for row in rows {
+        updates.push(incident_update(
+            row.map_err(|e| parse_error(INCIDENT_UPDATES_TABLE, e))?,
+        ));
+    }
+
+    updates.sort_by_key(|v| Reverse(v.created_at.0));
+
+    Ok(updates)
+}
+
+/// Fetches the updates of many incidents at once, keyed by incident and newest first.
+async fn fetch_updates_for(
+    session: &Session,
+    incident_ids: &[Uuid],
+) -> Result<HashMap<Uuid, Vec<IncidentUpdate>>, QueryError> {
+    let query = format!(
+        "SELECT incident_id, created_at, state, message FROM {} WHERE incident_id IN ?;",
+        INCIDENT_UPDATES_TABLE
+    );
+
+    let mut updates: HashMap<Uuid, Vec<IncidentUpdate>> = HashMap::new();
+    for ids in incident_ids.chunks(UPDATES_BATCH_SIZE) {
+        let rows = session
+            .query(query.as_str(), (ids,))
+            .await?
+            .rows_typed_or_empty::<IncidentUpdateRow>();
+
+        for row in rows {
+            let update = incident_update(row.map_err(|e| parse_error(INCIDENT_UPDATES_TABLE, e))?);
+            updates.entry(update.incident_id).or_default().push(update);
+        }
+    }
+
+    for v in updates.values_mut() {
+        v.sort_by_key(|v| Reverse(v.created_at.0));
+    }
+
+    Ok(updates)
+}
+
+/// Fetches all incidents along with their updates, newest first.
+///
+/// Incidents are read a page at a time and their updates are fetched in
+/// batches rather than one query per incident.
+pub async fn fetch_incidents(session: &Session) -> Result<Vec<Incident>, QueryError> {
+    let query = format!(
+        "SELECT id, title, state, impact, components, created_at, resolved_at FROM {};",
+        INCIDENTS_TABLE
+    );
+
+    let mut query = Query::new(query);
+    query.set_page_size(DEFAULT_PAGE_SIZE);
+
+    let rows: Vec<IncidentRow> = session
+        .query_iter(query, &[])
+        .await?
+        .into_typed::<IncidentRow>()
+        .map_err(|e| match e {
+            NextRowError::QueryError(e) => e,
+            NextRowError::FromRowError(e) => parse_error(INCIDENTS_TABLE, e),
+        })
+        .try_collect()
+        .await?;
+
+    let ids = rows.iter().map(|row| row.0).collect::<Vec<_>>();
+    let mut updates = fetch_updates_for(session, &ids).await?;
+
+    let mut incidents = vec![];
+    for (id, title, state, impact, components, created_at, resolved_at) in rows {
+        let updates = updates.remove(&id).unwrap_or_default();
+        incidents.push(Incident {
+            id,
+            title,
+            state,
+            impact,
+            components,
+            created_at,
+            resolved_at,
+            updates,
+        });
+    }
+
+    incidents.sort_by_key(|v| Reverse(v.created_at.0));
+
+    Ok(incidents)
+}
+
+fn incident_update((incident_id, created_at, state, message): IncidentUpdateRow) -> IncidentUpdate {
+    IncidentUpdate {
+        incident_id,
+        state,
+        message,
+        created_at,
+    }
+}
+
+fn parse_error(table: &str, e: impl std::fmt::Display) -> QueryError {
+    QueryError::InvalidMessage(format!("Failed to parse row from {}: {}", table, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn component(state: ComponentState) -> ComponentStatus {
+        ComponentStatus {
+            id: "api".to_string(),
+            name: "API".to_string(),
+            state,
+            updated_at: Timestamp::default(),
+        }
+    }
+
+    fn incident(state: IncidentState, impact: ComponentState) -> Incident {
+        Incident {
+            id: Uuid::new_v4(),
+            title: "Elevated error rates".to_string(),
+            state,
+            impact,
+            components: Set::from(vec!["api".to_string()]),
+            created_at: Timestamp::default(),
+            resolved_at: None,
+            updates: vec![],
+        }
+    }
+
+    #[test]
+    fn test_overall_picks_most_severe() {
+        let components = vec![
+            component(ComponentState::Operational),
+            component(ComponentState::PartialOutage),
+            component(ComponentState::UnderMaintenance),
+        ];
+
+        assert_eq!(overall(&components, &[]), ComponentState::PartialOutage);
+        assert_eq!(overall(&[], &[]), ComponentState::Operational);
+    }
+
+    #[test]
+    fn test_overall_ignores_resolved_incidents() {
+        let components = vec![component(ComponentState::Operational)];
+        let incidents = vec![
+            incident(IncidentState::Resolved, ComponentState::MajorOutage),
+            incident(
+                IncidentState::Monitoring,
+                ComponentState::DegradedPerformance,
+            ),
+        ];
+
+        assert_eq!(
+            overall(&components, &incidents),
+            ComponentState::DegradedPerformance
+        );
+    }
+}
@@ -0,0 +1,254 @@
+use std::collections::{HashSet, VecDeque};
+use std::sync::Mutex;
+
+use crate::notifications::embed::{MAX_EMBEDS, MAX_EMBED_CHARS, MAX_EMBED_FIELDS};
+use crate::notifications::{Dispatcher, Embed, EmbedBuilder, WebhookMessage};
+use crate::types::{JsSafeBigInt, Timestamp};
+
+const APPROVED_COLOR: u32 = 0x57F287;
+const TRENDING_COLOR: u32 = 0xFEE75C;
+
+/// The number of recent event keys remembered for dedupe.
+const DEDUPE_HISTORY: usize = 1024;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ListingEvent {
+    Approved {
+        id: JsSafeBigInt,
+        name: String,
+        url: String,
+    },
+    TrendingChanged {
+        id: JsSafeBigInt,
+        name: String,
+        url: String,
+        rank: u32,
+        previous_rank: Option<u32>,
+    },
+}
+
+impl ListingEvent {
+    /// The key used to detect duplicate deliveries of the same event.
+    fn dedupe_key(&self) -> String {
+        match self {
+            Self::Approved { id, .. } => format!("approved:{}", id),
+            Self::TrendingChanged { id, rank, .. } => format!("trending:{}:{}", id, rank),
+        }
+    }
+}
+
+#[derive(Default)]
+struct FeedState {
+    seen: HashSet<String>,
+    history: VecDeque<String>,
+    pending: Vec<ListingEvent>,
+}
+
+/// Formats listing events for our announcement channels.
+///
+/// Events are buffered until `flush` is called, approvals are batched into as
+/// few embeds as possible and events which were already posted recently are dropped.
+pub struct ChannelFeed<D> {
+    dispatcher: D,
+    state: Mutex<FeedState>,
+}
+
+impl<D: Dispatcher> ChannelFeed<D> {
+    pub fn new(dispatcher: D) -> Self {
+        Self {
+            dispatcher,
+            state: Mutex::new(FeedState::default()),
+        }
+    }
+
+    /// Queues the event, returns `false` if it is a duplicate.
+    pub fn push(&self, event: ListingEvent) -> bool {
+        let key = event.dedupe_key();
+        let mut state = self.state.lock().unwrap();
+
+        if !state.seen.insert(key.clone()) {
+            return false;
+        }
+
+        state.history.push_back(key);
+        if state.history.len() > DEDUPE_HISTORY {
+            if let Some(oldest) = state.history.pop_front() {
+                state.seen.remove(&oldest);
+            }
+        }
+
+        state.pending.push(event);
+        true
+    }
+
+    /// Sends all queued events, returning the number of messages dispatched.
+    pub fn flush(&self) -> usize {
+        let pending = std::mem::take(&mut self.state.lock().unwrap().pending);
+        let messages = pack(render(pending));
+        let sent = messages.len();
+
+        for message in messages {
+            self.dispatcher.dispatch(message);
+        }
+
+        sent
+    }
+}
+
+/// Groups the embeds into as few messages as fit within Discord's limits.
+fn pack(embeds: Vec<Embed>) -> Vec<WebhookMessage> {
+    let mut messages: Vec<WebhookMessage> = vec![];
+    let mut chars = 0;
+
+    for embed in embeds {
+        let len = embed.char_count();
+
+        match messages.last_mut() {
+            Some(message)
+                if message.embeds.len() < MAX_EMBEDS && chars + len <= MAX_EMBED_CHARS =>
+            {
+                chars += len;
+                message.embeds.push(embed);
+            }
+            _ => {
+                chars = len;
+                messages.push(WebhookMessage {
+                    content: None,
+                    embeds: vec![embed],
+                });
+            }
+        }
+    }
+
+    messages
+}
+
+fn render(events: Vec<ListingEvent>) -> Vec<Embed> {
+    let now = Timestamp::default();
+    let mut approvals = vec![];
+    let mut embeds = vec![];
+
+    for event in events {
+        match event {
+            ListingEvent::Approved { name, url, .. } => approvals.push((name, url)),
+            ListingEvent::TrendingChanged {
+                name,
+                url,
+                rank,
+                previous_rank,
+                ..
+            } => {
+                let description = match previous_rank {
+                    Some(previous) => format!("Moved from #{} to #{}", previous, rank),
+                    None => format!("Entered trending at #{}", rank),
+                };
+
+                embeds.push(
+                    EmbedBuilder::new()
+                        .title(format!("{} is trending", name))
+                        .url(url)
+                        .description(description)
+                        .color(TRENDING_COLOR)
+                        .timestamp(now)
+                        .build(),
+                );
+            }
+        }
+    }
+
+    let approval_embeds = approvals.chunks(MAX_EMBED_FIELDS).map(|chunk| {
+        let title = match chunk.len() {
+            1 => "New listing approved".to_string(),
+            n => format!("{} new listings approved", n),
+        };
+
+        chunk
+            .iter()
+            .fold(EmbedBuilder::new(), |builder, (name, url)| {
+                builder.field(name, format!("[View listing]({})", url), true)
+            })
+            .title(title)
+            .color(APPROVED_COLOR)
+            .timestamp(now)
+            .build()
+    });
+
+    approval_embeds.chain(embeds).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct Collect(Mutex<Vec<WebhookMessage>>);
+
+    impl Dispatcher for &Collect {
+        fn dispatch(&self, message: WebhookMessage) {
+            self.0.lock().unwrap().push(message);
+        }
+    }
+
+    fn approved(id: i64) -> ListingEvent {
+        ListingEvent::Approved {
+            id: JsSafeBigInt(id),
+            name: format!("bot {}", id),
+            url: format!("https://discordlist.gg/bot/{}", id),
+        }
+    }
+
+    #[test]
+    fn test_approvals_batched_and_deduped() {
+        let collect = Collect::default();
+        let feed = ChannelFeed::new(&collect);
+
+        assert!(feed.push(approved(1)));
+        assert!(feed.push(approved(2)));
+        assert!(!feed.push(approved(1)));
+        assert!(feed.push(ListingEvent::TrendingChanged {
+            id: JsSafeBigInt(3),
+            name: "bot 3".to_string(),
+            url: "https://discordlist.gg/bot/3".to_string(),
+            rank: 1,
+            previous_rank: Some(4),
+        }));
+
+        assert_eq!(feed.flush(), 1);
+        assert_eq!(feed.flush(), 0);
+
+        let messages = collect.0.lock().unwrap();
+        let embeds = &messages[0].embeds;
+        assert_eq!(embeds.len(), 2);
+        assert_eq!(embeds[0].title.as_deref(), Some("2 new listings approved"));
+        assert_eq!(embeds[0].fields.len(), 2);
+        assert_eq!(
+            embeds[1].description.as_deref(),
+            Some("Moved from #4 to #1")
+        );
+    }
+
+    #[test]
+    fn test_large_batches_split() {
+        let collect = Collect::default();
+        let feed = ChannelFeed::new(&collect);
+
+        let count = MAX_EMBED_FIELDS * MAX_EMBEDS + 1;
+        for id in 0..count as i64 {
+            feed.push(approved(id));
+        }
+
+        assert_eq!(feed.flush(), 3);
+
+        let messages = collect.0.lock().unwrap();
+        let mut fields = 0;
+        for message in messages.iter() {
+            assert!(message.embeds.len() <= MAX_EMBEDS);
+
+            let chars: usize = message.embeds.iter().map(Embed::char_count).sum();
+            assert!(chars <= MAX_EMBED_CHARS);
+
+            fields += message.embeds.iter().map(|v| v.fields.len()).sum::<usize>();
+        }
+        assert_eq!(fields, count);
+    }
+}
@@ -0,0 +1,190 @@
+use std::str::FromStr;
+
+use chrono::{Datelike, Duration, LocalResult, NaiveDate, NaiveTime, TimeZone, Utc, Weekday};
+use chrono_tz::Tz;
+
+use crate::types::Timestamp;
+
+/// The half-open period `[start, end)` a single digest covers.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct DigestWindow {
+    pub start: Timestamp,
+    pub end: Timestamp,
+}
+
+impl DigestWindow {
+    #[inline]
+    pub fn contains(&self, ts: Timestamp) -> bool {
+        self.start.0 <= ts.0 && ts.0 < self.end.0
+    }
+}
+
+/// When a user receives their weekly digest, in their own timezone.
+///
+/// Consecutive windows always share a boundary so a listing falls into
+/// exactly one digest, even when the week spans a DST change.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct DigestSchedule {
+    pub weekday: Weekday,
+    /// The local hour the digest is sent at, hours past 23 send at 23:00.
+    pub hour: u32,
+    pub timezone: Tz,
+}
+
+impl DigestSchedule {
+    /// A schedule sending on Monday at 09:00 in the given timezone.
+    pub fn new(timezone: Tz) -> Self {
+        Self {
+            weekday: Weekday::Mon,
+            hour: 9,
+            timezone,
+        }
+    }
+
+    /// Creates a schedule from a stored IANA timezone name, falling back to UTC
+    /// for unknown or missing names.
+    pub fn for_timezone(name: Option<&str>) -> Self {
+        let timezone = name.and_then(|v| Tz::from_str(v).ok()).unwrap_or(Tz::UTC);
+        Self::new(timezone)
+    }
+
+    pub fn at(mut self, weekday: Weekday, hour: u32) -> Self {
+        self.weekday = weekday;
+        self.hour = hour.min(23);
+        self
+    }
+
+    /// The instant of the send on the given local date.
+    ///
+    /// Times skipped by a DST change are moved forward to the first valid
+    /// time and repeated times use the first occurrence.
+    fn send_on(&self, date: NaiveDate) -> Timestamp {
+        let mut local = date.and_time(NaiveTime::MIN) + Duration::hours(self.hour.min(23) as i64);
+
+        loop {
+            match self.timezone.from_local_datetime(&local) {
+                LocalResult::Single(v) | LocalResult::Ambiguous(v, _) => {
+                    return Timestamp(v.with_timezone(&Utc))
+                }
+                LocalResult::None => local += Duration::minutes(30),
+            }
+        }
+    }
+
+    /// The most recent send at or before the given time.
+    fn previous_send(&self, now: Timestamp) -> (NaiveDate, Timestamp) {
+        let local_date = now.0.with_timezone(&self.timezone).date_naive();
+        let days_back = (7 + local_date.weekday().num_days_from_monday()
+            - self.weekday.num_days_from_monday())
+            % 7;
+
+        let date = local_date - Duration::days(days_back as i64);
+        let send = self.send_on(date);
+        if send.0 <= now.0 {
+            return (date, send);
+        }
+
+        let date = date - Duration::weeks(1);
+        (date, self.send_on(date))
+    }
+
+    /// The most recently completed window, this is what should be sent at `now`.
+    pub fn last_completed(&self, now: Timestamp) -> DigestWindow {
+        let (date, end) = self.previous_send(now);
+        let start = self.send_on(date - Duration::weeks(1));
+        DigestWindow { start, end }
+    }
+
+    /// The window which is currently accumulating listings.
+    pub fn current(&self, now: Timestamp) -> DigestWindow {
+        let (date, start) = self.previous_send(now);
+        let end = self.send_on(date + Duration::weeks(1));
+        DigestWindow { start, end }
+    }
+
+    /// Every window from the one containing `from` onwards.
+    pub fn windows(self, from: Timestamp) -> impl Iterator<Item = DigestWindow> {
+        let (date, _) = self.previous_send(from);
+
+        (0..).map(move |week| {
+            let start = date + Duration::weeks(week);
+            DigestWindow {
+                start: self.send_on(start),
+                end: self.send_on(start + Duration::weeks(1)),
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ts(rfc3339: &str) -> Timestamp {
+        Timestamp::from_str(rfc3339).unwrap()
+    }
+
+    #[test]
+    fn test_windows_are_contiguous_across_dst() {
+        let schedule = DigestSchedule::for_timezone(Some("Europe/London"));
+        let windows: Vec<_> = schedule
+            .windows(ts("2023-03-01T00:00:00Z"))
+            .take(10)
+            .collect();
+
+        for pair in windows.windows(2) {
+            assert_eq!(pair[0].end, pair[1].start);
+        }
+
+        // The clocks go forward on the 26th so this week is an hour short.
+        let spring = windows
+            .iter()
+            .find(|v| v.contains(ts("2023-03-26T12:00:00Z")))
+            .unwrap();
+        assert_eq!(spring.end.0 - spring.start.0, Duration::hours(167));
+        assert_eq!(spring.end, ts("2023-03-27T08:00:00Z"));
+    }
+
+    #[test]
+    fn test_out_of_range_hour_is_clamped() {
+        let schedule = DigestSchedule {
+            hour: 24,
+            ..DigestSchedule::new(Tz::UTC)
+        };
+        let clamped = DigestSchedule::new(Tz::UTC).at(Weekday::Mon, 23);
+
+        let now = ts("2023-06-07T00:00:00Z");
+        assert_eq!(schedule.current(now), clamped.current(now));
+        assert_eq!(schedule.current(now).start, ts("2023-06-05T23:00:00Z"));
+    }
+
+    #[test]
+    fn test_boundaries() {
+        let schedule = DigestSchedule::for_timezone(Some("America/New_York"));
+        let send = ts("2023-06-05T13:00:00Z");
+
+        assert_eq!(schedule.last_completed(send).end, send);
+        assert_eq!(schedule.current(send).start, send);
+        assert_eq!(
+            schedule.last_completed(ts("2023-06-05T12:59:59Z")).end,
+            ts("2023-05-29T13:00:00Z")
+        );
+    }
+
+    #[test]
+    fn test_skipped_hour_moves_forward() {
+        let schedule = DigestSchedule::for_timezone(Some("Europe/London")).at(Weekday::Sun, 1);
+        let window = schedule.current(ts("2023-03-26T12:00:00Z"));
+
+        assert_eq!(window.start, ts("2023-03-26T01:00:00Z"));
+    }
+
+    #[test]
+    fn test_unknown_timezone_falls_back() {
+        assert_eq!(
+            DigestSchedule::for_timezone(Some("Mars/Olympus")).timezone,
+            Tz::UTC
+        );
+        assert_eq!(DigestSchedule::for_timezone(None).timezone, Tz::UTC);
+    }
+}
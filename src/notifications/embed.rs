@@ -0,0 +1,182 @@
+use crate::types::Timestamp;
+
+/// The maximum number of fields Discord allows on a single embed.
+pub const MAX_EMBED_FIELDS: usize = 25;
+/// The maximum number of embeds Discord allows on a single message.
+pub const MAX_EMBEDS: usize = 10;
+/// The maximum number of characters Discord allows across all embeds of a message.
+pub const MAX_EMBED_CHARS: usize = 6000;
+
+const MAX_TITLE_CHARS: usize = 256;
+const MAX_DESCRIPTION_CHARS: usize = 4096;
+const MAX_FIELD_NAME_CHARS: usize = 256;
+const MAX_FIELD_VALUE_CHARS: usize = 1024;
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct EmbedField {
+    pub name: String,
+    pub value: String,
+    #[serde(default)]
+    pub inline: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Embed {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<Timestamp>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<EmbedField>,
+}
+
+impl Embed {
+    /// The number of characters Discord counts towards [MAX_EMBED_CHARS].
+    pub fn char_count(&self) -> usize {
+        let fields = self
+            .fields
+            .iter()
+            .map(|v| v.name.chars().count() + v.value.chars().count());
+
+        [&self.title, &self.description]
+            .into_iter()
+            .flatten()
+            .map(|v| v.chars().count())
+            .chain(fields)
+            .sum()
+    }
+}
+
+/// Cuts the text down to `max` characters, ending it with an ellipsis if anything was removed.
+fn truncate(text: String, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text;
+    }
+
+    match text.char_indices().nth(max.saturating_sub(1)) {
+        Some((i, _)) if max > 0 => format!("{}…", &text[..i]),
+        _ => String::new(),
+    }
+}
+
+/// Builds embeds within Discord's limits, text which is too long is truncated.
+#[derive(Debug, Clone, Default)]
+pub struct EmbedBuilder {
+    embed: Embed,
+}
+
+impl EmbedBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.embed.title = Some(truncate(title.into(), MAX_TITLE_CHARS));
+        self
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.embed.description = Some(truncate(description.into(), MAX_DESCRIPTION_CHARS));
+        self
+    }
+
+    pub fn url(mut self, url: impl Into<String>) -> Self {
+        self.embed.url = Some(url.into());
+        self
+    }
+
+    pub fn color(mut self, color: u32) -> Self {
+        self.embed.color = Some(color);
+        self
+    }
+
+    pub fn timestamp(mut self, timestamp: Timestamp) -> Self {
+        self.embed.timestamp = Some(timestamp);
+        self
+    }
+
+    /// Adds a field to the embed, fields beyond Discord's limit are dropped.
+    pub fn field(
+        mut self,
+        name: impl Into<String>,
+        value: impl Into<String>,
+        inline: bool,
+    ) -> Self {
+        if self.embed.fields.len() < MAX_EMBED_FIELDS {
+            self.embed.fields.push(EmbedField {
+                name: truncate(name.into(), MAX_FIELD_NAME_CHARS),
+                value: truncate(value.into(), MAX_FIELD_VALUE_CHARS),
+                inline,
+            });
+        }
+        self
+    }
+
+    /// Builds the embed, dropping fields and then shortening the description
+    /// until it fits in [MAX_EMBED_CHARS].
+    pub fn build(mut self) -> Embed {
+        while self.embed.char_count() > MAX_EMBED_CHARS && !self.embed.fields.is_empty() {
+            self.embed.fields.pop();
+        }
+
+        let excess = self.embed.char_count().saturating_sub(MAX_EMBED_CHARS);
+        if excess > 0 {
+            if let Some(description) = self.embed.description.take() {
+                let max = description.chars().count().saturating_sub(excess);
+                self.embed.description = Some(truncate(description, max)).filter(|v| !v.is_empty());
+            }
+        }
+
+        self.embed
+    }
+}
+
+/// The body of a Discord webhook execution.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct WebhookMessage {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+    pub embeds: Vec<Embed>,
+}
+
+/// Somewhere webhook messages can be queued to be sent to a channel.
+pub trait Dispatcher {
+    fn dispatch(&self, message: WebhookMessage);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncates_to_limits() {
+        let embed = EmbedBuilder::new()
+            .title("t".repeat(300))
+            .field("name", "v".repeat(2000), false)
+            .build();
+        assert_eq!(
+            embed.title.as_ref().unwrap().chars().count(),
+            MAX_TITLE_CHARS
+        );
+        assert!(embed.title.unwrap().ends_with('…'));
+        assert_eq!(embed.fields[0].value.chars().count(), MAX_FIELD_VALUE_CHARS);
+
+        let embed = (0..MAX_EMBED_FIELDS)
+            .fold(EmbedBuilder::new(), |builder, i| {
+                builder.field(format!("field {}", i), "ü".repeat(1024), false)
+            })
+            .description("d".repeat(4096))
+            .build();
+        assert!(embed.char_count() <= MAX_EMBED_CHARS);
+        assert_eq!(embed.fields.len(), 1);
+
+        let embed = EmbedBuilder::new().title("short").build();
+        assert_eq!(embed.title.as_deref(), Some("short"));
+    }
+}
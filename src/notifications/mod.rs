@@ -0,0 +1,7 @@
+pub mod channel_feed;
+pub mod digest;
+mod embed;
+
+pub use channel_feed::{ChannelFeed, ListingEvent};
+pub use digest::{DigestSchedule, DigestWindow};
+pub use embed::{Dispatcher, Embed, EmbedBuilder, EmbedField, WebhookMessage};
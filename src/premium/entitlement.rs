@@ -0,0 +1,171 @@
+use poem_openapi::{Enum, Object};
+
+use crate::types::Timestamp;
+
+#[derive(
+    Enum,
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    strum::AsRefStr,
+    strum::EnumString,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[oai(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+/// Tiers are ordered, a higher tier includes everything of the tiers below it.
+pub enum Tier {
+    #[default]
+    Free,
+    Pro,
+    Business,
+}
+
+cql_text_enum!(Tier);
+
+#[derive(
+    Enum,
+    Copy,
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    strum::AsRefStr,
+    strum::EnumString,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[oai(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum EntitlementSource {
+    /// A recurring subscription managed by the payments service.
+    Subscription,
+    Gift,
+    Promotion,
+    /// Granted by staff.
+    Manual,
+}
+
+cql_text_enum!(EntitlementSource);
+
+/// A grant of a tier which lasts until it expires.
+#[derive(
+    Object,
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    scylla::FromRow,
+    scylla::ValueList,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+pub struct Entitlement {
+    pub tier: Tier,
+    /// The entitlement is active up to, but not including, this time.
+    pub expires: Timestamp,
+    pub source: EntitlementSource,
+}
+
+impl Entitlement {
+    #[inline]
+    pub fn is_active_at(&self, now: &Timestamp) -> bool {
+        now.0 < self.expires.0
+    }
+}
+
+/// The effective tier of an account.
+#[derive(Object, Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ResolvedTier {
+    pub tier: Tier,
+    /// When the tier next drops, not set for the free tier.
+    #[oai(skip_serializing_if_is_none)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub until: Option<Timestamp>,
+}
+
+/// Resolves the effective tier from all of an account's entitlements.
+///
+/// Entitlements overlap rather than queue, so the highest active tier wins and
+/// stays until the last entitlement of that tier or above expires. There is no
+/// grace period, once an entitlement lapses the account immediately drops to
+/// the next active tier, even mid-billing-period.
+pub fn resolve(entitlements: &[Entitlement], now: &Timestamp) -> ResolvedTier {
+    let active = || entitlements.iter().filter(|v| v.is_active_at(now));
+
+    let tier = match active().map(|v| v.tier).max() {
+        None | Some(Tier::Free) => return ResolvedTier::default(),
+        Some(tier) => tier,
+    };
+
+    let until = active()
+        .filter(|v| v.tier >= tier)
+        .map(|v| v.expires)
+        .max_by_key(|v| v.0);
+
+    ResolvedTier { tier, until }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entitlement(tier: Tier, expires: i64) -> Entitlement {
+        Entitlement {
+            tier,
+            expires: Timestamp::from(expires),
+            source: EntitlementSource::Subscription,
+        }
+    }
+
+    #[test]
+    fn test_highest_active_tier_wins() {
+        let entitlements = [
+            entitlement(Tier::Pro, 3_000),
+            entitlement(Tier::Business, 2_000),
+            entitlement(Tier::Business, 1_500),
+        ];
+
+        let resolved = resolve(&entitlements, &Timestamp::from(1_000));
+        assert_eq!(resolved.tier, Tier::Business);
+        assert_eq!(resolved.until, Some(Timestamp::from(2_000)));
+    }
+
+    #[test]
+    fn test_lapsed_entitlement_drops_immediately() {
+        let entitlements = [
+            entitlement(Tier::Pro, 3_000),
+            entitlement(Tier::Business, 2_000),
+        ];
+
+        let resolved = resolve(&entitlements, &Timestamp::from(2_000));
+        assert_eq!(resolved.tier, Tier::Pro);
+        assert_eq!(resolved.until, Some(Timestamp::from(3_000)));
+
+        assert_eq!(
+            resolve(&entitlements, &Timestamp::from(3_000)),
+            ResolvedTier::default()
+        );
+        assert_eq!(resolve(&[], &Timestamp::from(0)), ResolvedTier::default());
+    }
+
+    #[test]
+    fn test_tier_ordering() {
+        assert!(Tier::Free < Tier::Pro && Tier::Pro < Tier::Business);
+        assert_eq!(Tier::Business.as_ref(), "business");
+        assert_eq!(
+            serde_json::to_value(entitlement(Tier::Pro, 0)).unwrap()["tier"],
+            "pro"
+        );
+    }
+}
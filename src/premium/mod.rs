@@ -0,0 +1,3 @@
+pub mod entitlement;
+
+pub use entitlement::{resolve, Entitlement, EntitlementSource, ResolvedTier, Tier};
@@ -0,0 +1,294 @@
+use std::fmt::{Display, Formatter};
+
+use crate::proto::v1;
+use crate::search::{BotDocument, PackDocument};
+use crate::tags::VisibleTag;
+use crate::types::{JsSafeBigInt, Owners, Timestamp, MAX_ADDITIONAL_OWNERS};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProtoError {
+    /// A message field required by the crate type was not set.
+    Missing(&'static str),
+    /// A field is set but its value cannot be represented by the crate type.
+    Invalid { field: &'static str, reason: String },
+}
+
+impl Display for ProtoError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Missing(field) => write!(f, "missing field {}", field),
+            Self::Invalid { field, reason } => write!(f, "invalid field {}: {}", field, reason),
+        }
+    }
+}
+
+impl std::error::Error for ProtoError {}
+
+impl From<Timestamp> for prost_types::Timestamp {
+    fn from(v: Timestamp) -> Self {
+        Self {
+            seconds: v.0.timestamp(),
+            nanos: v.0.timestamp_subsec_nanos() as i32,
+        }
+    }
+}
+
+impl TryFrom<prost_types::Timestamp> for Timestamp {
+    type Error = ProtoError;
+
+    fn try_from(v: prost_types::Timestamp) -> Result<Self, Self::Error> {
+        u32::try_from(v.nanos)
+            .ok()
+            .and_then(|nanos| chrono::DateTime::from_timestamp(v.seconds, nanos))
+            .map(Timestamp)
+            .ok_or_else(|| ProtoError::Invalid {
+                field: "timestamp",
+                reason: "timestamp is out of range".to_string(),
+            })
+    }
+}
+
+impl From<VisibleTag> for v1::VisibleTag {
+    fn from(v: VisibleTag) -> Self {
+        Self {
+            name: v.name,
+            display_name: v.display_name,
+            category: v.category,
+            emoji: v.emoji,
+        }
+    }
+}
+
+impl From<v1::VisibleTag> for VisibleTag {
+    fn from(v: v1::VisibleTag) -> Self {
+        Self {
+            name: v.name,
+            display_name: v.display_name,
+            category: v.category,
+            emoji: v.emoji,
+        }
+    }
+}
+
+impl From<Owners> for v1::Owners {
+    fn from(v: Owners) -> Self {
+        Self {
+            primary: v.primary.0,
+            additional: v.additional.iter().map(|id| id.0).collect(),
+        }
+    }
+}
+
+impl TryFrom<v1::Owners> for Owners {
+    type Error = ProtoError;
+
+    fn try_from(v: v1::Owners) -> Result<Self, Self::Error> {
+        let additional: Vec<_> = v.additional.into_iter().map(JsSafeBigInt).collect();
+        let owners = Owners::from_columns(JsSafeBigInt(v.primary), &additional);
+        if owners.additional.len() > MAX_ADDITIONAL_OWNERS {
+            return Err(ProtoError::Invalid {
+                field: "additional",
+                reason: format!("at most {} owners are allowed", MAX_ADDITIONAL_OWNERS),
+            });
+        }
+
+        Ok(owners)
+    }
+}
+
+impl From<BotDocument> for v1::BotDocument {
+    fn from(v: BotDocument) -> Self {
+        Self {
+            id: v.id.0,
+            name: v.name,
+            name_normalised: v.name_normalised,
+            description: v.description,
+            tags: v.tags,
+            votes: v.votes,
+            trending: v.trending,
+            guild_count: v.guild_count,
+            premium: v.premium,
+            premium_boost: v.premium_boost,
+            created_at: v.created_at,
+        }
+    }
+}
+
+impl From<v1::BotDocument> for BotDocument {
+    fn from(v: v1::BotDocument) -> Self {
+        Self {
+            id: JsSafeBigInt(v.id),
+            name: v.name,
+            name_normalised: v.name_normalised,
+            description: v.description,
+            tags: v.tags,
+            votes: v.votes,
+            trending: v.trending,
+            guild_count: v.guild_count,
+            premium: v.premium,
+            premium_boost: v.premium_boost,
+            created_at: v.created_at,
+        }
+    }
+}
+
+impl From<PackDocument> for v1::PackDocument {
+    fn from(v: PackDocument) -> Self {
+        Self {
+            id: v.id.0,
+            name: v.name,
+            name_normalised: v.name_normalised,
+            description: v.description,
+            tags: v.tags,
+            votes: v.votes,
+            trending: v.trending,
+            created_at: v.created_at,
+        }
+    }
+}
+
+impl From<v1::PackDocument> for PackDocument {
+    fn from(v: v1::PackDocument) -> Self {
+        Self {
+            id: JsSafeBigInt(v.id),
+            name: v.name,
+            name_normalised: v.name_normalised,
+            description: v.description,
+            tags: v.tags,
+            votes: v.votes,
+            trending: v.trending,
+            created_at: v.created_at,
+        }
+    }
+}
+
+#[cfg(feature = "events")]
+mod events {
+    use super::*;
+    use crate::events::{BotUpdated, TagReloaded, VoteCast};
+
+    impl From<BotUpdated> for v1::BotUpdated {
+        fn from(v: BotUpdated) -> Self {
+            Self {
+                bot_id: v.bot_id.0,
+                changed: v.changed,
+            }
+        }
+    }
+
+    impl From<v1::BotUpdated> for BotUpdated {
+        fn from(v: v1::BotUpdated) -> Self {
+            Self {
+                bot_id: JsSafeBigInt(v.bot_id),
+                changed: v.changed,
+            }
+        }
+    }
+
+    impl From<VoteCast> for v1::VoteCast {
+        fn from(v: VoteCast) -> Self {
+            Self {
+                bot_id: v.bot_id.0,
+                user_id: v.user_id.0,
+                cast_at: Some(v.cast_at.into()),
+            }
+        }
+    }
+
+    impl TryFrom<v1::VoteCast> for VoteCast {
+        type Error = ProtoError;
+
+        fn try_from(v: v1::VoteCast) -> Result<Self, Self::Error> {
+            Ok(Self {
+                bot_id: JsSafeBigInt(v.bot_id),
+                user_id: JsSafeBigInt(v.user_id),
+                cast_at: v
+                    .cast_at
+                    .ok_or(ProtoError::Missing("cast_at"))?
+                    .try_into()?,
+            })
+        }
+    }
+
+    impl From<TagReloaded> for v1::TagReloaded {
+        fn from(v: TagReloaded) -> Self {
+            Self {
+                registry: v.registry,
+            }
+        }
+    }
+
+    impl From<v1::TagReloaded> for TagReloaded {
+        fn from(v: v1::TagReloaded) -> Self {
+            Self {
+                registry: v.registry,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use prost::Message;
+
+    use super::*;
+
+    #[test]
+    fn test_wire_format() {
+        let msg = v1::BotUpdated {
+            bot_id: 1,
+            changed: vec!["name".to_string()],
+        };
+
+        // Field 1 as a varint then field 2 as a length delimited string.
+        assert_eq!(msg.encode_to_vec(), b"\x08\x01\x12\x04name");
+        assert_eq!(
+            v1::BotUpdated::decode(&b"\x08\x01\x12\x04name"[..]).unwrap(),
+            msg
+        );
+    }
+
+    #[test]
+    fn test_owners() {
+        let owners = Owners::from_columns(JsSafeBigInt(1), &[JsSafeBigInt(2)]);
+        let bytes = v1::Owners::from(owners.clone()).encode_to_vec();
+        let decoded = v1::Owners::decode(bytes.as_slice()).unwrap();
+        assert_eq!(Owners::try_from(decoded).unwrap(), owners);
+
+        let crowded = v1::Owners {
+            primary: 1,
+            additional: (2..10).collect(),
+        };
+        assert!(matches!(
+            Owners::try_from(crowded),
+            Err(ProtoError::Invalid {
+                field: "additional",
+                ..
+            })
+        ));
+    }
+
+    #[cfg(feature = "events")]
+    #[test]
+    fn test_vote_cast() {
+        use crate::events::VoteCast;
+
+        let vote = VoteCast {
+            bot_id: JsSafeBigInt(1),
+            user_id: JsSafeBigInt(2),
+            cast_at: Timestamp::from(1_700_000_000),
+        };
+        let msg = v1::VoteCast::from(vote.clone());
+        assert_eq!(msg.cast_at.as_ref().unwrap().seconds, 1_700_000_000);
+        assert_eq!(VoteCast::try_from(msg.clone()).unwrap(), vote);
+
+        let missing = v1::VoteCast {
+            cast_at: None,
+            ..msg
+        };
+        assert_eq!(
+            VoteCast::try_from(missing),
+            Err(ProtoError::Missing("cast_at"))
+        );
+    }
+}
@@ -0,0 +1,9 @@
+//! Protobuf messages for services which cannot read the bincode payloads,
+//! with conversions to and from the crate types.
+
+#[cfg(feature = "server")]
+mod convert;
+pub mod v1;
+
+#[cfg(feature = "server")]
+pub use convert::ProtoError;
@@ -0,0 +1,252 @@
+//! The `discordlist.v1` protobuf package.
+//!
+//! These are written in the form `prost-build` generates and are checked in
+//! so building the crate does not need `protoc`. They must be kept in line
+//! with `proto/discordlist/v1/discordlist.proto`, the tests compare the field
+//! names, numbers and wire types of both.
+
+use prost::Message;
+use prost_types::Timestamp;
+
+#[derive(Clone, PartialEq, Message)]
+pub struct VisibleTag {
+    #[prost(string, tag = "1")]
+    pub name: String,
+    #[prost(string, tag = "2")]
+    pub display_name: String,
+    #[prost(string, tag = "3")]
+    pub category: String,
+    #[prost(string, optional, tag = "4")]
+    pub emoji: Option<String>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct Owners {
+    #[prost(int64, tag = "1")]
+    pub primary: i64,
+    #[prost(int64, repeated, tag = "2")]
+    pub additional: Vec<i64>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct BotDocument {
+    #[prost(int64, tag = "1")]
+    pub id: i64,
+    #[prost(string, tag = "2")]
+    pub name: String,
+    #[prost(string, tag = "3")]
+    pub name_normalised: String,
+    #[prost(string, tag = "4")]
+    pub description: String,
+    #[prost(string, repeated, tag = "5")]
+    pub tags: Vec<String>,
+    #[prost(int64, tag = "6")]
+    pub votes: i64,
+    #[prost(double, tag = "7")]
+    pub trending: f64,
+    #[prost(int64, tag = "8")]
+    pub guild_count: i64,
+    #[prost(bool, tag = "9")]
+    pub premium: bool,
+    #[prost(double, tag = "10")]
+    pub premium_boost: f64,
+    /// Seconds since the unix epoch.
+    #[prost(int64, tag = "11")]
+    pub created_at: i64,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct PackDocument {
+    #[prost(int64, tag = "1")]
+    pub id: i64,
+    #[prost(string, tag = "2")]
+    pub name: String,
+    #[prost(string, tag = "3")]
+    pub name_normalised: String,
+    #[prost(string, tag = "4")]
+    pub description: String,
+    #[prost(string, repeated, tag = "5")]
+    pub tags: Vec<String>,
+    #[prost(int64, tag = "6")]
+    pub votes: i64,
+    #[prost(double, tag = "7")]
+    pub trending: f64,
+    /// Seconds since the unix epoch.
+    #[prost(int64, tag = "8")]
+    pub created_at: i64,
+}
+
+/// Published on the `bot_updated` topic.
+#[derive(Clone, PartialEq, Message)]
+pub struct BotUpdated {
+    #[prost(int64, tag = "1")]
+    pub bot_id: i64,
+    #[prost(string, repeated, tag = "2")]
+    pub changed: Vec<String>,
+}
+
+/// Published on the `vote_cast` topic.
+#[derive(Clone, PartialEq, Message)]
+pub struct VoteCast {
+    #[prost(int64, tag = "1")]
+    pub bot_id: i64,
+    #[prost(int64, tag = "2")]
+    pub user_id: i64,
+    #[prost(message, optional, tag = "3")]
+    pub cast_at: Option<Timestamp>,
+}
+
+/// Published on the `tag_reloaded` topic.
+#[derive(Clone, PartialEq, Message)]
+pub struct TagReloaded {
+    #[prost(string, tag = "1")]
+    pub registry: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROTO: &str = include_str!("../../proto/discordlist/v1/discordlist.proto");
+
+    type Field = (String, u64, u64);
+
+    fn wire_type(ty: &str, repeated: bool) -> u64 {
+        match ty {
+            // Repeated scalars are packed.
+            _ if repeated => 2,
+            "int64" | "bool" => 0,
+            "double" => 1,
+            _ => 2,
+        }
+    }
+
+    /// The name, number and wire type of every field in the `.proto` file,
+    /// only the syntax the file uses is understood.
+    fn proto_messages() -> Vec<(String, Vec<Field>)> {
+        let mut messages = Vec::new();
+        for line in PROTO.lines().map(str::trim) {
+            if let Some(name) = line.strip_prefix("message ") {
+                messages.push((name.trim_end_matches(" {").to_string(), Vec::new()));
+                continue;
+            }
+
+            let Some((decl, number)) = line.strip_suffix(';').and_then(|v| v.split_once(" = "))
+            else {
+                continue;
+            };
+            let Some((_, fields)) = messages.last_mut() else {
+                continue;
+            };
+
+            let parts = decl.split_whitespace().collect::<Vec<_>>();
+            let (repeated, ty, name) = match parts[..] {
+                ["repeated", ty, name] => (true, ty, name),
+                ["optional", ty, name] | [ty, name] => (false, ty, name),
+                _ => panic!("unsupported field {:?}", line),
+            };
+            fields.push((
+                name.to_string(),
+                number.parse().unwrap(),
+                wire_type(ty, repeated),
+            ));
+        }
+
+        messages
+    }
+
+    fn read_varint(buf: &mut &[u8]) -> u64 {
+        prost::encoding::decode_varint(buf).unwrap()
+    }
+
+    /// The name, number and wire type of every field in a message which has
+    /// all of its fields set.
+    fn rust_message(msg: &impl Message) -> (String, Vec<Field>) {
+        let debug = format!("{:#?}", msg);
+        let name = debug.split_whitespace().next().unwrap().to_string();
+        let names = debug
+            .lines()
+            .filter_map(|v| v.strip_prefix("    "))
+            .filter(|v| !v.starts_with(' '))
+            .filter_map(|v| v.split_once(':').map(|(name, _)| name.to_string()));
+
+        let encoded = msg.encode_to_vec();
+        let mut buf = &encoded[..];
+        let mut keys = Vec::new();
+        while !buf.is_empty() {
+            let key = read_varint(&mut buf);
+            let len = match key & 7 {
+                0 => {
+                    read_varint(&mut buf);
+                    0
+                }
+                1 => 8,
+                2 => read_varint(&mut buf) as usize,
+                other => panic!("unexpected wire type {}", other),
+            };
+            buf = &buf[len..];
+            keys.push((key >> 3, key & 7));
+        }
+
+        let names = names.collect::<Vec<_>>();
+        assert_eq!(names.len(), keys.len(), "{} has unset fields", name);
+        let fields = names
+            .into_iter()
+            .zip(keys)
+            .map(|(name, (number, wire_type))| (name, number, wire_type))
+            .collect();
+        (name, fields)
+    }
+
+    #[test]
+    fn test_messages_match_proto_file() {
+        let s = || "a".to_string();
+        let messages = vec![
+            rust_message(&VisibleTag {
+                name: s(),
+                display_name: s(),
+                category: s(),
+                emoji: Some(s()),
+            }),
+            rust_message(&Owners {
+                primary: 1,
+                additional: vec![2],
+            }),
+            rust_message(&BotDocument {
+                id: 1,
+                name: s(),
+                name_normalised: s(),
+                description: s(),
+                tags: vec![s()],
+                votes: 1,
+                trending: 1.0,
+                guild_count: 1,
+                premium: true,
+                premium_boost: 1.0,
+                created_at: 1,
+            }),
+            rust_message(&PackDocument {
+                id: 1,
+                name: s(),
+                name_normalised: s(),
+                description: s(),
+                tags: vec![s()],
+                votes: 1,
+                trending: 1.0,
+                created_at: 1,
+            }),
+            rust_message(&BotUpdated {
+                bot_id: 1,
+                changed: vec![s()],
+            }),
+            rust_message(&VoteCast {
+                bot_id: 1,
+                user_id: 1,
+                cast_at: Some(Timestamp::default()),
+            }),
+            rust_message(&TagReloaded { registry: s() }),
+        ];
+
+        assert_eq!(messages, proto_messages());
+    }
+}
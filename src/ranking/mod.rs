@@ -0,0 +1,5 @@
+mod trending;
+mod wilson;
+
+pub use trending::{decay, effective_age, score, VIEW_WEIGHT, VOTE_WEIGHT};
+pub use wilson::{rating_lower_bound, wilson_lower_bound, Z_95};
@@ -0,0 +1,63 @@
+use std::time::Duration;
+
+pub const VOTE_WEIGHT: f64 = 1.0;
+pub const VIEW_WEIGHT: f64 = 0.05;
+
+/// The multiplier applied to a score of the given age, halving every `half_life`.
+///
+/// A zero half life means nothing decays.
+pub fn decay(age: Duration, half_life: Duration) -> f64 {
+    if half_life.is_zero() {
+        return 1.0;
+    }
+
+    0.5f64.powf(age.as_secs_f64() / half_life.as_secs_f64())
+}
+
+/// The age used when ranking an entity, bumping an entity resets its age.
+pub fn effective_age(age: Duration, since_last_bump: Option<Duration>) -> Duration {
+    match since_last_bump {
+        Some(bumped) => age.min(bumped),
+        None => age,
+    }
+}
+
+/// The time-decayed trending score of an entity.
+///
+/// Views are weighted far lower than votes and both are log scaled so a
+/// handful of very large entities do not drown out everything else. Bumps
+/// are not weighted, they count by resetting the age, see [effective_age].
+pub fn score(votes: u64, views: u64, age: Duration, half_life: Duration) -> f64 {
+    let engagement = VOTE_WEIGHT * (votes as f64).ln_1p() + VIEW_WEIGHT * (views as f64).ln_1p();
+    engagement * decay(age, half_life)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR: Duration = Duration::from_secs(60 * 60);
+
+    #[test]
+    fn test_decay_halves() {
+        assert_eq!(decay(Duration::ZERO, HOUR), 1.0);
+        assert!((decay(HOUR, HOUR) - 0.5).abs() < 1e-9);
+        assert!((decay(HOUR * 2, HOUR) - 0.25).abs() < 1e-9);
+        assert_eq!(decay(HOUR, Duration::ZERO), 1.0);
+    }
+
+    #[test]
+    fn test_score_ordering() {
+        assert_eq!(score(0, 0, Duration::ZERO, HOUR), 0.0);
+        assert!(score(100, 0, Duration::ZERO, HOUR) > score(10, 0, Duration::ZERO, HOUR));
+        assert!(score(10, 0, Duration::ZERO, HOUR) > score(0, 100, Duration::ZERO, HOUR));
+        assert!(score(100, 0, HOUR, HOUR) < score(100, 0, Duration::ZERO, HOUR));
+    }
+
+    #[test]
+    fn test_bump_resets_age() {
+        assert_eq!(effective_age(HOUR * 5, Some(HOUR)), HOUR);
+        assert_eq!(effective_age(HOUR, Some(HOUR * 5)), HOUR);
+        assert_eq!(effective_age(HOUR * 5, None), HOUR * 5);
+    }
+}
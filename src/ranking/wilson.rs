@@ -0,0 +1,61 @@
+/// The z-score for a 95% confidence interval.
+pub const Z_95: f64 = 1.96;
+
+/// The lower bound of the Wilson score interval for the given number of
+/// positive ratings out of the total.
+///
+/// This ranks entities with few ratings below entities with many ratings of
+/// the same average, returning `0.0` when there are no ratings.
+pub fn wilson_lower_bound(positive: f64, total: u64, z: f64) -> f64 {
+    if total == 0 {
+        return 0.0;
+    }
+
+    let n = total as f64;
+    let p = (positive / n).clamp(0.0, 1.0);
+    let z2 = z * z;
+
+    let centre = p + z2 / (2.0 * n);
+    let margin = z * ((p * (1.0 - p) + z2 / (4.0 * n)) / n).sqrt();
+
+    ((centre - margin) / (1.0 + z2 / n)).max(0.0)
+}
+
+/// The Wilson lower bound for star ratings between `1` and `max_rating`.
+///
+/// Each rating counts as a partial positive, e.g. a 4 out of 5 is 0.75 positive.
+pub fn rating_lower_bound(ratings: &[u8], max_rating: u8) -> f64 {
+    if max_rating <= 1 {
+        return 0.0;
+    }
+
+    let positive: f64 = ratings
+        .iter()
+        .map(|v| (v.clamp(&1, &max_rating) - 1) as f64 / (max_rating - 1) as f64)
+        .sum();
+
+    wilson_lower_bound(positive, ratings.len() as u64, Z_95)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wilson_bounds() {
+        assert_eq!(wilson_lower_bound(0.0, 0, Z_95), 0.0);
+        assert!((wilson_lower_bound(1.0, 1, Z_95) - 0.2065).abs() < 1e-3);
+        assert!((wilson_lower_bound(90.0, 100, Z_95) - 0.8256).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_more_ratings_rank_higher() {
+        let few = rating_lower_bound(&[5, 5], 5);
+        let many = rating_lower_bound(&[5; 50], 5);
+        let mixed = rating_lower_bound(&[5, 1, 5, 1, 5, 1], 5);
+
+        assert!(many > few);
+        assert!(few > mixed);
+        assert_eq!(rating_lower_bound(&[1, 1, 1], 5), 0.0);
+    }
+}
@@ -0,0 +1,201 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// The number of requests allowed within a period.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Quota {
+    pub burst: u32,
+    pub period: Duration,
+}
+
+impl Quota {
+    pub fn new(burst: u32, period: Duration) -> Self {
+        Self {
+            burst: burst.max(1),
+            period,
+        }
+    }
+
+    pub fn per_second(burst: u32) -> Self {
+        Self::new(burst, Duration::from_secs(1))
+    }
+
+    pub fn per_minute(burst: u32) -> Self {
+        Self::new(burst, Duration::from_secs(60))
+    }
+
+    /// The time it takes for a single request to be replenished.
+    #[inline]
+    pub fn emission_interval(&self) -> Duration {
+        self.period / self.burst
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct RateLimitInfo {
+    pub limit: u32,
+    pub remaining: u32,
+    /// The time until the bucket is completely replenished.
+    pub reset_after: Duration,
+    /// Set when the request was limited, the time until it can be retried.
+    pub retry_after: Option<Duration>,
+}
+
+impl RateLimitInfo {
+    #[inline]
+    pub fn is_limited(&self) -> bool {
+        self.retry_after.is_some()
+    }
+}
+
+/// A GCRA rate limiter keyed by arbitrary strings.
+///
+/// Only the theoretical arrival time is stored per key, so this is cheap to
+/// keep around for a large number of users. Keys which have fully replenished
+/// are dropped at most once per quota period as part of `check`, so the map
+/// only holds keys seen within roughly the last two periods.
+pub struct RateLimiter {
+    quota: Quota,
+    state: Mutex<State>,
+}
+
+struct State {
+    arrivals: HashMap<String, Instant>,
+    last_prune: Option<Instant>,
+}
+
+impl State {
+    fn prune(&mut self, now: Instant) {
+        self.arrivals.retain(|_, tat| *tat > now);
+        self.last_prune = Some(now);
+    }
+}
+
+impl RateLimiter {
+    pub fn new(quota: Quota) -> Self {
+        Self {
+            quota,
+            state: Mutex::new(State {
+                arrivals: HashMap::new(),
+                last_prune: None,
+            }),
+        }
+    }
+
+    #[inline]
+    pub fn quota(&self) -> Quota {
+        self.quota
+    }
+
+    pub fn check(&self, key: &str) -> RateLimitInfo {
+        self.check_at(key, Instant::now())
+    }
+
+    pub fn check_at(&self, key: &str, now: Instant) -> RateLimitInfo {
+        let interval = self.quota.emission_interval();
+        let tolerance = interval * self.quota.burst;
+
+        let mut state = self.state.lock().unwrap();
+        let prune_due = match state.last_prune {
+            Some(last) => now.saturating_duration_since(last) >= self.quota.period,
+            None => true,
+        };
+        if prune_due {
+            state.prune(now);
+        }
+
+        let arrivals = &mut state.arrivals;
+        let tat = arrivals.get(key).copied().unwrap_or(now).max(now);
+        let new_tat = tat + interval;
+        let allow_at = new_tat.checked_sub(tolerance).unwrap_or(now);
+
+        if now < allow_at {
+            return RateLimitInfo {
+                limit: self.quota.burst,
+                remaining: 0,
+                reset_after: tat - now,
+                retry_after: Some(allow_at - now),
+            };
+        }
+
+        arrivals.insert(key.to_string(), new_tat);
+
+        let used = new_tat - now;
+        let remaining =
+            (tolerance.saturating_sub(used).as_nanos() / interval.as_nanos().max(1)) as u32;
+
+        RateLimitInfo {
+            limit: self.quota.burst,
+            remaining,
+            reset_after: used,
+            retry_after: None,
+        }
+    }
+
+    /// Removes all keys which have fully replenished.
+    pub fn prune(&self) {
+        self.state.lock().unwrap().prune(Instant::now());
+    }
+
+    pub fn len(&self) -> usize {
+        self.state.lock().unwrap().arrivals.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_burst_then_limit() {
+        let limiter = RateLimiter::new(Quota::per_second(3));
+        let now = Instant::now();
+
+        let remaining: Vec<u32> = (0..3)
+            .map(|_| limiter.check_at("user", now).remaining)
+            .collect();
+        assert_eq!(remaining, vec![2, 1, 0]);
+
+        let info = limiter.check_at("user", now);
+        assert!(info.is_limited());
+        assert_eq!(info.retry_after, Some(Duration::from_nanos(333_333_333)));
+
+        assert!(!limiter.check_at("other", now).is_limited());
+    }
+
+    #[test]
+    fn test_replenishes() {
+        let limiter = RateLimiter::new(Quota::per_second(2));
+        let now = Instant::now();
+
+        limiter.check_at("user", now);
+        limiter.check_at("user", now);
+        assert!(limiter.check_at("user", now).is_limited());
+
+        let later = now + Duration::from_millis(500);
+        assert!(!limiter.check_at("user", later).is_limited());
+        assert!(limiter.check_at("user", later).is_limited());
+
+        let info = limiter.check_at("user", now + Duration::from_secs(5));
+        assert_eq!(info.remaining, 1);
+    }
+
+    #[test]
+    fn test_expires_idle_keys() {
+        let limiter = RateLimiter::new(Quota::per_second(2));
+        let now = Instant::now();
+
+        for i in 0..100 {
+            limiter.check_at(&i.to_string(), now);
+        }
+        assert_eq!(limiter.len(), 100);
+
+        limiter.check_at("user", now + Duration::from_secs(2));
+        assert_eq!(limiter.len(), 1);
+    }
+}
@@ -0,0 +1,50 @@
+use poem::http::{HeaderMap, HeaderValue};
+
+use crate::errors::ApiError;
+use crate::ratelimit::RateLimitInfo;
+
+pub const RATELIMIT_LIMIT_HEADER: &str = "X-RateLimit-Limit";
+pub const RATELIMIT_REMAINING_HEADER: &str = "X-RateLimit-Remaining";
+/// The number of seconds until the bucket is completely replenished.
+pub const RATELIMIT_RESET_HEADER: &str = "X-RateLimit-Reset";
+
+/// Sets the standard rate limit headers on a response.
+pub fn apply_headers(info: &RateLimitInfo, headers: &mut HeaderMap) {
+    let reset = info.reset_after.as_secs_f64().ceil() as u64;
+
+    headers.insert(RATELIMIT_LIMIT_HEADER, HeaderValue::from(info.limit));
+    headers.insert(
+        RATELIMIT_REMAINING_HEADER,
+        HeaderValue::from(info.remaining),
+    );
+    headers.insert(RATELIMIT_RESET_HEADER, HeaderValue::from(reset));
+}
+
+/// The error returned for a limited request, `None` if the request was allowed.
+pub fn limited_error(info: &RateLimitInfo) -> Option<ApiError> {
+    info.retry_after.map(ApiError::rate_limited)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_headers() {
+        let info = RateLimitInfo {
+            limit: 10,
+            remaining: 0,
+            reset_after: Duration::from_millis(2500),
+            retry_after: Some(Duration::from_millis(200)),
+        };
+
+        let mut headers = HeaderMap::new();
+        apply_headers(&info, &mut headers);
+
+        assert_eq!(headers.get(RATELIMIT_LIMIT_HEADER).unwrap(), "10");
+        assert_eq!(headers.get(RATELIMIT_REMAINING_HEADER).unwrap(), "0");
+        assert_eq!(headers.get(RATELIMIT_RESET_HEADER).unwrap(), "3");
+        assert!(limited_error(&info).is_some());
+    }
+}
@@ -0,0 +1,150 @@
+use std::sync::Arc;
+
+use poem::{Endpoint, IntoResponse, Middleware, PathPattern, Request, Response, Result};
+
+use crate::ratelimit::{apply_headers, limited_error, Quota, RateLimiter};
+
+type KeyFn = Arc<dyn Fn(&Request) -> String + Send + Sync>;
+
+/// Applies a rate limit per route and user.
+///
+/// Routes are keyed by their matched pattern, e.g. `/bots/:id`, so the
+/// middleware should be applied to the endpoints inside a `Route`. When it
+/// wraps the whole router the pattern isn't known yet and every route shares
+/// one bucket per user.
+///
+/// By default users are identified by their remote IP, use `with_user_key`
+/// to key by an authenticated user instead.
+#[derive(Clone)]
+pub struct RateLimit {
+    limiter: Arc<RateLimiter>,
+    user_key: KeyFn,
+}
+
+impl RateLimit {
+    pub fn new(quota: Quota) -> Self {
+        Self {
+            limiter: Arc::new(RateLimiter::new(quota)),
+            user_key: Arc::new(client_ip),
+        }
+    }
+
+    pub fn with_user_key(
+        mut self,
+        user_key: impl Fn(&Request) -> String + Send + Sync + 'static,
+    ) -> Self {
+        self.user_key = Arc::new(user_key);
+        self
+    }
+
+    #[inline]
+    pub fn limiter(&self) -> &Arc<RateLimiter> {
+        &self.limiter
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for RateLimit {
+    type Output = RateLimitEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        RateLimitEndpoint {
+            inner: ep,
+            config: self.clone(),
+        }
+    }
+}
+
+pub struct RateLimitEndpoint<E> {
+    inner: E,
+    config: RateLimit,
+}
+
+#[poem::async_trait]
+impl<E: Endpoint> Endpoint for RateLimitEndpoint<E> {
+    type Output = Response;
+
+    async fn call(&self, req: Request) -> Result<Self::Output> {
+        let route = req
+            .data::<PathPattern>()
+            .map(|v| v.0.as_ref())
+            .unwrap_or("*");
+        let key = format!(
+            "{}:{}:{}",
+            req.method(),
+            route,
+            (self.config.user_key)(&req)
+        );
+        let info = self.config.limiter.check(&key);
+
+        let mut resp = match limited_error(&info) {
+            Some(err) => err.into_response(),
+            None => self.inner.call(req).await?.into_response(),
+        };
+
+        apply_headers(&info, resp.headers_mut());
+        Ok(resp)
+    }
+}
+
+/// The remote IP without its port, a client gets a new port for every
+/// connection.
+fn client_ip(req: &Request) -> String {
+    match req.remote_addr().as_socket_addr() {
+        Some(addr) => addr.ip().to_string(),
+        None => req.remote_addr().to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use poem::http::StatusCode;
+    use poem::{handler, EndpointExt, Route};
+
+    #[handler]
+    fn index() -> &'static str {
+        "hello"
+    }
+
+    #[test]
+    fn test_middleware_limits() {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .unwrap();
+
+        rt.block_on(async {
+            let app =
+                index.with(RateLimit::new(Quota::per_minute(1)).with_user_key(|_| "user".into()));
+
+            let resp = app.call(Request::default()).await.unwrap();
+            assert_eq!(resp.status(), StatusCode::OK);
+            assert_eq!(resp.headers().get("X-RateLimit-Remaining").unwrap(), "0");
+
+            let resp = app.call(Request::default()).await.unwrap();
+            assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+            assert_eq!(resp.headers().get("Retry-After").unwrap(), "60");
+        });
+    }
+
+    #[test]
+    fn test_keys_by_route_pattern() {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .unwrap();
+
+        rt.block_on(async {
+            let limit = RateLimit::new(Quota::per_minute(1));
+            let app = Route::new().at("/bots/:id", index.with(limit.clone()));
+
+            let req = |path: &str| Request::builder().uri_str(path).finish();
+            let resp = app.call(req("/bots/1")).await.unwrap();
+            assert_eq!(resp.status(), StatusCode::OK);
+
+            let resp = app.call(req("/bots/2")).await.unwrap();
+            assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+            assert_eq!(limit.limiter().len(), 1);
+        });
+    }
+}
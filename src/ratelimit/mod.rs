@@ -0,0 +1,12 @@
+mod gcra;
+mod headers;
+#[cfg(feature = "ratelimit-middleware")]
+mod middleware;
+
+pub use gcra::{Quota, RateLimitInfo, RateLimiter};
+pub use headers::{
+    apply_headers, limited_error, RATELIMIT_LIMIT_HEADER, RATELIMIT_REMAINING_HEADER,
+    RATELIMIT_RESET_HEADER,
+};
+#[cfg(feature = "ratelimit-middleware")]
+pub use middleware::{RateLimit, RateLimitEndpoint};
@@ -0,0 +1,87 @@
+use std::fmt::{Display, Formatter};
+
+use crate::realtime::{Frame, PROTOCOL_VERSION};
+
+#[derive(Debug)]
+pub enum CodecError {
+    UnsupportedVersion(u8),
+    Malformed(serde_json::Error),
+}
+
+impl Display for CodecError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnsupportedVersion(v) => write!(f, "unsupported gateway protocol version {}", v),
+            Self::Malformed(e) => write!(f, "malformed gateway message: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for CodecError {}
+
+pub fn encode(frame: &Frame) -> String {
+    serde_json::to_string(frame).expect("gateway frames are always serializable")
+}
+
+/// Decodes a text frame, rejecting frames from other protocol versions.
+pub fn decode(data: &str) -> Result<Frame, CodecError> {
+    #[derive(serde::Deserialize)]
+    struct Version {
+        v: u8,
+    }
+
+    let version: Version = serde_json::from_str(data).map_err(CodecError::Malformed)?;
+    if version.v != PROTOCOL_VERSION {
+        return Err(CodecError::UnsupportedVersion(version.v));
+    }
+
+    serde_json::from_str(data).map_err(CodecError::Malformed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::realtime::{GatewayEvent, GatewayMessage};
+    use crate::responses::VoteCountUpdate;
+    use crate::types::JsSafeBigInt;
+
+    #[test]
+    fn test_round_trip() {
+        let frames = [
+            Frame::new(GatewayMessage::Hello {
+                heartbeat_interval_ms: 30_000,
+            }),
+            Frame::new(GatewayMessage::Heartbeat { seq: Some(4) }),
+            Frame::new(GatewayMessage::HeartbeatAck),
+            Frame::new(GatewayMessage::Subscribe {
+                listings: vec![JsSafeBigInt(1)],
+            }),
+            Frame::dispatch(
+                GatewayEvent::VoteCount(VoteCountUpdate {
+                    listing_id: JsSafeBigInt(1),
+                    votes: 12,
+                }),
+                5,
+            ),
+        ];
+
+        for frame in frames {
+            assert_eq!(decode(&encode(&frame)).unwrap(), frame);
+        }
+    }
+
+    #[test]
+    fn test_wire_format() {
+        let encoded = encode(&Frame::new(GatewayMessage::Heartbeat { seq: None }));
+        assert_eq!(encoded, r#"{"v":1,"op":"heartbeat","d":{"seq":null}}"#);
+
+        assert!(matches!(
+            decode(r#"{"v":2,"op":"heartbeat_ack"}"#),
+            Err(CodecError::UnsupportedVersion(2))
+        ));
+        assert!(matches!(
+            decode(r#"{"v":1,"op":"explode"}"#),
+            Err(CodecError::Malformed(_))
+        ));
+    }
+}
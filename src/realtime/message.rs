@@ -0,0 +1,77 @@
+use crate::errors::ErrorCode;
+use crate::responses::VoteCountUpdate;
+use crate::types::JsSafeBigInt;
+
+/// The version of the gateway protocol, bumped on any breaking change.
+pub const PROTOCOL_VERSION: u8 = 1;
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
+pub enum GatewayEvent {
+    VoteCount(VoteCountUpdate),
+    ListingUpdated { listing_id: JsSafeBigInt },
+}
+
+impl GatewayEvent {
+    pub fn listing_id(&self) -> JsSafeBigInt {
+        match self {
+            Self::VoteCount(v) => v.listing_id,
+            Self::ListingUpdated { listing_id } => *listing_id,
+        }
+    }
+}
+
+/// A gateway message, serialized with the op code in `op` and the payload in `d`.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "op", content = "d", rename_all = "snake_case")]
+pub enum GatewayMessage {
+    /// Sent by the server when a connection is opened.
+    Hello {
+        heartbeat_interval_ms: u64,
+    },
+    /// Sent by the client, `seq` is the last sequence number it received.
+    Heartbeat {
+        seq: Option<u64>,
+    },
+    HeartbeatAck,
+    Subscribe {
+        listings: Vec<JsSafeBigInt>,
+    },
+    Unsubscribe {
+        listings: Vec<JsSafeBigInt>,
+    },
+    Dispatch(GatewayEvent),
+    Error {
+        code: ErrorCode,
+        message: String,
+    },
+}
+
+/// The envelope every message is sent in.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Frame {
+    pub v: u8,
+    #[serde(flatten)]
+    pub message: GatewayMessage,
+    /// The sequence number of dispatched events.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub s: Option<u64>,
+}
+
+impl Frame {
+    pub fn new(message: GatewayMessage) -> Self {
+        Self {
+            v: PROTOCOL_VERSION,
+            message,
+            s: None,
+        }
+    }
+
+    pub fn dispatch(event: GatewayEvent, seq: u64) -> Self {
+        Self {
+            v: PROTOCOL_VERSION,
+            message: GatewayMessage::Dispatch(event),
+            s: Some(seq),
+        }
+    }
+}
@@ -0,0 +1,5 @@
+mod codec;
+mod message;
+
+pub use codec::{decode, encode, CodecError};
+pub use message::{Frame, GatewayEvent, GatewayMessage, PROTOCOL_VERSION};
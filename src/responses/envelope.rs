@@ -0,0 +1,144 @@
+use poem_openapi::payload::Json;
+use poem_openapi::types::{ParseFromJSON, ToJSON};
+use poem_openapi::{ApiResponse, Object};
+
+#[derive(Object, Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Pagination {
+    pub page: u32,
+    pub per_page: u32,
+    /// The total number of items if it is known.
+    #[oai(skip_serializing_if_is_none)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total: Option<u64>,
+}
+
+impl Pagination {
+    /// The total number of pages if the total number of items is known.
+    pub fn total_pages(&self) -> Option<u64> {
+        let per_page = self.per_page.max(1) as u64;
+        self.total.map(|v| v.div_ceil(per_page))
+    }
+}
+
+#[derive(Object, Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Meta {
+    #[oai(skip_serializing_if_is_none)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pagination: Option<Pagination>,
+}
+
+/// The `data`/`meta` shape every successful response body is wrapped in.
+#[derive(Object, Clone, Debug, PartialEq)]
+pub struct Envelope<T: ParseFromJSON + ToJSON + Send + Sync> {
+    pub data: T,
+    pub meta: Meta,
+}
+
+#[derive(ApiResponse, Debug)]
+pub enum ApiOk<T: ParseFromJSON + ToJSON + Send + Sync> {
+    #[oai(status = 200)]
+    Ok(Json<Envelope<T>>),
+}
+
+impl<T: ParseFromJSON + ToJSON + Send + Sync> ApiOk<T> {
+    pub fn new(data: T) -> Self {
+        Self::Ok(Json(Envelope {
+            data,
+            meta: Meta::default(),
+        }))
+    }
+
+    pub fn paginated(data: T, pagination: Pagination) -> Self {
+        Self::Ok(Json(Envelope {
+            data,
+            meta: Meta {
+                pagination: Some(pagination),
+            },
+        }))
+    }
+}
+
+#[derive(ApiResponse, Debug)]
+pub enum Created<T: ParseFromJSON + ToJSON + Send + Sync> {
+    #[oai(status = 201)]
+    Created(
+        Json<Envelope<T>>,
+        /// The URL of the created resource.
+        #[oai(header = "Location")]
+        Option<String>,
+    ),
+}
+
+impl<T: ParseFromJSON + ToJSON + Send + Sync> Created<T> {
+    pub fn new(data: T) -> Self {
+        Self::Created(
+            Json(Envelope {
+                data,
+                meta: Meta::default(),
+            }),
+            None,
+        )
+    }
+
+    pub fn with_location(self, location: impl Into<String>) -> Self {
+        let Self::Created(body, _) = self;
+        Self::Created(body, Some(location.into()))
+    }
+}
+
+#[derive(ApiResponse, Debug)]
+pub enum NoContent {
+    #[oai(status = 204)]
+    NoContent,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use poem::http::StatusCode;
+    use poem::IntoResponse;
+
+    fn run<F: std::future::Future>(fut: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(fut)
+    }
+
+    #[test]
+    fn test_paginated_shape() {
+        let pagination = Pagination {
+            page: 2,
+            per_page: 10,
+            total: Some(25),
+        };
+        assert_eq!(pagination.total_pages(), Some(3));
+
+        let resp = ApiOk::paginated(vec![1, 2, 3], pagination).into_response();
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let body: serde_json::Value =
+            serde_json::from_str(&run(resp.into_body().into_string()).unwrap()).unwrap();
+        assert_eq!(
+            body,
+            serde_json::json!({
+                "data": [1, 2, 3],
+                "meta": {"pagination": {"page": 2, "per_page": 10, "total": 25}},
+            })
+        );
+    }
+
+    #[test]
+    fn test_created_and_no_content() {
+        let resp = Created::new("hello".to_string())
+            .with_location("/bots/1")
+            .into_response();
+        assert_eq!(resp.status(), StatusCode::CREATED);
+        assert_eq!(resp.headers().get("Location").unwrap(), "/bots/1");
+
+        assert_eq!(
+            NoContent::NoContent.into_response().status(),
+            StatusCode::NO_CONTENT
+        );
+    }
+}
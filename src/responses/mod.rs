@@ -0,0 +1,5 @@
+mod envelope;
+pub mod sse;
+
+pub use envelope::{ApiOk, Created, Envelope, Meta, NoContent, Pagination};
+pub use sse::{for_listing, subscribe, EventStream, ListingScoped, VoteCountUpdate};
@@ -0,0 +1,91 @@
+use std::time::Duration;
+
+use futures_util::stream::BoxStream;
+use futures_util::StreamExt;
+use poem_openapi::types::{ToJSON, Type};
+use poem_openapi::Object;
+use tokio::sync::broadcast;
+use tokio_stream::wrappers::BroadcastStream;
+
+use crate::types::JsSafeBigInt;
+
+/// How often a comment is sent on idle connections so proxies do not close them.
+pub const DEFAULT_KEEP_ALIVE: Duration = Duration::from_secs(15);
+
+/// A Server-Sent Events response which is documented in the OpenAPI spec.
+pub type EventStream<T> = poem_openapi::payload::EventStream<BoxStream<'static, T>>;
+
+/// An event which relates to a single listing.
+pub trait ListingScoped {
+    fn listing_id(&self) -> JsSafeBigInt;
+}
+
+#[derive(Object, Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct VoteCountUpdate {
+    pub listing_id: JsSafeBigInt,
+    pub votes: i64,
+}
+
+impl ListingScoped for VoteCountUpdate {
+    fn listing_id(&self) -> JsSafeBigInt {
+        self.listing_id
+    }
+}
+
+/// Streams every event sent on the channel which matches the filter.
+///
+/// Slow connections which fall behind the channel skip the events they missed
+/// rather than being disconnected.
+pub fn subscribe<T, F>(rx: broadcast::Receiver<T>, filter: F) -> EventStream<T>
+where
+    T: Type + ToJSON + Clone + Send + 'static,
+    F: Fn(&T) -> bool + Send + 'static,
+{
+    let stream = BroadcastStream::new(rx).filter_map(move |event| {
+        let event = event.ok().filter(|v| filter(v));
+        async move { event }
+    });
+
+    EventStream::new(stream.boxed()).keep_alive(DEFAULT_KEEP_ALIVE)
+}
+
+/// Streams the events sent on the channel for a single listing.
+pub fn for_listing<T>(rx: broadcast::Receiver<T>, listing_id: JsSafeBigInt) -> EventStream<T>
+where
+    T: Type + ToJSON + ListingScoped + Clone + Send + 'static,
+{
+    subscribe(rx, move |event| event.listing_id() == listing_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use poem::IntoResponse;
+
+    #[test]
+    fn test_listing_filter() {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .unwrap();
+
+        rt.block_on(async {
+            let (tx, rx) = broadcast::channel(8);
+            let resp = for_listing(rx, JsSafeBigInt(1)).into_response();
+
+            for (listing_id, votes) in [(1, 10), (2, 3), (1, 11)] {
+                tx.send(VoteCountUpdate {
+                    listing_id: JsSafeBigInt(listing_id),
+                    votes,
+                })
+                .unwrap();
+            }
+            drop(tx);
+
+            let body = resp.into_body().into_string().await.unwrap();
+            assert!(body.contains("\"votes\":10"));
+            assert!(body.contains("\"votes\":11"));
+            assert!(!body.contains("\"votes\":3"));
+        });
+    }
+}
@@ -0,0 +1,124 @@
+use scylla::transport::errors::{DbError as ScyllaDbError, QueryError};
+
+use crate::db::DbError;
+
+/// Whether a failed operation may be attempted again.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum RetryClass {
+    /// The operation was never executed, retrying is always safe.
+    Retry,
+    /// The operation may have been executed, only retry it if it is idempotent.
+    RetryIfIdempotent,
+    Fatal,
+}
+
+impl RetryClass {
+    #[inline]
+    pub fn should_retry(&self, idempotent: bool) -> bool {
+        match self {
+            Self::Retry => true,
+            Self::RetryIfIdempotent => idempotent,
+            Self::Fatal => false,
+        }
+    }
+}
+
+pub trait Retryable {
+    fn retry_class(&self) -> RetryClass;
+}
+
+impl Retryable for QueryError {
+    fn retry_class(&self) -> RetryClass {
+        match self {
+            Self::DbError(e, _) => match e {
+                ScyllaDbError::Unavailable { .. }
+                | ScyllaDbError::Overloaded
+                | ScyllaDbError::IsBootstrapping
+                | ScyllaDbError::RateLimitReached { .. } => RetryClass::Retry,
+                ScyllaDbError::ReadTimeout { .. }
+                | ScyllaDbError::WriteTimeout { .. }
+                | ScyllaDbError::ServerError
+                | ScyllaDbError::TruncateError => RetryClass::RetryIfIdempotent,
+                _ => RetryClass::Fatal,
+            },
+            Self::UnableToAllocStreamId => RetryClass::Retry,
+            Self::IoError(_) | Self::TimeoutError | Self::TooManyOrphanedStreamIds(_) => {
+                RetryClass::RetryIfIdempotent
+            }
+            // The request deadline has passed, there is nobody left to retry for.
+            Self::RequestTimeout(_) => RetryClass::Fatal,
+            Self::BadQuery(_) | Self::ProtocolError(_) | Self::InvalidMessage(_) => {
+                RetryClass::Fatal
+            }
+        }
+    }
+}
+
+impl Retryable for DbError {
+    fn retry_class(&self) -> RetryClass {
+        match self {
+            Self::Query(e) => e.retry_class(),
+            Self::Rows(_) | Self::BatchFull => RetryClass::Fatal,
+        }
+    }
+}
+
+#[cfg(feature = "reqwest")]
+impl Retryable for reqwest::Error {
+    fn retry_class(&self) -> RetryClass {
+        if self.is_connect() {
+            return RetryClass::Retry;
+        }
+
+        if self.is_timeout() || self.is_request() {
+            return RetryClass::RetryIfIdempotent;
+        }
+
+        match self.status() {
+            Some(status) if status.as_u16() == 429 => RetryClass::Retry,
+            Some(status) if status.is_server_error() => RetryClass::RetryIfIdempotent,
+            _ => RetryClass::Fatal,
+        }
+    }
+}
+
+#[cfg(feature = "discord-http")]
+impl Retryable for crate::discord::HttpError {
+    fn retry_class(&self) -> RetryClass {
+        match self {
+            Self::Request(e) => e.retry_class(),
+            Self::Status(status, _) if *status >= 500 => RetryClass::RetryIfIdempotent,
+            // Rate limits are already waited out by the client.
+            Self::Status(..) | Self::RateLimited(_) => RetryClass::Fatal,
+            Self::Decode(_) | Self::DeadlineExceeded => RetryClass::Fatal,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_query_error_classes() {
+        let overloaded = QueryError::DbError(ScyllaDbError::Overloaded, String::new());
+        assert_eq!(overloaded.retry_class(), RetryClass::Retry);
+
+        let io = QueryError::IoError(Arc::new(std::io::ErrorKind::BrokenPipe.into()));
+        assert!(io.retry_class().should_retry(true));
+        assert!(!io.retry_class().should_retry(false));
+
+        let syntax = QueryError::DbError(ScyllaDbError::SyntaxError, String::new());
+        assert_eq!(syntax.retry_class(), RetryClass::Fatal);
+        assert_eq!(
+            QueryError::RequestTimeout("deadline".into()).retry_class(),
+            RetryClass::Fatal
+        );
+
+        assert_eq!(
+            DbError::Rows("bad row".into()).retry_class(),
+            RetryClass::Fatal
+        );
+    }
+}
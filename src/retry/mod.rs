@@ -0,0 +1,5 @@
+mod classify;
+mod policy;
+
+pub use classify::{RetryClass, Retryable};
+pub use policy::{retry, RetryPolicy};
@@ -0,0 +1,178 @@
+use std::future::Future;
+use std::time::Duration;
+
+use rand::Rng;
+
+use crate::middleware::remaining_budget;
+use crate::retry::Retryable;
+
+pub const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+pub const DEFAULT_BASE_DELAY: Duration = Duration::from_millis(50);
+pub const DEFAULT_MAX_DELAY: Duration = Duration::from_secs(2);
+
+/// Exponential backoff with full jitter.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    base_delay: Duration,
+    max_delay: Duration,
+    idempotent: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            base_delay: DEFAULT_BASE_DELAY,
+            max_delay: DEFAULT_MAX_DELAY,
+            idempotent: false,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy for operations which are safe to repeat, such as reads or upserts.
+    pub fn idempotent() -> Self {
+        Self {
+            idempotent: true,
+            ..Self::default()
+        }
+    }
+
+    /// The total number of attempts, including the first one.
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    pub fn with_base_delay(mut self, base_delay: Duration) -> Self {
+        self.base_delay = base_delay;
+        self
+    }
+
+    pub fn with_max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    #[inline]
+    pub fn is_idempotent(&self) -> bool {
+        self.idempotent
+    }
+
+    /// The largest delay before the given retry, starting at `0`.
+    pub fn backoff_ceiling(&self, retry: u32) -> Duration {
+        self.base_delay
+            .saturating_mul(2u32.saturating_pow(retry))
+            .min(self.max_delay)
+    }
+
+    /// A random delay between zero and the backoff ceiling.
+    pub fn delay_for(&self, retry: u32) -> Duration {
+        let ceiling = self.backoff_ceiling(retry);
+        rand::thread_rng().gen_range(Duration::ZERO..=ceiling)
+    }
+}
+
+/// Runs the operation, retrying failures the policy and error allow.
+///
+/// Gives up early rather than sleeping past the current request's deadline.
+pub async fn retry<T, E, F, Fut>(policy: &RetryPolicy, mut op: F) -> Result<T, E>
+where
+    E: Retryable,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    let mut attempt = 1;
+    loop {
+        let err = match op().await {
+            Ok(v) => return Ok(v),
+            Err(e) => e,
+        };
+
+        let can_retry =
+            attempt < policy.max_attempts && err.retry_class().should_retry(policy.idempotent);
+        if !can_retry {
+            return Err(err);
+        }
+
+        let delay = policy.delay_for(attempt - 1);
+        if remaining_budget().is_some_and(|budget| budget <= delay) {
+            return Err(err);
+        }
+
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::retry::RetryClass;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    #[derive(Debug, PartialEq)]
+    struct TestError(RetryClass);
+
+    impl Retryable for TestError {
+        fn retry_class(&self) -> RetryClass {
+            self.0
+        }
+    }
+
+    fn run<F: Future>(fut: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .unwrap()
+            .block_on(fut)
+    }
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy::idempotent()
+            .with_base_delay(Duration::from_millis(1))
+            .with_max_delay(Duration::from_millis(4))
+    }
+
+    #[test]
+    fn test_backoff_ceiling() {
+        let policy = policy();
+        assert_eq!(policy.backoff_ceiling(0), Duration::from_millis(1));
+        assert_eq!(policy.backoff_ceiling(2), Duration::from_millis(4));
+        assert_eq!(policy.backoff_ceiling(40), Duration::from_millis(4));
+        assert!(policy.delay_for(3) <= Duration::from_millis(4));
+    }
+
+    #[test]
+    fn test_retries_until_success() {
+        let attempts = AtomicU32::new(0);
+        let res = run(retry(&policy(), || async {
+            match attempts.fetch_add(1, Ordering::Relaxed) {
+                0 | 1 => Err(TestError(RetryClass::RetryIfIdempotent)),
+                n => Ok(n),
+            }
+        }));
+
+        assert_eq!(res, Ok(2));
+    }
+
+    #[test]
+    fn test_gives_up() {
+        let attempts = AtomicU32::new(0);
+        let res: Result<(), _> = run(retry(&policy().with_max_attempts(2), || async {
+            attempts.fetch_add(1, Ordering::Relaxed);
+            Err(TestError(RetryClass::Retry))
+        }));
+        assert_eq!(res, Err(TestError(RetryClass::Retry)));
+        assert_eq!(attempts.load(Ordering::Relaxed), 2);
+
+        let attempts = AtomicU32::new(0);
+        let non_idempotent = RetryPolicy::default().with_base_delay(Duration::from_millis(1));
+        let _: Result<(), _> = run(retry(&non_idempotent, || async {
+            attempts.fetch_add(1, Ordering::Relaxed);
+            Err(TestError(RetryClass::RetryIfIdempotent))
+        }));
+        assert_eq!(attempts.load(Ordering::Relaxed), 1);
+    }
+}
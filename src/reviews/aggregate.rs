@@ -0,0 +1,189 @@
+use std::cmp::Ordering;
+
+use poem_openapi::Object;
+
+use crate::ranking::{wilson_lower_bound, Z_95};
+use crate::reviews::{Rating, MAX_RATING, MIN_RATING};
+
+const BUCKETS: usize = (MAX_RATING - MIN_RATING + 1) as usize;
+
+/// The running totals of an entity's reviews.
+///
+/// This is updated incrementally as reviews are created, edited and deleted
+/// so the reviews never need to be re-read to show the summary.
+#[derive(
+    Object,
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    scylla::FromRow,
+    scylla::ValueList,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+pub struct ReviewAggregate {
+    pub count: i64,
+    pub sum: i64,
+    /// The number of reviews with each rating, starting at `1`.
+    pub distribution: Vec<i64>,
+}
+
+impl Default for ReviewAggregate {
+    fn default() -> Self {
+        Self {
+            count: 0,
+            sum: 0,
+            distribution: vec![0; BUCKETS],
+        }
+    }
+}
+
+impl ReviewAggregate {
+    pub fn from_ratings(ratings: impl IntoIterator<Item = Rating>) -> Self {
+        let mut slf = Self::default();
+        for rating in ratings {
+            slf.add(rating);
+        }
+        slf
+    }
+
+    pub fn add(&mut self, rating: Rating) {
+        self.apply(rating, 1);
+    }
+
+    pub fn remove(&mut self, rating: Rating) {
+        self.apply(rating, -1);
+    }
+
+    /// Replaces a rating when a review is edited.
+    pub fn update(&mut self, old: Rating, new: Rating) {
+        self.remove(old);
+        self.add(new);
+    }
+
+    /// Combines the totals of two aggregates, e.g. from different shards.
+    pub fn merge(&mut self, other: &Self) {
+        self.ensure_buckets();
+        self.count += other.count;
+        self.sum += other.sum;
+
+        for (bucket, v) in self.distribution.iter_mut().zip(other.distribution.iter()) {
+            *bucket += v;
+        }
+    }
+
+    fn apply(&mut self, rating: Rating, delta: i64) {
+        self.ensure_buckets();
+
+        let bucket = &mut self.distribution[rating.index()];
+        if delta < 0 && *bucket == 0 {
+            return;
+        }
+
+        *bucket += delta;
+        self.count += delta;
+        self.sum += rating.get() as i64 * delta;
+    }
+
+    /// Rows written before a rating was added may have a short distribution.
+    fn ensure_buckets(&mut self) {
+        if self.distribution.len() < BUCKETS {
+            self.distribution.resize(BUCKETS, 0);
+        }
+    }
+
+    pub fn mean(&self) -> Option<f64> {
+        (self.count > 0).then(|| self.sum as f64 / self.count as f64)
+    }
+
+    /// The rating at the given percentile between `0.0` and `1.0`, e.g. `0.5` for the median.
+    pub fn percentile(&self, p: f64) -> Option<Rating> {
+        if self.count <= 0 {
+            return None;
+        }
+
+        let rank = ((p.clamp(0.0, 1.0) * self.count as f64).ceil() as i64).max(1);
+
+        let mut seen = 0;
+        for (i, v) in self.distribution.iter().enumerate() {
+            seen += v;
+            if seen >= rank {
+                return Some(Rating::new(MIN_RATING + i as i32));
+            }
+        }
+
+        None
+    }
+
+    /// The Wilson lower bound of the ratings, used to order entities so a
+    /// handful of perfect reviews do not outrank hundreds of good ones.
+    pub fn wilson_score(&self) -> f64 {
+        let steps = (MAX_RATING - MIN_RATING) as f64;
+        let positive: f64 = self
+            .distribution
+            .iter()
+            .enumerate()
+            .map(|(i, v)| *v as f64 * i as f64 / steps)
+            .sum();
+
+        wilson_lower_bound(positive, self.count.max(0) as u64, Z_95)
+    }
+
+    /// Orders aggregates best first by their Wilson score.
+    pub fn cmp_wilson(&self, other: &Self) -> Ordering {
+        other.wilson_score().total_cmp(&self.wilson_score())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ranking::rating_lower_bound;
+
+    fn ratings(values: &[i32]) -> ReviewAggregate {
+        ReviewAggregate::from_ratings(values.iter().map(|v| Rating::new(*v)))
+    }
+
+    #[test]
+    fn test_incremental_updates() {
+        let mut agg = ratings(&[5, 4, 3]);
+        assert_eq!(agg.mean(), Some(4.0));
+
+        agg.update(Rating::new(3), Rating::new(5));
+        assert_eq!(agg.count, 3);
+        assert_eq!(agg.distribution, vec![0, 0, 0, 1, 2]);
+
+        agg.remove(Rating::new(1));
+        assert_eq!(agg.count, 3, "removing an unseen rating is ignored");
+
+        agg.merge(&ratings(&[1]));
+        assert_eq!(agg.count, 4);
+        assert_eq!(agg.sum, 15);
+        assert_eq!(ReviewAggregate::default().mean(), None);
+    }
+
+    #[test]
+    fn test_percentiles() {
+        let agg = ratings(&[1, 2, 2, 4, 5, 5, 5]);
+
+        assert_eq!(agg.percentile(0.0).unwrap().get(), 1);
+        assert_eq!(agg.percentile(0.5).unwrap().get(), 4);
+        assert_eq!(agg.percentile(1.0).unwrap().get(), 5);
+        assert_eq!(ReviewAggregate::default().percentile(0.5), None);
+    }
+
+    #[test]
+    fn test_wilson_ordering() {
+        let few = ratings(&[5, 5]);
+        let many = ratings(&[5; 40]);
+        let mixed = ratings(&[5, 1, 5, 1]);
+
+        let values: Vec<u8> = vec![5, 1, 5, 1];
+        assert!((mixed.wilson_score() - rating_lower_bound(&values, 5)).abs() < 1e-9);
+
+        let mut all = vec![mixed.clone(), few.clone(), many.clone()];
+        all.sort_by(ReviewAggregate::cmp_wilson);
+        assert_eq!(all, vec![many, few, mixed]);
+    }
+}
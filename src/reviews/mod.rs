@@ -0,0 +1,5 @@
+pub mod aggregate;
+pub mod rating;
+
+pub use aggregate::ReviewAggregate;
+pub use rating::{Rating, Review, ReviewBody, MAX_RATING, MIN_RATING};
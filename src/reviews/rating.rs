@@ -0,0 +1,174 @@
+use std::borrow::Cow;
+use std::fmt::{Display, Formatter};
+use std::ops::Deref;
+use std::str::FromStr;
+
+use poem_openapi::registry::{MetaSchema, MetaSchemaRef};
+use poem_openapi::types::{ParseError, ParseFromJSON, ParseResult, ToJSON, Type};
+use poem_openapi::Object;
+use scylla::cql_to_rust::{FromCqlVal, FromCqlValError};
+use scylla::frame::response::result::CqlValue;
+use scylla::frame::value::ValueTooBig;
+use serde::{Deserializer, Serializer};
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::types::{JsSafeBigInt, JsSafeInt, NormalisingString, Timestamp};
+
+pub const MIN_RATING: i32 = 1;
+pub const MAX_RATING: i32 = 5;
+
+pub type ReviewBody = NormalisingString<20, 2000, true>;
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+/// A star rating between `1` and `5`, values outside of the range are clamped.
+pub struct Rating(JsSafeInt);
+
+impl Rating {
+    pub fn new(v: i32) -> Self {
+        Self(JsSafeInt(v.clamp(MIN_RATING, MAX_RATING)))
+    }
+
+    #[inline]
+    pub fn get(&self) -> i32 {
+        self.0 .0
+    }
+
+    /// The position of the rating in a `[1, 5]` distribution.
+    #[inline]
+    pub(crate) fn index(&self) -> usize {
+        (self.get() - MIN_RATING) as usize
+    }
+}
+
+impl Default for Rating {
+    fn default() -> Self {
+        Self::new(MAX_RATING)
+    }
+}
+
+impl serde::Serialize for Rating {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Rating {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let inner = JsSafeInt::deserialize(deserializer)?;
+        Ok(Self::new(inner.0))
+    }
+}
+
+impl Display for Rating {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Deref for Rating {
+    type Target = JsSafeInt;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Type for Rating {
+    const IS_REQUIRED: bool = <JsSafeInt as Type>::IS_REQUIRED;
+    type RawValueType = <JsSafeInt as Type>::RawValueType;
+    type RawElementValueType = <JsSafeInt as Type>::RawElementValueType;
+
+    fn name() -> Cow<'static, str> {
+        Cow::from("Rating")
+    }
+
+    fn schema_ref() -> MetaSchemaRef {
+        MetaSchemaRef::Inline(Box::new(MetaSchema {
+            minimum: Some(MIN_RATING as f64),
+            maximum: Some(MAX_RATING as f64),
+            ..MetaSchema::new_with_format("integer", "int32")
+        }))
+    }
+
+    fn as_raw_value(&self) -> Option<&Self::RawValueType> {
+        self.0.as_raw_value()
+    }
+
+    fn raw_element_iter<'a>(
+        &'a self,
+    ) -> Box<dyn Iterator<Item = &'a Self::RawElementValueType> + 'a> {
+        self.0.raw_element_iter()
+    }
+}
+
+impl ToJSON for Rating {
+    fn to_json(&self) -> Option<Value> {
+        self.0.to_json()
+    }
+}
+
+impl ParseFromJSON for Rating {
+    fn parse_from_json(value: Option<Value>) -> ParseResult<Self> {
+        let inner = JsSafeInt::parse_from_json(value).map_err(|e| e.propagate())?;
+        Ok(Self::new(inner.0))
+    }
+}
+
+impl FromStr for Rating {
+    type Err = ParseError<Self>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let inner = JsSafeInt::from_str(s)?;
+        Ok(Self::new(inner.0))
+    }
+}
+
+impl FromCqlVal<CqlValue> for Rating {
+    fn from_cql(cql_val: CqlValue) -> Result<Self, FromCqlValError> {
+        JsSafeInt::from_cql(cql_val).map(|v| Self::new(v.0))
+    }
+}
+
+impl scylla::frame::value::Value for Rating {
+    fn serialize(&self, buf: &mut Vec<u8>) -> Result<(), ValueTooBig> {
+        self.0.serialize(buf)
+    }
+}
+
+#[derive(Object, Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct Review {
+    pub id: Uuid,
+    /// The bot or pack the review is for.
+    pub entity_id: JsSafeBigInt,
+    pub author_id: JsSafeBigInt,
+    pub rating: Rating,
+    pub body: ReviewBody,
+    pub created_at: Timestamp,
+    pub edited_at: Option<Timestamp>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_rating_clamped() {
+        assert_eq!(Rating::new(0).get(), 1);
+        assert_eq!(Rating::new(9).get(), 5);
+        assert_eq!(Rating::from_str("3").unwrap().get(), 3);
+        assert_eq!(Rating::parse_from_json(Some(json!(7))).unwrap().get(), 5);
+        assert_eq!(
+            serde_json::from_value::<Rating>(json!("-2")).unwrap().get(),
+            1
+        );
+        assert!(Rating::from_str("five").is_err());
+    }
+}
@@ -0,0 +1,315 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use scylla::frame::value::ValueList;
+use scylla::query::Query;
+use scylla::transport::errors::QueryError;
+use scylla::{QueryResult, Session};
+
+use crate::features::flags::{get_feature_flags, FeatureFlags, FlagSubject};
+use crate::middleware::with_deadline;
+
+/// The migration phase a table is currently in.
+///
+/// Tables move through the phases in order, the old table stays the source
+/// of truth for writes until the table is switched to `NewOnly`.
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    Hash,
+    strum::EnumString,
+    strum::Display,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[strum(serialize_all = "kebab_case", ascii_case_insensitive)]
+#[serde(rename_all = "kebab-case")]
+pub enum DualWriteMode {
+    /// Only the old table is written to and read from.
+    #[default]
+    OldOnly,
+    /// Writes go to both tables, reads are served by the old table.
+    DualWrite,
+    /// Writes go to both tables, reads are served by the new table
+    /// falling back to the old table when no rows are returned.
+    ReadNew,
+    /// Only the new table is written to and read from.
+    NewOnly,
+}
+
+impl DualWriteMode {
+    /// The feature flag which moves the table into this phase, e.g.
+    /// `dual-write.bots.read-new`.
+    pub fn flag_name(&self, table: &str) -> String {
+        format!("dual-write.{}.{}", table, self)
+    }
+
+    /// The phase of the table given the feature flags, the latest phase whose
+    /// flag is enabled wins so a migration is rolled back by disabling the
+    /// flag of its current phase.
+    ///
+    /// The flags are evaluated for no subject, so they are only enabled at 100%.
+    pub fn from_flags(table: &str, flags: &FeatureFlags) -> Self {
+        [Self::NewOnly, Self::ReadNew, Self::DualWrite]
+            .into_iter()
+            .find(|mode| flags.is_enabled(&mode.flag_name(table), &FlagSubject::default()))
+            .unwrap_or_default()
+    }
+
+    #[inline]
+    pub fn writes_old(&self) -> bool {
+        !matches!(self, Self::NewOnly)
+    }
+
+    #[inline]
+    pub fn writes_new(&self) -> bool {
+        !matches!(self, Self::OldOnly)
+    }
+
+    #[inline]
+    pub fn reads_new(&self) -> bool {
+        matches!(self, Self::ReadNew | Self::NewOnly)
+    }
+
+    #[inline]
+    pub fn falls_back_to_old(&self) -> bool {
+        matches!(self, Self::ReadNew)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct DivergenceMetrics {
+    new_write_failures: AtomicU64,
+    fallback_reads: AtomicU64,
+    divergent_reads: AtomicU64,
+    shadow_read_failures: AtomicU64,
+}
+
+impl DivergenceMetrics {
+    /// The number of writes to the new table which failed while the old table write succeeded.
+    pub fn new_write_failures(&self) -> u64 {
+        self.new_write_failures.load(Ordering::Relaxed)
+    }
+
+    /// The number of reads which found no rows in the new table and were served by the old one.
+    pub fn fallback_reads(&self) -> u64 {
+        self.fallback_reads.load(Ordering::Relaxed)
+    }
+
+    /// The number of verified reads where the old and new tables returned different rows.
+    pub fn divergent_reads(&self) -> u64 {
+        self.divergent_reads.load(Ordering::Relaxed)
+    }
+
+    /// The number of verified reads where the new table could not be read.
+    pub fn shadow_read_failures(&self) -> u64 {
+        self.shadow_read_failures.load(Ordering::Relaxed)
+    }
+}
+
+/// Mirrors writes to an old and a new table while a table is being migrated.
+///
+/// The mode of each table is looked up from the feature flags on every call, see
+/// [DualWriteMode::from_flags], so migrations can be progressed without
+/// restarting services. Queries respect
+/// the deadline of the current request.
+///
+/// Failures of the table which is not the source of truth don't fail the call,
+/// they are logged and counted in the [DivergenceMetrics].
+pub struct DualWriter {
+    session: Arc<Session>,
+    table: String,
+    verify_reads: bool,
+    metrics: DivergenceMetrics,
+}
+
+impl DualWriter {
+    pub fn new(session: Arc<Session>, table: impl Into<String>) -> Self {
+        Self {
+            session,
+            table: table.into(),
+            verify_reads: false,
+            metrics: DivergenceMetrics::default(),
+        }
+    }
+
+    /// Reads the new table alongside the old one while in `DualWrite` mode and records
+    /// any differences in the divergence metrics.
+    pub fn with_read_verification(mut self) -> Self {
+        self.verify_reads = true;
+        self
+    }
+
+    #[inline]
+    pub fn table(&self) -> &str {
+        &self.table
+    }
+
+    #[inline]
+    pub fn metrics(&self) -> &DivergenceMetrics {
+        &self.metrics
+    }
+
+    pub fn mode(&self) -> DualWriteMode {
+        DualWriteMode::from_flags(&self.table, &get_feature_flags().load())
+    }
+
+    pub async fn write(
+        &self,
+        old: impl Into<Query>,
+        new: impl Into<Query>,
+        values: impl ValueList,
+    ) -> Result<QueryResult, QueryError> {
+        let mode = self.mode();
+        let values = values.serialized()?;
+
+        if !mode.writes_old() {
+            return self.query(new, &values).await;
+        }
+
+        let result = self.query(old, &values).await?;
+
+        if mode.writes_new() {
+            if let Err(e) = self.query(new, &values).await {
+                tracing::warn!(table = %self.table, error = %e, "failed to write to the new table");
+                self.metrics
+                    .new_write_failures
+                    .fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        Ok(result)
+    }
+
+    pub async fn read(
+        &self,
+        old: impl Into<Query>,
+        new: impl Into<Query>,
+        values: impl ValueList,
+    ) -> Result<QueryResult, QueryError> {
+        let mode = self.mode();
+        let values = values.serialized()?;
+
+        if mode.reads_new() {
+            let result = self.query(new, &values).await?;
+
+            if !mode.falls_back_to_old() || has_rows(&result) {
+                return Ok(result);
+            }
+
+            self.metrics.fallback_reads.fetch_add(1, Ordering::Relaxed);
+            return self.query(old, &values).await;
+        }
+
+        let result = self.query(old, &values).await?;
+
+        if self.verify_reads && mode.writes_new() {
+            match self.query(new, &values).await {
+                Ok(shadow) if shadow.rows != result.rows => {
+                    self.metrics.divergent_reads.fetch_add(1, Ordering::Relaxed);
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    tracing::warn!(table = %self.table, error = %e, "failed to read the new table");
+                    self.metrics
+                        .shadow_read_failures
+                        .fetch_add(1, Ordering::Relaxed);
+                }
+            }
+        }
+
+        Ok(result)
+    }
+
+    async fn query(
+        &self,
+        query: impl Into<Query>,
+        values: impl ValueList,
+    ) -> Result<QueryResult, QueryError> {
+        with_deadline(self.session.query(query, values)).await?
+    }
+}
+
+#[inline]
+fn has_rows(result: &QueryResult) -> bool {
+    result
+        .rows
+        .as_ref()
+        .map(|rows| !rows.is_empty())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_mode_phases() {
+        assert!(DualWriteMode::OldOnly.writes_old());
+        assert!(!DualWriteMode::OldOnly.writes_new());
+        assert!(!DualWriteMode::OldOnly.reads_new());
+
+        assert!(DualWriteMode::DualWrite.writes_old());
+        assert!(DualWriteMode::DualWrite.writes_new());
+        assert!(!DualWriteMode::DualWrite.reads_new());
+
+        assert!(DualWriteMode::ReadNew.writes_old());
+        assert!(DualWriteMode::ReadNew.reads_new());
+        assert!(DualWriteMode::ReadNew.falls_back_to_old());
+
+        assert!(!DualWriteMode::NewOnly.writes_old());
+        assert!(DualWriteMode::NewOnly.reads_new());
+        assert!(!DualWriteMode::NewOnly.falls_back_to_old());
+    }
+
+    #[test]
+    fn test_mode_parsing() {
+        assert_eq!(
+            DualWriteMode::from_str("dual-write").unwrap(),
+            DualWriteMode::DualWrite
+        );
+        assert_eq!(
+            DualWriteMode::from_str("Read-New").unwrap(),
+            DualWriteMode::ReadNew
+        );
+        assert!(DualWriteMode::from_str("sideways").is_err());
+    }
+
+    #[test]
+    fn test_mode_from_flags() {
+        let flags: FeatureFlags = serde_json::from_value(serde_json::json!({
+            "dual-write.bots.dual-write": {"percentage": 100},
+            "dual-write.bots.read-new": {"percentage": 100},
+            "dual-write.packs.dual-write": {"percentage": 100},
+            "dual-write.packs.new-only": {"killed": true, "percentage": 100},
+            "dual-write.guilds.dual-write": {"percentage": 50},
+        }))
+        .unwrap();
+
+        assert_eq!(
+            DualWriteMode::ReadNew.flag_name("bots"),
+            "dual-write.bots.read-new"
+        );
+        assert_eq!(
+            DualWriteMode::from_flags("bots", &flags),
+            DualWriteMode::ReadNew
+        );
+        assert_eq!(
+            DualWriteMode::from_flags("packs", &flags),
+            DualWriteMode::DualWrite
+        );
+        assert_eq!(
+            DualWriteMode::from_flags("guilds", &flags),
+            DualWriteMode::OldOnly
+        );
+        assert_eq!(
+            DualWriteMode::from_flags("users", &flags),
+            DualWriteMode::OldOnly
+        );
+    }
+}
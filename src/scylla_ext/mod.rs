@@ -0,0 +1,3 @@
+pub mod dualwrite;
+
+pub use dualwrite::{DualWriteMode, DualWriter};
@@ -0,0 +1,157 @@
+use std::collections::BTreeSet;
+
+use crate::search::documents::normalise_name;
+use crate::types::NormalisingString;
+
+/// Prefixes shorter than this match too much to be worth indexing.
+pub const MIN_PREFIX_LEN: usize = 2;
+/// Queries longer than this are matched by the full text index instead.
+pub const MAX_PREFIX_LEN: usize = 20;
+
+/// How much the log of the trending score lifts a match. Kept small so a
+/// close match of an unknown bot still ranks above a weak match of a
+/// trending one.
+pub const TRENDING_WEIGHT: f64 = 0.1;
+
+/// The spellings a name can be typed as, lowercased and deduplicated.
+///
+/// This includes the name as written, its ASCII transliteration and the
+/// transliteration without spaces so `cool bot` also matches `coolbot`.
+pub fn variants<const MIN: usize, const MAX: usize, const REF_REAL: bool>(
+    name: &NormalisingString<MIN, MAX, REF_REAL>,
+) -> Vec<String> {
+    let raw = name
+        .as_raw()
+        .to_lowercase()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    let deburred = normalise_name(name.as_normalized());
+    let joined = deburred.replace(' ', "");
+
+    let mut variants = vec![];
+    for variant in [raw, deburred, joined] {
+        if !variant.is_empty() && !variants.contains(&variant) {
+            variants.push(variant);
+        }
+    }
+
+    variants
+}
+
+/// The prefixes of every variant of the name, and of every word within it,
+/// between [MIN_PREFIX_LEN] and [MAX_PREFIX_LEN] characters long.
+pub fn prefix_ngrams<const MIN: usize, const MAX: usize, const REF_REAL: bool>(
+    name: &NormalisingString<MIN, MAX, REF_REAL>,
+) -> Vec<String> {
+    let mut ngrams = BTreeSet::new();
+
+    for variant in variants(name) {
+        for (start, _) in word_starts(&variant) {
+            let chars = variant[start..].chars().collect::<Vec<_>>();
+            for len in MIN_PREFIX_LEN..=chars.len().min(MAX_PREFIX_LEN) {
+                ngrams.insert(chars[..len].iter().collect::<String>());
+            }
+        }
+    }
+
+    ngrams.into_iter().collect()
+}
+
+fn word_starts(s: &str) -> impl Iterator<Item = (usize, char)> + '_ {
+    s.char_indices()
+        .filter(|(i, _)| *i == 0 || s[..*i].ends_with(' '))
+}
+
+/// Normalises a typed query the same way names are indexed.
+pub fn normalise_query(query: &str) -> String {
+    let mut query = normalise_name(query);
+    if let Some((i, _)) = query.char_indices().nth(MAX_PREFIX_LEN) {
+        query.truncate(i);
+    }
+
+    query
+}
+
+/// How well `query` matches the start of `name` between `0.0` and `1.0`.
+fn match_quality(query: &str, name: &str) -> f64 {
+    if query.is_empty() {
+        return 0.0;
+    }
+
+    let coverage = query.chars().count() as f64 / name.chars().count().max(1) as f64;
+
+    if query == name {
+        1.0
+    } else if name.starts_with(query) {
+        0.6 + 0.3 * coverage
+    } else if word_starts(name).any(|(i, _)| name[i..].starts_with(query)) {
+        0.3 + 0.3 * coverage
+    } else if name.replace(' ', "").starts_with(query) {
+        0.3 * coverage
+    } else {
+        0.0
+    }
+}
+
+/// Ranks a typeahead suggestion by how well the query matches the name,
+/// lifted by the entity's trending score. Names which do not match score
+/// `0.0`.
+pub fn score<const MIN: usize, const MAX: usize, const REF_REAL: bool>(
+    query: &str,
+    name: &NormalisingString<MIN, MAX, REF_REAL>,
+    trending: f64,
+) -> f64 {
+    let query = normalise_query(query);
+    let quality = variants(name)
+        .iter()
+        .map(|v| match_quality(&query, v))
+        .fold(0.0, f64::max);
+
+    if quality == 0.0 {
+        return 0.0;
+    }
+
+    quality * (1.0 + TRENDING_WEIGHT * trending.max(0.0).ln_1p())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Name = NormalisingString<1, 32, true>;
+
+    #[test]
+    fn test_variants() {
+        let name = Name::from("Café Bot");
+        assert_eq!(variants(&name), ["café bot", "cafe bot", "cafebot"]);
+
+        let name = Name::from("dyno");
+        assert_eq!(variants(&name), ["dyno"]);
+    }
+
+    #[test]
+    fn test_prefix_ngrams() {
+        let ngrams = prefix_ngrams(&Name::from("Café Bot"));
+
+        for expected in ["ca", "caf", "café", "cafe", "cafeb", "bo", "bot"] {
+            assert!(ngrams.contains(&expected.to_string()), "{}", expected);
+        }
+        assert!(!ngrams.contains(&"c".to_string()));
+        assert!(!ngrams.contains(&"ot".to_string()));
+    }
+
+    #[test]
+    fn test_score_prefers_better_matches() {
+        let music = Name::from("Music Bot");
+        let exact = score("music bot", &music, 0.0);
+        let prefix = score("mus", &music, 0.0);
+        let word = score("bot", &music, 0.0);
+
+        assert_eq!(exact, 1.0);
+        assert!(exact > prefix && prefix > word && word > 0.0);
+        assert_eq!(score("xyz", &music, 100.0), 0.0);
+        assert!(score("mus", &music, 50.0) > prefix);
+        assert!(score("MÚS", &music, 0.0) > 0.0);
+    }
+}
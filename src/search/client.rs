@@ -0,0 +1,183 @@
+use std::fmt::{Display, Formatter};
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::search::{FilterExpr, IndexField, SortBy};
+use crate::types::JsSafeBigInt;
+
+/// The number of documents sent to the backend per request.
+pub const DEFAULT_BULK_SIZE: usize = 1000;
+
+pub const DEFAULT_SEARCH_LIMIT: u32 = 20;
+pub const MAX_SEARCH_LIMIT: u32 = 100;
+
+#[derive(Debug)]
+pub enum SearchError {
+    Encode(serde_json::Error),
+    Decode(serde_json::Error),
+    /// The backend responded with an unexpected status.
+    Status(u16, String),
+    /// The backend could not be reached.
+    Backend(String),
+    /// The backend accepted a write but failed to apply it.
+    Task(String),
+}
+
+impl Display for SearchError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Encode(e) => write!(f, "cannot encode search documents: {}", e),
+            Self::Decode(e) => write!(f, "cannot decode search response: {}", e),
+            Self::Status(status, body) => write!(f, "search returned {}: {}", status, body),
+            Self::Backend(e) => write!(f, "search request failed: {}", e),
+            Self::Task(e) => write!(f, "search task failed: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for SearchError {}
+
+/// The window of results to return.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SearchPage {
+    pub offset: u32,
+    pub limit: u32,
+}
+
+impl Default for SearchPage {
+    fn default() -> Self {
+        Self::new(0, DEFAULT_SEARCH_LIMIT)
+    }
+}
+
+impl SearchPage {
+    /// The limit is clamped between 1 and [MAX_SEARCH_LIMIT].
+    pub fn new(offset: u32, limit: u32) -> Self {
+        Self {
+            offset,
+            limit: limit.clamp(1, MAX_SEARCH_LIMIT),
+        }
+    }
+}
+
+/// The ids of the matching documents in ranked order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SearchResults {
+    pub ids: Vec<JsSafeBigInt>,
+    /// The backend's estimate of the total number of matches.
+    pub total: u64,
+}
+
+/// A client of a search backend shared by the indexer and the API.
+///
+/// Backends only implement the single request operations, bulk operations
+/// are split into batches of [SearchClient::bulk_size].
+#[async_trait::async_trait]
+pub trait SearchClient: Send + Sync {
+    /// Adds or replaces a batch of documents, keyed by their `id`.
+    async fn index_batch(&self, index: &str, documents: Vec<Value>) -> Result<(), SearchError>;
+
+    async fn delete_batch(&self, index: &str, ids: &[JsSafeBigInt]) -> Result<(), SearchError>;
+
+    async fn search<F: IndexField>(
+        &self,
+        index: &str,
+        query: &str,
+        filter: &FilterExpr,
+        sort: Option<SortBy<F>>,
+        page: SearchPage,
+    ) -> Result<SearchResults, SearchError>;
+
+    fn bulk_size(&self) -> usize {
+        DEFAULT_BULK_SIZE
+    }
+
+    async fn index_documents<D: Serialize + Sync>(
+        &self,
+        index: &str,
+        documents: &[D],
+    ) -> Result<(), SearchError> {
+        for chunk in documents.chunks(self.bulk_size().max(1)) {
+            let batch = chunk
+                .iter()
+                .map(serde_json::to_value)
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(SearchError::Encode)?;
+
+            self.index_batch(index, batch).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn delete(&self, index: &str, ids: &[JsSafeBigInt]) -> Result<(), SearchError> {
+        for chunk in ids.chunks(self.bulk_size().max(1)) {
+            self.delete_batch(index, chunk).await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct Recorder {
+        batches: Mutex<Vec<usize>>,
+    }
+
+    #[async_trait::async_trait]
+    impl SearchClient for Recorder {
+        async fn index_batch(&self, _: &str, documents: Vec<Value>) -> Result<(), SearchError> {
+            self.batches.lock().unwrap().push(documents.len());
+            Ok(())
+        }
+
+        async fn delete_batch(&self, _: &str, ids: &[JsSafeBigInt]) -> Result<(), SearchError> {
+            self.batches.lock().unwrap().push(ids.len());
+            Ok(())
+        }
+
+        async fn search<F: IndexField>(
+            &self,
+            _: &str,
+            _: &str,
+            _: &FilterExpr,
+            _: Option<SortBy<F>>,
+            _: SearchPage,
+        ) -> Result<SearchResults, SearchError> {
+            Ok(SearchResults::default())
+        }
+
+        fn bulk_size(&self) -> usize {
+            2
+        }
+    }
+
+    #[test]
+    fn test_bulk_operations_are_batched() {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+
+        rt.block_on(async {
+            let client = Recorder::default();
+            let ids = (0..5).map(JsSafeBigInt).collect::<Vec<_>>();
+
+            client.index_documents("bots", &ids).await.unwrap();
+            client.delete("bots", &ids[..3]).await.unwrap();
+
+            assert_eq!(*client.batches.lock().unwrap(), [2, 2, 1, 2, 1]);
+        });
+    }
+
+    #[test]
+    fn test_page_limits() {
+        assert_eq!(SearchPage::new(0, 0).limit, 1);
+        assert_eq!(SearchPage::new(0, 500).limit, MAX_SEARCH_LIMIT);
+    }
+}
@@ -0,0 +1,225 @@
+use crate::premium::Tier;
+use crate::tags::{BotTags, PackTags};
+use crate::types::{JsSafeBigInt, Timestamp};
+use crate::FieldNamesAsArray;
+
+/// The field names of the indexed documents.
+///
+/// Filters, sorts and facets must refer to fields through these so the
+/// query builder cannot drift from what the indexer writes.
+pub mod fields {
+    pub const ID: &str = "id";
+    pub const NAME: &str = "name";
+    pub const NAME_NORMALISED: &str = "name_normalised";
+    pub const DESCRIPTION: &str = "description";
+    pub const TAGS: &str = "tags";
+    pub const VOTES: &str = "votes";
+    pub const TRENDING: &str = "trending";
+    pub const GUILD_COUNT: &str = "guild_count";
+    pub const PREMIUM: &str = "premium";
+    pub const PREMIUM_BOOST: &str = "premium_boost";
+    pub const CREATED_AT: &str = "created_at";
+}
+
+/// Lowercases the name and transliterates it to ASCII so searches match
+/// names written with fancy unicode letters.
+pub fn normalise_name(name: &str) -> String {
+    deunicode::deunicode(name)
+        .to_lowercase()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The ranking multiplier given to entities of a premium tier.
+pub fn premium_boost(tier: Tier) -> f64 {
+    match tier {
+        Tier::Free => 1.0,
+        Tier::Pro => 1.1,
+        Tier::Business => 1.2,
+    }
+}
+
+/// A bot as it is stored in the search index.
+///
+/// Counts default to zero, set them from the bot's stats before indexing.
+#[derive(Debug, Clone, PartialEq, FieldNamesAsArray, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
+pub struct BotDocument {
+    pub id: JsSafeBigInt,
+    pub name: String,
+    pub name_normalised: String,
+    pub description: String,
+    /// The raw names of the bot's valid tags.
+    pub tags: Vec<String>,
+    pub votes: i64,
+    pub trending: f64,
+    pub guild_count: i64,
+    pub premium: bool,
+    pub premium_boost: f64,
+    /// Seconds since the unix epoch, so every backend can range filter it.
+    pub created_at: i64,
+}
+
+impl BotDocument {
+    pub fn new(
+        id: JsSafeBigInt,
+        name: &str,
+        description: &str,
+        tags: &BotTags,
+        tier: Tier,
+        created_at: &Timestamp,
+    ) -> Self {
+        Self {
+            id,
+            name: name.to_string(),
+            name_normalised: normalise_name(name),
+            description: description.to_string(),
+            tags: tags.as_raw(),
+            votes: 0,
+            trending: 0.0,
+            guild_count: 0,
+            premium: tier > Tier::Free,
+            premium_boost: premium_boost(tier),
+            created_at: created_at.timestamp(),
+        }
+    }
+}
+
+/// A pack as it is stored in the search index.
+#[derive(Debug, Clone, PartialEq, FieldNamesAsArray, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
+pub struct PackDocument {
+    pub id: JsSafeBigInt,
+    pub name: String,
+    pub name_normalised: String,
+    pub description: String,
+    pub tags: Vec<String>,
+    pub votes: i64,
+    pub trending: f64,
+    pub created_at: i64,
+}
+
+impl PackDocument {
+    pub fn new(
+        id: JsSafeBigInt,
+        name: &str,
+        description: &str,
+        tags: &PackTags,
+        created_at: &Timestamp,
+    ) -> Self {
+        Self {
+            id,
+            name: name.to_string(),
+            name_normalised: normalise_name(name),
+            description: description.to_string(),
+            tags: tags.as_raw().into_iter().collect(),
+            votes: 0,
+            trending: 0.0,
+            created_at: created_at.timestamp(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::{BotFacetField, BotSortField, PackFacetField, PackSortField};
+    use strum::IntoEnumIterator;
+
+    #[test]
+    fn test_normalise_name() {
+        assert_eq!(normalise_name("  Ｃｏｏｌ   Bot "), "cool bot");
+        assert_eq!(normalise_name("Música"), "musica");
+    }
+
+    #[test]
+    fn test_field_constants_match_documents() {
+        let bot = BotDocument::FIELD_NAMES_AS_ARRAY;
+        for field in [
+            fields::ID,
+            fields::NAME,
+            fields::NAME_NORMALISED,
+            fields::DESCRIPTION,
+            fields::TAGS,
+            fields::VOTES,
+            fields::TRENDING,
+            fields::GUILD_COUNT,
+            fields::PREMIUM,
+            fields::PREMIUM_BOOST,
+            fields::CREATED_AT,
+        ] {
+            assert!(bot.contains(&field), "bots are missing {}", field);
+        }
+
+        let pack = PackDocument::FIELD_NAMES_AS_ARRAY;
+        for field in [fields::ID, fields::NAME_NORMALISED, fields::TAGS] {
+            assert!(pack.contains(&field), "packs are missing {}", field);
+        }
+    }
+
+    #[test]
+    fn test_sort_and_facet_fields_are_indexed() {
+        let bot = BotDocument::FIELD_NAMES_AS_ARRAY;
+        let pack = PackDocument::FIELD_NAMES_AS_ARRAY;
+
+        for field in BotSortField::iter() {
+            assert!(bot.contains(&field.as_ref()), "{:?}", field);
+        }
+        for field in BotFacetField::iter() {
+            assert!(bot.contains(&field.as_ref()), "{:?}", field);
+        }
+        for field in PackSortField::iter() {
+            assert!(pack.contains(&field.as_ref()), "{:?}", field);
+        }
+        for field in PackFacetField::iter() {
+            assert!(pack.contains(&field.as_ref()), "{:?}", field);
+        }
+    }
+
+    #[test]
+    fn test_premium_boost() {
+        let doc = BotDocument::new(
+            JsSafeBigInt(1),
+            "Bot",
+            "",
+            &BotTags::default(),
+            Tier::Pro,
+            &Timestamp::from(0),
+        );
+
+        assert!(doc.premium);
+        assert_eq!(doc.premium_boost, 1.1);
+        assert_eq!(
+            serde_json::to_value(&doc).unwrap()[fields::NAME_NORMALISED],
+            "bot"
+        );
+    }
+
+    #[cfg(feature = "rkyv")]
+    #[test]
+    fn test_archived_document() {
+        let doc = BotDocument::new(
+            JsSafeBigInt(42),
+            "Cool Bot",
+            "A bot",
+            &BotTags::default(),
+            Tier::Free,
+            &Timestamp::from(0),
+        );
+
+        let bytes = rkyv::to_bytes::<rkyv::rancor::Error>(&doc).unwrap();
+        let archived = rkyv::access::<ArchivedBotDocument, rkyv::rancor::Error>(&bytes).unwrap();
+        assert_eq!(archived.id.0, 42);
+        assert_eq!(archived.name_normalised, "cool bot");
+
+        let decoded = rkyv::deserialize::<BotDocument, rkyv::rancor::Error>(archived).unwrap();
+        assert_eq!(decoded, doc);
+    }
+}
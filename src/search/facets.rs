@@ -0,0 +1,205 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use poem_openapi::registry::{MetaSchema, MetaSchemaRef};
+use poem_openapi::types::{
+    ParseError, ParseFromJSON, ParseFromParameter, ParseResult, ToJSON, Type,
+};
+use poem_openapi::{Enum, Object};
+use serde_json::Value;
+
+use crate::search::IndexField;
+
+#[derive(
+    Enum,
+    Copy,
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    strum::AsRefStr,
+    strum::EnumString,
+    strum::EnumIter,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[oai(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum BotFacetField {
+    Tags,
+    Premium,
+}
+
+#[derive(
+    Enum,
+    Copy,
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    strum::AsRefStr,
+    strum::EnumString,
+    strum::EnumIter,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[oai(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum PackFacetField {
+    Tags,
+}
+
+/// The facets requested for a search, restricted to the fields of a given index.
+///
+/// As a parameter this is a comma separated list e.g. `tags,premium`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Facets<F: IndexField>(pub Vec<F>);
+
+impl<F: IndexField> Default for Facets<F> {
+    fn default() -> Self {
+        Self(vec![])
+    }
+}
+
+impl<F: IndexField> Facets<F> {
+    pub fn fields(&self) -> impl Iterator<Item = &str> {
+        self.0.iter().map(|v| v.as_ref())
+    }
+}
+
+impl<F: IndexField> FromStr for Facets<F> {
+    type Err = ParseError<Self>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut fields = vec![];
+        for name in s.split(',').map(str::trim).filter(|v| !v.is_empty()) {
+            let field = F::from_str(name)
+                .map_err(|_| ParseError::custom(format!("Unknown facet {:?}", name)))?;
+
+            if !fields.iter().any(|v: &F| v.as_ref() == field.as_ref()) {
+                fields.push(field);
+            }
+        }
+
+        Ok(Self(fields))
+    }
+}
+
+impl<F: IndexField> Type for Facets<F> {
+    const IS_REQUIRED: bool = false;
+    type RawValueType = Self;
+    type RawElementValueType = Self;
+
+    fn name() -> Cow<'static, str> {
+        Cow::from("Facets")
+    }
+
+    fn schema_ref() -> MetaSchemaRef {
+        MetaSchemaRef::Inline(Box::new(MetaSchema {
+            description: Some("A comma separated list of facets."),
+            ..MetaSchema::new("string")
+        }))
+    }
+
+    fn as_raw_value(&self) -> Option<&Self::RawValueType> {
+        Some(self)
+    }
+
+    fn raw_element_iter<'a>(
+        &'a self,
+    ) -> Box<dyn Iterator<Item = &'a Self::RawElementValueType> + 'a> {
+        Box::new(std::iter::once(self))
+    }
+
+    fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl<F: IndexField> ToJSON for Facets<F> {
+    fn to_json(&self) -> Option<Value> {
+        Some(Value::String(self.fields().collect::<Vec<_>>().join(",")))
+    }
+}
+
+impl<F: IndexField> ParseFromJSON for Facets<F> {
+    fn parse_from_json(value: Option<Value>) -> ParseResult<Self> {
+        match value {
+            None | Some(Value::Null) => Ok(Self::default()),
+            Some(Value::String(v)) => Self::from_str(&v),
+            Some(other) => Err(ParseError::custom(format!(
+                "Expected comma separated facets got {:?}",
+                other
+            ))),
+        }
+    }
+}
+
+impl<F: IndexField> ParseFromParameter for Facets<F> {
+    fn parse_from_parameter(value: &str) -> ParseResult<Self> {
+        Self::from_str(value)
+    }
+}
+
+#[derive(Object, Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct FacetValue {
+    pub value: String,
+    pub count: u64,
+}
+
+#[derive(Object, Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct FacetCounts {
+    pub field: String,
+    /// The counts of each value, highest first.
+    pub values: Vec<FacetValue>,
+}
+
+impl FacetCounts {
+    /// Builds the counts from the distribution map returned by the search backend.
+    pub fn from_distribution(field: impl Into<String>, distribution: HashMap<String, u64>) -> Self {
+        let mut values: Vec<FacetValue> = distribution
+            .into_iter()
+            .map(|(value, count)| FacetValue { value, count })
+            .collect();
+
+        values.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.value.cmp(&b.value)));
+
+        Self {
+            field: field.into(),
+            values,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_facets() {
+        let facets = Facets::<BotFacetField>::from_str("tags, premium,tags").unwrap();
+        assert_eq!(facets.0, vec![BotFacetField::Tags, BotFacetField::Premium]);
+
+        assert!(Facets::<PackFacetField>::from_str("premium").is_err());
+    }
+
+    #[test]
+    fn test_distribution_ordering() {
+        let counts = FacetCounts::from_distribution(
+            "tags",
+            HashMap::from_iter([
+                ("music".to_string(), 4),
+                ("anime".to_string(), 9),
+                ("fun".to_string(), 4),
+            ]),
+        );
+
+        let values: Vec<&str> = counts.values.iter().map(|v| v.value.as_str()).collect();
+        assert_eq!(values, vec!["anime", "fun", "music"]);
+    }
+}
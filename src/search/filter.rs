@@ -0,0 +1,429 @@
+use std::borrow::Cow;
+use std::fmt::{Display, Formatter};
+use std::ops::{BitAnd, BitOr, Not};
+
+pub trait IntoFilter {
+    fn into_filter(self) -> FilterExpr;
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum FilterBackend {
+    Lnx,
+    Meilisearch,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum FilterOp {
+    Eq,
+    Ne,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FilterValue {
+    Str(String),
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+}
+
+impl From<String> for FilterValue {
+    fn from(v: String) -> Self {
+        Self::Str(v)
+    }
+}
+
+impl From<&str> for FilterValue {
+    fn from(v: &str) -> Self {
+        Self::Str(v.to_string())
+    }
+}
+
+impl From<i64> for FilterValue {
+    fn from(v: i64) -> Self {
+        Self::Int(v)
+    }
+}
+
+impl From<i32> for FilterValue {
+    fn from(v: i32) -> Self {
+        Self::Int(v as i64)
+    }
+}
+
+impl From<f64> for FilterValue {
+    fn from(v: f64) -> Self {
+        Self::Float(v)
+    }
+}
+
+impl From<bool> for FilterValue {
+    fn from(v: bool) -> Self {
+        Self::Bool(v)
+    }
+}
+
+impl Display for FilterValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Str(v) => write!(f, "\"{}\"", escape(v)),
+            Self::Int(v) => write!(f, "{}", v),
+            Self::Float(v) => write!(f, "{}", v),
+            Self::Bool(v) => write!(f, "{}", v),
+        }
+    }
+}
+
+pub type Filter = FilterExpr;
+
+/// A backend agnostic search filter.
+///
+/// Field names are expected to come from code rather than user input, values
+/// are always escaped when rendered.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FilterExpr {
+    Condition {
+        field: Cow<'static, str>,
+        op: FilterOp,
+        value: FilterValue,
+    },
+    And(Vec<FilterExpr>),
+    Or(Vec<FilterExpr>),
+    Not(Box<FilterExpr>),
+}
+
+impl Default for FilterExpr {
+    fn default() -> Self {
+        Self::And(vec![])
+    }
+}
+
+impl FilterExpr {
+    pub fn condition(
+        field: impl Into<Cow<'static, str>>,
+        op: FilterOp,
+        value: impl Into<FilterValue>,
+    ) -> Self {
+        Self::Condition {
+            field: field.into(),
+            op,
+            value: value.into(),
+        }
+    }
+
+    pub fn eq(field: impl Into<Cow<'static, str>>, value: impl Into<FilterValue>) -> Self {
+        Self::condition(field, FilterOp::Eq, value)
+    }
+
+    pub fn ne(field: impl Into<Cow<'static, str>>, value: impl Into<FilterValue>) -> Self {
+        Self::condition(field, FilterOp::Ne, value)
+    }
+
+    pub fn gt(field: impl Into<Cow<'static, str>>, value: impl Into<FilterValue>) -> Self {
+        Self::condition(field, FilterOp::Gt, value)
+    }
+
+    pub fn gte(field: impl Into<Cow<'static, str>>, value: impl Into<FilterValue>) -> Self {
+        Self::condition(field, FilterOp::Gte, value)
+    }
+
+    pub fn lt(field: impl Into<Cow<'static, str>>, value: impl Into<FilterValue>) -> Self {
+        Self::condition(field, FilterOp::Lt, value)
+    }
+
+    pub fn lte(field: impl Into<Cow<'static, str>>, value: impl Into<FilterValue>) -> Self {
+        Self::condition(field, FilterOp::Lte, value)
+    }
+
+    /// Matches when the field is equal to any of the given values, no values
+    /// match nothing.
+    pub fn one_of<V: Into<FilterValue>>(
+        field: impl Into<Cow<'static, str>>,
+        values: impl IntoIterator<Item = V>,
+    ) -> Self {
+        let field = field.into();
+        Self::any(values.into_iter().map(|v| Self::eq(field.clone(), v)))
+    }
+
+    /// Matches when every expression matches, an empty set matches everything.
+    pub fn all(exprs: impl IntoIterator<Item = FilterExpr>) -> Self {
+        Self::And(exprs.into_iter().collect())
+    }
+
+    /// Matches when any expression matches, an empty set matches nothing.
+    pub fn any(exprs: impl IntoIterator<Item = FilterExpr>) -> Self {
+        Self::Or(exprs.into_iter().collect())
+    }
+
+    pub fn and(self, other: FilterExpr) -> Self {
+        match self {
+            Self::And(mut exprs) => {
+                exprs.push(other);
+                Self::And(exprs)
+            }
+            slf => Self::And(vec![slf, other]),
+        }
+    }
+
+    pub fn or(self, other: FilterExpr) -> Self {
+        match self {
+            Self::Or(mut exprs) => {
+                exprs.push(other);
+                Self::Or(exprs)
+            }
+            slf => Self::Or(vec![slf, other]),
+        }
+    }
+
+    /// Returns true if the expression places no restrictions on the results.
+    pub fn is_empty(&self) -> bool {
+        match self {
+            Self::Condition { .. } => false,
+            Self::And(exprs) => exprs.iter().all(|v| v.is_empty()),
+            Self::Or(exprs) => exprs.iter().any(|v| v.is_empty()),
+            Self::Not(expr) => expr.matches_nothing(),
+        }
+    }
+
+    /// Returns true if no document can match the expression, e.g. an empty
+    /// [FilterExpr::any] or the negation of an empty filter.
+    pub fn matches_nothing(&self) -> bool {
+        match self {
+            Self::Condition { .. } => false,
+            Self::And(exprs) => exprs.iter().any(|v| v.matches_nothing()),
+            Self::Or(exprs) => exprs.iter().all(|v| v.matches_nothing()),
+            Self::Not(expr) => expr.is_empty(),
+        }
+    }
+
+    /// The sub expressions which affect the result of an `And` or `Or`.
+    fn operands(&self) -> Vec<&FilterExpr> {
+        match self {
+            Self::And(exprs) => exprs.iter().filter(|v| !v.is_empty()).collect(),
+            Self::Or(exprs) => exprs.iter().filter(|v| !v.matches_nothing()).collect(),
+            _ => vec![],
+        }
+    }
+
+    /// Renders the filter in the query syntax of the given backend.
+    ///
+    /// An empty filter renders as an empty string. Neither syntax can express
+    /// a filter which matches nothing so those also render as an empty
+    /// string, check [FilterExpr::matches_nothing] before querying.
+    pub fn render(&self, backend: FilterBackend) -> String {
+        match backend {
+            FilterBackend::Lnx => self.render_lnx(),
+            FilterBackend::Meilisearch => self.render_meilisearch(),
+        }
+    }
+
+    fn render_meilisearch(&self) -> String {
+        match self {
+            Self::Condition { field, op, value } => {
+                let op = match op {
+                    FilterOp::Eq => "=",
+                    FilterOp::Ne => "!=",
+                    FilterOp::Gt => ">",
+                    FilterOp::Gte => ">=",
+                    FilterOp::Lt => "<",
+                    FilterOp::Lte => "<=",
+                };
+
+                format!("{} {} {}", field, op, value)
+            }
+            Self::And(_) | Self::Or(_) if self.is_empty() || self.matches_nothing() => {
+                String::new()
+            }
+            Self::And(_) => join(self, " AND ", Self::render_meilisearch),
+            Self::Or(_) => join(self, " OR ", Self::render_meilisearch),
+            Self::Not(expr) if expr.is_empty() || expr.matches_nothing() => String::new(),
+            Self::Not(expr) => format!("NOT ({})", expr.render_meilisearch()),
+        }
+    }
+
+    fn render_lnx(&self) -> String {
+        match self {
+            Self::Condition { field, op, value } => match op {
+                FilterOp::Eq => format!("{}:{}", field, value),
+                FilterOp::Ne => format!("(NOT {}:{})", field, value),
+                FilterOp::Gt => format!("{}:{{{} TO *}}", field, value),
+                FilterOp::Gte => format!("{}:[{} TO *]", field, value),
+                FilterOp::Lt => format!("{}:{{* TO {}}}", field, value),
+                FilterOp::Lte => format!("{}:[* TO {}]", field, value),
+            },
+            Self::And(_) | Self::Or(_) if self.is_empty() || self.matches_nothing() => {
+                String::new()
+            }
+            Self::And(_) => join(self, " AND ", Self::render_lnx),
+            Self::Or(_) => join(self, " OR ", Self::render_lnx),
+            Self::Not(expr) if expr.is_empty() || expr.matches_nothing() => String::new(),
+            Self::Not(expr) => format!("(NOT {})", expr.render_lnx()),
+        }
+    }
+}
+
+impl BitAnd for FilterExpr {
+    type Output = FilterExpr;
+
+    fn bitand(self, rhs: Self) -> Self::Output {
+        self.and(rhs)
+    }
+}
+
+impl BitOr for FilterExpr {
+    type Output = FilterExpr;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        self.or(rhs)
+    }
+}
+
+impl Not for FilterExpr {
+    type Output = FilterExpr;
+
+    fn not(self) -> Self::Output {
+        Self::Not(Box::new(self))
+    }
+}
+
+impl IntoFilter for FilterExpr {
+    #[inline]
+    fn into_filter(self) -> FilterExpr {
+        self
+    }
+}
+
+fn join(expr: &FilterExpr, sep: &str, render: fn(&FilterExpr) -> String) -> String {
+    let parts: Vec<String> = expr
+        .operands()
+        .into_iter()
+        .map(|v| match v {
+            FilterExpr::And(_) | FilterExpr::Or(_) if v.operands().len() > 1 => {
+                format!("({})", render(v))
+            }
+            _ => render(v),
+        })
+        .collect();
+
+    parts.join(sep)
+}
+
+fn escape(v: &str) -> String {
+    let mut escaped = String::with_capacity(v.len());
+    for c in v.chars() {
+        if c == '"' || c == '\\' {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> FilterExpr {
+        FilterExpr::condition("tags", FilterOp::Eq, "music")
+            .and(FilterExpr::condition("votes", FilterOp::Gte, 100))
+            .and(
+                FilterExpr::condition("premium", FilterOp::Eq, true).or(FilterExpr::condition(
+                    "nsfw",
+                    FilterOp::Ne,
+                    true,
+                )),
+            )
+    }
+
+    #[test]
+    fn test_meilisearch_render() {
+        assert_eq!(
+            sample().render(FilterBackend::Meilisearch),
+            r#"tags = "music" AND votes >= 100 AND (premium = true OR nsfw != true)"#,
+        );
+    }
+
+    #[test]
+    fn test_lnx_render() {
+        assert_eq!(
+            sample().render(FilterBackend::Lnx),
+            r#"tags:"music" AND votes:[100 TO *] AND (premium:true OR (NOT nsfw:true))"#,
+        );
+    }
+
+    #[test]
+    fn test_value_escaping() {
+        let expr = FilterExpr::condition("name", FilterOp::Eq, r#"a" OR tags = "x\"#);
+        assert_eq!(
+            expr.render(FilterBackend::Meilisearch),
+            r#"name = "a\" OR tags = \"x\\""#,
+        );
+    }
+
+    #[test]
+    fn test_empty_groups_are_skipped() {
+        let expr = FilterExpr::default()
+            .and(FilterExpr::all([]))
+            .and(FilterExpr::condition("votes", FilterOp::Lt, 5));
+
+        assert_eq!(expr.render(FilterBackend::Meilisearch), "votes < 5");
+        assert_eq!(FilterExpr::default().render(FilterBackend::Lnx), "");
+    }
+
+    #[test]
+    fn test_empty_semantics() {
+        assert!(FilterExpr::all([]).is_empty());
+        assert!(FilterExpr::any([]).matches_nothing());
+        assert!(Filter::one_of("tags", Vec::<String>::new()).matches_nothing());
+        assert!((!FilterExpr::default()).matches_nothing());
+        assert!((!FilterExpr::any([])).is_empty());
+
+        let expr = Filter::eq("tags", "music") & Filter::one_of("tags", Vec::<String>::new());
+        assert!(expr.matches_nothing());
+        assert!(!expr.is_empty());
+
+        let expr = FilterExpr::any([]) | Filter::eq("tags", "music");
+        assert!(!expr.matches_nothing());
+        assert_eq!(expr.render(FilterBackend::Meilisearch), r#"tags = "music""#);
+    }
+
+    #[test]
+    fn test_operator_composition() {
+        let expr =
+            Filter::eq("tags", "music") & Filter::gte("votes", 100) | !Filter::eq("nsfw", true);
+
+        assert_eq!(
+            expr.render(FilterBackend::Meilisearch),
+            r#"(tags = "music" AND votes >= 100) OR NOT (nsfw = true)"#,
+        );
+    }
+
+    #[test]
+    fn test_one_of() {
+        let expr = Filter::one_of("tags", ["music", "utility"]) & Filter::lt("votes", 5);
+
+        assert_eq!(
+            expr.render(FilterBackend::Meilisearch),
+            r#"(tags = "music" OR tags = "utility") AND votes < 5"#,
+        );
+        assert_eq!(
+            expr.render(FilterBackend::Lnx),
+            r#"(tags:"music" OR tags:"utility") AND votes:{* TO 5}"#,
+        );
+    }
+
+    #[test]
+    fn test_user_input_cannot_escape_value() {
+        let input = r#"music" OR premium = true OR tags = "x"#;
+        let expr = Filter::eq("tags", input) & Filter::eq("premium", false);
+
+        assert_eq!(
+            expr.render(FilterBackend::Meilisearch),
+            r#"tags = "music\" OR premium = true OR tags = \"x" AND premium = false"#,
+        );
+    }
+}
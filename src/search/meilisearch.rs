@@ -0,0 +1,309 @@
+use std::time::Duration;
+
+use reqwest::{Method, Url};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::middleware::{
+    propagate_deadline, propagate_request_id, with_deadline, DeadlineExceeded,
+};
+use crate::search::{
+    fields, FilterBackend, FilterExpr, IndexField, SearchClient, SearchError, SearchPage,
+    SearchResults, SortBy,
+};
+use crate::types::JsSafeBigInt;
+
+impl From<reqwest::Error> for SearchError {
+    fn from(e: reqwest::Error) -> Self {
+        Self::Backend(e.to_string())
+    }
+}
+
+impl From<DeadlineExceeded> for SearchError {
+    fn from(_: DeadlineExceeded) -> Self {
+        Self::Backend("request deadline exceeded".to_string())
+    }
+}
+
+/// How often a queued write task is polled until it finishes.
+pub const TASK_POLL_INTERVAL: Duration = Duration::from_millis(50);
+/// How long a write waits for its task to finish before failing.
+pub const DEFAULT_TASK_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TaskInfo {
+    task_uid: u64,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum TaskStatus {
+    Enqueued,
+    Processing,
+    Succeeded,
+    Failed,
+    Canceled,
+}
+
+#[derive(Deserialize)]
+struct TaskFailure {
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct Task {
+    status: TaskStatus,
+    #[serde(default)]
+    error: Option<TaskFailure>,
+}
+
+#[derive(Deserialize)]
+struct Hit {
+    id: JsSafeBigInt,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SearchResponse {
+    hits: Vec<Hit>,
+    #[serde(default)]
+    estimated_total_hits: u64,
+}
+
+/// A client of the Meilisearch REST API.
+///
+/// Document writes are queued as tasks by Meilisearch, every write waits
+/// for its task to finish and fails if the task did.
+#[derive(Clone)]
+pub struct MeilisearchClient {
+    client: reqwest::Client,
+    base: String,
+    api_key: Option<String>,
+    bulk_size: usize,
+    task_timeout: Duration,
+}
+
+impl MeilisearchClient {
+    pub fn new(base: impl Into<String>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base: base.into().trim_end_matches('/').to_string(),
+            api_key: None,
+            bulk_size: crate::search::DEFAULT_BULK_SIZE,
+            task_timeout: DEFAULT_TASK_TIMEOUT,
+        }
+    }
+
+    pub fn with_api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = Some(api_key.into());
+        self
+    }
+
+    pub fn with_bulk_size(mut self, bulk_size: usize) -> Self {
+        self.bulk_size = bulk_size.max(1);
+        self
+    }
+
+    pub fn with_task_timeout(mut self, task_timeout: Duration) -> Self {
+        self.task_timeout = task_timeout;
+        self
+    }
+
+    /// The url of the path below the base, each segment is percent encoded.
+    fn url(&self, segments: &[&str]) -> Result<Url, SearchError> {
+        let mut url = Url::parse(&self.base).map_err(|e| SearchError::Backend(e.to_string()))?;
+        url.path_segments_mut()
+            .map_err(|_| SearchError::Backend("invalid base url".to_string()))?
+            .pop_if_empty()
+            .extend(segments);
+        Ok(url)
+    }
+
+    async fn request(
+        &self,
+        method: Method,
+        url: Url,
+        body: Option<&Value>,
+    ) -> Result<String, SearchError> {
+        let mut req = self.client.request(method, url);
+        if let Some(body) = body {
+            req = req.json(body);
+        }
+        if let Some(key) = self.api_key.as_ref() {
+            req = req.bearer_auth(key);
+        }
+
+        let req = propagate_deadline(propagate_request_id(req));
+        let resp = with_deadline(req.send()).await??;
+        let status = resp.status().as_u16();
+        let body = with_deadline(resp.text()).await??;
+
+        if !(200..300).contains(&status) {
+            return Err(SearchError::Status(status, body));
+        }
+
+        Ok(body)
+    }
+
+    /// Sends a write and waits for the task it queued to finish.
+    async fn write(&self, url: Url, body: &Value) -> Result<(), SearchError> {
+        let resp = self.request(Method::POST, url, Some(body)).await?;
+        let info = serde_json::from_str::<TaskInfo>(&resp).map_err(SearchError::Decode)?;
+
+        tokio::time::timeout(self.task_timeout, self.wait_for_task(info.task_uid))
+            .await
+            .map_err(|_| {
+                SearchError::Task(format!("task {} did not finish in time", info.task_uid))
+            })?
+    }
+
+    async fn wait_for_task(&self, uid: u64) -> Result<(), SearchError> {
+        let url = self.url(&["tasks", &uid.to_string()])?;
+
+        loop {
+            let resp = self.request(Method::GET, url.clone(), None).await?;
+            let task = serde_json::from_str::<Task>(&resp).map_err(SearchError::Decode)?;
+
+            match task.status {
+                TaskStatus::Succeeded => return Ok(()),
+                TaskStatus::Enqueued | TaskStatus::Processing => {
+                    tokio::time::sleep(TASK_POLL_INTERVAL).await
+                }
+                TaskStatus::Failed | TaskStatus::Canceled => {
+                    let reason = task
+                        .error
+                        .map(|e| e.message)
+                        .unwrap_or_else(|| "task was canceled".to_string());
+                    return Err(SearchError::Task(reason));
+                }
+            }
+        }
+    }
+}
+
+fn search_body<F: IndexField>(
+    query: &str,
+    filter: &FilterExpr,
+    sort: Option<SortBy<F>>,
+    page: SearchPage,
+) -> Value {
+    let mut body = json!({
+        "q": query,
+        "offset": page.offset,
+        "limit": page.limit,
+        "attributesToRetrieve": [fields::ID],
+    });
+
+    if !filter.is_empty() {
+        body["filter"] = Value::String(filter.render(FilterBackend::Meilisearch));
+    }
+
+    if let Some(sort) = sort {
+        body["sort"] = json!([sort.to_string()]);
+    }
+
+    body
+}
+
+fn parse_results(body: &str) -> Result<SearchResults, SearchError> {
+    let resp = serde_json::from_str::<SearchResponse>(body).map_err(SearchError::Decode)?;
+
+    Ok(SearchResults {
+        ids: resp.hits.into_iter().map(|v| v.id).collect(),
+        total: resp.estimated_total_hits,
+    })
+}
+
+#[async_trait::async_trait]
+impl SearchClient for MeilisearchClient {
+    async fn index_batch(&self, index: &str, documents: Vec<Value>) -> Result<(), SearchError> {
+        let mut url = self.url(&["indexes", index, "documents"])?;
+        url.query_pairs_mut().append_pair("primaryKey", fields::ID);
+        self.write(url, &Value::Array(documents)).await
+    }
+
+    async fn delete_batch(&self, index: &str, ids: &[JsSafeBigInt]) -> Result<(), SearchError> {
+        let url = self.url(&["indexes", index, "documents", "delete-batch"])?;
+        let ids = serde_json::to_value(ids).map_err(SearchError::Encode)?;
+        self.write(url, &ids).await
+    }
+
+    async fn search<F: IndexField>(
+        &self,
+        index: &str,
+        query: &str,
+        filter: &FilterExpr,
+        sort: Option<SortBy<F>>,
+        page: SearchPage,
+    ) -> Result<SearchResults, SearchError> {
+        if filter.matches_nothing() {
+            return Ok(SearchResults::default());
+        }
+
+        let url = self.url(&["indexes", index, "search"])?;
+        let body = search_body(query, filter, sort, page);
+        let body = self.request(Method::POST, url, Some(&body)).await?;
+
+        parse_results(&body)
+    }
+
+    fn bulk_size(&self) -> usize {
+        self.bulk_size
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::BotSortField;
+
+    #[test]
+    fn test_search_body() {
+        let filter = FilterExpr::eq(fields::TAGS, "music");
+        let body = search_body(
+            "cool bot",
+            &filter,
+            Some(SortBy::desc(BotSortField::Votes)),
+            SearchPage::new(40, 20),
+        );
+
+        assert_eq!(body["q"], "cool bot");
+        assert_eq!(body["filter"], "tags = \"music\"");
+        assert_eq!(body["sort"], json!(["votes:desc"]));
+        assert_eq!(body["offset"], 40);
+
+        let body =
+            search_body::<BotSortField>("", &FilterExpr::default(), None, SearchPage::default());
+        assert!(body.get("filter").is_none());
+        assert!(body.get("sort").is_none());
+    }
+
+    #[test]
+    fn test_url_encodes_segments() {
+        let client = MeilisearchClient::new("http://localhost:7700/");
+        let url = client
+            .url(&["indexes", "bots/../keys?x", "search"])
+            .unwrap();
+        assert_eq!(
+            url.as_str(),
+            "http://localhost:7700/indexes/bots%2F..%2Fkeys%3Fx/search"
+        );
+
+        let client = MeilisearchClient::new("http://localhost:7700/meili");
+        let url = client.url(&["tasks", "7"]).unwrap();
+        assert_eq!(url.as_str(), "http://localhost:7700/meili/tasks/7");
+    }
+
+    #[test]
+    fn test_parse_results() {
+        let body = r#"{"hits":[{"id":"123456789012345678"},{"id":42}],"estimatedTotalHits":2}"#;
+        let results = parse_results(body).unwrap();
+
+        assert_eq!(
+            results.ids,
+            [JsSafeBigInt(123456789012345678), JsSafeBigInt(42)]
+        );
+        assert_eq!(results.total, 2);
+    }
+}
@@ -0,0 +1,21 @@
+pub mod autocomplete;
+mod client;
+pub mod documents;
+mod facets;
+pub mod filter;
+#[cfg(feature = "meilisearch")]
+mod meilisearch;
+mod sort;
+
+pub use client::{
+    SearchClient, SearchError, SearchPage, SearchResults, DEFAULT_BULK_SIZE, DEFAULT_SEARCH_LIMIT,
+    MAX_SEARCH_LIMIT,
+};
+pub use documents::{fields, BotDocument, PackDocument};
+#[cfg(feature = "rkyv")]
+pub use documents::{ArchivedBotDocument, ArchivedPackDocument};
+pub use facets::{BotFacetField, FacetCounts, FacetValue, Facets, PackFacetField};
+pub use filter::{Filter, FilterBackend, FilterExpr, FilterOp, FilterValue, IntoFilter};
+#[cfg(feature = "meilisearch")]
+pub use meilisearch::{MeilisearchClient, DEFAULT_TASK_TIMEOUT, TASK_POLL_INTERVAL};
+pub use sort::{BotSortField, IndexField, PackSortField, SortBy, SortDirection};
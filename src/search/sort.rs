@@ -0,0 +1,234 @@
+use std::borrow::Cow;
+use std::fmt::{Debug, Display, Formatter};
+use std::str::FromStr;
+
+use poem_openapi::registry::{MetaSchema, MetaSchemaRef};
+use poem_openapi::types::{
+    ParseError, ParseFromJSON, ParseFromParameter, ParseResult, ToJSON, Type,
+};
+use poem_openapi::Enum;
+use serde_json::Value;
+use strum::IntoEnumIterator;
+
+/// A whitelisted field of a search index which clients may sort or facet by.
+pub trait IndexField:
+    Debug + Copy + AsRef<str> + FromStr + IntoEnumIterator + Send + Sync + 'static
+{
+}
+
+impl<T> IndexField for T where
+    T: Debug + Copy + AsRef<str> + FromStr + IntoEnumIterator + Send + Sync + 'static
+{
+}
+
+#[derive(
+    Enum,
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    Hash,
+    strum::AsRefStr,
+    strum::EnumString,
+    strum::EnumIter,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[oai(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase", ascii_case_insensitive)]
+#[serde(rename_all = "lowercase")]
+pub enum SortDirection {
+    Asc,
+    #[default]
+    Desc,
+}
+
+#[derive(
+    Enum,
+    Copy,
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    strum::AsRefStr,
+    strum::EnumString,
+    strum::EnumIter,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[oai(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum BotSortField {
+    Votes,
+    Trending,
+    GuildCount,
+    CreatedAt,
+}
+
+#[derive(
+    Enum,
+    Copy,
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    strum::AsRefStr,
+    strum::EnumString,
+    strum::EnumIter,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[oai(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum PackSortField {
+    Votes,
+    Trending,
+    CreatedAt,
+}
+
+/// A sort order restricted to the fields of a given index.
+///
+/// This is represented as `field:direction` e.g. `votes:desc`, the direction
+/// can be omitted in which case it defaults to descending.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct SortBy<F: IndexField> {
+    pub field: F,
+    pub direction: SortDirection,
+}
+
+impl<F: IndexField> SortBy<F> {
+    pub fn asc(field: F) -> Self {
+        Self {
+            field,
+            direction: SortDirection::Asc,
+        }
+    }
+
+    pub fn desc(field: F) -> Self {
+        Self {
+            field,
+            direction: SortDirection::Desc,
+        }
+    }
+
+    /// All valid sort orders for the index.
+    pub fn variants() -> impl Iterator<Item = Self> {
+        F::iter()
+            .flat_map(|field| SortDirection::iter().map(move |direction| Self { field, direction }))
+    }
+}
+
+impl<F: IndexField> Display for SortBy<F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.field.as_ref(), self.direction.as_ref())
+    }
+}
+
+impl<F: IndexField> FromStr for SortBy<F> {
+    type Err = ParseError<Self>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (field, direction) = s.split_once(':').unwrap_or((s, "desc"));
+
+        let field = F::from_str(field)
+            .map_err(|_| ParseError::custom(format!("Cannot sort by field {:?}", field)))?;
+        let direction = SortDirection::from_str(direction)
+            .map_err(|_| ParseError::custom(format!("Unknown sort direction {:?}", direction)))?;
+
+        Ok(Self { field, direction })
+    }
+}
+
+impl<F: IndexField> Type for SortBy<F> {
+    const IS_REQUIRED: bool = true;
+    type RawValueType = Self;
+    type RawElementValueType = Self;
+
+    fn name() -> Cow<'static, str> {
+        Cow::from("SortBy")
+    }
+
+    fn schema_ref() -> MetaSchemaRef {
+        MetaSchemaRef::Inline(Box::new(MetaSchema {
+            enum_items: Self::variants()
+                .map(|v| Value::String(v.to_string()))
+                .collect(),
+            ..MetaSchema::new("string")
+        }))
+    }
+
+    fn as_raw_value(&self) -> Option<&Self::RawValueType> {
+        Some(self)
+    }
+
+    fn raw_element_iter<'a>(
+        &'a self,
+    ) -> Box<dyn Iterator<Item = &'a Self::RawElementValueType> + 'a> {
+        Box::new(std::iter::once(self))
+    }
+}
+
+impl<F: IndexField> ToJSON for SortBy<F> {
+    fn to_json(&self) -> Option<Value> {
+        Some(Value::String(self.to_string()))
+    }
+}
+
+impl<F: IndexField> ParseFromJSON for SortBy<F> {
+    fn parse_from_json(value: Option<Value>) -> ParseResult<Self> {
+        let value = value.ok_or_else(|| ParseError::custom("Expected sort order got null"))?;
+
+        match value.as_str() {
+            Some(v) => Self::from_str(v),
+            None => Err(ParseError::custom(format!(
+                "Expected sort order got {:?}",
+                value
+            ))),
+        }
+    }
+}
+
+impl<F: IndexField> ParseFromParameter for SortBy<F> {
+    fn parse_from_parameter(value: &str) -> ParseResult<Self> {
+        Self::from_str(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_sort() {
+        let sort = SortBy::<BotSortField>::from_str("guild_count:asc").unwrap();
+        assert_eq!(sort, SortBy::asc(BotSortField::GuildCount));
+
+        let sort = SortBy::<BotSortField>::from_str("votes").unwrap();
+        assert_eq!(sort, SortBy::desc(BotSortField::Votes));
+    }
+
+    #[test]
+    fn test_non_whitelisted_field_rejected() {
+        assert!(SortBy::<PackSortField>::from_str("guild_count:asc").is_err());
+        assert!(SortBy::<BotSortField>::from_str("votes:sideways").is_err());
+    }
+
+    #[test]
+    fn test_schema_lists_variants() {
+        let schema = match SortBy::<PackSortField>::schema_ref() {
+            MetaSchemaRef::Inline(schema) => schema,
+            MetaSchemaRef::Reference(_) => panic!("Expected inline schema"),
+        };
+
+        assert_eq!(schema.enum_items.len(), 6);
+        assert!(schema
+            .enum_items
+            .contains(&Value::String("created_at:asc".to_string())));
+    }
+}
@@ -0,0 +1,7 @@
+#[cfg(feature = "s3")]
+mod s3;
+mod store;
+
+#[cfg(feature = "s3")]
+pub use s3::{S3Config, S3Store, MAX_PRESIGN_EXPIRY};
+pub use store::{validate_key, ObjectStore, StorageError, StoredObject, MAX_KEY_LENGTH};
@@ -0,0 +1,154 @@
+use std::fmt::{Display, Formatter};
+use std::time::Duration;
+
+use crate::errors::ApiError;
+use crate::media::{ImageFormat, ValidatedImage};
+use crate::types::DiscordUrl;
+
+/// The longest object key accepted, matching the S3 limit.
+pub const MAX_KEY_LENGTH: usize = 1024;
+
+#[derive(Debug)]
+pub enum StorageError {
+    /// The key is empty, too long or contains characters other than ascii
+    /// letters, digits and `/._-`.
+    InvalidKey,
+    /// The data is not one of the supported media types or does not match
+    /// the type it is stored as.
+    UnsupportedType,
+    /// The backend responded with an unexpected status.
+    Status(u16, String),
+    /// The backend could not be reached.
+    Backend(String),
+}
+
+impl Display for StorageError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidKey => write!(f, "invalid object key"),
+            Self::UnsupportedType => write!(f, "unsupported or mismatched content type"),
+            Self::Status(status, body) => write!(f, "object store returned {}: {}", status, body),
+            Self::Backend(e) => write!(f, "object store request failed: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for StorageError {}
+
+impl From<StorageError> for ApiError {
+    fn from(e: StorageError) -> Self {
+        match e {
+            StorageError::UnsupportedType => {
+                Self::validation("image must be a png, jpeg, gif or webp")
+            }
+            _ => Self::internal(),
+        }
+    }
+}
+
+/// The contents of an object and its media type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoredObject {
+    pub format: ImageFormat,
+    pub data: Vec<u8>,
+}
+
+impl StoredObject {
+    /// Checks the data really is of the given format before it is stored,
+    /// the format is served as the object's content type.
+    pub fn new(format: ImageFormat, data: Vec<u8>) -> Result<Self, StorageError> {
+        if ImageFormat::sniff(&data) != Some(format) {
+            return Err(StorageError::UnsupportedType);
+        }
+
+        Ok(Self { format, data })
+    }
+
+    /// Reads an object fetched from the backend, the content type is only
+    /// trusted if the data matches it.
+    pub fn from_content_type(content_type: &str, data: Vec<u8>) -> Result<Self, StorageError> {
+        let format = ImageFormat::from_mime(content_type).ok_or(StorageError::UnsupportedType)?;
+        Self::new(format, data)
+    }
+}
+
+impl From<ValidatedImage> for StoredObject {
+    fn from(image: ValidatedImage) -> Self {
+        Self {
+            format: image.format,
+            data: image.data,
+        }
+    }
+}
+
+/// Checks the key is safe to use as is in a url path.
+pub fn validate_key(key: &str) -> Result<(), StorageError> {
+    let valid = !key.is_empty()
+        && key.len() <= MAX_KEY_LENGTH
+        && !key.starts_with('/')
+        && key
+            .split('/')
+            .all(|v| !v.is_empty() && v != "." && v != "..")
+        && key
+            .bytes()
+            .all(|v| v.is_ascii_alphanumeric() || matches!(v, b'/' | b'.' | b'_' | b'-'));
+
+    if valid {
+        Ok(())
+    } else {
+        Err(StorageError::InvalidKey)
+    }
+}
+
+/// A store of uploaded media such as avatars and banners.
+///
+/// Implementations must call [validate_key] on every key they are given.
+#[async_trait::async_trait]
+pub trait ObjectStore: Send + Sync {
+    /// Adds or replaces the object at the key.
+    async fn put(&self, key: &str, object: StoredObject) -> Result<(), StorageError>;
+
+    async fn get(&self, key: &str) -> Result<Option<StoredObject>, StorageError>;
+
+    /// Removes the object, removing a missing object is not an error.
+    async fn delete(&self, key: &str) -> Result<(), StorageError>;
+
+    /// A url the object can be downloaded from until `expires_in` has passed.
+    fn presign(&self, key: &str, expires_in: Duration) -> Result<DiscordUrl, StorageError>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PNG: &[u8] = &[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+
+    #[test]
+    fn test_validate_key() {
+        assert!(validate_key("banners/abc123.webp").is_ok());
+        assert!(validate_key("avatars/1_2-3.png").is_ok());
+
+        for key in [
+            "",
+            "/banners/a.png",
+            "banners//a.png",
+            "../a.png",
+            "a b.png",
+            "a?.png",
+        ] {
+            assert!(validate_key(key).is_err(), "{}", key);
+        }
+        assert!(validate_key(&"a".repeat(MAX_KEY_LENGTH + 1)).is_err());
+    }
+
+    #[test]
+    fn test_content_type_must_match() {
+        assert!(StoredObject::new(ImageFormat::Png, PNG.to_vec()).is_ok());
+        assert!(StoredObject::new(ImageFormat::Jpeg, PNG.to_vec()).is_err());
+        assert!(StoredObject::new(ImageFormat::Png, b"<svg>".to_vec()).is_err());
+
+        let object = StoredObject::from_content_type("image/png; charset=binary", PNG.to_vec());
+        assert_eq!(object.unwrap().format, ImageFormat::Png);
+        assert!(StoredObject::from_content_type("text/html", PNG.to_vec()).is_err());
+    }
+}
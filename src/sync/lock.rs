@@ -0,0 +1,388 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::future::Future;
+use std::pin::pin;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use futures_util::future::{select, Either};
+use tokio::time::Instant;
+use uuid::Uuid;
+
+/// The default time a lock is held for unless renewed.
+pub const DEFAULT_LOCK_TTL: Duration = Duration::from_secs(60);
+
+/// The smallest margin a lease gives up to cover clock drift between the
+/// instances and the backend, on top of one percent of the ttl.
+const MIN_DRIFT_MARGIN: Duration = Duration::from_millis(2);
+
+#[derive(Debug)]
+pub enum LockError {
+    /// The lock backend could not be reached.
+    Backend(String),
+    /// The lease expired or was taken over before it could be renewed.
+    Lost,
+}
+
+impl Display for LockError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Backend(e) => write!(f, "lock backend error: {}", e),
+            Self::Lost => write!(f, "lock lease was lost"),
+        }
+    }
+}
+
+impl std::error::Error for LockError {}
+
+/// Stores the holder, expiry and fencing token of named locks.
+///
+/// Fencing tokens must increase every time a lock changes hands, even
+/// after it expired, so writes made with a stale lease can be rejected.
+#[async_trait::async_trait]
+pub trait LockBackend: Send + Sync {
+    /// Takes the lock if it is free or expired, returning its new fencing token.
+    async fn acquire(
+        &self,
+        name: &str,
+        holder: Uuid,
+        ttl: Duration,
+    ) -> Result<Option<i64>, LockError>;
+
+    /// Extends the lock, returning `false` if it is no longer held with the token.
+    async fn renew(
+        &self,
+        name: &str,
+        holder: Uuid,
+        token: i64,
+        ttl: Duration,
+    ) -> Result<bool, LockError>;
+
+    /// Frees the lock if it is still held with the token.
+    async fn release(&self, name: &str, holder: Uuid, token: i64) -> Result<(), LockError>;
+}
+
+/// A held lock.
+///
+/// The lease only trusts its own clock, it is considered expired slightly
+/// before the backend expires it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LockLease {
+    holder: Uuid,
+    token: i64,
+    valid_until: Instant,
+}
+
+impl LockLease {
+    /// Passed along with writes made while holding the lock, the target
+    /// rejects writes with a lower token than it has already seen.
+    #[inline]
+    pub fn fencing_token(&self) -> i64 {
+        self.token
+    }
+
+    #[inline]
+    pub fn is_valid(&self) -> bool {
+        Instant::now() < self.valid_until
+    }
+
+    pub fn remaining(&self) -> Duration {
+        self.valid_until.saturating_duration_since(Instant::now())
+    }
+}
+
+/// A named lock shared between instances, used so scheduled jobs such as
+/// re-ranking and rollups only run on one of them.
+pub struct DistributedLock {
+    backend: Arc<dyn LockBackend>,
+    name: String,
+    ttl: Duration,
+}
+
+impl DistributedLock {
+    pub fn new(backend: Arc<dyn LockBackend>, name: impl Into<String>) -> Self {
+        Self {
+            backend,
+            name: name.into(),
+            ttl: DEFAULT_LOCK_TTL,
+        }
+    }
+
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// The local expiry of a lease taken or renewed at `start`.
+    fn valid_until(&self, start: Instant) -> Instant {
+        let margin = (self.ttl / 100).max(MIN_DRIFT_MARGIN);
+        start + self.ttl.saturating_sub(margin)
+    }
+
+    /// Takes the lock, returning `None` if another instance holds it.
+    pub async fn try_acquire(&self) -> Result<Option<LockLease>, LockError> {
+        let holder = Uuid::new_v4();
+        let start = Instant::now();
+
+        let token = self.backend.acquire(&self.name, holder, self.ttl).await?;
+        Ok(token.map(|token| LockLease {
+            holder,
+            token,
+            valid_until: self.valid_until(start),
+        }))
+    }
+
+    /// Extends the lease by the ttl, long running jobs should renew well
+    /// before [LockLease::remaining] runs out.
+    pub async fn renew(&self, lease: &mut LockLease) -> Result<(), LockError> {
+        if !lease.is_valid() {
+            return Err(LockError::Lost);
+        }
+
+        let start = Instant::now();
+        let renewed = self
+            .backend
+            .renew(&self.name, lease.holder, lease.token, self.ttl)
+            .await?;
+        if !renewed {
+            return Err(LockError::Lost);
+        }
+
+        lease.valid_until = self.valid_until(start);
+        Ok(())
+    }
+
+    pub async fn release(&self, lease: LockLease) -> Result<(), LockError> {
+        self.backend
+            .release(&self.name, lease.holder, lease.token)
+            .await
+    }
+
+    /// Renews the lease every third of the ttl until it is lost, backend
+    /// errors are retried until the lease runs out.
+    async fn keep_alive(&self, lease: &mut LockLease) -> LockError {
+        loop {
+            tokio::time::sleep((self.ttl / 3).min(lease.remaining())).await;
+
+            let renewal = tokio::time::timeout(lease.remaining(), self.renew(lease)).await;
+            match renewal {
+                Ok(Ok(())) => {}
+                Ok(Err(LockError::Lost)) | Err(_) => return LockError::Lost,
+                Ok(Err(e)) => {
+                    tracing::warn!(lock = %self.name, error = %e, "failed to renew lock lease");
+                }
+            }
+        }
+    }
+
+    /// Runs the job if the lock can be taken, returning `None` if another
+    /// instance holds it.
+    ///
+    /// The lease is renewed while the job runs, if it is lost the job is
+    /// dropped and [LockError::Lost] is returned.
+    pub async fn run_exclusive<T, F, Fut>(&self, job: F) -> Result<Option<T>, LockError>
+    where
+        F: FnOnce(i64) -> Fut,
+        Fut: Future<Output = T>,
+    {
+        let Some(mut lease) = self.try_acquire().await? else {
+            return Ok(None);
+        };
+
+        let job = pin!(job(lease.fencing_token()));
+        let outcome = match select(job, pin!(self.keep_alive(&mut lease))).await {
+            Either::Left((output, _)) => Ok(output),
+            Either::Right((e, _)) => Err(e),
+        };
+
+        let released = self.release(lease).await;
+        let output = outcome?;
+        released?;
+        Ok(Some(output))
+    }
+}
+
+#[derive(Debug, Default)]
+struct MemoryLock {
+    holder: Option<Uuid>,
+    token: i64,
+    expires_at: Option<Instant>,
+}
+
+/// An in-process lock backend, for tests and single instance deployments.
+#[derive(Default)]
+pub struct MemoryLockBackend {
+    locks: Mutex<HashMap<String, MemoryLock>>,
+}
+
+impl MemoryLockBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait::async_trait]
+impl LockBackend for MemoryLockBackend {
+    async fn acquire(
+        &self,
+        name: &str,
+        holder: Uuid,
+        ttl: Duration,
+    ) -> Result<Option<i64>, LockError> {
+        let now = Instant::now();
+        let mut locks = self.locks.lock().unwrap();
+        let lock = locks.entry(name.to_string()).or_default();
+
+        let held = lock.holder.is_some() && lock.expires_at.is_some_and(|v| v > now);
+        if held {
+            return Ok(None);
+        }
+
+        lock.holder = Some(holder);
+        lock.token += 1;
+        lock.expires_at = Some(now + ttl);
+        Ok(Some(lock.token))
+    }
+
+    async fn renew(
+        &self,
+        name: &str,
+        holder: Uuid,
+        token: i64,
+        ttl: Duration,
+    ) -> Result<bool, LockError> {
+        let mut locks = self.locks.lock().unwrap();
+
+        match locks.get_mut(name) {
+            Some(lock) if lock.holder == Some(holder) && lock.token == token => {
+                lock.expires_at = Some(Instant::now() + ttl);
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    async fn release(&self, name: &str, holder: Uuid, token: i64) -> Result<(), LockError> {
+        let mut locks = self.locks.lock().unwrap();
+
+        if let Some(lock) = locks.get_mut(name) {
+            if lock.holder == Some(holder) && lock.token == token {
+                lock.holder = None;
+                lock.expires_at = None;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run<F: Future>(fut: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .unwrap()
+            .block_on(fut)
+    }
+
+    fn lock(backend: &Arc<MemoryLockBackend>, ttl: Duration) -> DistributedLock {
+        DistributedLock::new(backend.clone(), "rollups").with_ttl(ttl)
+    }
+
+    #[test]
+    fn test_only_one_holder() {
+        run(async {
+            let backend = Arc::new(MemoryLockBackend::new());
+            let ttl = Duration::from_secs(10);
+            let (a, b) = (lock(&backend, ttl), lock(&backend, ttl));
+
+            let lease = a.try_acquire().await.unwrap().unwrap();
+            assert_eq!(lease.fencing_token(), 1);
+            assert!(lease.remaining() <= Duration::from_millis(9900));
+            assert!(b.try_acquire().await.unwrap().is_none());
+
+            a.release(lease).await.unwrap();
+            let lease = b.try_acquire().await.unwrap().unwrap();
+            assert_eq!(lease.fencing_token(), 2);
+        })
+    }
+
+    #[test]
+    fn test_expiry_and_renewal() {
+        run(async {
+            let backend = Arc::new(MemoryLockBackend::new());
+            let ttl = Duration::from_millis(40);
+            let (a, b) = (lock(&backend, ttl), lock(&backend, ttl));
+
+            let mut lease = a.try_acquire().await.unwrap().unwrap();
+            a.renew(&mut lease).await.unwrap();
+            assert!(b.try_acquire().await.unwrap().is_none());
+
+            tokio::time::sleep(ttl).await;
+            assert!(!lease.is_valid());
+            let taken = b.try_acquire().await.unwrap().unwrap();
+            assert!(taken.fencing_token() > lease.fencing_token());
+            assert!(matches!(a.renew(&mut lease).await, Err(LockError::Lost)));
+
+            // Releasing a stale lease leaves the new holder's lock alone.
+            a.release(lease).await.unwrap();
+            assert!(a.try_acquire().await.unwrap().is_none());
+        })
+    }
+
+    #[test]
+    fn test_run_exclusive() {
+        run(async {
+            let backend = Arc::new(MemoryLockBackend::new());
+            let ttl = Duration::from_secs(10);
+            let (a, b) = (lock(&backend, ttl), lock(&backend, ttl));
+
+            let held = a.try_acquire().await.unwrap().unwrap();
+            assert_eq!(b.run_exclusive(|_| async { 1 }).await.unwrap(), None);
+
+            a.release(held).await.unwrap();
+            let token = b.run_exclusive(|token| async move { token }).await;
+            assert_eq!(token.unwrap(), Some(2));
+            assert!(a.try_acquire().await.unwrap().is_some());
+        })
+    }
+
+    #[test]
+    fn test_run_exclusive_renews() {
+        run(async {
+            let backend = Arc::new(MemoryLockBackend::new());
+            let ttl = Duration::from_millis(30);
+            let (a, b) = (lock(&backend, ttl), lock(&backend, ttl));
+
+            let output = a
+                .run_exclusive(|_| async {
+                    tokio::time::sleep(ttl * 3).await;
+                    assert!(b.try_acquire().await.unwrap().is_none());
+                    1
+                })
+                .await;
+            assert_eq!(output.unwrap(), Some(1));
+        })
+    }
+
+    #[test]
+    fn test_run_exclusive_cancels_on_loss() {
+        run(async {
+            let backend = Arc::new(MemoryLockBackend::new());
+            let ttl = Duration::from_millis(30);
+            let a = lock(&backend, ttl);
+
+            let output = a
+                .run_exclusive(|_| async {
+                    backend.locks.lock().unwrap().clear();
+                    tokio::time::sleep(ttl * 3).await;
+                    panic!("job should have been cancelled");
+                })
+                .await;
+            assert!(matches!(output, Err(LockError::Lost)));
+        })
+    }
+}
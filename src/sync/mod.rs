@@ -0,0 +1,11 @@
+mod lock;
+#[cfg(feature = "redis-lock")]
+mod redis;
+mod scylla;
+
+#[cfg(feature = "redis-lock")]
+pub use self::redis::RedisLockBackend;
+pub use self::scylla::{LockRow, LockStore, ScyllaLockBackend, LOCK_TABLE};
+pub use lock::{
+    DistributedLock, LockBackend, LockError, LockLease, MemoryLockBackend, DEFAULT_LOCK_TTL,
+};
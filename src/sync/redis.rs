@@ -0,0 +1,114 @@
+use std::time::Duration;
+
+use redis::aio::ConnectionManager;
+use redis::Script;
+use uuid::Uuid;
+
+use crate::sync::{LockBackend, LockError};
+
+const RENEW_SCRIPT: &str = r#"
+if redis.call("GET", KEYS[1]) == ARGV[1] then
+    return redis.call("PEXPIRE", KEYS[1], ARGV[2])
+end
+return 0
+"#;
+
+const RELEASE_SCRIPT: &str = r#"
+if redis.call("GET", KEYS[1]) == ARGV[1] then
+    return redis.call("DEL", KEYS[1])
+end
+return 0
+"#;
+
+impl From<redis::RedisError> for LockError {
+    fn from(e: redis::RedisError) -> Self {
+        Self::Backend(e.to_string())
+    }
+}
+
+/// Locks stored as `<prefix>:<name>` holding `<holder>:<token>`, the
+/// fencing token is a counter stored without expiry under `<prefix>:<name>:fence`.
+pub struct RedisLockBackend {
+    conn: ConnectionManager,
+    prefix: String,
+}
+
+impl RedisLockBackend {
+    pub fn new(conn: ConnectionManager, prefix: impl Into<String>) -> Self {
+        Self {
+            conn,
+            prefix: prefix.into(),
+        }
+    }
+
+    fn key(&self, name: &str) -> String {
+        format!("{}:{}", self.prefix, name)
+    }
+}
+
+fn value(holder: Uuid, token: i64) -> String {
+    format!("{}:{}", holder, token)
+}
+
+fn millis(ttl: Duration) -> u64 {
+    // Redis rejects an expiry of zero.
+    ttl.as_millis().max(1) as u64
+}
+
+#[async_trait::async_trait]
+impl LockBackend for RedisLockBackend {
+    async fn acquire(
+        &self,
+        name: &str,
+        holder: Uuid,
+        ttl: Duration,
+    ) -> Result<Option<i64>, LockError> {
+        let mut conn = self.conn.clone();
+        let key = self.key(name);
+
+        // A token taken by a failed attempt is skipped, tokens only need to increase.
+        let token: i64 = redis::cmd("INCR")
+            .arg(format!("{}:fence", key))
+            .query_async(&mut conn)
+            .await?;
+        let set: Option<String> = redis::cmd("SET")
+            .arg(&key)
+            .arg(value(holder, token))
+            .arg("NX")
+            .arg("PX")
+            .arg(millis(ttl))
+            .query_async(&mut conn)
+            .await?;
+
+        Ok(set.map(|_| token))
+    }
+
+    async fn renew(
+        &self,
+        name: &str,
+        holder: Uuid,
+        token: i64,
+        ttl: Duration,
+    ) -> Result<bool, LockError> {
+        let mut conn = self.conn.clone();
+        let renewed: i64 = Script::new(RENEW_SCRIPT)
+            .key(self.key(name))
+            .arg(value(holder, token))
+            .arg(millis(ttl))
+            .invoke_async(&mut conn)
+            .await?;
+
+        Ok(renewed == 1)
+    }
+
+    async fn release(&self, name: &str, holder: Uuid, token: i64) -> Result<(), LockError> {
+        let mut conn = self.conn.clone();
+        Script::new(RELEASE_SCRIPT)
+            .key(self.key(name))
+            .arg(value(holder, token))
+            .invoke_async::<_, i64>(&mut conn)
+            .await?;
+
+        Ok(())
+    }
+}
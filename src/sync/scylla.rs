@@ -0,0 +1,363 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use uuid::Uuid;
+
+use crate::db::{execute_lwt, insert_if_not_exists, DbError, Ignored, Session};
+use crate::sync::{LockBackend, LockError};
+use crate::types::Timestamp;
+
+pub const LOCK_TABLE: &str = "distributed_locks";
+
+const SELECT_QUERY: &str =
+    "SELECT holder, token, expires_at FROM distributed_locks WHERE name = ?;";
+const INSERT_QUERY: &str =
+    "INSERT INTO distributed_locks (name, holder, token, expires_at) VALUES (?, ?, ?, ?);";
+const TAKE_QUERY: &str = "UPDATE distributed_locks SET holder = ?, token = ?, expires_at = ? \
+    WHERE name = ? IF token = ? AND expires_at = ?;";
+const RENEW_QUERY: &str = "UPDATE distributed_locks SET expires_at = ? \
+    WHERE name = ? IF holder = ? AND token = ? AND expires_at > ?;";
+const RELEASE_QUERY: &str = "UPDATE distributed_locks SET holder = null, expires_at = null \
+    WHERE name = ? IF holder = ? AND token = ?;";
+
+impl From<DbError> for LockError {
+    fn from(e: DbError) -> Self {
+        Self::Backend(e.to_string())
+    }
+}
+
+/// The holder, fencing token and expiry of a lock row.
+pub type LockRow = (Option<Uuid>, i64, Option<Timestamp>);
+
+/// Runs the lock table statements, implemented by [Session].
+#[async_trait::async_trait]
+pub trait LockStore: Send + Sync {
+    async fn select(&self, name: &str) -> Result<Option<LockRow>, DbError>;
+
+    /// Creates the row if it does not exist yet.
+    async fn insert(&self, name: &str, row: LockRow) -> Result<bool, DbError>;
+
+    /// Replaces the row if its token and expiry are still the ones that were read.
+    async fn take(
+        &self,
+        name: &str,
+        seen: (i64, Option<Timestamp>),
+        row: LockRow,
+    ) -> Result<bool, DbError>;
+
+    /// Moves the expiry of a lease which is held and has not expired by `now`.
+    async fn renew(
+        &self,
+        name: &str,
+        holder: Uuid,
+        token: i64,
+        expires_at: Timestamp,
+        now: Timestamp,
+    ) -> Result<bool, DbError>;
+
+    async fn release(&self, name: &str, holder: Uuid, token: i64) -> Result<(), DbError>;
+}
+
+#[async_trait::async_trait]
+impl LockStore for Session {
+    async fn select(&self, name: &str) -> Result<Option<LockRow>, DbError> {
+        self.query_one::<LockRow>(SELECT_QUERY, (name,)).await
+    }
+
+    async fn insert(&self, name: &str, row: LockRow) -> Result<bool, DbError> {
+        let (holder, token, expires_at) = row;
+        let res = insert_if_not_exists::<Ignored>(
+            self,
+            INSERT_QUERY,
+            (name, holder, token, expires_at),
+        )
+        .await?;
+        Ok(res.is_applied())
+    }
+
+    async fn take(
+        &self,
+        name: &str,
+        seen: (i64, Option<Timestamp>),
+        row: LockRow,
+    ) -> Result<bool, DbError> {
+        let (holder, token, expires_at) = row;
+        let res = execute_lwt::<Ignored>(
+            self,
+            TAKE_QUERY,
+            (holder, token, expires_at, name, seen.0, seen.1),
+        )
+        .await?;
+        Ok(res.is_applied())
+    }
+
+    async fn renew(
+        &self,
+        name: &str,
+        holder: Uuid,
+        token: i64,
+        expires_at: Timestamp,
+        now: Timestamp,
+    ) -> Result<bool, DbError> {
+        let res = execute_lwt::<Ignored>(
+            self,
+            RENEW_QUERY,
+            (expires_at, name, holder, token, now),
+        )
+        .await?;
+        Ok(res.is_applied())
+    }
+
+    async fn release(&self, name: &str, holder: Uuid, token: i64) -> Result<(), DbError> {
+        execute_lwt::<Ignored>(self, RELEASE_QUERY, (name, holder, token)).await?;
+        Ok(())
+    }
+}
+
+/// Locks stored with lightweight transactions.
+///
+/// ```cql
+/// CREATE TABLE distributed_locks (
+///     name text PRIMARY KEY,
+///     holder uuid,
+///     token bigint,
+///     expires_at timestamp
+/// );
+/// ```
+///
+/// Rows are never expired with a TTL so the fencing token survives the
+/// lock being released or timing out.
+///
+/// Taking over an expired lock is conditioned on the expiry that was read,
+/// so a renewal landing in between makes the takeover fail instead of
+/// both instances believing they hold the lock.
+pub struct ScyllaLockBackend<S = Session> {
+    session: Arc<S>,
+}
+
+impl<S: LockStore> ScyllaLockBackend<S> {
+    pub fn new(session: Arc<S>) -> Self {
+        Self { session }
+    }
+}
+
+/// Ttls too large to represent expire at the latest representable time.
+fn expires_at(now: Timestamp, ttl: Duration) -> Timestamp {
+    let expires_at = chrono::Duration::from_std(ttl)
+        .ok()
+        .and_then(|ttl| now.0.checked_add_signed(ttl))
+        .unwrap_or(chrono::DateTime::<chrono::Utc>::MAX_UTC);
+    Timestamp(expires_at)
+}
+
+#[async_trait::async_trait]
+impl<S: LockStore> LockBackend for ScyllaLockBackend<S> {
+    async fn acquire(
+        &self,
+        name: &str,
+        holder: Uuid,
+        ttl: Duration,
+    ) -> Result<Option<i64>, LockError> {
+        let current = self.session.select(name).await?;
+        let now = Timestamp::default();
+
+        let (token, applied) = match current {
+            None => {
+                let row = (Some(holder), 1, Some(expires_at(now, ttl)));
+                (1, self.session.insert(name, row).await?)
+            }
+            Some((Some(_), _, Some(expires))) if expires.0 > now.0 => return Ok(None),
+            Some((_, token, expires)) => {
+                let row = (Some(holder), token + 1, Some(expires_at(now, ttl)));
+                let applied = self.session.take(name, (token, expires), row).await?;
+                (token + 1, applied)
+            }
+        };
+
+        Ok(applied.then_some(token))
+    }
+
+    async fn renew(
+        &self,
+        name: &str,
+        holder: Uuid,
+        token: i64,
+        ttl: Duration,
+    ) -> Result<bool, LockError> {
+        let now = Timestamp::default();
+        let renewed = self
+            .session
+            .renew(name, holder, token, expires_at(now, ttl), now)
+            .await?;
+
+        Ok(renewed)
+    }
+
+    async fn release(&self, name: &str, holder: Uuid, token: i64) -> Result<(), LockError> {
+        self.session.release(name, holder, token).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+
+    type Hook = Box<dyn FnOnce(&FakeLocks) + Send>;
+
+    /// Applies the same conditions as the CQL statements to a single row,
+    /// running `before_take` between reading the row and taking it over.
+    #[derive(Default)]
+    struct FakeLocks {
+        row: Mutex<Option<LockRow>>,
+        before_take: Mutex<Option<Hook>>,
+    }
+
+    #[async_trait::async_trait]
+    impl LockStore for FakeLocks {
+        async fn select(&self, _name: &str) -> Result<Option<LockRow>, DbError> {
+            Ok(*self.row.lock().unwrap())
+        }
+
+        async fn insert(&self, _name: &str, row: LockRow) -> Result<bool, DbError> {
+            let mut current = self.row.lock().unwrap();
+            if current.is_some() {
+                return Ok(false);
+            }
+            *current = Some(row);
+            Ok(true)
+        }
+
+        async fn take(
+            &self,
+            _name: &str,
+            seen: (i64, Option<Timestamp>),
+            row: LockRow,
+        ) -> Result<bool, DbError> {
+            if let Some(hook) = self.before_take.lock().unwrap().take() {
+                hook(self);
+            }
+
+            let mut current = self.row.lock().unwrap();
+            match current.as_mut() {
+                Some(current) if (current.1, current.2) == seen => {
+                    *current = row;
+                    Ok(true)
+                }
+                _ => Ok(false),
+            }
+        }
+
+        async fn renew(
+            &self,
+            _name: &str,
+            holder: Uuid,
+            token: i64,
+            expires_at: Timestamp,
+            now: Timestamp,
+        ) -> Result<bool, DbError> {
+            let mut current = self.row.lock().unwrap();
+            match current.as_mut() {
+                Some((Some(h), t, Some(expires)))
+                    if *h == holder && *t == token && expires.0 > now.0 =>
+                {
+                    *expires = expires_at;
+                    Ok(true)
+                }
+                _ => Ok(false),
+            }
+        }
+
+        async fn release(&self, _name: &str, holder: Uuid, token: i64) -> Result<(), DbError> {
+            let mut current = self.row.lock().unwrap();
+            if let Some(row) = current.as_mut() {
+                if row.0 == Some(holder) && row.1 == token {
+                    *row = (None, token, None);
+                }
+            }
+            Ok(())
+        }
+    }
+
+    fn block_on<F: std::future::Future>(fut: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(fut)
+    }
+
+    fn ago(secs: i64) -> Timestamp {
+        Timestamp(Timestamp::default().0 - chrono::Duration::seconds(secs))
+    }
+
+    #[test]
+    fn test_expires_at_clamps() {
+        let now = Timestamp::default();
+        let latest = expires_at(now, Duration::MAX);
+        assert_eq!(latest.0, chrono::DateTime::<chrono::Utc>::MAX_UTC);
+
+        let soon = expires_at(now, Duration::from_secs(60));
+        assert!(soon.0 > now.0);
+    }
+
+    #[test]
+    fn test_renew_between_read_and_take() {
+        block_on(async {
+            let old = Uuid::new_v4();
+            let store = Arc::new(FakeLocks::default());
+            // The lease was still valid when renewed, but the new holder
+            // read the row on a clock that already saw it as expired.
+            *store.row.lock().unwrap() = Some((Some(old), 1, Some(ago(1))));
+            *store.before_take.lock().unwrap() = Some(Box::new(move |store: &FakeLocks| {
+                let mut row = store.row.lock().unwrap();
+                row.as_mut().unwrap().2 = Some(expires_at(Timestamp::default(), Duration::from_secs(60)));
+            }));
+
+            let backend = ScyllaLockBackend::new(store.clone());
+            let taken = backend
+                .acquire("rollups", Uuid::new_v4(), Duration::from_secs(60))
+                .await
+                .unwrap();
+            assert_eq!(taken, None);
+
+            let row = store.row.lock().unwrap().unwrap();
+            assert_eq!((row.0, row.1), (Some(old), 1));
+        })
+    }
+
+    #[test]
+    fn test_expired_lease_is_not_renewed() {
+        block_on(async {
+            let old = Uuid::new_v4();
+            let store = Arc::new(FakeLocks::default());
+            *store.row.lock().unwrap() = Some((Some(old), 1, Some(ago(1))));
+            let backend = ScyllaLockBackend::new(store.clone());
+            let ttl = Duration::from_secs(60);
+
+            assert!(!backend.renew("rollups", old, 1, ttl).await.unwrap());
+
+            let new = Uuid::new_v4();
+            assert_eq!(backend.acquire("rollups", new, ttl).await.unwrap(), Some(2));
+            assert!(!backend.renew("rollups", old, 1, ttl).await.unwrap());
+            assert!(backend.renew("rollups", new, 2, ttl).await.unwrap());
+        })
+    }
+
+    #[test]
+    fn test_takes_released_lock() {
+        block_on(async {
+            let store = Arc::new(FakeLocks::default());
+            let backend = ScyllaLockBackend::new(store.clone());
+            let ttl = Duration::from_secs(60);
+            let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+
+            assert_eq!(backend.acquire("rollups", a, ttl).await.unwrap(), Some(1));
+            assert_eq!(backend.acquire("rollups", b, ttl).await.unwrap(), None);
+
+            backend.release("rollups", a, 1).await.unwrap();
+            assert_eq!(backend.acquire("rollups", b, ttl).await.unwrap(), Some(2));
+        })
+    }
+}
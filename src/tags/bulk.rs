@@ -0,0 +1,167 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::{Display, Formatter};
+
+use poem_openapi::Object;
+
+use crate::live::LiveValue;
+use crate::tags::{normalise_tag_name, Flag, VisibleTag};
+
+/// A kind of tag set which is backed by one of the tag registries.
+pub trait TagSet: Sized {
+    /// The maximum number of tags a single item may have.
+    const MAX_TAGS: usize = usize::MAX;
+
+    fn registry() -> &'static LiveValue<BTreeMap<String, Flag>>;
+
+    fn from_visible(tags: Vec<VisibleTag>) -> Self;
+}
+
+/// Everything wrong with the tags of a single item.
+#[derive(Object, Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct TagErrors {
+    pub unknown: Vec<String>,
+    pub duplicates: Vec<String>,
+    /// Set when the item has more tags than allowed.
+    #[oai(skip_serializing_if_is_none)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub too_many: Option<usize>,
+}
+
+impl TagErrors {
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.unknown.is_empty() && self.duplicates.is_empty() && self.too_many.is_none()
+    }
+}
+
+impl Display for TagErrors {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut problems = vec![];
+        if !self.unknown.is_empty() {
+            problems.push(format!("unknown tags {:?}", self.unknown));
+        }
+        if !self.duplicates.is_empty() {
+            problems.push(format!("duplicate tags {:?}", self.duplicates));
+        }
+        if let Some(max) = self.too_many {
+            problems.push(format!("more than {} tags", max));
+        }
+
+        write!(f, "{}", problems.join(", "))
+    }
+}
+
+impl std::error::Error for TagErrors {}
+
+/// Validates the tags of many items against a single load of the registry.
+///
+/// Every item is validated independently so one bad item does not fail the
+/// whole batch, e.g. `validate_many::<BotTags>(rows)`.
+pub fn validate_many<T: TagSet>(items: Vec<Vec<String>>) -> Vec<Result<T, TagErrors>> {
+    let lookup = T::registry().load();
+
+    items
+        .into_iter()
+        .map(|names| validate_one::<T>(names, lookup.as_ref()))
+        .collect()
+}
+
+fn validate_one<T: TagSet>(
+    names: Vec<String>,
+    lookup: &BTreeMap<String, Flag>,
+) -> Result<T, TagErrors> {
+    let mut errors = TagErrors::default();
+    let mut seen = HashSet::new();
+    let mut tags = vec![];
+
+    for name in names {
+        let name = normalise_tag_name(&name);
+
+        if !seen.insert(name.clone()) {
+            if !errors.duplicates.contains(&name) {
+                errors.duplicates.push(name);
+            }
+            continue;
+        }
+
+        match lookup.get(&name) {
+            Some(flag) => tags.push(flag.to_visible(name)),
+            None => errors.unknown.push(name),
+        }
+    }
+
+    if seen.len() > T::MAX_TAGS {
+        errors.too_many = Some(T::MAX_TAGS);
+    }
+
+    if errors.is_empty() {
+        Ok(T::from_visible(tags))
+    } else {
+        Err(errors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tags::{set_pack_tags, BotTags, PackTags};
+
+    fn flag(name: &str) -> (String, Flag) {
+        let mut display_name = name.to_string();
+        display_name[..1].make_ascii_uppercase();
+
+        (
+            name.to_string(),
+            Flag {
+                display_name,
+                category: "".to_string(),
+                emoji: None,
+            },
+        )
+    }
+
+    fn rows(items: &[&[&str]]) -> Vec<Vec<String>> {
+        items
+            .iter()
+            .map(|v| v.iter().map(|v| v.to_string()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_per_item_errors() {
+        let lookup = BTreeMap::from_iter([flag("music"), flag("utility")]);
+
+        let results: Vec<Result<BotTags, _>> =
+            rows(&[&["music", "Utility"], &["music", "cheese", "music"], &[]])
+                .into_iter()
+                .map(|v| validate_one(v, &lookup))
+                .collect();
+
+        assert_eq!(
+            results[0].as_ref().unwrap().as_raw(),
+            vec!["music", "utility"]
+        );
+        assert_eq!(
+            results[1].as_ref().unwrap_err(),
+            &TagErrors {
+                unknown: vec!["cheese".to_string()],
+                duplicates: vec!["music".to_string()],
+                too_many: None,
+            }
+        );
+        assert!(results[2].as_ref().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_pack_limit() {
+        set_pack_tags(BTreeMap::from_iter([flag("fun"), flag("games")])).unwrap();
+
+        let results = validate_many::<PackTags>(rows(&[&["fun"], &["fun", "games"]]));
+
+        assert_eq!(
+            results[0].as_ref().unwrap().as_raw().as_deref(),
+            Some("fun")
+        );
+        assert_eq!(results[1].as_ref().unwrap_err().too_many, Some(1));
+    }
+}
@@ -0,0 +1,116 @@
+use std::collections::BTreeMap;
+
+use once_cell::sync::Lazy;
+
+use crate::live::LiveValue;
+use crate::tags::registry::{TagRegistry, Tags};
+use crate::tags::{Flag, InvalidEmoji};
+
+static LOADED_GUILD_TAGS: Lazy<LiveValue<BTreeMap<String, Flag>>> = Lazy::new(LiveValue::default);
+
+pub fn get_guild_tags() -> &'static LiveValue<BTreeMap<String, Flag>> {
+    &LOADED_GUILD_TAGS
+}
+
+/// Loads a new registry, the current one is kept if any flag is invalid.
+pub fn set_guild_tags(lookup: BTreeMap<String, Flag>) -> Result<(), InvalidEmoji> {
+    GuildTagRegistry::load(lookup)
+}
+
+pub enum GuildTagRegistry {}
+
+impl TagRegistry for GuildTagRegistry {
+    const NAME: &'static str = "guild";
+    const SCHEMA_NAME: &'static str = "GuildTag";
+
+    fn lookup() -> &'static LiveValue<BTreeMap<String, Flag>> {
+        get_guild_tags()
+    }
+}
+
+pub type GuildTags = Tags<GuildTagRegistry>;
+
+#[cfg(feature = "rkyv")]
+pub type ArchivedGuildTags = crate::tags::registry::ArchivedTags<GuildTagRegistry>;
+
+#[cfg(feature = "graphql")]
+graphql_json_scalar!(GuildTags, "GuildTags");
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tags::VisibleTag;
+    use poem_openapi::types::{ParseFromJSON, Type};
+
+    fn lookup() {
+        let items = vec![
+            (
+                "gaming".into(),
+                Flag {
+                    display_name: "Gaming".into(),
+                    category: "".to_string(),
+                    emoji: None,
+                },
+            ),
+            (
+                "community".into(),
+                Flag {
+                    display_name: "Community".into(),
+                    category: "".to_string(),
+                    emoji: None,
+                },
+            ),
+            (
+                "anime".into(),
+                Flag {
+                    display_name: "Anime".into(),
+                    category: "".to_string(),
+                    emoji: None,
+                },
+            ),
+        ];
+
+        set_guild_tags(BTreeMap::from_iter(items)).unwrap()
+    }
+
+    #[test]
+    fn test_setting_flags() {
+        lookup();
+
+        let sample = serde_json::to_value(vec!["gaming", "hello", "anime"]).unwrap();
+        assert!(GuildTags::parse_from_json(Some(sample)).is_err());
+
+        let sample = serde_json::to_value(vec!["Gaming ", " anime"]).unwrap();
+        let tags =
+            GuildTags::parse_from_json(Some(sample)).expect("Successful parse from JSON Value.");
+
+        assert_eq!(
+            tags.to_vec(),
+            vec![
+                VisibleTag {
+                    name: "gaming".to_string(),
+                    display_name: "Gaming".into(),
+                    category: "".to_string(),
+                    emoji: None,
+                },
+                VisibleTag {
+                    name: "anime".to_string(),
+                    display_name: "Anime".into(),
+                    category: "".to_string(),
+                    emoji: None,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn test_uses_guild_registry() {
+        lookup();
+
+        assert_eq!(GuildTags::name(), "Tags<GuildTag>");
+        assert_eq!(
+            GuildTags::from_raw(&["gaming".into(), "music".into()]).len(),
+            1
+        );
+    }
+}
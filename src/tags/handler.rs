@@ -1,27 +1,152 @@
 use poem_openapi::Object;
+use scylla::frame::response::result::CqlValue;
 use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
 
 #[cfg(feature = "bincode")]
 use bincode::{Decode, Encode};
 
 #[cfg_attr(feature = "bincode", derive(Encode, Decode))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
 #[derive(Debug, Clone, Object, serde::Serialize, serde::Deserialize, Eq, PartialEq)]
 pub struct VisibleTag {
     pub name: String,
     pub display_name: String,
     pub category: String,
+    #[oai(skip_serializing_if_is_none)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub emoji: Option<String>,
+}
+
+impl VisibleTag {
+    /// A tag where only the name is known, used when reading rows which
+    /// stored bare tag names rather than the UDT.
+    pub fn from_name(name: String) -> Self {
+        Self {
+            display_name: name.clone(),
+            name,
+            category: String::new(),
+            emoji: None,
+        }
+    }
+}
+
+// Stored as `frozen<visible_tag>`, older rows may contain the bare tag name.
+cql_udt!(
+    VisibleTag {
+        name,
+        display_name,
+        category,
+        emoji
+    },
+    fallback = |v: CqlValue| v.into_string().map(VisibleTag::from_name)
+);
+
+/// The layout of [VisibleTag] before the emoji was added.
+#[cfg(feature = "bincode")]
+#[derive(Decode)]
+pub(crate) struct VisibleTagV1 {
+    name: String,
+    display_name: String,
+    category: String,
+}
+
+#[cfg(feature = "bincode")]
+impl From<VisibleTagV1> for VisibleTag {
+    fn from(v: VisibleTagV1) -> Self {
+        Self {
+            name: v.name,
+            display_name: v.display_name,
+            category: v.category,
+            emoji: None,
+        }
+    }
+}
+
+#[cfg(feature = "bincode")]
+impl crate::codec::VersionedDecode for VisibleTag {
+    const VERSION: u16 = 2;
+
+    fn migrations() -> crate::codec::Migrations<Self> {
+        crate::codec::Migrations::new().register(1, |old: VisibleTagV1| old.into())
+    }
 }
 
 #[derive(Debug)]
 pub struct Flag {
     pub display_name: String,
     pub category: String,
+    /// Must be a single emoji grapheme, see [validate_flags].
+    pub emoji: Option<String>,
+}
+
+impl Flag {
+    pub fn to_visible(&self, name: String) -> VisibleTag {
+        VisibleTag {
+            name,
+            display_name: self.display_name.clone(),
+            category: self.category.clone(),
+            emoji: self.emoji.clone(),
+        }
+    }
+}
+
+/// A flag whose emoji is not a single emoji grapheme.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidEmoji {
+    pub tag: String,
+    pub emoji: String,
+}
+
+impl Display for InvalidEmoji {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "tag {:?} has emoji {:?} which is not a single emoji",
+            self.tag, self.emoji
+        )
+    }
+}
+
+impl std::error::Error for InvalidEmoji {}
+
+/// Checks every flag of a registry before it is loaded.
+pub(crate) fn validate_flags(lookup: &BTreeMap<String, Flag>) -> Result<(), InvalidEmoji> {
+    for (tag, flag) in lookup {
+        if let Some(emoji) = flag.emoji.as_ref().filter(|v| !is_single_emoji(v)) {
+            return Err(InvalidEmoji {
+                tag: tag.clone(),
+                emoji: emoji.clone(),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks that the given value is exactly one emoji grapheme.
+pub fn is_single_emoji(v: &str) -> bool {
+    emojis::get(v).is_some()
 }
 
 pub fn get_tag<'a>(flag: &str, lookup: &'a BTreeMap<String, Flag>) -> Option<&'a Flag> {
     lookup.get(flag)
 }
 
+/// The name of a stored tag, either a bare name or a `visible_tag` UDT.
+pub fn tag_name(value: &CqlValue) -> Option<&String> {
+    match value {
+        CqlValue::UserDefinedType { fields, .. } => fields
+            .iter()
+            .find(|(name, _)| name == "name")
+            .and_then(|(_, value)| value.as_ref()?.as_text()),
+        other => other.as_text(),
+    }
+}
+
 pub fn filter_valid_tags<'a>(
     flags: impl Iterator<Item = &'a String>,
     lookup: &BTreeMap<String, Flag>,
@@ -29,13 +154,140 @@ pub fn filter_valid_tags<'a>(
     let mut named = vec![];
     for name in flags {
         if let Some(flag) = lookup.get(name) {
-            named.push(VisibleTag {
-                name: name.clone(),
-                display_name: flag.display_name.clone(),
-                category: flag.category.clone(),
-            });
+            named.push(flag.to_visible(name.clone()));
         }
     }
 
     named
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn udt(fields: Vec<(&str, Option<CqlValue>)>) -> CqlValue {
+        CqlValue::UserDefinedType {
+            keyspace: "dlist".into(),
+            type_name: "visible_tag".into(),
+            fields: fields
+                .into_iter()
+                .map(|(name, value)| (name.to_string(), value))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_udt_round_trip() {
+        use scylla::cql_to_rust::FromCqlVal;
+        use scylla::frame::value::Value;
+
+        let tag = VisibleTag {
+            name: "music".into(),
+            display_name: "Music".into(),
+            category: "fun".into(),
+            emoji: None,
+        };
+
+        let mut buf = vec![];
+        tag.serialize(&mut buf).unwrap();
+        assert_eq!(
+            i32::from_be_bytes(buf[..4].try_into().unwrap()) as usize,
+            buf.len() - 4
+        );
+        // The missing emoji is written as a null field.
+        assert_eq!(&buf[buf.len() - 4..], &(-1i32).to_be_bytes());
+
+        let value = udt(vec![
+            ("emoji", None),
+            ("category", Some(CqlValue::Text("fun".into()))),
+            ("display_name", Some(CqlValue::Text("Music".into()))),
+            ("name", Some(CqlValue::Text("music".into()))),
+        ]);
+        assert_eq!(tag_name(&value), Some(&"music".to_string()));
+        assert_eq!(VisibleTag::from_cql(value).unwrap(), tag);
+    }
+
+    #[test]
+    fn test_text_fallback() {
+        use scylla::cql_to_rust::FromCqlVal;
+
+        let value = CqlValue::Text("music".into());
+        assert_eq!(tag_name(&value), Some(&"music".to_string()));
+        assert_eq!(
+            VisibleTag::from_cql(value).unwrap(),
+            VisibleTag::from_name("music".into())
+        );
+        assert!(VisibleTag::from_cql(CqlValue::Int(1)).is_err());
+        assert!(VisibleTag::from_cql(udt(vec![("name", None)])).is_err());
+    }
+
+    #[test]
+    fn test_single_emoji_validation() {
+        assert!(is_single_emoji("🎵"));
+        assert!(is_single_emoji("👍🏽"));
+        assert!(is_single_emoji("🏳️‍🌈"));
+
+        assert!(!is_single_emoji(""));
+        assert!(!is_single_emoji("a"));
+        assert!(!is_single_emoji("🎵🎵"));
+        assert!(!is_single_emoji("🎵 "));
+    }
+
+    #[test]
+    fn test_invalid_emoji_is_rejected() {
+        let lookup = BTreeMap::from_iter([
+            (
+                "music".to_string(),
+                Flag {
+                    display_name: "Music".into(),
+                    category: "".into(),
+                    emoji: Some("🎵".into()),
+                },
+            ),
+            (
+                "utility".to_string(),
+                Flag {
+                    display_name: "Utility".into(),
+                    category: "".into(),
+                    emoji: Some("tools".into()),
+                },
+            ),
+        ]);
+
+        assert_eq!(
+            validate_flags(&lookup),
+            Err(InvalidEmoji {
+                tag: "utility".to_string(),
+                emoji: "tools".to_string(),
+            })
+        );
+
+        let lookup = BTreeMap::from_iter(lookup.into_iter().take(1));
+        assert_eq!(validate_flags(&lookup), Ok(()));
+
+        let names = ["music".to_string()];
+        let tags = filter_valid_tags(names.iter(), &lookup);
+        assert_eq!(tags[0].emoji.as_deref(), Some("🎵"));
+    }
+
+    #[cfg(feature = "bincode")]
+    #[test]
+    fn test_v1_migrated() {
+        use crate::codec::Versioned;
+
+        let mut bytes = 1u16.to_le_bytes().to_vec();
+        bytes.extend(
+            bincode::encode_to_vec(("music", "Music", "fun"), bincode::config::standard()).unwrap(),
+        );
+
+        assert_eq!(
+            Versioned::<VisibleTag>::decode(&bytes).unwrap(),
+            VisibleTag {
+                name: "music".into(),
+                display_name: "Music".into(),
+                category: "fun".into(),
+                emoji: None,
+            }
+        );
+    }
+}
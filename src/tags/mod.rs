@@ -1,11 +1,34 @@
-mod bots;
-mod handler;
-mod packs;
+cfg_server! {
+    mod bots;
+    pub mod bulk;
+    mod guilds;
+    mod handler;
+    mod packs;
+    mod registry;
+    mod schema;
 
-pub use bots::{get_bot_tags, set_bot_tags, BotTags};
-pub use handler::{filter_valid_tags, Flag, VisibleTag};
-pub use packs::{get_pack_tags, set_pack_tags, PackTags};
-
-pub trait IntoFilter {
-    fn into_filter(self) -> Vec<String>;
+    pub use crate::search::IntoFilter;
+    #[cfg(feature = "rkyv")]
+    pub use bots::ArchivedBotTags;
+    pub use bots::{get_bot_tags, set_bot_tags, BotTagRegistry, BotTags};
+    pub use bulk::{validate_many, TagErrors, TagSet};
+    #[cfg(feature = "rkyv")]
+    pub use guilds::ArchivedGuildTags;
+    pub use guilds::{get_guild_tags, set_guild_tags, GuildTagRegistry, GuildTags};
+    #[cfg(feature = "rkyv")]
+    pub use handler::ArchivedVisibleTag;
+    pub use handler::{
+        filter_valid_tags, tag_name, Flag, InvalidEmoji, VisibleTag,
+    };
+    #[cfg(feature = "rkyv")]
+    pub use packs::ArchivedPackTags;
+    pub use packs::{get_pack_tags, set_pack_tags, PackTagRegistry, PackTags};
+    #[cfg(feature = "rkyv")]
+    pub use registry::ArchivedTags;
+    pub use registry::{TagRegistry, Tags};
+    pub use schema::{with_tag_enum_schemas, TagEnumSpec};
 }
+
+mod names;
+
+pub use names::normalise_tag_name;
@@ -0,0 +1,16 @@
+/// The form tag names are looked up and stored in, so `" Music"` and
+/// `"music"` refer to the same tag.
+pub fn normalise_tag_name(name: &str) -> String {
+    name.trim().to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalise_tag_name() {
+        assert_eq!(normalise_tag_name("  Music "), "music");
+        assert_eq!(normalise_tag_name("ÉVÉNEMENTS"), "événements");
+    }
+}
@@ -1,13 +1,11 @@
-use arc_swap::ArcSwap;
 use std::borrow::Cow;
 use std::collections::BTreeMap;
 use std::fmt::{Debug, Formatter};
 use std::ops::Deref;
-use std::sync::Arc;
 
 #[cfg(feature = "bincode")]
 use bincode::{Decode, Encode};
-use once_cell::sync::OnceCell;
+use once_cell::sync::Lazy;
 
 use poem_openapi::registry::{MetaSchemaRef, Registry};
 use poem_openapi::types::{ParseError, ParseFromJSON, ParseResult, ToJSON, Type};
@@ -15,21 +13,40 @@ use scylla::cql_to_rust::{FromCqlVal, FromCqlValError};
 use scylla::frame::response::result::CqlValue;
 use scylla::frame::value::{Value, ValueTooBig};
 
+use crate::live::LiveValue;
+use crate::search::{fields, FilterExpr, FilterOp};
 use crate::tags::handler::get_tag;
-use crate::tags::{Flag, IntoFilter, VisibleTag};
+use crate::tags::registry::TagRegistry;
+use crate::tags::schema::{register_tag_names, tag_name_schema_ref};
+use crate::tags::{normalise_tag_name, Flag, IntoFilter, InvalidEmoji, TagSet, VisibleTag};
 
-static LOADED_PACK_TAGS: OnceCell<ArcSwap<BTreeMap<String, Flag>>> = OnceCell::new();
+static LOADED_PACK_TAGS: Lazy<LiveValue<BTreeMap<String, Flag>>> = Lazy::new(LiveValue::default);
 
-pub fn get_pack_tags() -> &'static ArcSwap<BTreeMap<String, Flag>> {
-    LOADED_PACK_TAGS.get_or_init(ArcSwap::default)
+pub fn get_pack_tags() -> &'static LiveValue<BTreeMap<String, Flag>> {
+    &LOADED_PACK_TAGS
 }
 
-pub fn set_pack_tags(lookup: BTreeMap<String, Flag>) {
-    let swap = LOADED_PACK_TAGS.get_or_init(ArcSwap::default);
-    swap.store(Arc::new(lookup));
+/// Loads a new registry, the current one is kept if any flag is invalid.
+pub fn set_pack_tags(lookup: BTreeMap<String, Flag>) -> Result<(), InvalidEmoji> {
+    PackTagRegistry::load(lookup)
+}
+
+pub enum PackTagRegistry {}
+
+impl TagRegistry for PackTagRegistry {
+    const NAME: &'static str = "pack";
+    const SCHEMA_NAME: &'static str = "PackTag";
+
+    fn lookup() -> &'static LiveValue<BTreeMap<String, Flag>> {
+        get_pack_tags()
+    }
 }
 
 #[cfg_attr(feature = "bincode", derive(Encode, Decode))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
 #[derive(Default, Clone)]
 pub struct PackTags {
     inner: Option<VisibleTag>,
@@ -46,6 +63,7 @@ impl PackTags {
                     name: tag,
                     display_name: flag.display_name.clone(),
                     category: "".to_string(),
+                    emoji: flag.emoji.clone(),
                 }),
             }
         } else {
@@ -66,6 +84,15 @@ impl Deref for PackTags {
     }
 }
 
+#[cfg(feature = "rkyv")]
+impl Deref for ArchivedPackTags {
+    type Target = rkyv::option::ArchivedOption<crate::tags::ArchivedVisibleTag>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
 impl Debug for PackTags {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(f, "{:?}", self.inner)
@@ -97,15 +124,20 @@ impl Type for PackTags {
     type RawElementValueType = <Vec<VisibleTag> as Type>::RawElementValueType;
 
     fn name() -> Cow<'static, str> {
-        Cow::from("Tags<PackTag>")
+        Cow::from(format!("Tags<{}>", PackTagRegistry::SCHEMA_NAME))
     }
 
     fn schema_ref() -> MetaSchemaRef {
-        Vec::<String>::schema_ref()
+        tag_name_schema_ref(PackTagRegistry::SCHEMA_NAME)
     }
 
     fn register(registry: &mut Registry) {
-        <VisibleTag as Type>::register(registry)
+        <VisibleTag as Type>::register(registry);
+        register_tag_names::<Self>(
+            registry,
+            PackTagRegistry::SCHEMA_NAME,
+            get_pack_tags().load().as_ref(),
+        );
     }
 
     fn as_raw_value(&self) -> Option<&Self::RawValueType> {
@@ -127,7 +159,8 @@ impl ParseFromJSON for PackTags {
 
             let maybe_found = val
                 .as_str()
-                .and_then(|v| tags.get(&v.to_lowercase()).map(|f| (v, f)));
+                .map(normalise_tag_name)
+                .and_then(|v| tags.get(&v).map(|f| (v, f)));
 
             let (name, flag) = match maybe_found {
                 Some(flag) => flag,
@@ -135,11 +168,7 @@ impl ParseFromJSON for PackTags {
             };
 
             Ok(Self {
-                inner: Some(VisibleTag {
-                    name: name.to_lowercase(),
-                    display_name: flag.display_name.to_string(),
-                    category: flag.category.clone(),
-                }),
+                inner: Some(flag.to_visible(name)),
             })
         } else {
             Err(ParseError::custom("Cannot derive tags from null."))
@@ -153,6 +182,9 @@ impl ToJSON for PackTags {
     }
 }
 
+#[cfg(feature = "graphql")]
+graphql_json_scalar!(PackTags, "PackTags");
+
 impl Value for PackTags {
     fn serialize(&self, buf: &mut Vec<u8>) -> Result<(), ValueTooBig> {
         let flags = self.as_raw();
@@ -165,7 +197,7 @@ impl Value for PackTags {
 impl FromCqlVal<CqlValue> for PackTags {
     fn from_cql(cql_val: CqlValue) -> Result<Self, FromCqlValError> {
         let slf = match cql_val {
-            CqlValue::Text(s) => Self::from_raw(s.to_lowercase()),
+            CqlValue::Text(s) => Self::from_raw(normalise_tag_name(&s)),
             _ => Self::default(),
         };
 
@@ -173,13 +205,28 @@ impl FromCqlVal<CqlValue> for PackTags {
     }
 }
 
+impl TagSet for PackTags {
+    const MAX_TAGS: usize = 1;
+
+    fn registry() -> &'static LiveValue<BTreeMap<String, Flag>> {
+        get_pack_tags()
+    }
+
+    fn from_visible(tags: Vec<VisibleTag>) -> Self {
+        Self {
+            inner: tags.into_iter().next(),
+        }
+    }
+}
+
 impl IntoFilter for PackTags {
     #[inline]
-    fn into_filter(self) -> Vec<String> {
-        self.inner
-            .iter()
-            .map(|v| format!("tags = {:?}", v))
-            .collect()
+    fn into_filter(self) -> FilterExpr {
+        FilterExpr::all(
+            self.inner
+                .into_iter()
+                .map(|v| FilterExpr::condition(fields::TAGS, FilterOp::Eq, v.name)),
+        )
     }
 }
 
@@ -194,6 +241,7 @@ mod tests {
                 Flag {
                     display_name: "Music".into(),
                     category: "".to_string(),
+                    emoji: None,
                 },
             ),
             (
@@ -201,6 +249,7 @@ mod tests {
                 Flag {
                     display_name: "Moderation".into(),
                     category: "".to_string(),
+                    emoji: None,
                 },
             ),
             (
@@ -208,18 +257,19 @@ mod tests {
                 Flag {
                     display_name: "Utility".into(),
                     category: "".to_string(),
+                    emoji: None,
                 },
             ),
         ];
 
-        set_pack_tags(BTreeMap::from_iter(items))
+        set_pack_tags(BTreeMap::from_iter(items)).unwrap()
     }
 
     #[test]
     fn test_setting_flags() {
         lookup();
 
-        let sample = serde_json::to_value("music").unwrap();
+        let sample = serde_json::to_value(" Music ").unwrap();
         let tags =
             PackTags::parse_from_json(Some(sample)).expect("Successful parse from JSON Value.");
 
@@ -229,6 +279,7 @@ mod tests {
                 name: "music".to_string(),
                 display_name: "Music".to_string(),
                 category: "".to_string(),
+                emoji: None,
             })
         );
     }
@@ -0,0 +1,642 @@
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::fmt::{Debug, Formatter};
+use std::marker::PhantomData;
+use std::ops::Deref;
+
+use poem_openapi::registry::{MetaSchema, MetaSchemaRef, Registry};
+use poem_openapi::types::{ParseError, ParseFromJSON, ParseResult, ToJSON, Type};
+use scylla::cql_to_rust::{FromCqlVal, FromCqlValError};
+use scylla::frame::response::result::CqlValue;
+use scylla::frame::value::{Value, ValueTooBig};
+
+use crate::live::LiveValue;
+use crate::search::{fields, FilterExpr, FilterOp};
+use crate::tags::handler::validate_flags;
+use crate::tags::schema::{register_tag_names, tag_name_schema_ref};
+use crate::tags::{
+    filter_valid_tags, normalise_tag_name, tag_name, Flag, IntoFilter, InvalidEmoji, TagSet,
+    VisibleTag,
+};
+
+/// One of the tag registries, e.g. the bot or guild tags.
+pub trait TagRegistry: 'static {
+    /// The registry's name, e.g. `bot`, as used in reload events and metrics.
+    const NAME: &'static str;
+    /// The OpenAPI schema name of a single tag, e.g. `BotTag`.
+    const SCHEMA_NAME: &'static str;
+
+    fn lookup() -> &'static LiveValue<BTreeMap<String, Flag>>;
+
+    /// Loads a new registry, the current one is kept if any flag is invalid.
+    fn load(lookup: BTreeMap<String, Flag>) -> Result<(), InvalidEmoji> {
+        validate_flags(&lookup)?;
+        Self::lookup().set(lookup);
+
+        #[cfg(feature = "metrics")]
+        crate::metrics::record_tag_reload(Self::NAME);
+
+        Ok(())
+    }
+}
+
+/// The tags of a listing which may have many tags from the registry `R`.
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
+pub struct Tags<R> {
+    inner: Vec<VisibleTag>,
+    _registry: PhantomData<fn() -> R>,
+}
+
+impl<R: TagRegistry> Tags<R> {
+    pub fn from_raw(flags: &[String]) -> Self {
+        let lookup = R::lookup();
+        let inner = filter_valid_tags(flags.iter(), lookup.load().as_ref());
+        Self::from_visible(inner)
+    }
+
+    pub fn as_raw(&self) -> Vec<String> {
+        self.inner.iter().map(|v| v.name.to_string()).collect()
+    }
+
+    /// The tags bound as a `set<frozen<visible_tag>>`.
+    ///
+    /// Binding [Tags] itself writes the bare names, for `set<text>` columns
+    /// which have not been migrated yet. Both are read back by `FromCqlVal`.
+    pub fn as_udts(&self) -> &[VisibleTag] {
+        &self.inner
+    }
+}
+
+impl<R> Default for Tags<R> {
+    fn default() -> Self {
+        Self {
+            inner: vec![],
+            _registry: PhantomData,
+        }
+    }
+}
+
+impl<R> Clone for Tags<R> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            _registry: PhantomData,
+        }
+    }
+}
+
+impl<R> Deref for Tags<R> {
+    type Target = [VisibleTag];
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+#[cfg(feature = "rkyv")]
+impl<R> Deref for ArchivedTags<R> {
+    type Target = [crate::tags::ArchivedVisibleTag];
+
+    fn deref(&self) -> &Self::Target {
+        self.inner.as_slice()
+    }
+}
+
+impl<R> Debug for Tags<R> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self.inner)
+    }
+}
+
+#[cfg(feature = "bincode")]
+impl<R> bincode::Encode for Tags<R> {
+    fn encode<E: bincode::enc::Encoder>(
+        &self,
+        encoder: &mut E,
+    ) -> Result<(), bincode::error::EncodeError> {
+        self.inner.encode(encoder)
+    }
+}
+
+#[cfg(feature = "bincode")]
+impl<R> bincode::Decode for Tags<R> {
+    fn decode<D: bincode::de::Decoder>(
+        decoder: &mut D,
+    ) -> Result<Self, bincode::error::DecodeError> {
+        Ok(Self {
+            inner: bincode::Decode::decode(decoder)?,
+            _registry: PhantomData,
+        })
+    }
+}
+
+#[cfg(feature = "bincode")]
+impl<'de, R> bincode::BorrowDecode<'de> for Tags<R> {
+    fn borrow_decode<D: bincode::de::BorrowDecoder<'de>>(
+        decoder: &mut D,
+    ) -> Result<Self, bincode::error::DecodeError> {
+        bincode::Decode::decode(decoder)
+    }
+}
+
+#[cfg(feature = "bincode")]
+impl<R: TagRegistry> crate::codec::VersionedDecode for Tags<R> {
+    const VERSION: u16 = 2;
+
+    fn migrations() -> crate::codec::Migrations<Self> {
+        crate::codec::Migrations::new().register(
+            1,
+            |old: Vec<crate::tags::handler::VisibleTagV1>| Self {
+                inner: old.into_iter().map(Into::into).collect(),
+                _registry: PhantomData,
+            },
+        )
+    }
+}
+
+/// Cached with its version so the layout can change without flushing the cache.
+#[cfg(feature = "redis")]
+impl<R: TagRegistry> redis::ToRedisArgs for Tags<R> {
+    fn write_redis_args<W: ?Sized + redis::RedisWrite>(&self, out: &mut W) {
+        crate::types::redis::write_versioned(self, out)
+    }
+}
+
+#[cfg(feature = "redis")]
+impl<R: TagRegistry> redis::FromRedisValue for Tags<R> {
+    fn from_redis_value(v: &redis::Value) -> redis::RedisResult<Self> {
+        crate::types::redis::read_versioned(v)
+    }
+}
+
+impl<R> serde::Serialize for Tags<R> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serde::Serialize::serialize(&self.inner, serializer)
+    }
+}
+
+impl<'de, R> serde::Deserialize<'de> for Tags<R> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let inner: Vec<VisibleTag> = Vec::deserialize(deserializer)?;
+        Ok(Self {
+            inner,
+            _registry: PhantomData,
+        })
+    }
+}
+
+impl<R: TagRegistry> Type for Tags<R> {
+    const IS_REQUIRED: bool = false;
+    type RawValueType = Self;
+    type RawElementValueType = <Vec<VisibleTag> as Type>::RawElementValueType;
+
+    fn name() -> Cow<'static, str> {
+        Cow::from(format!("Tags<{}>", R::SCHEMA_NAME))
+    }
+
+    fn schema_ref() -> MetaSchemaRef {
+        MetaSchemaRef::Inline(Box::new(MetaSchema {
+            items: Some(Box::new(tag_name_schema_ref(R::SCHEMA_NAME))),
+            ..MetaSchema::new("array")
+        }))
+    }
+
+    fn as_raw_value(&self) -> Option<&Self::RawValueType> {
+        Some(self)
+    }
+
+    fn register(registry: &mut Registry) {
+        <VisibleTag as Type>::register(registry);
+        register_tag_names::<Self>(registry, R::SCHEMA_NAME, R::lookup().load().as_ref());
+    }
+
+    fn raw_element_iter<'a>(
+        &'a self,
+    ) -> Box<dyn Iterator<Item = &'a Self::RawElementValueType> + 'a> {
+        self.inner.raw_element_iter()
+    }
+}
+
+impl<R: TagRegistry> ParseFromJSON for Tags<R> {
+    fn parse_from_json(value: Option<serde_json::Value>) -> ParseResult<Self> {
+        if let Some(val) = value {
+            let flags: Vec<String> = match serde_json::from_value(val) {
+                Ok(flags) => flags,
+                Err(e) => return Err(ParseError::custom(format!("Cannot derive tags: {}", e))),
+            };
+
+            let lookup = R::lookup();
+            let tags = lookup.load();
+
+            let mut inner = vec![];
+            for flag_name in flags {
+                let flag_name = normalise_tag_name(&flag_name);
+                let flag = match tags.get(&flag_name) {
+                    Some(v) => v,
+                    None => {
+                        return Err(ParseError::custom(format!("Unknown tag: {:?}", flag_name)))
+                    }
+                };
+
+                inner.push(flag.to_visible(flag_name))
+            }
+
+            Ok(Self::from_visible(inner))
+        } else {
+            Err(ParseError::custom("Cannot derive tags from null."))
+        }
+    }
+}
+
+impl<R: TagRegistry> ToJSON for Tags<R> {
+    fn to_json(&self) -> Option<serde_json::Value> {
+        self.inner
+            .iter()
+            .map(|v| v.name.clone())
+            .collect::<Vec<_>>()
+            .to_json()
+    }
+}
+
+impl<R: TagRegistry> Value for Tags<R> {
+    fn serialize(&self, buf: &mut Vec<u8>) -> Result<(), ValueTooBig> {
+        let flags = self.as_raw();
+        flags.serialize(buf)?;
+
+        Ok(())
+    }
+}
+
+impl<R: TagRegistry> FromCqlVal<Option<CqlValue>> for Tags<R> {
+    fn from_cql(cql_val: Option<CqlValue>) -> Result<Self, FromCqlValError> {
+        let values = match cql_val {
+            Some(CqlValue::Set(items)) => items,
+            _ => return Ok(Self::default()),
+        };
+
+        let iter = values.iter().filter_map(tag_name);
+
+        let lookup = R::lookup();
+        let inner = filter_valid_tags(iter, lookup.load().as_ref());
+
+        Ok(Self::from_visible(inner))
+    }
+}
+
+impl<R: TagRegistry> TagSet for Tags<R> {
+    fn registry() -> &'static LiveValue<BTreeMap<String, Flag>> {
+        R::lookup()
+    }
+
+    fn from_visible(inner: Vec<VisibleTag>) -> Self {
+        Self {
+            inner,
+            _registry: PhantomData,
+        }
+    }
+}
+
+impl<R> IntoFilter for Tags<R> {
+    #[inline]
+    fn into_filter(self) -> FilterExpr {
+        FilterExpr::all(
+            self.inner
+                .into_iter()
+                .map(|v| FilterExpr::condition(fields::TAGS, FilterOp::Eq, v.name)),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::FilterBackend;
+    use crate::tags::{set_bot_tags, with_tag_enum_schemas, BotTags, TagEnumSpec};
+
+    fn lookup() {
+        let items = vec![
+            (
+                "music".into(),
+                Flag {
+                    display_name: "Music".into(),
+                    category: "".to_string(),
+                    emoji: None,
+                },
+            ),
+            (
+                "moderation".into(),
+                Flag {
+                    display_name: "Moderation".into(),
+                    category: "".to_string(),
+                    emoji: None,
+                },
+            ),
+            (
+                "utility".into(),
+                Flag {
+                    display_name: "Utility".into(),
+                    category: "".to_string(),
+                    emoji: None,
+                },
+            ),
+        ];
+
+        set_bot_tags(BTreeMap::from_iter(items)).unwrap()
+    }
+
+    #[test]
+    fn test_setting_flags() {
+        lookup();
+
+        let sample = serde_json::to_value(vec!["music", "hello", "utility"]).unwrap();
+        assert!(BotTags::parse_from_json(Some(sample)).is_err());
+
+        let sample = serde_json::to_value(vec![" Music", "UTILITY "]).unwrap();
+        let tags =
+            BotTags::parse_from_json(Some(sample)).expect("Successful parse from JSON Value.");
+
+        assert_eq!(
+            tags.inner,
+            vec![
+                VisibleTag {
+                    name: "music".to_string(),
+                    display_name: "Music".into(),
+                    category: "".to_string(),
+                    emoji: None,
+                },
+                VisibleTag {
+                    name: "utility".to_string(),
+                    display_name: "Utility".into(),
+                    category: "".to_string(),
+                    emoji: None,
+                },
+            ],
+        );
+    }
+
+    #[cfg(feature = "redis")]
+    #[test]
+    fn test_redis_round_trip() {
+        use redis::{FromRedisValue, ToRedisArgs};
+
+        lookup();
+        let tags = BotTags::from_raw(&["music".to_string(), "utility".to_string()]);
+
+        let mut args = tags.to_redis_args();
+        let cached = BotTags::from_redis_value(&redis::Value::Data(args.remove(0))).unwrap();
+        assert_eq!(cached.inner, tags.inner);
+        assert!(BotTags::from_redis_value(&redis::Value::Data(vec![9])).is_err());
+    }
+
+    #[cfg(feature = "graphql")]
+    #[test]
+    fn test_graphql_scalar() {
+        use async_graphql::{ScalarType, Value};
+
+        lookup();
+        let value = Value::List(vec![Value::from("music"), Value::from("utility")]);
+        let tags = <BotTags as ScalarType>::parse(value.clone()).unwrap();
+        assert_eq!(tags.len(), 2);
+        assert_eq!(ScalarType::to_value(&tags), value);
+
+        let unknown = Value::List(vec![Value::from("cheese")]);
+        assert!(<BotTags as ScalarType>::parse(unknown).is_err());
+    }
+
+    fn bot_tag_schemas() -> String {
+        let mut registry = Registry::new();
+        BotTags::register(&mut registry);
+        serde_json::to_string(&registry.schemas).unwrap()
+    }
+
+    #[test]
+    fn test_schema_lists_loaded_tags() {
+        lookup();
+
+        let (schemas, schema) =
+            with_tag_enum_schemas(|| (bot_tag_schemas(), BotTags::schema_ref()));
+        let schemas: serde_json::Value = serde_json::from_str(&schemas).unwrap();
+        assert_eq!(
+            schemas["BotTag"]["enum"],
+            serde_json::json!(["moderation", "music", "utility"])
+        );
+
+        assert_eq!(BotTags::name(), "Tags<BotTag>");
+        let schema = serde_json::to_value(schema).unwrap();
+        assert_eq!(schema["type"], "array");
+        assert_eq!(schema["items"]["$ref"], "#/components/schemas/BotTag");
+    }
+
+    #[test]
+    fn test_schema_without_tag_enums() {
+        lookup();
+
+        let schemas: serde_json::Value = serde_json::from_str(&bot_tag_schemas()).unwrap();
+        assert!(schemas.get("BotTag").is_none());
+
+        let schema = serde_json::to_value(BotTags::schema_ref()).unwrap();
+        assert_eq!(schema["type"], "array");
+        assert_eq!(schema["items"]["type"], "string");
+    }
+
+    #[test]
+    fn test_tag_enum_spec_rebuilds_on_reload() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+
+        rt.block_on(async {
+            lookup();
+
+            let builds = Arc::new(AtomicUsize::new(0));
+            let counter = builds.clone();
+            let spec = Arc::new(TagEnumSpec::new(move || {
+                counter.fetch_add(1, Ordering::Relaxed);
+                bot_tag_schemas()
+            }));
+            assert!(spec.get().contains("moderation"));
+
+            let handle = spec.clone().spawn_refresh();
+            tokio::task::yield_now().await;
+            let before = builds.load(Ordering::Relaxed);
+
+            lookup();
+            for _ in 0..100 {
+                if builds.load(Ordering::Relaxed) > before {
+                    break;
+                }
+                tokio::time::sleep(std::time::Duration::from_millis(1)).await;
+            }
+
+            assert!(builds.load(Ordering::Relaxed) > before);
+            assert!(spec.get().contains("moderation"));
+            handle.abort();
+        });
+    }
+
+    #[test]
+    fn test_loading_flags() {
+        lookup();
+
+        let sample = vec![
+            "music".into(),
+            "moderation".into(),
+            "utility".into(),
+            "Cheese".into(),
+        ];
+
+        let tags = BotTags::from_raw(&sample);
+
+        assert_eq!(
+            tags.inner,
+            vec![
+                VisibleTag {
+                    name: "music".to_string(),
+                    display_name: "Music".to_string(),
+                    category: "".to_string(),
+                    emoji: None,
+                },
+                VisibleTag {
+                    name: "moderation".to_string(),
+                    display_name: "Moderation".to_string(),
+                    category: "".to_string(),
+                    emoji: None,
+                },
+                VisibleTag {
+                    name: "utility".to_string(),
+                    display_name: "Utility".to_string(),
+                    category: "".to_string(),
+                    emoji: None,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn test_cql_round_trip() {
+        lookup();
+
+        let tags = BotTags::from_raw(&["music".into(), "utility".into()]);
+        let stored = CqlValue::Set(vec![
+            CqlValue::Text("music".into()),
+            CqlValue::Text("cheese".into()),
+            CqlValue::Text("utility".into()),
+        ]);
+
+        let loaded = BotTags::from_cql(Some(stored)).unwrap();
+        assert_eq!(loaded.inner, tags.inner);
+        assert!(BotTags::from_cql(None).unwrap().as_raw().is_empty());
+    }
+
+    /// Reads a bound `set<frozen<visible_tag>>` back the way scylla returns it.
+    fn read_udt_set(buf: &[u8]) -> CqlValue {
+        fn take<'a>(buf: &mut &'a [u8]) -> Option<&'a [u8]> {
+            let len = i32::from_be_bytes(buf[..4].try_into().unwrap());
+            *buf = &buf[4..];
+            let len = usize::try_from(len).ok()?;
+            let (value, rest) = buf.split_at(len);
+            *buf = rest;
+            Some(value)
+        }
+
+        let mut buf = take(&mut &buf[..]).unwrap();
+        let count = i32::from_be_bytes(buf[..4].try_into().unwrap());
+        buf = &buf[4..];
+
+        let items = (0..count)
+            .map(|_| {
+                let mut udt = take(&mut buf).unwrap();
+                let fields = ["name", "display_name", "category", "emoji"]
+                    .into_iter()
+                    .map(|name| {
+                        let value = take(&mut udt)
+                            .map(|v| CqlValue::Text(String::from_utf8(v.to_vec()).unwrap()));
+                        (name.to_string(), value)
+                    })
+                    .collect();
+
+                CqlValue::UserDefinedType {
+                    keyspace: "dlist".into(),
+                    type_name: "visible_tag".into(),
+                    fields,
+                }
+            })
+            .collect();
+
+        CqlValue::Set(items)
+    }
+
+    #[test]
+    fn test_cql_udt_round_trip() {
+        lookup();
+
+        let tags = BotTags::from_raw(&["music".into(), "utility".into()]);
+        let mut buf = vec![];
+        tags.as_udts().serialize(&mut buf).unwrap();
+
+        let stored = read_udt_set(&buf);
+        assert!(matches!(
+            &stored,
+            CqlValue::Set(items) if items.iter().all(|v| v.as_udt().is_some())
+        ));
+
+        let loaded = BotTags::from_cql(Some(stored)).unwrap();
+        assert_eq!(loaded.inner, tags.inner);
+
+        // Binding the tags directly still writes the bare names.
+        let mut raw = vec![];
+        tags.serialize(&mut raw).unwrap();
+        let mut names = vec![];
+        tags.as_raw().serialize(&mut names).unwrap();
+        assert_eq!(raw, names);
+    }
+
+    #[test]
+    fn test_filter_uses_tag_names() {
+        lookup();
+
+        let tags = BotTags::from_raw(&["music".into(), "utility".into()]);
+
+        assert_eq!(
+            tags.clone()
+                .into_filter()
+                .render(FilterBackend::Meilisearch),
+            r#"tags = "music" AND tags = "utility""#,
+        );
+        assert_eq!(
+            tags.into_filter().render(FilterBackend::Lnx),
+            r#"tags:"music" AND tags:"utility""#,
+        );
+    }
+
+    #[cfg(feature = "rkyv")]
+    #[test]
+    fn test_archived_tags() {
+        lookup();
+
+        let tags = BotTags::from_raw(&["music".into(), "utility".into()]);
+        let bytes = rkyv::to_bytes::<rkyv::rancor::Error>(&tags).unwrap();
+        let archived =
+            rkyv::access::<crate::tags::ArchivedBotTags, rkyv::rancor::Error>(&bytes).unwrap();
+
+        assert_eq!(archived.len(), 2);
+        assert_eq!(archived[1].display_name, "Utility");
+        assert!(archived[0].emoji.is_none());
+        assert!(
+            rkyv::access::<crate::tags::ArchivedBotTags, rkyv::rancor::Error>(&bytes[1..]).is_err()
+        );
+    }
+}
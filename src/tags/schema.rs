@@ -0,0 +1,116 @@
+use std::cell::Cell;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use futures_util::StreamExt;
+use poem_openapi::registry::{MetaSchema, MetaSchemaRef, Registry};
+use poem_openapi::types::Type;
+use tokio::task::JoinHandle;
+use tokio_stream::wrappers::WatchStream;
+
+use crate::live::LiveValue;
+use crate::tags::{get_bot_tags, get_guild_tags, get_pack_tags, Flag};
+
+thread_local! {
+    static TAG_ENUM_SCHEMAS: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Runs `f`, usually generating the OpenAPI spec, with the loaded tag names
+/// listed as an enum in the schemas of the tag sets, registered as `BotTag`,
+/// `GuildTag` and `PackTag`. Outside of `f` tags are plain strings.
+///
+/// The names are read when the spec is generated, use [TagEnumSpec] to keep
+/// a spec up to date as registries are reloaded.
+pub fn with_tag_enum_schemas<T>(f: impl FnOnce() -> T) -> T {
+    struct Restore(bool);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            TAG_ENUM_SCHEMAS.with(|v| v.set(self.0));
+        }
+    }
+
+    let _restore = Restore(TAG_ENUM_SCHEMAS.with(|v| v.replace(true)));
+    f()
+}
+
+#[inline]
+fn tag_enum_schemas() -> bool {
+    TAG_ENUM_SCHEMAS.with(Cell::get)
+}
+
+/// A reference to the registered enum of tag names, or a plain string when
+/// tag enums are disabled.
+pub(crate) fn tag_name_schema_ref(name: &str) -> MetaSchemaRef {
+    if tag_enum_schemas() {
+        MetaSchemaRef::Reference(name.to_string())
+    } else {
+        String::schema_ref()
+    }
+}
+
+/// Registers the names in `lookup` as the enum `name` if tag enums are enabled.
+pub(crate) fn register_tag_names<T>(
+    registry: &mut Registry,
+    name: &str,
+    lookup: &BTreeMap<String, Flag>,
+) {
+    if !tag_enum_schemas() {
+        return;
+    }
+
+    registry.create_schema::<T, _>(name.to_string(), |_| MetaSchema {
+        enum_items: lookup
+            .keys()
+            .map(|v| serde_json::Value::String(v.clone()))
+            .collect(),
+        ..MetaSchema::new("string")
+    });
+}
+
+/// An OpenAPI spec generated with [with_tag_enum_schemas], rebuilt whenever
+/// one of the tag registries is reloaded so the listed names stay current.
+pub struct TagEnumSpec {
+    build: Box<dyn Fn() -> String + Send + Sync>,
+    spec: LiveValue<String>,
+}
+
+impl TagEnumSpec {
+    /// Generates the spec with `build`, e.g. `move || api.spec()`.
+    pub fn new(build: impl Fn() -> String + Send + Sync + 'static) -> Self {
+        let spec = with_tag_enum_schemas(&build);
+
+        Self {
+            build: Box::new(build),
+            spec: LiveValue::new(spec),
+        }
+    }
+
+    /// The current spec.
+    #[inline]
+    pub fn get(&self) -> Arc<String> {
+        self.spec.get()
+    }
+
+    /// Generates the spec again from the loaded registries.
+    pub fn rebuild(&self) {
+        self.spec.set(with_tag_enum_schemas(&self.build));
+    }
+
+    /// Spawns a task rebuilding the spec after every registry reload.
+    pub fn spawn_refresh(self: Arc<Self>) -> JoinHandle<()> {
+        let mut reloads = futures_util::stream::select_all(
+            [get_bot_tags(), get_guild_tags(), get_pack_tags()]
+                .map(|registry| WatchStream::from_changes(registry.subscribe())),
+        );
+
+        tokio::spawn(async move {
+            // Catches reloads made before the task subscribed.
+            self.rebuild();
+
+            while reloads.next().await.is_some() {
+                self.rebuild();
+            }
+        })
+    }
+}
@@ -0,0 +1,3 @@
+pub mod progress;
+
+pub use progress::{poll, Progress, ProgressReporter, TaskState};
@@ -0,0 +1,244 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use poem_openapi::{Enum, Object};
+use scylla::transport::errors::QueryError;
+use scylla::Session;
+use tokio::time::Instant;
+use uuid::Uuid;
+
+use crate::db::{insert_using_ttl, WithTtl};
+use crate::errors::{ApiError, ApiResult};
+use crate::responses::ApiOk;
+use crate::types::Timestamp;
+
+pub const PROGRESS_TABLE: &str = "task_progress";
+
+/// How long progress rows are kept after their last update.
+pub const PROGRESS_TTL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// The default minimum time between progress writes.
+pub const DEFAULT_FLUSH_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(
+    Enum,
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    Hash,
+    strum::AsRefStr,
+    strum::EnumString,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[oai(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum TaskState {
+    #[default]
+    Queued,
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+impl TaskState {
+    #[inline]
+    pub fn is_finished(&self) -> bool {
+        matches!(self, Self::Completed | Self::Failed | Self::Cancelled)
+    }
+}
+
+cql_text_enum!(TaskState);
+
+/// The progress of a long running task such as a reindex or data export.
+#[derive(
+    Object,
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    scylla::FromRow,
+    scylla::ValueList,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+pub struct Progress {
+    pub task_id: Uuid,
+    pub state: TaskState,
+    pub processed: i64,
+    /// The number of items to process if it is known upfront.
+    pub total: Option<i64>,
+    /// The number of items which failed to process.
+    pub errors: i64,
+    pub updated_at: Timestamp,
+}
+
+impl Progress {
+    pub fn new(task_id: Uuid, total: Option<i64>) -> Self {
+        Self {
+            task_id,
+            state: TaskState::Queued,
+            processed: 0,
+            total,
+            errors: 0,
+            updated_at: Timestamp::default(),
+        }
+    }
+
+    /// The completed fraction of the task between `0.0` and `1.0`.
+    pub fn fraction(&self) -> Option<f64> {
+        let total = self.total?;
+
+        if total <= 0 {
+            return Some(1.0);
+        }
+
+        Some((self.processed as f64 / total as f64).clamp(0.0, 1.0))
+    }
+}
+
+/// Reports the progress of a task, writes are throttled so tight loops can
+/// report every item without hammering the database.
+///
+/// State changes are always written straight away.
+pub struct ProgressReporter {
+    session: Arc<Session>,
+    progress: Progress,
+    interval: Duration,
+    last_flush: Option<Instant>,
+}
+
+impl ProgressReporter {
+    pub fn new(session: Arc<Session>, task_id: Uuid, total: Option<i64>) -> Self {
+        Self {
+            session,
+            progress: Progress::new(task_id, total),
+            interval: DEFAULT_FLUSH_INTERVAL,
+            last_flush: None,
+        }
+    }
+
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    #[inline]
+    pub fn progress(&self) -> &Progress {
+        &self.progress
+    }
+
+    pub async fn start(&mut self) -> Result<(), QueryError> {
+        self.set_state(TaskState::Running).await
+    }
+
+    /// Records `processed` items with `errors` of them failing.
+    pub async fn advance(&mut self, processed: i64, errors: i64) -> Result<(), QueryError> {
+        self.progress.processed += processed;
+        self.progress.errors += errors;
+
+        if self.should_flush(Instant::now()) {
+            self.flush().await?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn finish(&mut self, state: TaskState) -> Result<(), QueryError> {
+        self.set_state(state).await
+    }
+
+    async fn set_state(&mut self, state: TaskState) -> Result<(), QueryError> {
+        self.progress.state = state;
+        self.flush().await
+    }
+
+    fn should_flush(&self, now: Instant) -> bool {
+        self.last_flush
+            .map(|v| now.saturating_duration_since(v) >= self.interval)
+            .unwrap_or(true)
+    }
+
+    async fn flush(&mut self) -> Result<(), QueryError> {
+        self.progress.updated_at = Timestamp::default();
+        store(&self.session, &self.progress).await?;
+        self.last_flush = Some(Instant::now());
+        Ok(())
+    }
+}
+
+pub async fn store(session: &Session, progress: &Progress) -> Result<(), QueryError> {
+    let query = insert_using_ttl(&format!(
+        "INSERT INTO {} (task_id, state, processed, total, errors, updated_at) \
+        VALUES (?, ?, ?, ?, ?, ?);",
+        PROGRESS_TABLE,
+    ))?;
+
+    session
+        .query(query, WithTtl::new(progress, PROGRESS_TTL))
+        .await?;
+
+    Ok(())
+}
+
+pub async fn fetch(session: &Session, task_id: Uuid) -> Result<Option<Progress>, QueryError> {
+    let query = format!(
+        "SELECT task_id, state, processed, total, errors, updated_at FROM {} WHERE task_id = ?;",
+        PROGRESS_TABLE
+    );
+
+    let row = session
+        .query(query, (task_id,))
+        .await?
+        .maybe_first_row_typed::<Progress>()
+        .map_err(|e| {
+            QueryError::InvalidMessage(format!(
+                "Failed to parse row from {}: {}",
+                PROGRESS_TABLE, e
+            ))
+        })?;
+
+    Ok(row)
+}
+
+/// Looks up a task's progress for the admin UI's poll endpoints.
+pub async fn poll(session: &Session, task_id: Uuid) -> ApiResult<ApiOk<Progress>> {
+    match fetch(session, task_id).await? {
+        Some(progress) => Ok(ApiOk::new(progress)),
+        None => Err(ApiError::not_found(format!("Unknown task {}", task_id))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fraction() {
+        let mut progress = Progress::new(Uuid::new_v4(), Some(200));
+        progress.processed = 50;
+        assert_eq!(progress.fraction(), Some(0.25));
+
+        progress.processed = 300;
+        assert_eq!(progress.fraction(), Some(1.0));
+
+        assert_eq!(Progress::new(Uuid::new_v4(), None).fraction(), None);
+        assert_eq!(Progress::new(Uuid::new_v4(), Some(0)).fraction(), Some(1.0));
+    }
+
+    #[test]
+    fn test_wire_format() {
+        let progress = Progress::new(Uuid::nil(), Some(10));
+        let value = serde_json::to_value(&progress).unwrap();
+
+        assert_eq!(value["state"], "queued");
+        assert_eq!(value["total"], 10);
+        assert!(TaskState::Cancelled.is_finished());
+        assert!(!TaskState::Running.is_finished());
+    }
+}
@@ -0,0 +1,12 @@
+//! The span field names shared by every service, so traces and logs can be
+//! correlated across services.
+
+pub const REQUEST_ID: &str = "request_id";
+pub const USER_ID: &str = "user_id";
+pub const HTTP_METHOD: &str = "http.method";
+pub const HTTP_ROUTE: &str = "http.route";
+pub const HTTP_STATUS: &str = "http.status_code";
+pub const DB_SYSTEM: &str = "db.system";
+pub const DB_OPERATION: &str = "db.operation";
+pub const DB_STATEMENT: &str = "db.statement";
+pub const ERROR: &str = "error";
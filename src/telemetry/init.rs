@@ -0,0 +1,81 @@
+use std::fmt::{Display, Formatter};
+
+use tracing_subscriber::EnvFilter;
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum LogFormat {
+    /// Human readable output for local development.
+    #[default]
+    Pretty,
+    /// One JSON object per line, used in production so logs can be indexed.
+    Json,
+}
+
+#[derive(Clone, Debug)]
+pub struct TelemetryConfig {
+    format: LogFormat,
+    default_directive: String,
+}
+
+impl Default for TelemetryConfig {
+    fn default() -> Self {
+        Self {
+            format: LogFormat::default(),
+            default_directive: "info".to_string(),
+        }
+    }
+}
+
+impl TelemetryConfig {
+    /// Uses JSON output when `LOG_FORMAT=json` is set.
+    pub fn from_env() -> Self {
+        let format = match std::env::var("LOG_FORMAT") {
+            Ok(v) if v.eq_ignore_ascii_case("json") => LogFormat::Json,
+            _ => LogFormat::Pretty,
+        };
+
+        Self::default().with_format(format)
+    }
+
+    pub fn with_format(mut self, format: LogFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// The filter used when `RUST_LOG` is not set.
+    pub fn with_default_directive(mut self, directive: impl Into<String>) -> Self {
+        self.default_directive = directive.into();
+        self
+    }
+}
+
+#[derive(Debug)]
+pub struct TelemetryError(String);
+
+impl Display for TelemetryError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "failed to initialise telemetry: {}", self.0)
+    }
+}
+
+impl std::error::Error for TelemetryError {}
+
+/// Installs the global tracing subscriber, filtered by `RUST_LOG`.
+pub fn init(config: TelemetryConfig) -> Result<(), TelemetryError> {
+    let filter = EnvFilter::try_from_default_env()
+        .or_else(|_| EnvFilter::try_new(&config.default_directive))
+        .map_err(|e| TelemetryError(e.to_string()))?;
+
+    let builder = tracing_subscriber::fmt().with_env_filter(filter);
+    let result = match config.format {
+        LogFormat::Pretty => builder.try_init(),
+        LogFormat::Json => builder
+            .json()
+            .flatten_event(true)
+            .with_current_span(true)
+            .with_span_list(true)
+            .try_init(),
+    };
+
+    result.map_err(|e| TelemetryError(e.to_string()))
+}
@@ -0,0 +1,101 @@
+use poem::{Endpoint, IntoResponse, Middleware, PathPattern, Request, Response, Result};
+use tracing::field::Empty;
+use tracing::{Instrument, Span};
+
+use crate::telemetry::fields::{HTTP_METHOD, HTTP_ROUTE, HTTP_STATUS, REQUEST_ID, USER_ID};
+use crate::types::{JsSafeBigInt, RequestId};
+
+/// Records the authenticated user on the current request span.
+pub fn record_user_id(user_id: JsSafeBigInt) {
+    Span::current().record(USER_ID, user_id.0);
+}
+
+/// Wraps every request in a `request` span with the standard fields.
+///
+/// The request id is read from the request data, so this must be applied
+/// inside of [PropagateRequestId](crate::middleware::PropagateRequestId).
+#[derive(Debug, Default, Copy, Clone)]
+pub struct RequestSpan;
+
+impl<E: Endpoint> Middleware<E> for RequestSpan {
+    type Output = RequestSpanEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        RequestSpanEndpoint { inner: ep }
+    }
+}
+
+pub struct RequestSpanEndpoint<E> {
+    inner: E,
+}
+
+#[poem::async_trait]
+impl<E: Endpoint> Endpoint for RequestSpanEndpoint<E> {
+    type Output = Response;
+
+    async fn call(&self, req: Request) -> Result<Self::Output> {
+        let span = tracing::info_span!(
+            "request",
+            { REQUEST_ID } = Empty,
+            { HTTP_METHOD } = %req.method(),
+            { HTTP_ROUTE } = Empty,
+            { HTTP_STATUS } = Empty,
+            { USER_ID } = Empty,
+        );
+        if let Some(id) = req.data::<RequestId>() {
+            span.record(REQUEST_ID, tracing::field::display(id));
+        }
+
+        let result = self
+            .inner
+            .call(req)
+            .instrument(span.clone())
+            .await
+            .map(IntoResponse::into_response);
+
+        let (route, status) = match &result {
+            Ok(resp) => (resp.data::<PathPattern>(), resp.status()),
+            Err(err) => (err.data::<PathPattern>(), err.status()),
+        };
+        if let Some(route) = route {
+            span.record(HTTP_ROUTE, &*route.0);
+        }
+        span.record(HTTP_STATUS, status.as_u16());
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::middleware::PropagateRequestId;
+    use poem::http::{StatusCode, Uri};
+    use poem::{handler, EndpointExt, Route};
+
+    #[handler]
+    fn hello() -> &'static str {
+        "hello"
+    }
+
+    #[test]
+    fn test_passes_responses_through() {
+        let app = Route::new()
+            .at("/", hello)
+            .with(RequestSpan)
+            .with(PropagateRequestId);
+
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        rt.block_on(async {
+            let req = Request::builder().uri(Uri::from_static("/")).finish();
+            assert_eq!(app.get_response(req).await.status(), StatusCode::OK);
+
+            let req = Request::builder()
+                .uri(Uri::from_static("/missing"))
+                .finish();
+            assert_eq!(app.get_response(req).await.status(), StatusCode::NOT_FOUND);
+        });
+    }
+}
@@ -0,0 +1,10 @@
+pub mod fields;
+#[cfg(feature = "telemetry")]
+mod init;
+mod middleware;
+mod query;
+
+#[cfg(feature = "telemetry")]
+pub use init::{init, LogFormat, TelemetryConfig, TelemetryError};
+pub use middleware::{record_user_id, RequestSpan, RequestSpanEndpoint};
+pub use query::{instrument_query, query_span};
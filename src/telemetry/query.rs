@@ -0,0 +1,26 @@
+use std::future::Future;
+
+use tracing::{Instrument, Span};
+
+use crate::telemetry::fields::{DB_OPERATION, DB_STATEMENT, DB_SYSTEM};
+
+/// A span for a single scylla query with the standard database fields.
+pub fn query_span(statement: &str) -> Span {
+    let operation = statement
+        .split_whitespace()
+        .next()
+        .unwrap_or_default()
+        .to_ascii_uppercase();
+
+    tracing::debug_span!(
+        "scylla.query",
+        { DB_SYSTEM } = "scylla",
+        { DB_OPERATION } = %operation,
+        { DB_STATEMENT } = statement,
+    )
+}
+
+/// Runs the query future inside a [query_span].
+pub async fn instrument_query<F: Future>(statement: &str, fut: F) -> F::Output {
+    fut.instrument(query_span(statement)).await
+}
@@ -0,0 +1,3 @@
+pub mod vcr;
+
+pub use vcr::{Cassette, Interaction, RecordedRequest, RecordedResponse, Vcr, VcrMode};
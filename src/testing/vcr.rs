@@ -0,0 +1,245 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Headers which are always redacted before an interaction is recorded.
+pub const REDACTED_HEADERS: &[&str] = &["authorization", "cookie", "set-cookie"];
+pub const REDACTED: &str = "[REDACTED]";
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub url: String,
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct RecordedResponse {
+    pub status: u16,
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    pub body: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Interaction {
+    pub request: RecordedRequest,
+    pub response: RecordedResponse,
+}
+
+/// A set of recorded interactions stored as a JSON fixture.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Cassette {
+    pub interactions: Vec<Interaction>,
+}
+
+impl Cassette {
+    pub fn load(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let data = std::fs::read_to_string(path)?;
+        serde_json::from_str(&data).map_err(std::io::Error::from)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        if let Some(parent) = path.as_ref().parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let data = serde_json::to_string_pretty(self).map_err(std::io::Error::from)?;
+        std::fs::write(path, data)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum VcrMode {
+    /// Real requests are made and their responses are recorded.
+    Record,
+    /// Responses are served from the cassette, no requests are made.
+    Replay,
+}
+
+/// Records and replays HTTP interactions for offline tests.
+///
+/// HTTP clients call `replay` before making a request and `record` after
+/// receiving a response. Interactions with the same method and url are
+/// replayed in the order they were recorded.
+pub struct Vcr {
+    mode: VcrMode,
+    path: PathBuf,
+    secrets: Vec<String>,
+    recorded: Mutex<Vec<Interaction>>,
+    pending: Mutex<BTreeMap<(String, String), VecDeque<RecordedResponse>>>,
+}
+
+impl Vcr {
+    pub fn record(path: impl Into<PathBuf>) -> Self {
+        Self {
+            mode: VcrMode::Record,
+            path: path.into(),
+            secrets: vec![],
+            recorded: Mutex::new(vec![]),
+            pending: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    pub fn replay(path: impl Into<PathBuf>) -> std::io::Result<Self> {
+        let path = path.into();
+        let cassette = Cassette::load(&path)?;
+
+        let mut pending: BTreeMap<_, VecDeque<_>> = BTreeMap::new();
+        for interaction in cassette.interactions {
+            let key = (interaction.request.method, interaction.request.url);
+            pending
+                .entry(key)
+                .or_default()
+                .push_back(interaction.response);
+        }
+
+        Ok(Self {
+            mode: VcrMode::Replay,
+            path,
+            secrets: vec![],
+            recorded: Mutex::new(vec![]),
+            pending: Mutex::new(pending),
+        })
+    }
+
+    /// Records when `DLIST_VCR_RECORD` is set, otherwise replays the fixture.
+    pub fn from_env(path: impl Into<PathBuf>) -> std::io::Result<Self> {
+        if std::env::var_os("DLIST_VCR_RECORD").is_some() {
+            Ok(Self::record(path))
+        } else {
+            Self::replay(path)
+        }
+    }
+
+    /// Adds a value which is replaced wherever it appears in a recording, e.g. a bot token.
+    pub fn redact(mut self, secret: impl Into<String>) -> Self {
+        let secret = secret.into();
+        if !secret.is_empty() {
+            self.secrets.push(secret);
+        }
+        self
+    }
+
+    #[inline]
+    pub fn mode(&self) -> VcrMode {
+        self.mode
+    }
+
+    /// The next recorded response for the request.
+    pub fn next_response(&self, method: &str, url: &str) -> Option<RecordedResponse> {
+        let key = (method.to_uppercase(), self.scrub(url));
+        self.pending
+            .lock()
+            .unwrap()
+            .get_mut(&key)
+            .and_then(|v| v.pop_front())
+    }
+
+    pub fn push(&self, mut request: RecordedRequest, mut response: RecordedResponse) {
+        request.method = request.method.to_uppercase();
+        request.url = self.scrub(&request.url);
+        request.body = request.body.map(|v| self.scrub(&v));
+        self.scrub_headers(&mut request.headers);
+
+        response.body = self.scrub(&response.body);
+        self.scrub_headers(&mut response.headers);
+
+        self.recorded
+            .lock()
+            .unwrap()
+            .push(Interaction { request, response });
+    }
+
+    /// Writes the recorded interactions to the fixture, does nothing when replaying.
+    pub fn save(&self) -> std::io::Result<()> {
+        if self.mode != VcrMode::Record {
+            return Ok(());
+        }
+
+        let cassette = Cassette {
+            interactions: self.recorded.lock().unwrap().clone(),
+        };
+        cassette.save(&self.path)
+    }
+
+    fn scrub(&self, value: &str) -> String {
+        self.secrets.iter().fold(value.to_string(), |acc, secret| {
+            acc.replace(secret, REDACTED)
+        })
+    }
+
+    fn scrub_headers(&self, headers: &mut BTreeMap<String, String>) {
+        for (name, value) in headers.iter_mut() {
+            if REDACTED_HEADERS.contains(&name.to_lowercase().as_str()) {
+                *value = REDACTED.to_string();
+            } else {
+                *value = self.scrub(value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(url: &str) -> RecordedRequest {
+        RecordedRequest {
+            method: "get".to_string(),
+            url: url.to_string(),
+            headers: BTreeMap::from_iter([
+                ("Authorization".to_string(), "Bot abc.def".to_string()),
+                ("User-Agent".to_string(), "dlist".to_string()),
+            ]),
+            body: None,
+        }
+    }
+
+    fn response(body: &str) -> RecordedResponse {
+        RecordedResponse {
+            status: 200,
+            headers: BTreeMap::new(),
+            body: body.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_record_then_replay() {
+        let path = std::env::temp_dir()
+            .join(format!("dlist-vcr-{}", uuid::Uuid::new_v4()))
+            .join("invites.json");
+
+        let vcr = Vcr::record(&path).redact("abc.def");
+        vcr.push(
+            request("https://discord.com/api/v10/invites/abc"),
+            response(r#"{"token":"abc.def","code":"abc"}"#),
+        );
+        vcr.push(
+            request("https://discord.com/api/v10/invites/abc"),
+            response(r#"{"code":"abc","uses":2}"#),
+        );
+        vcr.save().unwrap();
+
+        let saved = std::fs::read_to_string(&path).unwrap();
+        assert!(!saved.contains("abc.def"));
+        assert!(saved.contains("\"Authorization\": \"[REDACTED]\""));
+
+        let vcr = Vcr::replay(&path).unwrap();
+        let url = "https://discord.com/api/v10/invites/abc";
+        assert_eq!(
+            vcr.next_response("GET", url).unwrap().body,
+            r#"{"token":"[REDACTED]","code":"abc"}"#
+        );
+        assert_eq!(
+            vcr.next_response("GET", url).unwrap().body,
+            r#"{"code":"abc","uses":2}"#
+        );
+        assert_eq!(vcr.next_response("GET", url), None);
+
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
@@ -0,0 +1,63 @@
+//! `bincode` encodings for the shared types which cannot be derived, enabled
+//! by the `bincode` feature.
+
+use bincode::de::{BorrowDecoder, Decoder};
+use bincode::enc::Encoder;
+use bincode::error::{DecodeError, EncodeError};
+use bincode::{BorrowDecode, Decode, Encode};
+
+use crate::codec::VersionedDecode;
+use crate::types::{DiscordUrl, JsSafeBigInt, Set, Timestamp};
+
+impl Encode for Timestamp {
+    fn encode<E: Encoder>(&self, encoder: &mut E) -> Result<(), EncodeError> {
+        self.0.timestamp().encode(encoder)
+    }
+}
+
+impl Decode for Timestamp {
+    fn decode<D: Decoder>(decoder: &mut D) -> Result<Self, DecodeError> {
+        let inner = i64::decode(decoder)?;
+        chrono::DateTime::from_timestamp(inner, 0)
+            .map(Self)
+            .ok_or_else(|| DecodeError::OtherString(format!("timestamp {} is out of range", inner)))
+    }
+}
+
+bincode::impl_borrow_decode!(Timestamp);
+
+impl<T: Encode + 'static, const MAX: usize> Encode for Set<T, MAX> {
+    fn encode<E: Encoder>(&self, encoder: &mut E) -> Result<(), EncodeError> {
+        self.0.encode(encoder)
+    }
+}
+
+impl<T: Decode + 'static, const MAX: usize> Decode for Set<T, MAX> {
+    fn decode<D: Decoder>(decoder: &mut D) -> Result<Self, DecodeError> {
+        Vec::<T>::decode(decoder).map(Self)
+    }
+}
+
+impl<'de, T: BorrowDecode<'de>, const MAX: usize> BorrowDecode<'de> for Set<T, MAX> {
+    fn borrow_decode<D: BorrowDecoder<'de>>(decoder: &mut D) -> Result<Self, DecodeError> {
+        Vec::<T>::borrow_decode(decoder).map(Self)
+    }
+}
+
+impl VersionedDecode for JsSafeBigInt {
+    const VERSION: u16 = 1;
+}
+
+impl VersionedDecode for Timestamp {
+    const VERSION: u16 = 1;
+}
+
+impl VersionedDecode for DiscordUrl {
+    const VERSION: u16 = 1;
+}
+
+/// Shares the version of its elements, payloads written with older element
+/// layouts are not migrated.
+impl<T: VersionedDecode, const MAX: usize> VersionedDecode for Set<T, MAX> {
+    const VERSION: u16 = T::VERSION;
+}
@@ -0,0 +1,128 @@
+use std::fmt::{Display, Formatter};
+use std::num::ParseIntError;
+use std::ops::Deref;
+use std::str::FromStr;
+
+#[cfg(feature = "bincode")]
+use bincode::{Decode, Encode};
+
+use serde::de::Error;
+use serde::{Deserializer, Serializer};
+
+use crate::types::PossibleInt;
+
+#[cfg_attr(feature = "bincode", derive(Decode, Encode))]
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+/// An integer which must be within `MIN..=MAX` when parsed from user input.
+pub struct BoundedInt<const MIN: i64, const MAX: i64>(pub i64);
+
+impl<const MIN: i64, const MAX: i64> BoundedInt<MIN, MAX> {
+    pub fn new(v: i64) -> Option<Self> {
+        (MIN..=MAX).contains(&v).then_some(Self(v))
+    }
+
+    /// Creates a new value, clamping it to within the bounds.
+    pub fn clamped(v: i64) -> Self {
+        Self(v.clamp(MIN, MAX))
+    }
+
+    pub(super) fn out_of_bounds(value: i64) -> InvalidBoundedInt {
+        InvalidBoundedInt::OutOfBounds {
+            value,
+            min: MIN,
+            max: MAX,
+        }
+    }
+}
+
+/// Why a string is not a valid [BoundedInt].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InvalidBoundedInt {
+    Parse(ParseIntError),
+    OutOfBounds { value: i64, min: i64, max: i64 },
+}
+
+impl Display for InvalidBoundedInt {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Parse(e) => write!(f, "{}", e),
+            Self::OutOfBounds { value, min, max } => write!(
+                f,
+                "Value {} is outside of the allowed range of {} to {}.",
+                value, min, max
+            ),
+        }
+    }
+}
+
+impl std::error::Error for InvalidBoundedInt {}
+
+impl<const MIN: i64, const MAX: i64> serde::Serialize for BoundedInt<MIN, MAX> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<'de, const MIN: i64, const MAX: i64> serde::Deserialize<'de> for BoundedInt<MIN, MAX> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let inner = PossibleInt::deserialize(deserializer)?;
+        let v = match inner {
+            PossibleInt::Int(v) => v,
+            PossibleInt::Str(v) => v.parse::<i64>().map_err(D::Error::custom)?,
+        };
+
+        Self::new(v).ok_or_else(|| D::Error::custom(Self::out_of_bounds(v)))
+    }
+}
+
+impl<const MIN: i64, const MAX: i64> Default for BoundedInt<MIN, MAX> {
+    fn default() -> Self {
+        Self::clamped(0)
+    }
+}
+
+impl<const MIN: i64, const MAX: i64> Display for BoundedInt<MIN, MAX> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl<const MIN: i64, const MAX: i64> Deref for BoundedInt<MIN, MAX> {
+    type Target = i64;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<const MIN: i64, const MAX: i64> FromStr for BoundedInt<MIN, MAX> {
+    type Err = InvalidBoundedInt;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let v = s.parse::<i64>().map_err(InvalidBoundedInt::Parse)?;
+        Self::new(v).ok_or_else(|| Self::out_of_bounds(v))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bounds_enforced() {
+        assert!(serde_json::from_str::<BoundedInt<1, 5>>("0").is_err());
+        assert!(serde_json::from_str::<BoundedInt<1, 5>>("6").is_err());
+        assert!(serde_json::from_str::<BoundedInt<1, 5>>(r#""3""#).is_ok());
+        assert_eq!(
+            BoundedInt::<1, 5>::from_str("6").unwrap_err().to_string(),
+            "Value 6 is outside of the allowed range of 1 to 5."
+        );
+        assert_eq!(BoundedInt::<1, 5>::clamped(10), BoundedInt(5));
+    }
+}
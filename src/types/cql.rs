@@ -0,0 +1,175 @@
+//! Scylla column mappings for the shared types, enabled by the `scylla` feature.
+
+use std::str::FromStr;
+
+use scylla::cql_to_rust::{FromCqlVal, FromCqlValError};
+use scylla::frame::response::result::CqlValue;
+use scylla::frame::value::ValueTooBig;
+
+use crate::types::url::{constraints, is_safe_outbound_url, ConstrainedDiscordUrl};
+use crate::types::{
+    BoundedInt, DiscordUrl, EntityFlags, JsSafeBigInt, JsSafeInt, RequestId, SafeOutboundUrl, Set,
+    Timestamp,
+};
+
+impl FromCqlVal<CqlValue> for JsSafeBigInt {
+    fn from_cql(cql_val: CqlValue) -> Result<Self, FromCqlValError> {
+        match cql_val {
+            CqlValue::Counter(c) => Ok(Self(c.0)),
+            CqlValue::BigInt(v) => Ok(Self(v)),
+            _ => Err(FromCqlValError::BadCqlType),
+        }
+    }
+}
+
+impl scylla::frame::value::Value for JsSafeBigInt {
+    fn serialize(&self, buf: &mut Vec<u8>) -> Result<(), ValueTooBig> {
+        scylla::frame::value::Value::serialize(&self.0, buf)
+    }
+}
+
+impl FromCqlVal<CqlValue> for Timestamp {
+    fn from_cql(cql_val: CqlValue) -> Result<Self, FromCqlValError> {
+        cql_val
+            .as_duration()
+            .map(|v| Self::from(v.num_seconds()))
+            .ok_or(FromCqlValError::BadCqlType)
+    }
+}
+
+impl scylla::frame::value::Value for Timestamp {
+    fn serialize(&self, buf: &mut Vec<u8>) -> Result<(), ValueTooBig> {
+        self.0.timestamp_millis().serialize(buf)
+    }
+}
+
+impl FromCqlVal<CqlValue> for DiscordUrl {
+    fn from_cql(cql_val: CqlValue) -> Result<Self, FromCqlValError> {
+        if let Some(v) = cql_val.as_text() {
+            Self::from_str(v).map_err(|_| FromCqlValError::BadCqlType)
+        } else {
+            Err(FromCqlValError::BadCqlType)
+        }
+    }
+}
+
+impl scylla::frame::value::Value for DiscordUrl {
+    fn serialize(&self, buf: &mut Vec<u8>) -> Result<(), ValueTooBig> {
+        self.0.as_str().serialize(buf)
+    }
+}
+
+impl<T: constraints::ConstrainedUrl> FromCqlVal<CqlValue> for ConstrainedDiscordUrl<T> {
+    fn from_cql(cql_val: CqlValue) -> Result<Self, FromCqlValError> {
+        let slf = DiscordUrl::from_cql(cql_val)?;
+
+        if !T::is_valid(&slf) {
+            Err(FromCqlValError::BadCqlType)
+        } else {
+            Ok(Self::from(slf))
+        }
+    }
+}
+
+impl<T: constraints::ConstrainedUrl> scylla::frame::value::Value for ConstrainedDiscordUrl<T> {
+    fn serialize(&self, buf: &mut Vec<u8>) -> Result<(), ValueTooBig> {
+        self.0.serialize(buf)
+    }
+}
+
+impl FromCqlVal<CqlValue> for SafeOutboundUrl {
+    fn from_cql(cql_val: CqlValue) -> Result<Self, FromCqlValError> {
+        let slf = DiscordUrl::from_cql(cql_val)?;
+
+        if !is_safe_outbound_url(&slf) {
+            return Err(FromCqlValError::BadCqlType);
+        }
+
+        Ok(Self(slf))
+    }
+}
+
+impl scylla::frame::value::Value for SafeOutboundUrl {
+    fn serialize(&self, buf: &mut Vec<u8>) -> Result<(), ValueTooBig> {
+        self.0.serialize(buf)
+    }
+}
+
+impl<T: FromCqlVal<CqlValue>, const MAX: usize> FromCqlVal<Option<CqlValue>> for Set<T, MAX> {
+    fn from_cql(cql_val: Option<CqlValue>) -> Result<Self, FromCqlValError> {
+        if let Some(v) = cql_val {
+            Ok(Self(Vec::<T>::from_cql(v)?))
+        } else {
+            Ok(Self(Default::default()))
+        }
+    }
+}
+
+impl<T: scylla::frame::value::Value, const MAX: usize> scylla::frame::value::Value for Set<T, MAX> {
+    fn serialize(&self, buf: &mut Vec<u8>) -> Result<(), ValueTooBig> {
+        self.0.serialize(buf)
+    }
+}
+
+impl FromCqlVal<CqlValue> for JsSafeInt {
+    fn from_cql(cql_val: CqlValue) -> Result<Self, FromCqlValError> {
+        cql_val
+            .as_int()
+            .map(Self)
+            .ok_or(FromCqlValError::BadCqlType)
+    }
+}
+
+impl scylla::frame::value::Value for JsSafeInt {
+    fn serialize(&self, buf: &mut Vec<u8>) -> Result<(), ValueTooBig> {
+        self.0.serialize(buf)
+    }
+}
+
+impl<const MIN: i64, const MAX: i64> FromCqlVal<CqlValue> for BoundedInt<MIN, MAX> {
+    fn from_cql(cql_val: CqlValue) -> Result<Self, FromCqlValError> {
+        match cql_val {
+            CqlValue::BigInt(v) => Ok(Self(v)),
+            CqlValue::Int(v) => Ok(Self(v as i64)),
+            CqlValue::SmallInt(v) => Ok(Self(v as i64)),
+            CqlValue::TinyInt(v) => Ok(Self(v as i64)),
+            _ => Err(FromCqlValError::BadCqlType),
+        }
+    }
+}
+
+impl<const MIN: i64, const MAX: i64> scylla::frame::value::Value for BoundedInt<MIN, MAX> {
+    fn serialize(&self, buf: &mut Vec<u8>) -> Result<(), ValueTooBig> {
+        self.0.serialize(buf)
+    }
+}
+
+impl FromCqlVal<CqlValue> for EntityFlags {
+    fn from_cql(cql_val: CqlValue) -> Result<Self, FromCqlValError> {
+        cql_val
+            .as_bigint()
+            .map(Self)
+            .ok_or(FromCqlValError::BadCqlType)
+    }
+}
+
+impl scylla::frame::value::Value for EntityFlags {
+    fn serialize(&self, buf: &mut Vec<u8>) -> Result<(), ValueTooBig> {
+        scylla::frame::value::Value::serialize(&self.0, buf)
+    }
+}
+
+impl FromCqlVal<CqlValue> for RequestId {
+    fn from_cql(cql_val: CqlValue) -> Result<Self, FromCqlValError> {
+        cql_val
+            .as_uuid()
+            .map(Self)
+            .ok_or(FromCqlValError::BadCqlType)
+    }
+}
+
+impl scylla::frame::value::Value for RequestId {
+    fn serialize(&self, buf: &mut Vec<u8>) -> Result<(), ValueTooBig> {
+        scylla::frame::value::Value::serialize(&self.0, buf)
+    }
+}
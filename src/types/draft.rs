@@ -0,0 +1,182 @@
+use bincode::{Decode, Encode};
+use scylla::cql_to_rust::{FromCqlVal, FromCqlValError};
+use scylla::frame::response::result::CqlValue;
+use scylla::frame::value::ValueTooBig;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::db::BincodeBlob;
+use crate::diff::changes;
+use crate::types::{JsSafeBigInt, Timestamp};
+
+/// An edit of an entity waiting to be approved by staff.
+///
+/// Only the fields the submitter changed are recorded so approving the draft
+/// does not revert edits made to other fields since it was submitted. This is
+/// stored as a [BincodeBlob] so it does not need its own table per entity.
+#[derive(Clone, Debug, PartialEq, Encode, Decode, serde::Serialize, serde::Deserialize)]
+pub struct Draft<T> {
+    pub proposed: T,
+    /// The dot separated paths of the fields which were changed.
+    pub changed: Vec<String>,
+    pub submitter_id: JsSafeBigInt,
+    pub submitted_at: Timestamp,
+}
+
+impl<T: Serialize + DeserializeOwned> Draft<T> {
+    /// Creates a draft of the changes made from `original` to `proposed`.
+    pub fn new(original: &T, proposed: T, submitter_id: JsSafeBigInt) -> serde_json::Result<Self> {
+        let changed = changes(original, &proposed)?
+            .into_iter()
+            .map(|v| v.path)
+            .collect();
+
+        Ok(Self {
+            proposed,
+            changed,
+            submitter_id,
+            submitted_at: Timestamp::default(),
+        })
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.changed.is_empty()
+    }
+
+    /// Copies the changed fields of the draft onto the current version of the entity.
+    pub fn apply_onto(&self, current: &mut T) -> serde_json::Result<()> {
+        let proposed = serde_json::to_value(&self.proposed)?;
+        let mut merged = serde_json::to_value(&*current)?;
+
+        for path in self.changed.iter() {
+            match lookup(&proposed, path) {
+                Some(v) => insert(&mut merged, path, v.clone()),
+                None => remove(&mut merged, path),
+            }
+        }
+
+        *current = serde_json::from_value(merged)?;
+        Ok(())
+    }
+}
+
+fn lookup<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.').try_fold(value, |v, key| v.get(key))
+}
+
+fn insert(value: &mut Value, path: &str, new: Value) {
+    let mut target = value;
+
+    for key in path.split('.') {
+        if !target.is_object() {
+            *target = Value::Object(Default::default());
+        }
+
+        target = target
+            .as_object_mut()
+            .unwrap()
+            .entry(key)
+            .or_insert(Value::Null);
+    }
+
+    *target = new;
+}
+
+fn remove(value: &mut Value, path: &str) {
+    let (parent, key) = match path.rsplit_once('.') {
+        Some((parent, key)) => (lookup_mut(value, parent), key),
+        None => (Some(value), path),
+    };
+
+    if let Some(Value::Object(map)) = parent {
+        map.remove(key);
+    }
+}
+
+fn lookup_mut<'a>(value: &'a mut Value, path: &str) -> Option<&'a mut Value> {
+    path.split('.').try_fold(value, |v, key| v.get_mut(key))
+}
+
+impl<T: Encode> scylla::frame::value::Value for Draft<T> {
+    fn serialize(&self, buf: &mut Vec<u8>) -> Result<(), ValueTooBig> {
+        scylla::frame::value::Value::serialize(&BincodeBlob(self), buf)
+    }
+}
+
+impl<T: Decode> FromCqlVal<CqlValue> for Draft<T> {
+    fn from_cql(cql_val: CqlValue) -> Result<Self, FromCqlValError> {
+        let blob = cql_val.into_blob().ok_or(FromCqlValError::BadCqlType)?;
+
+        BincodeBlob::decode(&blob).map_err(|_| FromCqlValError::BadCqlType)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, Debug, PartialEq, Encode, Decode, serde::Serialize, serde::Deserialize)]
+    struct Links {
+        github: Option<String>,
+        website: Option<String>,
+    }
+
+    #[derive(Clone, Debug, PartialEq, Encode, Decode, serde::Serialize, serde::Deserialize)]
+    struct Bot {
+        name: String,
+        prefix: String,
+        links: Links,
+    }
+
+    fn bot() -> Bot {
+        Bot {
+            name: "Cool Bot".to_string(),
+            prefix: "!".to_string(),
+            links: Links {
+                github: None,
+                website: None,
+            },
+        }
+    }
+
+    #[test]
+    fn test_apply_only_changed_fields() {
+        let original = bot();
+        let mut proposed = bot();
+        proposed.prefix = "?".to_string();
+        proposed.links.github = Some("https://github.com/cool/bot".to_string());
+
+        let draft = Draft::new(&original, proposed, JsSafeBigInt(1)).unwrap();
+        assert_eq!(draft.changed, vec!["links.github", "prefix"]);
+
+        // Staff renamed the bot while the draft was waiting for review.
+        let mut current = bot();
+        current.name = "Cooler Bot".to_string();
+        draft.apply_onto(&mut current).unwrap();
+
+        assert_eq!(current.name, "Cooler Bot");
+        assert_eq!(current.prefix, "?");
+        assert_eq!(
+            current.links.github.as_deref(),
+            Some("https://github.com/cool/bot")
+        );
+    }
+
+    #[test]
+    fn test_blob_round_trip() {
+        let mut proposed = bot();
+        proposed.name = "Renamed".to_string();
+        let draft = Draft::new(&bot(), proposed, JsSafeBigInt(1)).unwrap();
+
+        let mut buf = vec![];
+        scylla::frame::value::Value::serialize(&draft, &mut buf).unwrap();
+
+        // Skip the 4 byte length prefix of the serialized value.
+        let decoded = Draft::<Bot>::from_cql(CqlValue::Blob(buf[4..].to_vec())).unwrap();
+        assert_eq!(decoded.proposed, draft.proposed);
+        assert_eq!(decoded.changed, draft.changed);
+        assert_eq!(decoded.submitter_id, draft.submitter_id);
+    }
+}
@@ -0,0 +1,195 @@
+use std::borrow::Cow;
+use std::fmt::{Display, Formatter};
+use std::ops::Deref;
+use std::str::FromStr;
+use std::time::Duration;
+
+use poem_openapi::registry::{MetaSchema, MetaSchemaRef};
+use poem_openapi::types::{ParseError, ParseFromJSON, ParseResult, ToJSON, Type};
+use serde::de::Error;
+use serde::{Deserializer, Serializer};
+use serde_json::Value;
+
+const UNITS: [(&str, u64); 4] = [("d", 86_400), ("h", 3_600), ("m", 60), ("s", 1)];
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default)]
+/// A whole number of seconds written as a human readable string such as
+/// `30d`, `1h30m` or `45s`.
+pub struct HumanDuration(pub Duration);
+
+impl HumanDuration {
+    pub const fn from_secs(secs: u64) -> Self {
+        Self(Duration::from_secs(secs))
+    }
+}
+
+impl serde::Serialize for HumanDuration {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.to_string().serialize(serializer)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for HumanDuration {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        Self::from_str(&s).map_err(|e| D::Error::custom(e.into_message()))
+    }
+}
+
+impl From<Duration> for HumanDuration {
+    fn from(v: Duration) -> Self {
+        Self(v)
+    }
+}
+
+impl Display for HumanDuration {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut remaining = self.0.as_secs();
+        if remaining == 0 {
+            return write!(f, "0s");
+        }
+
+        for (unit, secs) in UNITS {
+            if remaining >= secs {
+                write!(f, "{}{}", remaining / secs, unit)?;
+                remaining %= secs;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Deref for HumanDuration {
+    type Target = Duration;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Type for HumanDuration {
+    const IS_REQUIRED: bool = true;
+    type RawValueType = Self;
+    type RawElementValueType = Self;
+
+    fn name() -> Cow<'static, str> {
+        Cow::from("HumanDuration")
+    }
+
+    fn schema_ref() -> MetaSchemaRef {
+        MetaSchemaRef::Inline(Box::new(MetaSchema {
+            pattern: Some("^([0-9]+[dhms])+$".to_string()),
+            ..MetaSchema::new("string")
+        }))
+    }
+
+    fn as_raw_value(&self) -> Option<&Self::RawValueType> {
+        Some(self)
+    }
+
+    fn raw_element_iter<'a>(
+        &'a self,
+    ) -> Box<dyn Iterator<Item = &'a Self::RawElementValueType> + 'a> {
+        Box::new(self.as_raw_value().into_iter())
+    }
+}
+
+impl ToJSON for HumanDuration {
+    fn to_json(&self) -> Option<Value> {
+        Some(Value::String(self.to_string()))
+    }
+}
+
+impl ParseFromJSON for HumanDuration {
+    fn parse_from_json(value: Option<Value>) -> ParseResult<Self> {
+        match value {
+            Some(Value::String(v)) => Self::from_str(&v),
+            _ => Err(ParseError::custom("expected a duration string")),
+        }
+    }
+}
+
+impl FromStr for HumanDuration {
+    type Err = ParseError<Self>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || ParseError::custom(format!("invalid duration {:?}", s));
+
+        let mut rest = s.trim();
+        if rest.is_empty() {
+            return Err(invalid());
+        }
+
+        let mut total = 0u64;
+        while !rest.is_empty() {
+            let digits = rest
+                .find(|c: char| !c.is_ascii_digit())
+                .ok_or_else(invalid)?;
+            let value = rest[..digits].parse::<u64>().map_err(|_| invalid())?;
+            rest = &rest[digits..];
+
+            let (unit, secs) = UNITS
+                .iter()
+                .find(|(unit, _)| rest.starts_with(unit))
+                .ok_or_else(invalid)?;
+            rest = &rest[unit.len()..];
+
+            total = value
+                .checked_mul(*secs)
+                .and_then(|v| total.checked_add(v))
+                .ok_or_else(invalid)?;
+        }
+
+        Ok(Self::from_secs(total))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_display() {
+        let cases = [
+            ("30d", 30 * 86_400),
+            ("1h30m", 5_400),
+            ("90s", 90),
+            ("0s", 0),
+        ];
+        for (input, secs) in cases {
+            assert_eq!(HumanDuration::from_str(input).unwrap().as_secs(), secs);
+        }
+
+        assert_eq!(HumanDuration::from_secs(5_400).to_string(), "1h30m");
+        assert_eq!(HumanDuration::from_secs(90_061).to_string(), "1d1h1m1s");
+        assert_eq!(HumanDuration::default().to_string(), "0s");
+    }
+
+    #[test]
+    fn test_invalid() {
+        for input in [
+            "",
+            "30",
+            "d",
+            "5w",
+            "1h 30m",
+            "-1s",
+            "99999999999999999999d",
+        ] {
+            assert!(HumanDuration::from_str(input).is_err(), "{input}");
+        }
+
+        assert!(serde_json::from_str::<HumanDuration>("\"12x\"").is_err());
+        assert_eq!(
+            serde_json::from_str::<HumanDuration>("\"12m\"").unwrap(),
+            HumanDuration::from_secs(720)
+        );
+    }
+}
@@ -0,0 +1,191 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use strum::IntoEnumIterator;
+
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    strum::AsRefStr,
+    strum::EnumString,
+    strum::EnumIter,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[cfg_attr(feature = "poem", derive(poem_openapi::Enum))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "poem", oai(rename_all = "kebab-case"))]
+#[strum(serialize_all = "kebab-case")]
+#[serde(rename_all = "kebab-case")]
+/// A single account state, the discriminant is the bit it is stored in.
+///
+/// Flags must never be reordered or removed as that would change stored values.
+pub enum EntityFlag {
+    Verified = 0,
+    Certified = 1,
+    Premium = 2,
+    Staff = 3,
+    Banned = 4,
+    Nsfw = 5,
+    Hidden = 6,
+}
+
+impl EntityFlag {
+    #[inline]
+    pub fn bit(&self) -> i64 {
+        1 << *self as u8
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Default)]
+/// The states of a user or bot, stored as a `bigint` bitfield and exposed
+/// as a list of flag names.
+///
+/// Unknown bits are kept when round tripping through the database but are
+/// never exposed.
+pub struct EntityFlags(pub i64);
+
+impl EntityFlags {
+    #[inline]
+    pub fn bits(&self) -> i64 {
+        self.0
+    }
+
+    #[inline]
+    pub fn has(&self, flag: EntityFlag) -> bool {
+        self.0 & flag.bit() != 0
+    }
+
+    #[inline]
+    pub fn set(&mut self, flag: EntityFlag) {
+        self.0 |= flag.bit();
+    }
+
+    #[inline]
+    pub fn clear(&mut self, flag: EntityFlag) {
+        self.0 &= !flag.bit();
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = EntityFlag> + '_ {
+        EntityFlag::iter().filter(|flag| self.has(*flag))
+    }
+}
+
+impl FromIterator<EntityFlag> for EntityFlags {
+    fn from_iter<I: IntoIterator<Item = EntityFlag>>(iter: I) -> Self {
+        let mut flags = Self::default();
+        for flag in iter {
+            flags.set(flag);
+        }
+        flags
+    }
+}
+
+impl From<i64> for EntityFlags {
+    fn from(v: i64) -> Self {
+        Self(v)
+    }
+}
+
+impl Display for EntityFlags {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let names = self
+            .iter()
+            .map(|v| v.as_ref().to_string())
+            .collect::<Vec<_>>();
+        write!(f, "{}", names.join(","))
+    }
+}
+
+impl Serialize for EntityFlags {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+impl<'de> Deserialize<'de> for EntityFlags {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Vec::<EntityFlag>::deserialize(deserializer).map(Self::from_iter)
+    }
+}
+
+impl FromStr for EntityFlags {
+    type Err = UnknownFlag;
+
+    /// Parses a comma separated list of flag names.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.split(',')
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .map(|v| EntityFlag::from_str(v).map_err(|_| UnknownFlag(v.to_string())))
+            .collect()
+    }
+}
+
+/// A flag name which is not an [EntityFlag].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownFlag(pub String);
+
+impl Display for UnknownFlag {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "unknown flag {:?}", self.0)
+    }
+}
+
+impl std::error::Error for UnknownFlag {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_has_set_clear() {
+        let mut flags = EntityFlags::default();
+        flags.set(EntityFlag::Verified);
+        flags.set(EntityFlag::Nsfw);
+        assert!(flags.has(EntityFlag::Verified));
+        assert!(!flags.has(EntityFlag::Banned));
+        assert_eq!(flags.bits(), 0b100001);
+
+        flags.clear(EntityFlag::Verified);
+        assert_eq!(flags.iter().collect::<Vec<_>>(), vec![EntityFlag::Nsfw]);
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let flags = EntityFlags::from_iter([EntityFlag::Staff, EntityFlag::Premium]);
+        assert_eq!(
+            serde_json::to_value(flags).unwrap(),
+            json!(["premium", "staff"])
+        );
+
+        let parsed = serde_json::from_value::<EntityFlags>(json!(["staff", "premium"])).unwrap();
+        assert_eq!(parsed, flags);
+        assert!(serde_json::from_value::<EntityFlags>(json!(["admin"])).is_err());
+
+        assert_eq!(
+            "verified, banned".parse::<EntityFlags>().unwrap().bits(),
+            0b10001
+        );
+        assert!("verified, admin".parse::<EntityFlags>().is_err());
+    }
+
+    #[test]
+    fn test_unknown_bits_hidden() {
+        let flags = EntityFlags::from(1 << 40 | 1);
+        assert_eq!(flags.to_string(), "verified");
+        assert_eq!(flags.bits(), 1 << 40 | 1);
+    }
+}
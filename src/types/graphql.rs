@@ -0,0 +1,124 @@
+//! `async_graphql` scalars for the shared types, enabled by the `graphql`
+//! feature.
+//!
+//! The scalars parse and render values through their `poem_openapi` impls so
+//! the GraphQL gateway accepts exactly what the REST APIs accept.
+
+use std::borrow::Cow;
+
+use async_graphql::parser::types::Field;
+use async_graphql::resolver_utils::resolve_list;
+use async_graphql::{
+    registry, ContextSelectionSet, InputType, InputValueError, InputValueResult, OutputType,
+    Positioned, ServerResult, Value,
+};
+
+use crate::types::{DiscordInvite, DiscordUrl, JsSafeBigInt, Set, Timestamp};
+
+graphql_json_scalar!(JsSafeBigInt, "BigInt");
+graphql_json_scalar!(Timestamp, "Timestamp");
+graphql_json_scalar!(DiscordUrl, "Url");
+graphql_json_scalar!(DiscordInvite, "DiscordInvite");
+
+/// A list of `T`, inputs with more than `MAX` items are rejected.
+impl<T: InputType, const MAX: usize> InputType for Set<T, MAX> {
+    type RawValueType = Vec<T>;
+
+    fn type_name() -> Cow<'static, str> {
+        Vec::<T>::type_name()
+    }
+
+    fn qualified_type_name() -> String {
+        Vec::<T>::qualified_type_name()
+    }
+
+    fn create_type_info(registry: &mut registry::Registry) -> String {
+        Vec::<T>::create_type_info(registry)
+    }
+
+    fn parse(value: Option<Value>) -> InputValueResult<Self> {
+        let inner = Vec::<T>::parse(value).map_err(InputValueError::propagate)?;
+
+        if inner.len() > MAX {
+            return Err(InputValueError::custom(Self::too_many_items()));
+        }
+
+        Ok(Self(inner))
+    }
+
+    fn to_value(&self) -> Value {
+        self.0.to_value()
+    }
+
+    fn as_raw_value(&self) -> Option<&Self::RawValueType> {
+        Some(&self.0)
+    }
+}
+
+impl<T: OutputType, const MAX: usize> OutputType for Set<T, MAX> {
+    fn type_name() -> Cow<'static, str> {
+        <Vec<T> as OutputType>::type_name()
+    }
+
+    fn qualified_type_name() -> String {
+        <Vec<T> as OutputType>::qualified_type_name()
+    }
+
+    fn create_type_info(registry: &mut registry::Registry) -> String {
+        <Vec<T> as OutputType>::create_type_info(registry)
+    }
+
+    async fn resolve(
+        &self,
+        ctx: &ContextSelectionSet<'_>,
+        field: &Positioned<Field>,
+    ) -> ServerResult<Value> {
+        resolve_list(ctx, field, &self.0, Some(self.0.len())).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use async_graphql::ScalarType;
+
+    use super::*;
+
+    #[test]
+    fn test_scalars_match_rest() {
+        assert_eq!(
+            <JsSafeBigInt as ScalarType>::parse(Value::from("1234")).unwrap(),
+            JsSafeBigInt(1234)
+        );
+        assert_eq!(
+            <JsSafeBigInt as ScalarType>::parse(Value::from(1234)).unwrap(),
+            JsSafeBigInt(1234)
+        );
+        assert_eq!(
+            ScalarType::to_value(&JsSafeBigInt(1234)),
+            Value::from("1234")
+        );
+
+        let url = DiscordUrl::from_str("https://discordlist.gg").unwrap();
+        assert_eq!(
+            <DiscordUrl as ScalarType>::parse(ScalarType::to_value(&url)).unwrap(),
+            url
+        );
+        assert!(<DiscordUrl as ScalarType>::parse(Value::from("not a url")).is_err());
+    }
+
+    #[test]
+    fn test_set_limit() {
+        let value = Value::List(vec![Value::from("1"), Value::from("2")]);
+        let set = <Set<JsSafeBigInt, 2> as InputType>::parse(Some(value.clone())).unwrap();
+        assert_eq!(set.0, vec![JsSafeBigInt(1), JsSafeBigInt(2)]);
+        assert_eq!(InputType::to_value(&set), value);
+
+        assert!(<Set<JsSafeBigInt, 1> as InputType>::parse(Some(value)).is_err());
+        assert_eq!(
+            <Set<JsSafeBigInt> as InputType>::qualified_type_name(),
+            "[BigInt!]!"
+        );
+    }
+}
@@ -8,6 +8,7 @@ use bincode::{Decode, Encode};
 
 use poem_openapi::registry::MetaSchemaRef;
 use poem_openapi::types::{ParseError, ParseFromJSON, ParseResult, ToJSON, Type};
+use poem_openapi::{Enum, Object};
 use scylla::cql_to_rust::{FromCqlVal, FromCqlValError};
 use scylla::frame::response::result::CqlValue;
 use scylla::frame::value::ValueTooBig;
@@ -15,10 +16,143 @@ use serde::{Deserializer, Serializer};
 use serde_json::{json, Value};
 use url::Url;
 
+use crate::discord::Invite;
+#[cfg(feature = "discord-http")]
+use crate::discord::{DiscordHttp, HttpError};
+use crate::types::{JsSafeBigInt, Timestamp};
+
 #[cfg_attr(feature = "bincode", derive(Decode, Encode))]
 #[derive(Clone, PartialEq, Eq, Hash, Debug)]
 pub struct DiscordInvite(#[cfg_attr(feature = "bincode", bincode(with_serde))] pub Url);
 
+impl DiscordInvite {
+    /// The guild invite code if this is a `discord.gg` or `discord.com/invite` link.
+    pub fn code(&self) -> Option<&str> {
+        let mut segments = self.0.path_segments()?.filter(|v| !v.is_empty());
+
+        let code = match self.0.host_str()? {
+            "discord.gg" => segments.next()?,
+            "discord.com" | "discordapp.com" => match segments.next()? {
+                "invite" => segments.next()?,
+                _ => return None,
+            },
+            _ => return None,
+        };
+
+        code.chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-')
+            .then_some(code)
+    }
+
+    /// Whether the invite is a bot authorization link, these never expire
+    /// unlike guild invites which need to be resolved to know if they are alive.
+    pub fn is_permanent_format(&self) -> bool {
+        let is_discord = matches!(self.0.host_str(), Some("discord.com" | "discordapp.com"));
+
+        is_discord
+            && self.0.path().trim_end_matches('/') == "/oauth2/authorize"
+            && self
+                .0
+                .query_pairs()
+                .any(|(k, v)| k == "client_id" && v.parse::<u64>().is_ok())
+    }
+
+    /// Checks the invite against Discord, bot authorization links are
+    /// reported as permanent without making a request.
+    #[cfg(feature = "discord-http")]
+    pub async fn resolve(&self, http: &DiscordHttp) -> Result<InviteResolution, HttpError> {
+        if self.is_permanent_format() {
+            return Ok(InviteResolution::status(InviteStatus::Permanent));
+        }
+
+        match self.code() {
+            Some(code) => Ok(InviteResolution::from_invite(http.get_invite(code).await?)),
+            None => Ok(InviteResolution::status(InviteStatus::Unresolvable)),
+        }
+    }
+}
+
+#[derive(
+    Enum,
+    Copy,
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    strum::AsRefStr,
+    strum::EnumString,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[oai(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum InviteStatus {
+    /// The invite is alive and never expires.
+    Permanent,
+    /// The invite is alive but will expire.
+    Temporary,
+    /// The invite does not exist or has expired.
+    Dead,
+    /// The invite is not a Discord link so cannot be checked, e.g. a redirect service.
+    Unresolvable,
+}
+
+#[derive(Object, Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+pub struct InviteResolution {
+    pub status: InviteStatus,
+    pub guild_id: Option<JsSafeBigInt>,
+    pub approximate_member_count: Option<u64>,
+    pub approximate_presence_count: Option<u64>,
+    pub expires_at: Option<Timestamp>,
+}
+
+impl InviteResolution {
+    fn status(status: InviteStatus) -> Self {
+        Self {
+            status,
+            guild_id: None,
+            approximate_member_count: None,
+            approximate_presence_count: None,
+            expires_at: None,
+        }
+    }
+
+    /// Builds the resolution from the invite endpoint's response, `None`
+    /// being an unknown or expired invite.
+    pub fn from_invite(invite: Option<Invite>) -> Self {
+        let invite = match invite {
+            Some(invite) => invite,
+            None => return Self::status(InviteStatus::Dead),
+        };
+
+        let status = if invite.expires_at.is_some() {
+            InviteStatus::Temporary
+        } else {
+            InviteStatus::Permanent
+        };
+
+        Self {
+            status,
+            guild_id: invite.guild.map(|v| v.id),
+            approximate_member_count: invite.approximate_member_count,
+            approximate_presence_count: invite.approximate_presence_count,
+            expires_at: invite.expires_at,
+        }
+    }
+
+    #[inline]
+    pub fn is_alive(&self) -> bool {
+        matches!(
+            self.status,
+            InviteStatus::Permanent | InviteStatus::Temporary
+        )
+    }
+}
+
 impl serde::Serialize for DiscordInvite {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -137,3 +271,61 @@ impl scylla::frame::value::Value for DiscordInvite {
         self.0.as_str().serialize(buf)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn invite(s: &str) -> DiscordInvite {
+        DiscordInvite::parse_from_json(Some(json!(s))).unwrap()
+    }
+
+    #[test]
+    fn test_invite_code() {
+        assert_eq!(invite("discord.gg/dlist").code(), Some("dlist"));
+        assert_eq!(
+            invite("https://discord.com/invite/abc-123/").code(),
+            Some("abc-123")
+        );
+        assert_eq!(invite("https://invite.bot/dlist").code(), None);
+        assert_eq!(
+            invite("https://discord.com/oauth2/authorize?client_id=1").code(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_permanent_format() {
+        assert!(
+            invite("https://discord.com/oauth2/authorize?client_id=1234&scope=bot")
+                .is_permanent_format()
+        );
+        assert!(!invite("https://discord.com/oauth2/authorize?scope=bot").is_permanent_format());
+        assert!(!invite("discord.gg/dlist").is_permanent_format());
+        assert!(!invite("https://invite.bot/dlist").is_permanent_format());
+    }
+
+    #[test]
+    fn test_resolution_from_invite() {
+        assert_eq!(
+            InviteResolution::from_invite(None).status,
+            InviteStatus::Dead
+        );
+
+        let invite: Invite = serde_json::from_value(json!({
+            "code": "dlist",
+            "guild": {"id": "42", "name": "DList"},
+            "approximate_member_count": 100,
+            "approximate_presence_count": 10,
+            "expires_at": null,
+        }))
+        .unwrap();
+
+        let resolution = InviteResolution::from_invite(Some(invite));
+        assert!(resolution.is_alive());
+        assert_eq!(resolution.status, InviteStatus::Permanent);
+        assert_eq!(resolution.guild_id, Some(JsSafeBigInt(42)));
+        assert_eq!(resolution.approximate_member_count, Some(100));
+    }
+}
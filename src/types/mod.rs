@@ -1,18 +1,52 @@
 mod bigint;
+#[cfg(feature = "bincode")]
+mod binary;
+mod bounded;
+#[cfg(feature = "scylla")]
+mod cql;
+mod flags;
 mod integer;
-mod invite;
+#[cfg(feature = "poem")]
+mod openapi;
+mod owners;
+#[cfg(feature = "postgres")]
+mod postgres;
+#[cfg(feature = "redis")]
+pub(crate) mod redis;
+mod request_id;
+#[cfg(feature = "utoipa")]
+mod schema;
+mod secret;
 mod set;
 mod timestamp;
-mod unicode_aware;
 pub mod url;
 
-pub use self::url::DiscordUrl;
+pub use self::url::{DiscordUrl, SafeOutboundUrl};
 pub use bigint::JsSafeBigInt;
+pub use bounded::{BoundedInt, InvalidBoundedInt};
+pub use flags::{EntityFlag, EntityFlags, UnknownFlag};
 pub use integer::JsSafeInt;
-pub use invite::DiscordInvite;
+pub use owners::{Owners, MAX_ADDITIONAL_OWNERS};
+pub use request_id::{InvalidRequestId, RequestId, REQUEST_ID_HEADER};
+pub use secret::{serialize_exposed, Secret, REDACTED};
 pub use set::Set;
 pub use timestamp::Timestamp;
-pub use unicode_aware::NormalisingString;
+
+cfg_server! {
+    #[cfg(feature = "bincode")]
+    mod draft;
+    mod duration;
+    #[cfg(feature = "graphql")]
+    mod graphql;
+    mod invite;
+    mod unicode_aware;
+
+    #[cfg(feature = "bincode")]
+    pub use draft::Draft;
+    pub use duration::HumanDuration;
+    pub use invite::{DiscordInvite, InviteResolution, InviteStatus};
+    pub use unicode_aware::NormalisingString;
+}
 
 #[derive(serde::Serialize, serde::Deserialize)]
 #[serde(untagged)]
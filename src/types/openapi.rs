@@ -0,0 +1,560 @@
+//! `poem_openapi` types for the shared types, enabled by the `poem` feature.
+
+use std::borrow::Cow;
+use std::str::FromStr;
+
+use poem_openapi::registry::{MetaSchema, MetaSchemaRef, Registry};
+use poem_openapi::types::{ParseError, ParseFromJSON, ParseResult, ToJSON, Type};
+use serde_json::{json, Value};
+use url::Url;
+use uuid::Uuid;
+
+use crate::types::timestamp::DateTime;
+use crate::types::url::{constraints, is_safe_outbound_url, is_valid_url, ConstrainedDiscordUrl};
+use crate::types::{
+    BoundedInt, DiscordUrl, EntityFlag, EntityFlags, JsSafeBigInt, JsSafeInt, RequestId,
+    SafeOutboundUrl, Set, Timestamp,
+};
+
+impl Type for JsSafeBigInt {
+    const IS_REQUIRED: bool = <String as Type>::IS_REQUIRED;
+    type RawValueType = <i64 as Type>::RawValueType;
+    type RawElementValueType = <i64 as Type>::RawElementValueType;
+
+    fn name() -> Cow<'static, str> {
+        Cow::from("BigInt")
+    }
+
+    fn schema_ref() -> MetaSchemaRef {
+        MetaSchemaRef::Inline(Box::new(MetaSchema {
+            pattern: Some("^-?[0-9]+$".to_string()),
+            ..MetaSchema::new("string")
+        }))
+    }
+
+    fn as_raw_value(&self) -> Option<&Self::RawValueType> {
+        Some(&self.0)
+    }
+
+    fn raw_element_iter<'a>(
+        &'a self,
+    ) -> Box<dyn Iterator<Item = &'a Self::RawElementValueType> + 'a> {
+        self.0.raw_element_iter()
+    }
+}
+
+impl ToJSON for JsSafeBigInt {
+    fn to_json(&self) -> Option<Value> {
+        Some(json!(self.0.to_string()))
+    }
+}
+
+impl ParseFromJSON for JsSafeBigInt {
+    fn parse_from_json(value: Option<Value>) -> ParseResult<Self> {
+        let v = value.ok_or_else(|| ParseError::custom("cannot convert value into integer"))?;
+
+        let slf = match v {
+            Value::String(v) => Self::from_str(&v)?,
+            other => other
+                .as_i64()
+                .map(Self)
+                .ok_or_else(|| ParseError::custom("cannot convert value into integer"))?,
+        };
+
+        Ok(slf)
+    }
+}
+
+impl Type for Timestamp {
+    const IS_REQUIRED: bool = <DateTime as Type>::IS_REQUIRED;
+    type RawValueType = <DateTime as Type>::RawValueType;
+    type RawElementValueType = <DateTime as Type>::RawElementValueType;
+
+    fn name() -> Cow<'static, str> {
+        Cow::from("Timestamp<rfc3339>")
+    }
+
+    fn schema_ref() -> MetaSchemaRef {
+        MetaSchemaRef::Inline(Box::new(MetaSchema::new_with_format("string", "date-time")))
+    }
+
+    fn as_raw_value(&self) -> Option<&Self::RawValueType> {
+        Some(&self.0)
+    }
+
+    fn raw_element_iter<'a>(
+        &'a self,
+    ) -> Box<dyn Iterator<Item = &'a Self::RawElementValueType> + 'a> {
+        self.0.raw_element_iter()
+    }
+}
+
+impl ToJSON for Timestamp {
+    fn to_json(&self) -> Option<Value> {
+        Some(json!(self.0.to_rfc3339()))
+    }
+}
+
+impl ParseFromJSON for Timestamp {
+    fn parse_from_json(value: Option<Value>) -> ParseResult<Self> {
+        let value = value.ok_or_else(|| ParseError::custom("invalid timestamp given"))?;
+
+        if let Some(v) = value.as_i64() {
+            return Ok(Self::from(v));
+        }
+
+        if let Some(v) = value.as_str() {
+            return Ok(Self::from_str(v)?);
+        }
+
+        Err(ParseError::custom("invalid timestamp given"))
+    }
+}
+
+impl Type for DiscordUrl {
+    const IS_REQUIRED: bool = <Url as Type>::IS_REQUIRED;
+    type RawValueType = <Url as Type>::RawValueType;
+    type RawElementValueType = <Url as Type>::RawElementValueType;
+
+    fn name() -> Cow<'static, str> {
+        Cow::from("Url")
+    }
+
+    fn schema_ref() -> MetaSchemaRef {
+        Url::schema_ref()
+    }
+
+    fn as_raw_value(&self) -> Option<&Self::RawValueType> {
+        Some(&self.0)
+    }
+
+    fn raw_element_iter<'a>(
+        &'a self,
+    ) -> Box<dyn Iterator<Item = &'a Self::RawElementValueType> + 'a> {
+        self.0.raw_element_iter()
+    }
+}
+
+impl ToJSON for DiscordUrl {
+    fn to_json(&self) -> Option<Value> {
+        Some(json!(self.0.to_string()))
+    }
+}
+
+impl ParseFromJSON for DiscordUrl {
+    fn parse_from_json(value: Option<Value>) -> ParseResult<Self> {
+        let value = value.ok_or_else(|| ParseError::custom("Invalid url provided."))?;
+
+        if let Some(v) = value.as_str() {
+            let url = Url::from_str(v)?;
+
+            if !is_valid_url(&url) {
+                return Err(ParseError::custom("Invalid url provided."));
+            }
+
+            return Ok(Self(url));
+        }
+
+        Err(ParseError::custom("Invalid url provided."))
+    }
+}
+
+impl<T: constraints::ConstrainedUrl + Sync + Send + 'static> Type for ConstrainedDiscordUrl<T> {
+    const IS_REQUIRED: bool = <DiscordUrl as Type>::IS_REQUIRED;
+    type RawValueType = <DiscordUrl as Type>::RawValueType;
+    type RawElementValueType = <DiscordUrl as Type>::RawElementValueType;
+
+    fn name() -> Cow<'static, str> {
+        DiscordUrl::name()
+    }
+
+    fn schema_ref() -> MetaSchemaRef {
+        DiscordUrl::schema_ref()
+    }
+
+    fn as_raw_value(&self) -> Option<&Self::RawValueType> {
+        self.0.as_raw_value()
+    }
+
+    fn raw_element_iter<'a>(
+        &'a self,
+    ) -> Box<dyn Iterator<Item = &'a Self::RawElementValueType> + 'a> {
+        self.0.raw_element_iter()
+    }
+}
+
+impl<T: constraints::ConstrainedUrl + Sync + Send + 'static> ToJSON for ConstrainedDiscordUrl<T> {
+    fn to_json(&self) -> Option<Value> {
+        self.0.to_json()
+    }
+}
+
+impl<T: constraints::ConstrainedUrl + Sync + Send + 'static> ParseFromJSON
+    for ConstrainedDiscordUrl<T>
+{
+    fn parse_from_json(value: Option<Value>) -> ParseResult<Self> {
+        let slf = DiscordUrl::parse_from_json(value).map_err(|e| e.propagate())?;
+
+        if !T::is_valid(&slf) {
+            Err(ParseError::custom("Invalid url provided."))
+        } else {
+            Ok(Self::from(slf))
+        }
+    }
+}
+
+impl Type for SafeOutboundUrl {
+    const IS_REQUIRED: bool = <DiscordUrl as Type>::IS_REQUIRED;
+    type RawValueType = <DiscordUrl as Type>::RawValueType;
+    type RawElementValueType = <DiscordUrl as Type>::RawElementValueType;
+
+    fn name() -> Cow<'static, str> {
+        DiscordUrl::name()
+    }
+
+    fn schema_ref() -> MetaSchemaRef {
+        DiscordUrl::schema_ref()
+    }
+
+    fn as_raw_value(&self) -> Option<&Self::RawValueType> {
+        self.0.as_raw_value()
+    }
+
+    fn raw_element_iter<'a>(
+        &'a self,
+    ) -> Box<dyn Iterator<Item = &'a Self::RawElementValueType> + 'a> {
+        self.0.raw_element_iter()
+    }
+}
+
+impl ToJSON for SafeOutboundUrl {
+    fn to_json(&self) -> Option<Value> {
+        self.0.to_json()
+    }
+}
+
+impl ParseFromJSON for SafeOutboundUrl {
+    fn parse_from_json(value: Option<Value>) -> ParseResult<Self> {
+        let slf = DiscordUrl::parse_from_json(value).map_err(|e| e.propagate())?;
+
+        if !is_safe_outbound_url(&slf) {
+            return Err(ParseError::custom("Url points to a disallowed address."));
+        }
+
+        Ok(Self(slf))
+    }
+}
+
+impl<T: Type, const MAX: usize> Type for Set<T, MAX> {
+    const IS_REQUIRED: bool = true;
+    type RawValueType = <Vec<T> as Type>::RawValueType;
+    type RawElementValueType = <Vec<T> as Type>::RawElementValueType;
+
+    fn name() -> Cow<'static, str> {
+        Cow::Owned(format!("Set<{}>", T::name()))
+    }
+
+    fn schema_ref() -> MetaSchemaRef {
+        match Vec::<T>::schema_ref() {
+            MetaSchemaRef::Inline(mut schema) if MAX != usize::MAX => {
+                schema.max_items = Some(MAX);
+                MetaSchemaRef::Inline(schema)
+            }
+            other => other,
+        }
+    }
+
+    fn register(registry: &mut Registry) {
+        T::register(registry);
+    }
+
+    fn as_raw_value(&self) -> Option<&Self::RawValueType> {
+        self.0.as_raw_value()
+    }
+
+    fn raw_element_iter<'a>(
+        &'a self,
+    ) -> Box<dyn Iterator<Item = &'a Self::RawElementValueType> + 'a> {
+        self.0.raw_element_iter()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl<T: ToJSON, const MAX: usize> ToJSON for Set<T, MAX> {
+    fn to_json(&self) -> Option<Value> {
+        self.0.to_json()
+    }
+}
+
+impl<T: ParseFromJSON, const MAX: usize> ParseFromJSON for Set<T, MAX> {
+    fn parse_from_json(value: Option<Value>) -> ParseResult<Self> {
+        let inner =
+            Vec::<T>::parse_from_json(value).map_err(|e| ParseError::custom(e.into_message()))?;
+
+        if inner.len() > MAX {
+            return Err(ParseError::custom(Self::too_many_items()));
+        }
+
+        Ok(Self(inner))
+    }
+}
+
+impl Type for JsSafeInt {
+    const IS_REQUIRED: bool = <i32 as Type>::IS_REQUIRED;
+    type RawValueType = <i32 as Type>::RawValueType;
+    type RawElementValueType = <i32 as Type>::RawElementValueType;
+
+    fn name() -> Cow<'static, str> {
+        Cow::from("Integer")
+    }
+
+    fn schema_ref() -> MetaSchemaRef {
+        i64::schema_ref()
+    }
+
+    fn as_raw_value(&self) -> Option<&Self::RawValueType> {
+        Some(&self.0)
+    }
+
+    fn raw_element_iter<'a>(
+        &'a self,
+    ) -> Box<dyn Iterator<Item = &'a Self::RawElementValueType> + 'a> {
+        self.0.raw_element_iter()
+    }
+}
+
+impl ToJSON for JsSafeInt {
+    fn to_json(&self) -> Option<Value> {
+        Some(json!(self.0))
+    }
+}
+
+impl ParseFromJSON for JsSafeInt {
+    fn parse_from_json(value: Option<Value>) -> ParseResult<Self> {
+        let v = value.ok_or_else(|| ParseError::custom("cannot convert value into integer"))?;
+
+        let slf = match v {
+            Value::String(v) => Self::from_str(&v)?,
+            other => other
+                .as_i64()
+                .map(|v| v as i32)
+                .map(Self)
+                .ok_or_else(|| ParseError::custom("cannot convert value into integer"))?,
+        };
+
+        Ok(slf)
+    }
+}
+
+impl<const MIN: i64, const MAX: i64> Type for BoundedInt<MIN, MAX> {
+    const IS_REQUIRED: bool = <i64 as Type>::IS_REQUIRED;
+    type RawValueType = <i64 as Type>::RawValueType;
+    type RawElementValueType = <i64 as Type>::RawElementValueType;
+
+    fn name() -> Cow<'static, str> {
+        Cow::from("Integer")
+    }
+
+    fn schema_ref() -> MetaSchemaRef {
+        MetaSchemaRef::Inline(Box::new(MetaSchema {
+            minimum: Some(MIN as f64),
+            maximum: Some(MAX as f64),
+            ..MetaSchema::new_with_format("integer", "int64")
+        }))
+    }
+
+    fn as_raw_value(&self) -> Option<&Self::RawValueType> {
+        Some(&self.0)
+    }
+
+    fn raw_element_iter<'a>(
+        &'a self,
+    ) -> Box<dyn Iterator<Item = &'a Self::RawElementValueType> + 'a> {
+        self.0.raw_element_iter()
+    }
+}
+
+impl<const MIN: i64, const MAX: i64> ToJSON for BoundedInt<MIN, MAX> {
+    fn to_json(&self) -> Option<Value> {
+        Some(json!(self.0))
+    }
+}
+
+impl<const MIN: i64, const MAX: i64> ParseFromJSON for BoundedInt<MIN, MAX> {
+    fn parse_from_json(value: Option<Value>) -> ParseResult<Self> {
+        let v = value.ok_or_else(|| ParseError::custom("cannot convert value into integer"))?;
+
+        match v {
+            Value::String(v) => Ok(Self::from_str(&v)?),
+            other => {
+                let v = other
+                    .as_i64()
+                    .ok_or_else(|| ParseError::custom("cannot convert value into integer"))?;
+
+                Self::new(v).ok_or_else(|| ParseError::custom(Self::out_of_bounds(v)))
+            }
+        }
+    }
+}
+
+impl Type for EntityFlags {
+    const IS_REQUIRED: bool = true;
+    type RawValueType = Self;
+    type RawElementValueType = Self;
+
+    fn name() -> Cow<'static, str> {
+        Cow::from("EntityFlags")
+    }
+
+    fn schema_ref() -> MetaSchemaRef {
+        Vec::<EntityFlag>::schema_ref()
+    }
+
+    fn register(registry: &mut Registry) {
+        EntityFlag::register(registry);
+    }
+
+    fn as_raw_value(&self) -> Option<&Self::RawValueType> {
+        Some(self)
+    }
+
+    fn raw_element_iter<'a>(
+        &'a self,
+    ) -> Box<dyn Iterator<Item = &'a Self::RawElementValueType> + 'a> {
+        Box::new(self.as_raw_value().into_iter())
+    }
+}
+
+impl ToJSON for EntityFlags {
+    fn to_json(&self) -> Option<Value> {
+        self.iter().collect::<Vec<_>>().to_json()
+    }
+}
+
+impl ParseFromJSON for EntityFlags {
+    fn parse_from_json(value: Option<Value>) -> ParseResult<Self> {
+        Vec::<EntityFlag>::parse_from_json(value)
+            .map(Self::from_iter)
+            .map_err(|e| ParseError::custom(e.into_message()))
+    }
+}
+
+impl Type for RequestId {
+    const IS_REQUIRED: bool = true;
+    type RawValueType = Self;
+    type RawElementValueType = Self;
+
+    fn name() -> Cow<'static, str> {
+        Cow::from("RequestId")
+    }
+
+    fn schema_ref() -> MetaSchemaRef {
+        Uuid::schema_ref()
+    }
+
+    fn as_raw_value(&self) -> Option<&Self::RawValueType> {
+        Some(self)
+    }
+
+    fn raw_element_iter<'a>(
+        &'a self,
+    ) -> Box<dyn Iterator<Item = &'a Self::RawElementValueType> + 'a> {
+        Box::new(self.as_raw_value().into_iter())
+    }
+}
+
+impl ToJSON for RequestId {
+    fn to_json(&self) -> Option<Value> {
+        Some(Value::String(self.to_string()))
+    }
+}
+
+impl ParseFromJSON for RequestId {
+    fn parse_from_json(value: Option<Value>) -> ParseResult<Self> {
+        match value {
+            Some(Value::String(v)) => Ok(Self::from_str(&v)?),
+            _ => Err(ParseError::custom("expected a request id string")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_max_items_enforced() {
+        let res = Set::<String, 2>::parse_from_json(Some(json!(["a", "b", "c"])));
+        assert!(res.is_err(), "Expected item limit validation to fail");
+
+        let res = Set::<String, 2>::parse_from_json(Some(json!(["a", "b"])));
+        assert!(res.is_ok(), "Expected successful parse");
+
+        let res = Set::<String>::parse_from_json(Some(json!(["a", "b", "c"])));
+        assert!(res.is_ok(), "Expected successful parse");
+    }
+
+    #[test]
+    fn test_schema_contains_max_items() {
+        let schema = match Set::<String, 5>::schema_ref() {
+            MetaSchemaRef::Inline(schema) => schema,
+            MetaSchemaRef::Reference(_) => panic!("Expected inline schema"),
+        };
+        assert_eq!(schema.max_items, Some(5));
+
+        let schema = match Set::<String>::schema_ref() {
+            MetaSchemaRef::Inline(schema) => schema,
+            MetaSchemaRef::Reference(_) => panic!("Expected inline schema"),
+        };
+        assert_eq!(schema.max_items, None);
+    }
+
+    #[test]
+    fn test_bounds_enforced() {
+        assert!(BoundedInt::<1, 5>::parse_from_json(Some(json!(0))).is_err());
+        assert!(BoundedInt::<1, 5>::parse_from_json(Some(json!(6))).is_err());
+        assert!(BoundedInt::<1, 5>::parse_from_json(Some(json!("3"))).is_ok());
+    }
+
+    #[test]
+    fn test_schema_contains_bounds() {
+        let schema = match BoundedInt::<1, 5>::schema_ref() {
+            MetaSchemaRef::Inline(schema) => schema,
+            MetaSchemaRef::Reference(_) => panic!("Expected inline schema"),
+        };
+
+        assert_eq!(schema.minimum, Some(1.0));
+        assert_eq!(schema.maximum, Some(5.0));
+    }
+
+    #[test]
+    fn test_flags_json_round_trip() {
+        let flags = EntityFlags::from_iter([EntityFlag::Staff, EntityFlag::Premium]);
+        assert_eq!(flags.to_json(), Some(json!(["premium", "staff"])));
+
+        let parsed = EntityFlags::parse_from_json(Some(json!(["staff", "premium"]))).unwrap();
+        assert_eq!(parsed, flags);
+        assert!(EntityFlags::parse_from_json(Some(json!(["admin"]))).is_err());
+    }
+
+    #[test]
+    fn test_string_schemas_are_constrained() {
+        let schema = match JsSafeBigInt::schema_ref() {
+            MetaSchemaRef::Inline(schema) => schema,
+            MetaSchemaRef::Reference(_) => panic!("Expected inline schema"),
+        };
+        assert_eq!(schema.ty, "string");
+        assert_eq!(schema.pattern.as_deref(), Some("^-?[0-9]+$"));
+
+        let schema = match Timestamp::schema_ref() {
+            MetaSchemaRef::Inline(schema) => schema,
+            MetaSchemaRef::Reference(_) => panic!("Expected inline schema"),
+        };
+        assert_eq!(schema.format, Some("date-time"));
+    }
+}
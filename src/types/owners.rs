@@ -0,0 +1,261 @@
+#[cfg(feature = "bincode")]
+use bincode::{Decode, Encode};
+
+#[cfg(feature = "server")]
+use crate::errors::{ApiError, ApiResult};
+use crate::types::{JsSafeBigInt, Set};
+#[cfg(feature = "server")]
+use crate::validation::{Validate, ValidationErrors};
+
+/// The number of owners a bot or pack may have besides the primary owner.
+pub const MAX_ADDITIONAL_OWNERS: usize = 5;
+
+/// The owners of a bot or pack.
+///
+/// The primary owner has full control over the entity, only they can
+/// transfer ownership or delete it. The additional owners can edit it.
+#[cfg_attr(feature = "bincode", derive(Encode, Decode))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "poem", derive(poem_openapi::Object))]
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Owners {
+    pub primary: JsSafeBigInt,
+    #[serde(default)]
+    // utoipa only accepts a literal, kept in line with `MAX_ADDITIONAL_OWNERS` by the schema tests.
+    #[cfg_attr(
+        feature = "utoipa",
+        schema(value_type = Vec<JsSafeBigInt>, max_items = 5)
+    )]
+    pub additional: Set<JsSafeBigInt, MAX_ADDITIONAL_OWNERS>,
+}
+
+// Stored as `frozen<owners>`, older rows may contain only the primary owner's id.
+#[cfg(feature = "scylla")]
+cql_udt!(
+    Owners {
+        primary,
+        additional
+    },
+    fallback = |v: CqlValue| JsSafeBigInt::from_cql(v).ok().map(Owners::new)
+);
+
+impl Owners {
+    pub fn new(primary: JsSafeBigInt) -> Self {
+        Self {
+            primary,
+            additional: Set::default(),
+        }
+    }
+
+    /// Combines the separate owner columns, dropping duplicates and the
+    /// primary owner from the additional owners.
+    pub fn from_columns(primary: JsSafeBigInt, additional: &[JsSafeBigInt]) -> Self {
+        let mut owners = Self::new(primary);
+        for id in additional.iter().filter(|v| **v != primary) {
+            owners.additional.insert_no_dupe(*id);
+        }
+
+        owners
+    }
+
+    #[inline]
+    pub fn is_primary(&self, id: JsSafeBigInt) -> bool {
+        self.primary == id
+    }
+
+    #[inline]
+    pub fn contains(&self, id: JsSafeBigInt) -> bool {
+        self.is_primary(id) || self.additional.contains(&id)
+    }
+
+    /// Every owner, starting with the primary owner.
+    pub fn ids(&self) -> Vec<JsSafeBigInt> {
+        std::iter::once(self.primary)
+            .chain(self.additional.iter().copied())
+            .collect()
+    }
+}
+
+#[cfg(feature = "server")]
+impl Owners {
+    pub fn validate(&self) -> ApiResult<()> {
+        Ok(self.check()?)
+    }
+
+    pub fn add(&mut self, id: JsSafeBigInt) -> ApiResult<()> {
+        if self.contains(id) {
+            return Err(ApiError::validation("This user is already an owner."));
+        }
+
+        if self.additional.len() >= MAX_ADDITIONAL_OWNERS {
+            return Err(ApiError::validation(format!(
+                "There can be at most {} additional owners.",
+                MAX_ADDITIONAL_OWNERS
+            )));
+        }
+
+        self.additional.push(id);
+        Ok(())
+    }
+
+    /// Removes an additional owner, the primary owner can only be replaced
+    /// through [Owners::transfer].
+    pub fn remove(&mut self, id: JsSafeBigInt) -> ApiResult<()> {
+        if self.is_primary(id) {
+            return Err(ApiError::validation(
+                "The primary owner cannot be removed, transfer ownership first.",
+            ));
+        }
+
+        let before = self.additional.len();
+        self.additional.as_mut().retain(|v| *v != id);
+        if self.additional.len() == before {
+            return Err(ApiError::not_found("This user is not an owner."));
+        }
+
+        Ok(())
+    }
+
+    /// Makes `new_primary` the primary owner, returning the previous one.
+    ///
+    /// The previous primary owner stays on as an additional owner when
+    /// `keep_previous` is set.
+    pub fn transfer(
+        &mut self,
+        new_primary: JsSafeBigInt,
+        keep_previous: bool,
+    ) -> ApiResult<JsSafeBigInt> {
+        if self.is_primary(new_primary) {
+            return Err(ApiError::validation(
+                "This user is already the primary owner.",
+            ));
+        }
+
+        let mut additional = self.additional.clone();
+        additional.as_mut().retain(|v| *v != new_primary);
+        if keep_previous {
+            if additional.len() >= MAX_ADDITIONAL_OWNERS {
+                return Err(ApiError::validation(format!(
+                    "There can be at most {} additional owners.",
+                    MAX_ADDITIONAL_OWNERS
+                )));
+            }
+            additional.push(self.primary);
+        }
+
+        let previous = std::mem::replace(&mut self.primary, new_primary);
+        self.additional = additional;
+        Ok(previous)
+    }
+}
+
+#[cfg(feature = "server")]
+impl Validate for Owners {
+    fn validate_fields(&self, errors: &mut ValidationErrors) {
+        if self.additional.contains(&self.primary) {
+            errors.add(
+                "additional",
+                "The primary owner cannot also be an additional owner.",
+            );
+        }
+
+        if self.additional.len() > MAX_ADDITIONAL_OWNERS {
+            errors.add(
+                "additional",
+                format!(
+                    "There can be at most {} additional owners.",
+                    MAX_ADDITIONAL_OWNERS
+                ),
+            );
+        }
+
+        let mut seen = Vec::with_capacity(self.additional.len());
+        for (i, id) in self.additional.iter().enumerate() {
+            if seen.contains(id) {
+                errors.add(
+                    format!("additional.{}", i),
+                    "Additional owners must be unique.",
+                );
+            }
+            seen.push(*id);
+        }
+    }
+}
+
+#[cfg(all(test, feature = "server"))]
+mod tests {
+    use super::*;
+    use scylla::cql_to_rust::FromCqlVal;
+    use scylla::frame::response::result::CqlValue;
+
+    fn id(v: i64) -> JsSafeBigInt {
+        JsSafeBigInt(v)
+    }
+
+    #[test]
+    fn test_from_columns() {
+        let owners = Owners::from_columns(id(1), &[id(2), id(1), id(2), id(3)]);
+
+        assert_eq!(owners.ids(), [id(1), id(2), id(3)]);
+        assert!(owners.validate().is_ok());
+        assert!(owners.contains(id(3)));
+        assert!(!owners.is_primary(id(3)));
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut owners = Owners::new(id(1));
+        owners.additional.push(id(1));
+        assert!(owners.validate().is_err());
+
+        let owners = Owners {
+            primary: id(1),
+            additional: Set(vec![id(2), id(2)]),
+        };
+        assert!(owners.validate().is_err());
+        assert_eq!(
+            owners.check().unwrap_err().get("additional.1"),
+            Some("Additional owners must be unique.")
+        );
+    }
+
+    #[test]
+    fn test_add_and_remove() {
+        let mut owners = Owners::new(id(1));
+        for v in 2..2 + MAX_ADDITIONAL_OWNERS as i64 {
+            owners.add(id(v)).unwrap();
+        }
+
+        assert!(owners.add(id(100)).is_err());
+        assert!(owners.add(id(2)).is_err());
+        assert!(owners.remove(id(1)).is_err());
+        assert!(owners.remove(id(100)).is_err());
+
+        owners.remove(id(2)).unwrap();
+        assert!(!owners.contains(id(2)));
+    }
+
+    #[test]
+    fn test_transfer() {
+        let mut owners = Owners::from_columns(id(1), &[id(2)]);
+
+        assert_eq!(owners.transfer(id(2), true).unwrap(), id(1));
+        assert_eq!(owners.ids(), [id(2), id(1)]);
+
+        assert_eq!(owners.transfer(id(3), false).unwrap(), id(2));
+        assert_eq!(owners.ids(), [id(3), id(1)]);
+        assert!(owners.transfer(id(3), true).is_err());
+
+        let mut full = Owners::from_columns(id(1), &(2..7).map(id).collect::<Vec<_>>());
+        assert!(full.transfer(id(100), true).is_err());
+        assert_eq!(full.primary, id(1));
+        assert!(full.transfer(id(2), true).is_ok());
+        assert!(full.validate().is_ok());
+    }
+
+    #[test]
+    fn test_cql_fallback() {
+        let owners = Owners::from_cql(CqlValue::BigInt(42)).unwrap();
+        assert_eq!(owners, Owners::new(id(42)));
+    }
+}
@@ -0,0 +1,146 @@
+//! `sqlx` Postgres mappings for the core types, stored as the same column
+//! types the values have in Scylla.
+//!
+//! | Type           | Column        |
+//! |----------------|---------------|
+//! | `JsSafeBigInt` | `int8`        |
+//! | `EntityFlags`  | `int8`        |
+//! | `Timestamp`    | `timestamptz` |
+//! | `DiscordUrl`   | `text`        |
+//! | `Set<T>`       | `T[]`         |
+
+use std::str::FromStr;
+
+use sqlx::encode::IsNull;
+use sqlx::error::BoxDynError;
+use sqlx::postgres::{PgArgumentBuffer, PgHasArrayType, PgTypeInfo, PgValueRef};
+use sqlx::{Decode, Encode, Postgres, Type};
+
+use crate::types::{DiscordUrl, EntityFlags, JsSafeBigInt, Set, Timestamp};
+
+/// Maps a newtype onto the Postgres type of its inner value.
+macro_rules! pg_newtype {
+    ($name:ident($inner:ty)) => {
+        impl Type<Postgres> for $name {
+            fn type_info() -> PgTypeInfo {
+                <$inner as Type<Postgres>>::type_info()
+            }
+
+            fn compatible(ty: &PgTypeInfo) -> bool {
+                <$inner as Type<Postgres>>::compatible(ty)
+            }
+        }
+
+        impl PgHasArrayType for $name {
+            fn array_type_info() -> PgTypeInfo {
+                <$inner as PgHasArrayType>::array_type_info()
+            }
+        }
+
+        impl Encode<'_, Postgres> for $name {
+            fn encode_by_ref(&self, buf: &mut PgArgumentBuffer) -> Result<IsNull, BoxDynError> {
+                self.0.encode_by_ref(buf)
+            }
+        }
+
+        impl<'r> Decode<'r, Postgres> for $name {
+            fn decode(value: PgValueRef<'r>) -> Result<Self, BoxDynError> {
+                <$inner as Decode<'r, Postgres>>::decode(value).map(Self)
+            }
+        }
+    };
+}
+
+pg_newtype!(JsSafeBigInt(i64));
+pg_newtype!(EntityFlags(i64));
+pg_newtype!(Timestamp(chrono::DateTime<chrono::Utc>));
+
+impl Type<Postgres> for DiscordUrl {
+    fn type_info() -> PgTypeInfo {
+        <String as Type<Postgres>>::type_info()
+    }
+
+    fn compatible(ty: &PgTypeInfo) -> bool {
+        <String as Type<Postgres>>::compatible(ty)
+    }
+}
+
+impl PgHasArrayType for DiscordUrl {
+    fn array_type_info() -> PgTypeInfo {
+        <String as PgHasArrayType>::array_type_info()
+    }
+}
+
+impl Encode<'_, Postgres> for DiscordUrl {
+    fn encode_by_ref(&self, buf: &mut PgArgumentBuffer) -> Result<IsNull, BoxDynError> {
+        <&str as Encode<Postgres>>::encode(self.0.as_str(), buf)
+    }
+}
+
+impl<'r> Decode<'r, Postgres> for DiscordUrl {
+    fn decode(value: PgValueRef<'r>) -> Result<Self, BoxDynError> {
+        // Rows written by other services go through the same checks as user input.
+        Ok(Self::from_str(<&str as Decode<'r, Postgres>>::decode(
+            value,
+        )?)?)
+    }
+}
+
+impl<T: PgHasArrayType, const MAX: usize> Type<Postgres> for Set<T, MAX> {
+    fn type_info() -> PgTypeInfo {
+        <Vec<T> as Type<Postgres>>::type_info()
+    }
+
+    fn compatible(ty: &PgTypeInfo) -> bool {
+        <Vec<T> as Type<Postgres>>::compatible(ty)
+    }
+}
+
+impl<'q, T, const MAX: usize> Encode<'q, Postgres> for Set<T, MAX>
+where
+    T: Encode<'q, Postgres> + Type<Postgres> + PgHasArrayType,
+{
+    fn encode_by_ref(&self, buf: &mut PgArgumentBuffer) -> Result<IsNull, BoxDynError> {
+        <&[T] as Encode<Postgres>>::encode(self.0.as_slice(), buf)
+    }
+}
+
+// Like the other storage formats, the `MAX` cap is only applied to user input.
+impl<'r, T, const MAX: usize> Decode<'r, Postgres> for Set<T, MAX>
+where
+    T: for<'a> Decode<'a, Postgres> + Type<Postgres> + PgHasArrayType,
+{
+    fn decode(value: PgValueRef<'r>) -> Result<Self, BoxDynError> {
+        <Vec<T> as Decode<'r, Postgres>>::decode(value).map(Self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_column_types() {
+        assert_eq!(JsSafeBigInt::type_info(), PgTypeInfo::with_name("INT8"));
+        assert_eq!(EntityFlags::type_info(), PgTypeInfo::with_name("INT8"));
+        assert_eq!(Timestamp::type_info(), PgTypeInfo::with_name("TIMESTAMPTZ"));
+        assert_eq!(DiscordUrl::type_info(), PgTypeInfo::with_name("TEXT"));
+        assert_eq!(
+            Set::<JsSafeBigInt, 5>::type_info(),
+            PgTypeInfo::with_name("_INT8")
+        );
+        assert!(DiscordUrl::compatible(&PgTypeInfo::with_name("VARCHAR")));
+    }
+
+    #[test]
+    fn test_encode() {
+        let mut buf = PgArgumentBuffer::default();
+        let url = DiscordUrl::from_str("https://discordlist.gg/").unwrap();
+        assert!(matches!(url.encode_by_ref(&mut buf).unwrap(), IsNull::No));
+        assert_eq!(&**buf, b"https://discordlist.gg/");
+
+        let mut buf = PgArgumentBuffer::default();
+        let _ = JsSafeBigInt(1).encode_by_ref(&mut buf).unwrap();
+        assert_eq!(&**buf, 1i64.to_be_bytes());
+    }
+}
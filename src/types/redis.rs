@@ -0,0 +1,112 @@
+//! `redis` arguments and values for the shared types, enabled by the `redis`
+//! feature.
+//!
+//! Values are stored in their [Versioned] bincode representation so they can
+//! be cached without wrapping them in a newtype.
+
+use redis::{ErrorKind, FromRedisValue, RedisResult, RedisWrite, ToRedisArgs, Value};
+
+use crate::codec::{Versioned, VersionedDecode};
+use crate::types::{DiscordUrl, JsSafeBigInt, Set, Timestamp};
+
+pub(crate) fn write_versioned<T: VersionedDecode, W: ?Sized + RedisWrite>(value: &T, out: &mut W) {
+    let bytes = Versioned::encode(value).expect("bincode encoding into a vec cannot fail");
+    out.write_arg(&bytes);
+}
+
+pub(crate) fn read_versioned<T: VersionedDecode>(value: &Value) -> RedisResult<T> {
+    match value {
+        Value::Data(bytes) => Versioned::decode(bytes)
+            .map_err(|e| (ErrorKind::TypeError, "Invalid bincode value", e.to_string()).into()),
+        other => Err((
+            ErrorKind::TypeError,
+            "Expected a bincode value",
+            format!("{:?}", other),
+        )
+            .into()),
+    }
+}
+
+macro_rules! redis_versioned {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl ToRedisArgs for $ty {
+                fn write_redis_args<W: ?Sized + RedisWrite>(&self, out: &mut W) {
+                    write_versioned(self, out)
+                }
+            }
+
+            impl FromRedisValue for $ty {
+                fn from_redis_value(v: &Value) -> RedisResult<Self> {
+                    read_versioned(v)
+                }
+            }
+        )*
+    };
+}
+
+redis_versioned!(JsSafeBigInt, Timestamp, DiscordUrl);
+
+impl<T: VersionedDecode, const MAX: usize> ToRedisArgs for Set<T, MAX> {
+    fn write_redis_args<W: ?Sized + RedisWrite>(&self, out: &mut W) {
+        write_versioned(self, out)
+    }
+}
+
+impl<T: VersionedDecode, const MAX: usize> FromRedisValue for Set<T, MAX> {
+    fn from_redis_value(v: &Value) -> RedisResult<Self> {
+        read_versioned(v)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    fn round_trip<T: ToRedisArgs + FromRedisValue>(value: &T) -> T {
+        let mut args = value.to_redis_args();
+        assert_eq!(args.len(), 1);
+        T::from_redis_value(&Value::Data(args.remove(0))).unwrap()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        assert_eq!(round_trip(&JsSafeBigInt(1234)), JsSafeBigInt(1234));
+        assert_eq!(
+            round_trip(&Timestamp::from(1_650_000_000)),
+            Timestamp::from(1_650_000_000)
+        );
+
+        let url = DiscordUrl::from_str("https://discordlist.gg").unwrap();
+        assert_eq!(round_trip(&url), url);
+
+        let set: Set<JsSafeBigInt, 5> = Set(vec![JsSafeBigInt(1), JsSafeBigInt(2)]);
+        assert_eq!(round_trip(&set).0, set.0);
+    }
+
+    #[test]
+    fn test_invalid_values() {
+        assert!(JsSafeBigInt::from_redis_value(&Value::Nil).is_err());
+        assert!(Timestamp::from_redis_value(&Value::Data(vec![])).is_err());
+
+        // Unversioned payloads and trailing bytes are both rejected.
+        let raw = bincode::encode_to_vec(JsSafeBigInt(1234), bincode::config::standard()).unwrap();
+        assert!(JsSafeBigInt::from_redis_value(&Value::Data(raw)).is_err());
+        let mut bytes = JsSafeBigInt(1234).to_redis_args().remove(0);
+        bytes.push(0);
+        assert!(JsSafeBigInt::from_redis_value(&Value::Data(bytes)).is_err());
+        assert_eq!(
+            Option::<JsSafeBigInt>::from_redis_value(&Value::Nil).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_out_of_range_timestamp() {
+        let mut bytes = 1u16.to_le_bytes().to_vec();
+        bytes.extend(bincode::encode_to_vec(i64::MAX, bincode::config::standard()).unwrap());
+        assert!(Timestamp::from_redis_value(&Value::Data(bytes)).is_err());
+    }
+}
@@ -0,0 +1,116 @@
+use std::fmt::{Display, Formatter};
+use std::ops::Deref;
+use std::str::FromStr;
+
+use serde::de::Error;
+use serde::{Deserializer, Serializer};
+use uuid::Uuid;
+
+/// The header a [RequestId] is passed along in.
+pub const REQUEST_ID_HEADER: &str = "X-Request-Id";
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+/// The id which correlates a request across every service it passes through.
+///
+/// New ids are UUIDv7 so they sort by the time the request started.
+pub struct RequestId(pub Uuid);
+
+impl RequestId {
+    pub fn new() -> Self {
+        Self(Uuid::now_v7())
+    }
+}
+
+impl Default for RequestId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl serde::Serialize for RequestId {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for RequestId {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        Self::from_str(&s).map_err(D::Error::custom)
+    }
+}
+
+impl From<Uuid> for RequestId {
+    fn from(v: Uuid) -> Self {
+        Self(v)
+    }
+}
+
+impl Display for RequestId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Deref for RequestId {
+    type Target = Uuid;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl FromStr for RequestId {
+    type Err = InvalidRequestId;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Uuid::parse_str(s.trim())
+            .map(Self)
+            .map_err(|_| InvalidRequestId)
+    }
+}
+
+/// A request id which is not a UUID.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidRequestId;
+
+impl Display for InvalidRequestId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid request id")
+    }
+}
+
+impl std::error::Error for InvalidRequestId {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sortable() {
+        let first = RequestId::new();
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        let second = RequestId::new();
+
+        assert_eq!(first.get_version_num(), 7);
+        assert!(first < second);
+        assert!(first.to_string() < second.to_string());
+    }
+
+    #[test]
+    fn test_parse() {
+        let id = RequestId::new();
+        assert_eq!(RequestId::from_str(&id.to_string()).unwrap(), id);
+        assert_eq!(
+            serde_json::from_value::<RequestId>(serde_json::to_value(id).unwrap()).unwrap(),
+            id
+        );
+        assert!(RequestId::from_str("req-1").is_err());
+    }
+}
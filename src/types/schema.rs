@@ -0,0 +1,283 @@
+//! `utoipa` schemas for the shared types, mirroring their `poem_openapi`
+//! schemas so services built on other frameworks can document them too.
+//!
+//! Generic types are named after their parameters as each instantiation
+//! has different constraints.
+
+use std::borrow::Cow;
+
+use utoipa::openapi::schema::{ArrayBuilder, KnownFormat, ObjectBuilder, SchemaFormat, Type};
+use utoipa::openapi::{RefOr, Schema};
+use utoipa::{PartialSchema, ToSchema};
+
+use crate::types::url::{constraints::ConstrainedUrl, ConstrainedDiscordUrl};
+use crate::types::{
+    BoundedInt, DiscordUrl, EntityFlag, EntityFlags, JsSafeBigInt, JsSafeInt, RequestId,
+    SafeOutboundUrl, Set, Timestamp,
+};
+#[cfg(feature = "server")]
+use crate::types::{DiscordInvite, HumanDuration, NormalisingString};
+
+type Schemas = Vec<(String, RefOr<Schema>)>;
+
+fn string(format: Option<KnownFormat>) -> ObjectBuilder {
+    ObjectBuilder::new()
+        .schema_type(Type::String)
+        .format(format.map(SchemaFormat::KnownFormat))
+}
+
+fn url() -> RefOr<Schema> {
+    string(Some(KnownFormat::Uri)).into()
+}
+
+impl PartialSchema for JsSafeBigInt {
+    fn schema() -> RefOr<Schema> {
+        string(None)
+            .description(Some("A 64 bit integer encoded as a string."))
+            .pattern(Some("^-?[0-9]+$"))
+            .into()
+    }
+}
+
+impl ToSchema for JsSafeBigInt {
+    fn name() -> Cow<'static, str> {
+        Cow::Borrowed("BigInt")
+    }
+}
+
+impl PartialSchema for JsSafeInt {
+    fn schema() -> RefOr<Schema> {
+        ObjectBuilder::new()
+            .schema_type(Type::Integer)
+            .format(Some(SchemaFormat::KnownFormat(KnownFormat::Int64)))
+            .into()
+    }
+}
+
+impl ToSchema for JsSafeInt {
+    fn name() -> Cow<'static, str> {
+        Cow::Borrowed("Integer")
+    }
+}
+
+impl<const MIN: i64, const MAX: i64> PartialSchema for BoundedInt<MIN, MAX> {
+    fn schema() -> RefOr<Schema> {
+        ObjectBuilder::new()
+            .schema_type(Type::Integer)
+            .format(Some(SchemaFormat::KnownFormat(KnownFormat::Int64)))
+            .minimum(Some(MIN))
+            .maximum(Some(MAX))
+            .into()
+    }
+}
+
+impl<const MIN: i64, const MAX: i64> ToSchema for BoundedInt<MIN, MAX> {
+    fn name() -> Cow<'static, str> {
+        Cow::Owned(format!("Integer_{}_{}", MIN, MAX))
+    }
+}
+
+#[cfg(feature = "server")]
+impl PartialSchema for HumanDuration {
+    fn schema() -> RefOr<Schema> {
+        string(None)
+            .description(Some("A duration such as `1h30m`."))
+            .pattern(Some("^([0-9]+[dhms])+$"))
+            .into()
+    }
+}
+
+#[cfg(feature = "server")]
+impl ToSchema for HumanDuration {}
+
+impl PartialSchema for Timestamp {
+    fn schema() -> RefOr<Schema> {
+        string(Some(KnownFormat::DateTime)).into()
+    }
+}
+
+impl ToSchema for Timestamp {}
+
+impl PartialSchema for RequestId {
+    fn schema() -> RefOr<Schema> {
+        string(Some(KnownFormat::Uuid)).into()
+    }
+}
+
+impl ToSchema for RequestId {}
+
+impl PartialSchema for EntityFlags {
+    fn schema() -> RefOr<Schema> {
+        ArrayBuilder::new().items(EntityFlag::schema()).into()
+    }
+}
+
+impl ToSchema for EntityFlags {
+    fn schemas(schemas: &mut Schemas) {
+        schemas.push((EntityFlag::name().into_owned(), EntityFlag::schema()));
+    }
+}
+
+impl PartialSchema for DiscordUrl {
+    fn schema() -> RefOr<Schema> {
+        url()
+    }
+}
+
+impl ToSchema for DiscordUrl {
+    fn name() -> Cow<'static, str> {
+        Cow::Borrowed("Url")
+    }
+}
+
+impl<T: ConstrainedUrl> PartialSchema for ConstrainedDiscordUrl<T> {
+    fn schema() -> RefOr<Schema> {
+        url()
+    }
+}
+
+impl<T: ConstrainedUrl> ToSchema for ConstrainedDiscordUrl<T> {
+    fn name() -> Cow<'static, str> {
+        DiscordUrl::name()
+    }
+}
+
+impl PartialSchema for SafeOutboundUrl {
+    fn schema() -> RefOr<Schema> {
+        url()
+    }
+}
+
+impl ToSchema for SafeOutboundUrl {
+    fn name() -> Cow<'static, str> {
+        DiscordUrl::name()
+    }
+}
+
+#[cfg(feature = "server")]
+impl PartialSchema for DiscordInvite {
+    fn schema() -> RefOr<Schema> {
+        url()
+    }
+}
+
+#[cfg(feature = "server")]
+impl ToSchema for DiscordInvite {}
+
+impl<T: ToSchema, const MAX: usize> PartialSchema for Set<T, MAX> {
+    fn schema() -> RefOr<Schema> {
+        let max_items = (MAX != usize::MAX).then_some(MAX);
+        ArrayBuilder::new()
+            .items(T::schema())
+            .max_items(max_items)
+            .into()
+    }
+}
+
+impl<T: ToSchema, const MAX: usize> ToSchema for Set<T, MAX> {
+    fn name() -> Cow<'static, str> {
+        match MAX {
+            usize::MAX => Cow::Owned(format!("Set_{}", T::name())),
+            max => Cow::Owned(format!("Set_{}_{}", T::name(), max)),
+        }
+    }
+
+    fn schemas(schemas: &mut Schemas) {
+        T::schemas(schemas);
+    }
+}
+
+#[cfg(feature = "server")]
+impl<const MIN: usize, const MAX: usize, const REF_REAL: bool> PartialSchema
+    for NormalisingString<MIN, MAX, REF_REAL>
+{
+    fn schema() -> RefOr<Schema> {
+        string(None)
+            .min_length(Some(MIN))
+            .max_length(Some(MAX))
+            .into()
+    }
+}
+
+#[cfg(feature = "server")]
+impl<const MIN: usize, const MAX: usize, const REF_REAL: bool> ToSchema
+    for NormalisingString<MIN, MAX, REF_REAL>
+{
+    fn name() -> Cow<'static, str> {
+        Cow::Owned(format!("String_{}_{}", MIN, MAX))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use utoipa::openapi::ComponentsBuilder;
+
+    use super::*;
+    #[cfg(feature = "server")]
+    use crate::types::InviteResolution;
+    use crate::types::{Owners, MAX_ADDITIONAL_OWNERS};
+
+    fn to_json<T: PartialSchema>() -> serde_json::Value {
+        serde_json::to_value(T::schema()).unwrap()
+    }
+
+    #[test]
+    fn test_scalar_schemas() {
+        assert_eq!(to_json::<JsSafeBigInt>()["type"], "string");
+        assert_eq!(to_json::<Timestamp>()["format"], "date-time");
+        assert_eq!(to_json::<SafeOutboundUrl>()["format"], "uri");
+        assert_eq!(
+            to_json::<BoundedInt<1, 5>>(),
+            json!({"type": "integer", "format": "int64", "minimum": 1, "maximum": 5})
+        );
+        #[cfg(feature = "server")]
+        assert_eq!(to_json::<NormalisingString<2, 32, true>>()["maxLength"], 32);
+        assert_eq!(
+            to_json::<EntityFlags>()["items"]["enum"],
+            json!([
+                "verified",
+                "certified",
+                "premium",
+                "staff",
+                "banned",
+                "nsfw",
+                "hidden"
+            ])
+        );
+    }
+
+    fn component_schemas<T: ToSchema>() -> serde_json::Value {
+        let mut schemas = vec![(T::name().into_owned(), T::schema())];
+        T::schemas(&mut schemas);
+        serde_json::to_value(
+            ComponentsBuilder::new()
+                .schemas_from_iter(schemas)
+                .build()
+                .schemas,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_derived_schemas() {
+        let schemas = component_schemas::<Owners>();
+        let owners = &schemas["Owners"]["properties"];
+        assert_eq!(owners["primary"]["$ref"], "#/components/schemas/BigInt");
+        assert_eq!(owners["additional"]["maxItems"], MAX_ADDITIONAL_OWNERS);
+        assert_eq!(
+            owners["additional"]["items"]["$ref"],
+            "#/components/schemas/BigInt"
+        );
+        assert_eq!(schemas["BigInt"]["type"], "string");
+    }
+
+    #[cfg(feature = "server")]
+    #[test]
+    fn test_server_schemas() {
+        let schemas = component_schemas::<InviteResolution>();
+        let status = &schemas["InviteStatus"]["enum"];
+        assert_eq!(status[0], "permanent");
+        assert!(schemas["Timestamp"].is_object());
+    }
+}
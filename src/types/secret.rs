@@ -0,0 +1,117 @@
+use std::fmt::{Debug, Display, Formatter};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use zeroize::Zeroize;
+
+/// The value shown in place of redacted fields and secrets.
+pub const REDACTED: &str = "[redacted]";
+
+/// A value which must never be logged or returned, such as a token, signing
+/// key or password.
+///
+/// It is redacted when debug printed, displayed or serialized, the value is
+/// only reachable through [Secret::expose]. The memory holding it is zeroed
+/// once it is dropped.
+#[derive(Clone, Default)]
+pub struct Secret<T: Zeroize>(T);
+
+impl<T: Zeroize> Secret<T> {
+    pub fn new(value: T) -> Self {
+        Self(value)
+    }
+
+    #[inline]
+    pub fn expose(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: Zeroize> From<T> for Secret<T> {
+    fn from(value: T) -> Self {
+        Self(value)
+    }
+}
+
+impl<T: Zeroize> Drop for Secret<T> {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl<T: Zeroize> Debug for Secret<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", REDACTED)
+    }
+}
+
+impl<T: Zeroize> Display for Secret<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", REDACTED)
+    }
+}
+
+impl<T: Zeroize> Serialize for Secret<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(REDACTED)
+    }
+}
+
+impl<'de, T: Zeroize + Deserialize<'de>> Deserialize<'de> for Secret<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        T::deserialize(deserializer).map(Self)
+    }
+}
+
+/// Serializes the secret's value, for use with `#[serde(serialize_with)]`
+/// where the value really has to be written out, such as when it is sent
+/// to the service it belongs to.
+pub fn serialize_exposed<T, S>(secret: &Secret<T>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Zeroize + Serialize,
+    S: Serializer,
+{
+    secret.expose().serialize(serializer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize, Deserialize)]
+    struct Credentials {
+        username: String,
+        password: Secret<String>,
+        #[serde(serialize_with = "serialize_exposed")]
+        token: Secret<String>,
+    }
+
+    #[test]
+    fn test_redacted_output() {
+        let secret = Secret::new("hunter2".to_string());
+
+        assert_eq!(format!("{:?}", secret), "[redacted]");
+        assert_eq!(secret.to_string(), "[redacted]");
+        assert_eq!(secret.expose(), "hunter2");
+    }
+
+    #[test]
+    fn test_serde() {
+        let credentials: Credentials = serde_json::from_str(
+            r#"{"username": "scylla", "password": "hunter2", "token": "abc"}"#,
+        )
+        .unwrap();
+        assert_eq!(credentials.password.expose(), "hunter2");
+
+        let json = serde_json::to_string(&credentials).unwrap();
+        assert_eq!(
+            json,
+            r#"{"username":"scylla","password":"[redacted]","token":"abc"}"#
+        );
+    }
+}
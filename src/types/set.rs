@@ -1,30 +1,60 @@
-use std::borrow::Cow;
 use std::fmt::{Debug, Display, Formatter};
+use std::marker::PhantomData;
 use std::ops::{Deref, DerefMut};
 
-#[cfg(feature = "bincode")]
-use bincode::{Decode, Encode};
+use serde::de::{Error, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize};
 
-use poem_openapi::registry::{MetaSchemaRef, Registry};
-use poem_openapi::types::{ParseError, ParseFromJSON, ParseResult, ToJSON, Type};
-use scylla::cql_to_rust::{FromCqlVal, FromCqlValError};
-use scylla::frame::response::result::CqlValue;
-use scylla::frame::value::ValueTooBig;
-use serde::{Deserialize, Serialize};
-use serde_json::Value;
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
+/// A list of values, optionally capped to `MAX` items when parsed from user input.
+pub struct Set<T, const MAX: usize = { usize::MAX }>(pub Vec<T>);
 
-#[cfg_attr(feature = "bincode", derive(Decode, Encode))]
-#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
-pub struct Set<T>(pub Vec<T>);
-
-impl<T> Set<T> {
+impl<T, const MAX: usize> Set<T, MAX> {
     #[inline]
     pub fn push(&mut self, v: T) {
         self.0.push(v)
     }
+
+    pub(super) fn too_many_items() -> String {
+        format!("Set contains more than the maximum of {} items.", MAX)
+    }
+}
+
+impl<'de, T: Deserialize<'de>, const MAX: usize> Deserialize<'de> for Set<T, MAX> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct SetVisitor<T, const MAX: usize>(PhantomData<T>);
+
+        impl<'de, T: Deserialize<'de>, const MAX: usize> Visitor<'de> for SetVisitor<T, MAX> {
+            type Value = Set<T, MAX>;
+
+            fn expecting(&self, f: &mut Formatter) -> std::fmt::Result {
+                write!(f, "a list of at most {} items", MAX)
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let hint = seq.size_hint().unwrap_or_default().min(MAX).min(1024);
+                let mut inner = Vec::with_capacity(hint);
+
+                // Stop as soon as the cap is passed rather than reading the whole list.
+                while let Some(v) = seq.next_element()? {
+                    if inner.len() == MAX {
+                        return Err(A::Error::custom(Set::<T, MAX>::too_many_items()));
+                    }
+                    inner.push(v);
+                }
+
+                Ok(Set(inner))
+            }
+        }
+
+        deserializer.deserialize_seq(SetVisitor::<T, MAX>(PhantomData))
+    }
 }
 
-impl<T: PartialEq> Set<T> {
+impl<T: PartialEq, const MAX: usize> Set<T, MAX> {
     pub fn insert_no_dupe(&mut self, v: T) {
         if self.0.contains(&v) {
             return;
@@ -39,31 +69,31 @@ impl<T: PartialEq> Set<T> {
     }
 }
 
-impl<T> Default for Set<T> {
+impl<T, const MAX: usize> Default for Set<T, MAX> {
     fn default() -> Self {
         Self(vec![])
     }
 }
 
-impl<T> From<Vec<T>> for Set<T> {
+impl<T, const MAX: usize> From<Vec<T>> for Set<T, MAX> {
     fn from(v: Vec<T>) -> Self {
         Self(v)
     }
 }
 
-impl<T> FromIterator<T> for Set<T> {
+impl<T, const MAX: usize> FromIterator<T> for Set<T, MAX> {
     fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
         Self(Vec::from_iter(iter))
     }
 }
 
-impl<T> Display for Set<T> {
+impl<T, const MAX: usize> Display for Set<T, MAX> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(f, "Set<{}>", std::any::type_name::<T>())
     }
 }
 
-impl<T> Deref for Set<T> {
+impl<T, const MAX: usize> Deref for Set<T, MAX> {
     type Target = [T];
 
     fn deref(&self) -> &Self::Target {
@@ -71,77 +101,31 @@ impl<T> Deref for Set<T> {
     }
 }
 
-impl<T> DerefMut for Set<T> {
+impl<T, const MAX: usize> DerefMut for Set<T, MAX> {
     fn deref_mut(&mut self) -> &mut Self::Target {
         &mut self.0
     }
 }
 
-impl<T> AsMut<Vec<T>> for Set<T> {
+impl<T, const MAX: usize> AsMut<Vec<T>> for Set<T, MAX> {
     fn as_mut(&mut self) -> &mut Vec<T> {
         &mut self.0
     }
 }
 
-impl<T: Type> Type for Set<T> {
-    const IS_REQUIRED: bool = true;
-    type RawValueType = <Vec<T> as Type>::RawValueType;
-    type RawElementValueType = <Vec<T> as Type>::RawElementValueType;
-
-    fn name() -> Cow<'static, str> {
-        Cow::Owned(format!("Set<{}>", T::name()))
-    }
-
-    fn schema_ref() -> MetaSchemaRef {
-        Vec::<T>::schema_ref()
-    }
-
-    fn register(registry: &mut Registry) {
-        T::register(registry);
-    }
-
-    fn as_raw_value(&self) -> Option<&Self::RawValueType> {
-        self.0.as_raw_value()
-    }
-
-    fn raw_element_iter<'a>(
-        &'a self,
-    ) -> Box<dyn Iterator<Item = &'a Self::RawElementValueType> + 'a> {
-        self.0.raw_element_iter()
-    }
-
-    fn is_empty(&self) -> bool {
-        self.0.is_empty()
-    }
-}
-
-impl<T: ToJSON> ToJSON for Set<T> {
-    fn to_json(&self) -> Option<Value> {
-        self.0.to_json()
-    }
-}
-
-impl<T: ParseFromJSON> ParseFromJSON for Set<T> {
-    fn parse_from_json(value: Option<Value>) -> ParseResult<Self> {
-        let inner =
-            Vec::<T>::parse_from_json(value).map_err(|e| ParseError::custom(e.into_message()))?;
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        Ok(Self(inner))
-    }
-}
+    #[test]
+    fn test_deserialize_enforces_max() {
+        let set: Set<u8, 2> = serde_json::from_str("[1, 2]").unwrap();
+        assert_eq!(set.0, [1, 2]);
 
-impl<T: FromCqlVal<CqlValue>> FromCqlVal<Option<CqlValue>> for Set<T> {
-    fn from_cql(cql_val: Option<CqlValue>) -> Result<Self, FromCqlValError> {
-        if let Some(v) = cql_val {
-            Ok(Self(Vec::<T>::from_cql(v)?))
-        } else {
-            Ok(Self(Default::default()))
-        }
-    }
-}
+        let err = serde_json::from_str::<Set<u8, 2>>("[1, 2, 3]").unwrap_err();
+        assert!(err.to_string().contains("maximum of 2 items"));
 
-impl<T: scylla::frame::value::Value> scylla::frame::value::Value for Set<T> {
-    fn serialize(&self, buf: &mut Vec<u8>) -> Result<(), ValueTooBig> {
-        self.0.serialize(buf)
+        let set: Set<u8> = serde_json::from_str("[1, 2, 3]").unwrap();
+        assert_eq!(set.len(), 3);
     }
 }
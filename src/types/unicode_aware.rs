@@ -4,20 +4,15 @@ use std::fmt::{Debug, Display, Formatter};
 use std::ops::Deref;
 
 #[cfg(feature = "bincode")]
-use bincode::{
-    de::Decoder,
-    enc::Encoder,
-    error::{DecodeError, EncodeError},
-    Decode, Encode,
-};
-use poem_openapi::registry::MetaSchemaRef;
+use bincode::{Decode, Encode};
+use poem_openapi::registry::{MetaSchema, MetaSchemaRef};
 use poem_openapi::types::{ParseError, ParseFromJSON, ParseResult, ToJSON, Type};
 use scylla::cql_to_rust::{FromCqlVal, FromCqlValError};
 use scylla::frame::response::result::CqlValue;
 use scylla::frame::value::ValueTooBig;
 use serde_json::Value;
 
-#[derive(Debug)]
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
 #[cfg_attr(feature = "bincode", derive(Decode, Encode))]
 /// A string type that normalises text to ASCII from unicode.
 ///
@@ -130,11 +125,15 @@ impl<const MIN: usize, const MAX: usize, const REF_REAL: bool> Type
     }
 
     fn schema_ref() -> MetaSchemaRef {
-        String::schema_ref()
+        MetaSchemaRef::Inline(Box::new(MetaSchema {
+            min_length: Some(MIN),
+            max_length: Some(MAX),
+            ..MetaSchema::new("string")
+        }))
     }
 
     fn as_raw_value(&self) -> Option<&Self::RawValueType> {
-        Some(&self)
+        Some(self)
     }
 
     fn raw_element_iter<'a>(
@@ -244,4 +243,16 @@ mod tests {
         let s = NormalisingString::<2, 20, true>::parse_from_json(Some(json!(thing)));
         assert!(s.is_ok(), "Expected successful parse");
     }
+
+    #[test]
+    fn test_schema_contains_length_constraints() {
+        let schema = match NormalisingString::<5, 100, true>::schema_ref() {
+            MetaSchemaRef::Inline(schema) => schema,
+            MetaSchemaRef::Reference(_) => panic!("Expected inline schema"),
+        };
+
+        assert_eq!(schema.ty, "string");
+        assert_eq!(schema.min_length, Some(5));
+        assert_eq!(schema.max_length, Some(100));
+    }
 }
@@ -1,20 +1,14 @@
-use std::borrow::Cow;
 use std::fmt::{Display, Formatter};
 use std::marker::PhantomData;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
 use std::ops::Deref;
 use std::str::FromStr;
 
 #[cfg(feature = "bincode")]
 use bincode::{Decode, Encode};
 
-use poem_openapi::registry::MetaSchemaRef;
-use poem_openapi::types::{ParseError, ParseFromJSON, ParseResult, ToJSON, Type};
-use scylla::cql_to_rust::{FromCqlVal, FromCqlValError};
-use scylla::frame::response::result::CqlValue;
-use scylla::frame::value::ValueTooBig;
 use serde::{Deserializer, Serializer};
-use serde_json::{json, Value};
-use url::Url;
+use url::{Host, Url};
 
 #[cfg_attr(feature = "bincode", derive(Decode, Encode))]
 #[derive(Clone, PartialEq, Eq, Hash, Debug)]
@@ -59,88 +53,46 @@ impl Deref for DiscordUrl {
     }
 }
 
-impl Type for DiscordUrl {
-    const IS_REQUIRED: bool = <Url as Type>::IS_REQUIRED;
-    type RawValueType = <Url as Type>::RawValueType;
-    type RawElementValueType = <Url as Type>::RawElementValueType;
-
-    fn name() -> Cow<'static, str> {
-        Cow::from("Url")
-    }
-
-    fn schema_ref() -> MetaSchemaRef {
-        Url::schema_ref()
-    }
-
-    fn as_raw_value(&self) -> Option<&Self::RawValueType> {
-        Some(&self.0)
-    }
-
-    fn raw_element_iter<'a>(
-        &'a self,
-    ) -> Box<dyn Iterator<Item = &'a Self::RawElementValueType> + 'a> {
-        self.0.raw_element_iter()
-    }
-}
-
-impl ToJSON for DiscordUrl {
-    fn to_json(&self) -> Option<Value> {
-        Some(json!(self.0.to_string()))
-    }
-}
-
-impl ParseFromJSON for DiscordUrl {
-    fn parse_from_json(value: Option<Value>) -> ParseResult<Self> {
-        let value = value.ok_or_else(|| ParseError::custom("Invalid url provided."))?;
-
-        if let Some(v) = value.as_str() {
-            let url = Url::from_str(v)?;
-
-            if !is_valid_url(&url) {
-                return Err(ParseError::custom("Invalid url provided."));
-            }
-
-            return Ok(Self(url));
-        }
-
-        Err(ParseError::custom("Invalid url provided."))
-    }
-}
-
 impl FromStr for DiscordUrl {
-    type Err = poem_openapi::types::ParseError<Self>;
+    type Err = InvalidUrl;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let url = Url::from_str(s)?;
+        let url = Url::from_str(s).map_err(InvalidUrl::Parse)?;
 
         if !is_valid_url(&url) {
-            return Err(ParseError::custom("Invalid url provided."));
+            return Err(InvalidUrl::Rejected);
         }
 
         Ok(Self(url))
     }
 }
 
-impl FromCqlVal<CqlValue> for DiscordUrl {
-    fn from_cql(cql_val: CqlValue) -> Result<Self, FromCqlValError> {
-        if let Some(v) = cql_val.as_text() {
-            Self::from_str(v).map_err(|_| FromCqlValError::BadCqlType)
-        } else {
-            Err(FromCqlValError::BadCqlType)
-        }
-    }
+/// Why a string is not a valid [DiscordUrl].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InvalidUrl {
+    Parse(url::ParseError),
+    /// The url parsed but is not an absolute http(s) url to a public host.
+    Rejected,
+    /// The url could point at our own infrastructure, see [SafeOutboundUrl].
+    Disallowed,
 }
 
-impl scylla::frame::value::Value for DiscordUrl {
-    fn serialize(&self, buf: &mut Vec<u8>) -> Result<(), ValueTooBig> {
-        self.0.as_str().serialize(buf)
+impl Display for InvalidUrl {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Parse(e) => write!(f, "{}", e),
+            Self::Rejected => write!(f, "Invalid url provided."),
+            Self::Disallowed => write!(f, "Url points to a disallowed address."),
+        }
     }
 }
 
-fn is_valid_url(url: &Url) -> bool {
+impl std::error::Error for InvalidUrl {}
+
+pub(super) fn is_valid_url(url: &Url) -> bool {
     if let Some(host) = url.host_str() {
         if host == "127.0.0.1" || host == "localhost" {
-            return false
+            return false;
         }
     }
 
@@ -156,7 +108,7 @@ pub struct ConstrainedDiscordUrl<T: constraints::ConstrainedUrl>(pub DiscordUrl,
 
 impl<T: constraints::ConstrainedUrl> From<DiscordUrl> for ConstrainedDiscordUrl<T> {
     fn from(v: DiscordUrl) -> Self {
-        Self(v, PhantomData::default())
+        Self(v, PhantomData)
     }
 }
 
@@ -174,114 +126,244 @@ impl<T: constraints::ConstrainedUrl> Deref for ConstrainedDiscordUrl<T> {
     }
 }
 
-impl<T: constraints::ConstrainedUrl + Sync + Send + 'static> Type for ConstrainedDiscordUrl<T> {
-    const IS_REQUIRED: bool = <DiscordUrl as Type>::IS_REQUIRED;
-    type RawValueType = <DiscordUrl as Type>::RawValueType;
-    type RawElementValueType = <DiscordUrl as Type>::RawElementValueType;
+impl<T: constraints::ConstrainedUrl> FromStr for ConstrainedDiscordUrl<T> {
+    type Err = InvalidUrl;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let slf = DiscordUrl::from_str(s)?;
 
-    fn name() -> Cow<'static, str> {
-        DiscordUrl::name()
+        if !T::is_valid(&slf) {
+            Err(InvalidUrl::Rejected)
+        } else {
+            Ok(Self::from(slf))
+        }
     }
+}
+
+/// Hostnames of cloud metadata services which resolve to internal addresses.
+const METADATA_HOSTS: &[&str] = &["metadata.google.internal", "metadata", "instance-data"];
+
+/// A user supplied URL which the backend makes requests to, e.g. webhooks.
+///
+/// On top of the [DiscordUrl] checks this rejects anything which could point
+/// at our own infrastructure: localhost, private and reserved IP literals,
+/// metadata endpoints and ports other than 80 and 443. Hostnames can still
+/// resolve to internal addresses so the resolved addresses should be checked
+/// with `resolve` before connecting.
+#[derive(Clone, PartialEq, Eq, Hash, Debug, serde::Serialize)]
+pub struct SafeOutboundUrl(pub DiscordUrl);
+
+impl<'de> serde::Deserialize<'de> for SafeOutboundUrl {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        Self::from_str(&s).map_err(serde::de::Error::custom)
+    }
+}
+
+impl SafeOutboundUrl {
+    /// Resolves the host, failing if any of the addresses are not public.
+    ///
+    /// Callers should connect to the returned addresses rather than resolving
+    /// the host again so the check cannot be bypassed with DNS rebinding.
+    #[cfg(feature = "outbound-dns")]
+    pub async fn resolve(&self) -> Result<Vec<std::net::SocketAddr>, OutboundUrlError> {
+        let host = self.host_str().ok_or(OutboundUrlError::NoAddresses)?;
+        let port = self.port_or_known_default().unwrap_or(443);
+
+        let addrs: Vec<std::net::SocketAddr> = tokio::net::lookup_host((host, port))
+            .await
+            .map_err(OutboundUrlError::Resolve)?
+            .collect();
+
+        if addrs.is_empty() {
+            return Err(OutboundUrlError::NoAddresses);
+        }
+
+        if let Some(addr) = addrs.iter().find(|v| !is_public_ip(v.ip())) {
+            return Err(OutboundUrlError::BlockedAddress(addr.ip()));
+        }
 
-    fn schema_ref() -> MetaSchemaRef {
-        DiscordUrl::schema_ref()
+        Ok(addrs)
     }
+}
+
+#[cfg(feature = "outbound-dns")]
+#[derive(Debug)]
+pub enum OutboundUrlError {
+    Resolve(std::io::Error),
+    NoAddresses,
+    BlockedAddress(IpAddr),
+}
 
-    fn as_raw_value(&self) -> Option<&Self::RawValueType> {
-        self.0.as_raw_value()
+#[cfg(feature = "outbound-dns")]
+impl Display for OutboundUrlError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Resolve(e) => write!(f, "failed to resolve host: {}", e),
+            Self::NoAddresses => write!(f, "host did not resolve to any addresses"),
+            Self::BlockedAddress(ip) => write!(f, "host resolved to blocked address {}", ip),
+        }
     }
+}
+
+#[cfg(feature = "outbound-dns")]
+impl std::error::Error for OutboundUrlError {}
 
-    fn raw_element_iter<'a>(
-        &'a self,
-    ) -> Box<dyn Iterator<Item = &'a Self::RawElementValueType> + 'a> {
-        self.0.raw_element_iter()
+impl Display for SafeOutboundUrl {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
     }
 }
 
-impl<T: constraints::ConstrainedUrl + Sync + Send + 'static> ToJSON for ConstrainedDiscordUrl<T> {
-    fn to_json(&self) -> Option<Value> {
-        self.0.to_json()
+impl Deref for SafeOutboundUrl {
+    type Target = DiscordUrl;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
     }
 }
 
-impl<T: constraints::ConstrainedUrl + Sync + Send + 'static> ParseFromJSON
-    for ConstrainedDiscordUrl<T>
-{
-    fn parse_from_json(value: Option<Value>) -> ParseResult<Self> {
-        let slf = DiscordUrl::parse_from_json(value).map_err(|e| e.propagate())?;
+impl FromStr for SafeOutboundUrl {
+    type Err = InvalidUrl;
 
-        if !T::is_valid(&slf) {
-            Err(ParseError::custom("Invalid url provided."))
-        } else {
-            Ok(Self::from(slf))
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let slf = DiscordUrl::from_str(s)?;
+
+        if !is_safe_outbound_url(&slf) {
+            return Err(InvalidUrl::Disallowed);
         }
+
+        Ok(Self(slf))
     }
 }
 
-impl<T: constraints::ConstrainedUrl + Sync + Send + 'static> FromStr for ConstrainedDiscordUrl<T> {
-    type Err = poem_openapi::types::ParseError<Self>;
+pub(super) fn is_safe_outbound_url(url: &Url) -> bool {
+    if !matches!(url.port(), None | Some(80) | Some(443)) {
+        return false;
+    }
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let slf = DiscordUrl::from_str(s).map_err(|e| e.propagate())?;
+    match url.host() {
+        Some(Host::Domain(domain)) => {
+            let domain = domain.trim_end_matches('.').to_ascii_lowercase();
 
-        if !T::is_valid(&slf) {
-            Err(ParseError::custom("Invalid url provided."))
-        } else {
-            Ok(Self::from(slf))
+            !(domain == "localhost"
+                || domain.ends_with(".localhost")
+                || domain.ends_with(".internal")
+                || domain.ends_with(".local")
+                || METADATA_HOSTS.contains(&domain.as_str()))
         }
+        Some(Host::Ipv4(ip)) => is_public_ip(IpAddr::V4(ip)),
+        Some(Host::Ipv6(ip)) => is_public_ip(IpAddr::V6(ip)),
+        None => false,
     }
 }
 
-impl<T: constraints::ConstrainedUrl> FromCqlVal<CqlValue> for ConstrainedDiscordUrl<T> {
-    fn from_cql(cql_val: CqlValue) -> Result<Self, FromCqlValError> {
-        let slf = DiscordUrl::from_cql(cql_val)?;
+/// Whether the address is publicly routable, this also covers the
+/// `169.254.169.254` metadata endpoint as it is link local.
+pub fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_ipv4(ip),
+        IpAddr::V6(ip) => {
+            if let Some(v4) = embedded_ipv4(ip) {
+                return is_public_ipv4(v4);
+            }
 
-        if !T::is_valid(&slf) {
-            Err(FromCqlValError::BadCqlType)
-        } else {
-            Ok(Self::from(slf))
+            is_public_ipv6(ip)
         }
     }
 }
 
-impl<T: constraints::ConstrainedUrl> scylla::frame::value::Value for ConstrainedDiscordUrl<T> {
-    fn serialize(&self, buf: &mut Vec<u8>) -> Result<(), ValueTooBig> {
-        self.0.serialize(buf)
+fn is_public_ipv4(ip: Ipv4Addr) -> bool {
+    let [a, b, ..] = ip.octets();
+
+    !(ip.is_private()
+        || ip.is_loopback()
+        || ip.is_link_local()
+        || ip.is_broadcast()
+        || ip.is_documentation()
+        || ip.is_unspecified()
+        || ip.is_multicast()
+        // "This network" 0.0.0.0/8
+        || a == 0
+        // Shared address space 100.64.0.0/10
+        || (a == 100 && (64..128).contains(&b))
+        // Benchmarking 198.18.0.0/15
+        || (a == 198 && (18..20).contains(&b))
+        // Reserved 240.0.0.0/4
+        || a >= 240)
+}
+
+/// The IPv4 address an IPv6 address carries for translation or tunnelling,
+/// these reach the IPv4 address so must pass the same checks.
+fn embedded_ipv4(ip: Ipv6Addr) -> Option<Ipv4Addr> {
+    let segments = ip.segments();
+    let last = |a: u16, b: u16| Ipv4Addr::from(((a as u32) << 16) | b as u32);
+
+    if let Some(v4) = ip.to_ipv4_mapped() {
+        return Some(v4);
+    }
+
+    match segments {
+        // IPv4 compatible ::a.b.c.d, this also covers :: and ::1
+        [0, 0, 0, 0, 0, 0, a, b] => Some(last(a, b)),
+        // NAT64 64:ff9b::/96
+        [0x64, 0xff9b, 0, 0, 0, 0, a, b] => Some(last(a, b)),
+        // 6to4 2002::/16
+        [0x2002, a, b, ..] => Some(last(a, b)),
+        _ => None,
     }
 }
 
+fn is_public_ipv6(ip: Ipv6Addr) -> bool {
+    let first = ip.segments()[0];
+
+    !(ip.is_loopback()
+        || ip.is_unspecified()
+        || ip.is_multicast()
+        // Unique local fc00::/7
+        || (first & 0xfe00) == 0xfc00
+        // Link local fe80::/10
+        || (first & 0xffc0) == 0xfe80
+        // Site local fec0::/10
+        || (first & 0xffc0) == 0xfec0
+        // Documentation 2001:db8::/32
+        || (first == 0x2001 && ip.segments()[1] == 0x0db8))
+}
+
 pub mod constraints {
     use crate::types::DiscordUrl;
 
     #[inline]
     fn twitter_url(url: &DiscordUrl) -> bool {
         url.domain()
-            .map(|v|[
-                "twitter.com",
-                "www.twitter.com",
-            ].contains(&v))
+            .map(|v| ["twitter.com", "www.twitter.com"].contains(&v))
             .unwrap_or_default()
     }
 
     #[inline]
     fn github_url(url: &DiscordUrl) -> bool {
-        url.domain().map(|v| [
-            "github.com",
-            "gitlab.com",
-            "bitbucket.org",
-            "www.github.com",
-            "www.gitlab.com",
-            "www.bitbucket.org",
-        ].contains(&v)).unwrap_or_default()
+        url.domain()
+            .map(|v| {
+                [
+                    "github.com",
+                    "gitlab.com",
+                    "bitbucket.org",
+                    "www.github.com",
+                    "www.gitlab.com",
+                    "www.bitbucket.org",
+                ]
+                .contains(&v)
+            })
+            .unwrap_or_default()
     }
 
     #[inline]
     fn instagram_url(url: &DiscordUrl) -> bool {
         url.domain()
-            .map(|v|[
-                "instagram.com",
-                "www.instagram.com",
-            ].contains(&v))
+            .map(|v| ["instagram.com", "www.instagram.com"].contains(&v))
             .unwrap_or_default()
     }
 
@@ -315,10 +397,7 @@ mod tests {
     #[test]
     fn test_ip_http_url() {
         let res = DiscordUrl::from_str("http://192.168.1.2:6000/zyxa");
-        assert!(
-            res.is_ok(),
-            "Expected url pass for raw ips."
-        );
+        assert!(res.is_ok(), "Expected url pass for raw ips.");
     }
 
     #[test]
@@ -339,6 +418,47 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_safe_outbound_url() {
+        let res = SafeOutboundUrl::from_str("https://example.com/webhook");
+        assert!(res.is_ok(), "Expected url pass for public hosts.");
+
+        let res = SafeOutboundUrl::from_str("https://example.com:443/webhook");
+        assert!(res.is_ok(), "Expected url pass for standard ports.");
+
+        for url in [
+            "http://192.168.1.2/zyxa",
+            "http://10.0.0.1/",
+            "http://169.254.169.254/latest/meta-data/",
+            "http://100.64.0.1/",
+            "http://[::1]/",
+            "http://[fd00::1]/",
+            "http://[::ffff:127.0.0.1]/",
+            "http://[::169.254.169.254]/",
+            "http://[64:ff9b::a9fe:a9fe]/",
+            "http://[2002:a00:1::]/",
+            "http://[fec0::1]/",
+            "http://2130706433/",
+            "http://metadata.google.internal/",
+            "http://api.localhost/",
+            "https://example.com:8080/webhook",
+        ] {
+            let res = SafeOutboundUrl::from_str(url);
+            assert!(res.is_err(), "Expected url rejection for {}", url);
+        }
+    }
+
+    #[test]
+    fn test_safe_outbound_url_deserialize() {
+        let url: SafeOutboundUrl =
+            serde_json::from_str(r#""https://example.com/webhook""#).unwrap();
+        assert_eq!(url.as_str(), "https://example.com/webhook");
+
+        assert!(serde_json::from_str::<SafeOutboundUrl>(r#""http://169.254.169.254/""#).is_err());
+        assert!(is_public_ip("2002:5db8:d822::".parse().unwrap()));
+        assert!(is_public_ip("64:ff9b::5db8:d822".parse().unwrap()));
+    }
+
     #[test]
     fn test_github_constrained_url_expect_ok() {
         let res = ConstrainedDiscordUrl::<GitHubUrl>::from_str("https://github.com");
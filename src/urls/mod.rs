@@ -0,0 +1,6 @@
+pub mod redirects;
+
+pub use redirects::{
+    get_redirects, set_redirects, RedirectEndpoint, RedirectError, RedirectLookup, RedirectLookups,
+    RedirectRule, RedirectStatus, RedirectTable,
+};
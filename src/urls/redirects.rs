@@ -0,0 +1,527 @@
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::sync::Arc;
+
+use once_cell::sync::Lazy;
+use poem::http::{Method, StatusCode};
+use poem::web::Redirect;
+use poem::{Endpoint, IntoResponse, Request, Response};
+
+use crate::live::LiveValue;
+
+static LOADED_REDIRECTS: Lazy<LiveValue<RedirectTable>> = Lazy::new(LiveValue::default);
+
+pub fn get_redirects() -> &'static LiveValue<RedirectTable> {
+    &LOADED_REDIRECTS
+}
+
+pub fn set_redirects(table: RedirectTable) {
+    LOADED_REDIRECTS.set(table);
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RedirectStatus {
+    /// `301`, clients may change the method to `GET` when following it.
+    MovedPermanently,
+    /// `308`, clients must repeat the request with the same method and body.
+    PermanentRedirect,
+}
+
+impl RedirectStatus {
+    /// `301` for safe methods so crawlers and old browsers handle it, `308`
+    /// for everything else so request bodies are not dropped.
+    pub fn for_method(method: &Method) -> Self {
+        if method == Method::GET || method == Method::HEAD {
+            Self::MovedPermanently
+        } else {
+            Self::PermanentRedirect
+        }
+    }
+
+    pub fn code(&self) -> StatusCode {
+        match self {
+            Self::MovedPermanently => StatusCode::MOVED_PERMANENTLY,
+            Self::PermanentRedirect => StatusCode::PERMANENT_REDIRECT,
+        }
+    }
+}
+
+/// A legacy path and the canonical path it now lives at.
+///
+/// Paths are made of literal segments, `:name` parameters matching a single
+/// segment and an optional trailing `*name` wildcard matching the rest of the
+/// path, e.g. `/bots/:id` or `/tags/*rest`. The target can use any of the
+/// parameters captured by the source, any other `:name` parameter is filled
+/// by the [RedirectLookup] registered under that name, e.g. `/bots/:id` to
+/// `/b/:slug`.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct RedirectRule {
+    pub from: String,
+    pub to: String,
+    /// Picked from the request method when not set.
+    #[serde(default)]
+    pub status: Option<RedirectStatus>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RedirectError {
+    /// The wildcard was not the last segment of the source path.
+    MisplacedWildcard(String),
+    /// The target uses a wildcard the source does not capture.
+    UnknownParameter { rule: String, name: String },
+    /// The target could be read as a link to another host, e.g. `/\evil.com`.
+    UnsafeTarget(String),
+}
+
+impl Display for RedirectError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MisplacedWildcard(rule) => {
+                write!(f, "wildcard must be the last segment of {:?}", rule)
+            }
+            Self::UnknownParameter { rule, name } => {
+                write!(f, "{:?} uses unknown parameter {:?}", rule, name)
+            }
+            Self::UnsafeTarget(rule) => {
+                write!(f, "{:?} could redirect to another host", rule)
+            }
+        }
+    }
+}
+
+impl std::error::Error for RedirectError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Literal(String),
+    Param(String),
+    Wildcard(String),
+}
+
+#[derive(Debug, Clone)]
+struct CompiledRule {
+    rule: RedirectRule,
+    segments: Vec<Segment>,
+    /// The target parameters which are looked up rather than captured.
+    lookups: Vec<String>,
+}
+
+impl CompiledRule {
+    fn compile(rule: RedirectRule) -> Result<Self, RedirectError> {
+        let segments: Vec<Segment> = split(&rule.from)
+            .map(|v| {
+                if let Some(name) = v.strip_prefix(':') {
+                    Segment::Param(name.to_string())
+                } else if let Some(name) = v.strip_prefix('*') {
+                    Segment::Wildcard(name.to_string())
+                } else {
+                    Segment::Literal(v.to_string())
+                }
+            })
+            .collect();
+
+        let wildcard_pos = segments
+            .iter()
+            .position(|v| matches!(v, Segment::Wildcard(_)));
+        if matches!(wildcard_pos, Some(pos) if pos != segments.len() - 1) {
+            return Err(RedirectError::MisplacedWildcard(rule.from));
+        }
+
+        if !is_safe_target(&rule.to) {
+            return Err(RedirectError::UnsafeTarget(rule.to));
+        }
+
+        let mut lookups = Vec::new();
+        for part in split(&rule.to) {
+            let Some(name) = part.strip_prefix([':', '*']) else {
+                continue;
+            };
+
+            let is_captured = segments.iter().any(|v| match v {
+                Segment::Param(n) | Segment::Wildcard(n) => n == name,
+                Segment::Literal(_) => false,
+            });
+
+            if is_captured {
+                continue;
+            } else if part.starts_with(':') {
+                lookups.push(name.to_string());
+            } else {
+                return Err(RedirectError::UnknownParameter {
+                    rule: rule.from,
+                    name: name.to_string(),
+                });
+            }
+        }
+
+        Ok(Self {
+            rule,
+            segments,
+            lookups,
+        })
+    }
+
+    fn captures<'a>(&self, path: &'a str) -> Option<BTreeMap<&str, String>> {
+        let mut parts = split(path);
+        let mut captures = BTreeMap::new();
+
+        for segment in self.segments.iter() {
+            match segment {
+                Segment::Literal(v) => {
+                    if parts.next()? != v {
+                        return None;
+                    }
+                }
+                Segment::Param(name) => {
+                    captures.insert(name.as_str(), parts.next()?.to_string());
+                }
+                Segment::Wildcard(name) => {
+                    let rest = parts.by_ref().collect::<Vec<&'a str>>().join("/");
+                    captures.insert(name.as_str(), rest);
+                }
+            }
+        }
+
+        if parts.next().is_some() {
+            return None;
+        }
+
+        Some(captures)
+    }
+
+    /// Fills in the looked up parameters, returns `false` if any of them could not be found.
+    async fn lookup<'a>(
+        &'a self,
+        captures: &mut BTreeMap<&'a str, String>,
+        lookups: &RedirectLookups,
+    ) -> poem::Result<bool> {
+        for name in self.lookups.iter() {
+            let Some(lookup) = lookups.0.get(name) else {
+                tracing::error!(rule = %self.rule.from, name = %name, "no redirect lookup registered");
+                return Ok(false);
+            };
+
+            match lookup.lookup(captures).await? {
+                Some(value) => captures.insert(name.as_str(), value),
+                None => return Ok(false),
+            };
+        }
+
+        Ok(true)
+    }
+
+    fn target(&self, captures: &BTreeMap<&str, String>) -> String {
+        let path = split(&self.rule.to)
+            .map(|v| match v.strip_prefix([':', '*']) {
+                Some(name) => captures.get(name).map(|v| v.as_str()).unwrap_or_default(),
+                None => v,
+            })
+            .filter(|v| !v.is_empty())
+            .collect::<Vec<_>>()
+            .join("/");
+
+        format!("/{}", path)
+    }
+}
+
+fn split(path: &str) -> impl Iterator<Item = &str> {
+    path.split('/').filter(|v| !v.is_empty())
+}
+
+/// Browsers treat `//host` and `/\host` as links to another host and ignore
+/// tabs and newlines in urls, so targets must not start with either.
+fn is_safe_target(target: &str) -> bool {
+    let mut chars = target.chars().filter(|c| !matches!(c, '\t' | '\r' | '\n'));
+    !matches!(
+        (chars.next(), chars.next()),
+        (Some('/' | '\\'), Some('/' | '\\'))
+    )
+}
+
+/// Looks up a target parameter which is not captured from the source path,
+/// e.g. the slug of the bot captured by `/bots/:id`.
+#[poem::async_trait]
+pub trait RedirectLookup: Send + Sync {
+    /// The parameter's value, `None` when there is nothing to redirect to.
+    async fn lookup(&self, captures: &BTreeMap<&str, String>) -> poem::Result<Option<String>>;
+}
+
+/// The lookups available to redirect rules keyed by the parameter they fill.
+#[derive(Clone, Default)]
+pub struct RedirectLookups(BTreeMap<String, Arc<dyn RedirectLookup>>);
+
+impl RedirectLookups {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register(
+        mut self,
+        name: impl Into<String>,
+        lookup: impl RedirectLookup + 'static,
+    ) -> Self {
+        self.0.insert(name.into(), Arc::new(lookup));
+        self
+    }
+}
+
+/// The set of legacy redirects, rules are checked in order and the first
+/// matching rule wins.
+#[derive(Debug, Clone, Default)]
+pub struct RedirectTable {
+    rules: Vec<CompiledRule>,
+}
+
+impl RedirectTable {
+    pub fn new(rules: Vec<RedirectRule>) -> Result<Self, RedirectError> {
+        let rules = rules
+            .into_iter()
+            .map(CompiledRule::compile)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self { rules })
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// The canonical path and status for the given path and query, if any rule matches.
+    ///
+    /// A rule whose lookups find nothing does not match.
+    pub async fn resolve(
+        &self,
+        method: &Method,
+        path: &str,
+        query: Option<&str>,
+        lookups: &RedirectLookups,
+    ) -> poem::Result<Option<(String, RedirectStatus)>> {
+        for compiled in self.rules.iter() {
+            let Some(mut captures) = compiled.captures(path) else {
+                continue;
+            };
+            if !compiled.lookup(&mut captures, lookups).await? {
+                continue;
+            }
+
+            let mut target = compiled.target(&captures);
+            if !is_safe_target(&target) {
+                tracing::warn!(path = %path, target = %target, "refusing unsafe redirect");
+                return Ok(None);
+            }
+
+            if let Some(query) = query.filter(|v| !v.is_empty()) {
+                target.push('?');
+                target.push_str(query);
+            }
+
+            let status = compiled
+                .rule
+                .status
+                .unwrap_or_else(|| RedirectStatus::for_method(method));
+
+            return Ok(Some((target, status)));
+        }
+
+        Ok(None)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for RedirectTable {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let rules = Vec::<RedirectRule>::deserialize(deserializer)?;
+        Self::new(rules).map_err(serde::de::Error::custom)
+    }
+}
+
+/// A catch-all endpoint which redirects legacy paths using the loaded redirect table.
+///
+/// Responds with `404 Not Found` when no rule matches.
+#[derive(Clone, Default)]
+pub struct RedirectEndpoint {
+    lookups: RedirectLookups,
+}
+
+impl RedirectEndpoint {
+    pub fn new(lookups: RedirectLookups) -> Self {
+        Self { lookups }
+    }
+}
+
+#[poem::async_trait]
+impl Endpoint for RedirectEndpoint {
+    type Output = Response;
+
+    async fn call(&self, req: Request) -> poem::Result<Self::Output> {
+        let table = get_redirects().get();
+        let uri = req.uri();
+
+        let resp = match table
+            .resolve(req.method(), uri.path(), uri.query(), &self.lookups)
+            .await?
+        {
+            Some((target, RedirectStatus::MovedPermanently)) => {
+                Redirect::moved_permanent(target).into_response()
+            }
+            Some((target, RedirectStatus::PermanentRedirect)) => {
+                Redirect::permanent(target).into_response()
+            }
+            None => StatusCode::NOT_FOUND.into_response(),
+        };
+
+        Ok(resp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    struct BotSlugs;
+
+    #[poem::async_trait]
+    impl RedirectLookup for BotSlugs {
+        async fn lookup(&self, captures: &BTreeMap<&str, String>) -> poem::Result<Option<String>> {
+            let slug = match captures["id"].as_str() {
+                "123" => Some("cool-bot"),
+                "666" => Some("/evil.com"),
+                _ => None,
+            };
+            Ok(slug.map(String::from))
+        }
+    }
+
+    fn table() -> RedirectTable {
+        serde_json::from_value(json!([
+            {"from": "/bots/:id/vote", "to": "/b/:id/vote", "status": "permanent_redirect"},
+            {"from": "/bots/:id", "to": "/b/:slug"},
+            {"from": "/bots/:id", "to": "/b/:id"},
+            {"from": "/tag/:tag", "to": "/tags/:tag"},
+            {"from": "/docs/*rest", "to": "/developers/*rest"},
+            {"from": "/go/*rest", "to": "/*rest"},
+            {"from": "/u/:id", "to": "/:slug"},
+        ]))
+        .unwrap()
+    }
+
+    fn resolve(
+        method: Method,
+        path: &str,
+        query: Option<&str>,
+    ) -> Option<(String, RedirectStatus)> {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let lookups = RedirectLookups::new().register("slug", BotSlugs);
+
+        rt.block_on(table().resolve(&method, path, query, &lookups))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_resolve() {
+        assert_eq!(
+            resolve(Method::GET, "/bots/123", Some("ref=home")),
+            Some((
+                "/b/cool-bot?ref=home".to_string(),
+                RedirectStatus::MovedPermanently
+            ))
+        );
+        assert_eq!(
+            resolve(Method::POST, "/bots/123/", None),
+            Some(("/b/cool-bot".to_string(), RedirectStatus::PermanentRedirect))
+        );
+        assert_eq!(
+            resolve(Method::GET, "/bots/123/vote", None),
+            Some(("/b/123/vote".to_string(), RedirectStatus::PermanentRedirect))
+        );
+        assert_eq!(
+            resolve(Method::GET, "/docs/api/v1/bots", None),
+            Some((
+                "/developers/api/v1/bots".to_string(),
+                RedirectStatus::MovedPermanently
+            ))
+        );
+        assert_eq!(resolve(Method::GET, "/bots", None), None);
+        assert_eq!(resolve(Method::GET, "/packs/1", None), None);
+    }
+
+    #[test]
+    fn test_missing_lookups_fall_through() {
+        assert_eq!(
+            resolve(Method::GET, "/bots/456", None),
+            Some(("/b/456".to_string(), RedirectStatus::MovedPermanently))
+        );
+    }
+
+    #[test]
+    fn test_refuses_unsafe_targets() {
+        assert_eq!(resolve(Method::GET, "/go/\\evil.com", None), None);
+        assert_eq!(resolve(Method::GET, "/u/666", None), None);
+        assert_eq!(
+            resolve(Method::GET, "/go/tags/music", None),
+            Some(("/tags/music".to_string(), RedirectStatus::MovedPermanently))
+        );
+    }
+
+    #[test]
+    fn test_invalid_rules() {
+        let err = RedirectTable::new(vec![RedirectRule {
+            from: "/bots/:id".to_string(),
+            to: "/b/*rest".to_string(),
+            status: None,
+        }])
+        .unwrap_err();
+        assert!(matches!(err, RedirectError::UnknownParameter { name, .. } if name == "rest"));
+
+        let err = RedirectTable::new(vec![RedirectRule {
+            from: "/docs/*rest/more".to_string(),
+            to: "/developers".to_string(),
+            status: None,
+        }])
+        .unwrap_err();
+        assert!(matches!(err, RedirectError::MisplacedWildcard(_)));
+
+        for to in ["//evil.com", "/\\evil.com", "/\t/evil.com"] {
+            let err = RedirectTable::new(vec![RedirectRule {
+                from: "/out".to_string(),
+                to: to.to_string(),
+                status: None,
+            }])
+            .unwrap_err();
+            assert_eq!(err, RedirectError::UnsafeTarget(to.to_string()));
+        }
+    }
+
+    #[test]
+    fn test_handler() {
+        set_redirects(table());
+        let endpoint = RedirectEndpoint::default();
+
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+
+        rt.block_on(async {
+            let req = Request::builder().uri_str("/tag/music").finish();
+            let resp = endpoint.call(req).await.unwrap();
+            assert_eq!(resp.status(), StatusCode::MOVED_PERMANENTLY);
+            assert_eq!(resp.headers().get("Location").unwrap(), "/tags/music");
+
+            let req = Request::builder().uri_str("/unknown").finish();
+            let resp = endpoint.call(req).await.unwrap();
+            assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+        });
+    }
+}
@@ -0,0 +1,99 @@
+use serde::Serialize;
+use serde_json::{Number, Value};
+
+/// The largest integer which can be represented exactly as a float.
+const MAX_SAFE_FLOAT_INT: f64 = 9007199254740992.0;
+
+/// Serializes the value as canonical JSON.
+///
+/// Object keys are sorted, there is no insignificant whitespace and floats
+/// without a fractional part are written as integers, so equal values always
+/// produce byte-identical output regardless of how they were built.
+pub fn canonical_json(value: &Value) -> String {
+    let mut out = String::new();
+    write_value(value, &mut out);
+    out
+}
+
+/// Converts the value to JSON and serializes it as canonical JSON.
+pub fn to_canonical_json<T: Serialize + ?Sized>(value: &T) -> serde_json::Result<String> {
+    serde_json::to_value(value).map(|v| canonical_json(&v))
+}
+
+fn write_value(value: &Value, out: &mut String) {
+    match value {
+        Value::Null => out.push_str("null"),
+        Value::Bool(v) => out.push_str(if *v { "true" } else { "false" }),
+        Value::Number(v) => write_number(v, out),
+        Value::String(v) => write_string(v, out),
+        Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_value(item, out);
+            }
+            out.push(']');
+        }
+        Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_unstable_by(|a, b| a.0.cmp(b.0));
+
+            out.push('{');
+            for (i, (key, item)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_string(key, out);
+                out.push(':');
+                write_value(item, out);
+            }
+            out.push('}');
+        }
+    }
+}
+
+fn write_number(v: &Number, out: &mut String) {
+    match v.as_f64() {
+        Some(f) if v.is_f64() && f.fract() == 0.0 && f.abs() < MAX_SAFE_FLOAT_INT => {
+            out.push_str(&(f as i64).to_string())
+        }
+        _ => out.push_str(&v.to_string()),
+    }
+}
+
+fn write_string(v: &str, out: &mut String) {
+    out.push_str(&Value::String(v.to_string()).to_string());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_sorted_and_compact() {
+        let value = json!({
+            "b": [1, 2, {"z": null, "a": true}],
+            "a": "line\n\"quoted\"",
+        });
+
+        assert_eq!(
+            canonical_json(&value),
+            r#"{"a":"line\n\"quoted\"","b":[1,2,{"a":true,"z":null}]}"#
+        );
+    }
+
+    #[test]
+    fn test_stable_numbers() {
+        assert_eq!(
+            canonical_json(&json!([1.0, -0.0, 1.5, 1e21, 10])),
+            "[1,0,1.5,1e+21,10]"
+        );
+        assert_eq!(
+            canonical_json(&json!({"x": 2.0})),
+            canonical_json(&json!({"x": 2}))
+        );
+    }
+}
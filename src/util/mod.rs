@@ -0,0 +1,5 @@
+mod canonical;
+mod tolerant;
+
+pub use canonical::{canonical_json, to_canonical_json};
+pub use tolerant::Tolerant;
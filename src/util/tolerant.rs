@@ -0,0 +1,191 @@
+use std::collections::{BTreeMap, HashSet};
+use std::ops::{Deref, DerefMut};
+use std::sync::Mutex;
+
+use once_cell::sync::Lazy;
+use serde::de::{DeserializeOwned, Error};
+use serde::{Deserializer, Serializer};
+use serde_json::Value;
+
+/// The most unknown fields remembered as reported. Payloads with arbitrary
+/// keys, e.g. maps keyed by ID, would otherwise grow the set without bound,
+/// once full any new fields are logged every time.
+const MAX_REPORTED_FIELDS: usize = 1024;
+
+static REPORTED_FIELDS: Lazy<Mutex<HashSet<String>>> = Lazy::new(Default::default);
+
+/// Deserializes `T` while collecting any fields `T` does not know about.
+///
+/// Upstream APIs add fields without notice, this keeps parsing working while
+/// still surfacing the additions. Each unknown field is logged once per type
+/// for the lifetime of the process.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tolerant<T> {
+    pub value: T,
+    /// The unknown fields keyed by their path, e.g. `user.banner_color`.
+    pub unknown: BTreeMap<String, Value>,
+}
+
+impl<T> Tolerant<T> {
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T> Deref for Tolerant<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.value
+    }
+}
+
+impl<T> DerefMut for Tolerant<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.value
+    }
+}
+
+impl<T: serde::Serialize> serde::Serialize for Tolerant<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.value.serialize(serializer)
+    }
+}
+
+impl<'de, T: DeserializeOwned> serde::Deserialize<'de> for Tolerant<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let raw = Value::deserialize(deserializer)?;
+
+        let mut paths = vec![];
+        let value: T = serde_ignored::deserialize(&raw, |path| {
+            paths.push((path.to_string(), json_pointer(&path)))
+        })
+        .map_err(D::Error::custom)?;
+
+        let unknown: BTreeMap<String, Value> = paths
+            .into_iter()
+            .map(|(path, pointer)| {
+                let value = raw.pointer(&pointer).cloned().unwrap_or_default();
+                (path, value)
+            })
+            .collect();
+
+        report::<T>(&unknown);
+
+        Ok(Self { value, unknown })
+    }
+}
+
+/// Converts the path to an RFC 6901 pointer, keys can contain `.`, `/` and
+/// `~` so this can't be derived from the dotted form.
+fn json_pointer(path: &serde_ignored::Path) -> String {
+    use serde_ignored::Path;
+
+    match path {
+        Path::Root => String::new(),
+        Path::Seq { parent, index } => format!("{}/{}", json_pointer(parent), index),
+        Path::Map { parent, key } => format!(
+            "{}/{}",
+            json_pointer(parent),
+            key.replace('~', "~0").replace('/', "~1")
+        ),
+        Path::Some { parent }
+        | Path::NewtypeStruct { parent }
+        | Path::NewtypeVariant { parent } => json_pointer(parent),
+    }
+}
+
+fn report<T>(unknown: &BTreeMap<String, Value>) {
+    if unknown.is_empty() {
+        return;
+    }
+
+    let type_name = std::any::type_name::<T>();
+    let mut reported = REPORTED_FIELDS.lock().unwrap();
+    for path in unknown.keys() {
+        let key = format!("{}::{}", type_name, path);
+        let is_new = if reported.len() < MAX_REPORTED_FIELDS {
+            reported.insert(key)
+        } else {
+            !reported.contains(&key)
+        };
+
+        if is_new {
+            tracing::warn!(
+                type_name = type_name,
+                field = path.as_str(),
+                "encountered unknown field while deserializing"
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct User {
+        id: u64,
+        profile: Profile,
+    }
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct Profile {
+        name: String,
+    }
+
+    #[test]
+    fn test_collects_unknown_fields() {
+        let parsed: Tolerant<User> = serde_json::from_value(json!({
+            "id": 1,
+            "banner": "abc",
+            "profile": {"name": "bob", "pronouns": "they/them"},
+        }))
+        .unwrap();
+
+        assert_eq!(parsed.id, 1);
+        assert_eq!(parsed.profile.name, "bob");
+        assert_eq!(
+            parsed.unknown,
+            BTreeMap::from_iter([
+                ("banner".to_string(), json!("abc")),
+                ("profile.pronouns".to_string(), json!("they/them")),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_escapes_keys() {
+        let parsed: Tolerant<User> = serde_json::from_value(json!({
+            "id": 1,
+            "a/b": 1,
+            "c~d": 2,
+            "profile": {"name": "bob", "x.y": 3},
+        }))
+        .unwrap();
+
+        assert_eq!(
+            parsed.unknown,
+            BTreeMap::from_iter([
+                ("a/b".to_string(), json!(1)),
+                ("c~d".to_string(), json!(2)),
+                ("profile.x.y".to_string(), json!(3)),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_errors_still_propagate() {
+        let parsed = serde_json::from_value::<Tolerant<User>>(json!({"id": "nope"}));
+        assert!(parsed.is_err());
+    }
+}
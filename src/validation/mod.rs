@@ -0,0 +1,181 @@
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+
+use crate::errors::{ApiError, ErrorBody, ErrorCode};
+
+/// Every problem with a payload keyed by the path of the field at fault,
+/// such as `links.github` or `shards.2.shard_id`.
+///
+/// Only the first problem found with a field is kept.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ValidationErrors(BTreeMap<String, String>);
+
+impl ValidationErrors {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, path: impl Into<String>, message: impl Into<String>) {
+        self.0.entry(path.into()).or_insert_with(|| message.into());
+    }
+
+    /// Validates a nested value, its problems are added under `prefix`.
+    pub fn nested<T: Validate + ?Sized>(&mut self, prefix: impl Display, value: &T) {
+        let mut inner = Self::new();
+        value.validate_fields(&mut inner);
+
+        for (path, message) in inner.0 {
+            self.add(format!("{}.{}", prefix, path), message);
+        }
+    }
+
+    #[inline]
+    pub fn get(&self, path: &str) -> Option<&str> {
+        self.0.get(path).map(String::as_str)
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.0.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+
+    /// `Ok` if no problems were added.
+    pub fn into_result(self) -> Result<(), Self> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(self)
+        }
+    }
+}
+
+impl Display for ValidationErrors {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (i, (path, message)) in self.iter().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{}: {}", path, message)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ValidationErrors {}
+
+/// Renders as a validation error with an `errors` object keyed by field path.
+impl From<ValidationErrors> for ApiError {
+    fn from(e: ValidationErrors) -> Self {
+        Self::Validation(ErrorBody {
+            code: ErrorCode::ValidationFailed,
+            message: e.to_string(),
+            errors: Some(e.0),
+        })
+    }
+}
+
+/// A payload which checks its own fields.
+pub trait Validate {
+    /// Adds every problem to `errors`, paths are relative to this value.
+    fn validate_fields(&self, errors: &mut ValidationErrors);
+
+    fn check(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        self.validate_fields(&mut errors);
+        errors.into_result()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use poem::IntoResponse;
+    use serde_json::json;
+
+    use super::*;
+
+    struct Links {
+        github: Option<String>,
+    }
+
+    impl Validate for Links {
+        fn validate_fields(&self, errors: &mut ValidationErrors) {
+            if self
+                .github
+                .as_deref()
+                .is_some_and(|v| !v.starts_with("https://github.com/"))
+            {
+                errors.add("github", "Must be a GitHub url.");
+            }
+        }
+    }
+
+    struct Payload {
+        name: String,
+        links: Links,
+    }
+
+    impl Validate for Payload {
+        fn validate_fields(&self, errors: &mut ValidationErrors) {
+            if self.name.is_empty() {
+                errors.add("name", "A name is required.");
+                errors.add("name", "The name is too short.");
+            }
+
+            errors.nested("links", &self.links);
+        }
+    }
+
+    #[test]
+    fn test_field_paths() {
+        let payload = Payload {
+            name: String::new(),
+            links: Links {
+                github: Some("https://example.com".to_string()),
+            },
+        };
+
+        let errors = payload.check().unwrap_err();
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors.get("name"), Some("A name is required."));
+        assert_eq!(errors.get("links.github"), Some("Must be a GitHub url."));
+
+        let valid = Payload {
+            name: "Rusty".to_string(),
+            links: Links { github: None },
+        };
+        assert!(valid.check().is_ok());
+    }
+
+    #[test]
+    fn test_api_error_body() {
+        let mut errors = ValidationErrors::new();
+        errors.add("links.github", "Must be a GitHub url.");
+
+        let err = ApiError::from(errors);
+        assert_eq!(err.code(), ErrorCode::ValidationFailed);
+
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let body = rt
+            .block_on(
+                err.into_response()
+                    .into_body()
+                    .into_json::<serde_json::Value>(),
+            )
+            .unwrap();
+        assert_eq!(
+            body["errors"],
+            json!({"links.github": "Must be a GitHub url."})
+        );
+    }
+}
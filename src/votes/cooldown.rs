@@ -0,0 +1,88 @@
+use chrono::{Datelike, Duration, Weekday};
+
+use crate::types::Timestamp;
+
+/// The time a user must wait between votes for the same entity.
+pub const VOTE_COOLDOWN: Duration = Duration::hours(12);
+
+/// The cooldown window following a user's last vote.
+///
+/// The boundary is inclusive, a user can vote again at exactly
+/// `last_voted_at + window`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct VoteCooldown {
+    pub last_voted_at: Timestamp,
+    pub window: Duration,
+}
+
+impl VoteCooldown {
+    pub fn new(last_voted_at: Timestamp) -> Self {
+        Self {
+            last_voted_at,
+            window: VOTE_COOLDOWN,
+        }
+    }
+
+    pub fn with_window(mut self, window: Duration) -> Self {
+        self.window = window;
+        self
+    }
+
+    #[inline]
+    pub fn next_vote_at(&self) -> Timestamp {
+        Timestamp(self.last_voted_at.0 + self.window)
+    }
+
+    #[inline]
+    pub fn can_vote_at(&self, now: Timestamp) -> bool {
+        now.0 >= self.next_vote_at().0
+    }
+
+    /// The time left until the user can vote again, zero if they already can.
+    pub fn remaining(&self, now: Timestamp) -> std::time::Duration {
+        (self.next_vote_at().0 - now.0).to_std().unwrap_or_default()
+    }
+}
+
+/// The number of votes a single vote counts as, votes cast on a weekend (UTC) count double.
+pub fn weekend_multiplier(at: Timestamp) -> i32 {
+    match at.0.weekday() {
+        Weekday::Sat | Weekday::Sun => 2,
+        _ => 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Friday 2022-07-01 00:00:00 UTC
+    const FRIDAY: i64 = 1656633600;
+
+    #[test]
+    fn test_cooldown_boundary() {
+        let cooldown = VoteCooldown::new(Timestamp::from(FRIDAY));
+        let boundary = FRIDAY + VOTE_COOLDOWN.num_seconds();
+
+        assert!(!cooldown.can_vote_at(Timestamp::from(boundary - 1)));
+        assert!(cooldown.can_vote_at(Timestamp::from(boundary)));
+        assert_eq!(
+            cooldown.remaining(Timestamp::from(boundary - 60)),
+            std::time::Duration::from_secs(60)
+        );
+        assert_eq!(
+            cooldown.remaining(Timestamp::from(boundary + 60)),
+            std::time::Duration::ZERO
+        );
+    }
+
+    #[test]
+    fn test_weekend_multiplier() {
+        let day = Duration::days(1).num_seconds();
+
+        assert_eq!(weekend_multiplier(Timestamp::from(FRIDAY)), 1);
+        assert_eq!(weekend_multiplier(Timestamp::from(FRIDAY + day)), 2);
+        assert_eq!(weekend_multiplier(Timestamp::from(FRIDAY + day * 2)), 2);
+        assert_eq!(weekend_multiplier(Timestamp::from(FRIDAY + day * 3)), 1);
+    }
+}
@@ -0,0 +1,69 @@
+use poem_openapi::{Enum, Object};
+
+use crate::types::{JsSafeBigInt, Timestamp};
+use crate::votes::weekend_multiplier;
+
+#[derive(
+    Enum,
+    Copy,
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    strum::AsRefStr,
+    strum::EnumString,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[oai(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum VoteTarget {
+    Bot,
+    Pack,
+}
+
+cql_text_enum!(VoteTarget);
+
+/// A single vote cast by a user.
+///
+/// The field order matches the column order of the vote event tables so this
+/// can be read and written directly as a row.
+#[derive(
+    Object,
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    scylla::FromRow,
+    scylla::ValueList,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+pub struct VoteEvent {
+    pub target: VoteTarget,
+    pub entity_id: JsSafeBigInt,
+    pub user_id: JsSafeBigInt,
+    pub voted_at: Timestamp,
+    /// The number of votes this vote counts as.
+    pub weight: i32,
+}
+
+impl VoteEvent {
+    /// Creates a new vote with the weekend multiplier applied.
+    pub fn new(
+        target: VoteTarget,
+        entity_id: JsSafeBigInt,
+        user_id: JsSafeBigInt,
+        voted_at: Timestamp,
+    ) -> Self {
+        Self {
+            target,
+            entity_id,
+            user_id,
+            voted_at,
+            weight: weekend_multiplier(voted_at),
+        }
+    }
+}
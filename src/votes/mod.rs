@@ -0,0 +1,7 @@
+mod cooldown;
+mod event;
+mod streak;
+
+pub use cooldown::{weekend_multiplier, VoteCooldown, VOTE_COOLDOWN};
+pub use event::{VoteEvent, VoteTarget};
+pub use streak::{StreakChange, VoteStreak, STREAK_GRACE};
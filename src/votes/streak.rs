@@ -0,0 +1,101 @@
+use chrono::Duration;
+
+use crate::types::Timestamp;
+use crate::votes::{VoteCooldown, VOTE_COOLDOWN};
+
+/// How long after the cooldown ends a user has to vote before their streak resets.
+pub const STREAK_GRACE: Duration = Duration::hours(24);
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum StreakChange {
+    /// The vote was cast during the cooldown and was not counted.
+    TooSoon,
+    Started,
+    Extended,
+    /// The previous streak expired and a new one was started.
+    Reset,
+}
+
+/// A user's run of consecutive votes for an entity.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct VoteStreak {
+    pub count: u32,
+    pub last_voted_at: Option<Timestamp>,
+}
+
+impl VoteStreak {
+    /// The time after which the streak is lost if no vote was cast.
+    pub fn expires_at(&self) -> Option<Timestamp> {
+        self.last_voted_at
+            .map(|v| Timestamp(v.0 + VOTE_COOLDOWN + STREAK_GRACE))
+    }
+
+    /// The streak as it stands at the given time, accounting for expiry.
+    pub fn current(&self, now: Timestamp) -> u32 {
+        match self.expires_at() {
+            Some(expires_at) if now.0 <= expires_at.0 => self.count,
+            _ => 0,
+        }
+    }
+
+    pub fn record(&mut self, at: Timestamp) -> StreakChange {
+        let last_voted_at = match self.last_voted_at {
+            None => {
+                self.count = 1;
+                self.last_voted_at = Some(at);
+                return StreakChange::Started;
+            }
+            Some(v) => v,
+        };
+
+        if !VoteCooldown::new(last_voted_at).can_vote_at(at) {
+            return StreakChange::TooSoon;
+        }
+
+        let change = if self.current(at) > 0 {
+            self.count += 1;
+            StreakChange::Extended
+        } else {
+            self.count = 1;
+            StreakChange::Reset
+        };
+
+        self.last_voted_at = Some(at);
+        change
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR: i64 = 60 * 60;
+
+    #[test]
+    fn test_streak_transitions() {
+        let mut streak = VoteStreak::default();
+
+        assert_eq!(streak.record(Timestamp::from(0)), StreakChange::Started);
+        assert_eq!(streak.record(Timestamp::from(HOUR)), StreakChange::TooSoon);
+        assert_eq!(
+            streak.record(Timestamp::from(12 * HOUR)),
+            StreakChange::Extended
+        );
+        assert_eq!(streak.count, 2);
+
+        assert_eq!(
+            streak.record(Timestamp::from(48 * HOUR + 1)),
+            StreakChange::Reset
+        );
+        assert_eq!(streak.count, 1);
+    }
+
+    #[test]
+    fn test_current_expires() {
+        let mut streak = VoteStreak::default();
+        streak.record(Timestamp::from(0));
+
+        assert_eq!(streak.current(Timestamp::from(36 * HOUR)), 1);
+        assert_eq!(streak.current(Timestamp::from(36 * HOUR + 1)), 0);
+    }
+}
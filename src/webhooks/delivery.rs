@@ -0,0 +1,289 @@
+use chrono::Duration;
+use poem_openapi::{Enum, Object};
+use scylla::transport::errors::QueryError;
+use scylla::Session;
+use uuid::Uuid;
+
+use crate::types::{JsSafeBigInt, Timestamp};
+use crate::webhooks::{VotePayload, WebhookKind};
+
+/// Partitioned by `bot_id` and clustered by `id`.
+pub const WEBHOOK_DELIVERY_TABLE: &str = "webhook_deliveries";
+
+/// The number of failed attempts before a delivery is dead lettered.
+pub const DEFAULT_MAX_DELIVERY_ATTEMPTS: i32 = 10;
+pub const DEFAULT_DELIVERY_BASE_DELAY: Duration = Duration::seconds(30);
+pub const DEFAULT_DELIVERY_MAX_DELAY: Duration = Duration::hours(6);
+
+#[derive(
+    Enum,
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    Hash,
+    strum::AsRefStr,
+    strum::EnumString,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[oai(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum DeliveryState {
+    /// Waiting for its next attempt.
+    #[default]
+    Pending,
+    Delivered,
+    /// Every attempt failed, the delivery is only retried if redelivered by hand.
+    DeadLettered,
+}
+
+impl DeliveryState {
+    #[inline]
+    pub fn is_finished(&self) -> bool {
+        matches!(self, Self::Delivered | Self::DeadLettered)
+    }
+}
+
+cql_text_enum!(DeliveryState);
+
+/// When failed deliveries are retried.
+///
+/// Unlike [RetryPolicy](crate::retry::RetryPolicy) the delays are not
+/// jittered, deliveries are already spread out by when their votes happened.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct DeliverySchedule {
+    max_attempts: i32,
+    base_delay: Duration,
+    max_delay: Duration,
+}
+
+impl Default for DeliverySchedule {
+    fn default() -> Self {
+        Self {
+            max_attempts: DEFAULT_MAX_DELIVERY_ATTEMPTS,
+            base_delay: DEFAULT_DELIVERY_BASE_DELAY,
+            max_delay: DEFAULT_DELIVERY_MAX_DELAY,
+        }
+    }
+}
+
+impl DeliverySchedule {
+    pub fn with_max_attempts(mut self, max_attempts: i32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    pub fn with_base_delay(mut self, base_delay: Duration) -> Self {
+        self.base_delay = base_delay;
+        self
+    }
+
+    pub fn with_max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    #[inline]
+    pub fn max_attempts(&self) -> i32 {
+        self.max_attempts
+    }
+
+    /// The delay before the next attempt once `attempts` have failed.
+    pub fn delay_after(&self, attempts: i32) -> Duration {
+        let exponent = attempts.saturating_sub(1).clamp(0, 30) as u32;
+        self.base_delay
+            .checked_mul(2i32.saturating_pow(exponent))
+            .unwrap_or(self.max_delay)
+            .min(self.max_delay)
+    }
+}
+
+/// A webhook waiting to be sent or already sent to a bot.
+///
+/// Shared by the dispatcher and the redeliver endpoint, the field order
+/// matches the column order of [WEBHOOK_DELIVERY_TABLE].
+#[derive(
+    Object,
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    scylla::FromRow,
+    scylla::ValueList,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+pub struct Delivery {
+    pub bot_id: JsSafeBigInt,
+    /// A UUIDv7 so deliveries are listed in the order they were created.
+    pub id: Uuid,
+    pub kind: WebhookKind,
+    /// The JSON body sent to the webhook.
+    pub payload: String,
+    pub state: DeliveryState,
+    /// The number of attempts made so far.
+    pub attempts: i32,
+    pub next_attempt: Timestamp,
+    /// The HTTP status of the last attempt, missing if no response was received.
+    pub last_status: Option<i32>,
+    pub created_at: Timestamp,
+}
+
+impl Delivery {
+    pub fn new(bot_id: JsSafeBigInt, kind: WebhookKind, payload: String, now: Timestamp) -> Self {
+        Self {
+            bot_id,
+            id: Uuid::now_v7(),
+            kind,
+            payload,
+            state: DeliveryState::Pending,
+            attempts: 0,
+            next_attempt: now,
+            last_status: None,
+            created_at: now,
+        }
+    }
+
+    pub fn for_vote(payload: &VotePayload, now: Timestamp) -> serde_json::Result<Self> {
+        Ok(Self::new(
+            payload.bot_id,
+            payload.kind,
+            serde_json::to_string(payload)?,
+            now,
+        ))
+    }
+
+    #[inline]
+    pub fn is_due(&self, now: Timestamp) -> bool {
+        self.state == DeliveryState::Pending && now.0 >= self.next_attempt.0
+    }
+
+    pub fn record_success(&mut self, status: u16) {
+        self.attempts += 1;
+        self.last_status = Some(status as i32);
+        self.state = DeliveryState::Delivered;
+    }
+
+    /// Schedules the next attempt after a failure, dead lettering the
+    /// delivery once the schedule's attempts are used up.
+    pub fn record_failure(
+        &mut self,
+        status: Option<u16>,
+        schedule: &DeliverySchedule,
+        now: Timestamp,
+    ) -> DeliveryState {
+        self.attempts += 1;
+        self.last_status = status.map(i32::from);
+
+        if self.attempts >= schedule.max_attempts() {
+            self.state = DeliveryState::DeadLettered;
+        } else {
+            self.state = DeliveryState::Pending;
+            self.next_attempt = Timestamp(now.0 + schedule.delay_after(self.attempts));
+        }
+
+        self.state
+    }
+
+    /// Queues the delivery to be sent again straight away with a fresh set
+    /// of attempts, used when an owner asks for a webhook to be redelivered.
+    pub fn redeliver(&mut self, now: Timestamp) {
+        self.state = DeliveryState::Pending;
+        self.attempts = 0;
+        self.next_attempt = now;
+    }
+}
+
+pub async fn store(session: &Session, delivery: &Delivery) -> Result<(), QueryError> {
+    let query = format!(
+        "INSERT INTO {} (bot_id, id, kind, payload, state, attempts, next_attempt, \
+        last_status, created_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?);",
+        WEBHOOK_DELIVERY_TABLE,
+    );
+
+    session.query(query, delivery).await?;
+
+    Ok(())
+}
+
+pub async fn fetch(
+    session: &Session,
+    bot_id: JsSafeBigInt,
+    id: Uuid,
+) -> Result<Option<Delivery>, QueryError> {
+    let query = format!(
+        "SELECT bot_id, id, kind, payload, state, attempts, next_attempt, last_status, \
+        created_at FROM {} WHERE bot_id = ? AND id = ?;",
+        WEBHOOK_DELIVERY_TABLE
+    );
+
+    session
+        .query(query, (bot_id, id))
+        .await?
+        .maybe_first_row_typed::<Delivery>()
+        .map_err(|e| {
+            QueryError::InvalidMessage(format!(
+                "Failed to parse row from {}: {}",
+                WEBHOOK_DELIVERY_TABLE, e
+            ))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1656633600;
+
+    fn delivery() -> Delivery {
+        let payload = VotePayload::test(JsSafeBigInt(1), JsSafeBigInt(2));
+        Delivery::for_vote(&payload, Timestamp::from(NOW)).unwrap()
+    }
+
+    #[test]
+    fn test_delay_after() {
+        let schedule = DeliverySchedule::default();
+
+        assert_eq!(schedule.delay_after(1), Duration::seconds(30));
+        assert_eq!(schedule.delay_after(3), Duration::minutes(2));
+        assert_eq!(schedule.delay_after(12), DEFAULT_DELIVERY_MAX_DELAY);
+        assert_eq!(schedule.delay_after(i32::MAX), DEFAULT_DELIVERY_MAX_DELAY);
+    }
+
+    #[test]
+    fn test_retries_then_dead_letters() {
+        let schedule = DeliverySchedule::default().with_max_attempts(3);
+        let now = Timestamp::from(NOW);
+        let mut delivery = delivery();
+        assert!(delivery.is_due(now));
+
+        assert_eq!(
+            delivery.record_failure(Some(500), &schedule, now),
+            DeliveryState::Pending
+        );
+        assert_eq!(delivery.next_attempt, Timestamp::from(NOW + 30));
+        assert!(!delivery.is_due(now));
+
+        delivery.record_failure(None, &schedule, now);
+        assert_eq!(delivery.next_attempt, Timestamp::from(NOW + 60));
+        assert_eq!(delivery.last_status, None);
+
+        assert_eq!(
+            delivery.record_failure(Some(502), &schedule, now),
+            DeliveryState::DeadLettered
+        );
+        assert!(!delivery.is_due(Timestamp::from(NOW + 3600)));
+
+        delivery.redeliver(now);
+        assert!(delivery.is_due(now));
+        assert_eq!(delivery.attempts, 0);
+
+        delivery.record_success(204);
+        assert!(delivery.state.is_finished());
+        assert_eq!(delivery.last_status, Some(204));
+    }
+}
@@ -0,0 +1,7 @@
+pub mod delivery;
+mod payload;
+mod signature;
+
+pub use delivery::{Delivery, DeliverySchedule, DeliveryState, WEBHOOK_DELIVERY_TABLE};
+pub use payload::{VotePayload, WebhookKind};
+pub use signature::{SignatureError, WebhookSigner, SIGNATURE_HEADER};
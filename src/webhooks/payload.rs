@@ -0,0 +1,77 @@
+#[cfg(feature = "bincode")]
+use bincode::{Decode, Encode};
+
+use poem_openapi::{Enum, Object};
+
+use crate::types::{JsSafeBigInt, Timestamp};
+use crate::util::Tolerant;
+use crate::votes::{VoteEvent, VoteTarget};
+
+#[cfg_attr(feature = "bincode", derive(Decode, Encode))]
+#[derive(
+    Enum,
+    Copy,
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    strum::AsRefStr,
+    strum::EnumString,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[oai(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookKind {
+    Vote,
+    /// Sent when the owner presses the test button on the dashboard.
+    Test,
+}
+
+cql_text_enum!(WebhookKind);
+
+/// The body sent to a bot's webhook when a user votes for it.
+#[cfg_attr(feature = "bincode", derive(Decode, Encode))]
+#[derive(Object, Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct VotePayload {
+    pub kind: WebhookKind,
+    pub bot_id: JsSafeBigInt,
+    pub user_id: JsSafeBigInt,
+    /// The number of votes the vote counted as.
+    pub weight: i32,
+    pub voted_at: Timestamp,
+}
+
+impl VotePayload {
+    /// Creates the payload for a vote, returns `None` if the vote was not for a bot.
+    pub fn from_event(event: &VoteEvent) -> Option<Self> {
+        if event.target != VoteTarget::Bot {
+            return None;
+        }
+
+        Some(Self {
+            kind: WebhookKind::Vote,
+            bot_id: event.entity_id,
+            user_id: event.user_id,
+            weight: event.weight,
+            voted_at: event.voted_at,
+        })
+    }
+
+    /// Parses a received payload, fields added in newer versions are collected rather than rejected.
+    pub fn from_slice(body: &[u8]) -> serde_json::Result<Tolerant<Self>> {
+        serde_json::from_slice(body)
+    }
+
+    pub fn test(bot_id: JsSafeBigInt, user_id: JsSafeBigInt) -> Self {
+        Self {
+            kind: WebhookKind::Test,
+            bot_id,
+            user_id,
+            weight: 1,
+            voted_at: Timestamp::default(),
+        }
+    }
+}
@@ -0,0 +1,177 @@
+use std::fmt::{Display, Formatter};
+use std::time::Duration;
+
+use hmac::Mac;
+
+use crate::crypto::{hmac_sha256, signing_key, EmptySecret, HmacSha256};
+use crate::types::{Secret, Timestamp};
+use crate::util::to_canonical_json;
+
+pub const SIGNATURE_HEADER: &str = "X-DList-Signature";
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SignatureError {
+    /// The header is not in the `t=<timestamp>,v1=<signature>` format.
+    Malformed,
+    /// The signature was created outside of the allowed tolerance.
+    Expired,
+    Mismatch,
+}
+
+impl Display for SignatureError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Malformed => write!(f, "malformed webhook signature"),
+            Self::Expired => write!(f, "webhook signature has expired"),
+            Self::Mismatch => write!(f, "webhook signature does not match"),
+        }
+    }
+}
+
+impl std::error::Error for SignatureError {}
+
+/// Signs and verifies webhook bodies with the bot's webhook secret.
+///
+/// The signature covers the timestamp as well as the body so old requests
+/// cannot be replayed, the header is formatted as `t=<unix seconds>,v1=<hex hmac>`.
+#[derive(Clone)]
+pub struct WebhookSigner {
+    secret: Secret<Vec<u8>>,
+}
+
+impl WebhookSigner {
+    pub fn new(secret: impl AsRef<[u8]>) -> Result<Self, EmptySecret> {
+        Ok(Self {
+            secret: signing_key(secret)?,
+        })
+    }
+
+    fn mac(&self, timestamp: i64, body: &[u8]) -> HmacSha256 {
+        hmac_sha256(
+            self.secret.expose(),
+            &[timestamp.to_string().as_bytes(), b".", body],
+        )
+    }
+
+    /// Produces the value of the signature header for the body.
+    pub fn sign(&self, timestamp: Timestamp, body: &[u8]) -> String {
+        let timestamp = timestamp.0.timestamp();
+        let signature = self.mac(timestamp, body).finalize().into_bytes();
+
+        format!("t={},v1={}", timestamp, hex::encode(signature))
+    }
+
+    /// Serializes the payload as canonical JSON and signs it, returning the body
+    /// alongside the signature header.
+    pub fn sign_json<T: serde::Serialize>(
+        &self,
+        timestamp: Timestamp,
+        payload: &T,
+    ) -> serde_json::Result<(String, String)> {
+        let body = to_canonical_json(payload)?;
+        let header = self.sign(timestamp, body.as_bytes());
+        Ok((body, header))
+    }
+
+    /// Verifies the signature header in constant time.
+    pub fn verify(
+        &self,
+        header: &str,
+        body: &[u8],
+        tolerance: Duration,
+        now: Timestamp,
+    ) -> Result<(), SignatureError> {
+        let mut timestamp = None;
+        let mut signature = None;
+        for part in header.split(',') {
+            match part.trim().split_once('=') {
+                Some(("t", v)) => timestamp = v.parse::<i64>().ok(),
+                Some(("v1", v)) => signature = hex::decode(v).ok(),
+                _ => {}
+            }
+        }
+
+        let (timestamp, signature) = timestamp.zip(signature).ok_or(SignatureError::Malformed)?;
+
+        if now.0.timestamp().abs_diff(timestamp) > tolerance.as_secs() {
+            return Err(SignatureError::Expired);
+        }
+
+        self.mac(timestamp, body)
+            .verify_slice(&signature)
+            .map_err(|_| SignatureError::Mismatch)
+    }
+}
+
+impl std::fmt::Debug for WebhookSigner {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WebhookSigner").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOLERANCE: Duration = Duration::from_secs(300);
+
+    #[test]
+    fn test_sign_and_verify() {
+        let signer = WebhookSigner::new("super-secret").unwrap();
+        assert!(WebhookSigner::new("").is_err());
+        let now = Timestamp::from(1656633600);
+        let header = signer.sign(now, b"{\"kind\":\"vote\"}");
+
+        assert!(header.starts_with("t=1656633600,v1="));
+        assert_eq!(
+            signer.verify(&header, b"{\"kind\":\"vote\"}", TOLERANCE, now),
+            Ok(())
+        );
+        assert_eq!(
+            signer.verify(&header, b"{\"kind\":\"test\"}", TOLERANCE, now),
+            Err(SignatureError::Mismatch)
+        );
+        assert_eq!(
+            WebhookSigner::new("other").unwrap().verify(
+                &header,
+                b"{\"kind\":\"vote\"}",
+                TOLERANCE,
+                now
+            ),
+            Err(SignatureError::Mismatch)
+        );
+    }
+
+    #[test]
+    fn test_sign_json_is_canonical() {
+        let signer = WebhookSigner::new("super-secret").unwrap();
+        let now = Timestamp::from(1656633600);
+
+        let (body, header) = signer
+            .sign_json(now, &serde_json::json!({"b": 1, "a": 2}))
+            .unwrap();
+        assert_eq!(body, r#"{"a":2,"b":1}"#);
+        assert_eq!(header, signer.sign(now, body.as_bytes()));
+    }
+
+    #[test]
+    fn test_verify_rejects_bad_headers() {
+        let signer = WebhookSigner::new("super-secret").unwrap();
+        let now = Timestamp::from(1656633600);
+        let header = signer.sign(now, b"body");
+
+        let later = Timestamp::from(1656633600 + 301);
+        assert_eq!(
+            signer.verify(&header, b"body", TOLERANCE, later),
+            Err(SignatureError::Expired)
+        );
+        assert_eq!(
+            signer.verify("v1=abcd", b"body", TOLERANCE, now),
+            Err(SignatureError::Malformed)
+        );
+        assert_eq!(
+            signer.verify("t=1656633600,v1=zz", b"body", TOLERANCE, now),
+            Err(SignatureError::Malformed)
+        );
+    }
+}